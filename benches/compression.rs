@@ -0,0 +1,111 @@
+//! Measures the time taken and the size produced by writing documents of different sizes and
+//! compositions at several compression levels, along with the level chosen by
+//! `NbtWriteBuilder::auto_compression`. The sizes are printed once per document before its timings.
+
+extern crate criterion;
+extern crate quartz_nbt;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use flate2::Compression;
+use quartz_nbt::{
+    compound,
+    io::{self, Flavor, NbtWriteBuilder},
+    NbtCompound,
+    NbtList,
+    NbtTag,
+};
+use std::{fs::File, time::Duration};
+
+fn item_stack() -> NbtCompound {
+    compound! {
+        "id": "minecraft:diamond_sword",
+        "Count": 1i8,
+        "tag": { "Damage": 12i32 }
+    }
+}
+
+// A chunk-like document, mostly made up of packed block states with some repetition in them
+fn chunk() -> NbtCompound {
+    let mut sections = NbtList::new();
+    for y in 0 .. 24i64 {
+        let states = (0 .. 4096i64)
+            .map(|i| (i / 64 + y).wrapping_mul(0x0101_0101_0101))
+            .collect::<Vec<_>>();
+        sections.push(compound! {
+            "Y": y as i8,
+            "block_states": {
+                "palette": [{ "Name": "minecraft:stone" }, { "Name": "minecraft:air" }],
+                "data": NbtTag::LongArray(states)
+            }
+        });
+    }
+
+    compound! {
+        "DataVersion": 3465i32,
+        "sections": sections,
+        "Heightmaps": { "WORLD_SURFACE": NbtTag::LongArray(vec![0x2040_8102_0408_1020; 37]) }
+    }
+}
+
+fn load(filename: &str) -> NbtCompound {
+    io::read_nbt(&mut File::open(filename).unwrap(), Flavor::GzCompressed)
+        .unwrap()
+        .0
+}
+
+fn bench_document(name: &str, nbt: &NbtCompound, c: &mut Criterion) {
+    let uncompressed = io::write_nbt_bytes(None, nbt, Flavor::Uncompressed)
+        .unwrap()
+        .len();
+    let levels = [
+        ("None", Compression::none()),
+        ("Fast", Compression::fast()),
+        ("Default", Compression::default()),
+        ("Best", Compression::best()),
+    ];
+
+    println!("{}: {} bytes uncompressed", name, uncompressed);
+    for (label, level) in levels {
+        let len = io::write_nbt_bytes(None, nbt, Flavor::ZlibCompressedWith(level))
+            .unwrap()
+            .len();
+        println!("  {:<8} {:>9} bytes", label, len);
+    }
+    println!(
+        "  auto chooses level {}",
+        io::auto_compression_level(nbt).level()
+    );
+
+    let mut group = c.benchmark_group(format!("Compression/{}", name));
+    group.throughput(Throughput::Bytes(uncompressed as u64));
+    for (label, level) in levels {
+        let writer = NbtWriteBuilder::new().flavor(Flavor::ZlibCompressedWith(level));
+        group.bench_function(label, |b| {
+            b.iter(|| black_box(writer.write_to_bytes(black_box(nbt)).unwrap()))
+        });
+    }
+    let writer = NbtWriteBuilder::new()
+        .flavor(Flavor::ZlibCompressed)
+        .auto_compression(true);
+    group.bench_function("Auto", |b| {
+        b.iter(|| black_box(writer.write_to_bytes(black_box(nbt)).unwrap()))
+    });
+    group.finish();
+}
+
+fn bench(c: &mut Criterion) {
+    bench_document("Item Stack", &item_stack(), c);
+    bench_document("Player", &load("benches/assets/complex_player.dat"), c);
+    bench_document("Level", &load("benches/assets/level.dat"), c);
+    bench_document("Big1", &load("benches/assets/big1.nbt"), c);
+    bench_document("Chunk", &chunk(), c);
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default()
+        .sample_size(50)
+        .warm_up_time(Duration::from_secs(1));
+    targets = bench
+}
+criterion_main!(benches);
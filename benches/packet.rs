@@ -0,0 +1,102 @@
+#![cfg(feature = "bytes")]
+//! Benchmarks for encoding packet-sized compounds, such as item stacks and block entity updates,
+//! into protocol buffers.
+
+extern crate criterion;
+extern crate quartz_nbt;
+
+use bytes::BytesMut;
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use quartz_nbt::{
+    compound,
+    io::{self, Flavor, LengthPrefix, NbtFrame},
+    NbtCompound,
+};
+use std::{io::Write, time::Duration};
+
+fn item_stack() -> NbtCompound {
+    compound! {
+        "id": "minecraft:diamond_sword",
+        "Count": 1i8,
+        "tag": {
+            "Damage": 12i32,
+            "display": {
+                "Name": "{\"text\":\"Excalibur\"}",
+                "Lore": ["{\"text\":\"Sharp\"}", "{\"text\":\"Shiny\"}"]
+            },
+            "Enchantments": [
+                { "id": "minecraft:sharpness", "lvl": 5i16 },
+                { "id": "minecraft:unbreaking", "lvl": 3i16 }
+            ]
+        }
+    }
+}
+
+fn bench(c: &mut Criterion) {
+    let nbt = item_stack();
+    let len = io::write_nbt_bytes(None, &nbt, Flavor::Uncompressed)
+        .unwrap()
+        .len();
+
+    let mut group = c.benchmark_group("Packet");
+    group.throughput(Throughput::Bytes(len as u64));
+
+    group.bench_function("Write Into Vec Then Copy", |b| {
+        let mut packet = BytesMut::with_capacity(1024);
+        b.iter(|| {
+            packet.clear();
+            let bytes = io::write_nbt_bytes(None, black_box(&nbt), Flavor::Uncompressed).unwrap();
+            packet.extend_from_slice(&bytes);
+            black_box(&packet);
+        })
+    });
+    group.bench_function("Write Into BufMut", |b| {
+        let mut packet = BytesMut::with_capacity(1024);
+        b.iter(|| {
+            packet.clear();
+            io::write_nbt_buf(&mut packet, None, black_box(&nbt), Flavor::Uncompressed).unwrap();
+            black_box(&packet);
+        })
+    });
+    group.bench_function("Write Framed", |b| {
+        let mut stream = Vec::with_capacity(1024);
+        b.iter(|| {
+            stream.clear();
+            io::write_nbt_framed(
+                &mut stream,
+                LengthPrefix::VarInt,
+                None,
+                black_box(&nbt),
+                Flavor::Uncompressed,
+            )
+            .unwrap();
+            black_box(&stream);
+        })
+    });
+    group.bench_function("Write Framed Vectored", |b| {
+        let mut stream = Vec::with_capacity(1024);
+        b.iter(|| {
+            stream.clear();
+            let frame = NbtFrame::encode(
+                LengthPrefix::VarInt,
+                None,
+                black_box(&nbt),
+                Flavor::Uncompressed,
+            )
+            .unwrap();
+            let written = stream.write_vectored(&frame.io_slices()).unwrap();
+            assert_eq!(written, frame.len());
+            black_box(&stream);
+        })
+    });
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default()
+        .sample_size(500)
+        .warm_up_time(Duration::from_secs(1));
+    targets = bench
+}
+criterion_main!(benches);
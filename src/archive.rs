@@ -0,0 +1,175 @@
+use crate::{
+    ArchivedNbtCompound,
+    ArchivedNbtList,
+    ArchivedNbtTag,
+    ArchivedNbtTagKind,
+    NbtCompound,
+    NbtTag,
+    NbtTagKind,
+};
+use rkyv::{rancor::Error, util::AlignedVec};
+
+/// Archives the given compound, returning bytes which can be accessed in place with [`access`].
+///
+/// # Example
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::archive;
+///
+/// let compound = compound! { "name": "Steve", "xp": 30i32, "pos": [1.0f64, 64.0f64, -3.5f64] };
+/// let bytes = archive::to_bytes(&compound)?;
+///
+/// let archived = archive::access(&bytes)?;
+/// assert_eq!(archived.get("name").map(ArchivedNbtTag::kind), Some(NbtTagKind::String));
+/// assert_eq!(archive::from_bytes(&bytes)?, compound);
+/// # Ok::<_, rkyv::rancor::Error>(())
+/// ```
+///
+/// [`access`]: crate::archive::access
+pub fn to_bytes(compound: &NbtCompound) -> Result<AlignedVec, Error> {
+    rkyv::to_bytes(compound)
+}
+
+/// Validates the given bytes as an archived compound and returns a reference to it without
+/// deserializing it. The bytes must be aligned to at least 16 bytes, which is the case for an
+/// [`AlignedVec`] and for memory maps, and any other alignment is reported as an error.
+///
+/// Validation visits every tag of the archive, but does not allocate. See [`to_bytes`] for an
+/// example.
+///
+/// [`AlignedVec`]: rkyv::util::AlignedVec
+/// [`to_bytes`]: crate::archive::to_bytes
+pub fn access(bytes: &[u8]) -> Result<&ArchivedNbtCompound, Error> {
+    rkyv::access::<ArchivedNbtCompound, Error>(bytes)
+}
+
+/// Validates the given bytes as an archived compound and deserializes it. This is equivalent to
+/// calling [`to_compound`] on the result of [`access`].
+///
+/// [`to_compound`]: crate::ArchivedNbtCompound::to_compound
+/// [`access`]: crate::archive::access
+pub fn from_bytes(bytes: &[u8]) -> Result<NbtCompound, Error> {
+    rkyv::from_bytes::<NbtCompound, Error>(bytes)
+}
+
+impl ArchivedNbtTag {
+    /// Returns the kind of this tag.
+    pub fn kind(&self) -> NbtTagKind {
+        match self {
+            ArchivedNbtTag::Byte(_) => NbtTagKind::Byte,
+            ArchivedNbtTag::Short(_) => NbtTagKind::Short,
+            ArchivedNbtTag::Int(_) => NbtTagKind::Int,
+            ArchivedNbtTag::Long(_) => NbtTagKind::Long,
+            ArchivedNbtTag::Float(_) => NbtTagKind::Float,
+            ArchivedNbtTag::Double(_) => NbtTagKind::Double,
+            ArchivedNbtTag::ByteArray(_) => NbtTagKind::ByteArray,
+            ArchivedNbtTag::String(_) => NbtTagKind::String,
+            ArchivedNbtTag::List(_) => NbtTagKind::List,
+            ArchivedNbtTag::Compound(_) => NbtTagKind::Compound,
+            ArchivedNbtTag::IntArray(_) => NbtTagKind::IntArray,
+            ArchivedNbtTag::LongArray(_) => NbtTagKind::LongArray,
+        }
+    }
+
+    /// Deserializes this tag into an owned [`NbtTag`].
+    ///
+    /// [`NbtTag`]: crate::NbtTag
+    pub fn to_tag(&self) -> Result<NbtTag, Error> {
+        rkyv::deserialize::<NbtTag, Error>(self)
+    }
+}
+
+impl ArchivedNbtList {
+    /// Returns the number of elements in this list.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns true if this list has no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the element at the given index, or `None` if the index is out of bounds.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&ArchivedNbtTag> {
+        self.0.get(index)
+    }
+
+    /// Returns an iterator over the elements of this list.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &ArchivedNbtTag> {
+        self.0.iter()
+    }
+
+    /// Returns the element kind hint this list was archived with. See
+    /// [`NbtList::element_kind`] for details.
+    ///
+    /// [`NbtList::element_kind`]: crate::NbtList::element_kind
+    #[inline]
+    pub fn element_kind(&self) -> Option<NbtTagKind> {
+        self.1.as_ref().map(|&kind| kind.into())
+    }
+}
+
+impl ArchivedNbtCompound {
+    /// Returns the number of tags in this compound.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns true if this compound has no tags.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the tag with the given key, or `None` if there is no such tag.
+    #[inline]
+    pub fn get(&self, key: &str) -> Option<&ArchivedNbtTag> {
+        self.0.get(key)
+    }
+
+    /// Returns true if this compound contains a tag with the given key.
+    #[inline]
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.0.contains_key(key)
+    }
+
+    /// Returns an iterator over the keys and tags of this compound, in no particular order unless
+    /// the `preserve_order` feature is enabled.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &ArchivedNbtTag)> {
+        self.0.iter().map(|(key, tag)| (key.as_str(), tag))
+    }
+
+    /// Deserializes this compound into an owned [`NbtCompound`].
+    ///
+    /// [`NbtCompound`]: crate::NbtCompound
+    pub fn to_compound(&self) -> Result<NbtCompound, Error> {
+        rkyv::deserialize::<NbtCompound, Error>(self)
+    }
+}
+
+impl From<ArchivedNbtTagKind> for NbtTagKind {
+    fn from(kind: ArchivedNbtTagKind) -> Self {
+        match kind {
+            ArchivedNbtTagKind::Byte => NbtTagKind::Byte,
+            ArchivedNbtTagKind::Short => NbtTagKind::Short,
+            ArchivedNbtTagKind::Int => NbtTagKind::Int,
+            ArchivedNbtTagKind::Long => NbtTagKind::Long,
+            ArchivedNbtTagKind::Float => NbtTagKind::Float,
+            ArchivedNbtTagKind::Double => NbtTagKind::Double,
+            ArchivedNbtTagKind::ByteArray => NbtTagKind::ByteArray,
+            ArchivedNbtTagKind::String => NbtTagKind::String,
+            ArchivedNbtTagKind::List => NbtTagKind::List,
+            ArchivedNbtTagKind::Compound => NbtTagKind::Compound,
+            ArchivedNbtTagKind::IntArray => NbtTagKind::IntArray,
+            ArchivedNbtTagKind::LongArray => NbtTagKind::LongArray,
+        }
+    }
+}
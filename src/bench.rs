@@ -0,0 +1,425 @@
+use crate::{
+    io::{self, Flavor, NbtIoError},
+    NbtCompound,
+};
+use std::{
+    fmt::{self, Display, Formatter},
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "serde")]
+use serde::{de::DeserializeOwned, Serialize};
+#[cfg(feature = "serde")]
+use std::error::Error;
+
+/// Measures the throughput of reading and writing NBT data, so that the APIs of this crate can be
+/// compared on real documents.
+///
+/// Each measurement runs the operation a number of times without timing it, so that caches and
+/// allocators are warmed up, and then times a fixed number of iterations. The results are far
+/// noisier than those of a dedicated benchmarking harness, but are cheap to obtain and need no
+/// extra dependencies.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::{bench::Bencher, io::{self, Flavor}};
+///
+/// let nbt = compound! { "Heightmap": [L; 64, 64, 65, 65, 66], "Sections": [{ "Y": 4i8 }] };
+/// let bytes = io::write_nbt_bytes(Some("Level"), &nbt, Flavor::GzCompressed)?;
+///
+/// let bencher = Bencher::new().warmup(1).iterations(5);
+/// let read = bencher.read(&bytes, Flavor::GzCompressed)?;
+/// assert_eq!(read.iterations(), 5);
+/// assert_eq!(read.bytes(), bytes.len());
+/// println!("Read {:.1} MiB/s", read.bytes_per_second() / (1024.0 * 1024.0));
+/// # Ok::<(), io::NbtIoError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bencher {
+    warmup: u32,
+    iterations: u32,
+}
+
+impl Bencher {
+    /// Returns a bencher with the default settings.
+    pub const fn new() -> Self {
+        Bencher {
+            warmup: 3,
+            iterations: 20,
+        }
+    }
+
+    /// Sets the number of untimed iterations run before each measurement. This defaults to 3.
+    pub const fn warmup(mut self, warmup: u32) -> Self {
+        self.warmup = warmup;
+        self
+    }
+
+    /// Sets the number of timed iterations of each measurement. This defaults to 20, and is
+    /// clamped to at least one.
+    pub const fn iterations(mut self, iterations: u32) -> Self {
+        self.iterations = if iterations == 0 { 1 } else { iterations };
+        self
+    }
+
+    /// Measures reading the given bytes into a compound with [`read_nbt`]. The throughput is
+    /// given in terms of the bytes as passed to this function, so it is based on the compressed
+    /// size of compressed flavors.
+    ///
+    /// [`read_nbt`]: crate::io::read_nbt
+    pub fn read(&self, bytes: &[u8], flavor: Flavor) -> Result<Throughput, NbtIoError> {
+        self.measure(bytes.len(), || {
+            black_box(io::read_nbt(&mut &*bytes, flavor)?);
+            Ok(())
+        })
+    }
+
+    /// Measures writing the given compound with [`write_nbt`]. The throughput is given in terms
+    /// of the bytes written.
+    ///
+    /// [`write_nbt`]: crate::io::write_nbt
+    pub fn write(&self, nbt: &NbtCompound, flavor: Flavor) -> Result<Throughput, NbtIoError> {
+        let mut buffer = Vec::new();
+        io::write_nbt(&mut buffer, None, nbt, flavor)?;
+        let len = buffer.len();
+
+        self.measure(len, || {
+            buffer.clear();
+            io::write_nbt(&mut buffer, None, nbt, flavor)?;
+            black_box(&buffer);
+            Ok(())
+        })
+    }
+
+    /// Measures deserializing the given bytes into the given type with [`deserialize`]. This
+    /// method is only available when the `serde` feature is enabled.
+    ///
+    /// [`deserialize`]: crate::serde::deserialize
+    #[cfg(feature = "serde")]
+    pub fn deserialize<T: DeserializeOwned>(
+        &self,
+        bytes: &[u8],
+        flavor: Flavor,
+    ) -> Result<Throughput, NbtIoError> {
+        self.measure(bytes.len(), || {
+            black_box(crate::serde::deserialize::<T>(bytes, flavor)?);
+            Ok(())
+        })
+    }
+
+    /// Measures serializing the given value with [`serialize_into`]. This method is only
+    /// available when the `serde` feature is enabled.
+    ///
+    /// [`serialize_into`]: crate::serde::serialize_into
+    #[cfg(feature = "serde")]
+    pub fn serialize<T: Serialize>(
+        &self,
+        value: &T,
+        flavor: Flavor,
+    ) -> Result<Throughput, NbtIoError> {
+        let mut buffer = Vec::new();
+        crate::serde::serialize_into(&mut buffer, value, None, flavor)?;
+        let len = buffer.len();
+
+        self.measure(len, || {
+            buffer.clear();
+            crate::serde::serialize_into(&mut buffer, value, None, flavor)?;
+            black_box(&buffer);
+            Ok(())
+        })
+    }
+
+    /// Measures every way of reading and writing the given document, such as the contents of a
+    /// file, in the flavor it was detected to have as well as uncompressed. When the `serde`
+    /// feature is enabled, the document is also deserialized and serialized as an
+    /// [`NbtCompound`].
+    ///
+    /// ```no_run
+    /// use quartz_nbt::bench::Bencher;
+    ///
+    /// let bytes = std::fs::read("level.dat")?;
+    /// let report = Bencher::new().report(&bytes)?;
+    /// println!("{}", report);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// [`NbtCompound`]: crate::NbtCompound
+    pub fn report(&self, bytes: &[u8]) -> Result<BenchReport, NbtIoError> {
+        let flavor = io::detect_flavor(bytes);
+        let (nbt, _) = io::read_nbt(&mut &*bytes, flavor)?;
+        let uncompressed = io::write_nbt_bytes(None, &nbt, Flavor::Uncompressed)?;
+
+        Ok(BenchReport {
+            flavor,
+            read: self.read(bytes, flavor)?,
+            write: self.write(&nbt, flavor)?,
+            read_uncompressed: self.read(&uncompressed, Flavor::Uncompressed)?,
+            write_uncompressed: self.write(&nbt, Flavor::Uncompressed)?,
+            #[cfg(feature = "serde")]
+            deserialize: self.deserialize::<NbtCompound>(bytes, flavor)?,
+            #[cfg(feature = "serde")]
+            serialize: self.serialize(&nbt, flavor)?,
+        })
+    }
+
+    fn measure<F>(&self, bytes: usize, mut f: F) -> Result<Throughput, NbtIoError>
+    where F: FnMut() -> Result<(), NbtIoError> {
+        for _ in 0 .. self.warmup {
+            f()?;
+        }
+
+        let start = Instant::now();
+        for _ in 0 .. self.iterations {
+            f()?;
+        }
+
+        Ok(Throughput {
+            bytes,
+            iterations: self.iterations,
+            elapsed: start.elapsed(),
+        })
+    }
+}
+
+impl Default for Bencher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The result of a measurement taken by a [`Bencher`].
+///
+/// [`Bencher`]: crate::bench::Bencher
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Throughput {
+    bytes: usize,
+    iterations: u32,
+    elapsed: Duration,
+}
+
+impl Throughput {
+    /// Returns the number of bytes processed by each iteration.
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    /// Returns the number of timed iterations.
+    pub fn iterations(&self) -> u32 {
+        self.iterations
+    }
+
+    /// Returns the total time taken by the timed iterations.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Returns the average time taken by each iteration.
+    pub fn per_iteration(&self) -> Duration {
+        self.elapsed / self.iterations
+    }
+
+    /// Returns the number of bytes processed per second.
+    pub fn bytes_per_second(&self) -> f64 {
+        let total = self.bytes as f64 * f64::from(self.iterations);
+        total / self.elapsed.as_secs_f64().max(f64::MIN_POSITIVE)
+    }
+}
+
+impl Display for Throughput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:.2} MiB/s ({:?} per iteration)",
+            self.bytes_per_second() / (1024.0 * 1024.0),
+            self.per_iteration()
+        )
+    }
+}
+
+/// The measurements taken by [`Bencher::report`].
+///
+/// [`Bencher::report`]: crate::bench::Bencher::report
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BenchReport {
+    flavor: Flavor,
+    read: Throughput,
+    write: Throughput,
+    read_uncompressed: Throughput,
+    write_uncompressed: Throughput,
+    #[cfg(feature = "serde")]
+    deserialize: Throughput,
+    #[cfg(feature = "serde")]
+    serialize: Throughput,
+}
+
+impl BenchReport {
+    /// Returns the flavor the document was detected to have.
+    pub fn flavor(&self) -> Flavor {
+        self.flavor
+    }
+
+    /// Returns the throughput of reading the document in its flavor.
+    pub fn read(&self) -> Throughput {
+        self.read
+    }
+
+    /// Returns the throughput of writing the document in its flavor.
+    pub fn write(&self) -> Throughput {
+        self.write
+    }
+
+    /// Returns the throughput of reading the document without compression.
+    pub fn read_uncompressed(&self) -> Throughput {
+        self.read_uncompressed
+    }
+
+    /// Returns the throughput of writing the document without compression.
+    pub fn write_uncompressed(&self) -> Throughput {
+        self.write_uncompressed
+    }
+
+    /// Returns the throughput of deserializing the document in its flavor as an [`NbtCompound`].
+    /// This method is only available when the `serde` feature is enabled.
+    ///
+    /// [`NbtCompound`]: crate::NbtCompound
+    #[cfg(feature = "serde")]
+    pub fn deserialize(&self) -> Throughput {
+        self.deserialize
+    }
+
+    /// Returns the throughput of serializing the document in its flavor as an [`NbtCompound`].
+    /// This method is only available when the `serde` feature is enabled.
+    ///
+    /// [`NbtCompound`]: crate::NbtCompound
+    #[cfg(feature = "serde")]
+    pub fn serialize(&self) -> Throughput {
+        self.serialize
+    }
+}
+
+impl Display for BenchReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "read ({:?}): {}", self.flavor, self.read)?;
+        writeln!(f, "write ({:?}): {}", self.flavor, self.write)?;
+        writeln!(f, "read (Uncompressed): {}", self.read_uncompressed)?;
+        write!(f, "write (Uncompressed): {}", self.write_uncompressed)?;
+        #[cfg(feature = "serde")]
+        {
+            writeln!(f)?;
+            writeln!(f, "deserialize ({:?}): {}", self.flavor, self.deserialize)?;
+            write!(f, "serialize ({:?}): {}", self.flavor, self.serialize)?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks that the [`io`] and [`serde`] code paths agree on the given compound, returning the
+/// first disagreement found.
+///
+/// The compound is written uncompressed with both [`write_nbt`] and [`serialize`], which must
+/// produce identical bytes. Each path must then read the bytes written by the other back into the
+/// original compound. This is useful for checking that data which downstream code handles with
+/// both APIs round trips the same way. This function is only available when the `serde` feature
+/// is enabled.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::bench;
+///
+/// let nbt = compound! {
+///     "id": "minecraft:chest",
+///     "Items": [{ "Slot": 0i8, "id": "minecraft:stone", "Count": 64i8 }],
+///     "Heightmap": [I; 1, 2, 3]
+/// };
+/// assert!(bench::check_parity(Some("block"), &nbt).is_ok());
+/// ```
+///
+/// [`io`]: crate::io
+/// [`serde`]: crate::serde
+/// [`write_nbt`]: crate::io::write_nbt
+/// [`serialize`]: crate::serde::serialize
+#[cfg(feature = "serde")]
+pub fn check_parity(root_name: Option<&str>, nbt: &NbtCompound) -> Result<(), ParityError> {
+    let io_bytes = io::write_nbt_bytes(root_name, nbt, Flavor::Uncompressed)?;
+    let serde_bytes = crate::serde::serialize(nbt, root_name, Flavor::Uncompressed)?;
+    if io_bytes != serde_bytes {
+        let offset = io_bytes
+            .iter()
+            .zip(&serde_bytes)
+            .position(|(io, serde)| io != serde)
+            .unwrap_or_else(|| io_bytes.len().min(serde_bytes.len()));
+        return Err(ParityError::Bytes {
+            offset,
+            io_len: io_bytes.len(),
+            serde_len: serde_bytes.len(),
+        });
+    }
+
+    let (read, _) = io::read_nbt(&mut serde_bytes.as_slice(), Flavor::Uncompressed)?;
+    if read != *nbt {
+        return Err(ParityError::IoRead);
+    }
+
+    let (read, _) = crate::serde::deserialize::<NbtCompound>(&io_bytes, Flavor::Uncompressed)?;
+    if read != *nbt {
+        return Err(ParityError::SerdeRead);
+    }
+
+    Ok(())
+}
+
+/// A disagreement between the [`io`] and [`serde`] code paths, as returned by [`check_parity`].
+///
+/// [`io`]: crate::io
+/// [`serde`]: crate::serde
+/// [`check_parity`]: crate::bench::check_parity
+#[cfg(feature = "serde")]
+#[derive(Debug)]
+pub enum ParityError {
+    /// One of the paths failed with the given error.
+    Io(NbtIoError),
+    /// The bytes written by the two paths differ.
+    Bytes {
+        /// The offset of the first differing byte.
+        offset: usize,
+        /// The number of bytes written by the `io` path.
+        io_len: usize,
+        /// The number of bytes written by the `serde` path.
+        serde_len: usize,
+    },
+    /// The `io` path read a different compound than the one which was written.
+    IoRead,
+    /// The `serde` path read a different compound than the one which was written.
+    SerdeRead,
+}
+
+#[cfg(feature = "serde")]
+impl From<NbtIoError> for ParityError {
+    fn from(error: NbtIoError) -> Self {
+        ParityError::Io(error)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Display for ParityError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ParityError::Io(error) => write!(f, "{}", error),
+            &ParityError::Bytes {
+                offset,
+                io_len,
+                serde_len,
+            } => write!(
+                f,
+                "io wrote {} bytes and serde wrote {} bytes, which first differ at offset {}",
+                io_len, serde_len, offset
+            ),
+            ParityError::IoRead => write!(f, "io read a different compound than was written"),
+            ParityError::SerdeRead => write!(f, "serde read a different compound than was written"),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Error for ParityError {}
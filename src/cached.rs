@@ -0,0 +1,418 @@
+use crate::{
+    io::{self, read_root_header, read_tag_body_dyn, write_tag_body, Flavor, NbtIoError},
+    path::{AsNbtPath, Step},
+    raw,
+    NbtCompound,
+    NbtList,
+    NbtReprError,
+    NbtStructureError,
+    NbtTag,
+    NbtTagKind,
+};
+use std::{
+    collections::HashMap,
+    io::{Cursor, Read, Write},
+    mem,
+};
+
+/// A binary NBT document which remembers its encoding, so that saving it again after changing a
+/// few tags only encodes the lists and compounds which were changed.
+///
+/// Reading a document with [`read_from`] keeps a copy of its uncompressed bytes along with the
+/// position of every list and compound within them. Tags changed through [`get_path_mut`] mark
+/// themselves and the lists and compounds containing them as changed, and [`save_to`] copies the
+/// bytes of every other list and compound instead of encoding them again. This makes save loops
+/// which tweak a handful of values in large documents, such as player data, considerably faster.
+/// The positions of the saved encoding are remembered in turn, so every save only encodes what
+/// changed since the last one.
+///
+/// Since the uncompressed bytes are kept in memory, a cached document takes about twice as much
+/// memory as the compound alone. Changes made through [`root_mut`] cannot be tracked, so the next
+/// save encodes the whole document.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::io::{self, CachedDocument, Flavor};
+///
+/// let player = compound! {
+///     "XpLevel": 30i32,
+///     "Inventory": [{ "Slot": 0i8, "id": "minecraft:stone", "Count": 64i8 }],
+///     "EnderItems": []
+/// };
+/// let bytes = io::write_nbt_bytes(Some("Player"), &player, Flavor::GzCompressed)?;
+///
+/// let mut document = CachedDocument::read_from(&mut bytes.as_slice(), Flavor::GzCompressed)?;
+/// *document.get_path_mut("XpLevel")? = NbtTag::Int(31);
+/// *document.get_path_mut("Inventory[0].Count")? = NbtTag::Byte(63);
+///
+/// let mut saved = Vec::new();
+/// document.save_to(&mut saved)?;
+///
+/// let (read, root_name) = io::read_nbt(&mut saved.as_slice(), Flavor::GzCompressed)?;
+/// assert_eq!(root_name, "Player");
+/// assert_eq!(read.get::<_, i32>("XpLevel")?, 31);
+/// assert_eq!(&read, document.root());
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+///
+/// [`read_from`]: crate::io::CachedDocument::read_from
+/// [`get_path_mut`]: crate::io::CachedDocument::get_path_mut
+/// [`save_to`]: crate::io::CachedDocument::save_to
+/// [`root_mut`]: crate::io::CachedDocument::root_mut
+#[derive(Debug, Clone)]
+pub struct CachedDocument {
+    root: NbtCompound,
+    root_name: String,
+    flavor: Flavor,
+    bytes: Vec<u8>,
+    cache: Option<Node>,
+}
+
+impl CachedDocument {
+    /// Creates a document with the given root compound and name, which is saved with the given
+    /// flavor. Since the document has not been encoded yet, the first save encodes all of it.
+    pub fn new<S: Into<String>>(root_name: S, root: NbtCompound, flavor: Flavor) -> Self {
+        CachedDocument {
+            root,
+            root_name: root_name.into(),
+            flavor,
+            bytes: Vec::new(),
+            cache: None,
+        }
+    }
+
+    /// Reads the given flavor of NBT data from the given reader just like [`read_nbt`], and
+    /// remembers its encoding. The document is saved with the same flavor.
+    ///
+    /// [`read_nbt`]: crate::io::read_nbt
+    pub fn read_from<R: Read>(reader: &mut R, flavor: Flavor) -> Result<Self, NbtIoError> {
+        let mut bytes = Vec::new();
+        io::recompress(reader, &mut bytes, flavor, Flavor::Uncompressed)?;
+
+        let mut cursor = Cursor::new(bytes.as_slice());
+        let root_name = read_root_header(&mut cursor)?;
+        let (root, cache) = read_cached(&mut cursor, 0xA, 0)?;
+        let root = match root {
+            NbtTag::Compound(root) => root,
+            _ => unreachable!(),
+        };
+
+        Ok(CachedDocument {
+            root,
+            root_name,
+            flavor,
+            bytes,
+            cache,
+        })
+    }
+
+    /// Returns the root compound of this document.
+    pub fn root(&self) -> &NbtCompound {
+        &self.root
+    }
+
+    /// Returns the root compound of this document for arbitrary changes. Since these changes
+    /// cannot be tracked, the next save encodes the whole document.
+    pub fn root_mut(&mut self) -> &mut NbtCompound {
+        self.cache = None;
+        &mut self.root
+    }
+
+    /// Returns the name of the root compound.
+    pub fn root_name(&self) -> &str {
+        &self.root_name
+    }
+
+    /// Returns the flavor this document is saved with.
+    pub fn flavor(&self) -> Flavor {
+        self.flavor
+    }
+
+    /// Returns the tag which the given [`NbtPath`] selects in the root compound for changing it,
+    /// marking it as changed. The lists and compounds containing the tag are encoded again when
+    /// the document is saved, as is the tag itself, but their other contents are copied from the
+    /// previous encoding.
+    ///
+    /// An error is returned if the path is invalid or if it does not select exactly one tag.
+    ///
+    /// [`NbtPath`]: crate::path::NbtPath
+    pub fn get_path_mut<P: AsNbtPath + ?Sized>(
+        &mut self,
+        path: &P,
+    ) -> Result<&mut NbtTag, NbtReprError> {
+        let parsed = path.as_nbt_path().map_err(NbtReprError::from_any)?;
+        let steps = match parsed.locate_one(&self.root) {
+            Some(steps) => steps,
+            None => return Err(NbtStructureError::missing_tag(parsed.to_string()).into()),
+        };
+
+        if let Some(cache) = &mut self.cache {
+            cache.invalidate(&steps);
+        }
+
+        let missing = || NbtStructureError::missing_tag(parsed.to_string());
+        let (first, rest) = steps.split_first().ok_or_else(missing)?;
+        let mut tag = match first {
+            Step::Key(key) => self.root.0.get_mut(key.as_ref()),
+            Step::Index(_) => None,
+        }
+        .ok_or_else(missing)?;
+        for step in rest {
+            tag = match (step, tag) {
+                (Step::Key(key), NbtTag::Compound(compound)) => compound.0.get_mut(key.as_ref()),
+                (&Step::Index(index), NbtTag::List(list)) => list.0.get_mut(index),
+                _ => None,
+            }
+            .ok_or_else(missing)?;
+        }
+
+        Ok(tag)
+    }
+
+    /// Writes this document to the given writer with its flavor, copying the encoding of every
+    /// list and compound which has not changed since the document was read or last saved.
+    ///
+    /// An error is returned if the document contains a non-homogenous list, in which case nothing
+    /// is written.
+    pub fn save_to<W: Write>(&mut self, writer: &mut W) -> Result<(), NbtIoError> {
+        let mut bytes = Vec::with_capacity(self.bytes.len());
+        raw::write_u8(&mut bytes, 0xA)?;
+        raw::write_string(&mut bytes, &self.root_name)?;
+
+        // The root is moved into a tag so that it is written like any other compound
+        let root = NbtTag::Compound(mem::take(&mut self.root));
+        let result = write_cached(&mut bytes, &root, self.cache.take(), &self.bytes);
+        self.root = match root {
+            NbtTag::Compound(root) => root,
+            _ => unreachable!(),
+        };
+
+        self.cache = result?;
+        self.bytes = bytes;
+
+        io::recompress(
+            &mut self.bytes.as_slice(),
+            writer,
+            Flavor::Uncompressed,
+            self.flavor,
+        )?;
+        Ok(())
+    }
+}
+
+// The position of a list or compound within the previous encoding of a document, along with the
+// positions of the lists and compounds it contains. The start of a node is relative to the start
+// of its parent, so that unchanged subtrees can be moved into a new encoding as they are.
+#[derive(Debug, Clone)]
+struct Node {
+    start: usize,
+    len: usize,
+    // Whether a tag within this node changed, in which case its encoding cannot be copied
+    changed: bool,
+    children: Children,
+}
+
+#[derive(Debug, Clone)]
+enum Children {
+    None,
+    List(Vec<Option<Node>>),
+    Compound(HashMap<String, Node>),
+}
+
+impl Node {
+    // Marks the nodes along the given steps as changed, and forgets the node at the end of them
+    // since the tag there may be replaced entirely
+    fn invalidate(&mut self, steps: &[Step<'_>]) {
+        self.changed = true;
+        let (first, rest) = match steps.split_first() {
+            Some(split) => split,
+            None => return,
+        };
+
+        let child = match (first, &mut self.children) {
+            (Step::Key(key), Children::Compound(children)) if rest.is_empty() => {
+                children.remove(key.as_ref());
+                return;
+            }
+            (&Step::Index(index), Children::List(children)) if rest.is_empty() => {
+                if let Some(child) = children.get_mut(index) {
+                    *child = None;
+                }
+                return;
+            }
+            (Step::Key(key), Children::Compound(children)) => children.get_mut(key.as_ref()),
+            (&Step::Index(index), Children::List(children)) =>
+                children.get_mut(index).and_then(Option::as_mut),
+            _ => None,
+        };
+
+        if let Some(child) = child {
+            child.invalidate(rest);
+        }
+    }
+}
+
+// Reads the body of a tag, returning a node for it if it is a list or compound
+fn read_cached(
+    reader: &mut Cursor<&[u8]>,
+    tag_id: u8,
+    depth: usize,
+) -> Result<(NbtTag, Option<Node>), NbtIoError> {
+    let start = reader.position() as usize;
+    let (tag, children) = match tag_id {
+        0x9 => {
+            let depth = io::child_depth(depth)?;
+            let tag_id = raw::read_u8(reader)?;
+            let len = raw::read_len(reader)?;
+
+            if tag_id > 0xC || (tag_id == 0 && len > 0) {
+                return Err(NbtIoError::InvalidTagId(tag_id));
+            }
+
+            let mut list = NbtList::with_capacity(len.min(raw::PREALLOC_LIMIT));
+            list.1 = NbtTagKind::from_id(tag_id);
+            let mut children = Vec::new();
+            for _ in 0 .. len {
+                let (element, node) = read_cached(reader, tag_id, depth)?;
+                list.push(element);
+                if matches!(tag_id, 0x9 | 0xA) {
+                    children.push(node);
+                }
+            }
+
+            (NbtTag::List(list), Children::List(children))
+        }
+        0xA => {
+            let depth = io::child_depth(depth)?;
+            let mut compound = NbtCompound::new();
+            let mut children = HashMap::new();
+            loop {
+                let tag_id = raw::read_u8(reader)?;
+                if tag_id == 0x0 {
+                    break;
+                }
+
+                let name = raw::read_string(reader)?;
+                let (tag, node) = read_cached(reader, tag_id, depth)?;
+                match node {
+                    Some(node) => children.insert(name.clone(), node),
+                    None => children.remove(&name),
+                };
+                compound.insert(name, tag);
+            }
+
+            (NbtTag::Compound(compound), Children::Compound(children))
+        }
+        _ => return Ok((read_tag_body_dyn(reader, tag_id, depth)?, None)),
+    };
+
+    let end = reader.position() as usize;
+    Ok((tag, Some(make_node(start, start, end, children))))
+}
+
+// Writes the body of a tag, copying the encoding of the given node if it is unchanged, and
+// returns a node for the new encoding if the tag is a list or compound. The start of the old node
+// is absolute rather than relative to its parent.
+fn write_cached(
+    bytes: &mut Vec<u8>,
+    tag: &NbtTag,
+    node: Option<Node>,
+    old: &[u8],
+) -> Result<Option<Node>, NbtIoError> {
+    let start = bytes.len();
+    let (old_start, old_children) = match node {
+        Some(node) if !node.changed => {
+            bytes.extend_from_slice(&old[node.start .. node.start + node.len]);
+            return Ok(Some(Node { start, ..node }));
+        }
+        Some(node) => (node.start, node.children),
+        None => (0, Children::None),
+    };
+
+    let children = match tag {
+        NbtTag::List(list) if !list.is_empty() => {
+            let list_type = raw::id_for_tag(Some(&list[0]));
+            raw::write_u8(bytes, list_type)?;
+            raw::write_len(bytes, list.len())?;
+
+            let mut old_children = match old_children {
+                Children::List(children) => children.into_iter(),
+                _ => Vec::new().into_iter(),
+            };
+            let mut children = Vec::new();
+            for element in list.iter() {
+                let tag_id = raw::id_for_tag(Some(element));
+                if tag_id != list_type {
+                    return Err(NbtIoError::NonHomogenousList {
+                        list_type,
+                        encountered_type: tag_id,
+                    });
+                }
+
+                let old_child = old_children.next().flatten().map(|child| Node {
+                    start: old_start + child.start,
+                    ..child
+                });
+                let child = write_cached(bytes, element, old_child, old)?;
+                if matches!(tag_id, 0x9 | 0xA) {
+                    children.push(child);
+                }
+            }
+
+            Children::List(children)
+        }
+        NbtTag::Compound(compound) => {
+            let mut old_children = match old_children {
+                Children::Compound(children) => children,
+                _ => HashMap::new(),
+            };
+            let mut children = HashMap::new();
+            for (name, tag) in compound.inner() {
+                raw::write_u8(bytes, raw::id_for_tag(Some(tag)))?;
+                raw::write_string(bytes, name)?;
+
+                let old_child = old_children.remove(name).map(|child| Node {
+                    start: old_start + child.start,
+                    ..child
+                });
+                if let Some(child) = write_cached(bytes, tag, old_child, old)? {
+                    children.insert(name.clone(), child);
+                }
+            }
+            raw::write_u8(bytes, raw::id_for_tag(None))?;
+
+            Children::Compound(children)
+        }
+        _ => {
+            write_tag_body(bytes, tag)?;
+            return Ok(match tag {
+                NbtTag::List(_) => Some(make_node(start, start, bytes.len(), Children::None)),
+                _ => None,
+            });
+        }
+    };
+
+    Ok(Some(make_node(start, start, bytes.len(), children)))
+}
+
+// Creates a node spanning the given range, making the starts of its children relative to it
+fn make_node(origin: usize, start: usize, end: usize, mut children: Children) -> Node {
+    match &mut children {
+        Children::None => {}
+        Children::List(nodes) =>
+            for node in nodes.iter_mut().flatten() {
+                node.start -= origin;
+            },
+        Children::Compound(nodes) =>
+            for node in nodes.values_mut() {
+                node.start -= origin;
+            },
+    }
+
+    Node {
+        start,
+        len: end - start,
+        changed: false,
+        children,
+    }
+}
@@ -0,0 +1,229 @@
+#[cfg(not(feature = "bytemuck"))]
+use std::{mem::ManuallyDrop, slice};
+
+/// Converts a vec of unsigned bytes into a vec of signed bytes without copying or reallocating.
+///
+/// ```
+/// use quartz_nbt::cast;
+///
+/// assert_eq!(cast::byte_buf_to_signed(vec![0, 127, 128, 255]), vec![0, 127, -128, -1]);
+/// ```
+#[inline]
+pub fn byte_buf_to_signed(buf: Vec<u8>) -> Vec<i8> {
+    #[cfg(feature = "bytemuck")]
+    {
+        bytemuck::allocation::cast_vec(buf)
+    }
+
+    #[cfg(not(feature = "bytemuck"))]
+    {
+        let mut me = ManuallyDrop::new(buf);
+        // Pointer cast is valid because i8 and u8 have the same layout
+        let ptr = me.as_mut_ptr() as *mut i8;
+        let length = me.len();
+        let capacity = me.capacity();
+
+        // Safety
+        // * `ptr` was allocated by a Vec
+        // * i8 has the same size and alignment as u8
+        // * `length` and `capacity` came from a valid Vec
+        unsafe { Vec::from_raw_parts(ptr, length, capacity) }
+    }
+}
+
+/// Converts a vec of signed bytes into a vec of unsigned bytes without copying or reallocating.
+///
+/// ```
+/// use quartz_nbt::cast;
+///
+/// assert_eq!(cast::byte_buf_to_unsigned(vec![0, 127, -128, -1]), vec![0, 127, 128, 255]);
+/// ```
+#[inline]
+pub fn byte_buf_to_unsigned(buf: Vec<i8>) -> Vec<u8> {
+    #[cfg(feature = "bytemuck")]
+    {
+        bytemuck::allocation::cast_vec(buf)
+    }
+
+    #[cfg(not(feature = "bytemuck"))]
+    {
+        let mut me = ManuallyDrop::new(buf);
+        // Pointer cast is valid because i8 and u8 have the same layout
+        let ptr = me.as_mut_ptr() as *mut u8;
+        let length = me.len();
+        let capacity = me.capacity();
+
+        // Safety
+        // * `ptr` was allocated by a Vec
+        // * u8 has the same size and alignment as i8
+        // * `length` and `capacity` came from a valid Vec
+        unsafe { Vec::from_raw_parts(ptr, length, capacity) }
+    }
+}
+
+/// Reinterprets a slice of unsigned bytes as a slice of signed bytes.
+///
+/// ```
+/// use quartz_nbt::cast;
+///
+/// assert_eq!(cast::bytes_to_signed(&[1, 255]), &[1, -1]);
+/// ```
+#[inline]
+pub fn bytes_to_signed(bytes: &[u8]) -> &[i8] {
+    #[cfg(feature = "bytemuck")]
+    {
+        bytemuck::cast_slice(bytes)
+    }
+
+    #[cfg(not(feature = "bytemuck"))]
+    {
+        let data = bytes.as_ptr() as *const i8;
+        let len = bytes.len();
+
+        // Safety
+        // * `data` is valid for len * 1 bytes
+        //     * The entire memory range of `data` is contained in a single
+        //       allocated object since it came from a valid slice
+        //     * `data` is non-null and aligned correctly for u8 (and thus i8)
+        // * `data` points to exactly `len` consecutive bytes
+        // * The constructed reference adopts the lifetime of the provided reference
+        // * `len` <= isize::MAX because `len` came from a valid slice
+        unsafe { slice::from_raw_parts(data, len) }
+    }
+}
+
+/// Reinterprets a slice of signed bytes as a slice of unsigned bytes.
+///
+/// ```
+/// use quartz_nbt::cast;
+///
+/// assert_eq!(cast::bytes_to_unsigned(&[1, -1]), &[1, 255]);
+/// ```
+#[inline]
+pub fn bytes_to_unsigned(bytes: &[i8]) -> &[u8] {
+    #[cfg(feature = "bytemuck")]
+    {
+        bytemuck::cast_slice(bytes)
+    }
+
+    #[cfg(not(feature = "bytemuck"))]
+    {
+        let data = bytes.as_ptr() as *const u8;
+        let len = bytes.len();
+
+        // Safety
+        // * `data` is valid for len * 1 bytes
+        //     * The entire memory range of `data` is contained in a single
+        //       allocated object since it came from a valid slice
+        //     * `data` is non-null and aligned correctly for i8 (and thus u8)
+        // * `data` points to exactly `len` consecutive bytes
+        // * The constructed reference adopts the lifetime of the provided reference
+        // * `len` <= isize::MAX because `len` came from a valid slice
+        unsafe { slice::from_raw_parts(data, len) }
+    }
+}
+
+/// Reinterprets a mutable slice of unsigned bytes as a mutable slice of signed bytes.
+///
+/// ```
+/// use quartz_nbt::cast;
+///
+/// let mut bytes = [0u8; 2];
+/// cast::bytes_to_signed_mut(&mut bytes)[1] = -1;
+/// assert_eq!(bytes, [0, 255]);
+/// ```
+#[inline]
+pub fn bytes_to_signed_mut(bytes: &mut [u8]) -> &mut [i8] {
+    #[cfg(feature = "bytemuck")]
+    {
+        bytemuck::cast_slice_mut(bytes)
+    }
+
+    #[cfg(not(feature = "bytemuck"))]
+    {
+        let data = bytes.as_mut_ptr() as *mut i8;
+        let len = bytes.len();
+
+        // Safety
+        // * See `bytes_to_signed`
+        // * The provided reference is mutable, so no other references to its data exist, and the
+        //   constructed reference borrows it for its entire lifetime
+        unsafe { slice::from_raw_parts_mut(data, len) }
+    }
+}
+
+/// Reinterprets a mutable slice of signed bytes as a mutable slice of unsigned bytes.
+///
+/// ```
+/// use quartz_nbt::cast;
+///
+/// let mut bytes = [0i8; 2];
+/// cast::bytes_to_unsigned_mut(&mut bytes)[1] = 255;
+/// assert_eq!(bytes, [0, -1]);
+/// ```
+#[inline]
+pub fn bytes_to_unsigned_mut(bytes: &mut [i8]) -> &mut [u8] {
+    #[cfg(feature = "bytemuck")]
+    {
+        bytemuck::cast_slice_mut(bytes)
+    }
+
+    #[cfg(not(feature = "bytemuck"))]
+    {
+        let data = bytes.as_mut_ptr() as *mut u8;
+        let len = bytes.len();
+
+        // Safety
+        // * See `bytes_to_unsigned`
+        // * The provided reference is mutable, so no other references to its data exist, and the
+        //   constructed reference borrows it for its entire lifetime
+        unsafe { slice::from_raw_parts_mut(data, len) }
+    }
+}
+
+// Reinterprets a mutable slice of ints as a mutable slice of their bytes in native byte order, so
+// that arrays can be read into directly
+#[inline]
+pub(crate) fn ints_to_bytes_mut(ints: &mut [i32]) -> &mut [u8] {
+    #[cfg(feature = "bytemuck")]
+    {
+        bytemuck::cast_slice_mut(ints)
+    }
+
+    #[cfg(not(feature = "bytemuck"))]
+    {
+        let data = ints.as_mut_ptr() as *mut u8;
+        let len = ints.len() * 4;
+
+        // Safety
+        // * `data` is valid for `len` bytes since it points to `ints.len()` consecutive i32s,
+        //   which have a size of four bytes and no padding
+        // * `data` is non-null and aligned correctly for u8, which has an alignment of one
+        // * Every bit pattern is a valid u8, and writing any bytes through the constructed
+        //   reference leaves a valid i32
+        // * The provided reference is mutable, so no other references to its data exist, and the
+        //   constructed reference borrows it for its entire lifetime
+        // * `len` <= isize::MAX because it is the size of a valid slice
+        unsafe { slice::from_raw_parts_mut(data, len) }
+    }
+}
+
+// Reinterprets a mutable slice of longs as a mutable slice of their bytes in native byte order, so
+// that arrays can be read into directly
+#[inline]
+pub(crate) fn longs_to_bytes_mut(longs: &mut [i64]) -> &mut [u8] {
+    #[cfg(feature = "bytemuck")]
+    {
+        bytemuck::cast_slice_mut(longs)
+    }
+
+    #[cfg(not(feature = "bytemuck"))]
+    {
+        let data = longs.as_mut_ptr() as *mut u8;
+        let len = longs.len() * 8;
+
+        // Safety
+        // * See `ints_to_bytes_mut`, where i64s have a size of eight bytes and no padding
+        unsafe { slice::from_raw_parts_mut(data, len) }
+    }
+}
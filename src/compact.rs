@@ -0,0 +1,367 @@
+use crate::{
+    io::{self, Flavor, NbtIoError},
+    raw,
+    NbtCompound,
+    NbtList,
+    NbtTag,
+    NbtTagKind,
+};
+use flate2::{
+    read::{DeflateDecoder, GzDecoder, ZlibDecoder},
+    Compression,
+};
+use std::io::{Read, Write};
+
+/// The bytes every compact document starts with, followed by [`VERSION`].
+///
+/// [`VERSION`]: crate::compact::VERSION
+pub const MAGIC: [u8; 4] = *b"QNBC";
+
+/// The version of the compact encoding written by this crate. Documents with any other version are
+/// rejected, since the encoding may change in incompatible ways.
+pub const VERSION: u8 = 1;
+
+/// Writes the given compound in the compact encoding, compressed according to the given flavor. If
+/// no root name is provided, an empty string is used.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::{
+///     compact,
+///     io::{self, Flavor, NbtIoError},
+/// };
+///
+/// let nbt = compound! {
+///     "DataVersion": 3955i32,
+///     "Heightmap": [L; 64, 64, 65, 65, 66, 64],
+///     "Sections": [{ "Y": -4i8 }, { "Y": -3i8 }]
+/// };
+///
+/// let mut bytes = Vec::new();
+/// compact::write_compact(&mut bytes, None, &nbt, Flavor::Uncompressed)?;
+/// assert!(bytes.len() < io::write_nbt_bytes(None, &nbt, Flavor::Uncompressed)?.len());
+///
+/// let (read, _) = compact::read_compact(&mut bytes.as_slice(), Flavor::Uncompressed)?;
+/// assert_eq!(read, nbt);
+/// # Ok::<(), NbtIoError>(())
+/// ```
+pub fn write_compact<W: Write>(
+    writer: &mut W,
+    root_name: Option<&str>,
+    root: &NbtCompound,
+    flavor: Flavor,
+) -> Result<(), NbtIoError> {
+    io::with_encoder(writer, flavor, Compression::default(), |encoder| {
+        write_compact_uncompressed(encoder, root_name, root)
+    })
+}
+
+/// Reads a compound in the compact encoding, compressed according to the given flavor, returning
+/// the compound and its root name.
+///
+/// Malformed data is rejected with an error rather than causing a panic, just like with
+/// [`read_nbt`]. See [`write_compact`] for an example.
+///
+/// [`read_nbt`]: crate::io::read_nbt
+/// [`write_compact`]: crate::compact::write_compact
+pub fn read_compact<R: Read>(
+    reader: &mut R,
+    flavor: Flavor,
+) -> Result<(NbtCompound, String), NbtIoError> {
+    match flavor {
+        Flavor::Uncompressed => read_compact_uncompressed(reader),
+        Flavor::ZlibCompressed | Flavor::ZlibCompressedWith(_) =>
+            read_compact_uncompressed(&mut ZlibDecoder::new(reader)),
+        Flavor::GzCompressed | Flavor::GzCompressedWith(_) =>
+            read_compact_uncompressed(&mut GzDecoder::new(reader)),
+        Flavor::RawDeflate | Flavor::RawDeflateWith(_) =>
+            read_compact_uncompressed(&mut DeflateDecoder::new(reader)),
+    }
+}
+
+/// Converts a document in the compact encoding to standard NBT, which can be read by Minecraft.
+/// The flavors describe the compression of the input and output respectively.
+///
+/// The whole document is read into memory before it is written. Strings longer than 65535 bytes
+/// once encoded, which the compact encoding allows, cannot be represented in standard NBT.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::{
+///     compact,
+///     io::{self, Flavor, NbtIoError},
+/// };
+///
+/// let nbt = compound! { "id": "minecraft:chest", "x": -120i32, "y": 64i32, "z": 17i32 };
+/// let gz = io::write_nbt_bytes(Some("block"), &nbt, Flavor::GzCompressed)?;
+///
+/// let mut cached = Vec::new();
+/// compact::nbt_to_compact(&mut gz.as_slice(), &mut cached, Flavor::GzCompressed, Flavor::Uncompressed)?;
+///
+/// let mut restored = Vec::new();
+/// compact::compact_to_nbt(&mut cached.as_slice(), &mut restored, Flavor::Uncompressed, Flavor::GzCompressed)?;
+/// assert_eq!(io::read_nbt(&mut restored.as_slice(), Flavor::GzCompressed)?, (nbt, "block".to_owned()));
+/// # Ok::<(), NbtIoError>(())
+/// ```
+pub fn compact_to_nbt<R: Read, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    from: Flavor,
+    to: Flavor,
+) -> Result<(), NbtIoError> {
+    let (root, root_name) = read_compact(reader, from)?;
+    io::write_nbt(writer, Some(&root_name), &root, to)
+}
+
+/// Converts standard NBT to the compact encoding. The flavors describe the compression of the input
+/// and output respectively. See [`compact_to_nbt`] for an example.
+///
+/// The whole document is read into memory before it is written.
+///
+/// [`compact_to_nbt`]: crate::compact::compact_to_nbt
+pub fn nbt_to_compact<R: Read, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    from: Flavor,
+    to: Flavor,
+) -> Result<(), NbtIoError> {
+    let (root, root_name) = io::read_nbt(reader, from)?;
+    write_compact(writer, Some(&root_name), &root, to)
+}
+
+fn write_compact_uncompressed<W: Write>(
+    writer: &mut W,
+    root_name: Option<&str>,
+    root: &NbtCompound,
+) -> Result<(), NbtIoError> {
+    writer.write_all(&MAGIC)?;
+    raw::write_u8(writer, VERSION)?;
+    write_string(writer, root_name.unwrap_or(""))?;
+    write_compound(writer, root)
+}
+
+fn write_compound<W: Write>(writer: &mut W, compound: &NbtCompound) -> Result<(), NbtIoError> {
+    for (name, tag) in compound.inner() {
+        raw::write_u8(writer, raw::id_for_tag(Some(tag)))?;
+        write_string(writer, name)?;
+        write_tag_body(writer, tag)?;
+    }
+
+    // TAG_End
+    raw::write_u8(writer, raw::id_for_tag(None))?;
+    Ok(())
+}
+
+fn write_tag_body<W: Write>(writer: &mut W, tag: &NbtTag) -> Result<(), NbtIoError> {
+    match tag {
+        &NbtTag::Byte(value) => raw::write_i8(writer, value)?,
+        &NbtTag::Short(value) => write_varint(writer, zigzag(value.into()))?,
+        &NbtTag::Int(value) => write_varint(writer, zigzag(value.into()))?,
+        &NbtTag::Long(value) => write_varint(writer, zigzag(value))?,
+        &NbtTag::Float(value) => raw::write_f32(writer, value)?,
+        &NbtTag::Double(value) => raw::write_f64(writer, value)?,
+        NbtTag::ByteArray(value) => {
+            write_len(writer, value.len())?;
+            writer.write_all(crate::cast::bytes_to_unsigned(value.as_slice()))?;
+        }
+        NbtTag::String(value) => write_string(writer, value)?,
+        NbtTag::List(value) => {
+            let list_type = match value.first() {
+                Some(first) => raw::id_for_tag(Some(first)),
+                None => value.1.map_or(raw::id_for_tag(None), NbtTagKind::id),
+            };
+            raw::write_u8(writer, list_type)?;
+            write_len(writer, value.len())?;
+
+            for element in value.iter() {
+                let tag_id = raw::id_for_tag(Some(element));
+                if tag_id != list_type {
+                    return Err(NbtIoError::NonHomogenousList {
+                        list_type,
+                        encountered_type: tag_id,
+                    });
+                }
+
+                write_tag_body(writer, element)?;
+            }
+        }
+        NbtTag::Compound(value) => write_compound(writer, value)?,
+        // Arrays are delta encoded, since neighbouring elements, such as those of heightmaps and
+        // sorted IDs, tend to be close to each other
+        NbtTag::IntArray(value) => {
+            write_len(writer, value.len())?;
+            let mut previous = 0i32;
+            for &int in value {
+                write_varint(writer, zigzag(int.wrapping_sub(previous).into()))?;
+                previous = int;
+            }
+        }
+        NbtTag::LongArray(value) => {
+            write_len(writer, value.len())?;
+            let mut previous = 0i64;
+            for &long in value {
+                write_varint(writer, zigzag(long.wrapping_sub(previous)))?;
+                previous = long;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn read_compact_uncompressed<R: Read>(reader: &mut R) -> Result<(NbtCompound, String), NbtIoError> {
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    if magic != MAGIC {
+        return Err(NbtIoError::InvalidCompactData("missing magic bytes"));
+    }
+    if raw::read_u8(reader)? != VERSION {
+        return Err(NbtIoError::InvalidCompactData("unsupported version"));
+    }
+
+    let root_name = read_string(reader)?;
+    let root = read_compound(reader, 0)?;
+    Ok((root, root_name))
+}
+
+// The depth is the number of lists and compounds enclosing the compound being read
+fn read_compound<R: Read>(reader: &mut R, depth: usize) -> Result<NbtCompound, NbtIoError> {
+    let depth = io::child_depth(depth)?;
+    let mut compound = NbtCompound::new();
+    let mut tag_id = raw::read_u8(reader)?;
+
+    // Read until TAG_End
+    while tag_id != 0x0 {
+        let name = read_string(reader)?;
+        let tag = read_tag_body(reader, tag_id, depth)?;
+        compound.insert(name, tag);
+        tag_id = raw::read_u8(reader)?;
+    }
+
+    Ok(compound)
+}
+
+fn read_tag_body<R: Read>(reader: &mut R, tag_id: u8, depth: usize) -> Result<NbtTag, NbtIoError> {
+    let tag = match tag_id {
+        0x1 => NbtTag::Byte(raw::read_i8(reader)?),
+        0x2 => NbtTag::Short(read_int(reader)?),
+        0x3 => NbtTag::Int(read_int(reader)?),
+        0x4 => NbtTag::Long(read_int(reader)?),
+        0x5 => NbtTag::Float(raw::read_f32(reader)?),
+        0x6 => NbtTag::Double(raw::read_f64(reader)?),
+        0x7 => {
+            let len = read_len(reader)?;
+            let array = raw::read_byte_array(reader, len)?;
+            NbtTag::ByteArray(crate::cast::byte_buf_to_signed(array))
+        }
+        0x8 => NbtTag::String(read_string(reader)?),
+        0x9 => {
+            let depth = io::child_depth(depth)?;
+            let tag_id = raw::read_u8(reader)?;
+            let len = read_len(reader)?;
+            if tag_id > 0xC || (tag_id == 0 && len > 0) {
+                return Err(NbtIoError::InvalidTagId(tag_id));
+            }
+
+            let mut list = NbtList::with_capacity(len.min(raw::PREALLOC_LIMIT));
+            list.1 = NbtTagKind::from_id(tag_id);
+            for _ in 0 .. len {
+                list.push(read_tag_body(reader, tag_id, depth)?);
+            }
+            NbtTag::List(list)
+        }
+        0xA => NbtTag::Compound(read_compound(reader, depth)?),
+        0xB => {
+            let len = read_len(reader)?;
+            let mut array = Vec::with_capacity(len.min(raw::PREALLOC_LIMIT));
+            let mut previous = 0i32;
+            for _ in 0 .. len {
+                previous = previous.wrapping_add(read_int(reader)?);
+                array.push(previous);
+            }
+            NbtTag::IntArray(array)
+        }
+        0xC => {
+            let len = read_len(reader)?;
+            let mut array = Vec::with_capacity(len.min(raw::PREALLOC_LIMIT));
+            let mut previous = 0i64;
+            for _ in 0 .. len {
+                previous = previous.wrapping_add(read_int(reader)?);
+                array.push(previous);
+            }
+            NbtTag::LongArray(array)
+        }
+        _ => return Err(NbtIoError::InvalidTagId(tag_id)),
+    };
+
+    Ok(tag)
+}
+
+fn write_string<W: Write>(writer: &mut W, string: &str) -> Result<(), NbtIoError> {
+    write_len(writer, string.len())?;
+    writer.write_all(string.as_bytes())?;
+    Ok(())
+}
+
+fn read_string<R: Read>(reader: &mut R) -> Result<String, NbtIoError> {
+    let len = read_len(reader)?;
+    String::from_utf8(raw::read_byte_array(reader, len)?)
+        .map_err(|_| NbtIoError::InvalidCompactData("string is not valid UTF-8"))
+}
+
+fn write_len<W: Write>(writer: &mut W, len: usize) -> Result<(), NbtIoError> {
+    write_varint(writer, len as u64)
+}
+
+fn read_len<R: Read>(reader: &mut R) -> Result<usize, NbtIoError> {
+    usize::try_from(read_varint(reader)?)
+        .map_err(|_| NbtIoError::InvalidCompactData("length is too large"))
+}
+
+// Reads a zigzag encoded integer, checking that it fits in the given type
+fn read_int<R: Read, T: TryFrom<i64>>(reader: &mut R) -> Result<T, NbtIoError> {
+    T::try_from(unzigzag(read_varint(reader)?))
+        .map_err(|_| NbtIoError::InvalidCompactData("integer is out of range"))
+}
+
+// Writes an unsigned LEB128 varint, which takes up to ten bytes
+fn write_varint<W: Write>(writer: &mut W, mut value: u64) -> Result<(), NbtIoError> {
+    let mut buffer = [0u8; 10];
+    let mut len = 0;
+    while value >= 0x80 {
+        buffer[len] = value as u8 | 0x80;
+        value >>= 7;
+        len += 1;
+    }
+    buffer[len] = value as u8;
+    writer.write_all(&buffer[..= len])?;
+    Ok(())
+}
+
+fn read_varint<R: Read>(reader: &mut R) -> Result<u64, NbtIoError> {
+    let mut value = 0u64;
+    for shift in (0 .. 64).step_by(7) {
+        let byte = raw::read_u8(reader)?;
+        // Only the lowest bit of the tenth byte fits in a u64
+        if shift == 63 && byte > 1 {
+            break;
+        }
+
+        value |= u64::from(byte & 0x7F) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+
+    Err(NbtIoError::InvalidCompactData("varint is too long"))
+}
+
+#[inline]
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+#[inline]
+fn unzigzag(value: u64) -> i64 {
+    (value >> 1) as i64 ^ -((value & 1) as i64)
+}
@@ -0,0 +1,674 @@
+use crate::{
+    snbt::{self, ParseOptions, ParserErrorType, SnbtError},
+    NbtCompound,
+};
+use std::fmt::{self, Display, Formatter};
+use ErrorKind::*;
+use Outcome::{Error, Value};
+
+/// The kind of a [`SnbtError`], without the position or expected token it carries.
+///
+/// Test vectors compare errors by kind only, since the exact position reported for an error is
+/// specific to this parser.
+///
+/// [`SnbtError`]: crate::snbt::SnbtError
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// See [`ParserErrorType::UnmatchedQuote`].
+    ///
+    /// [`ParserErrorType::UnmatchedQuote`]: crate::snbt::ParserErrorType::UnmatchedQuote
+    UnmatchedQuote,
+    /// See [`ParserErrorType::UnknownEscapeSequence`].
+    ///
+    /// [`ParserErrorType::UnknownEscapeSequence`]: crate::snbt::ParserErrorType::UnknownEscapeSequence
+    UnknownEscapeSequence,
+    /// See [`ParserErrorType::InvalidNumber`].
+    ///
+    /// [`ParserErrorType::InvalidNumber`]: crate::snbt::ParserErrorType::InvalidNumber
+    InvalidNumber,
+    /// See [`ParserErrorType::UnexpectedToken`].
+    ///
+    /// [`ParserErrorType::UnexpectedToken`]: crate::snbt::ParserErrorType::UnexpectedToken
+    UnexpectedToken,
+    /// See [`ParserErrorType::UnexpectedEOS`].
+    ///
+    /// [`ParserErrorType::UnexpectedEOS`]: crate::snbt::ParserErrorType::UnexpectedEOS
+    UnexpectedEOS,
+    /// See [`ParserErrorType::TrailingComma`].
+    ///
+    /// [`ParserErrorType::TrailingComma`]: crate::snbt::ParserErrorType::TrailingComma
+    TrailingComma,
+    /// See [`ParserErrorType::UnmatchedBrace`].
+    ///
+    /// [`ParserErrorType::UnmatchedBrace`]: crate::snbt::ParserErrorType::UnmatchedBrace
+    UnmatchedBrace,
+    /// See [`ParserErrorType::NonHomogenousList`].
+    ///
+    /// [`ParserErrorType::NonHomogenousList`]: crate::snbt::ParserErrorType::NonHomogenousList
+    NonHomogenousList,
+    /// See [`ParserErrorType::ExceededMaxDepth`].
+    ///
+    /// [`ParserErrorType::ExceededMaxDepth`]: crate::snbt::ParserErrorType::ExceededMaxDepth
+    ExceededMaxDepth,
+    /// See [`ParserErrorType::DuplicateKey`].
+    ///
+    /// [`ParserErrorType::DuplicateKey`]: crate::snbt::ParserErrorType::DuplicateKey
+    DuplicateKey,
+    /// See [`ParserErrorType::ExceededMaxTokenLength`].
+    ///
+    /// [`ParserErrorType::ExceededMaxTokenLength`]: crate::snbt::ParserErrorType::ExceededMaxTokenLength
+    ExceededMaxTokenLength,
+}
+
+impl ErrorKind {
+    /// Returns the kind of the given error.
+    pub fn of(error: &SnbtError) -> Self {
+        match error.error_type() {
+            ParserErrorType::UnmatchedQuote { .. } => UnmatchedQuote,
+            ParserErrorType::UnknownEscapeSequence { .. } => UnknownEscapeSequence,
+            ParserErrorType::InvalidNumber { .. } => InvalidNumber,
+            ParserErrorType::UnexpectedToken { .. } => UnexpectedToken,
+            ParserErrorType::UnexpectedEOS { .. } => UnexpectedEOS,
+            ParserErrorType::TrailingComma { .. } => TrailingComma,
+            ParserErrorType::UnmatchedBrace { .. } => UnmatchedBrace,
+            ParserErrorType::NonHomogenousList { .. } => NonHomogenousList,
+            ParserErrorType::ExceededMaxDepth { .. } => ExceededMaxDepth,
+            ParserErrorType::DuplicateKey { .. } => DuplicateKey,
+            ParserErrorType::ExceededMaxTokenLength { .. } => ExceededMaxTokenLength,
+        }
+    }
+}
+
+/// The expected result of parsing the input of a [`TestVector`].
+///
+/// [`TestVector`]: crate::conformance::TestVector
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Outcome {
+    /// The input parses successfully to the compound described by the given SNBT, which is itself
+    /// parsed with [`snbt::parse`] before comparing the two.
+    ///
+    /// [`snbt::parse`]: crate::snbt::parse
+    Value(&'static str),
+    /// The input fails to parse with an error of the given kind.
+    Error(ErrorKind),
+}
+
+/// The SNBT parsing mode a test vector is run in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mode {
+    /// The default, lenient mode of [`snbt::parse`].
+    ///
+    /// [`snbt::parse`]: crate::snbt::parse
+    Lenient,
+    /// The mode enabled with [`ParseOptions::vanilla_strict`].
+    ///
+    /// [`ParseOptions::vanilla_strict`]: crate::snbt::ParseOptions::vanilla_strict
+    Strict,
+}
+
+impl Mode {
+    /// Returns the parse options for this mode.
+    pub fn options(self) -> ParseOptions {
+        match self {
+            Mode::Lenient => ParseOptions::new(),
+            Mode::Strict => ParseOptions::new().vanilla_strict(true),
+        }
+    }
+}
+
+impl Display for Mode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Mode::Lenient => write!(f, "lenient"),
+            Mode::Strict => write!(f, "strict"),
+        }
+    }
+}
+
+/// An SNBT input paired with the expected result of parsing it in each [`Mode`].
+///
+/// [`Mode`]: crate::conformance::Mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TestVector {
+    /// The SNBT to parse.
+    pub input: &'static str,
+    /// The expected result in lenient mode.
+    pub lenient: Outcome,
+    /// The expected result in strict mode.
+    pub strict: Outcome,
+}
+
+impl TestVector {
+    /// Returns a test vector with different expected results in each mode.
+    pub const fn new(input: &'static str, lenient: Outcome, strict: Outcome) -> Self {
+        TestVector {
+            input,
+            lenient,
+            strict,
+        }
+    }
+
+    /// Returns a test vector with the same expected result in both modes.
+    pub const fn both(input: &'static str, outcome: Outcome) -> Self {
+        Self::new(input, outcome, outcome)
+    }
+
+    /// Returns the expected result in the given mode.
+    pub fn expected(&self, mode: Mode) -> Outcome {
+        match mode {
+            Mode::Lenient => self.lenient,
+            Mode::Strict => self.strict,
+        }
+    }
+}
+
+/// A test vector whose actual result did not match the expected one, as returned by [`run`].
+///
+/// [`run`]: crate::conformance::run
+#[derive(Debug, Clone, PartialEq)]
+pub struct Failure {
+    /// The input of the test vector.
+    pub input: &'static str,
+    /// The mode the input was parsed in.
+    pub mode: Mode,
+    /// The expected result.
+    pub expected: Outcome,
+    /// The actual result.
+    pub actual: Result<NbtCompound, ErrorKind>,
+}
+
+impl Display for Failure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} in {} mode: expected ", self.input, self.mode)?;
+        match self.expected {
+            Value(value) => write!(f, "{}", value)?,
+            Error(kind) => write!(f, "{:?}", kind)?,
+        }
+        match &self.actual {
+            Ok(value) => write!(f, " but found {}", value),
+            Err(kind) => write!(f, " but found {:?}", kind),
+        }
+    }
+}
+
+/// Parses the input of every given test vector in both modes with this crate's parser, returning
+/// the vectors whose results did not match.
+///
+/// ```
+/// use quartz_nbt::conformance::{self, ErrorKind, Outcome, TestVector};
+///
+/// let vectors = [
+///     TestVector::both("{id: 'minecraft:stone'}", Outcome::Value("{id: \"minecraft:stone\"}")),
+///     TestVector::new(
+///         "{id: minecraft:stone}",
+///         Outcome::Value("{id: \"minecraft:stone\"}"),
+///         Outcome::Error(ErrorKind::UnexpectedToken),
+///     ),
+/// ];
+/// assert!(conformance::run(&vectors).is_empty());
+/// assert!(conformance::run(conformance::VECTORS).is_empty());
+/// ```
+pub fn run(vectors: &[TestVector]) -> Vec<Failure> {
+    run_with(vectors, |input, mode| {
+        snbt::parse_with(input, mode.options())
+    })
+}
+
+/// Parses the input of every given test vector in both modes with the given function, returning
+/// the vectors whose results did not match.
+///
+/// This allows other SNBT parsers, or wrappers around this crate's parser, to be checked against
+/// the same vectors. The expected values are always parsed with [`snbt::parse`].
+///
+/// # Panics
+///
+/// Panics if the SNBT of an expected value fails to parse.
+///
+/// [`snbt::parse`]: crate::snbt::parse
+pub fn run_with<F>(vectors: &[TestVector], mut parse: F) -> Vec<Failure>
+where F: FnMut(&str, Mode) -> Result<NbtCompound, SnbtError> {
+    let mut failures = Vec::new();
+    for vector in vectors {
+        for mode in [Mode::Lenient, Mode::Strict] {
+            let expected = vector.expected(mode);
+            let actual = parse(vector.input, mode).map_err(|error| ErrorKind::of(&error));
+            let matches = match (expected, &actual) {
+                (Value(value), Ok(actual)) => match snbt::parse(value) {
+                    Ok(value) => value == *actual,
+                    Err(error) => panic!("Invalid expected value {:?}: {}", value, error),
+                },
+                (Error(kind), Err(actual)) => kind == *actual,
+                _ => false,
+            };
+
+            if !matches {
+                failures.push(Failure {
+                    input: vector.input,
+                    mode,
+                    expected,
+                    actual,
+                });
+            }
+        }
+    }
+    failures
+}
+
+/// Test vectors covering the SNBT syntax accepted by Minecraft: Java Edition from 1.13 through
+/// 1.21.4, along with the extensions accepted by this crate's lenient mode.
+///
+/// The strict result of every vector matches what the game's own parser produces for the same
+/// input, with the exception of error kinds, which the game does not distinguish. Each input is a
+/// whole document, so most vectors wrap the value under test in a compound with the key `v`.
+pub static VECTORS: &[TestVector] = &[
+    // Integers
+    TestVector::both("{v: 0}", Value("{v:0}")),
+    TestVector::both("{v: 1}", Value("{v:1}")),
+    TestVector::both("{v: -1}", Value("{v:-1}")),
+    TestVector::both("{v: +1}", Value("{v:1}")),
+    TestVector::both("{v: 2147483647}", Value("{v:2147483647}")),
+    TestVector::both("{v: -2147483648}", Value("{v:-2147483648}")),
+    TestVector::new(
+        "{v: 2147483648}",
+        Error(InvalidNumber),
+        Value("{v:\"2147483648\"}"),
+    ),
+    TestVector::new(
+        "{v: -2147483649}",
+        Error(InvalidNumber),
+        Value("{v:\"-2147483649\"}"),
+    ),
+    TestVector::new("{v: 007}", Value("{v:7}"), Value("{v:\"007\"}")),
+    TestVector::new("{v: 00}", Value("{v:0}"), Value("{v:\"00\"}")),
+    TestVector::both("{v: -0}", Value("{v:0}")),
+    // Bytes, shorts and longs
+    TestVector::both("{v: 1b}", Value("{v:1B}")),
+    TestVector::both("{v: 1B}", Value("{v:1B}")),
+    TestVector::both("{v: -128b}", Value("{v:-128B}")),
+    TestVector::both("{v: 127b}", Value("{v:127B}")),
+    TestVector::new("{v: 128b}", Error(InvalidNumber), Value("{v:\"128b\"}")),
+    TestVector::new("{v: -129b}", Error(InvalidNumber), Value("{v:\"-129b\"}")),
+    TestVector::both("{v: +5b}", Value("{v:5B}")),
+    TestVector::new("{v: 01b}", Value("{v:1B}"), Value("{v:\"01b\"}")),
+    TestVector::both("{v: 1s}", Value("{v:1S}")),
+    TestVector::both("{v: 1S}", Value("{v:1S}")),
+    TestVector::both("{v: 32767s}", Value("{v:32767S}")),
+    TestVector::new("{v: 32768s}", Error(InvalidNumber), Value("{v:\"32768s\"}")),
+    TestVector::new(
+        "{v: -32769s}",
+        Error(InvalidNumber),
+        Value("{v:\"-32769s\"}"),
+    ),
+    TestVector::both("{v: -32768s}", Value("{v:-32768S}")),
+    TestVector::both("{v: 1l}", Value("{v:1L}")),
+    TestVector::both("{v: 1L}", Value("{v:1L}")),
+    TestVector::both(
+        "{v: 9223372036854775807L}",
+        Value("{v:9223372036854775807L}"),
+    ),
+    TestVector::new(
+        "{v: 9223372036854775808L}",
+        Error(InvalidNumber),
+        Value("{v:\"9223372036854775808L\"}"),
+    ),
+    TestVector::both(
+        "{v: -9223372036854775808L}",
+        Value("{v:-9223372036854775808L}"),
+    ),
+    TestVector::new("{v: 01L}", Value("{v:1L}"), Value("{v:\"01L\"}")),
+    // Floats and doubles
+    TestVector::both("{v: 1f}", Value("{v:1F}")),
+    TestVector::both("{v: 1F}", Value("{v:1F}")),
+    TestVector::both("{v: 1.5f}", Value("{v:1.5F}")),
+    TestVector::both("{v: -1.5f}", Value("{v:-1.5F}")),
+    TestVector::both("{v: .5f}", Value("{v:0.5F}")),
+    TestVector::both("{v: -.5f}", Value("{v:-0.5F}")),
+    TestVector::both("{v: 1.f}", Value("{v:1F}")),
+    TestVector::both("{v: 1e3f}", Value("{v:1000F}")),
+    TestVector::both("{v: 1.5e3f}", Value("{v:1500F}")),
+    TestVector::both("{v: 1e-3f}", Value("{v:0.001F}")),
+    TestVector::both("{v: 1E+3f}", Value("{v:1000F}")),
+    TestVector::both("{v: 1d}", Value("{v:1D}")),
+    TestVector::both("{v: 1D}", Value("{v:1D}")),
+    TestVector::both("{v: 1.5d}", Value("{v:1.5D}")),
+    TestVector::both("{v: 1.5}", Value("{v:1.5D}")),
+    TestVector::both("{v: -1.5}", Value("{v:-1.5D}")),
+    TestVector::both("{v: +1.5}", Value("{v:1.5D}")),
+    TestVector::both("{v: .5}", Value("{v:0.5D}")),
+    TestVector::both("{v: +.5}", Value("{v:0.5D}")),
+    TestVector::both("{v: -.5}", Value("{v:-0.5D}")),
+    TestVector::both("{v: 1.}", Value("{v:1D}")),
+    TestVector::both("{v: 1.e3}", Value("{v:1000D}")),
+    TestVector::both("{v: 1.5e3}", Value("{v:1500D}")),
+    TestVector::both("{v: 1.5E3}", Value("{v:1500D}")),
+    TestVector::both("{v: 1.5e-3}", Value("{v:0.0015D}")),
+    TestVector::both("{v: 1.5e+3}", Value("{v:1500D}")),
+    TestVector::new("{v: 1e3}", Error(InvalidNumber), Value("{v:\"1e3\"}")),
+    TestVector::both("{v: 1e3d}", Value("{v:1000D}")),
+    TestVector::new("{v: 1.5e}", Error(InvalidNumber), Value("{v:\"1.5e\"}")),
+    TestVector::new(
+        "{v: 1.5e3.5}",
+        Error(InvalidNumber),
+        Value("{v:\"1.5e3.5\"}"),
+    ),
+    TestVector::both("{v: 0.1}", Value("{v:0.1D}")),
+    TestVector::both("{v: 00.5}", Value("{v:0.5D}")),
+    TestVector::new("{v: 1.2.3}", Error(InvalidNumber), Value("{v:\"1.2.3\"}")),
+    TestVector::new("{v: 1..2}", Error(InvalidNumber), Value("{v:\"1..2\"}")),
+    TestVector::both("{v: .}", Value("{v:.}")),
+    TestVector::both("{v: .5.5}", Value("{v:\".5.5\"}")),
+    // Malformed numbers
+    TestVector::new("{v: -}", Error(InvalidNumber), Value("{v:\"-\"}")),
+    TestVector::both("{v: +}", Value("{v:+}")),
+    TestVector::both("{v: +5x}", Value("{v:\"+5x\"}")),
+    TestVector::both("{v: +5L}", Value("{v:5L}")),
+    TestVector::new("{v: -b}", Error(InvalidNumber), Value("{v:\"-b\"}")),
+    TestVector::new("{v: 1x}", Error(InvalidNumber), Value("{v:\"1x\"}")),
+    TestVector::new("{v: 1bb}", Error(InvalidNumber), Value("{v:\"1bb\"}")),
+    TestVector::new("{v: 0x10}", Error(InvalidNumber), Value("{v:\"0x10\"}")),
+    TestVector::new("{v: 1_000}", Error(InvalidNumber), Value("{v:\"1_000\"}")),
+    TestVector::new("{v: 1,000}", Error(InvalidNumber), Error(UnexpectedToken)),
+    TestVector::new("{v: 12abc}", Error(InvalidNumber), Value("{v:\"12abc\"}")),
+    TestVector::both("{v: NaN}", Value("{v:NaN}")),
+    TestVector::both("{v: Infinity}", Value("{v:Infinity}")),
+    TestVector::new(
+        "{v: -Infinity}",
+        Error(InvalidNumber),
+        Value("{v:\"-Infinity\"}"),
+    ),
+    TestVector::both("{v: nan}", Value("{v:nan}")),
+    TestVector::new("{v: 1.5b}", Error(InvalidNumber), Value("{v:\"1.5b\"}")),
+    TestVector::new("{v: 1.5s}", Error(InvalidNumber), Value("{v:\"1.5s\"}")),
+    TestVector::new("{v: 1.5L}", Error(InvalidNumber), Value("{v:\"1.5L\"}")),
+    // Booleans
+    TestVector::both("{v: true}", Value("{v:1B}")),
+    TestVector::both("{v: false}", Value("{v:0B}")),
+    TestVector::both("{v: TRUE}", Value("{v:1B}")),
+    TestVector::both("{v: False}", Value("{v:0B}")),
+    TestVector::both("{v: 'true'}", Value("{v:\"true\"}")),
+    TestVector::both("{v: \"false\"}", Value("{v:\"false\"}")),
+    TestVector::both("{v: truee}", Value("{v:truee}")),
+    TestVector::both("{v: yes}", Value("{v:yes}")),
+    // Unquoted strings
+    TestVector::both("{v: abc}", Value("{v:abc}")),
+    TestVector::both("{v: ABC}", Value("{v:ABC}")),
+    TestVector::both("{v: a_b}", Value("{v:a_b}")),
+    TestVector::both("{v: a-b}", Value("{v:a-b}")),
+    TestVector::both("{v: a.b}", Value("{v:a.b}")),
+    TestVector::both("{v: a+b}", Value("{v:a+b}")),
+    TestVector::both("{v: minecraft.stone}", Value("{v:minecraft.stone}")),
+    TestVector::new(
+        "{v: minecraft:stone}",
+        Value("{v:\"minecraft:stone\"}"),
+        Error(UnexpectedToken),
+    ),
+    TestVector::new("{v: a b}", Value("{v:a b}"), Error(UnexpectedToken)),
+    TestVector::new("{v: a  b}", Value("{v:a  b}"), Error(UnexpectedToken)),
+    TestVector::new("{v: a/b}", Value("{v:a/b}"), Error(UnexpectedToken)),
+    TestVector::new("{v: a@b}", Value("{v:a@b}"), Error(UnexpectedToken)),
+    TestVector::new("{v: @s}", Value("{v:@s}"), Error(UnexpectedToken)),
+    TestVector::new("{v: é}", Value("{v:é}"), Error(UnexpectedToken)),
+    TestVector::new("{v: a#b}", Value("{v:a#b}"), Error(UnexpectedToken)),
+    TestVector::new("{v: a$b}", Value("{v:a$b}"), Error(UnexpectedToken)),
+    TestVector::new("{v: a*b}", Value("{v:a*b}"), Error(UnexpectedToken)),
+    TestVector::new("{v: a=b}", Value("{v:a=b}"), Error(UnexpectedToken)),
+    TestVector::new("{v: a?b}", Value("{v:a?b}"), Error(UnexpectedToken)),
+    TestVector::new("{v: a!b}", Value("{v:a!b}"), Error(UnexpectedToken)),
+    TestVector::new("{v: a{b}c}", Value("{v:\"a{b}c\"}"), Error(UnexpectedToken)),
+    TestVector::new("{v: a[b]c}", Value("{v:\"a[b]c\"}"), Error(UnexpectedToken)),
+    // Quoted strings
+    TestVector::both("{v: \"a\"}", Value("{v:a}")),
+    TestVector::both("{v: 'a'}", Value("{v:a}")),
+    TestVector::both("{v: \"\"}", Value("{v:\"\"}")),
+    TestVector::both("{v: ''}", Value("{v:\"\"}")),
+    TestVector::both("{v: \"a b\"}", Value("{v:a b}")),
+    TestVector::both("{v: \"a'b\"}", Value("{v:\"a'b\"}")),
+    TestVector::both("{v: 'a\"b'}", Value("{v:'a\"b'}")),
+    TestVector::both("{v: \"a\\\"b\"}", Value("{v:'a\"b'}")),
+    TestVector::both("{v: 'a\\'b'}", Value("{v:\"a'b\"}")),
+    TestVector::both("{v: \"a\\\\b\"}", Value("{v:\"a\\\\b\"}")),
+    TestVector::new(
+        "{v: \"a\\nb\"}",
+        Value("{v:\"a\\nb\"}"),
+        Error(UnknownEscapeSequence),
+    ),
+    TestVector::new(
+        "{v: \"a\\tb\"}",
+        Value("{v:\"a\\tb\"}"),
+        Error(UnknownEscapeSequence),
+    ),
+    TestVector::both("{v: \"A\"}", Value("{v:A}")),
+    TestVector::both("{v: \"é\"}", Value("{v:é}")),
+    TestVector::new(
+        "{v: \"\\u004\"}",
+        Error(UnexpectedToken),
+        Error(UnknownEscapeSequence),
+    ),
+    TestVector::new(
+        "{v: \"\\u004g\"}",
+        Error(UnexpectedToken),
+        Error(UnknownEscapeSequence),
+    ),
+    TestVector::both("{v: \"a\\qb\"}", Error(UnknownEscapeSequence)),
+    TestVector::both("{v: \"a}", Error(UnmatchedQuote)),
+    TestVector::both("{v: 'a}", Error(UnmatchedQuote)),
+    TestVector::both("{v: \"a'}", Error(UnmatchedQuote)),
+    TestVector::both("{v: \"{a: 1}\"}", Value("{v:\"{a: 1}\"}")),
+    TestVector::both("{v: \"[1, 2]\"}", Value("{v:\"[1, 2]\"}")),
+    TestVector::both("{v: \",\"}", Value("{v:\",\"}")),
+    TestVector::both("{v: \"123\"}", Value("{v:\"123\"}")),
+    TestVector::both("{v: '1b'}", Value("{v:\"1b\"}")),
+    // Lists
+    TestVector::both("{v: []}", Value("{v:[]}")),
+    TestVector::both("{v: [ ]}", Value("{v:[]}")),
+    TestVector::both("{v: [1]}", Value("{v:[1]}")),
+    TestVector::both("{v: [1, 2, 3]}", Value("{v:[1,2,3]}")),
+    TestVector::both("{v: [1b, 2b]}", Value("{v:[1B,2B]}")),
+    TestVector::both("{v: [1s, 2s]}", Value("{v:[1S,2S]}")),
+    TestVector::both("{v: [1L, 2L]}", Value("{v:[1L,2L]}")),
+    TestVector::both("{v: [1f, 2.5f]}", Value("{v:[1F,2.5F]}")),
+    TestVector::both("{v: [1.5, 2.5]}", Value("{v:[1.5D,2.5D]}")),
+    TestVector::both("{v: [1.5d, 2]}", Error(NonHomogenousList)),
+    TestVector::both("{v: [1, 2b]}", Error(NonHomogenousList)),
+    TestVector::both("{v: [1, 2L]}", Error(NonHomogenousList)),
+    TestVector::both("{v: [1, 1.5]}", Error(NonHomogenousList)),
+    TestVector::both("{v: [1, a]}", Error(NonHomogenousList)),
+    TestVector::both("{v: [a, b]}", Value("{v:[a,b]}")),
+    TestVector::both("{v: [a, 1]}", Error(NonHomogenousList)),
+    TestVector::both("{v: [\"a\", 'b']}", Value("{v:[a,b]}")),
+    TestVector::both("{v: [true, false]}", Value("{v:[1B,0B]}")),
+    TestVector::both("{v: [true, 1b]}", Value("{v:[1B,1B]}")),
+    TestVector::both("{v: [true, 1]}", Error(NonHomogenousList)),
+    TestVector::new(
+        "{v: [a b, c]}",
+        Value("{v:[a b,c]}"),
+        Error(UnexpectedToken),
+    ),
+    TestVector::new("{v: [1,]}", Error(UnexpectedToken), Value("{v:[1]}")),
+    TestVector::both("{v: [,1]}", Error(UnexpectedToken)),
+    TestVector::both("{v: [1,,2]}", Error(UnexpectedToken)),
+    TestVector::new("{v: [1 2]}", Error(InvalidNumber), Error(UnexpectedToken)),
+    TestVector::new("{v: [1}", Error(InvalidNumber), Error(UnexpectedToken)),
+    TestVector::new("{v: [1, 2}", Error(InvalidNumber), Error(UnexpectedToken)),
+    TestVector::both("{v: []]}", Error(UnexpectedToken)),
+    TestVector::both("{v: [[]]}", Value("{v:[[]]}")),
+    TestVector::both("{v: [[], []]}", Value("{v:[[],[]]}")),
+    TestVector::both("{v: [[1], [2]]}", Value("{v:[[1],[2]]}")),
+    TestVector::both("{v: [[1], [2b]]}", Value("{v:[[1],[2B]]}")),
+    TestVector::both("{v: [[1], []]}", Value("{v:[[1],[]]}")),
+    TestVector::both("{v: [[], [1]]}", Value("{v:[[],[1]]}")),
+    TestVector::both("{v: [[1], [a]]}", Value("{v:[[1],[a]]}")),
+    TestVector::both("{v: [{}]}", Value("{v:[{}]}")),
+    TestVector::both("{v: [{}, {}]}", Value("{v:[{},{}]}")),
+    TestVector::both("{v: [{a: 1}, {b: 2}]}", Value("{v:[{a:1},{b:2}]}")),
+    TestVector::both("{v: [{a: 1}, {a: 1b}]}", Value("{v:[{a:1},{a:1B}]}")),
+    TestVector::both("{v: [{a: 1}, 1]}", Error(NonHomogenousList)),
+    TestVector::both("{v: [[I; 1], [I; 2]]}", Value("{v:[[I;1],[I;2]]}")),
+    TestVector::both("{v: [[I; 1], [L; 2L]]}", Error(NonHomogenousList)),
+    TestVector::both("{v: [[B;], [I;]]}", Error(NonHomogenousList)),
+    TestVector::both("{v: [[1], [I; 1]]}", Error(NonHomogenousList)),
+    // Arrays
+    TestVector::both("{v: [I;]}", Value("{v:[I;]}")),
+    TestVector::both("{v: [B;]}", Value("{v:[B;]}")),
+    TestVector::both("{v: [L;]}", Value("{v:[L;]}")),
+    TestVector::both("{v: [I; ]}", Value("{v:[I;]}")),
+    TestVector::both("{v: [I; 1]}", Value("{v:[I;1]}")),
+    TestVector::both("{v: [I; 1, 2, 3]}", Value("{v:[I;1,2,3]}")),
+    TestVector::both("{v: [I; -1, +2]}", Value("{v:[I;-1,2]}")),
+    TestVector::new("{v: [i; 1]}", Value("{v:[I;1]}"), Error(UnexpectedToken)),
+    TestVector::both("{v: [B; 1b, 2b]}", Value("{v:[B;1,2]}")),
+    TestVector::new("{v: [b; 1b]}", Value("{v:[B;1]}"), Error(UnexpectedToken)),
+    TestVector::new(
+        "{v: [B; 1, 2]}",
+        Value("{v:[B;1,2]}"),
+        Error(NonHomogenousList),
+    ),
+    TestVector::new(
+        "{v: [B; 1b, 2]}",
+        Value("{v:[B;1,2]}"),
+        Error(NonHomogenousList),
+    ),
+    TestVector::new("{v: [B; 1s]}", Value("{v:[B;1]}"), Error(NonHomogenousList)),
+    TestVector::new(
+        "{v: [B; true]}",
+        Error(NonHomogenousList),
+        Value("{v:[B;1]}"),
+    ),
+    TestVector::both("{v: [L; 1L, 2L]}", Value("{v:[L;1,2]}")),
+    TestVector::new("{v: [l; 1l]}", Value("{v:[L;1]}"), Error(UnexpectedToken)),
+    TestVector::new(
+        "{v: [L; 1, 2]}",
+        Value("{v:[L;1,2]}"),
+        Error(NonHomogenousList),
+    ),
+    TestVector::new(
+        "{v: [L; 1L, 2]}",
+        Value("{v:[L;1,2]}"),
+        Error(NonHomogenousList),
+    ),
+    TestVector::new("{v: [I; 1b]}", Value("{v:[I;1]}"), Error(NonHomogenousList)),
+    TestVector::new("{v: [I; 1L]}", Value("{v:[I;1]}"), Error(NonHomogenousList)),
+    TestVector::both("{v: [I; 1.5]}", Error(NonHomogenousList)),
+    TestVector::both("{v: [I; a]}", Error(NonHomogenousList)),
+    TestVector::new("{v: [I; 1,]}", Error(TrailingComma), Value("{v:[I;1]}")),
+    TestVector::new("{v: [I; ,1]}", Value("{v:[I;1]}"), Error(UnexpectedToken)),
+    TestVector::new(
+        "{v: [I; 1 2]}",
+        Error(InvalidNumber),
+        Error(UnexpectedToken),
+    ),
+    TestVector::new("{v: [I; 1}", Error(InvalidNumber), Error(UnexpectedToken)),
+    TestVector::both("{v: [S; 1s]}", Error(UnexpectedToken)),
+    TestVector::both("{v: [F; 1f]}", Error(UnexpectedToken)),
+    TestVector::both("{v: [D; 1d]}", Error(UnexpectedToken)),
+    TestVector::both("{v: [X; 1]}", Error(UnexpectedToken)),
+    TestVector::both("{v: [\"I\"; 1]}", Error(UnexpectedToken)),
+    TestVector::both("{v: ['B'; 1b]}", Error(UnexpectedToken)),
+    TestVector::new("{v: [I 1]}", Value("{v:[I 1]}"), Error(UnexpectedToken)),
+    TestVector::both("{v: [I,1]}", Error(NonHomogenousList)),
+    TestVector::both("{v: [I;1,2]}", Value("{v:[I;1,2]}")),
+    TestVector::new(
+        "{v: [ I ; 1 , 2 ]}",
+        Value("{v:[I;1,2]}"),
+        Error(UnexpectedToken),
+    ),
+    TestVector::new(
+        "{v: [I; 2147483648]}",
+        Error(InvalidNumber),
+        Error(NonHomogenousList),
+    ),
+    TestVector::new(
+        "{v: [B; 128b]}",
+        Error(InvalidNumber),
+        Error(NonHomogenousList),
+    ),
+    TestVector::new(
+        "{v: [B; 300]}",
+        Error(InvalidNumber),
+        Error(NonHomogenousList),
+    ),
+    // Compounds
+    TestVector::both("{v: {}}", Value("{v:{}}")),
+    TestVector::both("{v: { }}", Value("{v:{}}")),
+    TestVector::both("{v: {a: 1}}", Value("{v:{a:1}}")),
+    TestVector::both("{v: {a: {b: {c: 1}}}}", Value("{v:{a:{b:{c:1}}}}")),
+    TestVector::both("{}", Value("{}")),
+    TestVector::both("{ }", Value("{}")),
+    TestVector::both("{a: 1}", Value("{a:1}")),
+    TestVector::new("{a: 1, b: 2}", Value("{a:1,b:2}"), Value("{b:2,a:1}")),
+    TestVector::both("{a: 1, a: 2}", Value("{a:2}")),
+    TestVector::both("{a: 1, a: b}", Value("{a:b}")),
+    TestVector::new("{a:1,b:2}", Value("{a:1,b:2}"), Value("{b:2,a:1}")),
+    TestVector::both("{ a : 1 , b : 2 }", Value("{b:2,a:1}")),
+    TestVector::new("{a: 1,}", Error(TrailingComma), Value("{a:1}")),
+    TestVector::new("{,a: 1}", Value("{a:1}"), Error(UnexpectedToken)),
+    TestVector::new("{a: 1,, b: 2}", Value("{a:1,b:2}"), Error(UnexpectedToken)),
+    TestVector::new("{a: 1 b: 2}", Error(InvalidNumber), Error(UnexpectedToken)),
+    TestVector::new("{a 1}", Error(UnexpectedEOS), Error(UnexpectedToken)),
+    TestVector::both("{a:}", Error(UnexpectedToken)),
+    TestVector::both("{a: }", Error(UnexpectedToken)),
+    TestVector::both("{:1}", Error(UnexpectedToken)),
+    TestVector::both("{a: 1", Error(UnmatchedBrace)),
+    TestVector::both("{\"a\": 1}", Value("{a:1}")),
+    TestVector::both("{'a': 1}", Value("{a:1}")),
+    TestVector::new("{\"\": 1}", Value("{\"\":1}"), Error(UnexpectedToken)),
+    TestVector::new("{'': 1}", Value("{\"\":1}"), Error(UnexpectedToken)),
+    TestVector::both("{\"a b\": 1}", Value("{a b:1}")),
+    TestVector::both("{\"a:b\": 1}", Value("{\"a:b\":1}")),
+    TestVector::new("{a b: 1}", Value("{a b:1}"), Error(UnexpectedToken)),
+    TestVector::new("{a:b: 1}", Value("{a:\"b: 1\"}"), Error(UnexpectedToken)),
+    TestVector::both("{a.b: 1}", Value("{a.b:1}")),
+    TestVector::both("{a-b: 1}", Value("{a-b:1}")),
+    TestVector::both("{a+b: 1}", Value("{a+b:1}")),
+    TestVector::both("{a_b: 1}", Value("{a_b:1}")),
+    TestVector::new("{1: 1}", Error(UnexpectedToken), Value("{\"1\":1}")),
+    TestVector::new("{1b: 1}", Error(UnexpectedToken), Value("{\"1b\":1}")),
+    TestVector::new("{-1: 1}", Error(UnexpectedToken), Value("{\"-1\":1}")),
+    TestVector::new("{1.5: 1}", Error(UnexpectedToken), Value("{\"1.5\":1}")),
+    TestVector::both("{true: 1}", Value("{\"true\":1}")),
+    TestVector::new("{é: 1}", Value("{é:1}"), Error(UnexpectedToken)),
+    TestVector::new("{@: 1}", Value("{@:1}"), Error(UnexpectedToken)),
+    TestVector::new("{a/b: 1}", Value("{a/b:1}"), Error(UnexpectedToken)),
+    TestVector::both("{[]: 1}", Error(UnexpectedToken)),
+    TestVector::both("{{}: 1}", Error(UnexpectedToken)),
+    TestVector::both("{\"a\\\"b\": 1}", Value("{'a\"b':1}")),
+    TestVector::both("{\"a}\": 1}", Value("{\"a}\":1}")),
+    TestVector::both("{a: \"}\"}", Value("{a:\"}\"}")),
+    TestVector::both("{a: '}'}", Value("{a:\"}\"}")),
+    TestVector::both("{a: \"]\"}", Value("{a:\"]\"}")),
+    TestVector::both("{a: b}", Value("{a:b}")),
+    TestVector::new("{a: b, c: d}", Value("{a:b,c:d}"), Value("{c:d,a:b}")),
+    TestVector::both("{a: \"b\", c: 'd'}", Value("{c:d,a:b}")),
+    // Document structure
+    TestVector::new("{a: 1}}", Value("{a:1}"), Error(UnexpectedToken)),
+    TestVector::both("{a: 1} ", Value("{a:1}")),
+    TestVector::both("{a: 1}\t", Value("{a:1}")),
+    TestVector::new("{a: 1}x", Value("{a:1}"), Error(UnexpectedToken)),
+    TestVector::new("{a: 1} {b: 2}", Value("{a:1}"), Error(UnexpectedToken)),
+    TestVector::new("{a: 1}}}", Value("{a:1}"), Error(UnexpectedToken)),
+    TestVector::both(" {a: 1}", Value("{a:1}")),
+    TestVector::both("\t{a: 1}", Value("{a:1}")),
+    TestVector::both("{", Error(UnmatchedBrace)),
+    TestVector::both("}", Error(UnexpectedToken)),
+    TestVector::both("{a:", Error(UnexpectedEOS)),
+    TestVector::both("{a: [", Error(UnexpectedEOS)),
+    TestVector::both("[]", Error(UnexpectedToken)),
+    TestVector::both("[1]", Error(UnexpectedToken)),
+    TestVector::both("1", Error(UnexpectedToken)),
+    TestVector::both("\"a\"", Error(UnexpectedToken)),
+    TestVector::both("a", Error(UnexpectedToken)),
+    TestVector::new("{a: 1]", Error(InvalidNumber), Error(UnexpectedToken)),
+    TestVector::new("{a: [1}]", Error(InvalidNumber), Error(UnexpectedToken)),
+    TestVector::both("{a: {b: 1}", Error(UnmatchedBrace)),
+    TestVector::new("{a: {b: 1]}", Error(InvalidNumber), Error(UnexpectedToken)),
+    TestVector::new("{a: {}}}", Value("{a:{}}"), Error(UnexpectedToken)),
+    TestVector::both("", Error(UnexpectedEOS)),
+    TestVector::both("{a:\n1}", Value("{a:1}")),
+    TestVector::both(
+        "{\n    a: 1,\n    b: [\n        1,\n        2\n    ]\n}",
+        Value("{a:1,b:[1,2]}"),
+    ),
+    TestVector::new("{a: 1,\n}", Error(TrailingComma), Value("{a:1}")),
+    TestVector::both("\n{a: 1}\n", Value("{a:1}")),
+    TestVector::both("{\"a\nb\": 1}", Value("{\"a\\nb\":1}")),
+];
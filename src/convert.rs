@@ -0,0 +1,207 @@
+use crate::{NbtStructureError, NbtTag, NbtTagKind};
+use std::borrow::Cow;
+
+/// A policy describing which conversions between tag kinds are permitted, and how values which do
+/// not fit in the target kind are handled.
+///
+/// Only numeric tags and numeric arrays can be converted between kinds. Under every policy, a tag
+/// which already has the target kind is returned as-is, and converting between non-numeric kinds
+/// results in a type mismatch error.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::convert::ConversionPolicy;
+///
+/// let tag = NbtTag::Int(300);
+///
+/// assert!(ConversionPolicy::Strict.convert(&tag, NbtTagKind::Long).is_err());
+/// assert_eq!(*ConversionPolicy::Widen.convert(&tag, NbtTagKind::Long).unwrap(), NbtTag::Long(300));
+/// assert!(ConversionPolicy::Widen.convert(&tag, NbtTagKind::Byte).is_err());
+/// assert_eq!(*ConversionPolicy::Saturate.convert(&tag, NbtTagKind::Byte).unwrap(), NbtTag::Byte(127));
+/// assert_eq!(*ConversionPolicy::Lossy.convert(&tag, NbtTagKind::Byte).unwrap(), NbtTag::Byte(44));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ConversionPolicy {
+    /// No conversions are performed; the tag must already have the target kind.
+    #[default]
+    Strict,
+    /// Only lossless widening conversions are performed, as described by
+    /// `NbtTag::`[`promote`](crate::NbtTag::promote).
+    Widen,
+    /// Any numeric conversion is performed, with out-of-range values clamped to the nearest value
+    /// representable by the target kind. Converting a NaN to an integral kind is an error.
+    Saturate,
+    /// Any numeric conversion is performed with the semantics of Rust's `as` casts, so integers
+    /// are truncated and floats are rounded towards zero and saturated.
+    Lossy,
+}
+
+impl ConversionPolicy {
+    /// Converts the given tag to the given kind according to this policy. If the tag already has
+    /// the given kind then it is returned without being cloned.
+    pub fn convert<'a>(
+        self,
+        tag: &'a NbtTag,
+        to: NbtTagKind,
+    ) -> Result<Cow<'a, NbtTag>, NbtStructureError> {
+        let from = tag.kind();
+        if from == to {
+            return Ok(Cow::Borrowed(tag));
+        }
+
+        let saturate = match self {
+            ConversionPolicy::Strict =>
+                return Err(NbtStructureError::type_mismatch(to.name(), from.name())),
+            ConversionPolicy::Widen => return tag.promote(to),
+            ConversionPolicy::Saturate => true,
+            ConversionPolicy::Lossy => false,
+        };
+
+        let converted = match *tag {
+            NbtTag::Byte(value) => convert_integral(i64::from(value), to, saturate),
+            NbtTag::Short(value) => convert_integral(i64::from(value), to, saturate),
+            NbtTag::Int(value) => convert_integral(i64::from(value), to, saturate),
+            NbtTag::Long(value) => convert_integral(value, to, saturate),
+            NbtTag::Float(value) => convert_float(f64::from(value), to, saturate),
+            NbtTag::Double(value) => convert_float(value, to, saturate),
+            NbtTag::ByteArray(ref array) => convert_array(array, to, saturate),
+            NbtTag::IntArray(ref array) => convert_array(array, to, saturate),
+            NbtTag::LongArray(ref array) => convert_array(array, to, saturate),
+            _ => None,
+        };
+
+        match converted {
+            Some(Ok(tag)) => Ok(Cow::Owned(tag)),
+            Some(Err(())) => Err(NbtStructureError::lossy_conversion(from.name(), to.name())),
+            None => Err(NbtStructureError::type_mismatch(to.name(), from.name())),
+        }
+    }
+
+    /// Converts the given tag to the type `T` according to this policy.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// use quartz_nbt::convert::ConversionPolicy;
+    ///
+    /// let tag = NbtTag::Double(2.75);
+    /// assert_eq!(ConversionPolicy::Lossy.convert_to::<i32>(&tag).unwrap(), 2);
+    /// assert_eq!(
+    ///     ConversionPolicy::Widen.convert_to::<Vec<i64>>(&NbtTag::IntArray(vec![1, 2])).unwrap(),
+    ///     vec![1i64, 2]
+    /// );
+    /// ```
+    pub fn convert_to<T: ConversionTarget>(self, tag: &NbtTag) -> Result<T, NbtStructureError> {
+        T::try_from(self.convert(tag, T::KIND)?.into_owned())
+    }
+}
+
+/// A type which a tag can be converted into through a [`ConversionPolicy`]. This trait is
+/// implemented for the numeric primitives and the array tag types.
+///
+/// [`ConversionPolicy`]: crate::convert::ConversionPolicy
+pub trait ConversionTarget: TryFrom<NbtTag, Error = NbtStructureError> + private::Sealed {
+    /// The kind of tag which wraps this type.
+    const KIND: NbtTagKind;
+}
+
+mod private {
+    pub trait Sealed {}
+}
+
+macro_rules! conversion_target {
+    ($($type:ty, $kind:ident);*) => {
+        $(
+            impl private::Sealed for $type {}
+
+            impl ConversionTarget for $type {
+                const KIND: NbtTagKind = NbtTagKind::$kind;
+            }
+        )*
+    };
+}
+
+conversion_target!(
+    i8, Byte;
+    i16, Short;
+    i32, Int;
+    i64, Long;
+    f32, Float;
+    f64, Double;
+    Vec<i8>, ByteArray;
+    Vec<i32>, IntArray;
+    Vec<i64>, LongArray
+);
+
+// Casts an i64 to a narrower integral type, either clamping or truncating the value
+macro_rules! cast_integral {
+    ($value:expr, $type:ty, $saturate:expr) => {
+        if $saturate {
+            $value.clamp(<$type>::MIN.into(), <$type>::MAX.into()) as $type
+        } else {
+            $value as $type
+        }
+    };
+}
+
+// The result of these functions is `None` if the target kind is not numeric (or, for arrays, not
+// an array), and `Some(Err(()))` if the value cannot be converted under the saturating policy
+
+fn convert_integral(value: i64, to: NbtTagKind, saturate: bool) -> Option<Result<NbtTag, ()>> {
+    let tag = match to {
+        NbtTagKind::Byte => NbtTag::Byte(cast_integral!(value, i8, saturate)),
+        NbtTagKind::Short => NbtTag::Short(cast_integral!(value, i16, saturate)),
+        NbtTagKind::Int => NbtTag::Int(cast_integral!(value, i32, saturate)),
+        NbtTagKind::Long => NbtTag::Long(value),
+        NbtTagKind::Float => NbtTag::Float(value as f32),
+        NbtTagKind::Double => NbtTag::Double(value as f64),
+        _ => return None,
+    };
+
+    Some(Ok(tag))
+}
+
+fn convert_float(value: f64, to: NbtTagKind, saturate: bool) -> Option<Result<NbtTag, ()>> {
+    let is_integral = matches!(
+        to,
+        NbtTagKind::Byte | NbtTagKind::Short | NbtTagKind::Int | NbtTagKind::Long
+    );
+    if saturate && is_integral && value.is_nan() {
+        return Some(Err(()));
+    }
+
+    // Float to int casts already saturate
+    let tag = match to {
+        NbtTagKind::Byte => NbtTag::Byte(value as i8),
+        NbtTagKind::Short => NbtTag::Short(value as i16),
+        NbtTagKind::Int => NbtTag::Int(value as i32),
+        NbtTagKind::Long => NbtTag::Long(value as i64),
+        NbtTagKind::Float if saturate && value.is_finite() =>
+            NbtTag::Float(value.clamp(f32::MIN.into(), f32::MAX.into()) as f32),
+        NbtTagKind::Float => NbtTag::Float(value as f32),
+        NbtTagKind::Double => NbtTag::Double(value),
+        _ => return None,
+    };
+
+    Some(Ok(tag))
+}
+
+fn convert_array<T>(array: &[T], to: NbtTagKind, saturate: bool) -> Option<Result<NbtTag, ()>>
+where T: Copy + Into<i64> {
+    let elements = array.iter().map(|&value| value.into());
+    let tag = match to {
+        NbtTagKind::ByteArray => NbtTag::ByteArray(
+            elements
+                .map(|value: i64| cast_integral!(value, i8, saturate))
+                .collect(),
+        ),
+        NbtTagKind::IntArray => NbtTag::IntArray(
+            elements
+                .map(|value: i64| cast_integral!(value, i32, saturate))
+                .collect(),
+        ),
+        NbtTagKind::LongArray => NbtTag::LongArray(elements.collect()),
+        _ => return None,
+    };
+
+    Some(Ok(tag))
+}
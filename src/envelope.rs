@@ -0,0 +1,301 @@
+use crate::{
+    io::{self, Flavor, NbtIoError},
+    NbtCompound,
+    NbtReprError,
+};
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    io::{Read, Write},
+    mem,
+    ops::RangeBounds,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The key of the header compound within an envelope.
+pub const HEADER_KEY: &str = "Envelope";
+/// The key of the payload compound within an envelope.
+pub const PAYLOAD_KEY: &str = "Data";
+
+const SCHEMA_KEY: &str = "Schema";
+const VERSION_KEY: &str = "Version";
+const CREATED_AT_KEY: &str = "CreatedAt";
+const TOOL_KEY: &str = "Tool";
+
+/// The metadata stored alongside a payload in an envelope: the schema the payload follows and its
+/// version, when the document was created, and which tool created it.
+///
+/// An envelope is a compound with two entries: the header under [`HEADER_KEY`], and the payload
+/// under [`PAYLOAD_KEY`]. The header has the following layout, and any other tags in it are
+/// ignored when reading so that later tools can record more metadata.
+///
+/// ```text
+/// {
+///     Envelope: {
+///         Schema: "myplugin:player_data",
+///         Version: 3,
+///         CreatedAt: 1719850123456L,
+///         Tool: "worldtool 1.4.2"
+///     },
+///     Data: { ... }
+/// }
+/// ```
+///
+/// # Example
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::envelope::{self, EnvelopeHeader};
+///
+/// let header = EnvelopeHeader::new("myplugin:player_data", 3).with_tool("worldtool 1.4.2");
+/// let document = header.wrap(compound! { "xp": 30i32 });
+///
+/// let (read_header, payload) = envelope::unwrap_checked(document, "myplugin:player_data", 1 ..= 3)?;
+/// assert_eq!(read_header, header);
+/// assert_eq!(payload, compound! { "xp": 30i32 });
+/// # Ok::<_, envelope::EnvelopeError>(())
+/// ```
+///
+/// [`HEADER_KEY`]: crate::envelope::HEADER_KEY
+/// [`PAYLOAD_KEY`]: crate::envelope::PAYLOAD_KEY
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EnvelopeHeader {
+    schema: String,
+    version: i32,
+    created_at: i64,
+    tool: String,
+}
+
+impl EnvelopeHeader {
+    /// Returns a header for a payload following the given version of the given schema, created
+    /// now. Schema IDs are best namespaced in the same way as Minecraft's resource locations, such
+    /// as `myplugin:player_data`.
+    pub fn new<S: Into<String>>(schema: S, version: i32) -> Self {
+        EnvelopeHeader {
+            schema: schema.into(),
+            version,
+            created_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_millis() as i64),
+            tool: String::new(),
+        }
+    }
+
+    /// Sets the time the document was created, in milliseconds since the Unix epoch, which is how
+    /// Minecraft stores times such as `LastPlayed`. This defaults to the time the header was
+    /// created.
+    pub fn with_created_at(mut self, millis: i64) -> Self {
+        self.created_at = millis;
+        self
+    }
+
+    /// Sets the name and version of the tool which created the document, such as
+    /// `worldtool 1.4.2`. This defaults to an empty string.
+    pub fn with_tool<S: Into<String>>(mut self, tool: S) -> Self {
+        self.tool = tool.into();
+        self
+    }
+
+    /// Returns the ID of the schema the payload follows.
+    pub fn schema(&self) -> &str {
+        &self.schema
+    }
+
+    /// Returns the version of the schema the payload follows.
+    pub fn version(&self) -> i32 {
+        self.version
+    }
+
+    /// Returns the time the document was created, in milliseconds since the Unix epoch.
+    pub fn created_at(&self) -> i64 {
+        self.created_at
+    }
+
+    /// Returns the name and version of the tool which created the document, which is empty if it
+    /// was not recorded.
+    pub fn tool(&self) -> &str {
+        &self.tool
+    }
+
+    /// Returns the header as a compound, as it is stored in an envelope.
+    pub fn to_compound(&self) -> NbtCompound {
+        let mut compound = NbtCompound::new();
+        compound.insert(SCHEMA_KEY, self.schema.as_str());
+        compound.insert(VERSION_KEY, self.version);
+        compound.insert(CREATED_AT_KEY, self.created_at);
+        compound.insert(TOOL_KEY, self.tool.as_str());
+        compound
+    }
+
+    /// Reads a header from the given compound, returning an error if any of its tags are missing
+    /// or have the wrong type. A missing `Tool` tag is read as an empty string.
+    pub fn from_compound(compound: &NbtCompound) -> Result<Self, NbtReprError> {
+        Ok(EnvelopeHeader {
+            schema: compound.get::<_, &str>(SCHEMA_KEY)?.to_owned(),
+            version: compound.get(VERSION_KEY)?,
+            created_at: compound.get(CREATED_AT_KEY)?,
+            tool: if compound.contains_key(TOOL_KEY) {
+                compound.get::<_, &str>(TOOL_KEY)?.to_owned()
+            } else {
+                String::new()
+            },
+        })
+    }
+
+    /// Wraps the given payload in an envelope with this header.
+    pub fn wrap(&self, payload: NbtCompound) -> NbtCompound {
+        let mut envelope = NbtCompound::new();
+        envelope.insert(HEADER_KEY, self.to_compound());
+        envelope.insert(PAYLOAD_KEY, payload);
+        envelope
+    }
+}
+
+/// Strips the envelope from the given document, returning its header and payload. The schema and
+/// version are not checked; see [`unwrap_checked`] to do so.
+///
+/// [`unwrap_checked`]: crate::envelope::unwrap_checked
+pub fn unwrap(mut document: NbtCompound) -> Result<(EnvelopeHeader, NbtCompound), EnvelopeError> {
+    let header = EnvelopeHeader::from_compound(document.get(HEADER_KEY)?)?;
+    let payload = mem::take(document.get_mut::<_, &mut NbtCompound>(PAYLOAD_KEY)?);
+    Ok((header, payload))
+}
+
+/// Strips the envelope from the given document, checking that its payload follows the given
+/// schema in one of the given versions. Migrating payloads of older versions is left to the
+/// caller, which can read the version from the returned header.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::envelope::{self, EnvelopeError, EnvelopeHeader};
+///
+/// let document = EnvelopeHeader::new("myplugin:player_data", 4).wrap(compound! {});
+/// assert!(matches!(
+///     envelope::unwrap_checked(document.clone(), "myplugin:player_data", ..= 3),
+///     Err(EnvelopeError::UnsupportedVersion(4))
+/// ));
+/// assert!(matches!(
+///     envelope::unwrap_checked(document, "otherplugin:player_data", ..),
+///     Err(EnvelopeError::SchemaMismatch { .. })
+/// ));
+/// ```
+pub fn unwrap_checked<V: RangeBounds<i32>>(
+    document: NbtCompound,
+    schema: &str,
+    versions: V,
+) -> Result<(EnvelopeHeader, NbtCompound), EnvelopeError> {
+    let (header, payload) = unwrap(document)?;
+    if header.schema != schema {
+        return Err(EnvelopeError::SchemaMismatch {
+            expected: schema.to_owned(),
+            found: header.schema,
+        });
+    }
+    if !versions.contains(&header.version) {
+        return Err(EnvelopeError::UnsupportedVersion(header.version));
+    }
+
+    Ok((header, payload))
+}
+
+/// Wraps the given payload in an envelope with the given header, and writes it to the given
+/// writer with [`write_nbt`].
+///
+/// [`write_nbt`]: crate::io::write_nbt
+pub fn write_enveloped<W: Write>(
+    writer: &mut W,
+    header: &EnvelopeHeader,
+    payload: NbtCompound,
+    flavor: Flavor,
+) -> Result<(), NbtIoError> {
+    io::write_nbt(writer, None, &header.wrap(payload), flavor)
+}
+
+/// Reads a document with [`read_nbt`] and strips its envelope with [`unwrap_checked`].
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::{envelope::{self, EnvelopeHeader}, io::Flavor};
+///
+/// let header = EnvelopeHeader::new("myplugin:player_data", 2);
+/// let mut bytes = Vec::new();
+/// envelope::write_enveloped(&mut bytes, &header, compound! { "xp": 30i32 }, Flavor::GzCompressed)?;
+///
+/// let (read_header, payload) = envelope::read_enveloped(
+///     &mut bytes.as_slice(),
+///     Flavor::GzCompressed,
+///     "myplugin:player_data",
+///     1 ..= 2,
+/// )?;
+/// assert_eq!(read_header.version(), 2);
+/// assert_eq!(payload, compound! { "xp": 30i32 });
+/// # Ok::<_, envelope::EnvelopeError>(())
+/// ```
+///
+/// [`read_nbt`]: crate::io::read_nbt
+/// [`unwrap_checked`]: crate::envelope::unwrap_checked
+pub fn read_enveloped<R: Read, V: RangeBounds<i32>>(
+    reader: &mut R,
+    flavor: Flavor,
+    schema: &str,
+    versions: V,
+) -> Result<(EnvelopeHeader, NbtCompound), EnvelopeError> {
+    let (document, _) = io::read_nbt(reader, flavor)?;
+    unwrap_checked(document, schema, versions)
+}
+
+/// An error which occurred while stripping the envelope from a document.
+#[derive(Debug)]
+pub enum EnvelopeError {
+    /// The document could not be read.
+    Io(NbtIoError),
+    /// The header or payload was missing, or one of their tags had the wrong type.
+    Malformed(NbtReprError),
+    /// The payload follows a different schema than the expected one.
+    SchemaMismatch {
+        /// The expected schema ID.
+        expected: String,
+        /// The schema ID recorded in the header.
+        found: String,
+    },
+    /// The payload follows the expected schema, but in the given unsupported version.
+    UnsupportedVersion(i32),
+}
+
+impl From<NbtIoError> for EnvelopeError {
+    fn from(error: NbtIoError) -> Self {
+        EnvelopeError::Io(error)
+    }
+}
+
+impl From<NbtReprError> for EnvelopeError {
+    fn from(error: NbtReprError) -> Self {
+        EnvelopeError::Malformed(error)
+    }
+}
+
+impl Display for EnvelopeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            EnvelopeError::Io(error) => write!(f, "{}", error),
+            EnvelopeError::Malformed(error) => write!(f, "Malformed envelope: {}", error),
+            EnvelopeError::SchemaMismatch { expected, found } => write!(
+                f,
+                "Expected a payload of schema {:?} but found {:?}",
+                expected, found
+            ),
+            EnvelopeError::UnsupportedVersion(version) =>
+                write!(f, "Unsupported schema version {}", version),
+        }
+    }
+}
+
+impl Error for EnvelopeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            EnvelopeError::Io(error) => Some(error),
+            EnvelopeError::Malformed(error) => Some(error),
+            _ => None,
+        }
+    }
+}
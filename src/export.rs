@@ -0,0 +1,180 @@
+use crate::{path::NbtPath, NbtCompound, NbtList, NbtTag};
+use std::{
+    collections::BTreeMap,
+    io::{self, Write},
+};
+
+/// Options controlling how compounds are written by [`to_csv`]. The default options write
+/// comma-separated values with a header row, and a column for every leaf found in any of the
+/// compounds.
+///
+/// [`to_csv`]: crate::export::to_csv
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvOptions {
+    pub(crate) delimiter: char,
+    pub(crate) header: bool,
+    pub(crate) columns: Option<Vec<NbtPath>>,
+}
+
+impl CsvOptions {
+    /// Returns the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the default options, but with fields separated by tabs rather than commas.
+    pub fn tsv() -> Self {
+        Self::new().delimiter('\t')
+    }
+
+    /// Sets the character separating the fields of each row. This defaults to `,`.
+    pub fn delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Sets whether the first row holds the path of each column. This defaults to `true`.
+    pub fn header(mut self, header: bool) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// Sets the paths selecting the value of each column from every compound, in order. By
+    /// default, the columns are the paths of every tag returned by [`NbtCompound::leaves`] for any
+    /// of the compounds, sorted by their string form.
+    ///
+    /// [`NbtCompound::leaves`]: crate::NbtCompound::leaves
+    pub fn columns<I>(mut self, columns: I) -> Self
+    where I: IntoIterator<Item = NbtPath> {
+        self.columns = Some(columns.into_iter().collect());
+        self
+    }
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            delimiter: ',',
+            header: true,
+            columns: None,
+        }
+    }
+}
+
+/// Writes the given compounds to the given writer as delimiter-separated values, one row per
+/// compound.
+///
+/// Each field holds the tags selected by its column's path from the row's compound. Numbers are
+/// written without their SNBT suffixes and strings are written as-is, whereas arrays, lists and
+/// compounds are written as SNBT. A field is left empty if the path selects no tags, and holds an
+/// SNBT list if it selects more than one. Fields containing the delimiter, quotes or line breaks
+/// are quoted, with quotes inside them doubled.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::{
+///     export::{self, CsvOptions},
+///     path::NbtPath,
+/// };
+///
+/// let players = [
+///     compound! { "Name": "Steve", "Health": 20.0f32, "Pos": [1.5f64, 64.0f64, -3.0f64] },
+///     compound! { "Name": "Alex, the second", "Health": 7.5f32 },
+/// ];
+/// let players = players.iter().collect::<Vec<_>>();
+///
+/// let mut csv = Vec::new();
+/// export::to_csv(&mut csv, &players, CsvOptions::new())?;
+/// assert_eq!(
+///     String::from_utf8(csv).unwrap(),
+///     "Health,Name,Pos[0],Pos[1],Pos[2]\n\
+///      20,Steve,1.5,64,-3\n\
+///      7.5,\"Alex, the second\",,,\n"
+/// );
+///
+/// let options = CsvOptions::tsv()
+///     .header(false)
+///     .columns([NbtPath::parse("Name")?, NbtPath::parse("Pos")?]);
+/// let mut tsv = Vec::new();
+/// export::to_csv(&mut tsv, &players, options)?;
+/// assert_eq!(
+///     String::from_utf8(tsv).unwrap(),
+///     "Steve\t[1.5D,64D,-3D]\nAlex, the second\t\n"
+/// );
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn to_csv<W: Write>(
+    writer: &mut W,
+    compounds: &[&NbtCompound],
+    options: CsvOptions,
+) -> io::Result<()> {
+    let columns = match options.columns {
+        Some(columns) => columns,
+        None => {
+            let mut columns = BTreeMap::new();
+            for (path, _) in compounds.iter().flat_map(|compound| compound.leaves()) {
+                columns.entry(path.to_string()).or_insert(path);
+            }
+            columns.into_values().collect()
+        }
+    };
+
+    let mut row = String::new();
+    if options.header {
+        for (index, column) in columns.iter().enumerate() {
+            push_field(&mut row, index, &column.to_string(), options.delimiter);
+        }
+        row.push('\n');
+        writer.write_all(row.as_bytes())?;
+    }
+
+    for compound in compounds {
+        row.clear();
+        for (index, column) in columns.iter().enumerate() {
+            let field = match column.select(compound).as_slice() {
+                [] => String::new(),
+                &[tag] => format_value(tag),
+                tags => NbtTag::List(tags.iter().copied().cloned().collect::<NbtList>()).to_snbt(),
+            };
+            push_field(&mut row, index, &field, options.delimiter);
+        }
+        row.push('\n');
+        writer.write_all(row.as_bytes())?;
+    }
+
+    Ok(())
+}
+
+fn format_value(tag: &NbtTag) -> String {
+    match tag {
+        NbtTag::Byte(value) => value.to_string(),
+        NbtTag::Short(value) => value.to_string(),
+        NbtTag::Int(value) => value.to_string(),
+        NbtTag::Long(value) => value.to_string(),
+        NbtTag::Float(value) => value.to_string(),
+        NbtTag::Double(value) => value.to_string(),
+        NbtTag::String(value) => value.clone(),
+        tag => tag.to_snbt(),
+    }
+}
+
+// Appends the given field to the row, preceded by the delimiter unless it is the first field
+fn push_field(row: &mut String, index: usize, field: &str, delimiter: char) {
+    if index > 0 {
+        row.push(delimiter);
+    }
+
+    if !field.contains([delimiter, '"', '\n', '\r']) {
+        row.push_str(field);
+        return;
+    }
+
+    row.push('"');
+    for ch in field.chars() {
+        if ch == '"' {
+            row.push('"');
+        }
+        row.push(ch);
+    }
+    row.push('"');
+}
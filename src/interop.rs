@@ -0,0 +1,472 @@
+#[cfg(any(feature = "serde_yaml", feature = "toml"))]
+use crate::NbtCompound;
+#[cfg(feature = "serde_yaml")]
+use crate::NbtTagKind;
+use crate::{NbtList, NbtStructureError, NbtTag};
+
+/// Converts the given tag to a YAML value.
+///
+/// Numeric tags become numbers, strings become strings, lists become sequences, and compounds
+/// become mappings. Since YAML has no typed arrays, byte, int and long arrays become sequences of
+/// numbers tagged with `!B`, `!I` and `!L` respectively, mirroring their SNBT prefixes, so that
+/// [`yaml_to_nbt`] can restore them.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::interop;
+///
+/// let compound = compound! { "motd": "Hello", "max-players": 20i32, "seed": [L; 1, 2] };
+/// let yaml = serde_yaml::to_string(&interop::nbt_to_yaml(&NbtTag::Compound(compound.clone())))
+///     .unwrap();
+/// assert!(yaml.contains("seed: !L\n- 1\n- 2\n"));
+///
+/// let value = serde_yaml::from_str(&yaml).unwrap();
+/// assert_eq!(interop::yaml_to_nbt(&value).unwrap(), NbtTag::Compound(compound));
+/// ```
+///
+/// [`yaml_to_nbt`]: crate::interop::yaml_to_nbt
+#[cfg(feature = "serde_yaml")]
+pub fn nbt_to_yaml(tag: &NbtTag) -> serde_yaml::Value {
+    use serde_yaml::{
+        value::{Tag, TaggedValue},
+        Mapping,
+        Value,
+    };
+
+    let tagged = |tag: &str, values: Vec<Value>| {
+        Value::Tagged(Box::new(TaggedValue {
+            tag: Tag::new(tag),
+            value: Value::Sequence(values),
+        }))
+    };
+
+    match tag {
+        NbtTag::Byte(value) => Value::from(*value),
+        NbtTag::Short(value) => Value::from(*value),
+        NbtTag::Int(value) => Value::from(*value),
+        NbtTag::Long(value) => Value::from(*value),
+        NbtTag::Float(value) => Value::from(f64::from(*value)),
+        NbtTag::Double(value) => Value::from(*value),
+        NbtTag::ByteArray(array) => tagged("B", array.iter().copied().map(Value::from).collect()),
+        NbtTag::String(string) => Value::String(string.clone()),
+        NbtTag::List(list) => Value::Sequence(list.iter().map(nbt_to_yaml).collect()),
+        NbtTag::Compound(compound) => Value::Mapping(
+            compound
+                .inner()
+                .iter()
+                .map(|(key, tag)| (Value::String(key.clone()), nbt_to_yaml(tag)))
+                .collect::<Mapping>(),
+        ),
+        NbtTag::IntArray(array) => tagged("I", array.iter().copied().map(Value::from).collect()),
+        NbtTag::LongArray(array) => tagged("L", array.iter().copied().map(Value::from).collect()),
+    }
+}
+
+/// Converts the given YAML value to a tag, reversing the conversion performed by
+/// [`nbt_to_yaml`].
+///
+/// The value is converted as follows:
+///  - Integers become int tags if they fit in an `i32`, and long tags otherwise. Integers outside
+///    the range of an `i64` cannot be represented and cause an error.
+///  - Floating point numbers become double tags, and booleans become byte tags holding `0` or
+///    `1`.
+///  - Sequences become lists. Since lists must be homogenous, sequences mixing different kinds
+///    of numbers are widened as with [`NbtList::normalize_numeric`], and any other mix of kinds
+///    causes an error.
+///  - Mappings become compounds. Keys which are numbers or booleans are converted to strings,
+///    whereas any other kind of key causes an error.
+///  - Sequences of integers tagged with `!B`, `!I` or `!L` become byte, int or long arrays, with
+///    an error if any element is out of range. The values of other tags are converted as if they
+///    were untagged.
+///  - Null entries in sequences and mappings are skipped. A null value anywhere else causes an
+///    error.
+///
+/// [`nbt_to_yaml`]: crate::interop::nbt_to_yaml
+/// [`NbtList::normalize_numeric`]: crate::NbtList::normalize_numeric
+#[cfg(feature = "serde_yaml")]
+pub fn yaml_to_nbt(value: &serde_yaml::Value) -> Result<NbtTag, NbtStructureError> {
+    use serde_yaml::Value;
+
+    fn kind_name(value: &Value) -> &'static str {
+        match value {
+            Value::Null => "null",
+            Value::Bool(_) => "boolean",
+            Value::Number(_) => "number",
+            Value::String(_) => "string",
+            Value::Sequence(_) => "sequence",
+            Value::Mapping(_) => "mapping",
+            Value::Tagged(_) => "tagged value",
+        }
+    }
+
+    // Converts the elements of a tagged sequence to the given integer type
+    fn array<T: TryFrom<i64>>(
+        value: &Value,
+        kind: NbtTagKind,
+    ) -> Result<Vec<T>, NbtStructureError> {
+        let sequence = match value {
+            Value::Sequence(sequence) => sequence,
+            value =>
+                return Err(NbtStructureError::type_mismatch(
+                    "sequence",
+                    kind_name(value),
+                )),
+        };
+
+        sequence
+            .iter()
+            .map(|element| match element.as_i64() {
+                Some(element) => T::try_from(element)
+                    .map_err(|_| NbtStructureError::lossy_conversion("Long", kind.name())),
+                None => Err(NbtStructureError::type_mismatch(
+                    "integer",
+                    kind_name(element),
+                )),
+            })
+            .collect()
+    }
+
+    match value {
+        Value::Null => Err(NbtStructureError::type_mismatch("tag", "null")),
+        Value::Bool(value) => Ok(NbtTag::Byte(*value as i8)),
+        Value::Number(number) =>
+            if let Some(value) = number.as_i64() {
+                Ok(integer_to_nbt(value))
+            } else if number.is_u64() {
+                Err(NbtStructureError::lossy_conversion("u64", "Long"))
+            } else {
+                Ok(NbtTag::Double(number.as_f64().unwrap_or(f64::NAN)))
+            },
+        Value::String(string) => Ok(NbtTag::String(string.clone())),
+        Value::Sequence(sequence) => list_to_nbt(
+            sequence
+                .iter()
+                .filter(|element| !element.is_null())
+                .map(yaml_to_nbt),
+        ),
+        Value::Mapping(mapping) => {
+            let mut compound = NbtCompound::new();
+            for (key, value) in mapping {
+                if value.is_null() {
+                    continue;
+                }
+
+                let key = match key {
+                    Value::String(key) => key.clone(),
+                    Value::Number(key) => key.to_string(),
+                    Value::Bool(key) => key.to_string(),
+                    key => return Err(NbtStructureError::type_mismatch("string", kind_name(key))),
+                };
+                compound.insert(key, yaml_to_nbt(value)?);
+            }
+            Ok(NbtTag::Compound(compound))
+        }
+        Value::Tagged(tagged) =>
+            if tagged.tag == "B" {
+                Ok(NbtTag::ByteArray(array(
+                    &tagged.value,
+                    NbtTagKind::ByteArray,
+                )?))
+            } else if tagged.tag == "I" {
+                Ok(NbtTag::IntArray(array(
+                    &tagged.value,
+                    NbtTagKind::IntArray,
+                )?))
+            } else if tagged.tag == "L" {
+                Ok(NbtTag::LongArray(array(
+                    &tagged.value,
+                    NbtTagKind::LongArray,
+                )?))
+            } else {
+                yaml_to_nbt(&tagged.value)
+            },
+    }
+}
+
+/// Converts the given tag to a TOML value.
+///
+/// Numeric tags become integers or floats, strings become strings, lists become arrays, and
+/// compounds become tables. TOML has no typed arrays, so byte, int and long arrays become arrays
+/// of integers, which [`toml_to_nbt`] converts back into lists rather than arrays.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::interop;
+///
+/// let compound = compound! { "motd": "Hello", "max-players": 20i32, "ports": [25565i32, 25566i32] };
+/// let toml = toml::to_string(&interop::nbt_to_toml(&NbtTag::Compound(compound.clone()))).unwrap();
+/// assert!(toml.contains("max-players = 20\n"));
+///
+/// let value = toml::from_str::<toml::Value>(&toml).unwrap();
+/// assert_eq!(interop::toml_to_nbt(&value).unwrap(), NbtTag::Compound(compound));
+/// ```
+///
+/// [`toml_to_nbt`]: crate::interop::toml_to_nbt
+#[cfg(feature = "toml")]
+pub fn nbt_to_toml(tag: &NbtTag) -> toml::Value {
+    use toml::Value;
+
+    match tag {
+        NbtTag::Byte(value) => Value::Integer(i64::from(*value)),
+        NbtTag::Short(value) => Value::Integer(i64::from(*value)),
+        NbtTag::Int(value) => Value::Integer(i64::from(*value)),
+        NbtTag::Long(value) => Value::Integer(*value),
+        NbtTag::Float(value) => Value::Float(f64::from(*value)),
+        NbtTag::Double(value) => Value::Float(*value),
+        NbtTag::ByteArray(array) => Value::Array(
+            array
+                .iter()
+                .map(|&value| Value::Integer(value.into()))
+                .collect(),
+        ),
+        NbtTag::String(string) => Value::String(string.clone()),
+        NbtTag::List(list) => Value::Array(list.iter().map(nbt_to_toml).collect()),
+        NbtTag::Compound(compound) => Value::Table(
+            compound
+                .inner()
+                .iter()
+                .map(|(key, tag)| (key.clone(), nbt_to_toml(tag)))
+                .collect(),
+        ),
+        NbtTag::IntArray(array) => Value::Array(
+            array
+                .iter()
+                .map(|&value| Value::Integer(value.into()))
+                .collect(),
+        ),
+        NbtTag::LongArray(array) =>
+            Value::Array(array.iter().map(|&value| Value::Integer(value)).collect()),
+    }
+}
+
+/// Converts the given TOML value to a tag, reversing the conversion performed by
+/// [`nbt_to_toml`].
+///
+/// Integers become int tags if they fit in an `i32`, and long tags otherwise. Floats become double
+/// tags, booleans become byte tags holding `0` or `1`, and datetimes become strings in their
+/// RFC 3339 form. Tables become compounds and arrays become lists. Since lists must be
+/// homogenous, arrays mixing different kinds of numbers are widened as with
+/// [`NbtList::normalize_numeric`], and any other mix of kinds causes an error.
+///
+/// [`nbt_to_toml`]: crate::interop::nbt_to_toml
+/// [`NbtList::normalize_numeric`]: crate::NbtList::normalize_numeric
+#[cfg(feature = "toml")]
+pub fn toml_to_nbt(value: &toml::Value) -> Result<NbtTag, NbtStructureError> {
+    use toml::Value;
+
+    match value {
+        Value::String(string) => Ok(NbtTag::String(string.clone())),
+        Value::Integer(value) => Ok(integer_to_nbt(*value)),
+        Value::Float(value) => Ok(NbtTag::Double(*value)),
+        Value::Boolean(value) => Ok(NbtTag::Byte(*value as i8)),
+        Value::Datetime(datetime) => Ok(NbtTag::String(datetime.to_string())),
+        Value::Array(array) => list_to_nbt(array.iter().map(toml_to_nbt)),
+        Value::Table(table) => {
+            let mut compound = NbtCompound::new();
+            for (key, value) in table {
+                compound.insert(key.clone(), toml_to_nbt(value)?);
+            }
+            Ok(NbtTag::Compound(compound))
+        }
+    }
+}
+
+#[cfg(any(feature = "serde_yaml", feature = "toml"))]
+fn integer_to_nbt(value: i64) -> NbtTag {
+    match i32::try_from(value) {
+        Ok(value) => NbtTag::Int(value),
+        Err(_) => NbtTag::Long(value),
+    }
+}
+
+#[cfg(any(feature = "serde_yaml", feature = "toml"))]
+fn list_to_nbt<I>(elements: I) -> Result<NbtTag, NbtStructureError>
+where I: Iterator<Item = Result<NbtTag, NbtStructureError>> {
+    let mut list = elements.collect::<Result<NbtList, _>>()?;
+    list.normalize_numeric()?;
+    Ok(NbtTag::List(list))
+}
+
+/// Converts the given tag to a hematite-nbt value. Every tag has a direct equivalent, although the
+/// element kind hint of an empty list is lost.
+///
+/// This conversion is only available when the `hematite-nbt` feature is enabled.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// let value = nbt::Value::from(NbtTag::List(NbtList::clone_from(&vec![1i32, 2])));
+/// assert_eq!(value, nbt::Value::List(vec![nbt::Value::Int(1), nbt::Value::Int(2)]));
+/// ```
+#[cfg(feature = "hematite-nbt")]
+impl From<NbtTag> for nbt::Value {
+    fn from(tag: NbtTag) -> Self {
+        use nbt::Value;
+
+        match tag {
+            NbtTag::Byte(value) => Value::Byte(value),
+            NbtTag::Short(value) => Value::Short(value),
+            NbtTag::Int(value) => Value::Int(value),
+            NbtTag::Long(value) => Value::Long(value),
+            NbtTag::Float(value) => Value::Float(value),
+            NbtTag::Double(value) => Value::Double(value),
+            NbtTag::ByteArray(value) => Value::ByteArray(value),
+            NbtTag::String(value) => Value::String(value),
+            NbtTag::List(list) => Value::List(list.into_iter().map(Value::from).collect()),
+            NbtTag::Compound(compound) => Value::Compound(
+                compound
+                    .into_iter()
+                    .map(|(key, tag)| (key, Value::from(tag)))
+                    .collect(),
+            ),
+            NbtTag::IntArray(value) => Value::IntArray(value),
+            NbtTag::LongArray(value) => Value::LongArray(value),
+        }
+    }
+}
+
+/// Converts the given hematite-nbt value to a tag. Since hematite-nbt does not check that the
+/// elements of a list all have the same type, an error is returned if a list mixes types, as such
+/// a list cannot be written.
+///
+/// This conversion is only available when the `hematite-nbt` feature is enabled.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use std::convert::TryFrom;
+///
+/// let value = nbt::Value::List(vec![nbt::Value::Int(1), nbt::Value::Int(2)]);
+/// assert_eq!(
+///     NbtTag::try_from(value).unwrap(),
+///     NbtTag::List(NbtList::clone_from(&vec![1i32, 2]))
+/// );
+///
+/// let value = nbt::Value::List(vec![nbt::Value::Int(1), nbt::Value::Long(2)]);
+/// assert!(NbtTag::try_from(value).is_err());
+/// ```
+#[cfg(feature = "hematite-nbt")]
+impl TryFrom<nbt::Value> for NbtTag {
+    type Error = NbtStructureError;
+
+    fn try_from(value: nbt::Value) -> Result<Self, Self::Error> {
+        use nbt::Value;
+
+        Ok(match value {
+            Value::Byte(value) => NbtTag::Byte(value),
+            Value::Short(value) => NbtTag::Short(value),
+            Value::Int(value) => NbtTag::Int(value),
+            Value::Long(value) => NbtTag::Long(value),
+            Value::Float(value) => NbtTag::Float(value),
+            Value::Double(value) => NbtTag::Double(value),
+            Value::ByteArray(value) => NbtTag::ByteArray(value),
+            Value::String(value) => NbtTag::String(value),
+            Value::List(values) =>
+                NbtTag::List(homogeneous_list(values.into_iter().map(NbtTag::try_from))?),
+            Value::Compound(map) => NbtTag::Compound(
+                map.into_iter()
+                    .map(|(key, value)| Ok((key, NbtTag::try_from(value)?)))
+                    .collect::<Result<_, NbtStructureError>>()?,
+            ),
+            Value::IntArray(value) => NbtTag::IntArray(value),
+            Value::LongArray(value) => NbtTag::LongArray(value),
+        })
+    }
+}
+
+/// Converts the given tag to a fastnbt value. Every tag has a direct equivalent, although the
+/// element kind hint of an empty list is lost.
+///
+/// This conversion is only available when the `fastnbt` feature is enabled.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// let value = fastnbt::Value::from(NbtTag::IntArray(vec![1, 2]));
+/// assert_eq!(value, fastnbt::Value::IntArray(fastnbt::IntArray::new(vec![1, 2])));
+/// ```
+#[cfg(feature = "fastnbt")]
+impl From<NbtTag> for fastnbt::Value {
+    fn from(tag: NbtTag) -> Self {
+        use fastnbt::{ByteArray, IntArray, LongArray, Value};
+
+        match tag {
+            NbtTag::Byte(value) => Value::Byte(value),
+            NbtTag::Short(value) => Value::Short(value),
+            NbtTag::Int(value) => Value::Int(value),
+            NbtTag::Long(value) => Value::Long(value),
+            NbtTag::Float(value) => Value::Float(value),
+            NbtTag::Double(value) => Value::Double(value),
+            NbtTag::ByteArray(value) => Value::ByteArray(ByteArray::new(value)),
+            NbtTag::String(value) => Value::String(value),
+            NbtTag::List(list) => Value::List(list.into_iter().map(Value::from).collect()),
+            NbtTag::Compound(compound) => Value::Compound(
+                compound
+                    .into_iter()
+                    .map(|(key, tag)| (key, Value::from(tag)))
+                    .collect(),
+            ),
+            NbtTag::IntArray(value) => Value::IntArray(IntArray::new(value)),
+            NbtTag::LongArray(value) => Value::LongArray(LongArray::new(value)),
+        }
+    }
+}
+
+/// Converts the given fastnbt value to a tag. Since fastnbt does not check that the elements of a
+/// list all have the same type, an error is returned if a list mixes types, as such a list cannot
+/// be written.
+///
+/// This conversion is only available when the `fastnbt` feature is enabled.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use std::convert::TryFrom;
+///
+/// let value = fastnbt::Value::LongArray(fastnbt::LongArray::new(vec![1, 2]));
+/// assert_eq!(NbtTag::try_from(value).unwrap(), NbtTag::LongArray(vec![1, 2]));
+///
+/// let value = fastnbt::Value::List(vec![fastnbt::Value::Byte(1), fastnbt::Value::Short(2)]);
+/// assert!(NbtTag::try_from(value).is_err());
+/// ```
+#[cfg(feature = "fastnbt")]
+impl TryFrom<fastnbt::Value> for NbtTag {
+    type Error = NbtStructureError;
+
+    fn try_from(value: fastnbt::Value) -> Result<Self, Self::Error> {
+        use fastnbt::Value;
+
+        Ok(match value {
+            Value::Byte(value) => NbtTag::Byte(value),
+            Value::Short(value) => NbtTag::Short(value),
+            Value::Int(value) => NbtTag::Int(value),
+            Value::Long(value) => NbtTag::Long(value),
+            Value::Float(value) => NbtTag::Float(value),
+            Value::Double(value) => NbtTag::Double(value),
+            Value::ByteArray(value) => NbtTag::ByteArray(value.into_inner()),
+            Value::String(value) => NbtTag::String(value),
+            Value::List(values) =>
+                NbtTag::List(homogeneous_list(values.into_iter().map(NbtTag::try_from))?),
+            Value::Compound(map) => NbtTag::Compound(
+                map.into_iter()
+                    .map(|(key, value)| Ok((key, NbtTag::try_from(value)?)))
+                    .collect::<Result<_, NbtStructureError>>()?,
+            ),
+            Value::IntArray(value) => NbtTag::IntArray(value.into_inner()),
+            Value::LongArray(value) => NbtTag::LongArray(value.into_inner()),
+        })
+    }
+}
+
+// Unlike list_to_nbt, numbers are not promoted, since both sides have the same numeric types and
+// a mixed list is a bug on the other side rather than an artifact of a looser format
+#[cfg(any(feature = "hematite-nbt", feature = "fastnbt"))]
+fn homogeneous_list<I>(elements: I) -> Result<NbtList, NbtStructureError>
+where I: Iterator<Item = Result<NbtTag, NbtStructureError>> {
+    let list = elements.collect::<Result<NbtList, _>>()?;
+    if let Some(kind) = list.element_kind() {
+        if let Some(tag) = list.iter().find(|tag| tag.kind() != kind) {
+            return Err(NbtStructureError::type_mismatch(
+                kind.name(),
+                tag.tag_name(),
+            ));
+        }
+    }
+    Ok(list)
+}
@@ -0,0 +1,58 @@
+use super::{read_nbt, write_nbt, Flavor, NbtIoError};
+use crate::NbtCompound;
+use std::io::Write;
+
+/// Reads the given flavor of NBT data from a base64 string, returning the resulting NBT compound
+/// and associated root name. Leading and trailing whitespace in the string is ignored.
+///
+/// Many plugin APIs embed NBT in JSON or YAML by Gz compressing it and then encoding it with the
+/// standard base64 alphabet; such strings are read with [`Flavor::GzCompressed`]. The string is
+/// decoded as it is read, so the binary data is never buffered in full.
+///
+/// This function is only available when the `base64` feature is enabled.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::io::{self, Flavor, NbtIoError};
+///
+/// let item = compound! { "id": "minecraft:diamond_sword", "Count": 1i8 };
+///
+/// let mut encoded = Vec::new();
+/// io::write_nbt_base64(&mut encoded, None, &item, Flavor::GzCompressed)?;
+/// let encoded = String::from_utf8(encoded).unwrap();
+///
+/// let (decoded, _) = io::read_nbt_base64(&encoded, Flavor::GzCompressed)?;
+/// assert_eq!(decoded, item);
+/// assert!(io::read_nbt_base64("not base64!", Flavor::GzCompressed).is_err());
+/// # Ok::<(), NbtIoError>(())
+/// ```
+///
+/// [`Flavor::GzCompressed`]: crate::io::Flavor::GzCompressed
+pub fn read_nbt_base64(encoded: &str, flavor: Flavor) -> Result<(NbtCompound, String), NbtIoError> {
+    let mut decoder = base64::read::DecoderReader::new(
+        encoded.trim().as_bytes(),
+        &base64::engine::general_purpose::STANDARD,
+    );
+    read_nbt(&mut decoder, flavor)
+}
+
+/// Writes the given flavor of NBT data to the given writer as a base64 string, using the standard
+/// alphabet with padding. If no root name is provided, an empty string is used. The data is encoded
+/// as it is written, so the binary data is never buffered in full.
+///
+/// See [`read_nbt_base64`] for an example. This function is only available when the `base64`
+/// feature is enabled.
+///
+/// [`read_nbt_base64`]: crate::io::read_nbt_base64
+pub fn write_nbt_base64<W: Write>(
+    writer: &mut W,
+    root_name: Option<&str>,
+    root: &NbtCompound,
+    flavor: Flavor,
+) -> Result<(), NbtIoError> {
+    let mut encoder =
+        base64::write::EncoderWriter::new(writer, &base64::engine::general_purpose::STANDARD);
+    write_nbt(&mut encoder, root_name, root, flavor)?;
+    encoder.finish()?;
+    Ok(())
+}
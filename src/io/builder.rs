@@ -0,0 +1,847 @@
+use super::{
+    auto_compression_level,
+    buffered,
+    detect_flavor,
+    read_document,
+    read_frame,
+    with_encoder,
+    write_nbt_uncompressed,
+    Decoder,
+    DocumentReader,
+    DuplicateKeyPolicy,
+    Flavor,
+    KeyRules,
+    KeyTransform,
+    LengthPrefix,
+    LengthRepair,
+    NbtIoError,
+    NbtSpans,
+    NegativeLengthPolicy,
+    PartialReadError,
+    PeekInfo,
+    RenamedKey,
+    TagExtension,
+    UnknownListType,
+    MAX_DEPTH,
+    RECOVERY_BUDGET_FACTOR,
+};
+#[cfg(feature = "zlib-dictionary")]
+use super::{read_to_stream_end, DictionaryDecoder, Encoder};
+use crate::{NbtCompound, NbtTag};
+use flate2::Compression;
+use std::{
+    fmt,
+    fmt::{Debug, Formatter},
+    io::{BufRead, Read, Write},
+};
+
+/// A builder for reading binary NBT data with options beyond those of [`read_nbt`], which is
+/// equivalent to a builder with only the flavor set.
+///
+/// A builder holds no state besides its options, so it can be kept around and used to read any
+/// number of documents.
+///
+/// Only the big-endian encoding of Java Edition is supported. The little-endian encoding used by
+/// Bedrock Edition is out of scope for this crate.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::io::{self, Flavor, KeyRules, NbtIoError, NbtReadBuilder, NbtWriteBuilder};
+///
+/// let nbt = compound! { "Items": [{ "id": "minecraft:stone", "Count": 1i8 }] };
+/// let bytes = NbtWriteBuilder::new()
+///     .flavor(Flavor::GzCompressed)
+///     .root_name("Player")
+///     .write_to_bytes(&nbt)?;
+///
+/// // The flavor is detected unless it is set
+/// let reader = NbtReadBuilder::new().key_rules(KeyRules::strict());
+/// assert_eq!(reader.read_from(&mut bytes.as_slice())?, (nbt.clone(), "Player".to_owned()));
+///
+/// // The root compound, the list, and the compound within it make three levels
+/// let shallow = reader.max_depth(2);
+/// assert!(matches!(
+///     shallow.read_from(&mut bytes.as_slice()),
+///     Err(NbtIoError::ExceededMaxDepth)
+/// ));
+/// # Ok::<(), NbtIoError>(())
+/// ```
+///
+/// [`read_nbt`]: crate::io::read_nbt
+#[derive(Clone, Copy)]
+pub struct NbtReadBuilder<'a> {
+    pub(crate) flavor: Option<Flavor>,
+    pub(crate) nameless_root: bool,
+    pub(crate) max_depth: usize,
+    pub(crate) latin1_fallback: bool,
+    pub(crate) key_rules: KeyRules,
+    pub(crate) key_transform: Option<&'a dyn KeyTransform>,
+    pub(crate) duplicate_keys: DuplicateKeyPolicy,
+    pub(crate) unknown_list_types: UnknownListType,
+    pub(crate) negative_lengths: NegativeLengthPolicy,
+    pub(crate) spans: bool,
+    pub(crate) partial: bool,
+    #[cfg(feature = "zlib-dictionary")]
+    pub(crate) dictionary: Option<&'a [u8]>,
+}
+
+impl<'a> NbtReadBuilder<'a> {
+    /// Returns a builder with the default options, which detects the flavor of the data and
+    /// otherwise reads it just like [`read_nbt`].
+    ///
+    /// [`read_nbt`]: crate::io::read_nbt
+    pub fn new() -> Self {
+        NbtReadBuilder {
+            flavor: None,
+            nameless_root: false,
+            max_depth: MAX_DEPTH,
+            latin1_fallback: false,
+            key_rules: KeyRules::new(),
+            key_transform: None,
+            duplicate_keys: DuplicateKeyPolicy::KeepLast,
+            unknown_list_types: UnknownListType::Error,
+            negative_lengths: NegativeLengthPolicy::Error,
+            spans: false,
+            partial: false,
+            #[cfg(feature = "zlib-dictionary")]
+            dictionary: None,
+        }
+    }
+
+    /// Sets the flavor of the data. By default, the flavor is detected from the first two bytes of
+    /// the data in the same way as [`read_nbt_with_info`].
+    ///
+    /// [`read_nbt_with_info`]: crate::io::read_nbt_with_info
+    pub fn flavor(mut self, flavor: Flavor) -> Self {
+        self.flavor = Some(flavor);
+        self
+    }
+
+    /// Sets whether the root compound is written without a name, as in the NBT sent over the
+    /// network since Minecraft 1.20.2. The root name is then read as an empty string. This
+    /// defaults to `false`.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// use quartz_nbt::io::{NbtIoError, NbtReadBuilder};
+    ///
+    /// let bytes = [0x0A, 0x01, 0, 1, b'x', 7, 0];
+    /// let (nbt, root_name) = NbtReadBuilder::new().nameless_root(true).read_from(&mut &bytes[..])?;
+    /// assert_eq!(nbt, compound! { "x": 7i8 });
+    /// assert!(root_name.is_empty());
+    /// # Ok::<(), NbtIoError>(())
+    /// ```
+    pub fn nameless_root(mut self, nameless: bool) -> Self {
+        self.nameless_root = nameless;
+        self
+    }
+
+    /// Sets the maximum number of lists and compounds, including the root compound, which may be
+    /// nested within each other, after which reading fails with [`NbtIoError::ExceededMaxDepth`].
+    /// This defaults to, and cannot be raised above, [`MAX_DEPTH`].
+    ///
+    /// [`NbtIoError::ExceededMaxDepth`]: crate::io::NbtIoError::ExceededMaxDepth
+    /// [`MAX_DEPTH`]: crate::io::MAX_DEPTH
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth.min(MAX_DEPTH);
+        self
+    }
+
+    /// Sets whether strings which are not valid Java CESU-8 are decoded as Latin-1 rather than
+    /// failing with [`NbtIoError::InvalidCesu8String`]. This defaults to `false`. The paths of the
+    /// strings which needed the fallback are listed by [`ReadReport::latin1_fallbacks`].
+    ///
+    /// [`NbtIoError::InvalidCesu8String`]: crate::io::NbtIoError::InvalidCesu8String
+    /// [`ReadReport::latin1_fallbacks`]: crate::io::ReadReport::latin1_fallbacks
+    pub fn latin1_fallback(mut self, fallback: bool) -> Self {
+        self.latin1_fallback = fallback;
+        self
+    }
+
+    /// Sets the rules every key in the data is checked against. This defaults to
+    /// [`KeyRules::new`], which accepts every key.
+    ///
+    /// [`KeyRules::new`]: crate::io::KeyRules::new
+    pub fn key_rules(mut self, rules: KeyRules) -> Self {
+        self.key_rules = rules;
+        self
+    }
+
+    /// Sets the transform the key of every compound entry is passed through as it is read. If the
+    /// transform maps several keys of a compound to the same key, they are handled by the
+    /// [`duplicate_keys`] policy, just as duplicate keys in the data. The root name is not
+    /// transformed, and the key rules are checked against the transformed keys. This defaults to no
+    /// transform.
+    ///
+    /// The keys which were changed are listed by [`ReadReport::renamed_keys`].
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// use quartz_nbt::io::{self, Flavor, NbtIoError, NbtReadBuilder};
+    ///
+    /// let nbt = compound! { "Items": [{ "Slot": 0i8 }] };
+    /// let bytes = io::write_nbt_bytes(None, &nbt, Flavor::GzCompressed)?;
+    ///
+    /// let lowercase = |key: &str| Some(key.to_lowercase());
+    /// let reader = NbtReadBuilder::new().key_transform(&lowercase);
+    /// let (read, _, report) = reader.read_from_with_report(&mut bytes.as_slice())?;
+    /// assert_eq!(read, compound! { "items": [{ "slot": 0i8 }] });
+    /// assert_eq!(report.renamed_keys().len(), 2);
+    ///
+    /// // The transform applies within the configured depth limit
+    /// assert!(reader.max_depth(2).read_from(&mut bytes.as_slice()).is_err());
+    /// # Ok::<(), NbtIoError>(())
+    /// ```
+    ///
+    /// [`duplicate_keys`]: crate::io::NbtReadBuilder::duplicate_keys
+    /// [`ReadReport::renamed_keys`]: crate::io::ReadReport::renamed_keys
+    pub fn key_transform(mut self, transform: &'a dyn KeyTransform) -> Self {
+        self.key_transform = Some(transform);
+        self
+    }
+
+    /// Sets how compounds containing the same key more than once are handled. This defaults to
+    /// [`DuplicateKeyPolicy::KeepLast`].
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// use quartz_nbt::io::{DuplicateKeyPolicy, Flavor, NbtIoError, NbtReadBuilder};
+    ///
+    /// // The compound { "a": 1b, "a": 2b }
+    /// let bytes = [0x0A, 0, 0, 0x01, 0, 1, b'a', 1, 0x01, 0, 1, b'a', 2, 0];
+    /// let reader = NbtReadBuilder::new().flavor(Flavor::Uncompressed);
+    ///
+    /// assert_eq!(reader.read_bytes(&bytes)?.0, compound! { "a": 2i8 });
+    /// let first = reader.duplicate_keys(DuplicateKeyPolicy::KeepFirst);
+    /// assert_eq!(first.read_bytes(&bytes)?.0, compound! { "a": 1i8 });
+    /// assert!(matches!(
+    ///     reader.duplicate_keys(DuplicateKeyPolicy::Error).read_bytes(&bytes),
+    ///     Err(NbtIoError::DuplicateKey { path }) if path == "a"
+    /// ));
+    /// # Ok::<(), NbtIoError>(())
+    /// ```
+    ///
+    /// [`DuplicateKeyPolicy::KeepLast`]: crate::io::DuplicateKeyPolicy::KeepLast
+    pub fn duplicate_keys(mut self, policy: DuplicateKeyPolicy) -> Self {
+        self.duplicate_keys = policy;
+        self
+    }
+
+    /// Sets how lists whose element type is not a known tag ID are handled. This defaults to
+    /// [`UnknownListType::Error`]. If the policy is [`UnknownListType::SkipRest`] and such a list is
+    /// found, [`ReadReport::stopped`] describes where reading stopped.
+    ///
+    /// [`UnknownListType::Error`]: crate::io::UnknownListType::Error
+    /// [`UnknownListType::SkipRest`]: crate::io::UnknownListType::SkipRest
+    /// [`ReadReport::stopped`]: crate::io::ReadReport::stopped
+    pub fn unknown_list_types(mut self, policy: UnknownListType) -> Self {
+        self.unknown_list_types = policy;
+        self
+    }
+
+    /// Sets how arrays and lists with negative lengths are handled. This defaults to
+    /// [`NegativeLengthPolicy::Error`]. The repairs made are listed by
+    /// [`ReadReport::length_repairs`].
+    ///
+    /// [`NegativeLengthPolicy::Error`]: crate::io::NegativeLengthPolicy::Error
+    /// [`ReadReport::length_repairs`]: crate::io::ReadReport::length_repairs
+    pub fn negative_lengths(mut self, policy: NegativeLengthPolicy) -> Self {
+        self.negative_lengths = policy;
+        self
+    }
+
+    /// Sets whether the range of bytes occupied by each tag is recorded, as described by
+    /// [`read_nbt_with_spans`]. This defaults to `false`. The spans are returned by
+    /// [`ReadReport::spans`].
+    ///
+    /// [`read_nbt_with_spans`]: crate::io::read_nbt_with_spans
+    /// [`ReadReport::spans`]: crate::io::ReadReport::spans
+    pub fn spans(mut self, spans: bool) -> Self {
+        self.spans = spans;
+        self
+    }
+
+    /// Sets whether as much of the document as possible is salvaged when reading it fails, as
+    /// described by [`read_nbt_partial`]. This defaults to `false`. If reading stopped early,
+    /// [`ReadReport::stopped`] describes where and why.
+    ///
+    /// Errors which occur before the document is reached, such as when detecting its flavor, and
+    /// keys rejected by the [`key_rules`] are still returned as usual.
+    ///
+    /// [`read_nbt_partial`]: crate::io::read_nbt_partial
+    /// [`ReadReport::stopped`]: crate::io::ReadReport::stopped
+    /// [`key_rules`]: crate::io::NbtReadBuilder::key_rules
+    pub fn partial(mut self, partial: bool) -> Self {
+        self.partial = partial;
+        self
+    }
+
+    /// Sets the preset dictionary Zlib and raw deflate data was compressed with by
+    /// [`write_nbt_with_dictionary`]. See [`read_nbt_with_dictionary`] for how the dictionary is
+    /// used. This defaults to no dictionary.
+    ///
+    /// This method is only available when the `zlib-dictionary` feature is enabled.
+    ///
+    /// [`write_nbt_with_dictionary`]: crate::io::write_nbt_with_dictionary
+    /// [`read_nbt_with_dictionary`]: crate::io::read_nbt_with_dictionary
+    #[cfg(feature = "zlib-dictionary")]
+    pub fn dictionary(mut self, dictionary: &'a [u8]) -> Self {
+        self.dictionary = Some(dictionary);
+        self
+    }
+
+    /// Reads NBT data from the given reader with these options, returning the resulting NBT
+    /// compound and associated root name.
+    pub fn read_from<R: Read>(&self, reader: &mut R) -> Result<(NbtCompound, String), NbtIoError> {
+        let (root, root_name, _) = self.read_from_with_report(reader)?;
+        Ok((root, root_name))
+    }
+
+    /// Reads NBT data from the given bytes with these options, returning the resulting NBT compound
+    /// and associated root name.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// use quartz_nbt::io::{self, Flavor, NbtIoError, NbtReadBuilder};
+    ///
+    /// let compound = compound! { "name": "Ferris" };
+    /// let bytes = io::write_nbt_bytes(None, &compound, Flavor::RawDeflate)?;
+    ///
+    /// let reader = NbtReadBuilder::new().flavor(Flavor::RawDeflate);
+    /// assert_eq!(reader.read_bytes(&bytes)?.0, compound);
+    /// # Ok::<(), NbtIoError>(())
+    /// ```
+    pub fn read_bytes(&self, bytes: &[u8]) -> Result<(NbtCompound, String), NbtIoError> {
+        self.read_from(&mut &*bytes)
+    }
+
+    /// Reads NBT data directly out of the given buffer with these options, returning the resulting
+    /// NBT compound and associated root name. Only the bytes making up the document are consumed,
+    /// including for compressed flavors, so any data following it is left in the buffer.
+    ///
+    /// This method is only available when the `bytes` feature is enabled.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// use bytes::{Buf, Bytes};
+    /// use quartz_nbt::io::{self, Flavor, NbtIoError, NbtReadBuilder};
+    ///
+    /// let mut packet = io::write_nbt_bytes(None, &compound! { "X": 1i32 }, Flavor::GzCompressed)?;
+    /// packet.extend_from_slice(&[0xFF, 0xFF]);
+    ///
+    /// let lowercase = |key: &str| Some(key.to_lowercase());
+    /// let reader = NbtReadBuilder::new().key_transform(&lowercase);
+    ///
+    /// let mut buf = Bytes::from(packet);
+    /// assert_eq!(reader.read_buf(&mut buf)?.0, compound! { "x": 1i32 });
+    /// assert_eq!(buf.remaining(), 2);
+    /// # Ok::<(), NbtIoError>(())
+    /// ```
+    #[cfg(feature = "bytes")]
+    pub fn read_buf<B: bytes::Buf>(
+        &self,
+        buf: &mut B,
+    ) -> Result<(NbtCompound, String), NbtIoError> {
+        let mut reader = bytes::Buf::reader(buf);
+        let target = Document { extension: None };
+        let (root, root_name, _) = match self.flavor {
+            Some(flavor) => self.read_flavor(reader, flavor, |reader| reader, true, target)?,
+            None => {
+                let mut magic = [0u8; 2];
+                reader.read_exact(&mut magic)?;
+                let flavor = detect_flavor(&magic);
+                self.read_flavor(magic.chain(reader), flavor, |reader| reader, true, target)?
+            }
+        };
+        Ok((root, root_name))
+    }
+
+    /// Reads NBT data from the given reader with these options like [`read_from`], also returning
+    /// a report of what was changed while reading it.
+    ///
+    /// [`read_from`]: crate::io::NbtReadBuilder::read_from
+    pub fn read_from_with_report<R: Read>(
+        &self,
+        reader: &mut R,
+    ) -> Result<(NbtCompound, String, ReadReport), NbtIoError> {
+        self.read_detected(reader, Document { extension: None })
+    }
+
+    /// Reads NBT data from the given reader with these options like [`read_from_with_report`], but
+    /// routes tags with unrecognized IDs to the given extension as described by
+    /// [`read_nbt_with_extension`].
+    ///
+    /// [`read_from_with_report`]: crate::io::NbtReadBuilder::read_from_with_report
+    /// [`read_nbt_with_extension`]: crate::io::read_nbt_with_extension
+    pub fn read_from_with_extension<R, E>(
+        &self,
+        reader: &mut R,
+        extension: &mut E,
+    ) -> Result<(NbtCompound, String, ReadReport), NbtIoError>
+    where
+        R: Read,
+        E: TagExtension + ?Sized,
+    {
+        let extension = &mut ExtensionRef(extension);
+        self.read_detected(reader, Document {
+            extension: Some(extension),
+        })
+    }
+
+    /// Reads the name of the root compound and the names and kinds of its entries with these
+    /// options, skipping over their values, as described by [`peek_header`].
+    ///
+    /// [`peek_header`]: crate::io::peek_header
+    pub fn peek_header<R: Read>(&self, reader: &mut R) -> Result<PeekInfo, NbtIoError> {
+        self.read_detected(reader, Header)
+    }
+
+    /// Reads a single frame of NBT data with these options, as described by [`read_nbt_framed`].
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// use quartz_nbt::io::{self, Flavor, LengthPrefix, NbtIoError, NbtReadBuilder};
+    ///
+    /// let nbt = compound! { "Name": "Ferris" };
+    /// let mut stream = Vec::new();
+    /// io::write_nbt_framed(&mut stream, LengthPrefix::U32, None, &nbt, Flavor::GzCompressed)?;
+    ///
+    /// // The flavor of each frame is detected unless it is set
+    /// let lowercase = |key: &str| Some(key.to_lowercase());
+    /// let reader = NbtReadBuilder::new().key_transform(&lowercase);
+    /// let (read, _) = reader.read_framed(&mut stream.as_slice(), LengthPrefix::U32, 1024)?;
+    /// assert_eq!(read, compound! { "name": "Ferris" });
+    /// # Ok::<(), NbtIoError>(())
+    /// ```
+    ///
+    /// [`read_nbt_framed`]: crate::io::read_nbt_framed
+    pub fn read_framed<R: Read>(
+        &self,
+        reader: &mut R,
+        prefix: LengthPrefix,
+        max_frame_size: usize,
+    ) -> Result<(NbtCompound, String), NbtIoError> {
+        let frame = read_frame(reader, prefix, max_frame_size)?;
+
+        let mut payload = frame.as_slice();
+        let result = self.read_from(&mut payload)?;
+        if !payload.is_empty() {
+            return Err(NbtIoError::TrailingFrameBytes(payload.len()));
+        }
+
+        Ok(result)
+    }
+
+    // Reads the data with the flavor which was set, or else the flavor detected from its first two
+    // bytes
+    fn read_detected<R, T>(&self, reader: &mut R, target: T) -> Result<T::Output, NbtIoError>
+    where
+        R: Read,
+        T: ReadTarget,
+    {
+        match self.flavor {
+            Some(flavor) => self.read_flavor(reader, flavor, buffered, false, target),
+            None => {
+                let mut magic = [0u8; 2];
+                reader.read_exact(&mut magic)?;
+                let flavor = detect_flavor(&magic);
+                self.read_flavor(&mut magic.chain(reader), flavor, buffered, false, target)
+            }
+        }
+    }
+
+    // Reads the given flavor of data, reading compressed data through the buffer made by `buffer`.
+    // If `to_stream_end` is set, the rest of a compressed stream is read after the document.
+    fn read_flavor<R, B, T>(
+        &self,
+        reader: R,
+        flavor: Flavor,
+        buffer: impl FnOnce(R) -> B,
+        to_stream_end: bool,
+        target: T,
+    ) -> Result<T::Output, NbtIoError>
+    where
+        R: Read,
+        B: BufRead,
+        T: ReadTarget,
+    {
+        #[cfg(feature = "zlib-dictionary")]
+        if let Some(dictionary) = self.dictionary {
+            let zlib_header = match flavor {
+                Flavor::ZlibCompressed | Flavor::ZlibCompressedWith(_) => Some(true),
+                Flavor::RawDeflate | Flavor::RawDeflateWith(_) => Some(false),
+                _ => None,
+            };
+            if let Some(zlib_header) = zlib_header {
+                let mut decoder = DictionaryDecoder::new(buffer(reader), zlib_header, dictionary)?;
+                return if to_stream_end {
+                    read_to_stream_end(&mut decoder, |decoder| target.read(self, decoder))
+                } else {
+                    target.read(self, &mut decoder)
+                };
+            }
+        }
+
+        Decoder::new(reader, flavor, buffer)
+            .read_with(to_stream_end, |decoder| target.read(self, decoder))
+    }
+
+    // Returns whether any option besides the flavor, nameless root and key rules is set, which the
+    // plain reader does not support
+    fn needs_document_reader(&self) -> bool {
+        self.max_depth < MAX_DEPTH
+            || self.latin1_fallback
+            || self.key_transform.is_some()
+            || self.duplicate_keys != DuplicateKeyPolicy::KeepLast
+            || self.unknown_list_types != UnknownListType::Error
+            || self.negative_lengths != NegativeLengthPolicy::Error
+            || self.spans
+            || self.partial
+    }
+}
+
+impl Default for NbtReadBuilder<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for NbtReadBuilder<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("NbtReadBuilder");
+        debug
+            .field("flavor", &self.flavor)
+            .field("nameless_root", &self.nameless_root)
+            .field("max_depth", &self.max_depth)
+            .field("latin1_fallback", &self.latin1_fallback)
+            .field("key_rules", &self.key_rules)
+            .field("key_transform", &self.key_transform.is_some())
+            .field("duplicate_keys", &self.duplicate_keys)
+            .field("unknown_list_types", &self.unknown_list_types)
+            .field("negative_lengths", &self.negative_lengths)
+            .field("spans", &self.spans)
+            .field("partial", &self.partial);
+        #[cfg(feature = "zlib-dictionary")]
+        debug.field("dictionary", &self.dictionary);
+        debug.finish()
+    }
+}
+
+// Something read out of the uncompressed data with the options of an `NbtReadBuilder`, which lets
+// each of its read methods share how the data is decompressed
+trait ReadTarget {
+    type Output;
+
+    fn read<R: Read>(
+        self,
+        options: &NbtReadBuilder<'_>,
+        reader: &mut R,
+    ) -> Result<Self::Output, NbtIoError>;
+}
+
+// The whole document, with custom tags passed to the extension if there is one
+struct Document<'e> {
+    extension: Option<&'e mut dyn TagExtension>,
+}
+
+impl ReadTarget for Document<'_> {
+    type Output = (NbtCompound, String, ReadReport);
+
+    fn read<R: Read>(
+        self,
+        options: &NbtReadBuilder<'_>,
+        reader: &mut R,
+    ) -> Result<Self::Output, NbtIoError> {
+        let (root, root_name, report) = if options.negative_lengths == NegativeLengthPolicy::Recover
+        {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes)?;
+            let mut document = DocumentReader::new(bytes.as_slice(), options);
+            document.document = Some(&bytes);
+            document.recovery_budget = bytes.len().saturating_mul(RECOVERY_BUDGET_FACTOR);
+            document.extension = self.extension;
+            document.read_document()?
+        } else if options.needs_document_reader() || self.extension.is_some() {
+            let mut document = DocumentReader::new(reader, options);
+            document.extension = self.extension;
+            document.read_document()?
+        } else {
+            let (root, root_name) = read_document(reader, options.nameless_root)?;
+            (root, root_name, ReadReport::default())
+        };
+
+        options.key_rules.validate(&root)?;
+        Ok((root, root_name, report))
+    }
+}
+
+// The root name and the names and kinds of the root compound's entries
+struct Header;
+
+impl ReadTarget for Header {
+    type Output = PeekInfo;
+
+    fn read<R: Read>(
+        self,
+        options: &NbtReadBuilder<'_>,
+        reader: &mut R,
+    ) -> Result<Self::Output, NbtIoError> {
+        DocumentReader::new(reader, options).peek_document()
+    }
+}
+
+// Lets an extension which may be unsized be passed on as a trait object
+struct ExtensionRef<'e, E: ?Sized>(&'e mut E);
+
+impl<E: TagExtension + ?Sized> TagExtension for ExtensionRef<'_, E> {
+    #[inline]
+    fn read_tag(
+        &mut self,
+        tag_id: u8,
+        reader: &mut dyn Read,
+    ) -> Result<Option<NbtTag>, NbtIoError> {
+        self.0.read_tag(tag_id, reader)
+    }
+}
+
+/// A record of what was changed while reading a document with
+/// [`NbtReadBuilder::read_from_with_report`].
+///
+/// [`NbtReadBuilder::read_from_with_report`]: crate::io::NbtReadBuilder::read_from_with_report
+#[derive(Debug, Default)]
+pub struct ReadReport {
+    pub(crate) latin1_fallbacks: Vec<String>,
+    pub(crate) renamed_keys: Vec<RenamedKey>,
+    pub(crate) length_repairs: Vec<LengthRepair>,
+    pub(crate) stopped: Option<PartialReadError>,
+    pub(crate) spans: Option<NbtSpans>,
+}
+
+impl ReadReport {
+    /// Returns the paths of the strings which were decoded as Latin-1 because they were not valid
+    /// Java CESU-8, as enabled by [`NbtReadBuilder::latin1_fallback`]. The root name has an empty
+    /// path.
+    ///
+    /// [`NbtReadBuilder::latin1_fallback`]: crate::io::NbtReadBuilder::latin1_fallback
+    pub fn latin1_fallbacks(&self) -> &[String] {
+        &self.latin1_fallbacks
+    }
+
+    /// Returns the keys which were changed by the transform set with
+    /// [`NbtReadBuilder::key_transform`].
+    ///
+    /// [`NbtReadBuilder::key_transform`]: crate::io::NbtReadBuilder::key_transform
+    pub fn renamed_keys(&self) -> &[RenamedKey] {
+        &self.renamed_keys
+    }
+
+    /// Returns the repairs made to negative lengths, as allowed by
+    /// [`NbtReadBuilder::negative_lengths`].
+    ///
+    /// [`NbtReadBuilder::negative_lengths`]: crate::io::NbtReadBuilder::negative_lengths
+    pub fn length_repairs(&self) -> &[LengthRepair] {
+        &self.length_repairs
+    }
+
+    /// Returns where and why reading stopped, if it stopped at an error as allowed by
+    /// [`NbtReadBuilder::partial`], or at a list whose element type is not a known tag ID as allowed
+    /// by [`UnknownListType::SkipRest`]. The rest of the document was skipped.
+    ///
+    /// [`NbtReadBuilder::partial`]: crate::io::NbtReadBuilder::partial
+    /// [`UnknownListType::SkipRest`]: crate::io::UnknownListType::SkipRest
+    pub fn stopped(&self) -> Option<&PartialReadError> {
+        self.stopped.as_ref()
+    }
+
+    /// Returns the range of bytes occupied by each tag which was read, if they were recorded as
+    /// enabled by [`NbtReadBuilder::spans`].
+    ///
+    /// [`NbtReadBuilder::spans`]: crate::io::NbtReadBuilder::spans
+    pub fn spans(&self) -> Option<&NbtSpans> {
+        self.spans.as_ref()
+    }
+
+    /// Returns true if nothing was changed or skipped while reading. Recorded spans are not
+    /// considered.
+    pub fn is_empty(&self) -> bool {
+        self.latin1_fallbacks.is_empty()
+            && self.renamed_keys.is_empty()
+            && self.length_repairs.is_empty()
+            && self.stopped.is_none()
+    }
+}
+
+/// A builder for writing binary NBT data with options beyond those of [`write_nbt`], which is
+/// equivalent to a builder with only the flavor and root name set.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::io::{self, Flavor, KeyIssue, KeyRules, NbtIoError, NbtWriteBuilder};
+///
+/// let nbt = compound! { "": 1i32 };
+/// let writer = NbtWriteBuilder::new().flavor(Flavor::ZlibCompressed).root_name("data");
+///
+/// let mut bytes = Vec::new();
+/// writer.write_to(&mut bytes, &nbt)?;
+/// assert_eq!(io::read_nbt(&mut bytes.as_slice(), Flavor::ZlibCompressed)?, (nbt.clone(), "data".to_owned()));
+///
+/// let strict = writer.key_rules(KeyRules::strict());
+/// assert!(matches!(
+///     strict.write_to_bytes(&nbt),
+///     Err(NbtIoError::SuspiciousKey { issue: KeyIssue::Empty, .. })
+/// ));
+/// # Ok::<(), NbtIoError>(())
+/// ```
+///
+/// [`write_nbt`]: crate::io::write_nbt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NbtWriteBuilder<'a> {
+    flavor: Flavor,
+    root_name: &'a str,
+    nameless_root: bool,
+    key_rules: KeyRules,
+    auto_compression: bool,
+    #[cfg(feature = "zlib-dictionary")]
+    dictionary: Option<&'a [u8]>,
+}
+
+impl<'a> NbtWriteBuilder<'a> {
+    /// Returns a builder with the default options, which writes uncompressed data with an empty
+    /// root name just like [`write_nbt`].
+    ///
+    /// [`write_nbt`]: crate::io::write_nbt
+    pub fn new() -> Self {
+        NbtWriteBuilder {
+            flavor: Flavor::Uncompressed,
+            root_name: "",
+            nameless_root: false,
+            key_rules: KeyRules::new(),
+            auto_compression: false,
+            #[cfg(feature = "zlib-dictionary")]
+            dictionary: None,
+        }
+    }
+
+    /// Sets the flavor of the data. This defaults to [`Flavor::Uncompressed`].
+    ///
+    /// [`Flavor::Uncompressed`]: crate::io::Flavor::Uncompressed
+    pub fn flavor(mut self, flavor: Flavor) -> Self {
+        self.flavor = flavor;
+        self
+    }
+
+    /// Sets the name of the root compound. This defaults to an empty string.
+    pub fn root_name(mut self, root_name: &'a str) -> Self {
+        self.root_name = root_name;
+        self
+    }
+
+    /// Sets whether the root compound is written without a name, as in the NBT sent over the
+    /// network since Minecraft 1.20.2, in which case the root name is ignored. This defaults to
+    /// `false`.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// use quartz_nbt::io::{NbtIoError, NbtWriteBuilder};
+    ///
+    /// let bytes = NbtWriteBuilder::new()
+    ///     .nameless_root(true)
+    ///     .write_to_bytes(&compound! { "x": 7i8 })?;
+    /// assert_eq!(bytes, [0x0A, 0x01, 0, 1, b'x', 7, 0]);
+    /// # Ok::<(), NbtIoError>(())
+    /// ```
+    pub fn nameless_root(mut self, nameless: bool) -> Self {
+        self.nameless_root = nameless;
+        self
+    }
+
+    /// Sets the rules every key in the data is checked against before anything is written. This
+    /// defaults to [`KeyRules::new`], which accepts every key.
+    ///
+    /// [`KeyRules::new`]: crate::io::KeyRules::new
+    pub fn key_rules(mut self, rules: KeyRules) -> Self {
+        self.key_rules = rules;
+        self
+    }
+
+    /// Sets whether the compression level is chosen by [`auto_compression_level`] based on the
+    /// data being written, rather than using the default level. This only applies to compressed
+    /// flavors which do not specify a level, such as [`Flavor::GzCompressed`], and defaults to
+    /// `false`.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// use quartz_nbt::io::{self, Flavor, NbtIoError, NbtWriteBuilder};
+    ///
+    /// let writer = NbtWriteBuilder::new()
+    ///     .flavor(Flavor::GzCompressed)
+    ///     .auto_compression(true);
+    ///
+    /// let nbt = compound! { "Heightmap": NbtTag::LongArray(vec![0; 4096]) };
+    /// let bytes = writer.write_to_bytes(&nbt)?;
+    /// assert_eq!(io::read_nbt(&mut bytes.as_slice(), Flavor::GzCompressed)?.0, nbt);
+    /// # Ok::<(), NbtIoError>(())
+    /// ```
+    ///
+    /// [`auto_compression_level`]: crate::io::auto_compression_level
+    /// [`Flavor::GzCompressed`]: crate::io::Flavor::GzCompressed
+    pub fn auto_compression(mut self, auto_compression: bool) -> Self {
+        self.auto_compression = auto_compression;
+        self
+    }
+
+    /// Sets the preset dictionary the data is compressed with. See [`write_nbt_with_dictionary`]
+    /// for how the dictionary is used. This defaults to no dictionary.
+    ///
+    /// Preset dictionaries are supported by the Zlib and raw deflate flavors, so writing any other
+    /// flavor with a dictionary set returns an [`InvalidInput`] error.
+    ///
+    /// This method is only available when the `zlib-dictionary` feature is enabled.
+    ///
+    /// [`write_nbt_with_dictionary`]: crate::io::write_nbt_with_dictionary
+    /// [`InvalidInput`]: std::io::ErrorKind::InvalidInput
+    #[cfg(feature = "zlib-dictionary")]
+    pub fn dictionary(mut self, dictionary: &'a [u8]) -> Self {
+        self.dictionary = Some(dictionary);
+        self
+    }
+
+    /// Writes the given compound to the given writer with these options.
+    pub fn write_to<W: Write>(&self, writer: &mut W, root: &NbtCompound) -> Result<(), NbtIoError> {
+        self.key_rules.validate(root)?;
+
+        let root_name = if self.nameless_root {
+            None
+        } else {
+            Some(self.root_name)
+        };
+        let default = if self.auto_compression {
+            auto_compression_level(root)
+        } else {
+            Compression::default()
+        };
+
+        #[cfg(feature = "zlib-dictionary")]
+        if let Some(dictionary) = self.dictionary {
+            return Encoder::with_dictionary(writer, self.flavor, default, dictionary)?
+                .finish_with(|encoder| write_nbt_uncompressed(encoder, root_name, root));
+        }
+
+        with_encoder(writer, self.flavor, default, |encoder| {
+            write_nbt_uncompressed(encoder, root_name, root)
+        })
+    }
+
+    /// Writes the given compound to a new buffer with these options and returns it.
+    pub fn write_to_bytes(&self, root: &NbtCompound) -> Result<Vec<u8>, NbtIoError> {
+        let mut bytes = Vec::new();
+        self.write_to(&mut bytes, root)?;
+        Ok(bytes)
+    }
+}
+
+impl Default for NbtWriteBuilder<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,81 @@
+#[cfg(feature = "serde")]
+use super::Decoder;
+use super::{write_nbt, Flavor, NbtIoError, NbtReadBuilder};
+use crate::NbtCompound;
+
+// Like `with_decoder`, but reads directly out of the given buffer, and reads the rest of a
+// compressed stream once the function returns so that only the bytes making up the stream are
+// consumed from the buffer
+#[cfg(feature = "serde")]
+pub(crate) fn with_buf_decoder<B, T, F>(buf: &mut B, flavor: Flavor, f: F) -> Result<T, NbtIoError>
+where
+    B: bytes::Buf,
+    F: FnOnce(
+        &mut Decoder<bytes::buf::Reader<&mut B>, bytes::buf::Reader<&mut B>>,
+    ) -> Result<T, NbtIoError>,
+{
+    Decoder::new(bytes::Buf::reader(buf), flavor, |reader| reader).read_with(true, f)
+}
+
+/// Reads the given flavor of NBT data directly out of the given buffer, such as a network receive
+/// buffer, and returns the resulting NBT compound and associated root name. Only the bytes making
+/// up the document are consumed, including for compressed flavors, so any data following it is
+/// left in the buffer.
+///
+/// This is a shorthand for [`NbtReadBuilder::read_buf`] with only the flavor set.
+///
+/// This function is only available when the `bytes` feature is enabled.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use bytes::{Buf, Bytes};
+/// use quartz_nbt::io::{self, Flavor, NbtIoError};
+///
+/// let mut packet = io::write_nbt_bytes(None, &compound! { "x": 1i32 }, Flavor::ZlibCompressed)?;
+/// packet.extend_from_slice(&[0xFF, 0xFF]);
+///
+/// let mut buf = Bytes::from(packet);
+/// let (nbt, _) = io::read_nbt_buf(&mut buf, Flavor::ZlibCompressed)?;
+/// assert_eq!(nbt, compound! { "x": 1i32 });
+/// assert_eq!(buf.remaining(), 2);
+/// # Ok::<(), NbtIoError>(())
+/// ```
+///
+/// [`NbtReadBuilder::read_buf`]: crate::io::NbtReadBuilder::read_buf
+pub fn read_nbt_buf<B: bytes::Buf>(
+    buf: &mut B,
+    flavor: Flavor,
+) -> Result<(NbtCompound, String), NbtIoError> {
+    NbtReadBuilder::new().flavor(flavor).read_buf(buf)
+}
+
+/// Writes the given flavor of NBT data directly into the given buffer, such as a protocol encoder's
+/// send buffer. If no root name is provided, an empty string is used.
+///
+/// Uncompressed data is encoded straight into the buffer, without an intermediate allocation. If
+/// the buffer runs out of space, an error is returned and the buffer is left holding whatever was
+/// written before that point.
+///
+/// This function is only available when the `bytes` feature is enabled.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use bytes::{BufMut, BytesMut};
+/// use quartz_nbt::io::{self, Flavor, NbtIoError};
+///
+/// let mut packet = BytesMut::new();
+/// packet.put_u8(0x0E);
+/// io::write_nbt_buf(&mut packet, None, &compound! { "x": 1i32 }, Flavor::Uncompressed)?;
+///
+/// let (nbt, _) = io::read_nbt_bytes(&packet[1 ..])?;
+/// assert_eq!(nbt, compound! { "x": 1i32 });
+/// # Ok::<(), NbtIoError>(())
+/// ```
+pub fn write_nbt_buf<B: bytes::BufMut>(
+    buf: &mut B,
+    root_name: Option<&str>,
+    root: &NbtCompound,
+    flavor: Flavor,
+) -> Result<(), NbtIoError> {
+    write_nbt(&mut bytes::BufMut::writer(buf), root_name, root, flavor)
+}
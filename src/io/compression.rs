@@ -0,0 +1,404 @@
+use super::{read_nbt_uncompressed, Flavor, NbtIoError};
+use crate::{NbtCompound, NbtTag};
+#[cfg(feature = "zlib-dictionary")]
+use flate2::Compress;
+use flate2::{
+    bufread,
+    write::{DeflateEncoder, GzEncoder, ZlibEncoder},
+    Compression,
+};
+use std::{
+    io,
+    io::{BufRead, BufReader, Read, Write},
+};
+
+/// Converts NBT data of one flavor into another, such as from Gz to Zlib compression, without
+/// parsing it. The data is decompressed and recompressed as it is streamed from the reader to the
+/// writer, and the number of uncompressed bytes copied is returned.
+///
+/// Since the data is not parsed, invalid NBT is converted as it is. Use [`recompress_validated`]
+/// to check that the data is valid NBT before anything is written.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::io::{self, Flavor};
+///
+/// let compound = compound! { "level": 30i32 };
+/// let gz = io::write_nbt_bytes(Some("root"), &compound, Flavor::GzCompressed)?;
+///
+/// let mut zlib = Vec::new();
+/// io::recompress(&mut gz.as_slice(), &mut zlib, Flavor::GzCompressed, Flavor::ZlibCompressed)?;
+/// assert_eq!(
+///     io::read_nbt(&mut zlib.as_slice(), Flavor::ZlibCompressed)?,
+///     (compound, "root".to_owned())
+/// );
+/// # Ok::<(), io::NbtIoError>(())
+/// ```
+///
+/// [`recompress_validated`]: crate::io::recompress_validated
+pub fn recompress<R: Read, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    from: Flavor,
+    to: Flavor,
+) -> Result<u64, NbtIoError> {
+    with_decoder(reader, from, |decoder| compress_into(decoder, writer, to))
+}
+
+/// Converts NBT data of one flavor into another just like [`recompress`], but checks that the
+/// data is a valid NBT document first. The uncompressed data is buffered in memory while it is
+/// checked, and nothing is written if it is invalid.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::io::{self, Flavor};
+///
+/// let bytes = io::write_nbt_bytes(None, &compound! { "a": 1i8 }, Flavor::Uncompressed)?;
+/// let mut output = Vec::new();
+/// let copied = io::recompress_validated(
+///     &mut bytes.as_slice(),
+///     &mut output,
+///     Flavor::Uncompressed,
+///     Flavor::GzCompressed,
+/// )?;
+/// assert_eq!(copied, bytes.len() as u64);
+///
+/// let mut output = Vec::new();
+/// let truncated = &bytes[.. 5];
+/// assert!(io::recompress_validated(
+///     &mut &truncated[..],
+///     &mut output,
+///     Flavor::Uncompressed,
+///     Flavor::GzCompressed
+/// )
+/// .is_err());
+/// assert!(output.is_empty());
+/// # Ok::<(), io::NbtIoError>(())
+/// ```
+///
+/// [`recompress`]: crate::io::recompress
+pub fn recompress_validated<R: Read, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    from: Flavor,
+    to: Flavor,
+) -> Result<u64, NbtIoError> {
+    let mut payload = Vec::new();
+    recompress(reader, &mut payload, from, Flavor::Uncompressed)?;
+    read_nbt_uncompressed(&mut payload.as_slice())?;
+    compress_into(&mut payload.as_slice(), writer, to)
+}
+
+// Copies the uncompressed data from the reader to the writer, compressing it as it is written
+fn compress_into<R: Read, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    flavor: Flavor,
+) -> Result<u64, NbtIoError> {
+    with_encoder(writer, flavor, Compression::default(), |encoder| {
+        Ok(io::copy(reader, encoder)?)
+    })
+}
+
+// Runs the given function with a writer which compresses everything written to it according to
+// the given flavor, then finishes the compressed stream. Flavors which do not specify a compression
+// level are compressed with the given default level.
+pub(crate) fn with_encoder<W, T, F>(
+    writer: &mut W,
+    flavor: Flavor,
+    default: Compression,
+    f: F,
+) -> Result<T, NbtIoError>
+where
+    W: Write,
+    F: FnOnce(&mut Encoder<'_, W>) -> Result<T, NbtIoError>,
+{
+    Encoder::new(writer, flavor, default).finish_with(f)
+}
+
+// A writer which compresses the data written to it according to a flavor
+pub(crate) enum Encoder<'a, W: Write> {
+    Uncompressed(&'a mut W),
+    // Also used for raw deflate data with a preset dictionary, in which case the compressor is
+    // created without the Zlib header
+    Zlib(ZlibEncoder<&'a mut W>),
+    Gz(GzEncoder<&'a mut W>),
+    RawDeflate(DeflateEncoder<&'a mut W>),
+}
+
+impl<'a, W: Write> Encoder<'a, W> {
+    fn new(writer: &'a mut W, flavor: Flavor, default: Compression) -> Self {
+        match flavor {
+            Flavor::Uncompressed => Encoder::Uncompressed(writer),
+            Flavor::ZlibCompressed => Encoder::Zlib(ZlibEncoder::new(writer, default)),
+            Flavor::ZlibCompressedWith(compression) =>
+                Encoder::Zlib(ZlibEncoder::new(writer, compression)),
+            Flavor::GzCompressed => Encoder::Gz(GzEncoder::new(writer, default)),
+            Flavor::GzCompressedWith(compression) =>
+                Encoder::Gz(GzEncoder::new(writer, compression)),
+            Flavor::RawDeflate => Encoder::RawDeflate(DeflateEncoder::new(writer, default)),
+            Flavor::RawDeflateWith(compression) =>
+                Encoder::RawDeflate(DeflateEncoder::new(writer, compression)),
+        }
+    }
+
+    // Returns an error for the flavors which do not support preset dictionaries
+    #[cfg(feature = "zlib-dictionary")]
+    pub(crate) fn with_dictionary(
+        writer: &'a mut W,
+        flavor: Flavor,
+        default: Compression,
+        dictionary: &[u8],
+    ) -> Result<Self, NbtIoError> {
+        let (zlib_header, compression) = match flavor {
+            Flavor::ZlibCompressed => (true, default),
+            Flavor::ZlibCompressedWith(compression) => (true, compression),
+            Flavor::RawDeflate => (false, default),
+            Flavor::RawDeflateWith(compression) => (false, compression),
+            _ =>
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "Preset dictionaries are only supported by the Zlib and raw deflate flavors",
+                )
+                .into()),
+        };
+
+        let mut compress = Compress::new(compression, zlib_header);
+        compress
+            .set_dictionary(dictionary)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+        Ok(Encoder::Zlib(ZlibEncoder::new_with_compress(
+            writer, compress,
+        )))
+    }
+
+    // Finish the streams explicitly so that errors writing their trailers are not lost
+    pub(crate) fn finish_with<T, F>(mut self, f: F) -> Result<T, NbtIoError>
+    where F: FnOnce(&mut Self) -> Result<T, NbtIoError> {
+        let result = f(&mut self)?;
+        match self {
+            Encoder::Uncompressed(_) => {}
+            Encoder::Zlib(encoder) => {
+                encoder.finish()?;
+            }
+            Encoder::Gz(encoder) => {
+                encoder.finish()?;
+            }
+            Encoder::RawDeflate(encoder) => {
+                encoder.finish()?;
+            }
+        }
+        Ok(result)
+    }
+}
+
+impl<W: Write> Write for Encoder<'_, W> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Encoder::Uncompressed(writer) => writer.write(buf),
+            Encoder::Zlib(encoder) => encoder.write(buf),
+            Encoder::Gz(encoder) => encoder.write(buf),
+            Encoder::RawDeflate(encoder) => encoder.write(buf),
+        }
+    }
+
+    #[inline]
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        match self {
+            Encoder::Uncompressed(writer) => writer.write_all(buf),
+            Encoder::Zlib(encoder) => encoder.write_all(buf),
+            Encoder::Gz(encoder) => encoder.write_all(buf),
+            Encoder::RawDeflate(encoder) => encoder.write_all(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Encoder::Uncompressed(writer) => writer.flush(),
+            Encoder::Zlib(encoder) => encoder.flush(),
+            Encoder::Gz(encoder) => encoder.flush(),
+            Encoder::RawDeflate(encoder) => encoder.flush(),
+        }
+    }
+}
+
+// The size of the buffer compressed data is read into, matching the decoders of flate2
+const DECODER_BUFFER_LEN: usize = 32 * 1024;
+
+// Runs the given function with a reader which decompresses the data read from the given reader
+// according to the given flavor. Uncompressed data is read straight from the reader, so nothing
+// past the end of the document is consumed.
+pub(crate) fn with_decoder<R, T, F>(reader: &mut R, flavor: Flavor, f: F) -> Result<T, NbtIoError>
+where
+    R: Read,
+    F: FnOnce(&mut Decoder<&mut R, BufReader<&mut R>>) -> Result<T, NbtIoError>,
+{
+    Decoder::new(reader, flavor, buffered).read_with(false, f)
+}
+
+#[inline]
+pub(crate) fn buffered<R: Read>(reader: R) -> BufReader<R> {
+    BufReader::with_capacity(DECODER_BUFFER_LEN, reader)
+}
+
+// A reader which decompresses the data read from `R` according to a flavor. Compressed data is
+// read through `B`, which buffers `R`.
+pub(crate) enum Decoder<R, B> {
+    Uncompressed(R),
+    Zlib(bufread::ZlibDecoder<B>),
+    Gz(bufread::GzDecoder<B>),
+    RawDeflate(bufread::DeflateDecoder<B>),
+}
+
+impl<R: Read, B: BufRead> Decoder<R, B> {
+    pub(crate) fn new(reader: R, flavor: Flavor, buffer: impl FnOnce(R) -> B) -> Self {
+        match flavor {
+            Flavor::Uncompressed => Decoder::Uncompressed(reader),
+            Flavor::ZlibCompressed | Flavor::ZlibCompressedWith(_) =>
+                Decoder::Zlib(bufread::ZlibDecoder::new(buffer(reader))),
+            Flavor::GzCompressed | Flavor::GzCompressedWith(_) =>
+                Decoder::Gz(bufread::GzDecoder::new(buffer(reader))),
+            Flavor::RawDeflate | Flavor::RawDeflateWith(_) =>
+                Decoder::RawDeflate(bufread::DeflateDecoder::new(buffer(reader))),
+        }
+    }
+
+    // Runs the given function with this decoder, reading the rest of a compressed stream
+    // afterwards if `to_stream_end` is set
+    pub(crate) fn read_with<T, F>(mut self, to_stream_end: bool, f: F) -> Result<T, NbtIoError>
+    where F: FnOnce(&mut Self) -> Result<T, NbtIoError> {
+        if to_stream_end && !matches!(self, Decoder::Uncompressed(_)) {
+            read_to_stream_end(&mut self, f)
+        } else {
+            f(&mut self)
+        }
+    }
+}
+
+impl<R: Read, B: BufRead> Read for Decoder<R, B> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Decoder::Uncompressed(reader) => reader.read(buf),
+            Decoder::Zlib(decoder) => decoder.read(buf),
+            Decoder::Gz(decoder) => decoder.read(buf),
+            Decoder::RawDeflate(decoder) => decoder.read(buf),
+        }
+    }
+
+    #[inline]
+    fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        match self {
+            Decoder::Uncompressed(reader) => reader.read_exact(buf),
+            Decoder::Zlib(decoder) => decoder.read_exact(buf),
+            Decoder::Gz(decoder) => decoder.read_exact(buf),
+            Decoder::RawDeflate(decoder) => decoder.read_exact(buf),
+        }
+    }
+}
+
+// Reads a value from the given decoder, then reads the rest of its compressed stream so that the
+// stream's trailer is consumed from the underlying reader
+pub(crate) fn read_to_stream_end<R, T, F>(decoder: &mut R, read: F) -> Result<T, NbtIoError>
+where
+    R: Read,
+    F: FnOnce(&mut R) -> Result<T, NbtIoError>,
+{
+    let value = read(decoder)?;
+    io::copy(decoder, &mut io::sink())?;
+    Ok(value)
+}
+
+// Payloads smaller than this many bytes are stored without compression
+const TINY_PAYLOAD_LEN: usize = 256;
+
+// Payloads whose arrays hold at least this many bytes, making up at least half of the payload, are
+// compressed with the best level
+const HUGE_ARRAYS_LEN: usize = 256 * 1024;
+
+/// Chooses a compression level for the given compound from the size and composition of its
+/// uncompressed encoding, as used by [`NbtWriteBuilder::auto_compression`].
+///
+///  - Payloads of fewer than 256 bytes, such as single item stacks, gain little from compression,
+///    so they are stored with [`Compression::none`]. The Zlib or Gz headers are still written, so
+///    the data can be read back with the same flavor.
+///  - Payloads dominated by large arrays, such as chunks with their packed block states and
+///    heightmaps, are compressed with [`Compression::best`] once their arrays exceed 256 KiB, since
+///    arrays compress slowly but well, and such data is usually written once and read many times.
+///  - Everything else uses [`Compression::default`].
+///
+/// The measurements these thresholds are based on can be reproduced with the `compression`
+/// benchmark.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use flate2::Compression;
+/// use quartz_nbt::io;
+///
+/// let item = compound! { "id": "minecraft:stone", "Count": 1i8 };
+/// assert_eq!(io::auto_compression_level(&item), Compression::none());
+///
+/// let chunk = compound! { "BlockStates": NbtTag::LongArray(vec![0; 65536]) };
+/// assert_eq!(io::auto_compression_level(&chunk), Compression::best());
+/// ```
+///
+/// [`NbtWriteBuilder::auto_compression`]: crate::io::NbtWriteBuilder::auto_compression
+/// [`Compression::none`]: flate2::Compression::none
+/// [`Compression::best`]: flate2::Compression::best
+/// [`Compression::default`]: flate2::Compression::default
+pub fn auto_compression_level(root: &NbtCompound) -> Compression {
+    let mut measure = PayloadMeasure::default();
+    measure.compound(root);
+
+    if measure.total < TINY_PAYLOAD_LEN {
+        Compression::none()
+    } else if measure.arrays >= HUGE_ARRAYS_LEN && measure.arrays * 2 >= measure.total {
+        Compression::best()
+    } else {
+        Compression::default()
+    }
+}
+
+// Tallies the encoded length of a payload, and how much of it is taken up by arrays. Strings are
+// measured by their UTF-8 length, which is close enough to their modified UTF-8 length.
+#[derive(Default)]
+struct PayloadMeasure {
+    total: usize,
+    arrays: usize,
+}
+
+impl PayloadMeasure {
+    fn compound(&mut self, compound: &NbtCompound) {
+        for (key, tag) in compound.inner() {
+            self.total += 3 + key.len();
+            self.tag(tag);
+        }
+        self.total += 1;
+    }
+
+    fn tag(&mut self, tag: &NbtTag) {
+        match tag {
+            NbtTag::Byte(_) => self.total += 1,
+            NbtTag::Short(_) => self.total += 2,
+            NbtTag::Int(_) | NbtTag::Float(_) => self.total += 4,
+            NbtTag::Long(_) | NbtTag::Double(_) => self.total += 8,
+            NbtTag::String(string) => self.total += 2 + string.len(),
+            NbtTag::List(list) => {
+                self.total += 5;
+                for tag in list.iter() {
+                    self.tag(tag);
+                }
+            }
+            NbtTag::Compound(compound) => self.compound(compound),
+            NbtTag::ByteArray(array) => self.array(array.len()),
+            NbtTag::IntArray(array) => self.array(array.len() * 4),
+            NbtTag::LongArray(array) => self.array(array.len() * 8),
+        }
+    }
+
+    fn array(&mut self, len: usize) {
+        self.total += 4 + len;
+        self.arrays += len;
+    }
+}
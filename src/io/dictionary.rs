@@ -0,0 +1,161 @@
+use super::{Flavor, NbtIoError, NbtReadBuilder, NbtWriteBuilder};
+use crate::NbtCompound;
+use flate2::{Decompress, FlushDecompress, Status};
+use std::{
+    io,
+    io::{BufRead, Read, Write},
+};
+
+/// Reads NBT data compressed with a preset dictionary by [`write_nbt_with_dictionary`], returning
+/// the resulting NBT compound and associated root name. The dictionary must be the same one the
+/// data was written with.
+///
+/// Preset dictionaries are supported by the Zlib and raw deflate flavors. Zlib data records a
+/// checksum of its dictionary, so reading it with a different dictionary fails with an
+/// [`InvalidData`] error, and Zlib data written without a dictionary is read as usual. Raw deflate
+/// data has no such checksum, so reading it with the wrong dictionary generally fails to parse. The
+/// dictionary is ignored for the other flavors, which are read as [`read_nbt`] would.
+///
+/// This is a shorthand for [`NbtReadBuilder`] with only the flavor and dictionary set.
+///
+/// This function is only available when the `zlib-dictionary` feature is enabled, which switches
+/// the compression backend of `flate2` to `zlib-rs`.
+///
+/// [`InvalidData`]: std::io::ErrorKind::InvalidData
+/// [`NbtReadBuilder`]: crate::io::NbtReadBuilder
+/// [`read_nbt`]: crate::io::read_nbt
+pub fn read_nbt_with_dictionary<R: Read>(
+    reader: &mut R,
+    flavor: Flavor,
+    dictionary: &[u8],
+) -> Result<(NbtCompound, String), NbtIoError> {
+    NbtReadBuilder::new()
+        .flavor(flavor)
+        .dictionary(dictionary)
+        .read_from(reader)
+}
+
+/// Writes the given tag compound with the given name to the provided writer, compressing it with
+/// the given preset dictionary. Dictionaries made up of data which commonly occurs in the written
+/// documents, such as their keys, make small documents compress considerably better. The data
+/// can only be read back with the same dictionary, using [`read_nbt_with_dictionary`].
+///
+/// Preset dictionaries are supported by the Zlib and raw deflate flavors. An [`InvalidInput`] error
+/// is returned for the other flavors.
+///
+/// This is a shorthand for [`NbtWriteBuilder`] with only the flavor, root name, and dictionary set.
+///
+/// This function is only available when the `zlib-dictionary` feature is enabled.
+///
+/// # Example
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::io::{self, Flavor};
+///
+/// let dictionary = b"\x0a\x08\x00\x04name\x03\x00\x02xp\x00";
+/// let compound = compound! { "name": "Steve", "xp": 30i32 };
+///
+/// let mut bytes = Vec::new();
+/// io::write_nbt_with_dictionary(&mut bytes, None, &compound, Flavor::ZlibCompressed, dictionary)?;
+///
+/// let (read, _) = io::read_nbt_with_dictionary(&mut &bytes[..], Flavor::ZlibCompressed, dictionary)?;
+/// assert_eq!(read, compound);
+/// assert!(io::read_nbt(&mut &bytes[..], Flavor::ZlibCompressed).is_err());
+///
+/// let mut bytes = Vec::new();
+/// assert!(io::write_nbt_with_dictionary(&mut bytes, None, &compound, Flavor::GzCompressed, dictionary).is_err());
+/// # Ok::<_, io::NbtIoError>(())
+/// ```
+///
+/// [`InvalidInput`]: std::io::ErrorKind::InvalidInput
+/// [`NbtWriteBuilder`]: crate::io::NbtWriteBuilder
+pub fn write_nbt_with_dictionary<W: Write>(
+    writer: &mut W,
+    root_name: Option<&str>,
+    root: &NbtCompound,
+    flavor: Flavor,
+    dictionary: &[u8],
+) -> Result<(), NbtIoError> {
+    NbtWriteBuilder::new()
+        .flavor(flavor)
+        .root_name(root_name.unwrap_or(""))
+        .dictionary(dictionary)
+        .write_to(writer, root)
+}
+
+// Decompresses a Zlib or raw deflate stream with a preset dictionary. The decoders of flate2 have
+// no way of supplying one, since a Zlib stream only accepts its dictionary after asking for it.
+pub(crate) struct DictionaryDecoder<'a, R> {
+    reader: R,
+    decompress: Decompress,
+    dictionary: &'a [u8],
+    done: bool,
+}
+
+impl<'a, R: BufRead> DictionaryDecoder<'a, R> {
+    pub(crate) fn new(reader: R, zlib_header: bool, dictionary: &'a [u8]) -> io::Result<Self> {
+        let mut decompress = Decompress::new(zlib_header);
+        // Raw deflate streams never ask for their dictionary, so it has to be set upfront
+        if !zlib_header {
+            decompress
+                .set_dictionary(dictionary)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+        }
+
+        Ok(DictionaryDecoder {
+            reader,
+            decompress,
+            dictionary,
+            done: false,
+        })
+    }
+}
+
+impl<R: BufRead> Read for DictionaryDecoder<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while !self.done && !buf.is_empty() {
+            let input = self.reader.fill_buf()?;
+            let at_eof = input.is_empty();
+            let flush = if at_eof {
+                FlushDecompress::Finish
+            } else {
+                FlushDecompress::None
+            };
+
+            let (total_in, total_out) = (self.decompress.total_in(), self.decompress.total_out());
+            let status = self.decompress.decompress(input, buf, flush);
+            let consumed = (self.decompress.total_in() - total_in) as usize;
+            let written = (self.decompress.total_out() - total_out) as usize;
+            self.reader.consume(consumed);
+
+            match status {
+                Ok(Status::StreamEnd) => self.done = true,
+                Ok(_) if consumed == 0 && written == 0 =>
+                    return Err(if at_eof {
+                        io::ErrorKind::UnexpectedEof.into()
+                    } else {
+                        io::Error::new(io::ErrorKind::InvalidData, "Deflate stream stalled")
+                    }),
+                Ok(_) => {}
+                Err(error) if error.needs_dictionary().is_some() => {
+                    self.decompress
+                        .set_dictionary(self.dictionary)
+                        .map_err(|_| {
+                            io::Error::new(
+                                io::ErrorKind::InvalidData,
+                                "Data was compressed with a different dictionary",
+                            )
+                        })?;
+                }
+                Err(error) => return Err(io::Error::new(io::ErrorKind::InvalidData, error)),
+            }
+
+            if written > 0 {
+                return Ok(written);
+            }
+        }
+
+        Ok(0)
+    }
+}
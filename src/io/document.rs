@@ -0,0 +1,598 @@
+use super::{
+    child_depth,
+    copy_tag_body,
+    read_root_id,
+    read_tag_body_dyn,
+    transform_key,
+    DuplicateKeyPolicy,
+    LengthRepair,
+    LengthRepairAction,
+    NbtIoError,
+    NbtReadBuilder,
+    NbtSpans,
+    NegativeLengthPolicy,
+    PartialReadError,
+    PeekInfo,
+    ReadReport,
+    TagExtension,
+    TagSpan,
+    UnknownListType,
+    MAX_DEPTH,
+};
+use crate::{
+    cast,
+    path::{push_index, push_key},
+    raw,
+    NbtCompound,
+    NbtList,
+    NbtTag,
+    NbtTagKind,
+};
+use std::{io, io::Read};
+
+// The lists and compounds enclosing the tag being read, which are needed to check whether the rest
+// of the document can be read from a given offset
+enum Frame {
+    Compound {
+        depth: usize,
+    },
+    List {
+        tag_id: u8,
+        remaining: usize,
+        depth: usize,
+    },
+}
+
+// How the rest of a document reads from an offset tried while recovering from a negative length
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Continuation {
+    Invalid,
+    // The root compound ended before the end of the document
+    Trailing,
+    // The document ended with the root compound, or the lookahead was read without error
+    Complete,
+}
+
+// The maximum number of bytes read from each offset tried when recovering from a negative length
+const RECOVERY_LOOKAHEAD: usize = 16 * 1024;
+
+// The number of bytes which may be read while trying offsets for every byte in the document
+pub(crate) const RECOVERY_BUDGET_FACTOR: usize = 64;
+
+// Tracks the number of bytes read from the underlying reader, saturating rather than wrapping
+// around for documents larger than the address space
+pub(crate) struct CountingReader<R> {
+    pub(crate) inner: R,
+    pub(crate) position: usize,
+}
+
+impl<R> CountingReader<R> {
+    pub(crate) fn new(inner: R) -> Self {
+        CountingReader { inner, position: 0 }
+    }
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.position = self.position.saturating_add(read);
+        Ok(read)
+    }
+}
+
+// Reads documents with the options of an `NbtReadBuilder` which the plain reader does not support,
+// keeping track of the path to the tag being read so that anything changed along the way can be
+// reported
+pub(crate) struct DocumentReader<'a, 'b, 'e, R> {
+    pub(crate) reader: CountingReader<R>,
+    pub(crate) options: &'b NbtReadBuilder<'a>,
+    // The whole uncompressed document, which is only needed to recover from negative lengths
+    pub(crate) document: Option<&'b [u8]>,
+    pub(crate) extension: Option<&'e mut dyn TagExtension>,
+    pub(crate) path: String,
+    frames: Vec<Frame>,
+    // The spans recorded so far, if they are being recorded
+    pub(crate) spans: Option<Vec<TagSpan>>,
+    // The offset of the last negative length read, which is reported once it is recovered from
+    pub(crate) negative_offset: usize,
+    // The number of bytes which may still be read while trying offsets to recover from
+    pub(crate) recovery_budget: usize,
+    pub(crate) report: ReadReport,
+}
+
+impl<'a, 'b, 'e, R: Read> DocumentReader<'a, 'b, 'e, R> {
+    pub(crate) fn new(reader: R, options: &'b NbtReadBuilder<'a>) -> Self {
+        DocumentReader {
+            reader: CountingReader::new(reader),
+            options,
+            document: None,
+            extension: None,
+            path: String::new(),
+            frames: Vec::new(),
+            spans: None,
+            negative_offset: 0,
+            recovery_budget: 0,
+            report: ReadReport::default(),
+        }
+    }
+
+    pub(crate) fn read_document(mut self) -> Result<(NbtCompound, String, ReadReport), NbtIoError> {
+        #[cfg(feature = "tracing")]
+        let span = crate::trace::read_span();
+
+        if self.options.spans {
+            self.spans = Some(vec![TagSpan {
+                path: String::new(),
+                range: 0 .. 0,
+            }]);
+        }
+
+        let read = self
+            .read_root_header()
+            .and_then(|root_name| Ok((self.read_tag_body(0xA, 0)?, root_name)));
+        let (root, root_name) = match read {
+            Ok((Some(NbtTag::Compound(compound)), root_name)) => (compound, root_name),
+            // Reading stops at the root if its header could not be read, or if an entry with a
+            // negative length could not be recovered from
+            Err(e) if self.options.partial => {
+                self.report.stopped = Some(self.stopped_at(e));
+                (NbtCompound::new(), String::new())
+            }
+            Err(e) => return Err(e),
+            _ => unreachable!(),
+        };
+
+        if let Some(mut spans) = self.spans.take() {
+            spans[0].range.end = self.reader.position;
+            self.report.spans = Some(NbtSpans { spans });
+        }
+
+        #[cfg(feature = "tracing")]
+        crate::trace::record_document(&span, self.reader.position, &root);
+        Ok((root, root_name, self.report))
+    }
+
+    // Reads only the root name and the names and kinds of the root compound's entries
+    pub(crate) fn peek_document(mut self) -> Result<PeekInfo, NbtIoError> {
+        let root_name = self.read_root_header()?;
+        let depth = self.child_depth(0)?;
+        // `copy_tag_body` limits nesting to `MAX_DEPTH`, so the depth is offset to apply the
+        // configured limit instead
+        let copy_depth = depth + (MAX_DEPTH - self.options.max_depth);
+        let mut entries: Vec<(String, NbtTagKind)> = Vec::new();
+        loop {
+            let tag_id = raw::read_u8(&mut self.reader)?;
+            if tag_id == 0x0 {
+                break;
+            }
+
+            let kind = NbtTagKind::from_id(tag_id).ok_or(NbtIoError::InvalidTagId(tag_id))?;
+            let name = self.read_key()?;
+            copy_tag_body(&mut self.reader, tag_id, &mut io::sink(), copy_depth)?;
+            let duplicate = self.options.duplicate_keys != DuplicateKeyPolicy::KeepLast
+                && entries.iter().any(|(entry, _)| *entry == name);
+            if !duplicate || !self.keep_duplicate(&name)? {
+                entries.push((name, kind));
+            }
+            self.path.clear();
+        }
+
+        Ok(PeekInfo { root_name, entries })
+    }
+
+    // Reads the root compound's tag ID and, unless the root is nameless, its name
+    fn read_root_header(&mut self) -> Result<String, NbtIoError> {
+        read_root_id(&mut self.reader)?;
+        if self.options.nameless_root {
+            Ok(String::new())
+        } else {
+            self.read_string()
+        }
+    }
+
+    // Like `child_depth`, but with the configured limit
+    fn child_depth(&self, depth: usize) -> Result<usize, NbtIoError> {
+        if depth < self.options.max_depth {
+            Ok(depth + 1)
+        } else {
+            Err(NbtIoError::ExceededMaxDepth)
+        }
+    }
+
+    // Returns `None` if the tag was dropped or reading stopped at it. Once reading has stopped,
+    // lists and compounds return the elements read so far.
+    fn read_tag_body(&mut self, tag_id: u8, depth: usize) -> Result<Option<NbtTag>, NbtIoError> {
+        let tag = match tag_id {
+            0x9 => {
+                let (depth, tag_id, len) = match self.read_list_header(depth) {
+                    Ok(header) => header,
+                    Err(e) => {
+                        self.stop(e)?;
+                        return Ok(Some(NbtTag::List(NbtList::new())));
+                    }
+                };
+                let custom = tag_id > 0xC && self.extension.is_some();
+                if (tag_id > 0xC && !custom) || (tag_id == 0 && len > 0) {
+                    return self.unknown_list_type(tag_id);
+                }
+
+                let mut list = NbtList::with_capacity(len.min(raw::PREALLOC_LIMIT));
+                list.1 = NbtTagKind::from_id(tag_id);
+                let frame = self.frames.len();
+                for index in 0 .. len {
+                    self.frames.truncate(frame);
+                    self.frames.push(Frame::List {
+                        tag_id,
+                        remaining: len - index - 1,
+                        depth,
+                    });
+                    let path_len = self.path.len();
+                    push_index(&mut self.path, index);
+                    let span = self.open_span(self.reader.position);
+                    let element = self.read_tag_body(tag_id, depth)?;
+                    self.close_span(span, element.is_some());
+                    list.extend(element);
+                    self.path.truncate(path_len);
+                    if self.report.stopped.is_some() {
+                        break;
+                    }
+                }
+                self.frames.truncate(frame);
+
+                NbtTag::List(list)
+            }
+            0xA => {
+                let frame = self.frames.len();
+                self.frames.push(Frame::Compound { depth });
+                let mut compound = NbtCompound::new();
+                let depth = match self.child_depth(depth) {
+                    Ok(depth) => depth,
+                    Err(e) => {
+                        self.stop(e)?;
+                        return Ok(Some(NbtTag::Compound(compound)));
+                    }
+                };
+                loop {
+                    let start = self.reader.position;
+                    let path_len = self.path.len();
+                    let (tag_id, name) = match self.read_entry_header() {
+                        Ok(Some(entry)) => entry,
+                        Ok(None) => break,
+                        Err(e) => {
+                            self.stop(e)?;
+                            break;
+                        }
+                    };
+
+                    let span = self.open_span(start);
+                    let entry_path_len = self.path.len();
+                    match self.read_tag_body(tag_id, depth) {
+                        Ok(Some(tag)) => {
+                            let kept = !compound.contains_key(name.as_str())
+                                || self.keep_duplicate(&name)?;
+                            self.close_span(span, kept);
+                            if kept {
+                                compound.insert(name, tag);
+                            }
+                        }
+                        Ok(None) => self.close_span(span, false),
+                        Err(NbtIoError::NegativeLength(len))
+                            if self.options.negative_lengths == NegativeLengthPolicy::Recover =>
+                        {
+                            self.close_span(span, false);
+                            self.frames.truncate(frame + 1);
+                            self.path.truncate(entry_path_len);
+                            self.recover(start, len)?;
+                        }
+                        Err(e) => return Err(e),
+                    }
+                    self.path.truncate(path_len);
+                    if self.report.stopped.is_some() {
+                        break;
+                    }
+                }
+                self.frames.truncate(frame);
+
+                NbtTag::Compound(compound)
+            }
+            _ => match self.read_value(tag_id, depth) {
+                Ok(Some(tag)) => tag,
+                Ok(None) => return Ok(None),
+                Err(e) => {
+                    self.stop(e)?;
+                    return Ok(None);
+                }
+            },
+        };
+
+        Ok(Some(tag))
+    }
+
+    // Reads any tag other than a list or compound, passing custom tags to the extension
+    fn read_value(&mut self, tag_id: u8, depth: usize) -> Result<Option<NbtTag>, NbtIoError> {
+        let tag = match tag_id {
+            0x7 => {
+                let len = self.read_len()?;
+                let array = raw::read_byte_array(&mut self.reader, len)?;
+                NbtTag::ByteArray(cast::byte_buf_to_signed(array))
+            }
+            0x8 => NbtTag::String(self.read_string()?),
+            0xB => {
+                let len = self.read_len()?;
+                NbtTag::IntArray(raw::read_i32_array(&mut self.reader, len)?)
+            }
+            0xC => {
+                let len = self.read_len()?;
+                NbtTag::LongArray(raw::read_i64_array(&mut self.reader, len)?)
+            }
+            _ => match &mut self.extension {
+                Some(extension) if tag_id > 0xC =>
+                    return extension.read_tag(tag_id, &mut self.reader),
+                _ => read_tag_body_dyn(&mut self.reader, tag_id, depth)?,
+            },
+        };
+
+        Ok(Some(tag))
+    }
+
+    // Returns the depth of the list's elements, their tag ID, and the list's length
+    fn read_list_header(&mut self, depth: usize) -> Result<(usize, u8, usize), NbtIoError> {
+        let depth = self.child_depth(depth)?;
+        let tag_id = raw::read_u8(&mut self.reader)?;
+        Ok((depth, tag_id, self.read_len()?))
+    }
+
+    // Reads the tag ID and key of a compound entry, pushing the key onto the path, or returns `None`
+    // upon reaching TAG_End
+    fn read_entry_header(&mut self) -> Result<Option<(u8, String)>, NbtIoError> {
+        match raw::read_u8(&mut self.reader)? {
+            0x0 => Ok(None),
+            tag_id => Ok(Some((tag_id, self.read_key()?))),
+        }
+    }
+
+    // Returns whether an entry whose key was already read in the same compound replaces the
+    // earlier entry, according to the duplicate key policy
+    fn keep_duplicate(&mut self, key: &str) -> Result<bool, NbtIoError> {
+        #[cfg(feature = "tracing")]
+        crate::trace::duplicate_key(key);
+        #[cfg(not(feature = "tracing"))]
+        let _ = key;
+
+        match self.options.duplicate_keys {
+            DuplicateKeyPolicy::KeepLast => Ok(true),
+            DuplicateKeyPolicy::KeepFirst => Ok(false),
+            DuplicateKeyPolicy::Error => {
+                let path = self.path.clone();
+                self.stop(NbtIoError::DuplicateKey { path })?;
+                Ok(false)
+            }
+        }
+    }
+
+    // Records where reading stopped if partial reads are enabled, or returns the error otherwise.
+    // Negative lengths which may be recovered from are always returned to the enclosing compound.
+    fn stop(&mut self, error: NbtIoError) -> Result<(), NbtIoError> {
+        let recoverable = self.document.is_some() && matches!(error, NbtIoError::NegativeLength(_));
+        if !self.options.partial || recoverable {
+            return Err(error);
+        }
+
+        self.report.stopped = Some(self.stopped_at(error));
+        Ok(())
+    }
+
+    fn stopped_at(&self, error: NbtIoError) -> PartialReadError {
+        PartialReadError {
+            error,
+            path: self.path.clone(),
+            offset: self.reader.position,
+        }
+    }
+
+    // Starts the span of the tag at the current path, which starts at the given offset, returning
+    // its index if spans are being recorded. Spans are started before those of their children so
+    // that they remain sorted by their start offset.
+    fn open_span(&mut self, start: usize) -> Option<usize> {
+        let spans = self.spans.as_mut()?;
+        spans.push(TagSpan {
+            path: self.path.clone(),
+            range: start .. start,
+        });
+        Some(spans.len() - 1)
+    }
+
+    // Ends the given span at the current offset, or removes it along with the spans of its
+    // children if the tag was not kept
+    fn close_span(&mut self, span: Option<usize>, kept: bool) {
+        if let (Some(spans), Some(index)) = (&mut self.spans, span) {
+            if kept {
+                spans[index].range.end = self.reader.position;
+            } else {
+                spans.truncate(index);
+            }
+        }
+    }
+
+    // Reads the length of an array or list, applying the policy for negative lengths
+    fn read_len(&mut self) -> Result<usize, NbtIoError> {
+        let offset = self.reader.position;
+        let len = raw::read_i32(&mut self.reader)?;
+        if len >= 0 {
+            return Ok(len as usize);
+        }
+
+        match self.options.negative_lengths {
+            NegativeLengthPolicy::Error => Err(NbtIoError::NegativeLength(len)),
+            NegativeLengthPolicy::TreatAsZero => {
+                self.report.length_repairs.push(LengthRepair {
+                    path: self.path.clone(),
+                    offset,
+                    length: len,
+                    action: LengthRepairAction::TreatedAsZero,
+                });
+                Ok(0)
+            }
+            NegativeLengthPolicy::Recover => {
+                self.negative_offset = offset;
+                Err(NbtIoError::NegativeLength(len))
+            }
+        }
+    }
+
+    // Drops the compound entry starting at the given offset, which contained the given negative
+    // length, by skipping ahead to where the rest of the document can be read from
+    fn recover(&mut self, start: usize, len: i32) -> Result<(), NbtIoError> {
+        let document = self.document.ok_or(NbtIoError::NegativeLength(len))?;
+        let position = self.reader.position;
+
+        let mut trailing = None;
+        let mut resume = None;
+        for offset in position .. document.len() {
+            if self.recovery_budget == 0 {
+                break;
+            }
+
+            let (continuation, read) = self.continuation_at(document, offset);
+            self.recovery_budget = self.recovery_budget.saturating_sub(read);
+            match continuation {
+                Continuation::Complete => {
+                    resume = Some(offset);
+                    break;
+                }
+                Continuation::Trailing if trailing.is_none() => trailing = Some(offset),
+                _ => {}
+            }
+        }
+        let resume = resume.or(trailing).ok_or(NbtIoError::NegativeLength(len))?;
+
+        self.report.length_repairs.push(LengthRepair {
+            path: self.path.clone(),
+            offset: self.negative_offset,
+            length: len,
+            action: LengthRepairAction::DroppedEntry {
+                skipped: resume - start,
+            },
+        });
+        io::copy(
+            &mut (&mut self.reader).take((resume - position) as u64),
+            &mut io::sink(),
+        )?;
+        Ok(())
+    }
+
+    // Checks the structure of the rest of the document from the given offset, continuing the
+    // innermost enclosing compound, up to the recovery lookahead. Also returns the number of bytes
+    // which were read.
+    fn continuation_at(&self, document: &[u8], offset: usize) -> (Continuation, usize) {
+        let end = document.len().min(offset + RECOVERY_LOOKAHEAD);
+        let mut reader = &document[offset .. end];
+        let sink = &mut io::sink();
+        // Whether a whole compound entry or list element was read, after which running out of
+        // lookahead rather than data means that the rest was readable so far
+        let mut progressed = false;
+        let result = self.frames.iter().rev().try_for_each(|frame| match *frame {
+            Frame::Compound { depth } => {
+                let depth = child_depth(depth)?;
+                loop {
+                    match raw::read_u8(&mut reader)? {
+                        0x0 => return Ok(()),
+                        tag_id @ 0x1 ..= 0xC => {
+                            raw::read_string(&mut reader)?;
+                            copy_tag_body(&mut reader, tag_id, sink, depth)?;
+                            progressed = true;
+                        }
+                        tag_id => return Err(NbtIoError::InvalidTagId(tag_id)),
+                    }
+                }
+            }
+            Frame::List {
+                tag_id,
+                remaining,
+                depth,
+            } => (0 .. remaining).try_for_each(|_| {
+                copy_tag_body(&mut reader, tag_id, sink, depth)?;
+                progressed = true;
+                Ok(())
+            }),
+        });
+
+        let continuation = match result {
+            Ok(()) if reader.is_empty() && end == document.len() => Continuation::Complete,
+            Ok(()) => Continuation::Trailing,
+            Err(NbtIoError::StdIo(error))
+                if progressed
+                    && end < document.len()
+                    && error.kind() == io::ErrorKind::UnexpectedEof =>
+                Continuation::Complete,
+            Err(_) => Continuation::Invalid,
+        };
+        (continuation, end - offset - reader.len())
+    }
+
+    fn unknown_list_type(&mut self, tag_id: u8) -> Result<Option<NbtTag>, NbtIoError> {
+        match self.options.unknown_list_types {
+            UnknownListType::Error => {
+                self.stop(NbtIoError::InvalidTagId(tag_id))?;
+                Ok(Some(NbtTag::List(NbtList::new())))
+            }
+            UnknownListType::SkipRest => {
+                self.report.stopped = Some(self.stopped_at(NbtIoError::InvalidTagId(tag_id)));
+                Ok(None)
+            }
+            UnknownListType::Capture => {
+                let mut remaining = Vec::new();
+                self.reader.read_to_end(&mut remaining)?;
+                Err(NbtIoError::UnknownListType {
+                    tag_id,
+                    path: self.path.clone(),
+                    remaining,
+                })
+            }
+        }
+    }
+
+    // Reads the key of a compound entry, passing it through the key transform, and pushes the
+    // resulting key onto the path
+    fn read_key(&mut self) -> Result<String, NbtIoError> {
+        let (key, fell_back) = self.read_string_with_fallback()?;
+        let key = match self.options.key_transform {
+            Some(transform) => transform_key(
+                key,
+                transform,
+                &mut self.path,
+                &mut self.report.renamed_keys,
+            ),
+            None => {
+                push_key(&mut self.path, &key);
+                key
+            }
+        };
+        if fell_back {
+            self.record_latin1_fallback();
+        }
+        Ok(key)
+    }
+
+    fn read_string(&mut self) -> Result<String, NbtIoError> {
+        let (string, fell_back) = self.read_string_with_fallback()?;
+        if fell_back {
+            self.record_latin1_fallback();
+        }
+        Ok(string)
+    }
+
+    fn read_string_with_fallback(&mut self) -> Result<(String, bool), NbtIoError> {
+        if self.options.latin1_fallback {
+            raw::read_string_with_fallback(&mut self.reader)
+        } else {
+            Ok((raw::read_string(&mut self.reader)?, false))
+        }
+    }
+
+    fn record_latin1_fallback(&mut self) {
+        #[cfg(feature = "tracing")]
+        crate::trace::latin1_fallback(&self.path);
+        self.report.latin1_fallbacks.push(self.path.clone());
+    }
+}
@@ -0,0 +1,86 @@
+use super::{Flavor, NbtIoError, NbtReadBuilder};
+use crate::{NbtCompound, NbtTag};
+use std::io::Read;
+
+/// Reads the payloads of tags with IDs which are not part of the NBT specification, allowing
+/// documents written by forked or modded servers to be read by [`read_nbt_with_extension`].
+///
+/// This trait is implemented for closures with the same signature as [`read_tag`].
+///
+/// [`read_nbt_with_extension`]: crate::io::read_nbt_with_extension
+/// [`read_tag`]: crate::io::TagExtension::read_tag
+pub trait TagExtension {
+    /// Reads the payload of a tag with the given unrecognized ID from the given reader. The name of
+    /// the tag, or the header of the list containing it, has already been read.
+    ///
+    /// The entire payload must be consumed so that the rest of the document can be read. The
+    /// returned tag is inserted in place of the custom tag, or the custom tag is dropped if `None` is
+    /// returned. IDs which are not supported should be rejected with
+    /// [`NbtIoError::InvalidTagId`].
+    ///
+    /// [`NbtIoError::InvalidTagId`]: crate::io::NbtIoError::InvalidTagId
+    fn read_tag(&mut self, tag_id: u8, reader: &mut dyn Read)
+        -> Result<Option<NbtTag>, NbtIoError>;
+}
+
+impl<F> TagExtension for F
+where F: FnMut(u8, &mut dyn Read) -> Result<Option<NbtTag>, NbtIoError>
+{
+    #[inline]
+    fn read_tag(
+        &mut self,
+        tag_id: u8,
+        reader: &mut dyn Read,
+    ) -> Result<Option<NbtTag>, NbtIoError> {
+        self(tag_id, reader)
+    }
+}
+
+/// Reads the given flavor of NBT data from the given reader just like [`read_nbt`], but routes
+/// tags with unrecognized IDs to the given extension rather than failing with
+/// [`NbtIoError::InvalidTagId`]. Custom IDs are supported both for compound entries and for the
+/// element type of lists.
+///
+/// This is a shorthand for [`NbtReadBuilder::read_from_with_extension`] with only the flavor set.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::io::{self, Flavor, NbtIoError};
+/// use std::io::Read;
+///
+/// // A compound containing a tag with ID 99, named "x", whose payload is a single byte
+/// let bytes = [0x0A, 0, 0, 99, 0, 1, b'x', 42, 0];
+/// assert!(io::read_nbt(&mut &bytes[..], Flavor::Uncompressed).is_err());
+///
+/// let mut extension = |tag_id: u8, reader: &mut dyn Read| match tag_id {
+///     99 => {
+///         let mut payload = [0u8; 1];
+///         reader.read_exact(&mut payload)?;
+///         Ok(Some(NbtTag::Short(payload[0].into())))
+///     }
+///     _ => Err(NbtIoError::InvalidTagId(tag_id)),
+/// };
+///
+/// let (nbt, _) =
+///     io::read_nbt_with_extension(&mut &bytes[..], Flavor::Uncompressed, &mut extension)?;
+/// assert_eq!(nbt, compound! { "x": 42i16 });
+/// # Ok::<(), NbtIoError>(())
+/// ```
+///
+/// [`read_nbt`]: crate::io::read_nbt
+/// [`NbtIoError::InvalidTagId`]: crate::io::NbtIoError::InvalidTagId
+/// [`NbtReadBuilder::read_from_with_extension`]: crate::io::NbtReadBuilder::read_from_with_extension
+pub fn read_nbt_with_extension<R, E>(
+    reader: &mut R,
+    flavor: Flavor,
+    extension: &mut E,
+) -> Result<(NbtCompound, String), NbtIoError>
+where
+    R: Read,
+    E: TagExtension + ?Sized,
+{
+    let (root, root_name, _) = NbtReadBuilder::new()
+        .flavor(flavor)
+        .read_from_with_extension(reader, extension)?;
+    Ok((root, root_name))
+}
@@ -0,0 +1,218 @@
+use super::{write_nbt, Flavor, NbtIoError, NbtReadBuilder};
+use crate::{raw, NbtCompound};
+use std::{
+    fmt::Debug,
+    io::{IoSlice, Read, Write},
+};
+
+/// Describes the length prefix written before each frame of NBT data by [`read_nbt_framed`] and
+/// [`write_nbt_framed`].
+///
+/// [`read_nbt_framed`]: crate::io::read_nbt_framed
+/// [`write_nbt_framed`]: crate::io::write_nbt_framed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LengthPrefix {
+    /// A VarInt, as used by Minecraft's network protocol.
+    VarInt,
+    /// A big-endian unsigned 32-bit integer.
+    U32,
+}
+
+/// Reads a single frame of NBT data consisting of a length prefix followed by exactly that many
+/// bytes of the given flavor of NBT data, returning the resulting NBT compound and associated root
+/// name.
+///
+/// Frames longer than `max_frame_size` bytes are rejected before any of their payload is read or
+/// buffered. Since the whole frame is consumed up front, the reader is always left positioned at
+/// the start of the next frame when this function succeeds, even if the reader is not buffered.
+///
+/// This is a shorthand for [`NbtReadBuilder::read_framed`] with only the flavor set.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::io::{self, Flavor, LengthPrefix, NbtIoError};
+/// use std::io::Cursor;
+///
+/// let first = compound! { "id": 1i32 };
+/// let second = compound! { "id": 2i32 };
+///
+/// let mut stream = Vec::new();
+/// io::write_nbt_framed(&mut stream, LengthPrefix::VarInt, None, &first, Flavor::Uncompressed)?;
+/// io::write_nbt_framed(&mut stream, LengthPrefix::VarInt, None, &second, Flavor::Uncompressed)?;
+///
+/// let mut stream = Cursor::new(stream);
+/// let read = |stream: &mut Cursor<Vec<u8>>| {
+///     io::read_nbt_framed(stream, LengthPrefix::VarInt, 1024, Flavor::Uncompressed)
+/// };
+/// assert_eq!(read(&mut stream)?.0, first);
+/// assert_eq!(read(&mut stream)?.0, second);
+///
+/// let mut stream = Cursor::new(vec![0xFF, 0xFF, 0x03]);
+/// assert!(matches!(read(&mut stream), Err(NbtIoError::FrameTooLarge { size: 65535, max: 1024 })));
+/// # Ok::<(), NbtIoError>(())
+/// ```
+///
+/// [`NbtReadBuilder::read_framed`]: crate::io::NbtReadBuilder::read_framed
+pub fn read_nbt_framed<R: Read>(
+    reader: &mut R,
+    prefix: LengthPrefix,
+    max_frame_size: usize,
+    flavor: Flavor,
+) -> Result<(NbtCompound, String), NbtIoError> {
+    NbtReadBuilder::new()
+        .flavor(flavor)
+        .read_framed(reader, prefix, max_frame_size)
+}
+
+// Reads a length prefix followed by that many bytes, rejecting frames longer than `max_frame_size`
+// before any of their payload is read
+pub(crate) fn read_frame<R: Read>(
+    reader: &mut R,
+    prefix: LengthPrefix,
+    max_frame_size: usize,
+) -> Result<Vec<u8>, NbtIoError> {
+    let size = match prefix {
+        LengthPrefix::VarInt => match raw::read_varint(reader)? {
+            Some(size) if size >= 0 => size as usize,
+            _ => return Err(NbtIoError::InvalidFrameLength),
+        },
+        LengthPrefix::U32 => raw::read_u32(reader)? as usize,
+    };
+    if size > max_frame_size {
+        return Err(NbtIoError::FrameTooLarge {
+            size,
+            max: max_frame_size,
+        });
+    }
+
+    Ok(raw::read_byte_array(reader, size)?)
+}
+
+/// Writes the given flavor of NBT data to the given writer as a single frame, preceded by its
+/// length in bytes. If no root name is provided, an empty string is used.
+///
+/// The data is encoded into a buffer before anything is written, so nothing is written to the
+/// writer if encoding fails or if the frame is too long for the given prefix. See [`NbtFrame`] to
+/// write the length prefix and the data separately, such as with a vectored write.
+///
+/// [`NbtFrame`]: crate::io::NbtFrame
+pub fn write_nbt_framed<W: Write>(
+    writer: &mut W,
+    prefix: LengthPrefix,
+    root_name: Option<&str>,
+    root: &NbtCompound,
+    flavor: Flavor,
+) -> Result<(), NbtIoError> {
+    let frame = NbtFrame::encode(prefix, root_name, root, flavor)?;
+    writer.write_all(frame.header())?;
+    writer.write_all(frame.payload())?;
+    Ok(())
+}
+
+/// A frame of NBT data encoded by [`NbtFrame::encode`], kept as a separate length prefix and
+/// payload so that the two can be handed to a vectored write, or copied into a protocol buffer
+/// after a packet header, without first being joined in another buffer.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::io::{self, Flavor, LengthPrefix, NbtFrame, NbtIoError};
+/// use std::io::Write;
+///
+/// let nbt = compound! { "id": 1i32 };
+/// let frame = NbtFrame::encode(LengthPrefix::VarInt, None, &nbt, Flavor::Uncompressed)?;
+/// assert_eq!(frame.header(), [frame.payload().len() as u8]);
+///
+/// let mut stream = Vec::new();
+/// assert_eq!(stream.write_vectored(&frame.io_slices())?, frame.len());
+///
+/// let mut stream = stream.as_slice();
+/// let (read, _) = io::read_nbt_framed(&mut stream, LengthPrefix::VarInt, 1024, Flavor::Uncompressed)?;
+/// assert_eq!(read, nbt);
+/// # Ok::<(), NbtIoError>(())
+/// ```
+///
+/// [`NbtFrame::encode`]: crate::io::NbtFrame::encode
+#[derive(Debug, Clone)]
+pub struct NbtFrame {
+    header: [u8; 5],
+    header_len: usize,
+    payload: Vec<u8>,
+}
+
+impl NbtFrame {
+    /// Encodes the given flavor of NBT data as a frame with the given length prefix. If no root
+    /// name is provided, an empty string is used.
+    pub fn encode(
+        prefix: LengthPrefix,
+        root_name: Option<&str>,
+        root: &NbtCompound,
+        flavor: Flavor,
+    ) -> Result<Self, NbtIoError> {
+        let mut payload = Vec::new();
+        write_nbt(&mut payload, root_name, root, flavor)?;
+
+        let mut header = [0u8; 5];
+        let mut header_writer = &mut header[..];
+        match prefix {
+            LengthPrefix::VarInt => {
+                let size = i32::try_from(payload.len()).map_err(|_| NbtIoError::FrameTooLarge {
+                    size: payload.len(),
+                    max: i32::MAX as usize,
+                })?;
+                raw::write_varint(&mut header_writer, size)?;
+            }
+            LengthPrefix::U32 => {
+                let size = u32::try_from(payload.len()).map_err(|_| NbtIoError::FrameTooLarge {
+                    size: payload.len(),
+                    max: u32::MAX as usize,
+                })?;
+                raw::write_u32(&mut header_writer, size)?;
+            }
+        }
+        let unused = header_writer.len();
+        let header_len = header.len() - unused;
+
+        Ok(NbtFrame {
+            header,
+            header_len,
+            payload,
+        })
+    }
+
+    /// Returns the length prefix of this frame.
+    #[inline]
+    pub fn header(&self) -> &[u8] {
+        &self.header[.. self.header_len]
+    }
+
+    /// Returns the encoded NBT data of this frame.
+    #[inline]
+    pub fn payload(&self) -> &[u8] {
+        &self.payload
+    }
+
+    /// Returns the total length of this frame in bytes, including its length prefix.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.header_len + self.payload.len()
+    }
+
+    /// Returns whether this frame is empty, which is never the case since a frame always contains
+    /// at least the header of its root compound.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the length prefix and the payload of this frame as slices for a vectored write.
+    #[inline]
+    pub fn io_slices(&self) -> [IoSlice<'_>; 2] {
+        [IoSlice::new(self.header()), IoSlice::new(self.payload())]
+    }
+
+    /// Returns the payload of this frame, discarding its length prefix.
+    #[inline]
+    pub fn into_payload(self) -> Vec<u8> {
+        self.payload
+    }
+}
@@ -0,0 +1,124 @@
+use super::{write_tag_body, NbtIoError};
+use crate::{raw, NbtTag};
+use std::{fmt, fmt::Display, io};
+
+/// Writes an annotated hexdump of the given tag, encoded as a named tag with an empty name, to the
+/// given writer. Each line starts with the offset of its first byte, and a new line is started at
+/// every tag header, list element and end tag, which is labeled with the kind of the tag and its
+/// name or index.
+pub(crate) fn write_hexdump<W: fmt::Write>(
+    writer: &mut W,
+    tag: &NbtTag,
+    bytes_per_line: usize,
+) -> Result<(), NbtIoError> {
+    let mut bytes = Vec::new();
+    let mut annotations = vec![(0, annotation(tag, NbtTag::string_to_snbt("")))];
+    raw::write_u8(&mut bytes, raw::id_for_tag(Some(tag)))?;
+    raw::write_string(&mut bytes, "")?;
+    write_tag_body_annotated(&mut bytes, tag, &mut annotations)?;
+
+    let bytes_per_line = bytes_per_line.max(1);
+    let ends = annotations
+        .iter()
+        .skip(1)
+        .map(|&(offset, _)| offset)
+        .chain(std::iter::once(bytes.len()));
+    for ((start, label), end) in annotations.iter().zip(ends) {
+        let segment = &bytes[*start .. end];
+        write_hexdump_line(
+            writer,
+            *start,
+            &segment[.. segment.len().min(bytes_per_line)],
+            bytes_per_line,
+            Some(label),
+        )?;
+        for (line, chunk) in segment.chunks(bytes_per_line).enumerate().skip(1) {
+            write_hexdump_line(
+                writer,
+                start + line * bytes_per_line,
+                chunk,
+                bytes_per_line,
+                None,
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+fn write_hexdump_line<W: fmt::Write>(
+    writer: &mut W,
+    offset: usize,
+    chunk: &[u8],
+    bytes_per_line: usize,
+    label: Option<&str>,
+) -> Result<(), NbtIoError> {
+    let result = (|| {
+        write!(writer, "{:08x} ", offset)?;
+        for byte in chunk {
+            write!(writer, " {:02x}", byte)?;
+        }
+        if let Some(label) = label {
+            let padding = (bytes_per_line - chunk.len()) * 3;
+            write!(writer, "{:padding$}  {}", "", label, padding = padding)?;
+        }
+        writeln!(writer)
+    })();
+    result.map_err(|error| NbtIoError::StdIo(io::Error::other(error)))
+}
+
+// Labels tags by their kind and name or index, and numbers and strings by their value as well
+fn annotation(tag: &NbtTag, name: impl Display) -> String {
+    match tag {
+        NbtTag::List(_)
+        | NbtTag::Compound(_)
+        | NbtTag::ByteArray(_)
+        | NbtTag::IntArray(_)
+        | NbtTag::LongArray(_) => format!("{} {}", tag.kind(), name),
+        _ => format!("{} {} = {}", tag.kind(), name, tag),
+    }
+}
+
+fn write_tag_body_annotated(
+    buffer: &mut Vec<u8>,
+    tag: &NbtTag,
+    annotations: &mut Vec<(usize, String)>,
+) -> Result<(), NbtIoError> {
+    match tag {
+        NbtTag::List(list) if !list.is_empty() => {
+            let list_type = raw::id_for_tag(Some(&list[0]));
+            raw::write_u8(buffer, list_type)?;
+            raw::write_len(buffer, list.len())?;
+
+            for (index, element) in list.iter().enumerate() {
+                let tag_id = raw::id_for_tag(Some(element));
+                if tag_id != list_type {
+                    return Err(NbtIoError::NonHomogenousList {
+                        list_type,
+                        encountered_type: tag_id,
+                    });
+                }
+
+                annotations.push((
+                    buffer.len(),
+                    annotation(element, format_args!("[{}]", index)),
+                ));
+                write_tag_body_annotated(buffer, element, annotations)?;
+            }
+        }
+        NbtTag::Compound(compound) => {
+            for (name, tag) in compound.inner() {
+                annotations.push((buffer.len(), annotation(tag, NbtTag::string_to_snbt(name))));
+                raw::write_u8(buffer, raw::id_for_tag(Some(tag)))?;
+                raw::write_string(buffer, name)?;
+                write_tag_body_annotated(buffer, tag, annotations)?;
+            }
+
+            annotations.push((buffer.len(), "End".to_owned()));
+            raw::write_u8(buffer, raw::id_for_tag(None))?;
+        }
+        _ => write_tag_body(buffer, tag)?,
+    }
+
+    Ok(())
+}
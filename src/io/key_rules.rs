@@ -0,0 +1,190 @@
+use super::{Flavor, NbtIoError, NbtReadBuilder, NbtWriteBuilder};
+use crate::{
+    path::{push_index, push_key},
+    NbtCompound,
+    NbtTag,
+};
+use std::{
+    fmt,
+    fmt::{Debug, Display, Formatter},
+    io::{Read, Write},
+};
+
+/// Rules for rejecting suspicious compound keys, such as those written by buggy tools, before they
+/// propagate into the game. By default no keys are rejected.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::io::{self, Flavor, KeyIssue, KeyRules, NbtIoError};
+///
+/// let nbt = compound! { "Items": [{ "id": "minecraft:stone", "": 1i8 }] };
+/// let rules = KeyRules::strict();
+///
+/// let mut bytes = Vec::new();
+/// let error = io::write_nbt_with_key_rules(&mut bytes, None, &nbt, Flavor::Uncompressed, &rules)
+///     .unwrap_err();
+/// assert!(matches!(
+///     error,
+///     NbtIoError::SuspiciousKey { path, issue: KeyIssue::Empty } if path == r#"Items[0]."""#
+/// ));
+/// assert!(bytes.is_empty());
+///
+/// io::write_nbt(&mut bytes, None, &nbt, Flavor::Uncompressed)?;
+/// assert!(io::read_nbt_with_key_rules(&mut bytes.as_slice(), Flavor::Uncompressed, &rules).is_err());
+/// assert!(io::read_nbt_with_key_rules(&mut bytes.as_slice(), Flavor::Uncompressed, &KeyRules::new()).is_ok());
+/// # Ok::<(), NbtIoError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct KeyRules {
+    deny_empty: bool,
+    deny_control_chars: bool,
+}
+
+impl KeyRules {
+    /// Returns rules which accept every key.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns rules which reject every kind of suspicious key.
+    pub fn strict() -> Self {
+        KeyRules {
+            deny_empty: true,
+            deny_control_chars: true,
+        }
+    }
+
+    /// Sets whether empty keys are rejected.
+    pub fn deny_empty(mut self, deny: bool) -> Self {
+        self.deny_empty = deny;
+        self
+    }
+
+    /// Sets whether keys containing control characters, such as newlines or NUL, are rejected.
+    pub fn deny_control_chars(mut self, deny: bool) -> Self {
+        self.deny_control_chars = deny;
+        self
+    }
+
+    /// Checks the given key against these rules, returning why it was rejected if it was.
+    pub fn check(&self, key: &str) -> Option<KeyIssue> {
+        if self.deny_empty && key.is_empty() {
+            return Some(KeyIssue::Empty);
+        }
+
+        if self.deny_control_chars {
+            if let Some(ch) = key.chars().find(|ch| ch.is_control()) {
+                return Some(KeyIssue::ControlChar(ch));
+            }
+        }
+
+        None
+    }
+
+    /// Checks every key in the given compound, recursively, against these rules, returning an error
+    /// for the first key which was rejected.
+    pub fn validate(&self, compound: &NbtCompound) -> Result<(), NbtIoError> {
+        if *self == KeyRules::new() {
+            return Ok(());
+        }
+
+        self.validate_compound(compound, &mut String::new())
+    }
+
+    fn validate_compound(
+        &self,
+        compound: &NbtCompound,
+        path: &mut String,
+    ) -> Result<(), NbtIoError> {
+        for (key, tag) in compound.inner() {
+            let path_len = path.len();
+            push_key(path, key);
+            if let Some(issue) = self.check(key) {
+                return Err(NbtIoError::SuspiciousKey {
+                    path: path.clone(),
+                    issue,
+                });
+            }
+            self.validate_tag(tag, path)?;
+            path.truncate(path_len);
+        }
+
+        Ok(())
+    }
+
+    fn validate_tag(&self, tag: &NbtTag, path: &mut String) -> Result<(), NbtIoError> {
+        match tag {
+            NbtTag::Compound(compound) => self.validate_compound(compound, path),
+            NbtTag::List(list) => {
+                for (index, tag) in list.iter().enumerate() {
+                    let path_len = path.len();
+                    push_index(path, index);
+                    self.validate_tag(tag, path)?;
+                    path.truncate(path_len);
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Describes why a key was rejected by a set of [`KeyRules`].
+///
+/// [`KeyRules`]: crate::io::KeyRules
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyIssue {
+    /// The key was empty.
+    Empty,
+    /// The key contained the given control character.
+    ControlChar(char),
+}
+
+impl Display for KeyIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyIssue::Empty => write!(f, "key is empty"),
+            KeyIssue::ControlChar(ch) =>
+                write!(f, "key contains control character U+{:04X}", *ch as u32),
+        }
+    }
+}
+
+/// Reads the given flavor of NBT data from the given reader just like [`read_nbt`], then checks
+/// every key in the resulting compound against the given rules.
+///
+/// This is a shorthand for [`NbtReadBuilder`] with only the flavor and key rules set.
+///
+/// [`read_nbt`]: crate::io::read_nbt
+/// [`NbtReadBuilder`]: crate::io::NbtReadBuilder
+pub fn read_nbt_with_key_rules<R: Read>(
+    reader: &mut R,
+    flavor: Flavor,
+    rules: &KeyRules,
+) -> Result<(NbtCompound, String), NbtIoError> {
+    NbtReadBuilder::new()
+        .flavor(flavor)
+        .key_rules(*rules)
+        .read_from(reader)
+}
+
+/// Checks every key in the given compound against the given rules, then writes the given flavor of
+/// NBT data to the given writer just like [`write_nbt`]. Nothing is written if a key is rejected.
+///
+/// This is a shorthand for [`NbtWriteBuilder`] with only the flavor, root name and key rules set.
+///
+/// [`write_nbt`]: crate::io::write_nbt
+/// [`NbtWriteBuilder`]: crate::io::NbtWriteBuilder
+pub fn write_nbt_with_key_rules<W: Write>(
+    writer: &mut W,
+    root_name: Option<&str>,
+    root: &NbtCompound,
+    flavor: Flavor,
+    rules: &KeyRules,
+) -> Result<(), NbtIoError> {
+    NbtWriteBuilder::new()
+        .flavor(flavor)
+        .root_name(root_name.unwrap_or(""))
+        .key_rules(*rules)
+        .write_to(writer, root)
+}
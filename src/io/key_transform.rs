@@ -0,0 +1,187 @@
+#[cfg(feature = "serde")]
+use super::{child_depth, copy_tag_body, read_list_header};
+use super::{Flavor, NbtIoError, NbtReadBuilder};
+#[cfg(feature = "serde")]
+use crate::{path::push_index, raw};
+use crate::{path::push_key, NbtCompound};
+use std::{fmt::Debug, io::Read};
+
+/// Changes the keys of compound entries while NBT data is read by a builder with
+/// [`NbtReadBuilder::key_transform`] set, or deserialized with
+/// [`DeserializerBuilder::deserialize_from_with_key_transform`], so that data written by tools with
+/// differing conventions can be normalized without rewriting every compound afterwards.
+///
+/// This trait is implemented for closures with the same signature as [`transform`].
+///
+/// [`NbtReadBuilder::key_transform`]: crate::io::NbtReadBuilder::key_transform
+/// [`DeserializerBuilder::deserialize_from_with_key_transform`]: crate::serde::DeserializerBuilder::deserialize_from_with_key_transform
+/// [`transform`]: crate::io::KeyTransform::transform
+pub trait KeyTransform {
+    /// Returns the key the entry with the given key is stored under instead, or `None` to keep the
+    /// key as it is. Returning the same key is equivalent to returning `None`.
+    fn transform(&self, key: &str) -> Option<String>;
+}
+
+impl<F> KeyTransform for F
+where F: Fn(&str) -> Option<String>
+{
+    #[inline]
+    fn transform(&self, key: &str) -> Option<String> {
+        self(key)
+    }
+}
+
+/// A key changed by a [`KeyTransform`] while reading NBT data.
+///
+/// [`KeyTransform`]: crate::io::KeyTransform
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenamedKey {
+    path: String,
+    original: String,
+}
+
+impl RenamedKey {
+    /// Returns the path of the renamed entry in the resulting data, which uses the new keys of the
+    /// entry and of every compound enclosing it.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the key the entry had in the original data.
+    pub fn original(&self) -> &str {
+        &self.original
+    }
+}
+
+/// Reads the given flavor of NBT data from the given reader just like [`read_nbt`], but passes
+/// the key of every compound entry through the given transform, and returns a record of the keys
+/// which were changed alongside the compound.
+///
+/// If the transform maps several keys of a compound to the same key, the entry read last is kept,
+/// just as with duplicate keys in the data. The root name is not transformed.
+///
+/// This is a shorthand for [`NbtReadBuilder`] with only the flavor and key transform set.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::io::{self, Flavor, NbtIoError};
+///
+/// let nbt = compound! { "Pos": [1i32, 2i32], "mymod:Data": { "Owner": "Alex", "level": 3i32 } };
+/// let bytes = io::write_nbt_bytes(None, &nbt, Flavor::Uncompressed)?;
+///
+/// let normalize = |key: &str| {
+///     let key = key.strip_prefix("mymod:").unwrap_or(key);
+///     Some(key.to_lowercase())
+/// };
+/// let (nbt, _, mut renamed) =
+///     io::read_nbt_with_key_transform(&mut bytes.as_slice(), Flavor::Uncompressed, &normalize)?;
+/// assert_eq!(nbt, compound! { "pos": [1i32, 2i32], "data": { "owner": "Alex", "level": 3i32 } });
+///
+/// renamed.sort_by(|a, b| a.path().cmp(b.path()));
+/// let renamed = renamed
+///     .iter()
+///     .map(|key| (key.path(), key.original()))
+///     .collect::<Vec<_>>();
+/// assert_eq!(renamed, [("data", "mymod:Data"), ("data.owner", "Owner"), ("pos", "Pos")]);
+/// # Ok::<(), NbtIoError>(())
+/// ```
+///
+/// [`read_nbt`]: crate::io::read_nbt
+/// [`NbtReadBuilder`]: crate::io::NbtReadBuilder
+pub fn read_nbt_with_key_transform<R, K>(
+    reader: &mut R,
+    flavor: Flavor,
+    transform: &K,
+) -> Result<(NbtCompound, String, Vec<RenamedKey>), NbtIoError>
+where
+    R: Read,
+    K: KeyTransform,
+{
+    let (root, root_name, report) = NbtReadBuilder::new()
+        .flavor(flavor)
+        .key_transform(transform)
+        .read_from_with_report(reader)?;
+    Ok((root, root_name, report.renamed_keys))
+}
+
+// Copies the given tag like `copy_tag_body`, but passes the keys of compound entries through the
+// given transform
+#[cfg(feature = "serde")]
+pub(crate) fn copy_tag_body_with_key_transform<R, K>(
+    reader: &mut R,
+    tag_id: u8,
+    dest: &mut Vec<u8>,
+    transform: &K,
+    path: &mut String,
+    renamed: &mut Vec<RenamedKey>,
+    depth: usize,
+) -> Result<(), NbtIoError>
+where
+    R: Read,
+    K: KeyTransform + ?Sized,
+{
+    match tag_id {
+        0x9 => {
+            let depth = child_depth(depth)?;
+            let (tag_id, len) = read_list_header(reader)?;
+            if tag_id > 0xC || (tag_id == 0 && len > 0) {
+                return Err(NbtIoError::InvalidTagId(tag_id));
+            }
+
+            dest.push(tag_id);
+            dest.extend_from_slice(&(len as i32).to_be_bytes());
+            for index in 0 .. len {
+                let path_len = path.len();
+                push_index(path, index);
+                copy_tag_body_with_key_transform(
+                    reader, tag_id, dest, transform, path, renamed, depth,
+                )?;
+                path.truncate(path_len);
+            }
+            Ok(())
+        }
+        0xA => {
+            let depth = child_depth(depth)?;
+            loop {
+                let tag_id = raw::read_u8(reader)?;
+                dest.push(tag_id);
+                if tag_id == 0x0 {
+                    return Ok(());
+                }
+
+                let path_len = path.len();
+                let name = transform_key(raw::read_string(reader)?, transform, path, renamed);
+                raw::write_string(dest, &name)?;
+                copy_tag_body_with_key_transform(
+                    reader, tag_id, dest, transform, path, renamed, depth,
+                )?;
+                path.truncate(path_len);
+            }
+        }
+        _ => copy_tag_body(reader, tag_id, dest, depth),
+    }
+}
+
+// Applies the transform to the given key and pushes the resulting key onto the path, recording
+// the entry if its key changed
+pub(crate) fn transform_key<K: KeyTransform + ?Sized>(
+    key: String,
+    transform: &K,
+    path: &mut String,
+    renamed: &mut Vec<RenamedKey>,
+) -> String {
+    match transform.transform(&key) {
+        Some(new_key) if new_key != key => {
+            push_key(path, &new_key);
+            renamed.push(RenamedKey {
+                path: path.clone(),
+                original: key,
+            });
+            new_key
+        }
+        _ => {
+            push_key(path, &key);
+            key
+        }
+    }
+}
@@ -0,0 +1,916 @@
+#[cfg(feature = "base64")]
+mod base64;
+mod builder;
+#[cfg(feature = "bytes")]
+mod bytes;
+mod compression;
+#[cfg(feature = "zlib-dictionary")]
+mod dictionary;
+mod document;
+mod extension;
+mod framing;
+mod hexdump;
+mod key_rules;
+mod key_transform;
+mod peek;
+mod recovery;
+mod spans;
+
+#[cfg(feature = "base64")]
+pub use self::base64::{read_nbt_base64, write_nbt_base64};
+#[cfg(all(feature = "bytes", feature = "serde"))]
+pub(crate) use self::bytes::with_buf_decoder;
+#[cfg(feature = "bytes")]
+pub use self::bytes::{read_nbt_buf, write_nbt_buf};
+pub use crate::cached::CachedDocument;
+pub use builder::{NbtReadBuilder, NbtWriteBuilder, ReadReport};
+#[cfg(feature = "serde")]
+pub(crate) use compression::with_decoder;
+pub use compression::{auto_compression_level, recompress, recompress_validated};
+#[cfg(feature = "zlib-dictionary")]
+use compression::{read_to_stream_end, Encoder};
+pub(crate) use compression::{with_encoder, Decoder};
+#[cfg(feature = "zlib-dictionary")]
+pub use dictionary::{read_nbt_with_dictionary, write_nbt_with_dictionary};
+pub use extension::{read_nbt_with_extension, TagExtension};
+pub use framing::{read_nbt_framed, write_nbt_framed, LengthPrefix, NbtFrame};
+pub(crate) use hexdump::write_hexdump;
+pub use key_rules::{read_nbt_with_key_rules, write_nbt_with_key_rules, KeyIssue, KeyRules};
+#[cfg(feature = "serde")]
+pub(crate) use key_transform::copy_tag_body_with_key_transform;
+pub use key_transform::{read_nbt_with_key_transform, KeyTransform, RenamedKey};
+pub use peek::{peek_header, PeekInfo};
+pub use recovery::{
+    read_nbt_partial,
+    read_nbt_with_latin1_fallback,
+    read_nbt_with_negative_lengths,
+    read_nbt_with_unknown_list_types,
+    DuplicateKeyPolicy,
+    LengthRepair,
+    LengthRepairAction,
+    NegativeLengthPolicy,
+    PartialReadError,
+    UnknownListType,
+};
+pub use spans::{read_nbt_with_spans, NbtSpans, TagSpan};
+
+use compression::buffered;
+#[cfg(feature = "zlib-dictionary")]
+use dictionary::DictionaryDecoder;
+use document::{CountingReader, DocumentReader, RECOVERY_BUDGET_FACTOR};
+use framing::read_frame;
+use key_transform::transform_key;
+
+use crate::{cast, raw, snbt::SnbtError, NbtCompound, NbtList, NbtTag, NbtTagKind};
+use flate2::{bufread, Compression};
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    io::{self, BufReader, Read, Write},
+};
+
+/// Describes the flavors of NBT data: uncompressed, Zlib compressed, Gz compressed, and raw
+/// deflate compressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Flavor {
+    /// Uncompressed NBT data.
+    Uncompressed,
+    /// Zlib compressed NBT data. When writing, the default compression level will be used.
+    ZlibCompressed,
+    /// Zlib compressed NBT data with the given compression level.
+    ZlibCompressedWith(Compression),
+    /// Gz compressed NBT data. When writing, the default compression level will be used.
+    GzCompressed,
+    /// Gz compressed NBT data with the given compression level.
+    GzCompressedWith(Compression),
+    /// NBT data compressed with raw deflate, without the header and checksum of the Zlib or Gz
+    /// formats, as stored by some non-vanilla tools. When writing, the default compression level
+    /// will be used.
+    ///
+    /// Since raw deflate data has no header, this flavor is never detected, and has to be set
+    /// explicitly when reading.
+    RawDeflate,
+    /// NBT data compressed with raw deflate with the given compression level.
+    RawDeflateWith(Compression),
+}
+
+/// The maximum number of lists and compounds, including the root compound, which may be nested
+/// within each other in data read by this crate. This is the same limit imposed by Minecraft.
+///
+/// Reading binary NBT or SNBT which exceeds this limit fails with
+/// [`NbtIoError::ExceededMaxDepth`] or [`ParserErrorType::ExceededMaxDepth`] respectively,
+/// rather than overflowing the stack. Optimized builds can read data nested this deeply on
+/// threads with the standard library's default stack size, but unoptimized builds use
+/// considerably more stack space per level and may need a larger stack to do so.
+///
+/// [`ParserErrorType::ExceededMaxDepth`]: crate::snbt::ParserErrorType::ExceededMaxDepth
+pub const MAX_DEPTH: usize = 512;
+
+/// Reads the given flavor of NBT data from the given reader, returning the resulting NBT
+/// compound and associated root name.
+///
+/// This is a shorthand for [`NbtReadBuilder`] with only the flavor set.
+///
+/// [`NbtReadBuilder`]: crate::io::NbtReadBuilder
+pub fn read_nbt<R: Read>(
+    reader: &mut R,
+    flavor: Flavor,
+) -> Result<(NbtCompound, String), NbtIoError> {
+    NbtReadBuilder::new().flavor(flavor).read_from(reader)
+}
+
+fn read_nbt_uncompressed<R: Read>(reader: &mut R) -> Result<(NbtCompound, String), NbtIoError> {
+    read_document(reader, false)
+}
+
+// Reads an uncompressed document, whose root compound has no name if `nameless_root` is set, in
+// which case an empty root name is returned
+fn read_document<R: Read>(
+    reader: &mut R,
+    nameless_root: bool,
+) -> Result<(NbtCompound, String), NbtIoError> {
+    #[cfg(feature = "tracing")]
+    let span = crate::trace::read_span();
+    #[cfg(feature = "tracing")]
+    let reader = &mut CountingReader::new(reader);
+
+    let root_name = if nameless_root {
+        read_root_id(reader)?;
+        String::new()
+    } else {
+        read_root_header(reader)?
+    };
+    let root = match read_tag_body_const::<_, 0xA>(reader, 0) {
+        Ok(NbtTag::Compound(compound)) => compound,
+        Err(e) => return Err(e),
+        _ => unreachable!(),
+    };
+
+    #[cfg(feature = "tracing")]
+    crate::trace::record_document(&span, reader.position, &root);
+    Ok((root, root_name))
+}
+
+// Reads the root compound's tag ID and name, returning the name
+pub(crate) fn read_root_header<R: Read>(reader: &mut R) -> Result<String, NbtIoError> {
+    read_root_id(reader)?;
+    raw::read_string(reader)
+}
+
+fn read_root_id<R: Read>(reader: &mut R) -> Result<(), NbtIoError> {
+    let root_id = raw::read_u8(reader)?;
+    if root_id != 0xA {
+        return Err(NbtIoError::TagTypeMismatch {
+            expected: 0xA,
+            found: root_id,
+        });
+    }
+
+    Ok(())
+}
+
+/// Reads NBT data from the given reader, detecting whether it is compressed, and returns the
+/// resulting NBT compound along with information about the document.
+///
+/// The flavor is detected from the first two bytes of data: Gz data always starts with the bytes
+/// `1F 8B`, Zlib data with a header whose first byte is usually `78`, and uncompressed data with
+/// the ID of the root compound. The detected flavor does not carry a compression level.
+///
+/// Unlike [`read_nbt`], this function consumes the remainder of a compressed stream after the root
+/// compound, so that its checksum is verified and [`DocumentInfo::compressed_len`] is exact.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::io::{self, Flavor};
+///
+/// let compound = compound! { "data": [I; 1, 2, 3] };
+/// let mut bytes = Vec::new();
+/// io::write_nbt(&mut bytes, Some("root"), &compound, Flavor::GzCompressed)?;
+///
+/// let (read, info) = io::read_nbt_with_info(&mut bytes.as_slice())?;
+/// assert_eq!(read, compound);
+/// assert_eq!(info.root_name(), "root");
+/// assert_eq!(info.flavor(), Flavor::GzCompressed);
+/// assert_eq!(info.compressed_len(), bytes.len());
+/// assert_eq!(info.uncompressed_len(), 31);
+/// # Ok::<(), io::NbtIoError>(())
+/// ```
+///
+/// [`read_nbt`]: crate::io::read_nbt
+/// [`DocumentInfo::compressed_len`]: crate::io::DocumentInfo::compressed_len
+pub fn read_nbt_with_info<R: Read>(
+    reader: &mut R,
+) -> Result<(NbtCompound, DocumentInfo), NbtIoError> {
+    let mut magic = [0u8; 2];
+    reader.read_exact(&mut magic)?;
+    let flavor = detect_flavor(&magic);
+
+    let mut source = CountingReader::new(magic.chain(reader));
+    let (root, root_name, uncompressed_len, compressed_len) = match flavor {
+        Flavor::Uncompressed => {
+            let (root, root_name) = read_nbt_uncompressed(&mut source)?;
+            (root, root_name, source.position, source.position)
+        }
+        _ => {
+            // Buffering is required to stop reading at the exact end of the compressed stream,
+            // after which any bytes read ahead are excluded from the compressed length
+            let mut buffered = BufReader::new(&mut source);
+            let (root, root_name, uncompressed_len) = if flavor == Flavor::GzCompressed {
+                read_decoded_with_len(bufread::GzDecoder::new(&mut buffered))?
+            } else {
+                read_decoded_with_len(bufread::ZlibDecoder::new(&mut buffered))?
+            };
+            let read_ahead = buffered.buffer().len();
+            (
+                root,
+                root_name,
+                uncompressed_len,
+                source.position - read_ahead,
+            )
+        }
+    };
+
+    Ok((root, DocumentInfo {
+        root_name,
+        flavor,
+        compressed_len,
+        uncompressed_len,
+    }))
+}
+
+// Detects the flavor of a document from its first two bytes, assuming that it is uncompressed if
+// fewer than two bytes are given
+pub(crate) fn detect_flavor(magic: &[u8]) -> Flavor {
+    match *magic {
+        [0x1F, 0x8B, ..] => Flavor::GzCompressed,
+        [0x78, second, ..] if u16::from_be_bytes([0x78, second]).is_multiple_of(31) =>
+            Flavor::ZlibCompressed,
+        _ => Flavor::Uncompressed,
+    }
+}
+
+// Reads a document from the given decoder, then drains the decoder so that the end of the stream
+// is reached, returning the document and its uncompressed length
+fn read_decoded_with_len<D: Read>(
+    mut decoder: D,
+) -> Result<(NbtCompound, String, usize), NbtIoError> {
+    let mut counted = CountingReader::new(&mut decoder);
+    let (root, root_name) = read_nbt_uncompressed(&mut counted)?;
+    let uncompressed_len = counted.position;
+    io::copy(&mut decoder, &mut io::sink())?;
+    Ok((root, root_name, uncompressed_len))
+}
+
+/// Information about a binary NBT document, as returned by [`read_nbt_with_info`].
+///
+/// [`read_nbt_with_info`]: crate::io::read_nbt_with_info
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentInfo {
+    root_name: String,
+    flavor: Flavor,
+    compressed_len: usize,
+    uncompressed_len: usize,
+}
+
+impl DocumentInfo {
+    /// Returns the name of the root compound.
+    pub fn root_name(&self) -> &str {
+        &self.root_name
+    }
+
+    /// Returns the detected flavor of the document. This is one of [`Flavor::Uncompressed`],
+    /// [`Flavor::ZlibCompressed`] or [`Flavor::GzCompressed`].
+    ///
+    /// [`Flavor::Uncompressed`]: crate::io::Flavor::Uncompressed
+    /// [`Flavor::ZlibCompressed`]: crate::io::Flavor::ZlibCompressed
+    /// [`Flavor::GzCompressed`]: crate::io::Flavor::GzCompressed
+    pub fn flavor(&self) -> Flavor {
+        self.flavor
+    }
+
+    /// Returns the number of bytes the document occupied in the reader, including any compression
+    /// headers and trailers.
+    pub fn compressed_len(&self) -> usize {
+        self.compressed_len
+    }
+
+    /// Returns the number of bytes of uncompressed NBT data in the document. This is the same as
+    /// the compressed length for uncompressed documents.
+    pub fn uncompressed_len(&self) -> usize {
+        self.uncompressed_len
+    }
+}
+
+pub(crate) fn read_tag_body_dyn<R: Read>(
+    reader: &mut R,
+    tag_id: u8,
+    depth: usize,
+) -> Result<NbtTag, NbtIoError> {
+    macro_rules! drive_reader {
+        ($($id:literal)*) => {
+            match tag_id {
+                $( $id => read_tag_body_const::<_, $id>(reader, depth), )*
+                _ => Err(NbtIoError::InvalidTagId(tag_id))
+            }
+        };
+    }
+
+    drive_reader!(0x1 0x2 0x3 0x4 0x5 0x6 0x7 0x8 0x9 0xA 0xB 0xC)
+}
+
+// The depth is the number of lists and compounds enclosing the tag being read
+#[inline]
+fn read_tag_body_const<R: Read, const TAG_ID: u8>(
+    reader: &mut R,
+    depth: usize,
+) -> Result<NbtTag, NbtIoError> {
+    let tag = match TAG_ID {
+        0x1 => NbtTag::Byte(raw::read_i8(reader)?),
+        0x2 => NbtTag::Short(raw::read_i16(reader)?),
+        0x3 => NbtTag::Int(raw::read_i32(reader)?),
+        0x4 => NbtTag::Long(raw::read_i64(reader)?),
+        0x5 => NbtTag::Float(raw::read_f32(reader)?),
+        0x6 => NbtTag::Double(raw::read_f64(reader)?),
+        0x7 => {
+            let len = raw::read_len(reader)?;
+            let array = raw::read_byte_array(reader, len)?;
+            NbtTag::ByteArray(cast::byte_buf_to_signed(array))
+        }
+        0x8 => NbtTag::String(raw::read_string(reader)?),
+        0x9 => {
+            let depth = child_depth(depth)?;
+            let tag_id = raw::read_u8(reader)?;
+            let len = raw::read_len(reader)?;
+
+            // Make sure we don't have a list of TAG_End unless it's empty or an invalid type
+            if tag_id > 0xC || (tag_id == 0 && len > 0) {
+                return Err(NbtIoError::InvalidTagId(tag_id));
+            }
+
+            let mut list = NbtList::with_capacity(len.min(raw::PREALLOC_LIMIT));
+            list.1 = NbtTagKind::from_id(tag_id);
+            if len == 0 {
+                return Ok(NbtTag::List(list));
+            }
+
+            macro_rules! drive_reader {
+                ($($id:literal)*) => {
+                    match tag_id {
+                        $(
+                            $id => {
+                                for _ in 0 .. len {
+                                    list.push(read_tag_body_const::<_, $id>(reader, depth)?);
+                                }
+                            },
+                        )*
+                        _ => return Err(NbtIoError::InvalidTagId(tag_id))
+                    }
+                };
+            }
+
+            drive_reader!(0x1 0x2 0x3 0x4 0x5 0x6 0x7 0x8 0x9 0xA 0xB 0xC);
+
+            NbtTag::List(list)
+        }
+        0xA => {
+            let depth = child_depth(depth)?;
+            let mut compound = NbtCompound::new();
+            let mut tag_id = raw::read_u8(reader)?;
+
+            // Read until TAG_End
+            while tag_id != 0x0 {
+                let name = raw::read_string(reader)?;
+                let tag = read_tag_body_dyn(reader, tag_id, depth)?;
+                #[cfg(feature = "tracing")]
+                if compound.contains_key(name.as_str()) {
+                    crate::trace::duplicate_key(&name);
+                }
+                compound.insert(name, tag);
+                tag_id = raw::read_u8(reader)?;
+            }
+
+            NbtTag::Compound(compound)
+        }
+        0xB => {
+            let len = raw::read_len(reader)?;
+            NbtTag::IntArray(raw::read_i32_array(reader, len)?)
+        }
+        0xC => {
+            let len = raw::read_len(reader)?;
+            NbtTag::LongArray(raw::read_i64_array(reader, len)?)
+        }
+        _ => unreachable!("read_tag_body_const called with unchecked TAG_ID"),
+    };
+
+    Ok(tag)
+}
+
+// Writes the payload of a tag to `dest` exactly as it was read, checking only that its structure
+// is well formed. Strings are copied without being decoded.
+pub(crate) fn copy_tag_body<R: Read, W: Write + ?Sized>(
+    reader: &mut R,
+    tag_id: u8,
+    dest: &mut W,
+    depth: usize,
+) -> Result<(), NbtIoError> {
+    match tag_id {
+        0x1 => copy_exact(reader, dest, 1),
+        0x2 => copy_exact(reader, dest, 2),
+        0x3 | 0x5 => copy_exact(reader, dest, 4),
+        0x4 | 0x6 => copy_exact(reader, dest, 8),
+        0x7 | 0xB | 0xC => {
+            let len = raw::read_len(reader)?;
+            dest.write_all(&(len as i32).to_be_bytes())?;
+            let width = match tag_id {
+                0x7 => 1,
+                0xB => 4,
+                _ => 8,
+            };
+            copy_exact(reader, dest, len as u64 * width)
+        }
+        0x8 => {
+            let len = raw::read_u16(reader)?;
+            dest.write_all(&len.to_be_bytes())?;
+            copy_exact(reader, dest, len as u64)
+        }
+        0x9 => {
+            let depth = child_depth(depth)?;
+            let (tag_id, len) = read_list_header(reader)?;
+            if tag_id > 0xC || (tag_id == 0 && len > 0) {
+                return Err(NbtIoError::InvalidTagId(tag_id));
+            }
+
+            dest.write_all(&[tag_id])?;
+            dest.write_all(&(len as i32).to_be_bytes())?;
+            for _ in 0 .. len {
+                copy_tag_body(reader, tag_id, dest, depth)?;
+            }
+            Ok(())
+        }
+        0xA => {
+            let depth = child_depth(depth)?;
+            loop {
+                let tag_id = raw::read_u8(reader)?;
+                dest.write_all(&[tag_id])?;
+                if tag_id == 0x0 {
+                    return Ok(());
+                }
+
+                copy_tag_body(reader, 0x8, dest, depth)?;
+                copy_tag_body(reader, tag_id, dest, depth)?;
+            }
+        }
+        _ => Err(NbtIoError::InvalidTagId(tag_id)),
+    }
+}
+
+fn copy_exact<R: Read, W: Write + ?Sized>(
+    reader: &mut R,
+    dest: &mut W,
+    len: u64,
+) -> Result<(), NbtIoError> {
+    let copied = io::copy(&mut reader.take(len), dest)?;
+    if copied < len {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+    }
+
+    Ok(())
+}
+
+fn read_list_header<R: Read>(reader: &mut R) -> Result<(u8, usize), NbtIoError> {
+    Ok((raw::read_u8(reader)?, raw::read_len(reader)?))
+}
+
+// Returns the depth of the children of a list or compound at the given depth, or an error if they
+// would be nested too deeply
+#[inline]
+pub(crate) fn child_depth(depth: usize) -> Result<usize, NbtIoError> {
+    if depth < MAX_DEPTH {
+        Ok(depth + 1)
+    } else {
+        Err(NbtIoError::ExceededMaxDepth)
+    }
+}
+
+/// Writes the given flavor of NBT data to the given writer. If no root name is provided, and empty
+/// string is used.
+///
+/// This is a shorthand for [`NbtWriteBuilder`] with only the flavor and root name set.
+///
+/// NBT cannot represent arrays or lists with more than `i32::MAX` elements, nor strings which are
+/// longer than 65535 bytes once encoded. Writing such a tag results in an error rather than a
+/// truncated length, although the data preceding it will already have been written.
+///
+/// [`NbtWriteBuilder`]: crate::io::NbtWriteBuilder
+pub fn write_nbt<W: Write>(
+    writer: &mut W,
+    root_name: Option<&str>,
+    root: &NbtCompound,
+    flavor: Flavor,
+) -> Result<(), NbtIoError> {
+    NbtWriteBuilder::new()
+        .flavor(flavor)
+        .root_name(root_name.unwrap_or(""))
+        .write_to(writer, root)
+}
+
+/// Reads NBT data from the given bytes, detecting whether it is compressed in the same way as
+/// [`read_nbt_with_info`], and returns the resulting NBT compound and associated root name.
+///
+/// This is a shorthand for [`NbtReadBuilder::read_bytes`] with the default options. Use the builder
+/// to read other flavors, such as [`Flavor::RawDeflate`], which cannot be detected.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::io::{self, Flavor, NbtIoError};
+///
+/// let compound = compound! { "name": "Ferris" };
+/// for flavor in [Flavor::Uncompressed, Flavor::ZlibCompressed, Flavor::GzCompressed] {
+///     let bytes = io::write_nbt_bytes(Some("root"), &compound, flavor)?;
+///     assert_eq!(io::read_nbt_bytes(&bytes)?, (compound.clone(), "root".to_owned()));
+/// }
+/// # Ok::<(), NbtIoError>(())
+/// ```
+///
+/// [`read_nbt_with_info`]: crate::io::read_nbt_with_info
+/// [`NbtReadBuilder::read_bytes`]: crate::io::NbtReadBuilder::read_bytes
+/// [`Flavor::RawDeflate`]: crate::io::Flavor::RawDeflate
+pub fn read_nbt_bytes(bytes: &[u8]) -> Result<(NbtCompound, String), NbtIoError> {
+    NbtReadBuilder::new().read_bytes(bytes)
+}
+
+/// Writes the given flavor of NBT data to a new buffer and returns it. If no root name is provided,
+/// an empty string is used.
+///
+/// See [`read_nbt_bytes`] for an example.
+///
+/// [`read_nbt_bytes`]: crate::io::read_nbt_bytes
+pub fn write_nbt_bytes(
+    root_name: Option<&str>,
+    root: &NbtCompound,
+    flavor: Flavor,
+) -> Result<Vec<u8>, NbtIoError> {
+    let mut bytes = Vec::new();
+    write_nbt(&mut bytes, root_name, root, flavor)?;
+    Ok(bytes)
+}
+
+/// Writes the given tag compound with the given name to the provided writer, writing only the raw
+/// NBT data without any compression. If no name is given, the root compound is written without
+/// one.
+fn write_nbt_uncompressed<W>(
+    writer: &mut W,
+    root_name: Option<&str>,
+    root: &NbtCompound,
+) -> Result<(), NbtIoError>
+where
+    W: Write,
+{
+    #[cfg(feature = "tracing")]
+    let span = crate::trace::write_span();
+    #[cfg(feature = "tracing")]
+    let writer = &mut crate::trace::CountingWriter::new(writer);
+
+    // Compound ID
+    raw::write_u8(writer, 0xA)?;
+    if let Some(root_name) = root_name {
+        raw::write_string(writer, root_name)?;
+    }
+    for (name, tag) in root.inner() {
+        raw::write_u8(writer, raw::id_for_tag(Some(tag)))?;
+        raw::write_string(writer, name)?;
+        write_tag_body(writer, tag)?;
+    }
+    raw::write_u8(writer, raw::id_for_tag(None))?;
+
+    #[cfg(feature = "tracing")]
+    crate::trace::record_document(&span, writer.position, root);
+    Ok(())
+}
+
+pub(crate) fn write_tag_body<W: Write>(writer: &mut W, tag: &NbtTag) -> Result<(), NbtIoError> {
+    match tag {
+        &NbtTag::Byte(value) => raw::write_i8(writer, value)?,
+        &NbtTag::Short(value) => raw::write_i16(writer, value)?,
+        &NbtTag::Int(value) => raw::write_i32(writer, value)?,
+        &NbtTag::Long(value) => raw::write_i64(writer, value)?,
+        &NbtTag::Float(value) => raw::write_f32(writer, value)?,
+        &NbtTag::Double(value) => raw::write_f64(writer, value)?,
+        NbtTag::ByteArray(value) => {
+            raw::write_len(writer, value.len())?;
+            writer.write_all(cast::bytes_to_unsigned(value.as_slice()))?;
+        }
+        NbtTag::String(value) => raw::write_string(writer, value)?,
+        NbtTag::List(value) =>
+            if value.is_empty() {
+                let list_type = value.1.map_or(raw::id_for_tag(None), NbtTagKind::id);
+                writer.write_all(&[list_type, 0, 0, 0, 0])?;
+            } else {
+                let list_type = raw::id_for_tag(Some(&value[0]));
+                raw::write_u8(writer, list_type)?;
+                raw::write_len(writer, value.len())?;
+
+                for sub_tag in value.as_ref() {
+                    let tag_id = raw::id_for_tag(Some(sub_tag));
+                    if tag_id != list_type {
+                        return Err(NbtIoError::NonHomogenousList {
+                            list_type,
+                            encountered_type: tag_id,
+                        });
+                    }
+
+                    write_tag_body(writer, sub_tag)?;
+                }
+            },
+        NbtTag::Compound(value) => {
+            for (name, tag) in value.inner() {
+                raw::write_u8(writer, raw::id_for_tag(Some(tag)))?;
+                raw::write_string(writer, name)?;
+                write_tag_body(writer, tag)?;
+            }
+
+            // TAG_End
+            raw::write_u8(writer, raw::id_for_tag(None))?;
+        }
+        NbtTag::IntArray(value) => {
+            raw::write_len(writer, value.len())?;
+
+            for &int in value.iter() {
+                raw::write_i32(writer, int)?;
+            }
+        }
+        NbtTag::LongArray(value) => {
+            raw::write_len(writer, value.len())?;
+
+            for &long in value.iter() {
+                raw::write_i64(writer, long)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Describes an error which occurred during the reading or writing of NBT data.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum NbtIoError {
+    /// A native I/O error.
+    StdIo(io::Error),
+    /// No root tag was found. All NBT data must start with a valid compound tag.
+    MissingRootTag,
+    /// A sequential data structure was found to be non-homogenous. All sequential structures
+    /// in NBT data are homogenous.
+    NonHomogenousList {
+        /// The list type.
+        list_type: u8,
+        /// The encountered type.
+        encountered_type: u8,
+    },
+    /// A serializer built with [`debug_checks`] enabled was given a non-homogenous sequence.
+    ///
+    /// [`debug_checks`]: crate::serde::SerializerBuilder::debug_checks
+    NonHomogenousField {
+        /// The path to the first element whose type differs from the rest of the sequence, using
+        /// the same format as [`NbtPath`].
+        ///
+        /// [`NbtPath`]: crate::path::NbtPath
+        path: String,
+        /// The list type.
+        list_type: u8,
+        /// The encountered type.
+        encountered_type: u8,
+    },
+    /// A type requested an option to be read from a list. Since options are indicated by the
+    /// absence or presence of a tag, and since all sequential types are length-prefixed,
+    /// options cannot exists within arrays in NBT data.
+    OptionInList,
+    /// A sequential type without a definite length was passed to a serializer.
+    MissingLength,
+    /// An invalid tag ID was encountered.
+    InvalidTagId(u8),
+    /// The first tag ID was expected, but the second was found.
+    TagTypeMismatch {
+        /// The expected ID.
+        expected: u8,
+        /// The found ID.
+        found: u8,
+    },
+    /// A sequential type was expected, but another was found.
+    ExpectedSeq,
+    /// An enum representation was expected, but another was found.
+    ExpectedEnum,
+    /// An invalid map key was encountered.
+    InvalidKey,
+    /// An invalid enum variant was encountered.
+    InvalidEnumVariant,
+    /// An invalid cesu8 string was encountered.
+    InvalidCesu8String,
+    /// An unsupported type was passed to a serializer or queried from a deserializer.
+    UnsupportedType(&'static str),
+    /// An SNBT string read from a stream failed to parse.
+    Snbt(SnbtError),
+    /// A frame's length prefix was malformed or negative.
+    InvalidFrameLength,
+    /// A frame exceeded the maximum allowed size.
+    FrameTooLarge {
+        /// The size of the frame in bytes.
+        size: usize,
+        /// The maximum allowed size in bytes.
+        max: usize,
+    },
+    /// A frame contained the given number of bytes after the end of its NBT data.
+    TrailingFrameBytes(usize),
+    /// A NaN or infinite float was passed to a serializer configured to reject them.
+    NonFiniteFloat(f64),
+    /// A compound contained a key which was rejected by the [`KeyRules`] in use.
+    ///
+    /// [`KeyRules`]: crate::io::KeyRules
+    SuspiciousKey {
+        /// The path to the tag with the rejected key.
+        path: String,
+        /// Why the key was rejected.
+        issue: KeyIssue,
+    },
+    /// A compound contained the same key more than once, and was read with
+    /// [`DuplicateKeyPolicy::Error`].
+    ///
+    /// [`DuplicateKeyPolicy::Error`]: crate::io::DuplicateKeyPolicy::Error
+    DuplicateKey {
+        /// The path to the second entry with the key, using the same format as [`NbtPath`].
+        ///
+        /// [`NbtPath`]: crate::path::NbtPath
+        path: String,
+    },
+    /// A list with an unknown element type was read with [`UnknownListType::Capture`].
+    ///
+    /// [`UnknownListType::Capture`]: crate::io::UnknownListType::Capture
+    UnknownListType {
+        /// The element type of the list.
+        tag_id: u8,
+        /// The path to the list, using the same format as [`NbtPath`].
+        ///
+        /// [`NbtPath`]: crate::path::NbtPath
+        path: String,
+        /// The bytes following the header of the list, up to the end of the document. For
+        /// compressed flavors, these are decompressed.
+        remaining: Vec<u8>,
+    },
+    /// An array or list was prefixed with the given negative length.
+    NegativeLength(i32),
+    /// Lists and compounds were nested more than [`MAX_DEPTH`] levels deep.
+    ///
+    /// [`MAX_DEPTH`]: crate::io::MAX_DEPTH
+    ExceededMaxDepth,
+    /// Data read by the [`compact`] module was malformed for the given reason.
+    ///
+    /// [`compact`]: crate::compact
+    InvalidCompactData(&'static str),
+    /// An array or list with the given number of elements was written, which exceeds the maximum
+    /// length of `i32::MAX` elements.
+    LengthTooLarge(usize),
+    /// A string which is the given number of bytes long once encoded was written, which exceeds
+    /// the maximum length of 65535 bytes.
+    StringTooLong(usize),
+    /// A custom error message.
+    Custom(Box<str>),
+}
+
+#[cfg(feature = "serde")]
+impl serde::ser::Error for NbtIoError {
+    fn custom<T>(msg: T) -> Self
+    where T: Display {
+        NbtIoError::Custom(msg.to_string().into_boxed_str())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::de::Error for NbtIoError {
+    fn custom<T>(msg: T) -> Self
+    where T: Display {
+        NbtIoError::Custom(msg.to_string().into_boxed_str())
+    }
+}
+
+impl From<io::Error> for NbtIoError {
+    fn from(error: io::Error) -> Self {
+        NbtIoError::StdIo(error)
+    }
+}
+
+impl From<SnbtError> for NbtIoError {
+    fn from(error: SnbtError) -> Self {
+        NbtIoError::Snbt(error)
+    }
+}
+
+impl Display for NbtIoError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            NbtIoError::StdIo(error) => write!(f, "{}", error),
+            NbtIoError::MissingRootTag =>
+                write!(f, "NBT tree does not start with a valid root tag."),
+            &NbtIoError::NonHomogenousList {
+                list_type,
+                encountered_type,
+            } => write!(
+                f,
+                "Encountered non-homogenous list or sequential type: expected {:X} but found {:X}",
+                list_type, encountered_type
+            ),
+            NbtIoError::NonHomogenousField {
+                path,
+                list_type,
+                encountered_type,
+            } => write!(
+                f,
+                "Encountered non-homogenous sequence at {}: expected {:X} but found {:X}",
+                path, list_type, encountered_type
+            ),
+            NbtIoError::OptionInList => write!(
+                f,
+                "Minecraft's NBT format cannot support options in sequential data structures"
+            ),
+            NbtIoError::MissingLength => write!(
+                f,
+                "Sequential types must have an initial computable length to be serializable"
+            ),
+            &NbtIoError::InvalidTagId(id) => write!(
+                f,
+                "Encountered invalid tag ID 0x{:X} during deserialization",
+                id
+            ),
+            &NbtIoError::TagTypeMismatch { expected, found } => write!(
+                f,
+                "Tag type mismatch: expected 0x{:X} but found 0x{:X}",
+                expected, found
+            ),
+            NbtIoError::ExpectedSeq => write!(f, "Expected sequential tag type (array)"),
+            NbtIoError::ExpectedEnum => write!(
+                f,
+                "Encountered invalid enum representation in the NBT tag tree"
+            ),
+            NbtIoError::InvalidKey => write!(f, "Map keys must be a valid string"),
+            NbtIoError::InvalidEnumVariant =>
+                write!(f, "Encountered invalid enum variant while deserializing"),
+            NbtIoError::InvalidCesu8String => write!(f, "Encountered invalid CESU8 string"),
+            NbtIoError::UnsupportedType(ty) =>
+                write!(f, "Type {} is not supported by Minecraft's NBT format", ty),
+            NbtIoError::Snbt(error) => write!(f, "{}", error),
+            NbtIoError::InvalidFrameLength => write!(f, "Encountered invalid frame length prefix"),
+            &NbtIoError::FrameTooLarge { size, max } => write!(
+                f,
+                "Frame of {} bytes exceeds the maximum size of {} bytes",
+                size, max
+            ),
+            &NbtIoError::TrailingFrameBytes(count) => write!(
+                f,
+                "Frame contains {} bytes after the end of its NBT data",
+                count
+            ),
+            &NbtIoError::NonFiniteFloat(value) => write!(
+                f,
+                "Encountered non-finite float {} during serialization",
+                value
+            ),
+            NbtIoError::SuspiciousKey { path, issue } =>
+                write!(f, "Rejected key at {}: {}", path, issue),
+            NbtIoError::DuplicateKey { path } => write!(f, "Encountered duplicate key at {}", path),
+            NbtIoError::UnknownListType {
+                tag_id,
+                path,
+                remaining,
+            } => write!(
+                f,
+                "Encountered list with unknown element type 0x{:X} at {}, followed by {} bytes",
+                tag_id,
+                path,
+                remaining.len()
+            ),
+            &NbtIoError::NegativeLength(len) => write!(f, "Encountered negative length {}", len),
+            NbtIoError::ExceededMaxDepth => write!(
+                f,
+                "Lists and compounds are nested more than {} levels deep",
+                MAX_DEPTH
+            ),
+            NbtIoError::InvalidCompactData(reason) =>
+                write!(f, "Encountered invalid compact NBT data: {}", reason),
+            &NbtIoError::LengthTooLarge(len) => write!(
+                f,
+                "Length {} exceeds the maximum array or list length of {}",
+                len,
+                i32::MAX
+            ),
+            &NbtIoError::StringTooLong(len) => write!(
+                f,
+                "String of {} bytes exceeds the maximum string length of {} bytes",
+                len,
+                u16::MAX
+            ),
+            NbtIoError::Custom(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl Error for NbtIoError {}
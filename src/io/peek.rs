@@ -0,0 +1,84 @@
+use super::{Flavor, NbtIoError, NbtReadBuilder};
+use crate::NbtTagKind;
+use std::{fmt::Debug, io::Read};
+
+/// Reads the root name of a document and the names and kinds of the entries in its root compound,
+/// without reading the values of those entries. This is much cheaper than [`read_nbt`] when only a
+/// preview of a document is needed, such as when listing many files, since nested values are
+/// skipped over rather than being decoded and allocated.
+///
+/// The structure of the skipped values is still checked, so malformed documents are rejected in
+/// the same way as by [`read_nbt`], except that strings within nested values are not decoded.
+///
+/// This is a shorthand for [`NbtReadBuilder::peek_header`] with only the flavor set.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::io::{self, Flavor};
+///
+/// let level = compound! {
+///     "Data": { "LevelName": "New World", "GameRules": { "keepInventory": "false" } },
+///     "Version": 19133
+/// };
+/// let mut bytes = Vec::new();
+/// io::write_nbt(&mut bytes, Some(""), &level, Flavor::GzCompressed)?;
+///
+/// let info = io::peek_header(&mut bytes.as_slice(), Flavor::GzCompressed)?;
+/// assert_eq!(info.root_name(), "");
+/// assert_eq!(info.len(), 2);
+/// assert_eq!(info.get("Data"), Some(NbtTagKind::Compound));
+/// assert_eq!(info.get("Version"), Some(NbtTagKind::Int));
+/// assert_eq!(info.get("LevelName"), None);
+/// # Ok::<(), io::NbtIoError>(())
+/// ```
+///
+/// [`read_nbt`]: crate::io::read_nbt
+/// [`NbtReadBuilder::peek_header`]: crate::io::NbtReadBuilder::peek_header
+pub fn peek_header<R: Read>(reader: &mut R, flavor: Flavor) -> Result<PeekInfo, NbtIoError> {
+    NbtReadBuilder::new().flavor(flavor).peek_header(reader)
+}
+
+/// A shallow preview of a binary NBT document, as returned by [`peek_header`].
+///
+/// [`peek_header`]: crate::io::peek_header
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeekInfo {
+    pub(crate) root_name: String,
+    pub(crate) entries: Vec<(String, NbtTagKind)>,
+}
+
+impl PeekInfo {
+    /// Returns the name of the root compound.
+    pub fn root_name(&self) -> &str {
+        &self.root_name
+    }
+
+    /// Returns an iterator over the names and kinds of the entries in the root compound, in the
+    /// order in which they appear in the document.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, NbtTagKind)> + '_ {
+        self.entries
+            .iter()
+            .map(|(name, kind)| (name.as_str(), *kind))
+    }
+
+    /// Returns the kind of the entry with the given name in the root compound, or `None` if there
+    /// is no such entry. If the name appears more than once, the kind of its last entry is
+    /// returned, matching the value kept when the document is read.
+    pub fn get(&self, name: &str) -> Option<NbtTagKind> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|(entry, _)| entry == name)
+            .map(|&(_, kind)| kind)
+    }
+
+    /// Returns the number of entries in the root compound.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the root compound is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
@@ -0,0 +1,429 @@
+use super::{Flavor, NbtIoError, NbtReadBuilder};
+use crate::NbtCompound;
+use std::{
+    error::Error,
+    fmt,
+    fmt::{Debug, Display, Formatter},
+    io::Read,
+};
+
+/// Reads the given flavor of NBT data from the given reader, salvaging as much data as possible if
+/// the data is truncated or corrupted.
+///
+/// Unlike [`read_nbt`], this function does not fail outright when it encounters an error. Instead, it
+/// returns the root compound containing every tag which was successfully read before the error,
+/// the root name (or an empty string if it could not be read), and an error describing where
+/// reading stopped. Lists and compounds which were only partially read are included with the
+/// elements read so far, whereas a primitive or array which could not be read in full is omitted.
+///
+/// This is a shorthand for [`NbtReadBuilder`] with only the flavor and [`partial`] set.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::io::{self, Flavor};
+///
+/// let nbt = compound! { "name": "Steve", "inventory": [{ "id": "stone" }, { "id": "dirt" }] };
+/// let mut bytes = Vec::new();
+/// io::write_nbt(&mut bytes, Some("Player"), &nbt, Flavor::Uncompressed).unwrap();
+///
+/// // Simulate a crash part way through writing the file
+/// bytes.truncate(bytes.len() - 5);
+///
+/// let (partial, root_name, error) = io::read_nbt_partial(&mut bytes.as_slice(), Flavor::Uncompressed);
+/// assert_eq!(root_name, "Player");
+/// assert!(error.is_some());
+/// assert_eq!(partial.get::<_, &NbtList>("inventory").unwrap().len(), 2);
+/// ```
+///
+/// [`read_nbt`]: crate::io::read_nbt
+/// [`NbtReadBuilder`]: crate::io::NbtReadBuilder
+/// [`partial`]: crate::io::NbtReadBuilder::partial
+pub fn read_nbt_partial<R: Read>(
+    reader: &mut R,
+    flavor: Flavor,
+) -> (NbtCompound, String, Option<PartialReadError>) {
+    let read = NbtReadBuilder::new()
+        .flavor(flavor)
+        .partial(true)
+        .read_from_with_report(reader);
+    match read {
+        Ok((root, root_name, report)) => (root, root_name, report.stopped),
+        // Partial reads only fail before the document is reached, which these options never do
+        Err(error) => (
+            NbtCompound::new(),
+            String::new(),
+            Some(PartialReadError {
+                error,
+                path: String::new(),
+                offset: 0,
+            }),
+        ),
+    }
+}
+
+/// Reads the given flavor of NBT data from the given reader just like [`read_nbt`], but decodes
+/// strings which are not valid Java CESU-8 as Latin-1 rather than failing with
+/// [`NbtIoError::InvalidCesu8String`]. Such strings can be found in files written by very old
+/// versions of Minecraft and third-party tools.
+///
+/// The paths of the tags whose names or string values needed the fallback are returned alongside
+/// the compound, with the root name recorded as an empty path. Since Latin-1 assigns a character
+/// to every byte, the fallback never fails, but it may not recover the text which was intended.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::io::{self, Flavor, NbtIoError};
+///
+/// // A compound with a string "sign" holding the Latin-1 encoding of "café"
+/// let bytes = [0x0A, 0, 0, 0x08, 0, 4, b's', b'i', b'g', b'n', 0, 4, b'c', b'a', b'f', 0xE9, 0];
+/// assert!(matches!(
+///     io::read_nbt(&mut &bytes[..], Flavor::Uncompressed),
+///     Err(NbtIoError::InvalidCesu8String)
+/// ));
+///
+/// let (nbt, _, fallbacks) =
+///     io::read_nbt_with_latin1_fallback(&mut &bytes[..], Flavor::Uncompressed)?;
+/// assert_eq!(nbt, compound! { "sign": "café" });
+/// assert_eq!(fallbacks, ["sign"]);
+/// # Ok::<(), NbtIoError>(())
+/// ```
+///
+/// [`read_nbt`]: crate::io::read_nbt
+/// [`NbtIoError::InvalidCesu8String`]: crate::io::NbtIoError::InvalidCesu8String
+pub fn read_nbt_with_latin1_fallback<R: Read>(
+    reader: &mut R,
+    flavor: Flavor,
+) -> Result<(NbtCompound, String, Vec<String>), NbtIoError> {
+    let (root, root_name, report) = NbtReadBuilder::new()
+        .flavor(flavor)
+        .latin1_fallback(true)
+        .read_from_with_report(reader)?;
+    Ok((root, root_name, report.latin1_fallbacks))
+}
+
+/// How a list whose element type is not a known tag ID is handled when reading with
+/// [`NbtReadBuilder::unknown_list_types`]. Such a list usually means that the document is corrupted
+/// or was written by a modded server.
+///
+/// [`NbtReadBuilder::unknown_list_types`]: crate::io::NbtReadBuilder::unknown_list_types
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UnknownListType {
+    /// Reading fails with [`NbtIoError::InvalidTagId`], as with [`read_nbt`].
+    ///
+    /// [`NbtIoError::InvalidTagId`]: crate::io::NbtIoError::InvalidTagId
+    /// [`read_nbt`]: crate::io::read_nbt
+    #[default]
+    Error,
+    /// Reading stops at the list, and every tag read before it is returned. The list itself and
+    /// the rest of the document are skipped.
+    SkipRest,
+    /// Reading fails with [`NbtIoError::UnknownListType`], which holds the path to the list and
+    /// the bytes of the document following its header, so that they can be inspected or logged.
+    ///
+    /// [`NbtIoError::UnknownListType`]: crate::io::NbtIoError::UnknownListType
+    Capture,
+}
+
+/// Reads the given flavor of NBT data from the given reader just like [`read_nbt`], but handles
+/// lists whose element type is not a known tag ID according to the given policy.
+///
+/// If the policy is [`UnknownListType::SkipRest`] and such a list is found, the returned
+/// [`PartialReadError`] describes where reading stopped. Any other error, such as a truncated
+/// document, is returned as usual.
+///
+/// This is a shorthand for [`NbtReadBuilder`] with only the flavor and the policy for unknown list
+/// types set.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::io::{self, Flavor, NbtIoError, UnknownListType};
+///
+/// // The compound { "a": 1b, "list": [...], "b": 2b } with an element type of 0x20
+/// let bytes = [
+///     0x0A, 0, 0,
+///     0x01, 0, 1, b'a', 1,
+///     0x09, 0, 4, b'l', b'i', b's', b't', 0x20, 0, 0, 0, 1, 0xFF,
+///     0x01, 0, 1, b'b', 2,
+///     0,
+/// ];
+///
+/// let (nbt, _, stopped) = io::read_nbt_with_unknown_list_types(
+///     &mut &bytes[..],
+///     Flavor::Uncompressed,
+///     UnknownListType::SkipRest,
+/// )?;
+/// assert_eq!(nbt, compound! { "a": 1i8 });
+/// assert_eq!(stopped.unwrap().path(), "list");
+///
+/// let error = io::read_nbt_with_unknown_list_types(
+///     &mut &bytes[..],
+///     Flavor::Uncompressed,
+///     UnknownListType::Capture,
+/// )
+/// .unwrap_err();
+/// match error {
+///     NbtIoError::UnknownListType { tag_id, path, remaining } => {
+///         assert_eq!((tag_id, path.as_str()), (0x20, "list"));
+///         assert_eq!(remaining, [0xFF, 0x01, 0, 1, b'b', 2, 0]);
+///     }
+///     _ => unreachable!(),
+/// }
+/// # Ok::<(), NbtIoError>(())
+/// ```
+///
+/// [`read_nbt`]: crate::io::read_nbt
+/// [`UnknownListType::SkipRest`]: crate::io::UnknownListType::SkipRest
+/// [`PartialReadError`]: crate::io::PartialReadError
+/// [`NbtReadBuilder`]: crate::io::NbtReadBuilder
+pub fn read_nbt_with_unknown_list_types<R: Read>(
+    reader: &mut R,
+    flavor: Flavor,
+    policy: UnknownListType,
+) -> Result<(NbtCompound, String, Option<PartialReadError>), NbtIoError> {
+    let (root, root_name, report) = NbtReadBuilder::new()
+        .flavor(flavor)
+        .unknown_list_types(policy)
+        .read_from_with_report(reader)?;
+    Ok((root, root_name, report.stopped))
+}
+
+/// How an array or list whose length prefix is negative, which means that the document is
+/// corrupted, is handled when reading with [`NbtReadBuilder::negative_lengths`].
+///
+/// [`NbtReadBuilder::negative_lengths`]: crate::io::NbtReadBuilder::negative_lengths
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NegativeLengthPolicy {
+    /// Reading fails with [`NbtIoError::NegativeLength`], as with [`read_nbt`].
+    ///
+    /// [`NbtIoError::NegativeLength`]: crate::io::NbtIoError::NegativeLength
+    /// [`read_nbt`]: crate::io::read_nbt
+    #[default]
+    Error,
+    /// The array or list is read as empty, and reading continues directly after its length
+    /// prefix. This recovers documents in which only the length was corrupted, such as those
+    /// written by tools which stored empty lists with a length of -1.
+    TreatAsZero,
+    /// The compound entry containing the negative length is dropped, and reading resumes at the
+    /// first offset following the length from which the rest of the document can be read without
+    /// error. If no such offset exists, the entry containing the enclosing compound is dropped
+    /// instead, and so on up to the root compound, after which reading fails with
+    /// [`NbtIoError::NegativeLength`].
+    ///
+    /// Recovery is bounded so that it stays fast on large or malicious documents. Each offset is
+    /// only checked until a compound entry or list element and the next 16 KiB of the document
+    /// have been read from it, and checking offsets may read at most 64 times as many bytes as the
+    /// document holds in total. An offset after which the root compound ends before the end of
+    /// the document is only used if no other offset works, so bytes trailing the root compound are
+    /// allowed.
+    ///
+    /// Since recovering requires looking ahead, the data is decompressed into memory before it is
+    /// read, and uncompressed data is read until the end of the reader.
+    ///
+    /// [`NbtIoError::NegativeLength`]: crate::io::NbtIoError::NegativeLength
+    Recover,
+}
+
+/// Reads the given flavor of NBT data from the given reader just like [`read_nbt`], but handles
+/// arrays and lists with negative lengths according to the given policy, returning a report of
+/// every repair made along with the document. Strings cannot have negative lengths, since their
+/// length is unsigned.
+///
+/// This is a shorthand for [`NbtReadBuilder`] with only the flavor and the policy for negative
+/// lengths set.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::io::{self, Flavor, LengthRepairAction, NbtIoError, NegativeLengthPolicy};
+///
+/// // The compound { "a": [I; <length -1>], "b": 2b }
+/// let bytes = [
+///     0x0A, 0, 0,
+///     0x0B, 0, 1, b'a', 0xFF, 0xFF, 0xFF, 0xFF,
+///     0x01, 0, 1, b'b', 2,
+///     0,
+/// ];
+///
+/// assert!(matches!(
+///     io::read_nbt(&mut &bytes[..], Flavor::Uncompressed),
+///     Err(NbtIoError::NegativeLength(-1))
+/// ));
+///
+/// let (nbt, _, repairs) = io::read_nbt_with_negative_lengths(
+///     &mut &bytes[..],
+///     Flavor::Uncompressed,
+///     NegativeLengthPolicy::TreatAsZero,
+/// )?;
+/// assert_eq!(nbt, compound! { "a": [I;], "b": 2i8 });
+/// assert_eq!(repairs[0].path(), "a");
+/// assert_eq!(repairs[0].action(), LengthRepairAction::TreatedAsZero);
+///
+/// let (nbt, _, repairs) = io::read_nbt_with_negative_lengths(
+///     &mut &bytes[..],
+///     Flavor::Uncompressed,
+///     NegativeLengthPolicy::Recover,
+/// )?;
+/// assert_eq!(nbt, compound! { "b": 2i8 });
+/// assert_eq!(repairs[0].length(), -1);
+/// assert_eq!(repairs[0].offset(), 7);
+/// assert_eq!(repairs[0].action(), LengthRepairAction::DroppedEntry { skipped: 8 });
+/// # Ok::<(), NbtIoError>(())
+/// ```
+///
+/// [`read_nbt`]: crate::io::read_nbt
+/// [`NbtReadBuilder`]: crate::io::NbtReadBuilder
+pub fn read_nbt_with_negative_lengths<R: Read>(
+    reader: &mut R,
+    flavor: Flavor,
+    policy: NegativeLengthPolicy,
+) -> Result<(NbtCompound, String, Vec<LengthRepair>), NbtIoError> {
+    let (root, root_name, report) = NbtReadBuilder::new()
+        .flavor(flavor)
+        .negative_lengths(policy)
+        .read_from_with_report(reader)?;
+    Ok((root, root_name, report.length_repairs))
+}
+
+/// A repair made to a negative length while reading with [`NbtReadBuilder::negative_lengths`].
+///
+/// [`NbtReadBuilder::negative_lengths`]: crate::io::NbtReadBuilder::negative_lengths
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LengthRepair {
+    pub(crate) path: String,
+    pub(crate) offset: usize,
+    pub(crate) length: i32,
+    pub(crate) action: LengthRepairAction,
+}
+
+impl LengthRepair {
+    /// Returns the path to the repaired array or list, or to the dropped entry.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the offset of the negative length prefix in the uncompressed data.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the negative length which was read.
+    pub fn length(&self) -> i32 {
+        self.length
+    }
+
+    /// Returns the action taken to repair the document.
+    pub fn action(&self) -> LengthRepairAction {
+        self.action
+    }
+}
+
+impl Display for LengthRepair {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: negative length {} at offset {} ",
+            self.path, self.length, self.offset
+        )?;
+        match self.action {
+            LengthRepairAction::TreatedAsZero => write!(f, "treated as zero"),
+            LengthRepairAction::DroppedEntry { skipped } =>
+                write!(f, "dropped along with {} bytes", skipped),
+        }
+    }
+}
+
+/// An action taken to repair a negative length, as listed by [`LengthRepair::action`].
+///
+/// [`LengthRepair::action`]: crate::io::LengthRepair::action
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LengthRepairAction {
+    /// The array or list was read as empty.
+    TreatedAsZero,
+    /// The compound entry was dropped.
+    DroppedEntry {
+        /// The number of bytes skipped, starting from the entry's tag ID.
+        skipped: usize,
+    },
+}
+
+/// How a compound containing the same key more than once is handled when reading with
+/// [`NbtReadBuilder::duplicate_keys`]. The keys are compared after the key transform, if any, has
+/// been applied.
+///
+/// [`NbtReadBuilder::duplicate_keys`]: crate::io::NbtReadBuilder::duplicate_keys
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DuplicateKeyPolicy {
+    /// The entry read last is kept, as with [`read_nbt`].
+    ///
+    /// [`read_nbt`]: crate::io::read_nbt
+    #[default]
+    KeepLast,
+    /// The entry read first is kept, and later entries with the same key are skipped.
+    KeepFirst,
+    /// Reading fails with [`NbtIoError::DuplicateKey`].
+    ///
+    /// [`NbtIoError::DuplicateKey`]: crate::io::NbtIoError::DuplicateKey
+    Error,
+}
+
+/// Describes where and why [`read_nbt_partial`], or a builder with [`NbtReadBuilder::partial`] or
+/// [`NbtReadBuilder::unknown_list_types`] set, stopped reading.
+///
+/// [`read_nbt_partial`]: crate::io::read_nbt_partial
+/// [`NbtReadBuilder::partial`]: crate::io::NbtReadBuilder::partial
+/// [`NbtReadBuilder::unknown_list_types`]: crate::io::NbtReadBuilder::unknown_list_types
+#[derive(Debug)]
+pub struct PartialReadError {
+    pub(crate) error: NbtIoError,
+    pub(crate) path: String,
+    pub(crate) offset: usize,
+}
+
+impl PartialReadError {
+    /// Returns the error which caused reading to stop.
+    pub fn error(&self) -> &NbtIoError {
+        &self.error
+    }
+
+    /// Returns the path to the tag which could not be read, using the same format as
+    /// [`NbtSpans`]. The path is empty if the root compound's header could not be read.
+    ///
+    /// [`NbtSpans`]: crate::io::NbtSpans
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the number of bytes which were read before the error occurred. For compressed
+    /// flavors, this refers to the decompressed data.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the underlying error.
+    pub fn into_inner(self) -> NbtIoError {
+        self.error
+    }
+}
+
+impl Display for PartialReadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(
+                f,
+                "{} (at byte {} in the root tag)",
+                self.error, self.offset
+            )
+        } else {
+            write!(
+                f,
+                "{} (at byte {} in {})",
+                self.error, self.offset, self.path
+            )
+        }
+    }
+}
+
+impl Error for PartialReadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
@@ -0,0 +1,124 @@
+use super::{Flavor, NbtIoError, NbtReadBuilder};
+use crate::NbtCompound;
+use std::{fmt::Debug, io::Read, ops::Range, slice};
+
+/// Reads the given flavor of NBT data from the given reader just like [`read_nbt`], but also
+/// records the range of bytes occupied by each tag in the data.
+///
+/// The span of a compound entry covers its tag ID, name, and payload, whereas the span of a list
+/// element covers only its payload. The root compound is recorded with an empty path. For
+/// compressed flavors, offsets refer to the decompressed data. Reading spans is considerably slower
+/// than [`read_nbt`], and is intended for debugging and diagnostic tools.
+///
+/// This is a shorthand for [`NbtReadBuilder`] with only the flavor and [`spans`] set.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::io::{self, Flavor};
+///
+/// let nbt = compound! { "pos": [1i32, 2, 3], "name": "foo" };
+/// let mut bytes = Vec::new();
+/// io::write_nbt(&mut bytes, None, &nbt, Flavor::Uncompressed).unwrap();
+///
+/// let (read, _, spans) = io::read_nbt_with_spans(&mut bytes.as_slice(), Flavor::Uncompressed).unwrap();
+/// assert_eq!(read, nbt);
+/// assert_eq!(spans.get(""), Some(0 .. bytes.len()));
+///
+/// // Each element of the list is a four byte int
+/// let element = spans.get("pos[1]").unwrap();
+/// assert_eq!(element.len(), 4);
+/// assert_eq!(&bytes[element.clone()], &2i32.to_be_bytes());
+/// assert_eq!(spans.innermost_at(element.start).unwrap().path(), "pos[1]");
+/// ```
+///
+/// [`read_nbt`]: crate::io::read_nbt
+/// [`NbtReadBuilder`]: crate::io::NbtReadBuilder
+/// [`spans`]: crate::io::NbtReadBuilder::spans
+pub fn read_nbt_with_spans<R: Read>(
+    reader: &mut R,
+    flavor: Flavor,
+) -> Result<(NbtCompound, String, NbtSpans), NbtIoError> {
+    let (root, root_name, report) = NbtReadBuilder::new()
+        .flavor(flavor)
+        .spans(true)
+        .read_from_with_report(reader)?;
+    Ok((root, root_name, report.spans.unwrap_or_default()))
+}
+
+/// The byte ranges occupied by each tag in a binary NBT document, as produced by
+/// [`read_nbt_with_spans`].
+///
+/// Paths take the form `Inventory[3].id`, where list elements are denoted by their index in square
+/// brackets. Keys containing characters other than ASCII letters, digits, `_`, `-`, and `+` are
+/// wrapped in double quotes, with any quotes or backslashes in them escaped.
+///
+/// [`read_nbt_with_spans`]: crate::io::read_nbt_with_spans
+#[derive(Debug, Clone, Default)]
+pub struct NbtSpans {
+    pub(crate) spans: Vec<TagSpan>,
+}
+
+impl NbtSpans {
+    /// Returns the byte range occupied by the tag at the given path, if it exists.
+    pub fn get(&self, path: &str) -> Option<Range<usize>> {
+        self.spans
+            .iter()
+            .find(|span| span.path == path)
+            .map(|span| span.range.clone())
+    }
+
+    /// Returns the most deeply nested tag whose span contains the given byte offset. This is
+    /// useful for determining which tag a corrupted byte belongs to.
+    pub fn innermost_at(&self, offset: usize) -> Option<&TagSpan> {
+        // Spans are ordered by their start offset, so nested spans come after their parents
+        self.spans
+            .iter()
+            .rev()
+            .find(|span| span.range.contains(&offset))
+    }
+
+    /// Returns an iterator over every recorded span, in the order the tags appeared in the data.
+    pub fn iter(&self) -> slice::Iter<'_, TagSpan> {
+        self.spans.iter()
+    }
+
+    /// Returns the number of recorded spans.
+    pub fn len(&self) -> usize {
+        self.spans.len()
+    }
+
+    /// Returns true if no spans were recorded.
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+}
+
+impl<'a> IntoIterator for &'a NbtSpans {
+    type IntoIter = slice::Iter<'a, TagSpan>;
+    type Item = &'a TagSpan;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.spans.iter()
+    }
+}
+
+/// The path to a tag and the range of bytes it occupied. See [`NbtSpans`].
+///
+/// [`NbtSpans`]: crate::io::NbtSpans
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagSpan {
+    pub(crate) path: String,
+    pub(crate) range: Range<usize>,
+}
+
+impl TagSpan {
+    /// Returns the path to the tag.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the range of bytes occupied by the tag.
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+}
@@ -1,4 +1,5 @@
 #![warn(rust_2018_idioms, missing_debug_implementations, missing_docs)]
+#![deny(unsafe_code)]
 
 /*!
 Provides support for encoding and decoding Minecraft's NBT format. This crate supports both
@@ -48,6 +49,12 @@ assert_eq!(read_compound.1, "root-tag"); // The root tag's name is generally unu
 assert_eq!(read_compound.0, compound);
 ```
 
+Reading binary NBT, whether through [`io`] or the `serde` module, and parsing SNBT never
+panic on malformed or malicious input, so they are safe to use on data received over the
+network. Invalid input results in an error instead, including negative lengths and lists or
+compounds nested more than [`io::MAX_DEPTH`] levels deep. Lengths read from the input are not
+trusted for preallocation, so memory is only allocated as the data it holds is actually read.
+
 # Querying Tags
 
 Generics are used to make the tag querying process as seamless as possible, however this
@@ -175,18 +182,259 @@ const SNBT: &str = "{short: -10s, string: fizzbuzz, array: [L; 1, 1, 2, 3, 5]}";
 assert_eq!(compound, snbt::parse(SNBT).unwrap());
 ```
 
+# Unsafe Code
+
+Unsafe code is denied throughout this crate, except in two modules:
+- [`cast`], which reinterprets slices and vecs of integers as the bytes they consist of, so that
+  byte arrays can be converted between signed and unsigned bytes, and int and long arrays can be
+  read without copying. When the `bytemuck` feature is enabled, these conversions are implemented
+  with that crate instead, and this crate contains no unsafe code of its own in this module.
+- [`memory`], whose allocator has to implement the unsafe `GlobalAlloc` trait, and forwards every
+  call to the allocator it wraps. This module is only compiled when the `alloc-track` feature is
+  enabled.
+
+Each unsafe block documents the invariants it relies on. The tests exercising this code run
+under [Miri](https://github.com/rust-lang/miri) in CI, and can be run locally with
+`cargo +nightly miri test --test soundness --features serde`.
+
+[`cast`]: crate::cast
+[`memory`]: crate::memory
 [`NbtCompound`]: crate::NbtCompound
 [`NbtList`]: crate::NbtList
 [`NbtRepr`]: crate::NbtRepr
 [`NbtTag`]: crate::NbtTag
 */
 
+/// Provides zero-copy archives of compounds with [`rkyv`].
+///
+/// When the `rkyv` feature is enabled, [`NbtTag`], [`NbtList`], [`NbtCompound`] and
+/// [`NbtTagKind`] implement rkyv's `Archive`, `Serialize` and `Deserialize` traits. A compound
+/// archived with [`to_bytes`] can be written to disk, and later memory-mapped and read in place
+/// with [`access`], which returns an [`ArchivedNbtCompound`] borrowing from the bytes. This suits
+/// tools which repeatedly reopen the same large, processed datasets, where parsing each document
+/// again would dominate.
+///
+/// Compared with reading binary NBT, even lazily by skipping the parts of a document which are not
+/// needed, archives have some tradeoffs:
+/// - Opening an archive costs a single validation pass which neither allocates nor copies, whereas
+///   binary NBT has to be decoded again every time, with modified UTF-8 strings converted and
+///   every number byte swapped.
+/// - Archives are uncompressed and usually larger than the equivalent NBT, and must be aligned in
+///   memory.
+/// - Archived tags are read-only. Modifying a document requires deserializing it with
+///   [`to_compound`] and archiving it again.
+/// - **Archives cannot be read by Minecraft or other NBT libraries**, and depend on the version
+///   of rkyv and on whether the `preserve_order` feature is enabled. They are therefore only
+///   suitable as caches of data whose source of truth is stored as NBT.
+///
+/// This module is only available when the `rkyv` feature is enabled.
+///
+/// [`rkyv`]: https://docs.rs/rkyv
+/// [`NbtTag`]: crate::NbtTag
+/// [`NbtList`]: crate::NbtList
+/// [`NbtCompound`]: crate::NbtCompound
+/// [`NbtTagKind`]: crate::NbtTagKind
+/// [`to_bytes`]: crate::archive::to_bytes
+/// [`access`]: crate::archive::access
+/// [`ArchivedNbtCompound`]: crate::ArchivedNbtCompound
+/// [`to_compound`]: crate::ArchivedNbtCompound::to_compound
+#[cfg(feature = "rkyv")]
+pub mod archive;
+/// Provides a lightweight harness for measuring the throughput of this crate on real documents.
+///
+/// [`Bencher`] times reads and writes of a document with the [`io`] module, and with the
+/// [`serde`] module when the `serde` feature is enabled, returning the numbers programmatically
+/// so that downstream crates can choose between the APIs for their own data. [`check_parity`]
+/// checks that both modules write identical bytes for a compound and read each other's output.
+/// This module is only available when the `bench-util` feature is enabled.
+///
+/// [`Bencher`]: crate::bench::Bencher
+/// [`io`]: crate::io
+/// [`serde`]: crate::serde
+/// [`check_parity`]: crate::bench::check_parity
+#[cfg(feature = "bench-util")]
+pub mod bench;
+mod cached;
+/// Provides safe conversions between signed and unsigned bytes.
+///
+/// NBT byte arrays are stored as `i8`s, whereas most I/O and hashing APIs work with `u8`s. The
+/// functions in this module reinterpret one as the other without copying. When the `bytemuck`
+/// feature is enabled, they are implemented with that crate rather than with `unsafe` code of
+/// our own.
+#[allow(unsafe_code)]
+pub mod cast;
+/// Provides an experimental compact binary encoding of NBT data for non-vanilla storage, such as
+/// caches holding large numbers of compounds.
+///
+/// The encoding has the same structure as binary NBT, but stores integers and lengths as varints,
+/// with signed values zigzag encoded so that small negative numbers stay small, and int and long
+/// arrays as the differences between consecutive elements. Strings are stored as UTF-8.
+///
+/// **This encoding cannot be read by Minecraft or other NBT libraries**, and may change in
+/// incompatible ways between releases of this crate, in which case documents written by older
+/// releases are rejected rather than misread. Use [`compact_to_nbt`] and [`nbt_to_compact`] to
+/// convert to and from standard NBT. This module is only available when the `compact` feature is
+/// enabled.
+///
+/// [`compact_to_nbt`]: crate::compact::compact_to_nbt
+/// [`nbt_to_compact`]: crate::compact::nbt_to_compact
+#[cfg(feature = "compact")]
+pub mod compact;
+/// Provides a table of SNBT test vectors and a harness for running them.
+///
+/// Each [`TestVector`] pairs an input with the tag or error kind expected when parsing it in the
+/// lenient mode and in the vanilla-strict mode of [`snbt::parse_with`]. [`VECTORS`] covers the
+/// syntax accepted by the game, and downstream crates can run it, or tables of their own, against
+/// this crate's parser or their own with [`run`] and [`run_with`]. This module is only available
+/// when the `test_vectors` feature is enabled.
+///
+/// [`TestVector`]: crate::conformance::TestVector
+/// [`snbt::parse_with`]: crate::snbt::parse_with
+/// [`VECTORS`]: crate::conformance::VECTORS
+/// [`run`]: crate::conformance::run
+/// [`run_with`]: crate::conformance::run_with
+#[cfg(feature = "test_vectors")]
+pub mod conformance;
+/// Provides explicit policies for converting tags between numeric kinds.
+///
+/// By default, the getters in this crate never coerce values: asking for an `i64` from an int tag
+/// is an error. A [`ConversionPolicy`] makes the permitted coercions explicit, so that tools can
+/// choose between rejecting mismatched kinds, widening them losslessly, clamping them, or casting
+/// them outright.
+///
+/// A policy can be passed to [`NbtCompound::get_converted`], [`NbtList::get_converted`],
+/// [`NbtCompound::get_path_converted`], [`SanitizeRules::conversion`], and, with the `serde`
+/// feature, [`DeserializerBuilder::conversion`].
+///
+/// [`ConversionPolicy`]: crate::convert::ConversionPolicy
+/// [`NbtCompound::get_converted`]: crate::NbtCompound::get_converted
+/// [`NbtList::get_converted`]: crate::NbtList::get_converted
+/// [`NbtCompound::get_path_converted`]: crate::NbtCompound::get_path_converted
+/// [`SanitizeRules::conversion`]: crate::sanitize::SanitizeRules::conversion
+/// [`DeserializerBuilder::conversion`]: crate::serde::DeserializerBuilder::conversion
+pub mod convert;
+/// Provides a standard envelope for storing a payload alongside metadata about it.
+///
+/// Tools which store their own documents commonly record which schema and version a payload
+/// follows, when it was written, and by what. An [`EnvelopeHeader`] holds this metadata and wraps a
+/// payload into an envelope compound, and [`unwrap_checked`] and [`read_enveloped`] strip the
+/// envelope again while checking the schema and version, so that tools agree on a single layout.
+///
+/// [`EnvelopeHeader`]: crate::envelope::EnvelopeHeader
+/// [`unwrap_checked`]: crate::envelope::unwrap_checked
+/// [`read_enveloped`]: crate::envelope::read_enveloped
+pub mod envelope;
+/// Provides exports of NBT data into tabular formats.
+///
+/// [`to_csv`] flattens a set of compounds into delimiter-separated values, with one row per
+/// compound and one column per [`NbtPath`], for loading into spreadsheets and databases.
+///
+/// [`to_csv`]: crate::export::to_csv
+/// [`NbtPath`]: crate::path::NbtPath
+pub mod export;
+/// Provides conversions between tags and the values of other configuration formats and NBT
+/// crates.
+///
+/// When the `serde_yaml` feature is enabled, tags can be converted to and from
+/// `serde_yaml::Value` with [`nbt_to_yaml`] and [`yaml_to_nbt`]. When the `toml` feature is
+/// enabled, they can be converted to and from `toml::Value` with [`nbt_to_toml`] and
+/// [`toml_to_nbt`]. Each function documents how kinds without a direct equivalent, such as typed
+/// arrays, are mapped.
+///
+/// When the `hematite-nbt` or `fastnbt` feature is enabled, `From` and `TryFrom` conversions
+/// between [`NbtTag`] and `nbt::Value` or `fastnbt::Value` respectively are implemented, so that
+/// code using those crates can be migrated gradually without writing and reading tags back as
+/// bytes. Converting to a tag fails if a list mixes types.
+///
+/// [`nbt_to_yaml`]: crate::interop::nbt_to_yaml
+/// [`yaml_to_nbt`]: crate::interop::yaml_to_nbt
+/// [`nbt_to_toml`]: crate::interop::nbt_to_toml
+/// [`toml_to_nbt`]: crate::interop::toml_to_nbt
+#[cfg(any(
+    feature = "serde_yaml",
+    feature = "toml",
+    feature = "hematite-nbt",
+    feature = "fastnbt"
+))]
+pub mod interop;
 /// Provides efficient serializer and deserializer implementations for arbitrary NBT tag trees. The
 /// functions in this module should be used for serializing and deserializing [`NbtCompound`]s
 /// over the utilities provided by serde.
 ///
+/// When the `tracing` feature is enabled, reading and writing a document, as well as parsing SNBT,
+/// happen within debug-level spans named `read_nbt`, `write_nbt`, and `parse_snbt`. Once the
+/// operation succeeds, each span records the number of tags in the document and how deeply they
+/// are nested. The binary spans also record the document's uncompressed size in bytes. Duplicate
+/// keys and strings decoded as Latin-1 are reported as debug-level events.
+///
 /// [`NbtCompound`]: crate::NbtCompound
 pub mod io;
+mod literal;
+/// Provides conversions between NBT lists and the vector types of [`glam`].
+///
+/// Lists of two or three doubles convert to and from `DVec2` and `DVec3`, and lists of floats to
+/// and from `Vec2` and `Vec3`. Compounds also gain the [`pos`], [`motion`] and [`rotation`]
+/// accessors for the vectors stored in entity data. This module is only available when the `math`
+/// feature is enabled.
+///
+/// [`glam`]: https://docs.rs/glam
+/// [`pos`]: crate::NbtCompound::pos
+/// [`motion`]: crate::NbtCompound::motion
+/// [`rotation`]: crate::NbtCompound::rotation
+#[cfg(feature = "math")]
+pub mod math;
+/// Provides measurements of the memory allocated while reading NBT data.
+///
+/// [`read_nbt_tracked`] reads a document and reports the peak and retained memory of the read, as
+/// well as the number of allocations made. This requires [`TrackingAllocator`] to be installed as
+/// the global allocator, and is only available when the `alloc-track` feature is enabled.
+///
+/// [`read_nbt_tracked`]: crate::memory::read_nbt_tracked
+/// [`TrackingAllocator`]: crate::memory::TrackingAllocator
+#[cfg(feature = "alloc-track")]
+#[allow(unsafe_code)]
+pub mod memory;
+/// Provides hooks into the merging of compounds with [`NbtCompound::merge_with`].
+///
+/// [`merge_traced`] merges a stack of compounds, such as the layers of a datapack configuration,
+/// and returns a [`Provenance`] table recording which layer supplied each tag of the result. An
+/// [`OverlayCompound`] presents a read-only view of such a stack without merging it.
+///
+/// [`NbtCompound::merge_with`]: crate::NbtCompound::merge_with
+/// [`merge_traced`]: crate::merge::merge_traced
+/// [`Provenance`]: crate::merge::Provenance
+/// [`OverlayCompound`]: crate::merge::OverlayCompound
+pub mod merge;
+/// Provides NBT paths in the syntax used by Minecraft's `/data` command.
+///
+/// An [`NbtPath`] such as `Inventory[{Slot:0b}].tag.display` selects tags from a compound by key,
+/// by list index, or by matching compounds against SNBT patterns embedded in the path.
+///
+/// [`NbtPath`]: crate::path::NbtPath
+pub mod path;
+/// Re-exports the most commonly used types and functions of this crate.
+///
+/// This includes the tag types, the functions and builders for reading and writing binary NBT, the
+/// [`snbt`] module, and the options types used to configure conversions, sanitization, and, when
+/// the `serde` feature is enabled, serialization. Each of these options types implements
+/// [`Default`].
+///
+/// ```
+/// use quartz_nbt::prelude::*;
+///
+/// let nbt = snbt::parse("{Pos: [1.0d, 64.0d, -2.5d], Health: 20.0f}")?;
+///
+/// let mut bytes = Vec::new();
+/// write_nbt(&mut bytes, None, &nbt, Flavor::GzCompressed)?;
+/// let (read, _) = read_nbt(&mut bytes.as_slice(), Flavor::GzCompressed)?;
+///
+/// assert_eq!(read, compound! { "Pos": [1.0f64, 64.0f64, -2.5f64], "Health": 20.0f32 });
+/// assert_eq!(NbtPath::parse("Pos[1]")?.select(&read), vec![&NbtTag::Double(64.0)]);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// [`snbt`]: crate::snbt
+pub mod prelude;
 mod raw;
 mod repr;
 /// When the `serde` feature is enabled, this module provides `Serializer` and `Deserializer`
@@ -275,10 +523,11 @@ mod repr;
 /// # Enum Representation
 ///
 /// All enum types can be represented in NBT, however not all types are efficiently representable.
-/// Unit variants are serialized according to their index in the enum, while all other variant
-/// types are serialized as compounds. Although we do not currently support serializing unit
-/// variants by name, we will likely add this feature in future versions, and already support
-/// deserializing unit variants by name.
+/// Unit variants are serialized as int tags holding their index in the enum, counting from zero in
+/// declaration order, while all other variant types are serialized as compounds with a single entry
+/// named after the variant. Unit variants can instead be serialized by name with
+/// [`SerializerBuilder::unit_variants`], which is how other Rust NBT crates represent them. Both
+/// representations are accepted when deserializing.
 ///
 /// ```
 /// # extern crate serde;
@@ -402,11 +651,23 @@ mod repr;
 /// [`Array`]: crate::serde::Array
 /// [`Cow`]: std::borrow::Cow
 /// [`deserialize_from_buffer`]: crate::serde::deserialize_from_buffer
+/// [`SerializerBuilder::unit_variants`]: crate::serde::SerializerBuilder::unit_variants
 #[cfg(feature = "serde")]
 #[allow(missing_debug_implementations)]
 pub mod serde;
 mod tag;
+#[cfg(feature = "tracing")]
+mod trace;
 
+/// Provides rules for repairing NBT data before it is written or handed to the game.
+///
+/// Data assembled by hand or converted from other formats can violate constraints which the game
+/// relies on, such as string length limits or homogenous lists. [`NbtCompound::sanitize`] applies
+/// a set of [`SanitizeRules`] to a compound and reports every fix it made.
+///
+/// [`NbtCompound::sanitize`]: crate::NbtCompound::sanitize
+/// [`SanitizeRules`]: crate::sanitize::SanitizeRules
+pub mod sanitize;
 /// Provides support for parsing stringified NBT data.
 ///
 /// SNBT is essentially an extension of JSON. It uses the same overarching syntax with some changes
@@ -471,7 +732,64 @@ mod tag;
 /// syntax of `{key: value, ...}`. Compounds must have every key be a string, but its values do not have
 /// to be homogenously typed. Whitespace is allowed to make compounds more readable, however one should
 /// refer to the section on strings to avoid unexpected elisions.
+///
+/// # Colored Output
+///
+/// When the `ansi` feature is enabled, SNBT can be colored with ANSI escape sequences for display
+/// in a terminal, in the style of the vanilla `/data` command, by setting an [`AnsiTheme`] in the
+/// [`FormatOptions`] or by calling [`NbtTag::to_ansi_snbt`].
+///
+/// [`AnsiTheme`]: crate::snbt::AnsiTheme
+/// [`FormatOptions`]: crate::snbt::FormatOptions
+/// [`NbtTag::to_ansi_snbt`]: crate::NbtTag::to_ansi_snbt
 pub mod snbt;
+/// Provides assertion and comparison utilities for tests which work with NBT data.
+///
+/// The [`assert_compound_eq`] macro is the main entry point of this module. Unlike `assert_eq`, it
+/// reports only the first difference between two compounds, along with the path to it, rather than
+/// dumping both compounds in their entirety.
+///
+/// When the `rand` feature is enabled, `generate` produces random compounds following a
+/// `Schema`, which is re-exported here from the [`validate`] module.
+///
+/// [`assert_compound_eq`]: crate::assert_compound_eq
+/// [`validate`]: crate::validate
+pub mod testing;
+/// Provides conversions for the JSON text components stored in NBT data, such as item names and
+/// book pages.
+///
+/// Before Minecraft 1.21.5, text components were stored as JSON inside string tags. Since then,
+/// they are stored as NBT directly. This module converts both encodings to and from
+/// `serde_json::Value`, and is only available when the `serde_json` feature is enabled.
+#[cfg(feature = "serde_json")]
+pub mod text;
+/// Provides validation of the entities and block entities found within NBT documents.
+///
+/// A [`Validator`] walks a whole document, such as a chunk or structure, and checks every entity
+/// and block entity it finds against the schemas registered for their ids in an [`IdRegistry`],
+/// reporting any ids which are unknown. Validation runs on documents which have already been read,
+/// such as with the functions in the [`io`] module.
+///
+/// The schemas themselves are described with [`Schema`] and [`TagSchema`], which can be written by
+/// hand or inferred from a sample document.
+///
+/// [`Validator`]: crate::validate::Validator
+/// [`IdRegistry`]: crate::validate::IdRegistry
+/// [`io`]: crate::io
+/// [`Schema`]: crate::validate::Schema
+/// [`TagSchema`]: crate::validate::TagSchema
+pub mod validate;
+
+/// Provides typed views of common structures found in vanilla Minecraft data.
+///
+/// [`GameRules`], [`Attributes`] and [`Enchantments`] wrap a reference to the compound or list
+/// holding the structure, and read or edit it in place through typed getters and setters. This
+/// avoids both defining serde structs for the whole document and working with raw string keys.
+///
+/// [`GameRules`]: crate::vanilla::GameRules
+/// [`Attributes`]: crate::vanilla::Attributes
+/// [`Enchantments`]: crate::vanilla::Enchantments
+pub mod vanilla;
 
 pub use repr::*;
 pub use tag::*;
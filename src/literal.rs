@@ -0,0 +1,212 @@
+use crate::{NbtCompound, NbtList, NbtTag, NbtTagKind};
+use std::fmt::{self, Display, Formatter, Write};
+
+// The indentation of each level of nested compounds and lists
+const INDENT: &str = "    ";
+
+// Formats a value as Rust source code which evaluates to it
+pub enum RustLiteral<'a> {
+    Tag(&'a NbtTag),
+    List(&'a NbtList),
+    Compound(&'a NbtCompound),
+}
+
+impl Display for RustLiteral<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            RustLiteral::Tag(tag) => write_tag(f, tag, 0),
+            RustLiteral::List(list) => write_list(f, list, 0),
+            RustLiteral::Compound(compound) => write_compound(f, compound, 0),
+        }
+    }
+}
+
+// Writes the given compound as an invocation of the `compound!` macro
+fn write_compound(f: &mut Formatter<'_>, compound: &NbtCompound, depth: usize) -> fmt::Result {
+    f.write_str("compound! ")?;
+    write_macro_compound(f, compound, depth)
+}
+
+// Writes an expression evaluating to the given tag
+fn write_tag(f: &mut Formatter<'_>, tag: &NbtTag, depth: usize) -> fmt::Result {
+    match tag {
+        &NbtTag::Byte(value) => write!(f, "NbtTag::Byte({})", value),
+        &NbtTag::Short(value) => write!(f, "NbtTag::Short({})", value),
+        &NbtTag::Int(value) => write!(f, "NbtTag::Int({})", value),
+        &NbtTag::Long(value) => write!(f, "NbtTag::Long({})", value),
+        &NbtTag::Float(value) => write!(f, "NbtTag::Float({})", Float(value.into(), "f32")),
+        &NbtTag::Double(value) => write!(f, "NbtTag::Double({})", Float(value, "f64")),
+        NbtTag::ByteArray(array) => write_vec(f, "NbtTag::ByteArray", array),
+        NbtTag::String(string) => write!(f, "NbtTag::String({:?}.to_owned())", string),
+        NbtTag::List(list) => {
+            f.write_str("NbtTag::List(")?;
+            write_list(f, list, depth)?;
+            f.write_char(')')
+        }
+        NbtTag::Compound(compound) => {
+            f.write_str("NbtTag::Compound(")?;
+            write_compound(f, compound, depth)?;
+            f.write_char(')')
+        }
+        NbtTag::IntArray(array) => write_vec(f, "NbtTag::IntArray", array),
+        NbtTag::LongArray(array) => write_vec(f, "NbtTag::LongArray", array),
+    }
+}
+
+// Writes an expression evaluating to the given list
+fn write_list(f: &mut Formatter<'_>, list: &NbtList, depth: usize) -> fmt::Result {
+    if list.is_empty() {
+        return match list.1 {
+            Some(kind) => write!(f, "NbtList::with_element_kind(NbtTagKind::{:?})", kind),
+            None => f.write_str("NbtList::new()"),
+        };
+    }
+
+    f.write_str("NbtList::from(vec![")?;
+    write_elements(f, list, depth, write_tag)?;
+    f.write_str("])")
+}
+
+// Writes a tag using the syntax accepted as a value by the `compound!` macro
+fn write_macro_value(f: &mut Formatter<'_>, tag: &NbtTag, depth: usize) -> fmt::Result {
+    match tag {
+        &NbtTag::Byte(value) => write!(f, "{}i8", value),
+        &NbtTag::Short(value) => write!(f, "{}i16", value),
+        &NbtTag::Int(value) => write!(f, "{}i32", value),
+        &NbtTag::Long(value) => write!(f, "{}i64", value),
+        &NbtTag::Float(value) => write!(f, "{}", Float(value.into(), "f32")),
+        &NbtTag::Double(value) => write!(f, "{}", Float(value, "f64")),
+        NbtTag::ByteArray(array) => write_macro_array(f, 'B', array.iter().map(|&x| x.into())),
+        NbtTag::String(string) => write!(f, "{:?}", string),
+        // The macro turns `[]` into a list without an element kind, and requires the elements of
+        // a list to have the same type
+        NbtTag::List(list) if (list.is_empty() && list.1.is_some()) || !is_uniform(list) =>
+            write_list(f, list, depth),
+        NbtTag::List(list) => {
+            f.write_char('[')?;
+            write_elements(f, list, depth, write_macro_value)?;
+            f.write_char(']')
+        }
+        NbtTag::Compound(compound) => write_macro_compound(f, compound, depth),
+        NbtTag::IntArray(array) => write_macro_array(f, 'I', array.iter().map(|&x| x.into())),
+        NbtTag::LongArray(array) => write_macro_array(f, 'L', array.iter().copied()),
+    }
+}
+
+fn write_macro_compound(
+    f: &mut Formatter<'_>,
+    compound: &NbtCompound,
+    depth: usize,
+) -> fmt::Result {
+    if compound.is_empty() {
+        return f.write_str("{}");
+    }
+
+    f.write_char('{')?;
+    for (index, (key, tag)) in compound.inner().iter().enumerate() {
+        if index > 0 {
+            f.write_char(',')?;
+        }
+        new_line(f, depth + 1)?;
+        write!(f, "{:?}: ", key)?;
+        write_macro_value(f, tag, depth + 1)?;
+    }
+    new_line(f, depth)?;
+    f.write_char('}')
+}
+
+// The macro casts each element of an array to the element type, so elements are left unsuffixed
+// unless they do not fit in an `i32`, which is the type unsuffixed literals default to
+fn write_macro_array<I>(f: &mut Formatter<'_>, specifier: char, elements: I) -> fmt::Result
+where I: Iterator<Item = i64> {
+    write!(f, "[{};", specifier)?;
+    for (index, element) in elements.enumerate() {
+        f.write_str(if index == 0 { " " } else { ", " })?;
+        if i32::try_from(element).is_ok() {
+            write!(f, "{}", element)?;
+        } else {
+            write!(f, "{}i64", element)?;
+        }
+    }
+    f.write_char(']')
+}
+
+// Writes the elements of a list on one line if they are all numbers or strings, and on separate
+// lines otherwise
+fn write_elements<F>(f: &mut Formatter<'_>, list: &NbtList, depth: usize, write: F) -> fmt::Result
+where F: Fn(&mut Formatter<'_>, &NbtTag, usize) -> fmt::Result {
+    let multiline = list.iter().any(|element| {
+        matches!(
+            element.kind(),
+            NbtTagKind::List
+                | NbtTagKind::Compound
+                | NbtTagKind::ByteArray
+                | NbtTagKind::IntArray
+                | NbtTagKind::LongArray
+        )
+    });
+
+    for (index, element) in list.iter().enumerate() {
+        if index > 0 {
+            f.write_str(if multiline { "," } else { ", " })?;
+        }
+        if multiline {
+            new_line(f, depth + 1)?;
+        }
+        write(f, element, depth + 1)?;
+    }
+    if multiline {
+        new_line(f, depth)?;
+    }
+    Ok(())
+}
+
+fn is_uniform(list: &NbtList) -> bool {
+    let mut kinds = list.iter().map(NbtTag::kind);
+    match kinds.next() {
+        Some(first) => kinds.all(|kind| kind == first),
+        None => true,
+    }
+}
+
+fn write_vec<T: Display>(f: &mut Formatter<'_>, variant: &str, array: &[T]) -> fmt::Result {
+    write!(f, "{}(vec![", variant)?;
+    for (index, element) in array.iter().enumerate() {
+        if index > 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "{}", element)?;
+    }
+    f.write_str("])")
+}
+
+fn new_line(f: &mut Formatter<'_>, depth: usize) -> fmt::Result {
+    f.write_char('\n')?;
+    for _ in 0 .. depth {
+        f.write_str(INDENT)?;
+    }
+    Ok(())
+}
+
+// A float formatted as a suffixed literal, or as one of the associated constants of its type if it
+// is not finite. The payloads of NaNs are not preserved.
+struct Float(f64, &'static str);
+
+impl Display for Float {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let Float(value, ty) = *self;
+        if value.is_nan() {
+            write!(f, "{}::NAN", ty)
+        } else if value == f64::INFINITY {
+            write!(f, "{}::INFINITY", ty)
+        } else if value == f64::NEG_INFINITY {
+            write!(f, "{}::NEG_INFINITY", ty)
+        } else if ty == "f32" {
+            // The debug representation is the shortest one which reads back as the same value,
+            // and always contains a decimal point or exponent
+            write!(f, "{:?}{}", value as f32, ty)
+        } else {
+            write!(f, "{:?}{}", value, ty)
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use crate::{NbtCompound, NbtList, NbtReprError, NbtStructureError, NbtTag};
+use glam::{DVec2, DVec3, Vec2, Vec3};
+
+macro_rules! vector_conversions {
+    ($($vector:ty, $elem:ty, $tag:ident, $len:literal);*) => {
+        $(
+            impl From<$vector> for NbtList {
+                #[inline]
+                fn from(vector: $vector) -> Self {
+                    NbtList::from(vector.to_array().to_vec())
+                }
+            }
+
+            impl From<$vector> for NbtTag {
+                #[inline]
+                fn from(vector: $vector) -> Self {
+                    NbtTag::List(NbtList::from(vector))
+                }
+            }
+
+            impl TryFrom<&NbtList> for $vector {
+                type Error = NbtStructureError;
+
+                fn try_from(list: &NbtList) -> Result<Self, Self::Error> {
+                    if list.len() != $len {
+                        return Err(NbtStructureError::invalid_length($len, list.len()));
+                    }
+
+                    let mut values = [0.0; $len];
+                    for (value, tag) in values.iter_mut().zip(list.iter()) {
+                        *value = <$elem>::try_from(tag)?;
+                    }
+                    Ok(<$vector>::from(values))
+                }
+            }
+
+            impl TryFrom<&NbtTag> for $vector {
+                type Error = NbtStructureError;
+
+                #[inline]
+                fn try_from(tag: &NbtTag) -> Result<Self, Self::Error> {
+                    match tag {
+                        NbtTag::List(list) => <$vector>::try_from(list),
+                        _ => Err(NbtStructureError::type_mismatch(
+                            concat!("List of ", stringify!($len), " ", stringify!($tag), "s"),
+                            tag.tag_name(),
+                        )),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+vector_conversions!(
+    DVec2, f64, Double, 2;
+    DVec3, f64, Double, 3;
+    Vec2, f32, Float, 2;
+    Vec3, f32, Float, 3
+);
+
+/// Accessors for the vectors stored in entity data. These methods are only available when the
+/// `math` feature is enabled.
+impl NbtCompound {
+    /// Returns the position of the entity described by this compound, which is stored in the
+    /// `Pos` tag as a list of three doubles.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// use glam::DVec3;
+    ///
+    /// let mut zombie = compound! { "id": "minecraft:zombie", "Pos": [1.5f64, 64.0f64, -3.5f64] };
+    /// assert_eq!(zombie.pos().unwrap(), DVec3::new(1.5, 64.0, -3.5));
+    ///
+    /// zombie.set_pos(zombie.pos().unwrap() + DVec3::Y);
+    /// assert_eq!(zombie.get::<_, &NbtList>("Pos").unwrap(), &NbtList::from(vec![1.5f64, 65.0, -3.5]));
+    ///
+    /// zombie.insert("Pos", NbtList::from(vec![0.0f64, 0.0]));
+    /// assert!(zombie.pos().is_err());
+    /// ```
+    pub fn pos(&self) -> Result<DVec3, NbtReprError> {
+        self.get("Pos")
+    }
+
+    /// Sets the `Pos` tag of this compound to the given position. See [`pos`].
+    ///
+    /// [`pos`]: crate::NbtCompound::pos
+    pub fn set_pos(&mut self, pos: DVec3) {
+        self.insert("Pos", pos);
+    }
+
+    /// Returns the velocity of the entity described by this compound in blocks per tick, which is
+    /// stored in the `Motion` tag as a list of three doubles.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// use glam::DVec3;
+    ///
+    /// let mut arrow = compound! { "Motion": [0.0f64, 0.5f64, 2.0f64] };
+    /// arrow.set_motion(arrow.motion().unwrap() * 0.99);
+    /// assert_eq!(arrow.motion().unwrap(), DVec3::new(0.0, 0.495, 1.98));
+    /// ```
+    pub fn motion(&self) -> Result<DVec3, NbtReprError> {
+        self.get("Motion")
+    }
+
+    /// Sets the `Motion` tag of this compound to the given velocity. See [`motion`].
+    ///
+    /// [`motion`]: crate::NbtCompound::motion
+    pub fn set_motion(&mut self, motion: DVec3) {
+        self.insert("Motion", motion);
+    }
+
+    /// Returns the rotation of the entity described by this compound, which is stored in the
+    /// `Rotation` tag as a list of two floats. The `x` component is the yaw and the `y` component
+    /// is the pitch, both in degrees.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// use glam::Vec2;
+    ///
+    /// let mut skeleton = compound! { "Rotation": [90.0f32, -15.0f32] };
+    /// assert_eq!(skeleton.rotation().unwrap(), Vec2::new(90.0, -15.0));
+    ///
+    /// skeleton.set_rotation(Vec2::ZERO);
+    /// assert_eq!(skeleton.rotation().unwrap(), Vec2::ZERO);
+    ///
+    /// // Rotations are stored as floats, not doubles
+    /// skeleton.insert("Rotation", NbtList::from(vec![0.0f64, 0.0]));
+    /// assert!(skeleton.rotation().is_err());
+    /// ```
+    pub fn rotation(&self) -> Result<Vec2, NbtReprError> {
+        self.get("Rotation")
+    }
+
+    /// Sets the `Rotation` tag of this compound to the given yaw and pitch. See [`rotation`].
+    ///
+    /// [`rotation`]: crate::NbtCompound::rotation
+    pub fn set_rotation(&mut self, rotation: Vec2) {
+        self.insert("Rotation", rotation);
+    }
+}
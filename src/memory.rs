@@ -0,0 +1,286 @@
+use crate::{
+    io::{self, Flavor, NbtIoError},
+    NbtCompound,
+};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    io::Read,
+    mem,
+};
+
+/// A global allocator which records the allocations made by the current thread while it is being
+/// tracked with [`track`], and forwards every request to another allocator.
+///
+/// Since Rust programs can only have one global allocator, this crate cannot install it itself.
+/// Applications which want to measure reads must register it with `#[global_allocator]`, after
+/// which the overhead outside of [`track`] is a single thread-local check per allocation.
+///
+/// ```
+/// use quartz_nbt::memory::TrackingAllocator;
+///
+/// #[global_allocator]
+/// static ALLOCATOR: TrackingAllocator = TrackingAllocator::new();
+/// # fn main() {}
+/// ```
+///
+/// [`track`]: crate::memory::track
+#[derive(Debug)]
+pub struct TrackingAllocator<A = System> {
+    inner: A,
+}
+
+impl TrackingAllocator<System> {
+    /// Returns a tracking allocator wrapping the system allocator.
+    pub const fn new() -> Self {
+        TrackingAllocator { inner: System }
+    }
+}
+
+impl<A> TrackingAllocator<A> {
+    /// Returns a tracking allocator wrapping the given allocator.
+    pub const fn wrap(inner: A) -> Self {
+        TrackingAllocator { inner }
+    }
+}
+
+impl Default for TrackingAllocator<System> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Safety
+// * Every method forwards to the wrapped allocator with the same arguments, and only updates
+//   thread-local counters, which never allocate, besides that
+unsafe impl<A: GlobalAlloc> GlobalAlloc for TrackingAllocator<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = self.inner.alloc(layout);
+        if !ptr.is_null() {
+            record(|state| state.allocate(layout.size()));
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = self.inner.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            record(|state| state.allocate(layout.size()));
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.inner.dealloc(ptr, layout);
+        record(|state| state.free(layout.size()));
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = self.inner.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            record(|state| {
+                state.free(layout.size());
+                state.allocate(new_size);
+            });
+        }
+        new_ptr
+    }
+}
+
+/// Statistics about the memory allocated while running a closure with [`track`].
+///
+/// All values are zero if [`TrackingAllocator`] is not the global allocator.
+///
+/// [`track`]: crate::memory::track
+/// [`TrackingAllocator`]: crate::memory::TrackingAllocator
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AllocStats {
+    peak_bytes: usize,
+    retained_bytes: usize,
+    allocations: usize,
+    allocated_bytes: usize,
+}
+
+impl AllocStats {
+    /// Returns the largest number of bytes which were allocated at once, not counting memory which
+    /// was allocated before tracking started.
+    pub fn peak_bytes(&self) -> usize {
+        self.peak_bytes
+    }
+
+    /// Returns the number of bytes which were allocated while tracking and still had not been
+    /// freed when tracking ended, such as the memory held by a returned compound.
+    pub fn retained_bytes(&self) -> usize {
+        self.retained_bytes
+    }
+
+    /// Returns the total number of allocations, with each reallocation counting as one.
+    pub fn allocations(&self) -> usize {
+        self.allocations
+    }
+
+    /// Returns the total number of bytes requested by every allocation and reallocation.
+    pub fn allocated_bytes(&self) -> usize {
+        self.allocated_bytes
+    }
+}
+
+/// Runs the given closure and returns its result along with statistics about the memory it
+/// allocated on the current thread. Allocations made by other threads, such as those of a rayon
+/// thread pool, are not counted.
+///
+/// Calls may be nested, in which case the allocations of the inner call count towards the outer
+/// call as well.
+///
+/// ```
+/// use quartz_nbt::memory::{self, TrackingAllocator};
+///
+/// #[global_allocator]
+/// static ALLOCATOR: TrackingAllocator = TrackingAllocator::new();
+///
+/// # fn main() {
+/// let (buffer, stats) = memory::track(|| {
+///     let scratch = vec![0u8; 4096];
+///     drop(scratch);
+///     vec![0u8; 1024]
+/// });
+/// assert_eq!(buffer.len(), 1024);
+/// assert_eq!(stats.allocations(), 2);
+/// assert_eq!(stats.peak_bytes(), 4096);
+/// assert_eq!(stats.retained_bytes(), 1024);
+/// # }
+/// ```
+pub fn track<T, F: FnOnce() -> T>(f: F) -> (T, AllocStats) {
+    let outer = STATE.with(|state| state.replace(TrackingState::START));
+    let guard = RestoreOnPanic(outer);
+    let result = f();
+    mem::forget(guard);
+    let inner = STATE.with(|state| state.replace(outer));
+
+    if outer.active {
+        record(|state| state.absorb(inner));
+    }
+
+    (result, inner.stats())
+}
+
+/// Reads the given flavor of NBT data from the given reader just like [`read_nbt`], additionally
+/// returning statistics about the memory allocated while reading. See [`track`] for details.
+///
+/// The peak includes the buffers of the decompressor, and the retained bytes are roughly the
+/// memory held by the returned compound and root name. This can be used to reject documents which
+/// exceed a memory budget, such as the data of a single player, before keeping them around.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::{
+///     io::{self, Flavor},
+///     memory::{self, TrackingAllocator},
+/// };
+///
+/// #[global_allocator]
+/// static ALLOCATOR: TrackingAllocator = TrackingAllocator::new();
+///
+/// # fn main() -> Result<(), io::NbtIoError> {
+/// let mut bytes = Vec::new();
+/// let nbt = compound! { "Inventory": [B; 0, 0, 0, 0, 0, 0, 0, 0] };
+/// io::write_nbt(&mut bytes, None, &nbt, Flavor::GzCompressed)?;
+///
+/// let (read, _, stats) = memory::read_nbt_tracked(&mut bytes.as_slice(), Flavor::GzCompressed)?;
+/// assert_eq!(read, nbt);
+/// assert!(stats.retained_bytes() >= 8);
+/// assert!(stats.peak_bytes() >= stats.retained_bytes());
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`read_nbt`]: crate::io::read_nbt
+/// [`track`]: crate::memory::track
+pub fn read_nbt_tracked<R: Read>(
+    reader: &mut R,
+    flavor: Flavor,
+) -> Result<(NbtCompound, String, AllocStats), NbtIoError> {
+    let (result, stats) = track(|| io::read_nbt(reader, flavor));
+    let (nbt, root_name) = result?;
+    Ok((nbt, root_name, stats))
+}
+
+thread_local! {
+    static STATE: Cell<TrackingState> = const { Cell::new(TrackingState::IDLE) };
+}
+
+// The allocations made since tracking started. The number of live bytes may be negative, since
+// memory allocated before tracking started may be freed while tracking.
+#[derive(Clone, Copy)]
+struct TrackingState {
+    active: bool,
+    live_bytes: isize,
+    peak_bytes: isize,
+    allocations: usize,
+    allocated_bytes: usize,
+}
+
+impl TrackingState {
+    const IDLE: Self = TrackingState {
+        active: false,
+        live_bytes: 0,
+        peak_bytes: 0,
+        allocations: 0,
+        allocated_bytes: 0,
+    };
+    const START: Self = TrackingState {
+        active: true,
+        ..Self::IDLE
+    };
+
+    fn allocate(&mut self, size: usize) {
+        self.live_bytes = self.live_bytes.saturating_add_unsigned(size);
+        self.peak_bytes = self.peak_bytes.max(self.live_bytes);
+        self.allocations += 1;
+        self.allocated_bytes = self.allocated_bytes.saturating_add(size);
+    }
+
+    fn free(&mut self, size: usize) {
+        self.live_bytes = self.live_bytes.saturating_sub_unsigned(size);
+    }
+
+    // Adds the allocations of a nested call to `track` to those of this one
+    fn absorb(&mut self, inner: TrackingState) {
+        self.peak_bytes = self
+            .peak_bytes
+            .max(self.live_bytes.saturating_add(inner.peak_bytes));
+        self.live_bytes = self.live_bytes.saturating_add(inner.live_bytes);
+        self.allocations += inner.allocations;
+        self.allocated_bytes = self.allocated_bytes.saturating_add(inner.allocated_bytes);
+    }
+
+    fn stats(&self) -> AllocStats {
+        AllocStats {
+            peak_bytes: self.peak_bytes.max(0) as usize,
+            retained_bytes: self.live_bytes.max(0) as usize,
+            allocations: self.allocations,
+            allocated_bytes: self.allocated_bytes,
+        }
+    }
+}
+
+// Stops tracking the closure passed to `track` if it panics
+struct RestoreOnPanic(TrackingState);
+
+impl Drop for RestoreOnPanic {
+    fn drop(&mut self) {
+        let _ = STATE.try_with(|state| state.set(self.0));
+    }
+}
+
+// Updates the state of the current thread if it is being tracked. The state is unavailable while
+// the thread is being torn down, in which case nothing is recorded.
+fn record<F: FnOnce(&mut TrackingState)>(f: F) {
+    let _ = STATE.try_with(|cell| {
+        let mut state = cell.get();
+        if state.active {
+            f(&mut state);
+            cell.set(state);
+        }
+    });
+}
@@ -0,0 +1,407 @@
+use crate::{path::push_key, NbtCompound, NbtReprError, NbtStructureError, NbtTag, NbtTagKind};
+use std::{borrow::Borrow, collections::BTreeMap, hash::Hash, ops::Bound};
+
+/// A hook which is notified of every tag written while merging compounds with
+/// [`NbtCompound::merge_with`].
+///
+/// Compounds present in both the target and the source are merged recursively, so the hook is not
+/// called for them. Every other tag supplied by the source replaces the tag at its path, or is
+/// inserted if there is none, and the hook is called once for each such tag. This is implemented
+/// for closures taking the path and the written tag.
+///
+/// [`NbtCompound::merge_with`]: crate::NbtCompound::merge_with
+pub trait MergeHook {
+    /// Called after the given tag was written at the given path, which is formatted like an
+    /// [`NbtPath`] relative to the root of the merge.
+    ///
+    /// [`NbtPath`]: crate::path::NbtPath
+    fn on_write(&mut self, path: &str, tag: &NbtTag);
+}
+
+impl<F: FnMut(&str, &NbtTag)> MergeHook for F {
+    #[inline]
+    fn on_write(&mut self, path: &str, tag: &NbtTag) {
+        self(path, tag)
+    }
+}
+
+/// A side table returned by [`merge_traced`], recording which source supplied the final value of
+/// each tag in a merged compound.
+///
+/// Each entry maps the path of a tag written by a merge to the source it came from. Entries for
+/// tags nested within a written tag are not recorded separately, so [`source`] looks up the
+/// nearest recorded ancestor of a path.
+///
+/// [`merge_traced`]: crate::merge::merge_traced
+/// [`source`]: crate::merge::Provenance::source
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Provenance<S> {
+    sources: BTreeMap<String, S>,
+}
+
+impl<S> Provenance<S> {
+    /// Returns an empty table.
+    pub fn new() -> Self {
+        Provenance {
+            sources: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the source which supplied the tag at the given path, or the source of the nearest
+    /// ancestor of that tag which was written as a whole. The path must use the format produced
+    /// by [`NbtPath`]'s `Display` implementation.
+    ///
+    /// [`NbtPath`]: crate::path::NbtPath
+    pub fn source(&self, path: &str) -> Option<&S> {
+        let mut path = path;
+        loop {
+            if let Some(source) = self.sources.get(path) {
+                return Some(source);
+            }
+
+            path = &path[.. parent_len(path)?];
+        }
+    }
+
+    /// Returns an iterator over the path of every written tag and the source which supplied it,
+    /// sorted by path.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &S)> + '_ {
+        self.sources
+            .iter()
+            .map(|(path, source)| (path.as_str(), source))
+    }
+
+    /// Returns the number of recorded paths.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.sources.len()
+    }
+
+    /// Returns whether no paths have been recorded.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.sources.is_empty()
+    }
+
+    /// Records that the tag at the given path was written by the given source, replacing the
+    /// records of any tags nested within it.
+    pub fn record(&mut self, path: &str, source: S) {
+        let nested = self
+            .sources
+            .range::<str, _>((Bound::Included(path), Bound::Unbounded))
+            .take_while(|(recorded, _)| recorded.starts_with(path))
+            .filter(|(recorded, _)| is_nested(recorded, path))
+            .map(|(recorded, _)| recorded.clone())
+            .collect::<Vec<_>>();
+        for recorded in nested {
+            self.sources.remove(&recorded);
+        }
+
+        self.sources.insert(path.to_owned(), source);
+    }
+}
+
+impl<S> Default for Provenance<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Merges the given layers of compounds in order, with later layers taking precedence, and
+/// returns the result along with the source of every tag in it.
+///
+/// Each layer is merged like Minecraft's `/data merge` command, as with [`NbtCompound::merge`]:
+/// compounds are merged recursively, and all other tags are replaced. The source paired with each
+/// layer is recorded for every tag that layer writes, which makes it easy to find out which file of
+/// a layered configuration, such as a stack of datapacks, supplied a value.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::merge;
+///
+/// let vanilla = compound! { "spawn": { "x": 0i32, "y": 64i32 }, "difficulty": "normal" };
+/// let server = compound! { "spawn": { "y": 80i32 }, "motd": "Hello" };
+/// let event = compound! { "difficulty": "hard" };
+///
+/// let (merged, provenance) = merge::merge_traced([
+///     ("vanilla", &vanilla),
+///     ("server.dat", &server),
+///     ("event.dat", &event),
+/// ]);
+///
+/// assert_eq!(merged, compound! {
+///     "spawn": { "x": 0i32, "y": 80i32 },
+///     "difficulty": "hard",
+///     "motd": "Hello"
+/// });
+/// assert_eq!(provenance.source("spawn.x"), Some(&"vanilla"));
+/// assert_eq!(provenance.source("spawn.y"), Some(&"server.dat"));
+/// assert_eq!(provenance.source("difficulty"), Some(&"event.dat"));
+/// assert_eq!(provenance.source("missing"), None);
+/// ```
+///
+/// [`NbtCompound::merge`]: crate::NbtCompound::merge
+pub fn merge_traced<'a, S, I>(layers: I) -> (NbtCompound, Provenance<S>)
+where
+    S: Clone,
+    I: IntoIterator<Item = (S, &'a NbtCompound)>,
+{
+    let mut merged = NbtCompound::new();
+    let mut provenance = Provenance::new();
+    for (source, layer) in layers {
+        merged.merge_with(layer, &mut |path: &str, _: &NbtTag| {
+            provenance.record(path, source.clone())
+        });
+    }
+    (merged, provenance)
+}
+
+/// A read-only view over a stack of compounds which resolves lookups as if the layers were merged,
+/// without building the merged compound.
+///
+/// Layers pushed later take precedence, as with [`merge_traced`]. Looking up a key returns the tag
+/// from the topmost layer which contains it, and [`get_compound`] returns a nested overlay of the
+/// compounds with the given key, so that the view agrees with the compound produced by merging
+/// the layers in order. A tag other than a compound hides every compound with the same key in the
+/// layers below it.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::merge::OverlayCompound;
+///
+/// let defaults = compound! { "spawn": { "x": 0i32, "y": 64i32 }, "pvp": true };
+/// let world = compound! { "spawn": { "y": 80i32 } };
+/// let player = compound! { "pvp": false };
+///
+/// let overlay = OverlayCompound::new()
+///     .with_layer(&defaults)
+///     .with_layer(&world)
+///     .with_layer(&player);
+///
+/// assert_eq!(overlay.get::<_, bool>("pvp").unwrap(), false);
+/// let spawn = overlay.get_compound("spawn").unwrap();
+/// assert_eq!(spawn.get::<_, i32>("x").unwrap(), 0);
+/// assert_eq!(spawn.get::<_, i32>("y").unwrap(), 80);
+/// assert_eq!(overlay.to_compound(), compound! {
+///     "spawn": { "x": 0i32, "y": 80i32 },
+///     "pvp": false
+/// });
+/// ```
+///
+/// [`merge_traced`]: crate::merge::merge_traced
+/// [`get_compound`]: crate::merge::OverlayCompound::get_compound
+#[derive(Debug, Clone, Default)]
+pub struct OverlayCompound<'a> {
+    layers: Vec<&'a NbtCompound>,
+}
+
+impl<'a> OverlayCompound<'a> {
+    /// Returns an overlay without any layers.
+    #[inline]
+    pub fn new() -> Self {
+        OverlayCompound { layers: Vec::new() }
+    }
+
+    /// Pushes the given compound on top of the existing layers.
+    #[inline]
+    pub fn push(&mut self, layer: &'a NbtCompound) {
+        self.layers.push(layer);
+    }
+
+    /// Pushes the given compound on top of the existing layers and returns this overlay.
+    #[inline]
+    pub fn with_layer(mut self, layer: &'a NbtCompound) -> Self {
+        self.push(layer);
+        self
+    }
+
+    /// Returns the layers of this overlay, from the bottom to the top.
+    #[inline]
+    pub fn layers(&self) -> &[&'a NbtCompound] {
+        &self.layers
+    }
+
+    /// Returns the tag with the given name from the topmost layer containing it, or `None` if no
+    /// layer contains it.
+    pub fn get_tag<K>(&self, name: &K) -> Option<&'a NbtTag>
+    where
+        String: Borrow<K>,
+        K: Hash + Eq + ?Sized,
+    {
+        self.layers
+            .iter()
+            .rev()
+            .find_map(|layer| layer.inner().get(name))
+    }
+
+    /// Returns the value of the tag with the given name from the topmost layer containing it, or
+    /// an error if no layer contains it or it has a different type. See [`NbtCompound::get`] for
+    /// details.
+    ///
+    /// Compounds are returned from a single layer, so [`get_compound`] should be used to look up
+    /// nested compounds through every layer.
+    ///
+    /// [`NbtCompound::get`]: crate::NbtCompound::get
+    /// [`get_compound`]: crate::merge::OverlayCompound::get_compound
+    pub fn get<'b, K, T>(&self, name: &'b K) -> Result<T, NbtReprError>
+    where
+        String: Borrow<K>,
+        K: Hash + Eq + ?Sized,
+        &'b K: Into<String>,
+        T: TryFrom<&'a NbtTag>,
+        T::Error: Into<anyhow::Error>,
+    {
+        T::try_from(
+            self.get_tag(name)
+                .ok_or_else(|| NbtStructureError::missing_tag(name))?,
+        )
+        .map_err(NbtReprError::from_any)
+    }
+
+    /// Returns an overlay of the compounds with the given name in each layer, or an error if no
+    /// layer contains a tag with that name or the topmost such tag is not a compound. The nested
+    /// overlay only includes the compounds above the topmost layer with a tag of another type.
+    pub fn get_compound<'b, K>(&self, name: &'b K) -> Result<OverlayCompound<'a>, NbtStructureError>
+    where
+        String: Borrow<K>,
+        K: Hash + Eq + ?Sized,
+        &'b K: Into<String>,
+    {
+        let mut layers = Vec::new();
+        for layer in self.layers.iter().rev() {
+            match layer.inner().get(name) {
+                Some(NbtTag::Compound(compound)) => layers.push(compound),
+                Some(tag) if layers.is_empty() =>
+                    return Err(NbtStructureError::type_mismatch(
+                        NbtTagKind::Compound.name(),
+                        tag.tag_name(),
+                    )),
+                Some(_) => break,
+                None => {}
+            }
+        }
+
+        if layers.is_empty() {
+            return Err(NbtStructureError::missing_tag(name));
+        }
+
+        layers.reverse();
+        Ok(OverlayCompound { layers })
+    }
+
+    /// Returns whether any layer contains a tag with the given name.
+    pub fn contains_key<K>(&self, key: &K) -> bool
+    where
+        String: Borrow<K>,
+        K: Hash + Eq + ?Sized,
+    {
+        self.layers.iter().any(|layer| layer.contains_key(key))
+    }
+
+    /// Returns an iterator over every key in this overlay and the tag from the topmost layer
+    /// containing it. Keys from higher layers are visited first, and each key is only visited
+    /// once.
+    pub fn iter(&self) -> impl Iterator<Item = (&'a str, &'a NbtTag)> + '_ {
+        self.layers
+            .iter()
+            .enumerate()
+            .rev()
+            .flat_map(move |(index, layer)| {
+                layer
+                    .inner()
+                    .iter()
+                    .filter(move |(key, _)| {
+                        !self.layers[index + 1 ..]
+                            .iter()
+                            .any(|above| above.contains_key(key.as_str()))
+                    })
+                    .map(|(key, tag)| (key.as_str(), tag))
+            })
+    }
+
+    /// Returns an iterator over every key in this overlay. See [`iter`] for details.
+    ///
+    /// [`iter`]: crate::merge::OverlayCompound::iter
+    pub fn keys(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.iter().map(|(key, _)| key)
+    }
+
+    /// Returns the number of distinct keys in this overlay.
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// Returns whether no layer contains any tags.
+    pub fn is_empty(&self) -> bool {
+        self.layers.iter().all(|layer| layer.is_empty())
+    }
+
+    /// Merges the layers of this overlay from the bottom to the top and returns the result, which
+    /// is the compound this overlay presents a view of.
+    pub fn to_compound(&self) -> NbtCompound {
+        let mut merged = NbtCompound::new();
+        for layer in &self.layers {
+            merged.merge(layer);
+        }
+        merged
+    }
+}
+
+impl<'a> FromIterator<&'a NbtCompound> for OverlayCompound<'a> {
+    /// Collects the layers of an overlay from the bottom to the top.
+    fn from_iter<I: IntoIterator<Item = &'a NbtCompound>>(iter: I) -> Self {
+        OverlayCompound {
+            layers: iter.into_iter().collect(),
+        }
+    }
+}
+
+pub(crate) fn merge_compound<H: MergeHook>(
+    target: &mut NbtCompound,
+    source: &NbtCompound,
+    path: &mut String,
+    hook: &mut H,
+) {
+    for (key, value) in &source.0 {
+        let len = path.len();
+        push_key(path, key);
+        match (target.0.get_mut(key), value) {
+            (Some(NbtTag::Compound(target)), NbtTag::Compound(value)) =>
+                merge_compound(target, value, path, hook),
+            (Some(tag), value) => {
+                *tag = value.clone();
+                hook.on_write(path, tag);
+            }
+            (None, value) => {
+                target.insert(key.clone(), value.clone());
+                hook.on_write(path, value);
+            }
+        }
+        path.truncate(len);
+    }
+}
+
+// Returns whether the first path refers to a tag nested within the tag at the second path
+fn is_nested(path: &str, ancestor: &str) -> bool {
+    path.len() > ancestor.len()
+        && path.starts_with(ancestor)
+        && matches!(path.as_bytes()[ancestor.len()], b'.' | b'[')
+}
+
+// Returns the length of the path of the parent of the tag at the given path, skipping over quoted
+// keys, or None if the path has no parent
+fn parent_len(path: &str) -> Option<usize> {
+    let mut parent = None;
+    let mut quoted = false;
+    let mut escaped = false;
+    for (index, ch) in path.char_indices() {
+        match ch {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            '.' | '[' if !quoted && index > 0 => parent = Some(index),
+            _ => {}
+        }
+    }
+    parent
+}
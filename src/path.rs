@@ -0,0 +1,1152 @@
+use crate::{snbt, snbt::SnbtError, NbtCompound, NbtList, NbtStructureError, NbtTag, NbtTagKind};
+use std::{
+    borrow::Cow,
+    error::Error,
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+/// A parsed NBT path, following the syntax used by Minecraft's `/data` command.
+///
+/// A path is a sequence of nodes, each of which selects zero or more tags from the tags selected
+/// by the previous node:
+///  - `key` or `"quoted key"`: selects the entry with the given key from a compound. Unquoted
+///    keys end at whitespace or at any of `.[]{}"'`, so keys containing those characters must be
+///    quoted with single or double quotes. Quoted keys may contain any characters, with `\"`, `\'`
+///    and `\\` escaping quotes and backslashes. [`escape_key`] quotes a key if necessary.
+///  - `key{...}`: selects the entry with the given key if it is a compound matching the given
+///    SNBT pattern.
+///  - `[n]`: selects the element at index `n` from a list. Negative indices count from the end of
+///    the list.
+///  - `[]`: selects every element of a list.
+///  - `[{...}]`: selects every element of a list which is a compound matching the given SNBT
+///    pattern.
+///
+/// Keys are separated from the previous node by a dot, whereas indices directly follow the
+/// previous node, as in `Inventory[0].tag.display.Name`. A path may also start with a pattern,
+/// such as `{OnGround:1b}.Pos`, in which case the root compound must match that pattern. See
+/// [`matches`] for how patterns are matched.
+///
+/// Since array elements are not stored as tags, indices can only be used with lists.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::path::NbtPath;
+///
+/// let player = compound! {
+///     "Inventory": [
+///         { "Slot": 0i8, "id": "minecraft:stone", "Count": 64i8 },
+///         { "Slot": 1i8, "id": "minecraft:dirt", "Count": 3i8 }
+///     ]
+/// };
+///
+/// let path: NbtPath = "Inventory[{Slot:1b}].id".parse().unwrap();
+/// assert_eq!(path.select(&player), vec![&NbtTag::from("minecraft:dirt")]);
+///
+/// let path = NbtPath::parse("Inventory[].Count").unwrap();
+/// assert_eq!(path.select(&player), vec![&NbtTag::Byte(64), &NbtTag::Byte(3)]);
+///
+/// let path = NbtPath::parse("Inventory[-1].Slot").unwrap();
+/// assert_eq!(path.select(&player), vec![&NbtTag::Byte(1)]);
+///
+/// let weird = compound! { "weird.key": ["a", "b"], "it's": { "[x]": 1i32 } };
+/// let path = NbtPath::parse("\"weird.key\"[0]").unwrap();
+/// assert_eq!(path.select(&weird), vec![&NbtTag::from("a")]);
+/// let path = NbtPath::parse("'it\\'s'.\"[x]\"").unwrap();
+/// assert_eq!(path.select(&weird), vec![&NbtTag::Int(1)]);
+/// ```
+///
+/// [`matches`]: crate::path::matches
+/// [`escape_key`]: crate::path::escape_key
+#[derive(Debug, Clone, PartialEq)]
+pub struct NbtPath {
+    nodes: Vec<PathNode>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum PathNode {
+    MatchRoot(NbtCompound),
+    Key(String),
+    MatchKey(String, NbtCompound),
+    Index(i32),
+    AllElements,
+    MatchElements(NbtCompound),
+}
+
+impl NbtPath {
+    /// Parses the given string into a path.
+    pub fn parse(path: &str) -> Result<Self, PathParseError> {
+        PathParser { path, index: 0 }.parse()
+    }
+
+    /// Returns every tag selected by this path from the given root compound, in the order they
+    /// appear in their parents.
+    pub fn select<'a>(&self, root: &'a NbtCompound) -> Vec<&'a NbtTag> {
+        let mut nodes = self.nodes.iter();
+        let mut selected = match nodes.next() {
+            Some(PathNode::MatchRoot(pattern)) =>
+                if matches(pattern, root) {
+                    match nodes.next() {
+                        Some(node) => node.select_from_compound(root),
+                        None => Vec::new(),
+                    }
+                } else {
+                    return Vec::new();
+                },
+            Some(node) => node.select_from_compound(root),
+            None => Vec::new(),
+        }
+        .into_iter()
+        .map(|(_, tag)| tag)
+        .collect::<Vec<_>>();
+
+        for node in nodes {
+            selected = selected
+                .into_iter()
+                .flat_map(|tag| node.select(tag).into_iter().map(|(_, tag)| tag))
+                .collect();
+        }
+
+        selected
+    }
+
+    // Returns the keys and indices leading to the only tag selected by this path, or None if it
+    // does not select exactly one tag
+    pub(crate) fn locate_one(&self, root: &NbtCompound) -> Option<Vec<Step<'static>>> {
+        let mut nodes = self.nodes.iter();
+        let first = match nodes.next()? {
+            PathNode::MatchRoot(pattern) if !matches(pattern, root) => return None,
+            PathNode::MatchRoot(_) => nodes.next()?,
+            node => node,
+        };
+
+        let mut selected = first
+            .select_from_compound(root)
+            .into_iter()
+            .map(|(step, tag)| (vec![step], tag))
+            .collect::<Vec<_>>();
+        for node in nodes {
+            selected = selected
+                .into_iter()
+                .flat_map(|(steps, tag)| {
+                    node.select(tag).into_iter().map(move |(step, tag)| {
+                        let mut steps = steps.clone();
+                        steps.push(step);
+                        (steps, tag)
+                    })
+                })
+                .collect();
+        }
+
+        match selected.pop() {
+            Some((steps, _)) if selected.is_empty() =>
+                Some(steps.into_iter().map(Step::into_owned).collect()),
+            _ => None,
+        }
+    }
+
+    // Sets every tag selected by this path to the given value, creating missing tags along the
+    // way, and returns the number of tags set
+    pub(crate) fn insert(
+        &self,
+        root: &mut NbtCompound,
+        value: &NbtTag,
+        options: InsertOptions,
+    ) -> Result<usize, NbtStructureError> {
+        match self.nodes.as_slice() {
+            [PathNode::MatchRoot(pattern), ..] if !matches(pattern, root) => Ok(0),
+            [PathNode::MatchRoot(_)] | [] => Ok(0),
+            [PathNode::MatchRoot(_), node, rest @ ..] | [node, rest @ ..] =>
+                node.insert_into_compound(root, rest, value, options),
+        }
+    }
+
+    // Removes every tag selected by this path, in the order they appear in their parents,
+    // optionally removing compounds and lists along the path which are left empty
+    pub(crate) fn remove(&self, root: &mut NbtCompound, prune_empty: bool) -> Vec<NbtTag> {
+        let mut removed = Vec::new();
+        match self.nodes.as_slice() {
+            [PathNode::MatchRoot(pattern), ..] if !matches(pattern, root) => {}
+            [PathNode::MatchRoot(_)] | [] => {}
+            [PathNode::MatchRoot(_), node, rest @ ..] | [node, rest @ ..] =>
+                node.remove_from_compound(root, rest, prune_empty, &mut removed),
+        }
+        removed
+    }
+}
+
+/// How list indices past the end of a list are handled when inserting tags by path, such as
+/// with [`NbtCompound::insert_path_with`].
+///
+/// [`NbtCompound::insert_path_with`]: crate::NbtCompound::insert_path_with
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ListPadding {
+    /// Indices must refer to existing elements, as with Minecraft's `/data` command.
+    #[default]
+    Reject,
+    /// The list is extended up to and including the index, filling the gap with empty tags of the
+    /// same kind as the element at the index, such as zero, an empty string or an empty compound.
+    ///
+    /// At most [`MAX_LIST_PADDING`] elements are added to a list, so indices further past its end
+    /// are rejected.
+    ///
+    /// [`MAX_LIST_PADDING`]: crate::path::MAX_LIST_PADDING
+    Fill,
+}
+
+/// The maximum number of elements added to a list to reach an index past its end when inserting
+/// tags with [`ListPadding::Fill`], which keeps a single large index from allocating a huge list.
+///
+/// [`ListPadding::Fill`]: crate::path::ListPadding::Fill
+pub const MAX_LIST_PADDING: usize = 1024;
+
+/// How tags which already exist at the destination are handled when moving tags by path with
+/// [`NbtCompound::move_path`].
+///
+/// [`NbtCompound::move_path`]: crate::NbtCompound::move_path
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PathConflict {
+    /// Existing tags are replaced, as with Minecraft's `/data modify ... set from` command.
+    #[default]
+    Replace,
+    /// Existing compounds are merged with the moved compound, recursively, as with
+    /// `/data modify ... merge from`. Tags of other types are replaced.
+    Merge,
+    /// An error is returned and nothing is moved.
+    Error,
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct InsertOptions {
+    pub(crate) padding: ListPadding,
+    pub(crate) merge: bool,
+}
+
+impl FromStr for NbtPath {
+    type Err = PathParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// A path which can be passed to the path methods of [`NbtCompound`] and [`NbtList`], such as
+/// [`NbtCompound::insert_path`].
+///
+/// This is implemented for strings, which are parsed on every call, and for [`NbtPath`] itself, so
+/// that a path used many times only needs to be parsed once.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::path::NbtPath;
+///
+/// let path = NbtPath::parse("\"minecraft:custom_data\".level")?;
+/// let mut items = vec![NbtCompound::new(), NbtCompound::new()];
+/// for (level, item) in items.iter_mut().enumerate() {
+///     item.insert_path(&path, level as i32)?;
+/// }
+/// assert_eq!(items[1].get_path(&path)?, &NbtTag::Int(1));
+/// assert_eq!(items[1].get_path("'minecraft:custom_data'.level")?, &NbtTag::Int(1));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// [`NbtCompound`]: crate::NbtCompound
+/// [`NbtList`]: crate::NbtList
+/// [`NbtCompound::insert_path`]: crate::NbtCompound::insert_path
+/// [`NbtPath`]: crate::path::NbtPath
+pub trait AsNbtPath {
+    /// Returns this path as an [`NbtPath`], parsing it if necessary.
+    ///
+    /// [`NbtPath`]: crate::path::NbtPath
+    fn as_nbt_path(&self) -> Result<Cow<'_, NbtPath>, PathParseError>;
+}
+
+impl AsNbtPath for NbtPath {
+    fn as_nbt_path(&self) -> Result<Cow<'_, NbtPath>, PathParseError> {
+        Ok(Cow::Borrowed(self))
+    }
+}
+
+impl AsNbtPath for str {
+    fn as_nbt_path(&self) -> Result<Cow<'_, NbtPath>, PathParseError> {
+        NbtPath::parse(self).map(Cow::Owned)
+    }
+}
+
+impl AsNbtPath for String {
+    fn as_nbt_path(&self) -> Result<Cow<'_, NbtPath>, PathParseError> {
+        self.as_str().as_nbt_path()
+    }
+}
+
+/// Formats this path using the same syntax it is parsed from, quoting keys which contain
+/// characters other than ASCII letters and digits, `_`, `-` and `+`.
+///
+/// ```
+/// use quartz_nbt::path::NbtPath;
+///
+/// let path = NbtPath::parse("Inventory[0].\"display name\"[].Lore").unwrap();
+/// assert_eq!(path.to_string(), "Inventory[0].\"display name\"[].Lore");
+/// assert_eq!(path.to_string().parse::<NbtPath>().unwrap(), path);
+/// ```
+impl Display for NbtPath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut path = String::new();
+        for node in &self.nodes {
+            match node {
+                PathNode::MatchRoot(pattern) => path.push_str(&pattern.to_snbt()),
+                PathNode::Key(key) => push_key(&mut path, key),
+                PathNode::MatchKey(key, pattern) => {
+                    push_key(&mut path, key);
+                    path.push_str(&pattern.to_snbt());
+                }
+                PathNode::Index(index) => {
+                    path.push('[');
+                    path.push_str(&index.to_string());
+                    path.push(']');
+                }
+                PathNode::AllElements => path.push_str("[]"),
+                PathNode::MatchElements(pattern) => {
+                    path.push('[');
+                    path.push_str(&pattern.to_snbt());
+                    path.push(']');
+                }
+            }
+        }
+        f.write_str(&path)
+    }
+}
+
+impl PathNode {
+    // Returns every tag selected by this node from the given tag along with the step to it
+    fn select<'a>(&self, tag: &'a NbtTag) -> Vec<(Step<'a>, &'a NbtTag)> {
+        match (self, tag) {
+            (PathNode::Index(index), NbtTag::List(list)) => {
+                let index = if *index < 0 {
+                    list.len().checked_sub(index.unsigned_abs() as usize)
+                } else {
+                    Some(*index as usize)
+                };
+                index
+                    .and_then(|index| Some((Step::Index(index), list.as_ref().get(index)?)))
+                    .into_iter()
+                    .collect()
+            }
+            (PathNode::AllElements, NbtTag::List(list)) => list
+                .iter()
+                .enumerate()
+                .map(|(index, element)| (Step::Index(index), element))
+                .collect(),
+            (PathNode::MatchElements(pattern), NbtTag::List(list)) => list
+                .iter()
+                .enumerate()
+                .filter(|(_, element)| match element {
+                    NbtTag::Compound(compound) => matches(pattern, compound),
+                    _ => false,
+                })
+                .map(|(index, element)| (Step::Index(index), element))
+                .collect(),
+            (_, NbtTag::Compound(compound)) => self.select_from_compound(compound),
+            _ => Vec::new(),
+        }
+    }
+
+    fn select_from_compound<'a>(&self, compound: &'a NbtCompound) -> Vec<(Step<'a>, &'a NbtTag)> {
+        match self {
+            PathNode::Key(key) => compound
+                .inner()
+                .get_key_value(key)
+                .map(|(key, tag)| (Step::Key(Cow::Borrowed(key)), tag))
+                .into_iter()
+                .collect(),
+            PathNode::MatchKey(key, pattern) => compound
+                .inner()
+                .get_key_value(key)
+                .filter(|(_, tag)| match tag {
+                    NbtTag::Compound(compound) => matches(pattern, compound),
+                    _ => false,
+                })
+                .map(|(key, tag)| (Step::Key(Cow::Borrowed(key)), tag))
+                .into_iter()
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    // The kind of tag which this node needs to exist in its parent in order to select from it
+    fn container_kind(&self) -> NbtTagKind {
+        match self {
+            PathNode::Index(_) | PathNode::AllElements | PathNode::MatchElements(_) =>
+                NbtTagKind::List,
+            _ => NbtTagKind::Compound,
+        }
+    }
+
+    fn insert(
+        &self,
+        tag: &mut NbtTag,
+        rest: &[PathNode],
+        value: &NbtTag,
+        options: InsertOptions,
+    ) -> Result<usize, NbtStructureError> {
+        let list = match (self, tag) {
+            (PathNode::Key(_) | PathNode::MatchKey(..), NbtTag::Compound(compound)) =>
+                return self.insert_into_compound(compound, rest, value, options),
+            (_, NbtTag::List(list)) if self.container_kind() == NbtTagKind::List => list,
+            (_, tag) =>
+                return Err(NbtStructureError::type_mismatch(
+                    self.container_kind().name(),
+                    tag.tag_name(),
+                )),
+        };
+
+        // The kind of element which is either replaced or descended into
+        let element_kind = match rest.first() {
+            Some(node) => node.container_kind(),
+            None => value.kind(),
+        };
+
+        match self {
+            PathNode::Index(index) => {
+                let length = list.len();
+                let index = if *index < 0 {
+                    length
+                        .checked_sub(index.unsigned_abs() as usize)
+                        .ok_or_else(|| {
+                            NbtStructureError::invalid_index(index.unsigned_abs() as usize, length)
+                        })?
+                } else {
+                    *index as usize
+                };
+
+                if index >= length {
+                    if options.padding == ListPadding::Reject || index - length >= MAX_LIST_PADDING
+                    {
+                        return Err(NbtStructureError::invalid_index(index, length));
+                    }
+
+                    check_element_kind(list, element_kind, None)?;
+                    let filler = empty_tag(element_kind);
+                    list.0.resize(index + 1, filler);
+                } else if rest.is_empty() {
+                    check_element_kind(list, element_kind, Some(index))?;
+                }
+
+                insert_into_element(&mut list.0[index], rest, value, options)
+            }
+            PathNode::AllElements => {
+                if rest.is_empty() {
+                    check_element_kind(list, element_kind, None)?;
+                }
+
+                let mut inserted = 0;
+                for element in list.0.iter_mut() {
+                    inserted += insert_into_element(element, rest, value, options)?;
+                }
+                Ok(inserted)
+            }
+            PathNode::MatchElements(pattern) => {
+                if rest.is_empty() {
+                    check_element_kind(list, element_kind, None)?;
+                }
+
+                let mut inserted = 0;
+                let mut matched = false;
+                for element in list.0.iter_mut() {
+                    if let NbtTag::Compound(compound) = element {
+                        if matches(pattern, compound) {
+                            matched = true;
+                            inserted += insert_into_element(element, rest, value, options)?;
+                        }
+                    }
+                }
+
+                // Create an element from the pattern if none matched, as Minecraft does
+                if !matched {
+                    check_element_kind(list, NbtTagKind::Compound, None)?;
+                    list.push(pattern.clone());
+                    let element = list.0.last_mut().unwrap();
+                    inserted += insert_into_element(element, rest, value, options)?;
+                }
+
+                Ok(inserted)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn insert_into_compound(
+        &self,
+        compound: &mut NbtCompound,
+        rest: &[PathNode],
+        value: &NbtTag,
+        options: InsertOptions,
+    ) -> Result<usize, NbtStructureError> {
+        match self {
+            PathNode::Key(key) => match rest.first() {
+                Some(next) => {
+                    let tag = compound
+                        .0
+                        .entry(key.clone())
+                        .or_insert_with(|| empty_tag(next.container_kind()));
+                    next.insert(tag, &rest[1 ..], value, options)
+                }
+                None => {
+                    match compound.0.get_mut(key) {
+                        Some(tag) if options.merge => merge_into(tag, value),
+                        _ => compound.insert(key.clone(), value.clone()),
+                    }
+                    Ok(1)
+                }
+            },
+            PathNode::MatchKey(key, pattern) => {
+                let tag = compound
+                    .0
+                    .entry(key.clone())
+                    .or_insert_with(|| NbtTag::Compound(pattern.clone()));
+                match tag {
+                    NbtTag::Compound(inner) if matches(pattern, inner) =>
+                        insert_into_element(tag, rest, value, options),
+                    _ => Ok(0),
+                }
+            }
+            _ => Err(NbtStructureError::type_mismatch(
+                NbtTagKind::List.name(),
+                NbtTagKind::Compound.name(),
+            )),
+        }
+    }
+
+    fn remove(
+        &self,
+        tag: &mut NbtTag,
+        rest: &[PathNode],
+        prune_empty: bool,
+        removed: &mut Vec<NbtTag>,
+    ) {
+        match (self, tag) {
+            (PathNode::Key(_) | PathNode::MatchKey(..), NbtTag::Compound(compound)) =>
+                self.remove_from_compound(compound, rest, prune_empty, removed),
+            (PathNode::Index(index), NbtTag::List(list)) => {
+                let index = if *index < 0 {
+                    list.len().checked_sub(index.unsigned_abs() as usize)
+                } else {
+                    Some(*index as usize).filter(|&index| index < list.len())
+                };
+
+                if let Some(index) = index {
+                    match rest {
+                        [node, rest @ ..] =>
+                            node.remove(&mut list.0[index], rest, prune_empty, removed),
+                        [] => removed.push(list.0.remove(index)),
+                    }
+                }
+            }
+            (PathNode::AllElements, NbtTag::List(list)) => match rest {
+                [node, rest @ ..] =>
+                    for element in list.0.iter_mut() {
+                        node.remove(element, rest, prune_empty, removed);
+                    },
+                [] => removed.append(&mut list.0),
+            },
+            (PathNode::MatchElements(pattern), NbtTag::List(list)) => {
+                let is_match = |element: &NbtTag| match element {
+                    NbtTag::Compound(compound) => matches(pattern, compound),
+                    _ => false,
+                };
+
+                match rest {
+                    [node, rest @ ..] =>
+                        for element in list.0.iter_mut().filter(|element| is_match(element)) {
+                            node.remove(element, rest, prune_empty, removed);
+                        },
+                    [] => {
+                        let (matching, kept) = std::mem::take(&mut list.0)
+                            .into_iter()
+                            .partition::<Vec<_>, _>(is_match);
+                        list.0 = kept;
+                        removed.extend(matching);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn remove_from_compound(
+        &self,
+        compound: &mut NbtCompound,
+        rest: &[PathNode],
+        prune_empty: bool,
+        removed: &mut Vec<NbtTag>,
+    ) {
+        let key = match self {
+            PathNode::Key(key) => key,
+            PathNode::MatchKey(key, pattern) => match compound.0.get(key) {
+                Some(NbtTag::Compound(inner)) if matches(pattern, inner) => key,
+                _ => return,
+            },
+            _ => return,
+        };
+
+        match rest {
+            [node, rest @ ..] => {
+                let count = removed.len();
+                let is_empty = match compound.0.get_mut(key) {
+                    Some(tag) => {
+                        node.remove(tag, rest, prune_empty, removed);
+                        match tag {
+                            NbtTag::Compound(inner) => inner.is_empty(),
+                            NbtTag::List(list) => list.is_empty(),
+                            _ => false,
+                        }
+                    }
+                    None => false,
+                };
+
+                if prune_empty && is_empty && removed.len() > count {
+                    remove_key(compound, key);
+                }
+            }
+            [] => removed.extend(remove_key(compound, key)),
+        }
+    }
+}
+
+// Removes the given key while keeping the order of the remaining entries
+pub(crate) fn remove_key(compound: &mut NbtCompound, key: &str) -> Option<NbtTag> {
+    #[cfg(feature = "preserve_order")]
+    return compound.0.shift_remove(key);
+    #[cfg(not(feature = "preserve_order"))]
+    return compound.0.remove(key);
+}
+
+// Replaces the given tag if the path ends with it, otherwise inserts into it
+fn insert_into_element(
+    tag: &mut NbtTag,
+    rest: &[PathNode],
+    value: &NbtTag,
+    options: InsertOptions,
+) -> Result<usize, NbtStructureError> {
+    match rest {
+        [node, rest @ ..] => node.insert(tag, rest, value, options),
+        [] => {
+            if options.merge {
+                merge_into(tag, value);
+            } else {
+                *tag = value.clone();
+            }
+            Ok(1)
+        }
+    }
+}
+
+// Merges the given value into the given tag, recursively merging compounds like Minecraft does
+fn merge_into(tag: &mut NbtTag, value: &NbtTag) {
+    match (tag, value) {
+        (NbtTag::Compound(compound), NbtTag::Compound(value)) => compound.merge(value),
+        (tag, value) => *tag = value.clone(),
+    }
+}
+
+// Walks the tags nested within a compound depth first, yielding every tag which is not a list or
+// compound along with the path to it
+pub(crate) struct Leaves<'a> {
+    stack: Vec<(Vec<PathNode>, &'a NbtTag)>,
+}
+
+impl<'a> Leaves<'a> {
+    pub(crate) fn new(root: &'a NbtCompound) -> Self {
+        let mut leaves = Leaves { stack: Vec::new() };
+        leaves.push_children(
+            &[],
+            root.0
+                .iter()
+                .map(|(key, tag)| (PathNode::Key(key.clone()), tag)),
+        );
+        leaves
+    }
+
+    #[cfg(feature = "rayon")]
+    pub(crate) fn from_entry(key: &str, tag: &'a NbtTag) -> Self {
+        Leaves {
+            stack: vec![(vec![PathNode::Key(key.to_owned())], tag)],
+        }
+    }
+
+    // Pushes the given children so that they are popped in the order they are given
+    fn push_children<I>(&mut self, parent: &[PathNode], children: I)
+    where I: Iterator<Item = (PathNode, &'a NbtTag)> {
+        let start = self.stack.len();
+        self.stack.extend(children.map(|(node, tag)| {
+            let mut nodes = Vec::with_capacity(parent.len() + 1);
+            nodes.extend_from_slice(parent);
+            nodes.push(node);
+            (nodes, tag)
+        }));
+        self.stack[start ..].reverse();
+    }
+}
+
+impl<'a> Iterator for Leaves<'a> {
+    type Item = (NbtPath, &'a NbtTag);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((nodes, tag)) = self.stack.pop() {
+            match tag {
+                NbtTag::List(list) => self.push_children(
+                    &nodes,
+                    list.0
+                        .iter()
+                        .enumerate()
+                        .map(|(index, tag)| (PathNode::Index(index as i32), tag)),
+                ),
+                NbtTag::Compound(compound) => self.push_children(
+                    &nodes,
+                    compound
+                        .0
+                        .iter()
+                        .map(|(key, tag)| (PathNode::Key(key.clone()), tag)),
+                ),
+                tag => return Some((NbtPath { nodes }, tag)),
+            }
+        }
+
+        None
+    }
+}
+
+// A key or index leading from a compound or list to one of its children
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Step<'a> {
+    Key(Cow<'a, str>),
+    Index(usize),
+}
+
+impl Step<'_> {
+    fn into_owned(self) -> Step<'static> {
+        match self {
+            Step::Key(key) => Step::Key(Cow::Owned(key.into_owned())),
+            Step::Index(index) => Step::Index(index),
+        }
+    }
+}
+
+enum Parent<'a> {
+    Compound(&'a mut NbtCompound),
+    List(&'a mut NbtList),
+}
+
+// Follows the given steps from the root, returning the compound or list they lead to
+fn follow<'a>(root: &'a mut NbtCompound, steps: &[Step<'_>]) -> Option<Parent<'a>> {
+    let mut parent = Parent::Compound(root);
+    for step in steps {
+        let child = match (step, parent) {
+            (Step::Key(key), Parent::Compound(compound)) => compound.0.get_mut(key.as_ref())?,
+            (&Step::Index(index), Parent::List(list)) => list.0.get_mut(index)?,
+            _ => return None,
+        };
+
+        parent = match child {
+            NbtTag::Compound(compound) => Parent::Compound(compound),
+            NbtTag::List(list) => Parent::List(list),
+            _ => return None,
+        };
+    }
+    Some(parent)
+}
+
+// Removes and returns the tag at the given location
+pub(crate) fn remove_at(root: &mut NbtCompound, location: &[Step<'_>]) -> Option<NbtTag> {
+    let (last, parents) = location.split_last()?;
+    match (last, follow(root, parents)?) {
+        (Step::Key(key), Parent::Compound(compound)) => remove_key(compound, key),
+        (&Step::Index(index), Parent::List(list)) if index < list.len() =>
+            Some(list.0.remove(index)),
+        _ => None,
+    }
+}
+
+// Puts a tag removed with remove_at back where it was, as long as its parent still exists
+pub(crate) fn restore_at(root: &mut NbtCompound, location: &[Step<'_>], tag: NbtTag) {
+    if let Some((last, parents)) = location.split_last() {
+        match (last, follow(root, parents)) {
+            (Step::Key(key), Some(Parent::Compound(compound))) =>
+                compound.insert(key.as_ref(), tag),
+            (&Step::Index(index), Some(Parent::List(list))) if index <= list.len() =>
+                list.0.insert(index, tag),
+            _ => {}
+        }
+    }
+}
+
+// Checks that a list can hold an element of the given kind, ignoring the element being replaced
+fn check_element_kind(
+    list: &NbtList,
+    kind: NbtTagKind,
+    replacing: Option<usize>,
+) -> Result<(), NbtStructureError> {
+    match list
+        .iter()
+        .enumerate()
+        .find(|&(index, _)| Some(index) != replacing)
+    {
+        Some((_, element)) if element.kind() != kind => Err(NbtStructureError::type_mismatch(
+            kind.name(),
+            element.tag_name(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn empty_tag(kind: NbtTagKind) -> NbtTag {
+    match kind {
+        NbtTagKind::Byte => NbtTag::Byte(0),
+        NbtTagKind::Short => NbtTag::Short(0),
+        NbtTagKind::Int => NbtTag::Int(0),
+        NbtTagKind::Long => NbtTag::Long(0),
+        NbtTagKind::Float => NbtTag::Float(0.0),
+        NbtTagKind::Double => NbtTag::Double(0.0),
+        NbtTagKind::ByteArray => NbtTag::ByteArray(Vec::new()),
+        NbtTagKind::String => NbtTag::String(String::new()),
+        NbtTagKind::List => NbtTag::List(NbtList::new()),
+        NbtTagKind::Compound => NbtTag::Compound(NbtCompound::new()),
+        NbtTagKind::IntArray => NbtTag::IntArray(Vec::new()),
+        NbtTagKind::LongArray => NbtTag::LongArray(Vec::new()),
+    }
+}
+
+/// Returns whether the given compound matches the given pattern, following the semantics of
+/// Minecraft's NBT paths.
+///
+/// A compound matches a pattern if it contains every key in the pattern with a matching value.
+/// Nested compounds are matched in the same way, so they may contain additional keys. A list
+/// matches a non-empty pattern list if every element of the pattern matches some element of the
+/// list, and an empty pattern list only matches an empty list. All other values must be equal.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::path;
+///
+/// let item = compound! {
+///     "id": "minecraft:diamond_sword",
+///     "tag": { "Damage": 3i32, "Enchantments": [{ "id": "sharpness", "lvl": 5i16 }] }
+/// };
+///
+/// assert!(path::matches(&compound! { "id": "minecraft:diamond_sword" }, &item));
+/// assert!(path::matches(&compound! { "tag": { "Enchantments": [{ "id": "sharpness" }] } }, &item));
+/// assert!(!path::matches(&compound! { "tag": { "Damage": 3i16 } }, &item));
+/// ```
+pub fn matches(pattern: &NbtCompound, compound: &NbtCompound) -> bool {
+    compare(pattern, compound, true)
+}
+
+pub(crate) fn matches_tag(pattern: &NbtTag, tag: &NbtTag) -> bool {
+    compare_tag(pattern, tag, true)
+}
+
+// Follows Minecraft's `NbtUtils.compareNbt`, where lists only need to contain the elements of the
+// pattern with `partial_lists`, and must otherwise be equal to it
+pub(crate) fn compare(pattern: &NbtCompound, compound: &NbtCompound, partial_lists: bool) -> bool {
+    pattern.inner().iter().all(|(key, pattern)| {
+        compound
+            .inner()
+            .get(key)
+            .is_some_and(|tag| compare_tag(pattern, tag, partial_lists))
+    })
+}
+
+pub(crate) fn compare_tag(pattern: &NbtTag, tag: &NbtTag, partial_lists: bool) -> bool {
+    match (pattern, tag) {
+        (NbtTag::Compound(pattern), NbtTag::Compound(compound)) =>
+            compare(pattern, compound, partial_lists),
+        (NbtTag::List(pattern), NbtTag::List(list)) if partial_lists =>
+            if pattern.is_empty() {
+                list.is_empty()
+            } else {
+                pattern.iter().all(|pattern| {
+                    list.iter()
+                        .any(|tag| compare_tag(pattern, tag, partial_lists))
+                })
+            },
+        _ => pattern == tag,
+    }
+}
+
+struct PathParser<'a> {
+    path: &'a str,
+    index: usize,
+}
+
+impl PathParser<'_> {
+    fn parse(mut self) -> Result<NbtPath, PathParseError> {
+        let mut nodes = Vec::new();
+
+        if self.peek() == Some('{') {
+            nodes.push(PathNode::MatchRoot(self.parse_pattern()?));
+            if self.peek().is_none() {
+                return Err(self.error(PathParseErrorKind::UnexpectedEnd));
+            }
+            self.expect('.')?;
+        }
+
+        loop {
+            match self.peek() {
+                Some('[') if !nodes.is_empty() => nodes.push(self.parse_bracket()?),
+                Some(_) => nodes.push(self.parse_key()?),
+                None => return Err(self.error(PathParseErrorKind::UnexpectedEnd)),
+            }
+
+            // Indices may be chained directly, whereas keys must be preceded by a dot
+            while self.peek() == Some('[') {
+                nodes.push(self.parse_bracket()?);
+            }
+
+            match self.peek() {
+                Some('.') => self.index += 1,
+                Some(ch) => return Err(self.error(PathParseErrorKind::UnexpectedChar(ch))),
+                None => return Ok(NbtPath { nodes }),
+            }
+        }
+    }
+
+    fn parse_key(&mut self) -> Result<PathNode, PathParseError> {
+        let key = match self.peek() {
+            Some(quote @ ('"' | '\'')) => self.parse_quoted(quote)?,
+            _ => {
+                let start = self.index;
+                while let Some(ch) = self.peek() {
+                    if matches!(ch, '.' | '[' | ']' | '{' | '}' | '"' | '\'') || ch.is_whitespace()
+                    {
+                        break;
+                    }
+                    self.index += ch.len_utf8();
+                }
+
+                if self.index == start {
+                    return Err(match self.peek() {
+                        Some(ch) => self.error(PathParseErrorKind::UnexpectedChar(ch)),
+                        None => self.error(PathParseErrorKind::UnexpectedEnd),
+                    });
+                }
+
+                self.path[start .. self.index].to_owned()
+            }
+        };
+
+        if self.peek() == Some('{') {
+            Ok(PathNode::MatchKey(key, self.parse_pattern()?))
+        } else {
+            Ok(PathNode::Key(key))
+        }
+    }
+
+    fn parse_quoted(&mut self, quote: char) -> Result<String, PathParseError> {
+        self.index += 1;
+        let mut key = String::new();
+        loop {
+            match self.next() {
+                Some(ch) if ch == quote => return Ok(key),
+                Some('\\') => match self.next() {
+                    Some(ch @ ('"' | '\'' | '\\')) => key.push(ch),
+                    Some(ch) => {
+                        self.index -= ch.len_utf8();
+                        return Err(self.error(PathParseErrorKind::InvalidEscape(ch)));
+                    }
+                    None => return Err(self.error(PathParseErrorKind::UnexpectedEnd)),
+                },
+                Some(ch) => key.push(ch),
+                None => return Err(self.error(PathParseErrorKind::UnexpectedEnd)),
+            }
+        }
+    }
+
+    fn parse_bracket(&mut self) -> Result<PathNode, PathParseError> {
+        self.expect('[')?;
+        let node = match self.peek() {
+            Some(']') => PathNode::AllElements,
+            Some('{') => PathNode::MatchElements(self.parse_pattern()?),
+            _ => {
+                let start = self.index;
+                while let Some(ch) = self.peek().filter(|&ch| ch == '-' || ch.is_ascii_digit()) {
+                    self.index += ch.len_utf8();
+                }
+
+                match self.path[start .. self.index].parse() {
+                    Ok(index) => PathNode::Index(index),
+                    Err(_) => {
+                        self.index = start;
+                        return Err(self.error(PathParseErrorKind::InvalidIndex));
+                    }
+                }
+            }
+        };
+        self.expect(']')?;
+        Ok(node)
+    }
+
+    fn parse_pattern(&mut self) -> Result<NbtCompound, PathParseError> {
+        match snbt::parse_and_size(&self.path[self.index ..]) {
+            Ok((pattern, size)) => {
+                self.index += size;
+                Ok(pattern)
+            }
+            Err(e) => Err(self.error(PathParseErrorKind::InvalidPattern(e))),
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), PathParseError> {
+        match self.peek() {
+            Some(ch) if ch == expected => {
+                self.index += ch.len_utf8();
+                Ok(())
+            }
+            Some(ch) => Err(self.error(PathParseErrorKind::UnexpectedChar(ch))),
+            None => Err(self.error(PathParseErrorKind::UnexpectedEnd)),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.path[self.index ..].chars().next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let ch = self.peek()?;
+        self.index += ch.len_utf8();
+        Some(ch)
+    }
+
+    fn error(&self, kind: PathParseErrorKind) -> PathParseError {
+        PathParseError {
+            index: self.index,
+            kind,
+        }
+    }
+}
+
+/// An error which occurred while parsing an [`NbtPath`].
+///
+/// [`NbtPath`]: crate::path::NbtPath
+#[derive(Debug)]
+pub struct PathParseError {
+    index: usize,
+    kind: PathParseErrorKind,
+}
+
+impl PathParseError {
+    /// Returns the byte index in the path at which the error occurred.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the kind of error which occurred.
+    pub fn kind(&self) -> &PathParseErrorKind {
+        &self.kind
+    }
+}
+
+impl Display for PathParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at index {} of NBT path", self.kind, self.index)
+    }
+}
+
+impl Error for PathParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.kind {
+            PathParseErrorKind::InvalidPattern(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// The kinds of errors which can occur while parsing an [`NbtPath`].
+///
+/// [`NbtPath`]: crate::path::NbtPath
+#[derive(Debug)]
+pub enum PathParseErrorKind {
+    /// The path ended unexpectedly.
+    UnexpectedEnd,
+    /// An unexpected character was encountered.
+    UnexpectedChar(char),
+    /// An invalid escape sequence was encountered in a quoted key.
+    InvalidEscape(char),
+    /// A list index was not a valid integer.
+    InvalidIndex,
+    /// A compound pattern was not valid SNBT.
+    InvalidPattern(SnbtError),
+}
+
+impl Display for PathParseErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PathParseErrorKind::UnexpectedEnd => write!(f, "Unexpected end"),
+            PathParseErrorKind::UnexpectedChar(ch) => write!(f, "Unexpected character '{}'", ch),
+            PathParseErrorKind::InvalidEscape(ch) =>
+                write!(f, "Invalid escape sequence '\\{}'", ch),
+            PathParseErrorKind::InvalidIndex => write!(f, "Invalid list index"),
+            PathParseErrorKind::InvalidPattern(e) => write!(f, "Invalid compound pattern ({})", e),
+        }
+    }
+}
+
+/// Returns the given compound key as it is written in an [`NbtPath`], quoting and escaping it if
+/// it contains characters other than ASCII letters and digits, `_`, `-` and `+`, as Minecraft does.
+///
+/// ```
+/// use quartz_nbt::path::escape_key;
+///
+/// assert_eq!(escape_key("Inventory"), "Inventory");
+/// assert_eq!(escape_key("weird.key"), "\"weird.key\"");
+/// assert_eq!(escape_key("say \"hi\""), r#""say \"hi\"""#);
+/// assert_eq!(escape_key(""), "\"\"");
+/// ```
+///
+/// [`NbtPath`]: crate::path::NbtPath
+pub fn escape_key(key: &str) -> Cow<'_, str> {
+    if is_simple_key(key) {
+        return Cow::Borrowed(key);
+    }
+
+    let mut escaped = String::with_capacity(key.len() + 2);
+    push_quoted_key(&mut escaped, key);
+    Cow::Owned(escaped)
+}
+
+pub(crate) fn push_key(path: &mut String, key: &str) {
+    if !path.is_empty() {
+        path.push('.');
+    }
+
+    if is_simple_key(key) {
+        path.push_str(key);
+    } else {
+        push_quoted_key(path, key);
+    }
+}
+
+// Follow the conventions of Minecraft's NBT paths, which quote any key that contains characters
+// other than these
+fn is_simple_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '_' | '-' | '+'))
+}
+
+fn push_quoted_key(path: &mut String, key: &str) {
+    path.push('"');
+    for ch in key.chars() {
+        if ch == '"' || ch == '\\' {
+            path.push('\\');
+        }
+        path.push(ch);
+    }
+    path.push('"');
+}
+
+pub(crate) fn push_index(path: &mut String, index: usize) {
+    path.push('[');
+    path.push_str(&index.to_string());
+    path.push(']');
+}
@@ -0,0 +1,27 @@
+pub use crate::{
+    compound,
+    convert::ConversionPolicy,
+    io::{
+        read_nbt,
+        read_nbt_bytes,
+        write_nbt,
+        write_nbt_bytes,
+        Flavor,
+        NbtIoError,
+        NbtReadBuilder,
+        NbtWriteBuilder,
+    },
+    path::NbtPath,
+    sanitize::SanitizeRules,
+    snbt::{self, FormatOptions, SnbtError},
+    NbtCompound,
+    NbtList,
+    NbtReprError,
+    NbtStructureError,
+    NbtTag,
+    NbtTagKind,
+    TagLookup,
+};
+
+#[cfg(feature = "serde")]
+pub use crate::serde::{deserialize, serialize, DeserializerBuilder, SerializerBuilder};
@@ -1,11 +1,8 @@
-use crate::{io::NbtIoError, NbtTag};
+use crate::{cast, io::NbtIoError, NbtTag};
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 use std::{
-    io::{Read, Result, Write},
-    mem::ManuallyDrop,
-    ptr,
+    io::{self, ErrorKind, Read, Result, Write},
     result::Result as StdResult,
-    slice,
 };
 
 #[inline]
@@ -58,6 +55,11 @@ pub fn read_i32<R: Read>(reader: &mut R) -> Result<i32> {
     reader.read_i32::<BigEndian>()
 }
 
+#[inline]
+pub fn read_u32<R: Read>(reader: &mut R) -> Result<u32> {
+    reader.read_u32::<BigEndian>()
+}
+
 #[inline]
 pub fn read_i64<R: Read>(reader: &mut R) -> Result<i64> {
     reader.read_i64::<BigEndian>()
@@ -73,6 +75,61 @@ pub fn read_f64<R: Read>(reader: &mut R) -> Result<f64> {
     reader.read_f64::<BigEndian>()
 }
 
+// The number of elements for which space is reserved up front when reading an array or list. Lengths
+// come from untrusted input, so longer collections grow as their elements are actually read rather
+// than being allocated all at once.
+pub const PREALLOC_LIMIT: usize = 1 << 16;
+
+// Reads the length prefix of an array or list, which is stored as a signed integer
+#[inline]
+pub fn read_len<R: Read>(reader: &mut R) -> StdResult<usize, NbtIoError> {
+    let len = read_i32(reader)?;
+    usize::try_from(len).map_err(|_| NbtIoError::NegativeLength(len))
+}
+
+// Converts the length of an array or list to the signed integer it is prefixed with
+#[inline]
+pub fn checked_len(len: usize) -> StdResult<i32, NbtIoError> {
+    i32::try_from(len).map_err(|_| NbtIoError::LengthTooLarge(len))
+}
+
+#[inline]
+pub fn write_len<W: Write>(writer: &mut W, len: usize) -> StdResult<(), NbtIoError> {
+    write_i32(writer, checked_len(len)?)?;
+    Ok(())
+}
+
+pub fn read_byte_array<R: Read>(reader: &mut R, len: usize) -> Result<Vec<u8>> {
+    if len <= PREALLOC_LIMIT {
+        let mut bytes = vec![0; len];
+        reader.read_exact(&mut bytes)?;
+        return Ok(bytes);
+    }
+
+    let mut bytes = Vec::with_capacity(PREALLOC_LIMIT);
+    reader.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() < len {
+        return Err(io::Error::from(ErrorKind::UnexpectedEof));
+    }
+
+    Ok(bytes)
+}
+
+// Reads a VarInt as used by Minecraft's network protocol, returning `None` if it is longer than
+// five bytes
+pub fn read_varint<R: Read>(reader: &mut R) -> Result<Option<i32>> {
+    let mut value = 0u32;
+    for shift in (0 .. 35).step_by(7) {
+        let byte = read_u8(reader)?;
+        value |= ((byte & 0x7F) as u32) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(Some(value as i32));
+        }
+    }
+
+    Ok(None)
+}
+
 pub fn read_string<R: Read>(reader: &mut R) -> StdResult<String, NbtIoError> {
     let len = read_u16(reader)? as usize;
     let mut bytes = vec![0; len];
@@ -86,6 +143,18 @@ pub fn read_string<R: Read>(reader: &mut R) -> StdResult<String, NbtIoError> {
     Ok(java_decoded.into_owned())
 }
 
+// Decodes strings which are not valid Java CESU-8 as Latin-1, returning whether that was the case
+pub fn read_string_with_fallback<R: Read>(reader: &mut R) -> StdResult<(String, bool), NbtIoError> {
+    let len = read_u16(reader)? as usize;
+    let mut bytes = vec![0; len];
+    reader.read_exact(&mut bytes)?;
+
+    match cesu8::from_java_cesu8(&bytes) {
+        Ok(string) => Ok((string.into_owned(), false)),
+        Err(_) => Ok((bytes.iter().copied().map(char::from).collect(), true)),
+    }
+}
+
 #[cfg(feature = "serde")]
 pub fn read_string_into<'a, R: Read>(
     reader: &mut R,
@@ -131,6 +200,11 @@ pub fn write_i32<W: Write>(writer: &mut W, value: i32) -> Result<()> {
     writer.write_i32::<BigEndian>(value)
 }
 
+#[inline]
+pub fn write_u32<W: Write>(writer: &mut W, value: u32) -> Result<()> {
+    writer.write_u32::<BigEndian>(value)
+}
+
 #[inline]
 pub fn write_i64<W: Write>(writer: &mut W, value: i64) -> Result<()> {
     writer.write_i64::<BigEndian>(value)
@@ -146,131 +220,73 @@ pub fn write_f64<W: Write>(writer: &mut W, value: f64) -> Result<()> {
     writer.write_f64::<BigEndian>(value)
 }
 
-pub fn write_string<W: Write>(writer: &mut W, string: &str) -> Result<()> {
-    let mod_utf8 = cesu8::to_java_cesu8(string);
-    write_u16(writer, mod_utf8.len() as u16)?;
-    writer.write_all(&mod_utf8)
-}
-
-#[inline]
-pub fn cast_byte_buf_to_signed(buf: Vec<u8>) -> Vec<i8> {
-    let mut me = ManuallyDrop::new(buf);
-    // Pointer cast is valid because i8 and u8 have the same layout
-    let ptr = me.as_mut_ptr() as *mut i8;
-    let length = me.len();
-    let capacity = me.capacity();
+pub fn write_varint<W: Write>(writer: &mut W, value: i32) -> Result<()> {
+    let mut value = value as u32;
+    loop {
+        if value & !0x7F == 0 {
+            return write_u8(writer, value as u8);
+        }
 
-    // Safety
-    // * `ptr` was allocated by a Vec
-    // * i8 has the same size and alignment as u8
-    // * `length` and `capacity` came from a valid Vec
-    unsafe { Vec::from_raw_parts(ptr, length, capacity) }
+        write_u8(writer, (value as u8 & 0x7F) | 0x80)?;
+        value >>= 7;
+    }
 }
 
-#[inline]
-pub fn cast_byte_buf_to_unsigned(buf: Vec<i8>) -> Vec<u8> {
-    let mut me = ManuallyDrop::new(buf);
-    // Pointer cast is valid because i8 and u8 have the same layout
-    let ptr = me.as_mut_ptr() as *mut u8;
-    let length = me.len();
-    let capacity = me.capacity();
-
-    // Safety
-    // * `ptr` was allocated by a Vec
-    // * u8 has the same size and alignment as i8
-    // * `length` and `capacity` came from a valid Vec
-    unsafe { Vec::from_raw_parts(ptr, length, capacity) }
+pub fn write_string<W: Write>(writer: &mut W, string: &str) -> StdResult<(), NbtIoError> {
+    let mod_utf8 = cesu8::to_java_cesu8(string);
+    let len =
+        u16::try_from(mod_utf8.len()).map_err(|_| NbtIoError::StringTooLong(mod_utf8.len()))?;
+    write_u16(writer, len)?;
+    writer.write_all(&mod_utf8)?;
+    Ok(())
 }
 
-// Currently unused, but might be used later
-#[inline]
-#[allow(dead_code)]
-pub fn cast_bytes_to_signed(bytes: &[u8]) -> &[i8] {
-    let data = bytes.as_ptr() as *const i8;
-    let len = bytes.len();
-
-    // Safety
-    // * `data` is valid for len * 1 bytes
-    //     * The entire memory range of `data` is contained in a single
-    //       allocated object since it came from a valid slice
-    //     * `data` is non-null and aligned correctly for u8 (and thus i8)
-    // * `data` points to exactly `len` consecutive bytes
-    // * The constructed reference adopts the lifetime of the provided reference
-    // * `len` <= isize::MAX because `len` came from a valid slice
-    unsafe { slice::from_raw_parts(data, len) }
+pub fn read_i32_array<R: Read>(reader: &mut R, len: usize) -> Result<Vec<i32>> {
+    read_chunked(reader, len, read_i32_array_exact)
 }
 
-#[inline]
-pub fn cast_bytes_to_unsigned(bytes: &[i8]) -> &[u8] {
-    let data = bytes.as_ptr() as *const u8;
-    let len = bytes.len();
-
-    // Safety
-    // * `data` is valid for len * 1 bytes
-    //     * The entire memory range of `data` is contained in a single
-    //       allocated object since it came from a valid slice
-    //     * `data` is non-null and aligned correctly for i8 (and thus u8)
-    // * `data` points to exactly `len` consecutive bytes
-    // * The constructed reference adopts the lifetime of the provided reference
-    // * `len` <= isize::MAX because `len` came from a valid slice
-    unsafe { slice::from_raw_parts(data, len) }
+pub fn read_i64_array<R: Read>(reader: &mut R, len: usize) -> Result<Vec<i64>> {
+    read_chunked(reader, len, read_i64_array_exact)
 }
 
-#[inline]
-pub fn read_i32_array<R: Read>(reader: &mut R, len: usize) -> Result<Vec<i32>> {
-    let mut bytes = ManuallyDrop::new(vec![0i32; len]);
-
-    let ptr = bytes.as_mut_ptr() as *mut u8;
-    let length = bytes.len() * 4;
-    let capacity = bytes.capacity() * 4;
-
-    let mut bytes = unsafe { Vec::from_raw_parts(ptr, length, capacity) };
+// Reads long arrays in chunks of at most `PREALLOC_LIMIT` elements, so that memory is only
+// allocated for elements which are present in the input
+fn read_chunked<R: Read, T>(
+    reader: &mut R,
+    len: usize,
+    read_exact: fn(&mut R, usize) -> Result<Vec<T>>,
+) -> Result<Vec<T>> {
+    if len <= PREALLOC_LIMIT {
+        return read_exact(reader, len);
+    }
 
-    reader.read_exact(&mut bytes)?;
+    let mut array = Vec::with_capacity(PREALLOC_LIMIT);
+    while array.len() < len {
+        let chunk = read_exact(reader, (len - array.len()).min(PREALLOC_LIMIT))?;
+        array.extend(chunk);
+    }
 
-    // Safety: the length of the vec is a multiple of 4, and the alignment is 4
-    Ok(unsafe { convert_be_int_array_in_place::<i32, 4>(bytes, i32::from_be_bytes) })
+    Ok(array)
 }
 
+// Reads the big-endian elements directly into the array, and then converts them to native byte
+// order in place
 #[inline]
-pub fn read_i64_array<R: Read>(reader: &mut R, len: usize) -> Result<Vec<i64>> {
-    let mut bytes = ManuallyDrop::new(vec![0i64; len]);
-
-    let ptr = bytes.as_mut_ptr() as *mut u8;
-    let length = bytes.len() * 8;
-    let capacity = bytes.capacity() * 8;
-
-    let mut bytes = unsafe { Vec::from_raw_parts(ptr, length, capacity) };
-
-    reader.read_exact(&mut bytes)?;
-
-    // Safety: the length of the vec is a multiple of 8, and the alignment is 8
-    Ok(unsafe { convert_be_int_array_in_place::<i64, 8>(bytes, i64::from_be_bytes) })
+fn read_i32_array_exact<R: Read>(reader: &mut R, len: usize) -> Result<Vec<i32>> {
+    let mut array = vec![0i32; len];
+    reader.read_exact(cast::ints_to_bytes_mut(&mut array))?;
+    for value in &mut array {
+        *value = i32::from_be(*value);
+    }
+    Ok(array)
 }
 
 #[inline]
-unsafe fn convert_be_int_array_in_place<I, const SIZE: usize>(
-    mut bytes: Vec<u8>,
-    convert: fn([u8; SIZE]) -> I,
-) -> Vec<I> {
-    let mut buf: [u8; SIZE];
-
-    let mut read = bytes.as_ptr() as *const [u8; SIZE];
-    let mut write = bytes.as_mut_ptr() as *mut I;
-    let end = bytes.as_ptr().add(bytes.len()) as *const [u8; SIZE];
-
-    while read != end {
-        buf = ptr::read(read);
-        ptr::write(write, convert(buf));
-        read = read.add(1);
-        write = write.add(1);
+fn read_i64_array_exact<R: Read>(reader: &mut R, len: usize) -> Result<Vec<i64>> {
+    let mut array = vec![0i64; len];
+    reader.read_exact(cast::longs_to_bytes_mut(&mut array))?;
+    for value in &mut array {
+        *value = i64::from_be(*value);
     }
-
-    let mut me = ManuallyDrop::new(bytes);
-
-    let ptr = me.as_mut_ptr() as *mut I;
-    let length = me.len();
-    let capacity = me.capacity();
-
-    Vec::from_raw_parts(ptr, length / SIZE, capacity / SIZE)
+    Ok(array)
 }
@@ -111,11 +111,49 @@ impl NbtStructureError {
         }
     }
 
+    pub(crate) fn invalid_range(start: usize, end: usize, length: usize) -> Self {
+        NbtStructureError {
+            repr: NbtStructureErrorRepr::InvalidRange { start, end, length },
+        }
+    }
+
     pub(crate) fn type_mismatch(expected: &'static str, found: &'static str) -> Self {
         NbtStructureError {
             repr: NbtStructureErrorRepr::TypeMismatch { expected, found },
         }
     }
+
+    #[cfg(feature = "math")]
+    pub(crate) fn invalid_length(expected: usize, found: usize) -> Self {
+        NbtStructureError {
+            repr: NbtStructureErrorRepr::InvalidLength { expected, found },
+        }
+    }
+
+    pub(crate) fn lossy_conversion(from: &'static str, to: &'static str) -> Self {
+        NbtStructureError {
+            repr: NbtStructureErrorRepr::LossyConversion { from, to },
+        }
+    }
+
+    pub(crate) fn non_conforming_keys(expected: &'static str, mut keys: Vec<Box<str>>) -> Self {
+        keys.sort_unstable();
+        NbtStructureError {
+            repr: NbtStructureErrorRepr::NonConformingKeys {
+                expected,
+                keys: keys.into_boxed_slice(),
+            },
+        }
+    }
+
+    pub(crate) fn conflicting_keys(mut keys: Vec<Box<str>>) -> Self {
+        keys.sort_unstable();
+        NbtStructureError {
+            repr: NbtStructureErrorRepr::ConflictingKeys {
+                keys: keys.into_boxed_slice(),
+            },
+        }
+    }
 }
 
 impl Debug for NbtStructureError {
@@ -131,11 +169,38 @@ impl Display for NbtStructureError {
                 write!(f, "Missing tag \"{}\"", tag_name),
             NbtStructureErrorRepr::InvalidIndex { index, length } =>
                 write!(f, "Index out of range: {} >= {}", index, length),
+            NbtStructureErrorRepr::InvalidRange { start, end, length } => write!(
+                f,
+                "Range out of bounds: {}..{} for length {}",
+                start, end, length
+            ),
             NbtStructureErrorRepr::TypeMismatch { expected, found } => write!(
                 f,
                 "Tag type mismatch: expected {} but found {}",
                 expected, found
             ),
+            #[cfg(feature = "math")]
+            NbtStructureErrorRepr::InvalidLength { expected, found } => write!(
+                f,
+                "List length mismatch: expected {} elements but found {}",
+                expected, found
+            ),
+            NbtStructureErrorRepr::LossyConversion { from, to } =>
+                write!(f, "Cannot losslessly convert {} to {}", from, to),
+            NbtStructureErrorRepr::NonConformingKeys { expected, keys } => {
+                write!(f, "Tags cannot be read as {}:", expected)?;
+                for key in keys.iter() {
+                    write!(f, " {:?}", key)?;
+                }
+                Ok(())
+            }
+            NbtStructureErrorRepr::ConflictingKeys { keys } => {
+                write!(f, "Tags conflict with existing tags:")?;
+                for key in keys.iter() {
+                    write!(f, " {:?}", key)?;
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -151,10 +216,31 @@ enum NbtStructureErrorRepr {
         index: usize,
         length: usize,
     },
+    InvalidRange {
+        start: usize,
+        end: usize,
+        length: usize,
+    },
     TypeMismatch {
         expected: &'static str,
         found: &'static str,
     },
+    #[cfg(feature = "math")]
+    InvalidLength {
+        expected: usize,
+        found: usize,
+    },
+    LossyConversion {
+        from: &'static str,
+        to: &'static str,
+    },
+    NonConformingKeys {
+        expected: &'static str,
+        keys: Box<[Box<str>]>,
+    },
+    ConflictingKeys {
+        keys: Box<[Box<str>]>,
+    },
 }
 
 /// Defines a type which has a full representation as a [`NbtCompound`].
@@ -0,0 +1,395 @@
+use crate::{
+    convert::ConversionPolicy,
+    path::{push_index, push_key},
+    NbtCompound,
+    NbtTag,
+    NbtTagKind,
+};
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+    slice,
+};
+
+/// The rules applied by [`NbtCompound::sanitize`].
+///
+/// By default, strings which are too long to be written are truncated, entries whose keys are too
+/// long are removed, NaN values are left untouched, and no schema is enforced. Non-homogenous lists are always fixed by removing
+/// the elements whose kind differs from that of the first element.
+///
+/// [`NbtCompound::sanitize`]: crate::NbtCompound::sanitize
+#[derive(Debug, Clone)]
+pub struct SanitizeRules {
+    max_string_len: usize,
+    truncate_strings: bool,
+    nan: NanHandling,
+    kinds: HashMap<String, NbtTagKind>,
+    conversion: ConversionPolicy,
+}
+
+impl SanitizeRules {
+    /// Returns the default rules.
+    pub fn new() -> Self {
+        SanitizeRules {
+            max_string_len: u16::MAX as usize,
+            truncate_strings: true,
+            nan: NanHandling::default(),
+            kinds: HashMap::new(),
+            conversion: ConversionPolicy::Strict,
+        }
+    }
+
+    /// Sets the maximum length of strings and compound keys, in bytes of their modified UTF-8
+    /// encoding. This defaults to, and cannot exceed, the 65535 byte limit imposed by the binary
+    /// format.
+    pub fn max_string_len(mut self, max_string_len: usize) -> Self {
+        self.max_string_len = max_string_len.min(u16::MAX as usize);
+        self
+    }
+
+    /// Sets whether overlong string values are truncated to the maximum length, or removed
+    /// entirely. Entries with overlong keys are always removed.
+    pub fn truncate_strings(mut self, truncate_strings: bool) -> Self {
+        self.truncate_strings = truncate_strings;
+        self
+    }
+
+    /// Sets how NaN float and double values are handled.
+    pub fn nan(mut self, nan: NanHandling) -> Self {
+        self.nan = nan;
+        self
+    }
+
+    /// Requires the tag at the given path to have the given kind. Paths use the format described
+    /// in [`NbtSpans`], except that `[]` may be used in place of an index to match every element
+    /// of a list, such as `Inventory[].Count`. Tags of the wrong kind are converted according to
+    /// the [`conversion`] policy, or removed if they cannot be converted.
+    ///
+    /// [`NbtSpans`]: crate::io::NbtSpans
+    /// [`conversion`]: crate::sanitize::SanitizeRules::conversion
+    pub fn expect_kind<P: Into<String>>(mut self, path: P, kind: NbtTagKind) -> Self {
+        self.kinds.insert(path.into(), kind);
+        self
+    }
+
+    /// Sets the policy used to convert tags whose kind does not match the expected kind. This
+    /// defaults to [`ConversionPolicy::Strict`], meaning such tags are always removed.
+    ///
+    /// [`ConversionPolicy::Strict`]: crate::convert::ConversionPolicy::Strict
+    pub fn conversion(mut self, conversion: ConversionPolicy) -> Self {
+        self.conversion = conversion;
+        self
+    }
+}
+
+impl Default for SanitizeRules {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Describes how NaN float and double values are handled by a sanitizer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum NanHandling {
+    /// NaN values are left untouched.
+    #[default]
+    Allow,
+    /// Tags containing NaN values are removed.
+    Remove,
+    /// NaN values are replaced with zero.
+    ReplaceWithZero,
+}
+
+/// A report of every fix made by [`NbtCompound::sanitize`].
+///
+/// [`NbtCompound::sanitize`]: crate::NbtCompound::sanitize
+#[derive(Debug, Clone, Default)]
+pub struct SanitizeReport {
+    fixes: Vec<SanitizeFix>,
+}
+
+impl SanitizeReport {
+    /// Returns the number of fixes made.
+    pub fn len(&self) -> usize {
+        self.fixes.len()
+    }
+
+    /// Returns true if no fixes were necessary.
+    pub fn is_empty(&self) -> bool {
+        self.fixes.is_empty()
+    }
+
+    /// Returns an iterator over the fixes made, in the order they were made.
+    pub fn iter(&self) -> slice::Iter<'_, SanitizeFix> {
+        self.fixes.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a SanitizeReport {
+    type IntoIter = slice::Iter<'a, SanitizeFix>;
+    type Item = &'a SanitizeFix;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.fixes.iter()
+    }
+}
+
+/// A single fix made by a sanitizer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SanitizeFix {
+    path: String,
+    action: SanitizeAction,
+}
+
+impl SanitizeFix {
+    /// Returns the path to the fixed tag. List indices refer to positions in the list before it
+    /// was sanitized.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the action taken to fix the tag.
+    pub fn action(&self) -> &SanitizeAction {
+        &self.action
+    }
+}
+
+impl Display for SanitizeFix {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.action)
+    }
+}
+
+/// An action taken by a sanitizer to fix a tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SanitizeAction {
+    /// An entry was removed because its key was too long.
+    RemovedLongKey,
+    /// A string was removed because it was too long.
+    RemovedLongString,
+    /// A string was truncated because it was too long.
+    TruncatedString {
+        /// The original length of the string's modified UTF-8 encoding.
+        original_len: usize,
+    },
+    /// A NaN value was removed.
+    RemovedNan,
+    /// A NaN value was replaced with zero.
+    ReplacedNan,
+    /// A list element was removed because its kind differed from the list's element kind.
+    RemovedListElement {
+        /// The kind of the list's elements.
+        expected: NbtTagKind,
+        /// The kind of the removed element.
+        found: NbtTagKind,
+    },
+    /// A tag was converted to the kind expected by the schema.
+    ConvertedKind {
+        /// The original kind of the tag.
+        from: NbtTagKind,
+        /// The kind expected by the schema.
+        to: NbtTagKind,
+    },
+    /// A tag was removed because its kind differed from the kind expected by the schema, and it
+    /// could not be converted.
+    RemovedWrongKind {
+        /// The kind expected by the schema.
+        expected: NbtTagKind,
+        /// The kind of the removed tag.
+        found: NbtTagKind,
+    },
+}
+
+impl Display for SanitizeAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            SanitizeAction::RemovedLongKey => write!(f, "removed entry with overlong key"),
+            SanitizeAction::RemovedLongString => write!(f, "removed overlong string"),
+            SanitizeAction::TruncatedString { original_len } =>
+                write!(f, "truncated string of length {}", original_len),
+            SanitizeAction::RemovedNan => write!(f, "removed NaN value"),
+            SanitizeAction::ReplacedNan => write!(f, "replaced NaN value with zero"),
+            SanitizeAction::RemovedListElement { expected, found } => write!(
+                f,
+                "removed {} element from list of {} elements",
+                found, expected
+            ),
+            SanitizeAction::ConvertedKind { from, to } => write!(f, "converted {} to {}", from, to),
+            SanitizeAction::RemovedWrongKind { expected, found } =>
+                write!(f, "removed {} where {} was expected", found, expected),
+        }
+    }
+}
+
+pub(crate) fn sanitize_compound(
+    compound: &mut NbtCompound,
+    rules: &SanitizeRules,
+) -> SanitizeReport {
+    let mut sanitizer = Sanitizer {
+        rules,
+        path: String::new(),
+        schema_path: String::new(),
+        report: SanitizeReport::default(),
+    };
+    sanitizer.compound(compound);
+    sanitizer.report
+}
+
+// Tracks both the concrete path to the current tag and the path used to look up its kind in the
+// schema, where list indices are replaced with `[]`
+struct Sanitizer<'a> {
+    rules: &'a SanitizeRules,
+    path: String,
+    schema_path: String,
+    report: SanitizeReport,
+}
+
+impl Sanitizer<'_> {
+    fn fix(&mut self, action: SanitizeAction) {
+        self.report.fixes.push(SanitizeFix {
+            path: self.path.clone(),
+            action,
+        });
+    }
+
+    fn compound(&mut self, compound: &mut NbtCompound) {
+        compound.0.retain(|key, tag| {
+            let (len, schema_len) = (self.path.len(), self.schema_path.len());
+            push_key(&mut self.path, key);
+            push_key(&mut self.schema_path, key);
+
+            let keep = if modified_utf8_len(key) > self.rules.max_string_len {
+                self.fix(SanitizeAction::RemovedLongKey);
+                false
+            } else {
+                self.tag(tag)
+            };
+
+            self.path.truncate(len);
+            self.schema_path.truncate(schema_len);
+            keep
+        });
+    }
+
+    // Returns false if the tag should be removed
+    fn tag(&mut self, tag: &mut NbtTag) -> bool {
+        let expected = self
+            .rules
+            .kinds
+            .get(&self.path)
+            .or_else(|| self.rules.kinds.get(&self.schema_path))
+            .copied();
+        if let Some(expected) = expected.filter(|&kind| kind != tag.kind()) {
+            let found = tag.kind();
+            match self.rules.conversion.convert(tag, expected) {
+                Ok(converted) => {
+                    let converted = converted.into_owned();
+                    *tag = converted;
+                    self.fix(SanitizeAction::ConvertedKind {
+                        from: found,
+                        to: expected,
+                    });
+                }
+                Err(_) => {
+                    self.fix(SanitizeAction::RemovedWrongKind { expected, found });
+                    return false;
+                }
+            }
+        }
+
+        match tag {
+            NbtTag::Float(value) if value.is_nan() => self.nan(|| *value = 0.0),
+            NbtTag::Double(value) if value.is_nan() => self.nan(|| *value = 0.0),
+            NbtTag::String(string) => {
+                let original_len = modified_utf8_len(string);
+                if original_len <= self.rules.max_string_len {
+                    return true;
+                }
+
+                if !self.rules.truncate_strings {
+                    self.fix(SanitizeAction::RemovedLongString);
+                    return false;
+                }
+
+                truncate_modified_utf8(string, self.rules.max_string_len);
+                self.fix(SanitizeAction::TruncatedString { original_len });
+                true
+            }
+            NbtTag::List(list) => {
+                let mut element_kind = None;
+                let mut index = 0;
+                list.0.retain_mut(|element| {
+                    let (len, schema_len) = (self.path.len(), self.schema_path.len());
+                    push_index(&mut self.path, index);
+                    self.schema_path.push_str("[]");
+                    index += 1;
+
+                    let mut keep = self.tag(element);
+                    if keep {
+                        let found = element.kind();
+                        match element_kind {
+                            None => element_kind = Some(found),
+                            Some(expected) if expected != found => {
+                                self.fix(SanitizeAction::RemovedListElement { expected, found });
+                                keep = false;
+                            }
+                            _ => {}
+                        }
+                    }
+
+                    self.path.truncate(len);
+                    self.schema_path.truncate(schema_len);
+                    keep
+                });
+                true
+            }
+            NbtTag::Compound(compound) => {
+                self.compound(compound);
+                true
+            }
+            _ => true,
+        }
+    }
+
+    fn nan(&mut self, replace: impl FnOnce()) -> bool {
+        match self.rules.nan {
+            NanHandling::Allow => true,
+            NanHandling::Remove => {
+                self.fix(SanitizeAction::RemovedNan);
+                false
+            }
+            NanHandling::ReplaceWithZero => {
+                replace();
+                self.fix(SanitizeAction::ReplacedNan);
+                true
+            }
+        }
+    }
+}
+
+// The length of the given string in Java's modified UTF-8, which encodes nul as two bytes and
+// characters outside the BMP as two three-byte surrogates
+fn modified_utf8_len(string: &str) -> usize {
+    string.chars().map(modified_utf8_char_len).sum()
+}
+
+fn modified_utf8_char_len(ch: char) -> usize {
+    match ch as u32 {
+        0 => 2,
+        0x1 ..= 0x7F => 1,
+        0x80 ..= 0x7FF => 2,
+        0x800 ..= 0xFFFF => 3,
+        _ => 6,
+    }
+}
+
+fn truncate_modified_utf8(string: &mut String, max_len: usize) {
+    let mut len = 0;
+    for (index, ch) in string.char_indices() {
+        len += modified_utf8_char_len(ch);
+        if len > max_len {
+            string.truncate(index);
+            return;
+        }
+    }
+}
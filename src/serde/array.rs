@@ -1,6 +1,6 @@
-use crate::raw;
+use crate::cast;
 use serde::{
-    de::{EnumAccess, Error, MapAccess, SeqAccess, Visitor},
+    de::{EnumAccess, Error, IgnoredAny, MapAccess, SeqAccess, Visitor},
     Deserialize,
     Deserializer,
     Serialize,
@@ -105,6 +105,167 @@ where T: ArrayNiche
     }
 }
 
+/// A wrapper around an array of a fixed length which is serialized as the NBT array type for its
+/// elements, like [`Array`], and fails to deserialize from an array of any other length.
+///
+/// The element type must be `i8` or `u8` for a [`ByteArray`], `i32` for an [`IntArray`], or
+/// `i64` for a [`LongArray`]. A length mismatch results in an error naming the array type along
+/// with the expected and found lengths, which is reported before any elements are read when the
+/// length is known up front, as it is for binary NBT. This makes it suitable for fields with a
+/// fixed layout, such as UUIDs stored as four ints.
+///
+/// ```
+/// # use quartz_nbt::{compound, io::Flavor};
+/// use quartz_nbt::serde::{deserialize, serialize, FixedArray};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Entity {
+///     #[serde(rename = "UUID")]
+///     uuid: FixedArray<i32, 4>,
+/// }
+///
+/// let entity = Entity { uuid: FixedArray::from([1, 2, 3, 4]) };
+/// let bytes = serialize(&entity, None, Flavor::Uncompressed)?;
+/// let (entity, _) = deserialize::<Entity>(&bytes, Flavor::Uncompressed)?;
+/// assert_eq!(FixedArray::into_inner(entity.uuid), [1, 2, 3, 4]);
+///
+/// let bytes = serialize(&compound! { "UUID": [I; 1, 2, 3] }, None, Flavor::Uncompressed)?;
+/// let error = deserialize::<Entity>(&bytes, Flavor::Uncompressed).err().unwrap();
+/// assert_eq!(error.to_string(), "invalid length 3, expected an int array of length 4");
+/// # Ok::<_, quartz_nbt::io::NbtIoError>(())
+/// ```
+///
+/// [`Array`]: crate::serde::Array
+/// [`ByteArray`]: crate::NbtTag::ByteArray
+/// [`IntArray`]: crate::NbtTag::IntArray
+/// [`LongArray`]: crate::NbtTag::LongArray
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct FixedArray<T, const N: usize>([T; N]);
+
+impl<T, const N: usize> FixedArray<T, N> {
+    /// Returns the inner array wrapped by this type.
+    #[inline]
+    pub fn into_inner(array: Self) -> [T; N] {
+        array.0
+    }
+}
+
+impl<T, const N: usize> Serialize for FixedArray<T, N>
+where [T; N]: ArrayNiche
+{
+    #[inline]
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: serde::Serializer {
+        serializer.serialize_newtype_struct(<[T; N]>::NICHE, self.0.as_ser_repr())
+    }
+}
+
+impl<'de, T, const N: usize> Deserialize<'de> for FixedArray<T, N>
+where
+    T: Deserialize<'de>,
+    [T; N]: ArrayNiche,
+{
+    #[inline]
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: serde::Deserializer<'de> {
+        deserializer
+            .deserialize_newtype_struct(<[T; N]>::NICHE, FixedArrayVisitor::<T, N>(PhantomData))
+    }
+}
+
+struct FixedArrayVisitor<T, const N: usize>(PhantomData<T>);
+
+impl<'de, T, const N: usize> Visitor<'de> for FixedArrayVisitor<T, N>
+where
+    T: Deserialize<'de>,
+    [T; N]: ArrayNiche,
+{
+    type Value = FixedArray<T, N>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match <[T; N]>::NICHE {
+            BYTE_ARRAY_NICHE => "a byte",
+            INT_ARRAY_NICHE => "an int",
+            _ => "a long",
+        };
+        write!(f, "{} array of length {}", kind, N)
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where D: Deserializer<'de> {
+        deserializer.deserialize_seq(self)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where A: SeqAccess<'de> {
+        if let Some(len) = seq.size_hint() {
+            if len != N {
+                return Err(A::Error::invalid_length(len, &self));
+            }
+        }
+
+        let mut elements = Vec::with_capacity(N);
+        while elements.len() < N {
+            match seq.next_element()? {
+                Some(element) => elements.push(element),
+                None => return Err(A::Error::invalid_length(elements.len(), &self)),
+            }
+        }
+
+        let mut len = N;
+        while seq.next_element::<IgnoredAny>()?.is_some() {
+            len += 1;
+        }
+        if len != N {
+            return Err(A::Error::invalid_length(len, &self));
+        }
+
+        match <[T; N]>::try_from(elements) {
+            Ok(array) => Ok(FixedArray(array)),
+            Err(elements) => Err(A::Error::invalid_length(elements.len(), &self)),
+        }
+    }
+}
+
+impl<T, const N: usize> AsRef<[T; N]> for FixedArray<T, N> {
+    #[inline]
+    fn as_ref(&self) -> &[T; N] {
+        &self.0
+    }
+}
+
+impl<T, const N: usize> AsMut<[T; N]> for FixedArray<T, N> {
+    #[inline]
+    fn as_mut(&mut self) -> &mut [T; N] {
+        &mut self.0
+    }
+}
+
+impl<T, const N: usize> Borrow<[T; N]> for FixedArray<T, N> {
+    #[inline]
+    fn borrow(&self) -> &[T; N] {
+        &self.0
+    }
+}
+
+impl<T, const N: usize> BorrowMut<[T; N]> for FixedArray<T, N> {
+    #[inline]
+    fn borrow_mut(&mut self) -> &mut [T; N] {
+        &mut self.0
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for FixedArray<T, N>
+where [T; N]: ArrayNiche
+{
+    #[inline]
+    fn from(array: [T; N]) -> Self {
+        FixedArray(array)
+    }
+}
+
 pub trait ArrayNiche {
     type SerRepr: ?Sized + Serialize;
     const NICHE: &'static str;
@@ -145,7 +306,7 @@ impl ArrayNiche for Vec<i8> {
 
     #[inline]
     fn as_ser_repr(&self) -> &Self::SerRepr {
-        raw::cast_bytes_to_unsigned(self.as_slice())
+        cast::bytes_to_unsigned(self.as_slice())
     }
 }
 
@@ -189,7 +350,7 @@ impl ArrayNiche for [i8] {
 
     #[inline]
     fn as_ser_repr(&self) -> &Self::SerRepr {
-        raw::cast_bytes_to_unsigned(self)
+        cast::bytes_to_unsigned(self)
     }
 }
 
@@ -234,7 +395,7 @@ impl<const N: usize> ArrayNiche for [i8; N] {
     #[inline]
     fn as_ser_repr(&self) -> &Self::SerRepr {
         let slice: &[i8] = self;
-        raw::cast_bytes_to_unsigned(slice)
+        cast::bytes_to_unsigned(slice)
     }
 }
 
@@ -1,9 +1,16 @@
-use super::TYPE_HINT_NICHE;
-use crate::{io::NbtIoError, raw};
+use super::{BYTE_ARRAY_NICHE, INT_ARRAY_NICHE, LONG_ARRAY_NICHE, RAW_NBT_NICHE, TYPE_HINT_NICHE};
+use crate::{
+    convert::ConversionPolicy,
+    io::{Flavor, KeyTransform, NbtIoError, RenamedKey},
+    raw,
+    NbtTag,
+    NbtTagKind,
+};
 use serde::{
     de::{
         self,
         value::CowStrDeserializer,
+        DeserializeOwned,
         DeserializeSeed,
         EnumAccess,
         IntoDeserializer,
@@ -25,6 +32,7 @@ use std::{
 /// The deserializer type for reading binary NBT data.
 pub struct Deserializer<'a, R, B> {
     reader: &'a mut R,
+    config: DeserializerConfig,
     _buffered: PhantomData<B>,
 }
 
@@ -33,18 +41,7 @@ impl<'a, R: Read> Deserializer<'a, R, Unbuffered> {
     /// does not start with a valid compound tag, an error is returned. Otherwise, the root name
     /// is returned along with the deserializer.
     pub fn new(reader: &'a mut R) -> Result<(Self, String), NbtIoError> {
-        if raw::read_u8(reader)? != 0xA {
-            return Err(NbtIoError::MissingRootTag);
-        }
-
-        let root_name = raw::read_string(reader)?;
-        Ok((
-            Deserializer {
-                reader,
-                _buffered: PhantomData,
-            },
-            root_name,
-        ))
+        DeserializerBuilder::new().build(reader)
     }
 }
 
@@ -63,6 +60,152 @@ where
     pub fn from_cursor(
         reader: &'a mut Cursor<&'buffer [u8]>,
     ) -> Result<(Self, Cow<'buffer, str>), NbtIoError> {
+        DeserializerBuilder::new().build_from_cursor(reader)
+    }
+}
+
+/// A builder for deserializers which read data with options other than the defaults.
+///
+/// By default, a sequence such as a `Vec<i32>` can be read from either a list tag or the array
+/// tag of the matching element type, and an [`Array`] can likewise be read from a list. Requiring
+/// strict sequences rejects these substitutions.
+///
+/// ```
+/// # extern crate serde;
+/// # use serde::Deserialize;
+/// use quartz_nbt::{
+///     compound,
+///     io::{self, Flavor, NbtIoError},
+///     serde::DeserializerBuilder,
+///     NbtTag,
+/// };
+///
+/// #[derive(Deserialize)]
+/// struct Entity {
+///     #[serde(rename = "UUID")]
+///     uuid: Vec<i32>,
+/// }
+///
+/// let nbt = compound! { "UUID": NbtTag::IntArray(vec![1, 2, 3, 4]) };
+/// let bytes = io::write_nbt_bytes(None, &nbt, Flavor::Uncompressed)?;
+///
+/// let (entity, _) = DeserializerBuilder::new().deserialize::<Entity>(&bytes, Flavor::Uncompressed)?;
+/// assert_eq!(entity.uuid, [1, 2, 3, 4]);
+///
+/// let strict = DeserializerBuilder::new().strict_sequences(true);
+/// assert!(matches!(
+///     strict.deserialize::<Entity>(&bytes, Flavor::Uncompressed),
+///     Err(NbtIoError::TagTypeMismatch { expected: 0x9, found: 0xB })
+/// ));
+/// # Ok::<(), NbtIoError>(())
+/// ```
+///
+/// [`Array`]: crate::serde::Array
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DeserializerBuilder {
+    config: DeserializerConfig,
+}
+
+impl DeserializerBuilder {
+    /// Returns a builder with the default options, which are the ones used by
+    /// [`deserialize`](crate::serde::deserialize).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether sequences must be read from the exact tag type they are serialized as. When
+    /// enabled, a `Vec` or other sequence can only be read from a list tag, and an [`Array`] only
+    /// from the array tag of its element type. This defaults to `false`.
+    ///
+    /// Sequences read through [`NbtTag`] or other self-describing types are unaffected.
+    ///
+    /// [`Array`]: crate::serde::Array
+    /// [`NbtTag`]: crate::NbtTag
+    pub fn strict_sequences(mut self, strict: bool) -> Self {
+        self.config.strict_sequences = strict;
+        self
+    }
+
+    /// Sets what the deserializer reports from [`is_human_readable`], which third-party types use
+    /// to pick their representation. This defaults to `false`, and should match the setting used
+    /// when the data was serialized. See [`SerializerBuilder::human_readable`] for details.
+    ///
+    /// [`is_human_readable`]: serde::Deserializer::is_human_readable
+    /// [`SerializerBuilder::human_readable`]: crate::serde::SerializerBuilder::human_readable
+    pub fn human_readable(mut self, human_readable: bool) -> Self {
+        self.config.human_readable = human_readable;
+        self
+    }
+
+    /// Sets the policy used to convert numeric tags to the kind of number requested by the type
+    /// being deserialized, such as reading an `i16` field from an int tag.
+    ///
+    /// By default no policy is applied and numbers are passed to the type as they were read,
+    /// which lets serde's own implementations accept any value that fits in the requested type.
+    /// Setting a policy replaces that with the policy's rules, so [`ConversionPolicy::Strict`]
+    /// requires every number to have exactly the requested kind, and [`ConversionPolicy::Saturate`]
+    /// clamps values which do not fit.
+    ///
+    /// ```
+    /// use quartz_nbt::{
+    ///     compound,
+    ///     convert::ConversionPolicy,
+    ///     io::{self, Flavor, NbtIoError},
+    ///     serde::DeserializerBuilder,
+    /// };
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize, Debug, PartialEq)]
+    /// struct Item {
+    ///     count: i8,
+    /// }
+    ///
+    /// let bytes = io::write_nbt_bytes(None, &compound! { "count": 300i32 }, Flavor::Uncompressed)?;
+    ///
+    /// assert!(DeserializerBuilder::new()
+    ///     .deserialize::<Item>(&bytes, Flavor::Uncompressed)
+    ///     .is_err());
+    /// let (item, _) = DeserializerBuilder::new()
+    ///     .conversion(ConversionPolicy::Saturate)
+    ///     .deserialize::<Item>(&bytes, Flavor::Uncompressed)?;
+    /// assert_eq!(item, Item { count: 127 });
+    /// # Ok::<(), NbtIoError>(())
+    /// ```
+    ///
+    /// [`ConversionPolicy::Strict`]: crate::convert::ConversionPolicy::Strict
+    /// [`ConversionPolicy::Saturate`]: crate::convert::ConversionPolicy::Saturate
+    pub fn conversion(mut self, policy: ConversionPolicy) -> Self {
+        self.config.conversion = Some(policy);
+        self
+    }
+
+    /// Attempts to construct a new deserializer with the given reader. See
+    /// [`Deserializer::new`] for details.
+    pub fn build<R: Read>(
+        self,
+        reader: &mut R,
+    ) -> Result<(Deserializer<'_, R, Unbuffered>, String), NbtIoError> {
+        if raw::read_u8(reader)? != 0xA {
+            return Err(NbtIoError::MissingRootTag);
+        }
+
+        let root_name = raw::read_string(reader)?;
+        Ok((
+            Deserializer {
+                reader,
+                config: self.config,
+                _buffered: PhantomData,
+            },
+            root_name,
+        ))
+    }
+
+    /// Attempts to construct a new deserializer which can borrow from the given cursor's buffer.
+    /// See [`Deserializer::from_cursor`] for details.
+    pub fn build_from_cursor<'a, 'buffer>(
+        self,
+        reader: &'a mut Cursor<&'buffer [u8]>,
+    ) -> Result<(CursorDeserializer<'a, 'buffer>, Cow<'buffer, str>), NbtIoError> {
         if raw::read_u8(reader)? != 0xA {
             return Err(NbtIoError::MissingRootTag);
         }
@@ -78,11 +221,163 @@ where
         Ok((
             Deserializer {
                 reader,
+                config: self.config,
                 _buffered: PhantomData,
             },
             root_name,
         ))
     }
+
+    /// Deserializes the given type from binary NBT data. See
+    /// [`deserialize`](crate::serde::deserialize) for details.
+    pub fn deserialize<T: DeserializeOwned>(
+        self,
+        bytes: &[u8],
+        flavor: Flavor,
+    ) -> Result<(T, String), NbtIoError> {
+        self.deserialize_from(&mut Cursor::new(bytes), flavor)
+    }
+
+    /// Deserializes the given type from binary NBT data read from the given reader. See
+    /// [`deserialize_from`](crate::serde::deserialize_from) for details.
+    pub fn deserialize_from<R: Read, T: DeserializeOwned>(
+        self,
+        reader: &mut R,
+        flavor: Flavor,
+    ) -> Result<(T, String), NbtIoError> {
+        crate::io::with_decoder(reader, flavor, |decoder| self.deserialize_from_raw(decoder))
+    }
+
+    /// Deserializes the given type from binary NBT data read directly out of the given buffer.
+    /// See [`deserialize_from_buf`](crate::serde::deserialize_from_buf) for details.
+    ///
+    /// This method is only available when the `bytes` feature is enabled.
+    #[cfg(feature = "bytes")]
+    pub fn deserialize_from_buf<B: bytes::Buf, T: DeserializeOwned>(
+        self,
+        buf: &mut B,
+        flavor: Flavor,
+    ) -> Result<(T, String), NbtIoError> {
+        crate::io::with_buf_decoder(buf, flavor, |decoder| self.deserialize_from_raw(decoder))
+    }
+
+    /// Deserializes the given type from binary NBT data read from the given reader, passing the key
+    /// of every compound entry through the given transform first, and returns a record of the keys
+    /// which were changed. Keys are transformed and recorded exactly as by
+    /// [`io::read_nbt_with_key_transform`].
+    ///
+    /// The data is decompressed and rewritten into a buffer before it is deserialized. If the
+    /// transform maps several keys of a compound to the same key, every entry is still passed to
+    /// the type being deserialized, which for derived structs results in a duplicate field error.
+    ///
+    /// ```
+    /// # extern crate serde;
+    /// # use serde::Deserialize;
+    /// use quartz_nbt::{
+    ///     compound,
+    ///     io::{self, Flavor, NbtIoError},
+    ///     serde::DeserializerBuilder,
+    /// };
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Item {
+    ///     id: String,
+    ///     count: i8,
+    /// }
+    ///
+    /// let nbt = compound! { "Id": "minecraft:stone", "Count": 64i8 };
+    /// let bytes = io::write_nbt_bytes(None, &nbt, Flavor::GzCompressed)?;
+    ///
+    /// let (item, _, renamed) = DeserializerBuilder::new().deserialize_from_with_key_transform::<_, Item, _>(
+    ///     &mut bytes.as_slice(),
+    ///     Flavor::GzCompressed,
+    ///     &|key: &str| Some(key.to_lowercase()),
+    /// )?;
+    /// assert_eq!((item.id.as_str(), item.count), ("minecraft:stone", 64));
+    /// assert_eq!(renamed.len(), 2);
+    /// # Ok::<(), NbtIoError>(())
+    /// ```
+    ///
+    /// [`io::read_nbt_with_key_transform`]: crate::io::read_nbt_with_key_transform
+    pub fn deserialize_from_with_key_transform<R, T, K>(
+        self,
+        reader: &mut R,
+        flavor: Flavor,
+        transform: &K,
+    ) -> Result<(T, String, Vec<RenamedKey>), NbtIoError>
+    where
+        R: Read,
+        T: DeserializeOwned,
+        K: KeyTransform + ?Sized,
+    {
+        let mut bytes = Vec::new();
+        let renamed = crate::io::with_decoder(reader, flavor, |decoder| {
+            transform_keys(decoder, &mut bytes, transform)
+        })?;
+
+        let (value, root_name) = self.deserialize_from_raw(&mut bytes.as_slice())?;
+        Ok((value, root_name, renamed))
+    }
+
+    fn deserialize_from_raw<R: Read, T: DeserializeOwned>(
+        self,
+        reader: &mut R,
+    ) -> Result<(T, String), NbtIoError> {
+        let (de, root_name) = self.build(reader)?;
+        Ok((T::deserialize(de)?, root_name))
+    }
+}
+
+// Copies the uncompressed document in the given reader to `dest`, passing every key through the
+// given transform
+fn transform_keys<R, K>(
+    reader: &mut R,
+    dest: &mut Vec<u8>,
+    transform: &K,
+) -> Result<Vec<RenamedKey>, NbtIoError>
+where
+    R: Read,
+    K: KeyTransform + ?Sized,
+{
+    let root_name = crate::io::read_root_header(reader)?;
+    dest.push(0xA);
+    raw::write_string(dest, &root_name)?;
+
+    let mut renamed = Vec::new();
+    crate::io::copy_tag_body_with_key_transform(
+        reader,
+        0xA,
+        dest,
+        transform,
+        &mut String::new(),
+        &mut renamed,
+        0,
+    )?;
+    Ok(renamed)
+}
+
+type CursorDeserializer<'a, 'buffer> =
+    Deserializer<'a, Cursor<&'buffer [u8]>, BufferedCursor<'buffer>>;
+
+// The options shared by every deserializer in a tree, set through `DeserializerBuilder`, along
+// with the number of lists and compounds enclosing the current tag
+#[derive(Debug, Clone, Copy, Default)]
+struct DeserializerConfig {
+    strict_sequences: bool,
+    human_readable: bool,
+    conversion: Option<ConversionPolicy>,
+    depth: usize,
+}
+
+impl DeserializerConfig {
+    // Returns the config for the children of a list or compound
+    #[inline]
+    fn nested(self) -> Result<Self, NbtIoError> {
+        Ok(DeserializerConfig {
+            depth: crate::io::child_depth(self.depth)?,
+            ..self
+        })
+    }
 }
 
 impl<'de, 'a, 'buffer, R, B> de::Deserializer<'de> for Deserializer<'a, R, B>
@@ -90,14 +385,13 @@ where
     'de: 'a,
     'buffer: 'de,
     R: Read,
-    B: BufferSpecialization<'buffer>,
+    B: BufferSpecialization<'buffer, R>,
 {
     type Error = NbtIoError;
 
     forward_to_deserialize_any! {
         bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
-        bytes byte_buf option unit unit_struct newtype_struct seq tuple
-        tuple_struct identifier ignored_any
+        bytes byte_buf newtype_struct seq tuple tuple_struct identifier ignored_any
     }
 
     #[inline]
@@ -106,10 +400,37 @@ where
         self.deserialize_map(visitor)
     }
 
+    // The root tag is always present, so options are always some, even if the serializer wrote
+    // None as an empty compound
+    #[inline]
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where V: Visitor<'de> {
+        visitor.visit_some(self)
+    }
+
+    #[inline]
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where V: Visitor<'de> {
+        self.deserialize_map(de::IgnoredAny)?;
+        visitor.visit_unit()
+    }
+
+    #[inline]
+    fn deserialize_unit_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_unit(visitor)
+    }
+
     #[inline]
     fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where V: Visitor<'de> {
-        DeserializeTag::<_, B, 0xA>::new(self.reader).deserialize_map(visitor)
+        DeserializeTag::<_, B, 0xA>::new(self.reader, self.config).deserialize_map(visitor)
     }
 
     #[inline]
@@ -135,18 +456,20 @@ where
     where
         V: Visitor<'de>,
     {
-        DeserializeTag::<_, B, 0xA>::new(self.reader).deserialize_enum(name, variants, visitor)
+        DeserializeTag::<_, B, 0xA>::new(self.reader, self.config)
+            .deserialize_enum(name, variants, visitor)
     }
 
     #[inline]
     fn is_human_readable(&self) -> bool {
-        false
+        self.config.human_readable
     }
 }
 
 #[inline]
 fn drive_visitor_seq_const<'de, 'a, 'buffer, R, V, B, const TAG_ID: u8>(
     reader: &'a mut R,
+    config: DeserializerConfig,
     visitor: V,
 ) -> Result<V::Value, NbtIoError>
 where
@@ -154,28 +477,28 @@ where
     'buffer: 'de,
     R: Read,
     V: Visitor<'de>,
-    B: BufferSpecialization<'buffer>,
+    B: BufferSpecialization<'buffer, R>,
 {
     match TAG_ID {
         0x7 => {
-            let len = raw::read_i32(reader)? as usize;
+            let len = raw::read_len(reader)?;
             visitor.visit_seq(DeserializeSeq::<_, _, 0x1, TAG_ID>::new(
-                DeserializeTag::<_, B, 0x1>::new(reader),
+                DeserializeTag::<_, B, 0x1>::new(reader, config),
                 len,
             ))
         }
-        0x9 => drive_visitor_seq_tag::<_, _, B>(reader, visitor),
+        0x9 => drive_visitor_seq_tag::<_, _, B>(reader, config, visitor),
         0xB => {
-            let len = raw::read_i32(reader)? as usize;
+            let len = raw::read_len(reader)?;
             visitor.visit_seq(DeserializeSeq::<_, _, 0x3, TAG_ID>::new(
-                DeserializeTag::<_, B, 0x3>::new(reader),
+                DeserializeTag::<_, B, 0x3>::new(reader, config),
                 len,
             ))
         }
         0xC => {
-            let len = raw::read_i32(reader)? as usize;
+            let len = raw::read_len(reader)?;
             visitor.visit_seq(DeserializeSeq::<_, _, 0x4, TAG_ID>::new(
-                DeserializeTag::<_, B, 0x4>::new(reader),
+                DeserializeTag::<_, B, 0x4>::new(reader, config),
                 len,
             ))
         }
@@ -185,6 +508,7 @@ where
 
 fn drive_visitor_seq_tag<'de, 'a, 'buffer, R, V, B>(
     reader: &'a mut R,
+    config: DeserializerConfig,
     visitor: V,
 ) -> Result<V::Value, NbtIoError>
 where
@@ -192,22 +516,23 @@ where
     'buffer: 'de,
     R: Read,
     V: Visitor<'de>,
-    B: BufferSpecialization<'buffer>,
+    B: BufferSpecialization<'buffer, R>,
 {
+    let config = config.nested()?;
     let id = raw::read_u8(reader)?;
-    let len = raw::read_i32(reader)? as usize;
+    let len = raw::read_len(reader)?;
 
     macro_rules! drive_visitor {
         ($($id:literal)*) => {
             match id {
                 0x0 => {
                     if len == 0 {
-                        visitor.visit_seq(DeserializeSeq::<_, _, 0x0, 0x9>::new(DeserializeTag::<_, B, 0x0>::new(reader), len))
+                        visitor.visit_seq(DeserializeSeq::<_, _, 0x0, 0x9>::new(DeserializeTag::<_, B, 0x0>::new(reader, config), len))
                     } else {
                         Err(NbtIoError::InvalidTagId(0))
                     }
                 }
-                $( $id => visitor.visit_seq(DeserializeSeq::<_, _, $id, 0x9>::new(DeserializeTag::<_, B, $id>::new(reader), len)), )*
+                $( $id => visitor.visit_seq(DeserializeSeq::<_, _, $id, 0x9>::new(DeserializeTag::<_, B, $id>::new(reader, config), len)), )*
                 _ => Err(NbtIoError::InvalidTagId(id))
             }
         };
@@ -219,15 +544,17 @@ where
 struct DeserializeEnum<'a, R, B, const TAG_ID: u8> {
     reader: &'a mut R,
     variant: Cow<'a, str>,
+    config: DeserializerConfig,
     _buffered: PhantomData<B>,
 }
 
 impl<'a, R, B, const TAG_ID: u8> DeserializeEnum<'a, R, B, TAG_ID> {
     #[inline]
-    fn new(reader: &'a mut R, variant: Cow<'a, str>) -> Self {
+    fn new(reader: &'a mut R, variant: Cow<'a, str>, config: DeserializerConfig) -> Self {
         DeserializeEnum {
             reader,
             variant,
+            config,
             _buffered: PhantomData,
         }
     }
@@ -238,7 +565,7 @@ where
     'de: 'a,
     'buffer: 'de,
     R: Read,
-    B: BufferSpecialization<'buffer>,
+    B: BufferSpecialization<'buffer, R>,
 {
     type Error = NbtIoError;
     type Variant = DeserializeVariant<'a, R, B, TAG_ID>;
@@ -247,24 +574,29 @@ where
     fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
     where V: DeserializeSeed<'de> {
         let de: CowStrDeserializer<'a, Self::Error> = self.variant.into_deserializer();
-        Ok((seed.deserialize(de)?, DeserializeVariant::new(self.reader)))
+        Ok((
+            seed.deserialize(de)?,
+            DeserializeVariant::new(self.reader, self.config),
+        ))
     }
 }
 
 struct DeserializeVariant<'a, R, B, const TAG_ID: u8> {
     reader: &'a mut R,
+    config: DeserializerConfig,
     _buffered: PhantomData<B>,
 }
 
 impl<'a, 'buffer, R, B, const TAG_ID: u8> DeserializeVariant<'a, R, B, TAG_ID>
 where
     R: Read,
-    B: BufferSpecialization<'buffer>,
+    B: BufferSpecialization<'buffer, R>,
 {
     #[inline]
-    fn new(reader: &'a mut R) -> Self {
+    fn new(reader: &'a mut R, config: DeserializerConfig) -> Self {
         DeserializeVariant {
             reader,
+            config,
             _buffered: PhantomData,
         }
     }
@@ -276,25 +608,30 @@ where
     'de: 'a,
     'buffer: 'de,
     R: Read,
-    B: BufferSpecialization<'buffer>,
+    B: BufferSpecialization<'buffer, R>,
 {
     type Error = NbtIoError;
 
+    // Unit variants are written as plain tags rather than compounds, so a compound selecting a
+    // unit variant is invalid
     #[cold]
     fn unit_variant(self) -> Result<(), Self::Error> {
-        unimplemented!("Unit variant should have been handled by deserialize_enum")
+        Err(NbtIoError::ExpectedEnum)
     }
 
     #[inline]
     fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
     where T: DeserializeSeed<'de> {
-        seed.deserialize(&mut DeserializeTag::<_, B, TAG_ID>::new(self.reader))
+        seed.deserialize(&mut DeserializeTag::<_, B, TAG_ID>::new(
+            self.reader,
+            self.config,
+        ))
     }
 
     #[inline]
     fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
     where V: Visitor<'de> {
-        drive_visitor_seq_const::<_, _, B, TAG_ID>(self.reader, visitor)
+        drive_visitor_seq_const::<_, _, B, TAG_ID>(self.reader, self.config, visitor)
     }
 
     #[inline]
@@ -307,7 +644,7 @@ where
         V: Visitor<'de>,
     {
         if TAG_ID == 0xA {
-            visitor.visit_map(DeserializeMap::<_, B>::new(self.reader))
+            visitor.visit_map(DeserializeMap::<_, B>::new(self.reader, self.config)?)
         } else {
             Err(NbtIoError::TagTypeMismatch {
                 expected: 0xA,
@@ -328,7 +665,7 @@ impl<'a, 'buffer, R, B, const TAG_ID: u8, const LIST_ID: u8>
     DeserializeSeq<'a, R, B, TAG_ID, LIST_ID>
 where
     R: Read,
-    B: BufferSpecialization<'buffer>,
+    B: BufferSpecialization<'buffer, R>,
 {
     #[inline]
     fn new(inner: DeserializeTag<'a, R, B, TAG_ID>, len: usize) -> Self {
@@ -347,7 +684,7 @@ where
     'de: 'a,
     'buffer: 'de,
     R: Read,
-    B: BufferSpecialization<'buffer>,
+    B: BufferSpecialization<'buffer, R>,
 {
     type Error = NbtIoError;
 
@@ -412,21 +749,23 @@ enum TypeHintDispatchState {
 struct DeserializeMap<'a, R, B> {
     reader: &'a mut R,
     tag_id: u8,
+    config: DeserializerConfig,
     _buffered: PhantomData<B>,
 }
 
 impl<'a, 'buffer, R, B> DeserializeMap<'a, R, B>
 where
     R: Read,
-    B: BufferSpecialization<'buffer>,
+    B: BufferSpecialization<'buffer, R>,
 {
     #[inline]
-    fn new(reader: &'a mut R) -> Self {
-        DeserializeMap {
+    fn new(reader: &'a mut R, config: DeserializerConfig) -> Result<Self, NbtIoError> {
+        Ok(DeserializeMap {
             reader,
             tag_id: 0,
+            config: config.nested()?,
             _buffered: PhantomData,
-        }
+        })
     }
 
     fn drive_value_visitor<'de, V>(
@@ -442,7 +781,7 @@ where
         macro_rules! drive_visitor {
             ($($id:literal)*) => {
                 match tag_id {
-                    $( $id => seed.deserialize(&mut DeserializeTag::<_, B, $id>::new(self.reader)), )*
+                    $( $id => seed.deserialize(&mut DeserializeTag::<_, B, $id>::new(self.reader, self.config)), )*
                     _ => Err(NbtIoError::InvalidTagId(tag_id))
                 }
             };
@@ -457,7 +796,7 @@ where
     'de: 'a,
     'buffer: 'de,
     R: Read,
-    B: BufferSpecialization<'buffer>,
+    B: BufferSpecialization<'buffer, R>,
 {
     type Error = NbtIoError;
 
@@ -470,7 +809,7 @@ where
             return Ok(None);
         }
 
-        let mut de = DeserializeTag::<_, B, 0x8>::new(self.reader);
+        let mut de = DeserializeTag::<_, B, 0x8>::new(self.reader, self.config);
         seed.deserialize(&mut de).map(Some)
     }
 
@@ -483,21 +822,81 @@ where
 
 pub struct DeserializeTag<'a, R, B, const TAG_ID: u8> {
     reader: &'a mut R,
+    config: DeserializerConfig,
+    // The array tag required by an `Array` being deserialized with strict sequences
+    expected_array: Option<u8>,
     _buffered: PhantomData<B>,
 }
 
 impl<'a, 'buffer, R, B, const TAG_ID: u8> DeserializeTag<'a, R, B, TAG_ID>
 where
     R: Read,
-    B: BufferSpecialization<'buffer>,
+    B: BufferSpecialization<'buffer, R>,
 {
     #[inline]
-    fn new(reader: &'a mut R) -> DeserializeTag<'a, R, B, TAG_ID> {
+    fn new(reader: &'a mut R, config: DeserializerConfig) -> DeserializeTag<'a, R, B, TAG_ID> {
         DeserializeTag {
             reader,
+            config,
+            expected_array: None,
             _buffered: PhantomData,
         }
     }
+
+    // Reads the numeric tag this deserializer is positioned at
+    fn read_number(&mut self) -> Result<NbtTag, NbtIoError> {
+        Ok(match TAG_ID {
+            0x1 => NbtTag::Byte(raw::read_i8(self.reader)?),
+            0x2 => NbtTag::Short(raw::read_i16(self.reader)?),
+            0x3 => NbtTag::Int(raw::read_i32(self.reader)?),
+            0x4 => NbtTag::Long(raw::read_i64(self.reader)?),
+            0x5 => NbtTag::Float(raw::read_f32(self.reader)?),
+            0x6 => NbtTag::Double(raw::read_f64(self.reader)?),
+            _ => unreachable!(),
+        })
+    }
+}
+
+fn visit_number<'de, V>(tag: &NbtTag, visitor: V) -> Result<V::Value, NbtIoError>
+where V: Visitor<'de> {
+    match *tag {
+        NbtTag::Byte(value) => visitor.visit_i8(value),
+        NbtTag::Short(value) => visitor.visit_i16(value),
+        NbtTag::Int(value) => visitor.visit_i32(value),
+        NbtTag::Long(value) => visitor.visit_i64(value),
+        NbtTag::Float(value) => visitor.visit_f32(value),
+        NbtTag::Double(value) => visitor.visit_f64(value),
+        _ => unreachable!(),
+    }
+}
+
+// Implements the numeric `deserialize_*` methods, which apply the configured conversion policy
+// before visiting when the tag being read is numeric
+macro_rules! deserialize_converted {
+    ($($method:ident => $kind:ident),* $(,)?) => {
+        $(
+            #[inline]
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+            where V: Visitor<'de> {
+                let policy = match self.config.conversion {
+                    Some(policy) if (0x1 ..= 0x6).contains(&TAG_ID) => policy,
+                    _ => return self.deserialize_any(visitor),
+                };
+
+                let tag = self.read_number()?;
+                let converted = policy.convert(&tag, NbtTagKind::$kind).map_err(|error| {
+                    match policy {
+                        ConversionPolicy::Strict => NbtIoError::TagTypeMismatch {
+                            expected: NbtTagKind::$kind.id(),
+                            found: TAG_ID,
+                        },
+                        _ => de::Error::custom(error),
+                    }
+                })?;
+                visit_number(&converted, visitor)
+            }
+        )*
+    };
 }
 
 impl<'de, 'a, 'buffer, 'b, R, B, const TAG_ID: u8> de::Deserializer<'de>
@@ -506,12 +905,21 @@ where
     'de: 'a,
     'buffer: 'de,
     R: Read,
-    B: BufferSpecialization<'buffer>,
+    B: BufferSpecialization<'buffer, R>,
 {
     type Error = NbtIoError;
 
     forward_to_deserialize_any! {
-        i8 i16 i32 i64 i128 u16 u32 u64 u128 char f32 f64 string
+        i128 u16 u32 u64 u128 char string
+    }
+
+    deserialize_converted! {
+        deserialize_i8 => Byte,
+        deserialize_i16 => Short,
+        deserialize_i32 => Int,
+        deserialize_i64 => Long,
+        deserialize_f32 => Float,
+        deserialize_f64 => Double,
     }
 
     #[inline]
@@ -525,26 +933,26 @@ where
             0x5 => visitor.visit_f32(raw::read_f32(self.reader)?),
             0x6 => visitor.visit_f64(raw::read_f64(self.reader)?),
             0x7 => {
-                let len = raw::read_i32(self.reader)? as usize;
+                let len = raw::read_len(self.reader)?;
                 visitor.visit_seq(DeserializeSeq::<_, _, 0x1, 0x7>::new(
-                    DeserializeTag::<_, B, 0x1>::new(self.reader),
+                    DeserializeTag::<_, B, 0x1>::new(self.reader, self.config),
                     len,
                 ))
             }
             0x8 => visitor.visit_string(raw::read_string(self.reader)?),
-            0x9 => drive_visitor_seq_tag::<_, _, B>(self.reader, visitor),
-            0xA => visitor.visit_map(DeserializeMap::<_, B>::new(self.reader)),
+            0x9 => drive_visitor_seq_tag::<_, _, B>(self.reader, self.config, visitor),
+            0xA => visitor.visit_map(DeserializeMap::<_, B>::new(self.reader, self.config)?),
             0xB => {
-                let len = raw::read_i32(self.reader)? as usize;
+                let len = raw::read_len(self.reader)?;
                 visitor.visit_seq(DeserializeSeq::<_, _, 0x3, 0xB>::new(
-                    DeserializeTag::<_, B, 0x3>::new(self.reader),
+                    DeserializeTag::<_, B, 0x3>::new(self.reader, self.config),
                     len,
                 ))
             }
             0xC => {
-                let len = raw::read_i32(self.reader)? as usize;
+                let len = raw::read_len(self.reader)?;
                 visitor.visit_seq(DeserializeSeq::<_, _, 0x4, 0xC>::new(
-                    DeserializeTag::<_, B, 0x4>::new(self.reader),
+                    DeserializeTag::<_, B, 0x4>::new(self.reader, self.config),
                     len,
                 ))
             }
@@ -576,10 +984,8 @@ where
     fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where V: Visitor<'de> {
         if TAG_ID == 0x7 {
-            let len = raw::read_i32(self.reader)? as usize;
-            let mut array = vec![0u8; len];
-            self.reader.read_exact(&mut array)?;
-            visitor.visit_byte_buf(array)
+            let len = raw::read_len(self.reader)?;
+            visitor.visit_byte_buf(raw::read_byte_array(self.reader, len)?)
         } else {
             self.deserialize_any(visitor)
         }
@@ -589,16 +995,12 @@ where
     fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where V: Visitor<'de> {
         if TAG_ID == 0x7 {
-            let len = raw::read_i32(self.reader)? as usize;
+            let len = raw::read_len(self.reader)?;
 
             if B::BUFFERED {
-                // Safety: R is `&'a mut Cursor<&'buffer [u8]>` and `B` is
-                // `BufferedCursor<'buffer>` by the constructor `Deserializer::from_cursor`
-                visitor.visit_borrowed_bytes(unsafe { B::read_bytes(self.reader, len) }?)
+                visitor.visit_borrowed_bytes(B::read_bytes(self.reader, len)?)
             } else {
-                let mut array = vec![0u8; len];
-                self.reader.read_exact(&mut array)?;
-                visitor.visit_bytes(&array)
+                visitor.visit_bytes(&raw::read_byte_array(self.reader, len)?)
             }
         } else {
             self.deserialize_any(visitor)
@@ -611,9 +1013,7 @@ where
         if TAG_ID == 0x8 {
             if B::BUFFERED {
                 let len = raw::read_u16(self.reader)? as usize;
-                // Safety: R is `&'a mut Cursor<&'buffer [u8]>` and `B` is
-                // `BufferedCursor<'buffer>` by the constructor `Deserializer::from_cursor`
-                let bytes: &'de [u8] = unsafe { B::read_bytes(self.reader, len) }?;
+                let bytes: &'de [u8] = B::read_bytes(self.reader, len)?;
 
                 let string = match cesu8::from_java_cesu8(bytes) {
                     Ok(string) => string,
@@ -663,25 +1063,58 @@ where
     #[inline]
     fn deserialize_newtype_struct<V>(
         self,
-        _name: &'static str,
+        name: &'static str,
         visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_newtype_struct(self)
+        if name == RAW_NBT_NICHE {
+            let mut bytes = vec![TAG_ID];
+            crate::io::copy_tag_body(self.reader, TAG_ID, &mut bytes, self.config.depth)?;
+            return visitor.visit_byte_buf(bytes);
+        }
+
+        if !self.config.strict_sequences {
+            return visitor.visit_newtype_struct(self);
+        }
+
+        self.expected_array = match name {
+            BYTE_ARRAY_NICHE => Some(0x7),
+            INT_ARRAY_NICHE => Some(0xB),
+            LONG_ARRAY_NICHE => Some(0xC),
+            _ => None,
+        };
+        let result = visitor.visit_newtype_struct(&mut *self);
+        self.expected_array = None;
+        result
     }
 
     #[inline]
     fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where V: Visitor<'de> {
-        drive_visitor_seq_const::<_, _, B, TAG_ID>(self.reader, visitor)
+        if self.config.strict_sequences {
+            let expected = match self.expected_array.take() {
+                Some(id) => id,
+                None if matches!(TAG_ID, 0x7 | 0xB | 0xC) => 0x9,
+                None => TAG_ID,
+            };
+
+            if expected != TAG_ID {
+                return Err(NbtIoError::TagTypeMismatch {
+                    expected,
+                    found: TAG_ID,
+                });
+            }
+        }
+
+        drive_visitor_seq_const::<_, _, B, TAG_ID>(self.reader, self.config, visitor)
     }
 
     #[inline]
     fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where V: Visitor<'de> {
-        visitor.visit_map(DeserializeMap::<_, B>::new(self.reader))
+        visitor.visit_map(DeserializeMap::<_, B>::new(self.reader, self.config)?)
     }
 
     #[inline]
@@ -752,6 +1185,7 @@ where
             }
             // Newtype, tuple, and struct variants
             0xA => {
+                let config = self.config.nested()?;
                 let id = raw::read_u8(self.reader)?;
                 let mut buf = Vec::new();
                 let variant = raw::read_string_into(self.reader, &mut buf)?;
@@ -759,7 +1193,7 @@ where
                 macro_rules! drive_visitor {
                     ($($id:literal)*) => {
                         match id {
-                            $( $id => visitor.visit_enum(DeserializeEnum::<_, B, $id>::new(self.reader, variant)), )*
+                            $( $id => visitor.visit_enum(DeserializeEnum::<_, B, $id>::new(self.reader, variant, config)), )*
                             _ => Err(NbtIoError::InvalidTagId(id))
                         }
                     };
@@ -792,6 +1226,11 @@ where
     where V: Visitor<'de> {
         self.deserialize_any(visitor)
     }
+
+    #[inline]
+    fn is_human_readable(&self) -> bool {
+        self.config.human_readable
+    }
 }
 
 struct TypeHintDeserializer<const TAG_ID: u8>;
@@ -845,20 +1284,29 @@ impl de::Error for TypeHintDeserializerError {
     }
 }
 
-// A trait to implement specialization - sort of.
-pub unsafe trait BufferSpecialization<'buffer> {
+// A trait to implement specialization - sort of. Implementations are tied to the reader they are
+// used with, so only a deserializer reading from a cursor over a slice can borrow bytes from it.
+pub trait BufferSpecialization<'buffer, R> {
     const BUFFERED: bool;
 
-    unsafe fn read_bytes<'de, R>(_reader: &mut R, _len: usize) -> Result<&'de [u8], io::Error>
-    where 'buffer: 'de {
-        panic!("read_bytes called on a non-buffered reader")
-    }
+    // Extracts a reference to a slice of bytes out of the given reader. This is only called if
+    // `BUFFERED` is true.
+    fn read_bytes<'de>(reader: &mut R, len: usize) -> Result<&'de [u8], io::Error>
+    where 'buffer: 'de;
 }
 
 pub struct Unbuffered;
 
-unsafe impl BufferSpecialization<'static> for Unbuffered {
+impl<R> BufferSpecialization<'static, R> for Unbuffered {
     const BUFFERED: bool = false;
+
+    fn read_bytes<'de>(_reader: &mut R, _len: usize) -> Result<&'de [u8], io::Error>
+    where 'static: 'de {
+        Err(io::Error::new(
+            ErrorKind::Unsupported,
+            "Cannot borrow bytes from a non-buffered reader",
+        ))
+    }
 }
 
 pub struct BufferedCursor<'buffer> {
@@ -867,19 +1315,17 @@ pub struct BufferedCursor<'buffer> {
     _phantom: PhantomData<fn(&'buffer [u8])>,
 }
 
-unsafe impl<'buffer> BufferSpecialization<'buffer> for BufferedCursor<'buffer> {
+impl<'buffer> BufferSpecialization<'buffer, Cursor<&'buffer [u8]>> for BufferedCursor<'buffer> {
     const BUFFERED: bool = true;
 
-    /// Extracts a reference to a slice of bytes out of the given reader.
-    ///
-    /// # Safety
-    ///
-    /// The caller must assert that `R` is `Cursor<&'buffer [u8]>`, otherwise unconscionable
-    /// amounts of UB will ensue.
-    unsafe fn read_bytes<'de, R>(reader: &mut R, len: usize) -> Result<&'de [u8], io::Error>
-    where 'buffer: 'de {
-        let cursor: &mut Cursor<&'buffer [u8]> = &mut *(reader as *mut R as *mut _);
-        read_bytes_from_cursor(cursor, len)
+    fn read_bytes<'de>(
+        reader: &mut Cursor<&'buffer [u8]>,
+        len: usize,
+    ) -> Result<&'de [u8], io::Error>
+    where
+        'buffer: 'de,
+    {
+        read_bytes_from_cursor(reader, len)
     }
 }
 
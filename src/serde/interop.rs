@@ -0,0 +1,434 @@
+use crate::{
+    tag::{TagSeed, TagWithBools},
+    Map,
+    NbtCompound,
+    NbtList,
+    NbtTag,
+    NbtTagKind,
+};
+use serde::{
+    de::{self, DeserializeSeed, EnumAccess, MapAccess, SeqAccess, VariantAccess, Visitor},
+    Deserialize,
+    Deserializer,
+    Serialize,
+    Serializer,
+};
+use std::{fmt, marker::PhantomData};
+
+/// Describes how booleans are handled when converting tags to and from other serde formats, such
+/// as JSON, since NBT has no boolean type of its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum BoolConversion {
+    /// Booleans are deserialized as byte tags holding `0` or `1`, and byte tags are always
+    /// serialized as integers. This matches the `Serialize` and `Deserialize` implementations of
+    /// [`NbtTag`], and means that booleans do not survive a round trip.
+    ///
+    /// [`NbtTag`]: crate::NbtTag
+    #[default]
+    Implicit,
+    /// Booleans are deserialized as byte tags holding `0` or `1`, and byte tags holding `0` or `1`
+    /// are serialized as booleans. Sequences of booleans are deserialized as lists of byte tags
+    /// rather than as byte arrays. Booleans survive a round trip, at the cost of turning any other
+    /// byte tag which holds `0` or `1` into a boolean.
+    Bidirectional,
+    /// Deserializing a boolean is an error, and byte tags are always serialized as integers.
+    Error,
+}
+
+/// Options for converting tags to and from other serde formats. Unlike the `Serialize` and
+/// `Deserialize` implementations of the tag types, these options make lossy conversions between
+/// the formats explicit.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::serde::{BoolConversion, TagSerdeOptions};
+///
+/// let json = r#"{"CustomNameVisible":true,"Tags":["boss"]}"#;
+///
+/// let options = TagSerdeOptions::new().bools(BoolConversion::Bidirectional);
+/// let compound = options
+///     .deserialize_compound(&mut serde_json::Deserializer::from_str(json))
+///     .unwrap();
+/// assert_eq!(compound, compound! { "CustomNameVisible": 1i8, "Tags": ["boss"] });
+///
+/// let mut output = Vec::new();
+/// options
+///     .serialize_compound(&compound, &mut serde_json::Serializer::new(&mut output))
+///     .unwrap();
+/// assert_eq!(serde_json::from_slice::<serde_json::Value>(&output).unwrap()["CustomNameVisible"], true);
+///
+/// let options = TagSerdeOptions::new().bools(BoolConversion::Error);
+/// assert!(options
+///     .deserialize_compound(&mut serde_json::Deserializer::from_str(json))
+///     .is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct TagSerdeOptions {
+    bools: BoolConversion,
+}
+
+impl TagSerdeOptions {
+    /// Returns the default options, which behave exactly like the `Serialize` and `Deserialize`
+    /// implementations of the tag types.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how booleans are handled. Defaults to [`BoolConversion::Implicit`].
+    ///
+    /// [`BoolConversion::Implicit`]: crate::serde::BoolConversion::Implicit
+    pub fn bools(mut self, bools: BoolConversion) -> Self {
+        self.bools = bools;
+        self
+    }
+
+    /// Deserializes a tag from the given deserializer according to these options.
+    pub fn deserialize_tag<'de, D>(self, deserializer: D) -> Result<NbtTag, D::Error>
+    where D: Deserializer<'de> {
+        de::DeserializeSeed::deserialize(TagSeed(self.bools), deserializer)
+    }
+
+    /// Deserializes a compound from the given deserializer according to these options.
+    pub fn deserialize_compound<'de, D>(self, deserializer: D) -> Result<NbtCompound, D::Error>
+    where D: Deserializer<'de> {
+        match self.deserialize_tag(deserializer)? {
+            NbtTag::Compound(compound) => Ok(compound),
+            tag => Err(de::Error::invalid_type(
+                de::Unexpected::Other(tag.kind().name()),
+                &"a compound",
+            )),
+        }
+    }
+
+    /// Serializes the given tag to the given serializer according to these options.
+    pub fn serialize_tag<S>(self, tag: &NbtTag, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer {
+        serde::Serialize::serialize(&TagWithBools(tag, self.bools), serializer)
+    }
+
+    /// Serializes the given compound to the given serializer according to these options.
+    pub fn serialize_compound<S>(
+        self,
+        compound: &NbtCompound,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_map(
+            compound
+                .inner()
+                .iter()
+                .map(|(name, tag)| (name, TagWithBools(tag, self.bools))),
+        )
+    }
+}
+
+/// A wrapper which serializes a tag, list or compound to other serde formats along with the kind
+/// of every tag within it, so that it can be deserialized again without losing any type
+/// information.
+///
+/// The `Serialize` and `Deserialize` implementations of the tag types map each tag to the closest
+/// type in the serde data model, which self-describing formats such as JSON, CBOR and MessagePack
+/// do not fully preserve. When a tag takes a round trip through one of those formats:
+///  - Integer tags may come back as narrower or wider integers, since these formats only record
+///    the value of an integer, not the width of the tag it came from.
+///  - Float tags may come back as doubles. JSON writes non-finite floats as `null`, which cannot
+///    be read back as a tag.
+///  - Byte, int and long arrays and lists of integers are all written as sequences of integers,
+///    which come back as whichever array kind the format picks for their first element. Empty
+///    lists and arrays all come back as empty lists.
+///
+/// Wrapping a value in `TaggedValue` instead writes each tag as an externally tagged enum variant
+/// named after its [`NbtTagKind`], such as `{"Int":5}` in JSON. Compounds and lists are written
+/// as maps and sequences of such variants. The only remaining loss is that JSON cannot represent
+/// non-finite floats at all.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::serde::TaggedValue;
+///
+/// let compound = compound! { "Count": 1i8, "ids": [I; 1, 2], "Motion": [0.5f32] };
+///
+/// let json = serde_json::to_string(&TaggedValue(compound.clone())).unwrap();
+/// let TaggedValue(decoded) = serde_json::from_str::<TaggedValue<NbtCompound>>(&json).unwrap();
+/// assert_eq!(decoded, compound);
+///
+/// // Without the wrapper, the kinds of the tags are lost
+/// let json = serde_json::to_string(&compound).unwrap();
+/// assert_ne!(serde_json::from_str::<NbtCompound>(&json).unwrap(), compound);
+/// ```
+///
+/// [`NbtTagKind`]: crate::NbtTagKind
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TaggedValue<T>(pub T);
+
+impl Serialize for TaggedValue<NbtTag> {
+    #[inline]
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer {
+        TaggedTag(&self.0).serialize(serializer)
+    }
+}
+
+impl Serialize for TaggedValue<NbtList> {
+    #[inline]
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer {
+        TaggedList(&self.0).serialize(serializer)
+    }
+}
+
+impl Serialize for TaggedValue<NbtCompound> {
+    #[inline]
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer {
+        TaggedCompound(&self.0).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for TaggedValue<NbtTag> {
+    #[inline]
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: Deserializer<'de> {
+        TaggedTagSeed.deserialize(deserializer).map(TaggedValue)
+    }
+}
+
+impl<'de> Deserialize<'de> for TaggedValue<NbtList> {
+    #[inline]
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: Deserializer<'de> {
+        TaggedListSeed.deserialize(deserializer).map(TaggedValue)
+    }
+}
+
+impl<'de> Deserialize<'de> for TaggedValue<NbtCompound> {
+    #[inline]
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: Deserializer<'de> {
+        TaggedCompoundSeed
+            .deserialize(deserializer)
+            .map(TaggedValue)
+    }
+}
+
+const TAG_VARIANTS: &[&str] = &[
+    "Byte",
+    "Short",
+    "Int",
+    "Long",
+    "Float",
+    "Double",
+    "ByteArray",
+    "String",
+    "List",
+    "Compound",
+    "IntArray",
+    "LongArray",
+];
+
+struct TaggedTag<'a>(&'a NbtTag);
+
+impl Serialize for TaggedTag<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer {
+        let kind = self.0.kind();
+        let index = u32::from(kind.id() - 1);
+        let name = TAG_VARIANTS[index as usize];
+        match self.0 {
+            NbtTag::Byte(value) =>
+                serializer.serialize_newtype_variant("NbtTag", index, name, value),
+            NbtTag::Short(value) =>
+                serializer.serialize_newtype_variant("NbtTag", index, name, value),
+            NbtTag::Int(value) =>
+                serializer.serialize_newtype_variant("NbtTag", index, name, value),
+            NbtTag::Long(value) =>
+                serializer.serialize_newtype_variant("NbtTag", index, name, value),
+            NbtTag::Float(value) =>
+                serializer.serialize_newtype_variant("NbtTag", index, name, value),
+            NbtTag::Double(value) =>
+                serializer.serialize_newtype_variant("NbtTag", index, name, value),
+            NbtTag::ByteArray(array) =>
+                serializer.serialize_newtype_variant("NbtTag", index, name, array),
+            NbtTag::String(value) =>
+                serializer.serialize_newtype_variant("NbtTag", index, name, value),
+            NbtTag::List(list) =>
+                serializer.serialize_newtype_variant("NbtTag", index, name, &TaggedList(list)),
+            NbtTag::Compound(compound) => serializer.serialize_newtype_variant(
+                "NbtTag",
+                index,
+                name,
+                &TaggedCompound(compound),
+            ),
+            NbtTag::IntArray(array) =>
+                serializer.serialize_newtype_variant("NbtTag", index, name, array),
+            NbtTag::LongArray(array) =>
+                serializer.serialize_newtype_variant("NbtTag", index, name, array),
+        }
+    }
+}
+
+struct TaggedList<'a>(&'a NbtList);
+
+impl Serialize for TaggedList<'_> {
+    #[inline]
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer {
+        serializer.collect_seq(self.0.iter().map(TaggedTag))
+    }
+}
+
+struct TaggedCompound<'a>(&'a NbtCompound);
+
+impl Serialize for TaggedCompound<'_> {
+    #[inline]
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer {
+        serializer.collect_map(
+            self.0
+                .inner()
+                .iter()
+                .map(|(name, tag)| (name, TaggedTag(tag))),
+        )
+    }
+}
+
+#[derive(Clone, Copy)]
+struct TaggedTagSeed;
+
+impl<'de> DeserializeSeed<'de> for TaggedTagSeed {
+    type Value = NbtTag;
+
+    #[inline]
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where D: Deserializer<'de> {
+        deserializer.deserialize_enum("NbtTag", TAG_VARIANTS, self)
+    }
+}
+
+impl<'de> Visitor<'de> for TaggedTagSeed {
+    type Value = NbtTag;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a tagged NBT tag")
+    }
+
+    fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+    where A: EnumAccess<'de> {
+        let (kind, variant) = data.variant_seed(KindSeed)?;
+        match kind {
+            NbtTagKind::Byte => variant.newtype_variant().map(NbtTag::Byte),
+            NbtTagKind::Short => variant.newtype_variant().map(NbtTag::Short),
+            NbtTagKind::Int => variant.newtype_variant().map(NbtTag::Int),
+            NbtTagKind::Long => variant.newtype_variant().map(NbtTag::Long),
+            NbtTagKind::Float => variant.newtype_variant().map(NbtTag::Float),
+            NbtTagKind::Double => variant.newtype_variant().map(NbtTag::Double),
+            NbtTagKind::ByteArray => variant.newtype_variant().map(NbtTag::ByteArray),
+            NbtTagKind::String => variant.newtype_variant().map(NbtTag::String),
+            NbtTagKind::List => variant
+                .newtype_variant_seed(TaggedListSeed)
+                .map(NbtTag::List),
+            NbtTagKind::Compound => variant
+                .newtype_variant_seed(TaggedCompoundSeed)
+                .map(NbtTag::Compound),
+            NbtTagKind::IntArray => variant.newtype_variant().map(NbtTag::IntArray),
+            NbtTagKind::LongArray => variant.newtype_variant().map(NbtTag::LongArray),
+        }
+    }
+}
+
+// Deserializes the name or index of a variant written by TaggedTag
+struct KindSeed;
+
+impl<'de> DeserializeSeed<'de> for KindSeed {
+    type Value = NbtTagKind;
+
+    #[inline]
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where D: Deserializer<'de> {
+        deserializer.deserialize_identifier(self)
+    }
+}
+
+impl<'de> Visitor<'de> for KindSeed {
+    type Value = NbtTagKind;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the name of a tag kind")
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        u8::try_from(v)
+            .ok()
+            .and_then(|index| NbtTagKind::from_id(index.wrapping_add(1)))
+            .ok_or_else(|| E::invalid_value(de::Unexpected::Unsigned(v), &self))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        match TAG_VARIANTS.iter().position(|&name| name == v) {
+            Some(index) => self.visit_u64(index as u64),
+            None => Err(E::unknown_variant(v, TAG_VARIANTS)),
+        }
+    }
+}
+
+struct TaggedListSeed;
+
+impl<'de> DeserializeSeed<'de> for TaggedListSeed {
+    type Value = NbtList;
+
+    #[inline]
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where D: Deserializer<'de> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for TaggedListSeed {
+    type Value = NbtList;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a sequence of tagged NBT tags")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where A: SeqAccess<'de> {
+        let capacity = seq.size_hint().unwrap_or(0);
+        let mut list = NbtList::with_capacity(capacity.min(crate::raw::PREALLOC_LIMIT));
+        while let Some(tag) = seq.next_element_seed(TaggedTagSeed)? {
+            list.push(tag);
+        }
+        Ok(list)
+    }
+}
+
+struct TaggedCompoundSeed;
+
+impl<'de> DeserializeSeed<'de> for TaggedCompoundSeed {
+    type Value = NbtCompound;
+
+    #[inline]
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where D: Deserializer<'de> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for TaggedCompoundSeed {
+    type Value = NbtCompound;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a map of tagged NBT tags")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where A: MapAccess<'de> {
+        let capacity = map.size_hint().unwrap_or(0);
+        let mut compound = Map::with_capacity(capacity.min(crate::raw::PREALLOC_LIMIT));
+        while let Some((key, tag)) = map.next_entry_seed(PhantomData::<String>, TaggedTagSeed)? {
+            compound.insert(key, tag);
+        }
+        Ok(NbtCompound(compound))
+    }
+}
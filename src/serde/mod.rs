@@ -1,19 +1,37 @@
 mod array;
 mod de;
+mod interop;
+mod raw_nbt;
 mod ser;
 mod util;
 
-pub use array::Array;
-pub(crate) use array::{TypeHint, TYPE_HINT_NICHE};
-pub use de::Deserializer;
-pub use ser::{Serializer, UncheckedSerializer};
+pub use array::{Array, FixedArray};
+pub(crate) use array::{
+    TypeHint,
+    BYTE_ARRAY_NICHE,
+    INT_ARRAY_NICHE,
+    LONG_ARRAY_NICHE,
+    TYPE_HINT_NICHE,
+};
+pub use de::{Deserializer, DeserializerBuilder};
+pub use interop::{BoolConversion, TagSerdeOptions, TaggedValue};
+pub use raw_nbt::RawNbt;
+pub(crate) use raw_nbt::RAW_NBT_NICHE;
+pub(crate) use ser::EMPTY_LIST_NICHES;
+pub use ser::{
+    NonFiniteFloats,
+    Serializer,
+    SerializerBuilder,
+    UncheckedSerializer,
+    UnitVariantEncoding,
+};
 pub use util::Ser;
 
-use crate::io::{Flavor, NbtIoError};
+use crate::io::{self, Flavor, NbtIoError};
 use flate2::{
-    read::{GzDecoder, ZlibDecoder},
-    write::{GzEncoder, ZlibEncoder},
+    write::{DeflateEncoder, ZlibEncoder},
     Compression,
+    Crc,
 };
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::{
@@ -22,7 +40,31 @@ use std::{
 };
 
 /// Serializes the given value as binary NBT data, returning the resulting Vec. The value must
-/// be a struct or non-unit enum variant, else the serializer will return with an error.
+/// be a struct, map or non-unit enum variant, else the serializer will return with
+/// [`NbtIoError::MissingRootTag`].
+///
+/// As exceptions, `()`, unit structs and `None` are written as an empty compound, and `Some(value)`
+/// is written the same way as `value`. Note that deserializing an `Option` from the root tag always
+/// produces `Some`, since a root tag is always present.
+///
+/// ```
+/// # use quartz_nbt::compound;
+/// use quartz_nbt::io::{self, Flavor};
+///
+/// let heartbeat = quartz_nbt::serde::serialize(&(), Some("ping"), Flavor::Uncompressed)?;
+/// assert_eq!(
+///     quartz_nbt::serde::serialize(&None::<()>, Some("ping"), Flavor::Uncompressed)?,
+///     heartbeat
+/// );
+///
+/// let (nbt, root_name) = io::read_nbt(&mut heartbeat.as_slice(), Flavor::Uncompressed)?;
+/// assert_eq!((nbt, root_name.as_str()), (compound! {}, "ping"));
+///
+/// let ((), _) = quartz_nbt::serde::deserialize(&heartbeat, Flavor::Uncompressed)?;
+/// # Ok::<(), io::NbtIoError>(())
+/// ```
+///
+/// [`NbtIoError::MissingRootTag`]: crate::io::NbtIoError::MissingRootTag
 pub fn serialize<T: Serialize>(
     value: &T,
     root_name: Option<&str>,
@@ -50,35 +92,17 @@ pub fn serialize_unchecked<T: Serialize>(
 
 /// Serializes the given value as binary NBT data, writing to the given writer.
 ///
-/// The value must be a struct or non-unit enum variant, else the serializer will return with an
-/// error.
+/// The value must be a struct, map or non-unit enum variant, else the serializer will return with
+/// an error. See [`serialize`] for the handling of unit types and options.
+///
+/// [`serialize`]: crate::serde::serialize
 pub fn serialize_into<W: Write, T: Serialize>(
     writer: &mut W,
     value: &T,
     root_name: Option<&str>,
     flavor: Flavor,
 ) -> Result<(), NbtIoError> {
-    let (mode, compression) = match flavor {
-        Flavor::Uncompressed => {
-            return value.serialize(Serializer::new(writer, root_name));
-        }
-        Flavor::ZlibCompressed => (2, Compression::default()),
-        Flavor::ZlibCompressedWith(compression) => (2, compression),
-        Flavor::GzCompressed => (1, Compression::default()),
-        Flavor::GzCompressedWith(compression) => (1, compression),
-    };
-
-    if mode == 1 {
-        value.serialize(Serializer::new(
-            &mut GzEncoder::new(writer, compression),
-            root_name,
-        ))
-    } else {
-        value.serialize(Serializer::new(
-            &mut ZlibEncoder::new(writer, compression),
-            root_name,
-        ))
-    }
+    SerializerBuilder::new().serialize_into(writer, value, root_name, flavor)
 }
 
 /// Similar to [`serialize_into`], but elides checks for homogeneity on sequential types and
@@ -92,29 +116,132 @@ pub fn serialize_into_unchecked<W: Write, T: Serialize>(
     root_name: Option<&str>,
     flavor: Flavor,
 ) -> Result<(), NbtIoError> {
-    let (mode, compression) = match flavor {
+    io::with_encoder(writer, flavor, Compression::default(), |encoder| {
+        value.serialize(UncheckedSerializer::new(encoder, root_name))
+    })
+}
+
+/// Serializes each of the given values as a separate document of binary NBT data with the given
+/// root name, writing the documents back-to-back to the given writer.
+///
+/// This is equivalent to calling [`serialize_into`] once per document, but a single scratch buffer
+/// and compression context are reused for every document rather than being set up anew each time,
+/// which makes a significant difference when writing many small documents. Compressed documents are
+/// each written as an independent Zlib or Gz stream.
+///
+/// If serializing a document fails, the documents before it will already have been written.
+///
+/// ```
+/// # extern crate serde;
+/// # use serde::Serialize;
+/// use quartz_nbt::{
+///     io::{self, Flavor},
+///     serde::serialize_batch_into,
+/// };
+///
+/// #[derive(Serialize)]
+/// struct Profile {
+///     xp: i32,
+/// }
+///
+/// let profiles = [("alice", Profile { xp: 10 }), ("bob", Profile { xp: 25 })];
+/// let mut bytes = Vec::new();
+/// serialize_batch_into(
+///     &mut bytes,
+///     profiles.iter().map(|(name, profile)| (*name, profile)),
+///     Flavor::Uncompressed,
+/// )?;
+///
+/// let mut reader = bytes.as_slice();
+/// let (first, name) = io::read_nbt(&mut reader, Flavor::Uncompressed)?;
+/// assert_eq!((name.as_str(), first.get::<_, i32>("xp")?), ("alice", 10));
+/// let (second, name) = io::read_nbt(&mut reader, Flavor::Uncompressed)?;
+/// assert_eq!((name.as_str(), second.get::<_, i32>("xp")?), ("bob", 25));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// [`serialize_into`]: crate::serde::serialize_into
+pub fn serialize_batch_into<'a, W, T, I>(
+    writer: &mut W,
+    documents: I,
+    flavor: Flavor,
+) -> Result<(), NbtIoError>
+where
+    W: Write,
+    T: Serialize + 'a,
+    I: IntoIterator<Item = (&'a str, &'a T)>,
+{
+    let mut compressor = match flavor {
         Flavor::Uncompressed => {
-            return value.serialize(UncheckedSerializer::new(writer, root_name));
+            for (root_name, value) in documents {
+                value.serialize(Serializer::new(writer, Some(root_name)))?;
+            }
+            return Ok(());
         }
-        Flavor::ZlibCompressed => (2, Compression::default()),
-        Flavor::ZlibCompressedWith(compression) => (2, compression),
-        Flavor::GzCompressed => (1, Compression::default()),
-        Flavor::GzCompressedWith(compression) => (1, compression),
+        Flavor::ZlibCompressed =>
+            BatchCompressor::Zlib(ZlibEncoder::new(Vec::new(), Compression::default())),
+        Flavor::ZlibCompressedWith(compression) =>
+            BatchCompressor::Zlib(ZlibEncoder::new(Vec::new(), compression)),
+        Flavor::GzCompressed =>
+            BatchCompressor::Gz(DeflateEncoder::new(Vec::new(), Compression::default())),
+        Flavor::GzCompressedWith(compression) =>
+            BatchCompressor::Gz(DeflateEncoder::new(Vec::new(), compression)),
+        Flavor::RawDeflate =>
+            BatchCompressor::RawDeflate(DeflateEncoder::new(Vec::new(), Compression::default())),
+        Flavor::RawDeflateWith(compression) =>
+            BatchCompressor::RawDeflate(DeflateEncoder::new(Vec::new(), compression)),
     };
 
-    if mode == 1 {
-        value.serialize(UncheckedSerializer::new(
-            &mut GzEncoder::new(writer, compression),
-            root_name,
-        ))
-    } else {
-        value.serialize(UncheckedSerializer::new(
-            &mut ZlibEncoder::new(writer, compression),
-            root_name,
-        ))
+    let mut scratch = Vec::new();
+    let mut spare = Vec::new();
+    for (root_name, value) in documents {
+        scratch.clear();
+        value.serialize(Serializer::new(&mut scratch, Some(root_name)))?;
+
+        // Resetting an encoder finishes its stream and hands back the buffer it was written to,
+        // while keeping its allocated compression state for the next document
+        let mut compressed = match &mut compressor {
+            BatchCompressor::Zlib(encoder) => {
+                encoder.write_all(&scratch)?;
+                encoder.reset(spare)?
+            }
+            BatchCompressor::RawDeflate(encoder) => {
+                encoder.write_all(&scratch)?;
+                encoder.reset(spare)?
+            }
+            BatchCompressor::Gz(encoder) => {
+                let mut crc = Crc::new();
+                crc.update(&scratch);
+
+                encoder.write_all(&scratch)?;
+                let mut compressed = encoder.reset(spare)?;
+                compressed.extend_from_slice(&crc.sum().to_le_bytes());
+                compressed.extend_from_slice(&crc.amount().to_le_bytes());
+                writer.write_all(&GZ_HEADER)?;
+                compressed
+            }
+        };
+
+        writer.write_all(&compressed)?;
+        compressed.clear();
+        spare = compressed;
     }
+
+    Ok(())
+}
+
+enum BatchCompressor {
+    Zlib(ZlibEncoder<Vec<u8>>),
+    RawDeflate(DeflateEncoder<Vec<u8>>),
+    // Gz streams are written as a fixed header, raw deflate data, and a CRC trailer, since the Gz
+    // encoder cannot be reset
+    Gz(DeflateEncoder<Vec<u8>>),
 }
 
+// Magic number, deflate compression method, no flags, no modification time, no extra flags, and
+// an unknown operating system
+const GZ_HEADER: [u8; 10] = [0x1F, 0x8B, 8, 0, 0, 0, 0, 0, 0, 0xFF];
+
 /// Deserializes the given type from uncompressed, binary NBT data, allowing for the type to borrow
 /// from the given buffer.
 ///
@@ -147,18 +274,23 @@ pub fn deserialize_from<R: Read, T: DeserializeOwned>(
     reader: &mut R,
     flavor: Flavor,
 ) -> Result<(T, String), NbtIoError> {
-    match flavor {
-        Flavor::Uncompressed => deserialize_from_raw(reader),
-        Flavor::ZlibCompressed | Flavor::ZlibCompressedWith(_) =>
-            deserialize_from_raw(&mut ZlibDecoder::new(reader)),
-        Flavor::GzCompressed | Flavor::GzCompressedWith(_) =>
-            deserialize_from_raw(&mut GzDecoder::new(reader)),
-    }
+    DeserializerBuilder::new().deserialize_from(reader, flavor)
 }
 
-fn deserialize_from_raw<'de: 'a, 'a, R: Read, T: Deserialize<'de>>(
-    reader: &'a mut R,
+/// Deserializes the given type from binary NBT data read directly out of the given buffer. Only
+/// the bytes making up the document are consumed, so any data following it is left in the buffer.
+/// See [`io::read_nbt_buf`] for details.
+///
+/// This is a shorthand for [`DeserializerBuilder::deserialize_from_buf`] with the default options.
+///
+/// This function is only available when the `bytes` feature is enabled.
+///
+/// [`io::read_nbt_buf`]: crate::io::read_nbt_buf
+/// [`DeserializerBuilder::deserialize_from_buf`]: crate::serde::DeserializerBuilder::deserialize_from_buf
+#[cfg(feature = "bytes")]
+pub fn deserialize_from_buf<B: bytes::Buf, T: DeserializeOwned>(
+    buf: &mut B,
+    flavor: Flavor,
 ) -> Result<(T, String), NbtIoError> {
-    let (de, root_name) = Deserializer::new(reader)?;
-    Ok((T::deserialize(de)?, root_name))
+    DeserializerBuilder::new().deserialize_from_buf(buf, flavor)
 }
@@ -0,0 +1,162 @@
+use crate::{
+    io::{self, NbtIoError},
+    NbtCompound,
+    NbtTag,
+};
+use serde::{
+    de::{self, Visitor},
+    Deserialize,
+    Deserializer,
+    Serialize,
+    Serializer,
+};
+use std::fmt;
+
+pub(crate) const RAW_NBT_NICHE: &str = "quartz_nbt_raw";
+
+/// A tag whose binary representation was captured verbatim during deserialization rather than
+/// being decoded, similar to `serde_json`'s `RawValue`.
+///
+/// When a struct deserialized with this crate's [`Deserializer`] has a field of type `RawNbt` (or
+/// `Box<RawNbt>`), the bytes of that field are copied out of the input without building any
+/// strings, lists or compounds. Serializing the struct again with this crate's serializer writes
+/// those bytes back out unchanged, so a document can be forwarded while only a few of its fields
+/// are inspected. The captured tag can still be decoded later with [`to_tag`] or
+/// [`to_compound`].
+///
+/// ```
+/// # use quartz_nbt::{compound, NbtCompound};
+/// use quartz_nbt::{io::Flavor, serde::RawNbt};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Entity {
+///     id: String,
+///     data: Box<RawNbt>,
+/// }
+///
+/// let nbt = compound! {
+///     "id": "minecraft:pig",
+///     "data": { "Health": 10.0f32, "Motion": [0.0f64, 0.0f64, 0.0f64] }
+/// };
+/// let bytes = quartz_nbt::serde::serialize(&nbt, None, Flavor::Uncompressed)?;
+///
+/// let entity: Entity = quartz_nbt::serde::deserialize(&bytes, Flavor::Uncompressed)?.0;
+/// assert_eq!(entity.id, "minecraft:pig");
+/// assert_eq!(
+///     entity.data.to_compound()?,
+///     compound! { "Health": 10.0f32, "Motion": [0.0f64, 0.0f64, 0.0f64] }
+/// );
+///
+/// // The untouched data is written back exactly as it was read
+/// assert_eq!(
+///     quartz_nbt::serde::serialize(&entity, None, Flavor::Uncompressed)?,
+///     bytes
+/// );
+/// # Ok::<(), quartz_nbt::io::NbtIoError>(())
+/// ```
+///
+/// Only this crate's serializer and deserializer know how to handle raw tags. Other data formats
+/// see the tag ID followed by the binary payload as a byte sequence.
+///
+/// [`Deserializer`]: crate::serde::Deserializer
+/// [`to_tag`]: crate::serde::RawNbt::to_tag
+/// [`to_compound`]: crate::serde::RawNbt::to_compound
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RawNbt {
+    // The tag ID followed by the binary payload of the tag
+    bytes: Vec<u8>,
+}
+
+impl RawNbt {
+    /// Encodes the given tag, returning its raw form.
+    pub fn from_tag(tag: &NbtTag) -> Result<Self, NbtIoError> {
+        let mut bytes = vec![tag.kind().id()];
+        io::write_tag_body(&mut bytes, tag)?;
+        Ok(RawNbt { bytes })
+    }
+
+    /// Returns the ID of the captured tag.
+    #[inline]
+    pub fn tag_id(&self) -> u8 {
+        self.bytes[0]
+    }
+
+    /// Returns the binary payload of the captured tag, which does not include its ID or name.
+    #[inline]
+    pub fn payload(&self) -> &[u8] {
+        &self.bytes[1 ..]
+    }
+
+    /// Decodes the captured tag.
+    pub fn to_tag(&self) -> Result<NbtTag, NbtIoError> {
+        io::read_tag_body_dyn(&mut self.payload(), self.tag_id(), 0)
+    }
+
+    /// Decodes the captured tag, returning an error if it is not a compound.
+    pub fn to_compound(&self) -> Result<NbtCompound, NbtIoError> {
+        if self.tag_id() != 0xA {
+            return Err(NbtIoError::TagTypeMismatch {
+                expected: 0xA,
+                found: self.tag_id(),
+            });
+        }
+
+        match self.to_tag()? {
+            NbtTag::Compound(compound) => Ok(compound),
+            _ => unreachable!("TAG_Compound decoded as another tag type"),
+        }
+    }
+}
+
+impl Serialize for RawNbt {
+    #[inline]
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer {
+        serializer.serialize_newtype_struct(RAW_NBT_NICHE, &RawBytes(&self.bytes))
+    }
+}
+
+struct RawBytes<'a>(&'a [u8]);
+
+impl Serialize for RawBytes<'_> {
+    #[inline]
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer {
+        serializer.serialize_bytes(self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for RawNbt {
+    #[inline]
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: Deserializer<'de> {
+        struct RawNbtVisitor;
+
+        impl<'de> Visitor<'de> for RawNbtVisitor {
+            type Value = RawNbt;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "A raw NBT tag")
+            }
+
+            fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+            where D: Deserializer<'de> {
+                deserializer.deserialize_byte_buf(self)
+            }
+
+            fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+                self.visit_byte_buf(v.to_vec())
+            }
+
+            fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+                match v.first() {
+                    Some(0x1 ..= 0xC) => Ok(RawNbt { bytes: v }),
+                    _ => Err(E::invalid_value(de::Unexpected::Bytes(&v), &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_newtype_struct(RAW_NBT_NICHE, RawNbtVisitor)
+    }
+}
@@ -1,8 +1,14 @@
 use super::{
     array::{BYTE_ARRAY_NICHE, INT_ARRAY_NICHE, LONG_ARRAY_NICHE},
+    raw_nbt::RAW_NBT_NICHE,
     util::{DefaultSerializer, Ser},
 };
-use crate::{io::NbtIoError, raw};
+use crate::{
+    io::{self, Flavor, NbtIoError},
+    path::{push_index, push_key},
+    raw,
+};
+use flate2::Compression;
 use serde::{
     ser::{
         Impossible,
@@ -22,18 +28,49 @@ use std::{
     marker::PhantomData,
 };
 
+// The names of the newtype structs which empty lists with an element kind hint are serialized
+// as, indexed by the id of the element kind minus one. The wrapped value is the empty list itself,
+// so that other serializers see an ordinary empty sequence.
+pub(crate) const EMPTY_LIST_NICHES: [&str; 12] = [
+    "quartz_nbt_empty_byte_list",
+    "quartz_nbt_empty_short_list",
+    "quartz_nbt_empty_int_list",
+    "quartz_nbt_empty_long_list",
+    "quartz_nbt_empty_float_list",
+    "quartz_nbt_empty_double_list",
+    "quartz_nbt_empty_byte_array_list",
+    "quartz_nbt_empty_string_list",
+    "quartz_nbt_empty_list_list",
+    "quartz_nbt_empty_compound_list",
+    "quartz_nbt_empty_int_array_list",
+    "quartz_nbt_empty_long_array_list",
+];
+
+// Returns the element id of the empty list serialized as a newtype struct with the given name
+fn empty_list_element_id(name: &str) -> Option<u8> {
+    EMPTY_LIST_NICHES
+        .iter()
+        .position(|&niche| niche == name)
+        .map(|index| index as u8 + 1)
+}
+
 /// The serializer type for writing binary NBT data.
 pub type Serializer<'a, W> = Ser<SerializerImpl<'a, W, Homogenous>>;
 
 /// An alternative serializer type for writing binary NBT data which elides checks for
 /// sequence homogeneity. Using this type could result in bogus NBT data.
-pub type UncheckedSerializer<'a, W> = Ser<SerializerImpl<'a, W, Unchecked>>;
+///
+/// The checks can be restored in debug builds with [`SerializerBuilder::debug_checks`], so that
+/// mistakes are caught during development without slowing down release builds.
+///
+/// [`SerializerBuilder::debug_checks`]: crate::serde::SerializerBuilder::debug_checks
+pub type UncheckedSerializer<'a, W> = Ser<SerializerImpl<'a, W, DebugHomogenous>>;
 
 impl<'a, W: Write> Serializer<'a, W> {
     /// Constructs a new serializer with the given writer and root name. If no root name is specified,
     /// then an empty string is written to the header.
     pub fn new(writer: &'a mut W, root_name: Option<&'a str>) -> Self {
-        SerializerImpl::new(writer, BorrowedPrefix::new(root_name.unwrap_or(""))).into_serializer()
+        SerializerBuilder::new().build(writer, root_name)
     }
 }
 
@@ -41,21 +78,390 @@ impl<'a, W: Write> UncheckedSerializer<'a, W> {
     /// Constructs a new unchecked serializer with the given writer and root name, If no root name is
     /// specified then an empty string is written to the header.
     pub fn new(writer: &'a mut W, root_name: Option<&'a str>) -> Self {
-        SerializerImpl::new(writer, BorrowedPrefix::new(root_name.unwrap_or(""))).into_serializer()
+        SerializerBuilder::new().build_unchecked(writer, root_name)
     }
 }
 
+/// A builder for serializers which write data in a representation other than the default.
+///
+/// ```
+/// # extern crate serde;
+/// # use serde::Serialize;
+/// use quartz_nbt::{
+///     compound,
+///     io::{self, Flavor},
+///     serde::{SerializerBuilder, UnitVariantEncoding},
+/// };
+///
+/// #[derive(Serialize)]
+/// enum GameMode {
+///     Survival,
+///     Creative,
+/// }
+///
+/// #[derive(Serialize)]
+/// struct Player {
+///     mode: GameMode,
+/// }
+///
+/// let bytes = SerializerBuilder::new()
+///     .unit_variants(UnitVariantEncoding::Name)
+///     .serialize(&Player { mode: GameMode::Creative }, None, Flavor::Uncompressed)?;
+///
+/// let (nbt, _) = io::read_nbt(&mut bytes.as_slice(), Flavor::Uncompressed)?;
+/// assert_eq!(nbt, compound! { "mode": "Creative" });
+/// # Ok::<(), io::NbtIoError>(())
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SerializerBuilder {
+    config: SerializerConfig,
+}
+
+impl SerializerBuilder {
+    /// Returns a builder with the default representation, which is the one used by
+    /// [`serialize`](crate::serde::serialize).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how unit enum variants are encoded. This defaults to [`UnitVariantEncoding::Index`].
+    pub fn unit_variants(mut self, encoding: UnitVariantEncoding) -> Self {
+        self.config.unit_variants = encoding;
+        self
+    }
+
+    /// Sets how NaN and infinite float and double values are handled, including those in
+    /// [`NbtTag`]s. This defaults to [`NonFiniteFloats::Allow`], which matches the game, as it
+    /// writes such values without complaint.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// use quartz_nbt::{
+    ///     io::{self, Flavor, NbtIoError},
+    ///     serde::{NonFiniteFloats, SerializerBuilder},
+    /// };
+    ///
+    /// let nbt = compound! { "Motion": [0.0f64, f64::NAN, f64::NEG_INFINITY] };
+    ///
+    /// let strict = SerializerBuilder::new().non_finite_floats(NonFiniteFloats::Error);
+    /// assert!(matches!(
+    ///     strict.serialize(&nbt, None, Flavor::Uncompressed),
+    ///     Err(NbtIoError::NonFiniteFloat(value)) if value.is_nan()
+    /// ));
+    ///
+    /// let bytes = SerializerBuilder::new()
+    ///     .non_finite_floats(NonFiniteFloats::Normalize)
+    ///     .serialize(&nbt, None, Flavor::Uncompressed)?;
+    /// assert_eq!(
+    ///     io::read_nbt(&mut bytes.as_slice(), Flavor::Uncompressed)?.0,
+    ///     compound! { "Motion": [0.0f64, 0.0f64, f64::MIN] }
+    /// );
+    /// # Ok::<(), NbtIoError>(())
+    /// ```
+    ///
+    /// [`NbtTag`]: crate::NbtTag
+    pub fn non_finite_floats(mut self, handling: NonFiniteFloats) -> Self {
+        self.config.non_finite = handling;
+        self
+    }
+
+    /// Sets what the serializer reports from [`is_human_readable`], which third-party types such
+    /// as those from `uuid`, `chrono` or the standard library's `IpAddr` use to pick their
+    /// representation. This defaults to `false`.
+    ///
+    /// Human-readable types generally serialize themselves as strings, which are written as string
+    /// tags. Otherwise, they tend to use compact forms such as byte sequences or tuples, which are
+    /// written as lists or arrays, so the result must still fit NBT's constraints: sequences must be
+    /// homogenous, `Option`s cannot appear in them, and unsigned integers other than `u8` are not
+    /// supported. For instance, the compact form of a `SocketAddr` holds its port as a `u16`, so it
+    /// can only be serialized as human-readable. Map keys are always serialized as human-readable,
+    /// since NBT keys must be strings.
+    ///
+    /// Data must be read back with a [`DeserializerBuilder`] using the same setting.
+    ///
+    /// ```
+    /// # extern crate serde;
+    /// # use serde::{Deserialize, Serialize};
+    /// use quartz_nbt::{
+    ///     compound,
+    ///     io::{self, Flavor, NbtIoError},
+    ///     serde::{DeserializerBuilder, SerializerBuilder},
+    /// };
+    /// use std::net::{IpAddr, Ipv4Addr};
+    ///
+    /// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    /// struct Server {
+    ///     address: IpAddr,
+    /// }
+    ///
+    /// let server = Server {
+    ///     address: IpAddr::V4(Ipv4Addr::LOCALHOST),
+    /// };
+    /// let bytes = SerializerBuilder::new()
+    ///     .human_readable(true)
+    ///     .serialize(&server, None, Flavor::Uncompressed)?;
+    /// assert_eq!(
+    ///     io::read_nbt(&mut bytes.as_slice(), Flavor::Uncompressed)?.0,
+    ///     compound! { "address": "127.0.0.1" }
+    /// );
+    ///
+    /// let (read, _) = DeserializerBuilder::new()
+    ///     .human_readable(true)
+    ///     .deserialize::<Server>(&bytes, Flavor::Uncompressed)?;
+    /// assert_eq!(read, server);
+    /// # Ok::<(), NbtIoError>(())
+    /// ```
+    ///
+    /// [`is_human_readable`]: serde::Serializer::is_human_readable
+    /// [`DeserializerBuilder`]: crate::serde::DeserializerBuilder
+    pub fn human_readable(mut self, human_readable: bool) -> Self {
+        self.config.human_readable = human_readable;
+        self
+    }
+
+    /// Sets whether serializers built with [`build_unchecked`] check that sequences are homogenous
+    /// when debug assertions are enabled. This defaults to `false`, and has no effect in builds
+    /// without debug assertions, where nothing is checked.
+    ///
+    /// Rather than the [`NonHomogenousList`] error returned by the checked serializer, a violation
+    /// caught by these checks causes a [`NonHomogenousField`] error holding the path to the
+    /// offending element.
+    ///
+    /// ```
+    /// # extern crate serde;
+    /// # use serde::Serialize;
+    /// use quartz_nbt::{io::NbtIoError, serde::SerializerBuilder};
+    ///
+    /// #[derive(Serialize)]
+    /// #[serde(untagged)]
+    /// enum Item {
+    ///     Count(i32),
+    ///     Name(&'static str),
+    /// }
+    ///
+    /// #[derive(Serialize)]
+    /// struct Chest {
+    ///     #[serde(rename = "Items")]
+    ///     items: Vec<Item>,
+    /// }
+    ///
+    /// let chest = Chest {
+    ///     items: vec![Item::Count(1), Item::Name("stone")],
+    /// };
+    ///
+    /// let mut bytes = Vec::new();
+    /// let result = chest.serialize(
+    ///     SerializerBuilder::new()
+    ///         .debug_checks(true)
+    ///         .build_unchecked(&mut bytes, None),
+    /// );
+    /// if cfg!(debug_assertions) {
+    ///     assert!(matches!(
+    ///         result,
+    ///         Err(NbtIoError::NonHomogenousField { path, .. }) if path == "Items[1]"
+    ///     ));
+    /// }
+    /// ```
+    ///
+    /// [`build_unchecked`]: crate::serde::SerializerBuilder::build_unchecked
+    /// [`NonHomogenousList`]: crate::io::NbtIoError::NonHomogenousList
+    /// [`NonHomogenousField`]: crate::io::NbtIoError::NonHomogenousField
+    pub fn debug_checks(mut self, enabled: bool) -> Self {
+        self.config.debug_checks = enabled;
+        self
+    }
+
+    /// Sets whether the entries of maps, such as `HashMap` fields and [`NbtCompound`]s, are written
+    /// sorted by key rather than in iteration order. This defaults to `false`.
+    ///
+    /// Since the iteration order of a `HashMap` changes from one run to the next, sorting makes the
+    /// output reproducible. The entries of each map are buffered in memory until the map ends.
+    /// Struct fields are always written in the order they are declared, and are not sorted.
+    ///
+    /// ```
+    /// # extern crate serde;
+    /// # use serde::Serialize;
+    /// use quartz_nbt::{
+    ///     io::{Flavor, NbtIoError},
+    ///     serde::SerializerBuilder,
+    /// };
+    /// use std::collections::HashMap;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Scores {
+    ///     objective: &'static str,
+    ///     scores: HashMap<&'static str, i32>,
+    /// }
+    ///
+    /// let scores = Scores {
+    ///     objective: "kills",
+    ///     scores: (0 .. 64).map(|i| (["alex", "steve", "herobrine", "notch"][i % 4], i as i32)).collect(),
+    /// };
+    ///
+    /// let builder = SerializerBuilder::new().sort_maps(true);
+    /// let bytes = builder.serialize(&scores, None, Flavor::Uncompressed)?;
+    /// for _ in 0 .. 8 {
+    ///     let other = scores.scores.iter().map(|(&k, &v)| (k, v)).collect();
+    ///     let scores = Scores { scores: other, ..scores };
+    ///     assert_eq!(builder.serialize(&scores, None, Flavor::Uncompressed)?, bytes);
+    /// }
+    /// # Ok::<(), NbtIoError>(())
+    /// ```
+    ///
+    /// [`NbtCompound`]: crate::NbtCompound
+    pub fn sort_maps(mut self, sort: bool) -> Self {
+        self.config.sort_maps = sort;
+        self
+    }
+
+    /// Constructs a serializer with the given writer and root name. If no root name is specified,
+    /// then an empty string is written to the header.
+    pub fn build<'a, W: Write>(
+        self,
+        writer: &'a mut W,
+        root_name: Option<&'a str>,
+    ) -> Serializer<'a, W> {
+        SerializerImpl::new(
+            writer,
+            BorrowedPrefix::new(root_name.unwrap_or("")),
+            self.config,
+        )
+        .into_serializer()
+    }
+
+    /// Constructs an unchecked serializer with the given writer and root name. See
+    /// [`UncheckedSerializer`] for details.
+    ///
+    /// [`UncheckedSerializer`]: crate::serde::UncheckedSerializer
+    pub fn build_unchecked<'a, W: Write>(
+        self,
+        writer: &'a mut W,
+        root_name: Option<&'a str>,
+    ) -> UncheckedSerializer<'a, W> {
+        SerializerImpl::new(
+            writer,
+            BorrowedPrefix::new(root_name.unwrap_or("")),
+            self.config,
+        )
+        .into_serializer()
+    }
+
+    /// Serializes the given value as binary NBT data, returning the resulting Vec. See
+    /// [`serialize`](crate::serde::serialize) for details.
+    pub fn serialize<T: Serialize>(
+        self,
+        value: &T,
+        root_name: Option<&str>,
+        flavor: Flavor,
+    ) -> Result<Vec<u8>, NbtIoError> {
+        let mut bytes = Vec::new();
+        self.serialize_into(&mut bytes, value, root_name, flavor)?;
+        Ok(bytes)
+    }
+
+    /// Serializes the given value as binary NBT data, writing to the given writer. See
+    /// [`serialize_into`](crate::serde::serialize_into) for details.
+    pub fn serialize_into<W: Write, T: Serialize>(
+        self,
+        writer: &mut W,
+        value: &T,
+        root_name: Option<&str>,
+        flavor: Flavor,
+    ) -> Result<(), NbtIoError> {
+        io::with_encoder(writer, flavor, Compression::default(), |encoder| {
+            value.serialize(self.build(encoder, root_name))
+        })
+    }
+}
+
+/// Describes how unit enum variants are encoded by a serializer.
+///
+/// Both encodings are always accepted when deserializing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum UnitVariantEncoding {
+    /// Unit variants are written as int tags containing the variant's index, which is its position
+    /// in the enum's declaration starting from zero. This is compact, but reordering or inserting
+    /// variants changes the meaning of existing data.
+    #[default]
+    Index,
+    /// Unit variants are written as string tags containing the variant's name. This is the
+    /// encoding used by `fastnbt` and `hematite-nbt`.
+    Name,
+}
+
+/// Describes how NaN and infinite float and double values are handled by a serializer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum NonFiniteFloats {
+    /// Non-finite values are written unchanged.
+    #[default]
+    Allow,
+    /// Serialization fails with [`NbtIoError::NonFiniteFloat`] when a non-finite value is
+    /// encountered.
+    ///
+    /// [`NbtIoError::NonFiniteFloat`]: crate::io::NbtIoError::NonFiniteFloat
+    Error,
+    /// NaN values are written as zero, and infinite values as the finite value of the same sign
+    /// which is furthest from zero.
+    Normalize,
+}
+
+macro_rules! apply_non_finite {
+    ($($name:ident: $float:ty),*) => {
+        impl NonFiniteFloats {
+            $(
+                #[inline]
+                fn $name(self, value: $float) -> Result<$float, NbtIoError> {
+                    if value.is_finite() {
+                        return Ok(value);
+                    }
+
+                    match self {
+                        NonFiniteFloats::Allow => Ok(value),
+                        NonFiniteFloats::Error => Err(NbtIoError::NonFiniteFloat(value as f64)),
+                        NonFiniteFloats::Normalize =>
+                            if value.is_nan() {
+                                Ok(0.0)
+                            } else if value > 0.0 {
+                                Ok(<$float>::MAX)
+                            } else {
+                                Ok(<$float>::MIN)
+                            },
+                    }
+                }
+            )*
+        }
+    };
+}
+
+apply_non_finite!(apply_f32: f32, apply_f64: f64);
+
+// The options shared by every serializer in a tree, set through `SerializerBuilder`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SerializerConfig {
+    unit_variants: UnitVariantEncoding,
+    non_finite: NonFiniteFloats,
+    debug_checks: bool,
+    human_readable: bool,
+    sort_maps: bool,
+}
+
 pub struct SerializerImpl<'a, W, C> {
     writer: &'a mut W,
     root_name: BorrowedPrefix<&'a str>,
+    config: SerializerConfig,
     _phantom: PhantomData<C>,
 }
 
 impl<'a, W: Write, C: TypeChecker> SerializerImpl<'a, W, C> {
-    fn new(writer: &'a mut W, root_name: BorrowedPrefix<&'a str>) -> Self {
+    fn new(
+        writer: &'a mut W,
+        root_name: BorrowedPrefix<&'a str>,
+        config: SerializerConfig,
+    ) -> Self {
         SerializerImpl {
             writer,
             root_name,
+            config,
             _phantom: PhantomData,
         }
     }
@@ -77,6 +483,32 @@ impl<'a, W: Write, C: TypeChecker> DefaultSerializer for SerializerImpl<'a, W, C
         NbtIoError::MissingRootTag
     }
 
+    // None and unit types are written as empty compounds so that generic code wrapping them in a
+    // root tag does not need to special-case them
+
+    #[inline]
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        self.serialize_unit()
+    }
+
+    #[inline]
+    fn serialize_some<T: ?Sized>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where T: Serialize {
+        value.serialize(self.into_serializer())
+    }
+
+    #[inline]
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        self.root_name.write(self.writer, 0xA)?;
+        raw::write_u8(self.writer, raw::id_for_tag(None))?;
+        Ok(())
+    }
+
+    #[inline]
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
+        self.serialize_unit()
+    }
+
     #[inline]
     fn serialize_newtype_variant<T: ?Sized>(
         self,
@@ -103,13 +535,13 @@ impl<'a, W: Write, C: TypeChecker> DefaultSerializer for SerializerImpl<'a, W, C
     ) -> Result<Self::SerializeTupleVariant, Self::Error> {
         self.root_name.write(self.writer, 0xA)?;
         let prefix = BorrowedPrefix::new(variant);
-        SerializeCompoundEntry::new(self.writer, prefix).serialize_seq(Some(len))
+        SerializeCompoundEntry::new(self.writer, prefix, self.config).serialize_seq(Some(len))
     }
 
     #[inline]
     fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
         self.root_name.write(self.writer, 0xA)?;
-        Ok(SerializeCompound::new(self.writer))
+        Ok(SerializeCompound::new(self.writer, self.config))
     }
 
     #[inline]
@@ -133,12 +565,12 @@ impl<'a, W: Write, C: TypeChecker> DefaultSerializer for SerializerImpl<'a, W, C
         raw::write_u8(self.writer, 0xA)?;
         raw::write_string(self.writer, variant)?;
         // The extra closing tag is added by the SerializeStructVariant impl
-        Ok(SerializeCompound::new(self.writer))
+        Ok(SerializeCompound::new(self.writer, self.config))
     }
 
     #[inline]
     fn is_human_readable(&self) -> bool {
-        false
+        self.config.human_readable
     }
 }
 
@@ -175,7 +607,7 @@ where W: Write
 
     #[inline]
     fn serialize_bytes(self, value: &[u8]) -> Result<Self::Ok, Self::Error> {
-        raw::write_i32(self.writer, value.len() as i32)?;
+        raw::write_len(self.writer, value.len())?;
         self.writer.write_all(value)?;
         Ok(())
     }
@@ -183,7 +615,7 @@ where W: Write
     #[inline]
     fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
         let len = len.ok_or(NbtIoError::MissingLength)?;
-        raw::write_i32(self.writer, len as i32)?;
+        raw::write_len(self.writer, len)?;
         Ok(self)
     }
 
@@ -212,7 +644,13 @@ where W: Write
     fn serialize_element<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
     where T: Serialize {
         value.serialize(
-            SerializeListElement::new(self.writer, NoPrefix, &UNCHECKED).into_serializer(),
+            SerializeListElement::new(
+                self.writer,
+                NoPrefix,
+                &UNCHECKED,
+                SerializerConfig::default(),
+            )
+            .into_serializer(),
         )
     }
 
@@ -258,10 +696,66 @@ where W: Write
     }
 }
 
+// Writes the bytes of a RawNbt, which start with the ID of the tag being written
+struct SerializeRaw<'a, W, P, C> {
+    writer: &'a mut W,
+    prefix: P,
+    type_checker: &'a C,
+}
+
+impl<'a, W, P, C> SerializeRaw<'a, W, P, C>
+where
+    W: Write,
+    P: Prefix,
+    C: TypeChecker,
+{
+    #[inline]
+    fn new(writer: &'a mut W, prefix: P, type_checker: &'a C) -> Self {
+        SerializeRaw {
+            writer,
+            prefix,
+            type_checker,
+        }
+    }
+}
+
+impl<'a, W, P, C> DefaultSerializer for SerializeRaw<'a, W, P, C>
+where
+    W: Write,
+    P: Prefix,
+    C: TypeChecker,
+{
+    type Error = NbtIoError;
+    type Ok = ();
+    type SerializeMap = Impossible<Self::Ok, Self::Error>;
+    type SerializeSeq = Impossible<Self::Ok, Self::Error>;
+    type SerializeStruct = Impossible<Self::Ok, Self::Error>;
+    type SerializeStructVariant = Impossible<Self::Ok, Self::Error>;
+    type SerializeTuple = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleStruct = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleVariant = Impossible<Self::Ok, Self::Error>;
+
+    #[cold]
+    fn unimplemented(self, _ty: &'static str) -> Self::Error {
+        panic!("RawNbt wrapper incorrectly used on non-byte type")
+    }
+
+    #[inline]
+    fn serialize_bytes(self, value: &[u8]) -> Result<Self::Ok, Self::Error> {
+        let (&tag_id, payload) = value.split_first().ok_or(NbtIoError::InvalidTagId(0x0))?;
+        self.type_checker.verify(tag_id)?;
+        self.prefix.write(self.writer, tag_id)?;
+        self.writer.write_all(payload)?;
+        Ok(())
+    }
+}
+
 pub struct SerializeList<'a, W, C> {
     writer: &'a mut W,
     length: Option<i32>,
+    index: usize,
     type_checker: C,
+    config: SerializerConfig,
 }
 
 impl<'a, W, C> SerializeList<'a, W, C>
@@ -269,11 +763,13 @@ where
     W: Write,
     C: TypeChecker,
 {
-    fn new(writer: &'a mut W, length: i32) -> Result<Self, NbtIoError> {
+    fn new(writer: &'a mut W, length: i32, config: SerializerConfig) -> Result<Self, NbtIoError> {
         Ok(SerializeList {
             writer,
             length: Some(length),
-            type_checker: C::new(),
+            index: 0,
+            type_checker: C::new(&config),
+            config,
         })
     }
 }
@@ -289,9 +785,11 @@ where
     #[inline]
     fn serialize_element<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
     where T: Serialize {
+        let index = self.index;
+        self.index += 1;
         match self.length.take() {
             None => value.serialize(
-                SerializeListElement::new(self.writer, NoPrefix, &self.type_checker)
+                SerializeListElement::new(self.writer, NoPrefix, &self.type_checker, self.config)
                     .into_serializer(),
             ),
             Some(length) => value.serialize(
@@ -299,10 +797,12 @@ where
                     self.writer,
                     LengthPrefix::new(length),
                     &self.type_checker,
+                    self.config,
                 )
                 .into_serializer(),
             ),
         }
+        .map_err(|error| locate(error, |path| push_index(path, index)))
     }
 
     #[inline]
@@ -387,6 +887,7 @@ struct SerializeListElement<'a, W, P, C> {
     writer: &'a mut W,
     prefix: P,
     type_checker: &'a C,
+    config: SerializerConfig,
 }
 
 impl<'a, W, P, C> SerializeListElement<'a, W, P, C>
@@ -396,11 +897,17 @@ where
     C: TypeChecker,
 {
     #[inline]
-    fn new(writer: &'a mut W, inner_prefix: P, inner_type_checker: &'a C) -> Self {
+    fn new(
+        writer: &'a mut W,
+        inner_prefix: P,
+        inner_type_checker: &'a C,
+        config: SerializerConfig,
+    ) -> Self {
         SerializeListElement {
             writer,
             prefix: inner_prefix,
             type_checker: inner_type_checker,
+            config,
         }
     }
 }
@@ -473,6 +980,7 @@ where
 
     #[inline]
     fn serialize_f32(self, value: f32) -> Result<Self::Ok, Self::Error> {
+        let value = self.config.non_finite.apply_f32(value)?;
         self.type_checker.verify(0x5)?;
         self.prefix.write(self.writer, 0x5)?;
         raw::write_f32(self.writer, value)?;
@@ -481,6 +989,7 @@ where
 
     #[inline]
     fn serialize_f64(self, value: f64) -> Result<Self::Ok, Self::Error> {
+        let value = self.config.non_finite.apply_f64(value)?;
         self.type_checker.verify(0x6)?;
         self.prefix.write(self.writer, 0x6)?;
         raw::write_f64(self.writer, value)?;
@@ -499,7 +1008,7 @@ where
     fn serialize_bytes(self, value: &[u8]) -> Result<Self::Ok, Self::Error> {
         self.type_checker.verify(0x7)?;
         self.prefix.write(self.writer, 0x7)?;
-        raw::write_i32(self.writer, value.len() as i32)?;
+        raw::write_len(self.writer, value.len())?;
         self.writer.write_all(value)?;
         Ok(())
     }
@@ -530,11 +1039,20 @@ where
         self,
         _name: &'static str,
         variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
     ) -> Result<Self::Ok, Self::Error> {
-        self.type_checker.verify(0x3)?;
-        self.prefix.write(self.writer, 0x3)?;
-        raw::write_i32(self.writer, variant_index as i32)?;
+        match self.config.unit_variants {
+            UnitVariantEncoding::Index => {
+                self.type_checker.verify(0x3)?;
+                self.prefix.write(self.writer, 0x3)?;
+                raw::write_i32(self.writer, variant_index as i32)?;
+            }
+            UnitVariantEncoding::Name => {
+                self.type_checker.verify(0x8)?;
+                self.prefix.write(self.writer, 0x8)?;
+                raw::write_string(self.writer, variant)?;
+            }
+        }
         Ok(())
     }
 
@@ -547,6 +1065,14 @@ where
     where
         T: Serialize,
     {
+        if let Some(element_id) = empty_list_element_id(name) {
+            self.type_checker.verify(0x9)?;
+            self.prefix.write(self.writer, 0x9)?;
+            raw::write_u8(self.writer, element_id)?;
+            raw::write_i32(self.writer, 0)?;
+            return Ok(());
+        }
+
         match name {
             BYTE_ARRAY_NICHE => {
                 self.type_checker.verify(0x7)?;
@@ -560,6 +1086,11 @@ where
                 self.type_checker.verify(0xC)?;
                 self.prefix.write(self.writer, 0xC)?;
             }
+            RAW_NBT_NICHE =>
+                return value.serialize(
+                    SerializeRaw::new(self.writer, self.prefix, self.type_checker)
+                        .into_serializer(),
+                ),
             _ => return value.serialize(self.into_serializer()),
         }
         value.serialize(SerializeArray::new(self.writer).into_serializer())
@@ -579,8 +1110,12 @@ where
         self.type_checker.verify(0xA)?;
         self.prefix.write(self.writer, 0xA)?;
         value.serialize(
-            SerializeCompoundEntry::<_, C, _>::new(self.writer, BorrowedPrefix::new(variant))
-                .into_serializer(),
+            SerializeCompoundEntry::<_, C, _>::new(
+                self.writer,
+                BorrowedPrefix::new(variant),
+                self.config,
+            )
+            .into_serializer(),
         )?;
         raw::write_u8(self.writer, raw::id_for_tag(None))?;
         Ok(())
@@ -592,7 +1127,7 @@ where
         self.prefix.write(self.writer, 0x9)?;
         let len = len.ok_or(NbtIoError::MissingLength)?;
 
-        SerializeList::new(self.writer, len as i32)
+        SerializeList::new(self.writer, raw::checked_len(len)?, self.config)
     }
 
     #[inline]
@@ -625,14 +1160,14 @@ where
 
         // Write the compound
         let prefix = BorrowedPrefix::new(variant);
-        SerializeCompoundEntry::new(self.writer, prefix).serialize_seq(Some(len))
+        SerializeCompoundEntry::new(self.writer, prefix, self.config).serialize_seq(Some(len))
     }
 
     #[inline]
     fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
         self.type_checker.verify(0xA)?;
         self.prefix.write(self.writer, 0xA)?;
-        Ok(SerializeCompound::new(self.writer))
+        Ok(SerializeCompound::new(self.writer, self.config))
     }
 
     #[inline]
@@ -657,30 +1192,56 @@ where
         raw::write_u8(self.writer, 0xA)?;
         raw::write_string(self.writer, variant)?;
         // The extra closing tag is added by the SerializeStructVariant impl
-        Ok(SerializeCompound::new(self.writer))
+        Ok(SerializeCompound::new(self.writer, self.config))
     }
 
     #[inline]
     fn is_human_readable(&self) -> bool {
-        false
+        self.config.human_readable
     }
 }
 
 pub struct SerializeCompound<'a, W, C> {
     writer: &'a mut W,
     key: Option<Box<[u8]>>,
+    // The encoded entries of a map and their keys, if maps are sorted
+    entries: Vec<(String, Vec<u8>)>,
+    config: SerializerConfig,
     _phantom: PhantomData<C>,
 }
 
 impl<'a, W: Write, C: TypeChecker> SerializeCompound<'a, W, C> {
     #[inline]
-    fn new(writer: &'a mut W) -> Self {
+    fn new(writer: &'a mut W, config: SerializerConfig) -> Self {
         SerializeCompound {
             writer,
             key: None,
+            entries: Vec::new(),
+            config,
             _phantom: PhantomData,
         }
     }
+
+    // Encodes an entry of a map into a buffer, to be written once all of its entries are known
+    fn buffer_entry<T: ?Sized + Serialize>(
+        &mut self,
+        key: &[u8],
+        value: &T,
+    ) -> Result<(), NbtIoError> {
+        let mut entry = Vec::new();
+        value
+            .serialize(
+                SerializeCompoundEntry::<_, C, _>::new(
+                    &mut entry,
+                    RawPrefix::new(key),
+                    self.config,
+                )
+                .into_serializer(),
+            )
+            .map_err(|error| locate(error, |path| push_raw_key(path, key)))?;
+        self.entries.push((raw::read_string(&mut &*key)?, entry));
+        Ok(())
+    }
 }
 
 impl<'a, W: Write, C: TypeChecker> SerializeMap for SerializeCompound<'a, W, C> {
@@ -703,10 +1264,17 @@ impl<'a, W: Write, C: TypeChecker> SerializeMap for SerializeCompound<'a, W, C>
             .key
             .take()
             .expect("serialize_value called before key was serialized.");
-        let prefix = RawPrefix::new(key);
-        value.serialize(
-            SerializeCompoundEntry::<_, C, _>::new(self.writer, prefix).into_serializer(),
-        )
+        if self.config.sort_maps {
+            return self.buffer_entry(&key, value);
+        }
+
+        let prefix = RawPrefix::new(&key);
+        value
+            .serialize(
+                SerializeCompoundEntry::<_, C, _>::new(self.writer, prefix, self.config)
+                    .into_serializer(),
+            )
+            .map_err(|error| locate(error, |path| push_raw_key(path, &key)))
     }
 
     #[inline]
@@ -719,14 +1287,37 @@ impl<'a, W: Write, C: TypeChecker> SerializeMap for SerializeCompound<'a, W, C>
         K: Serialize,
         V: Serialize,
     {
+        if self.config.sort_maps {
+            self.serialize_key(key)?;
+            return self.serialize_value(value);
+        }
+
         let prefix = BorrowedPrefix::new(key);
-        value.serialize(
-            SerializeCompoundEntry::<_, C, _>::new(self.writer, prefix).into_serializer(),
-        )
-    }
+        value
+            .serialize(
+                SerializeCompoundEntry::<_, C, _>::new(self.writer, prefix, self.config)
+                    .into_serializer(),
+            )
+            .map_err(|error| {
+                locate(error, |path| {
+                    let mut raw = Vec::new();
+                    if key
+                        .serialize(SerializeKey::new(&mut raw).into_serializer())
+                        .is_ok()
+                    {
+                        push_raw_key(path, &raw);
+                    }
+                })
+            })
+    }
+
+    #[inline]
+    fn end(mut self) -> Result<Self::Ok, Self::Error> {
+        self.entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        for (_, entry) in &self.entries {
+            self.writer.write_all(entry)?;
+        }
 
-    #[inline]
-    fn end(self) -> Result<Self::Ok, Self::Error> {
         raw::write_u8(self.writer, raw::id_for_tag(None))?;
         Ok(())
     }
@@ -746,9 +1337,12 @@ impl<'a, W: Write, C: TypeChecker> SerializeStruct for SerializeCompound<'a, W,
         T: Serialize,
     {
         let prefix = BorrowedPrefix::new(key);
-        value.serialize(
-            SerializeCompoundEntry::<_, C, _>::new(self.writer, prefix).into_serializer(),
-        )
+        value
+            .serialize(
+                SerializeCompoundEntry::<_, C, _>::new(self.writer, prefix, self.config)
+                    .into_serializer(),
+            )
+            .map_err(|error| locate(error, |path| push_key(path, key)))
     }
 
     #[inline]
@@ -785,15 +1379,17 @@ impl<'a, W: Write, C: TypeChecker> SerializeStructVariant for SerializeCompound<
 struct SerializeCompoundEntry<'a, W, C, P> {
     writer: &'a mut W,
     prefix: P,
+    config: SerializerConfig,
     _phantom: PhantomData<C>,
 }
 
 impl<'a, W: Write, C: TypeChecker, P: Prefix> SerializeCompoundEntry<'a, W, C, P> {
     #[inline]
-    fn new(writer: &'a mut W, prefix: P) -> Self {
+    fn new(writer: &'a mut W, prefix: P, config: SerializerConfig) -> Self {
         SerializeCompoundEntry {
             writer,
             prefix,
+            config,
             _phantom: PhantomData,
         }
     }
@@ -862,6 +1458,7 @@ where
 
     #[inline]
     fn serialize_f32(self, value: f32) -> Result<Self::Ok, Self::Error> {
+        let value = self.config.non_finite.apply_f32(value)?;
         self.prefix.write(self.writer, 0x5)?;
         raw::write_f32(self.writer, value)?;
         Ok(())
@@ -869,6 +1466,7 @@ where
 
     #[inline]
     fn serialize_f64(self, value: f64) -> Result<Self::Ok, Self::Error> {
+        let value = self.config.non_finite.apply_f64(value)?;
         self.prefix.write(self.writer, 0x6)?;
         raw::write_f64(self.writer, value)?;
         Ok(())
@@ -884,7 +1482,7 @@ where
     #[inline]
     fn serialize_bytes(self, value: &[u8]) -> Result<Self::Ok, Self::Error> {
         self.prefix.write(self.writer, 0x7)?;
-        raw::write_i32(self.writer, value.len() as i32)?;
+        raw::write_len(self.writer, value.len())?;
         self.writer.write_all(value)?;
         Ok(())
     }
@@ -915,10 +1513,18 @@ where
         self,
         _name: &'static str,
         variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
     ) -> Result<Self::Ok, Self::Error> {
-        self.prefix.write(self.writer, 0x3)?;
-        raw::write_i32(self.writer, variant_index as i32)?;
+        match self.config.unit_variants {
+            UnitVariantEncoding::Index => {
+                self.prefix.write(self.writer, 0x3)?;
+                raw::write_i32(self.writer, variant_index as i32)?;
+            }
+            UnitVariantEncoding::Name => {
+                self.prefix.write(self.writer, 0x8)?;
+                raw::write_string(self.writer, variant)?;
+            }
+        }
         Ok(())
     }
 
@@ -931,6 +1537,13 @@ where
     where
         T: Serialize,
     {
+        if let Some(element_id) = empty_list_element_id(name) {
+            self.prefix.write(self.writer, 0x9)?;
+            raw::write_u8(self.writer, element_id)?;
+            raw::write_i32(self.writer, 0)?;
+            return Ok(());
+        }
+
         match name {
             BYTE_ARRAY_NICHE => {
                 self.prefix.write(self.writer, 0x7)?;
@@ -941,6 +1554,10 @@ where
             LONG_ARRAY_NICHE => {
                 self.prefix.write(self.writer, 0xC)?;
             }
+            RAW_NBT_NICHE =>
+                return value.serialize(
+                    SerializeRaw::new(self.writer, self.prefix, &UNCHECKED).into_serializer(),
+                ),
             _ => return value.serialize(self.into_serializer()),
         }
         value.serialize(SerializeArray::new(self.writer).into_serializer())
@@ -959,8 +1576,12 @@ where
     {
         self.prefix.write(self.writer, 0xA)?;
         value.serialize(
-            SerializeCompoundEntry::<_, C, _>::new(self.writer, BorrowedPrefix::new(variant))
-                .into_serializer(),
+            SerializeCompoundEntry::<_, C, _>::new(
+                self.writer,
+                BorrowedPrefix::new(variant),
+                self.config,
+            )
+            .into_serializer(),
         )?;
         raw::write_u8(self.writer, raw::id_for_tag(None))?;
         Ok(())
@@ -971,7 +1592,7 @@ where
         self.prefix.write(self.writer, 0x9)?;
         let len = len.ok_or(NbtIoError::MissingLength)?;
 
-        SerializeList::new(self.writer, len as i32)
+        SerializeList::new(self.writer, raw::checked_len(len)?, self.config)
     }
 
     #[inline]
@@ -998,13 +1619,13 @@ where
     ) -> Result<Self::SerializeTupleVariant, Self::Error> {
         self.prefix.write(self.writer, 0xA)?;
         let prefix = BorrowedPrefix::new(variant);
-        SerializeCompoundEntry::new(self.writer, prefix).serialize_seq(Some(len))
+        SerializeCompoundEntry::new(self.writer, prefix, self.config).serialize_seq(Some(len))
     }
 
     #[inline]
     fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
         self.prefix.write(self.writer, 0xA)?;
-        Ok(SerializeCompound::new(self.writer))
+        Ok(SerializeCompound::new(self.writer, self.config))
     }
 
     #[inline]
@@ -1028,12 +1649,12 @@ where
         raw::write_u8(self.writer, 0xA)?;
         raw::write_string(self.writer, variant)?;
         // The extra closing tag is added by the SerializeStructVariant impl
-        Ok(SerializeCompound::new(self.writer))
+        Ok(SerializeCompound::new(self.writer, self.config))
     }
 
     #[inline]
     fn is_human_readable(&self) -> bool {
-        false
+        self.config.human_readable
     }
 }
 
@@ -1072,7 +1693,7 @@ impl<'a, W: Write> DefaultSerializer for SerializeKey<'a, W> {
 }
 
 pub trait TypeChecker: Sized {
-    fn new() -> Self;
+    fn new(config: &SerializerConfig) -> Self;
 
     fn verify(&self, tag_id: u8) -> Result<(), NbtIoError>;
 }
@@ -1083,7 +1704,7 @@ const UNCHECKED: Unchecked = Unchecked;
 
 impl TypeChecker for Unchecked {
     #[inline]
-    fn new() -> Self {
+    fn new(_config: &SerializerConfig) -> Self {
         Unchecked
     }
 
@@ -1099,7 +1720,7 @@ pub struct Homogenous {
 
 impl TypeChecker for Homogenous {
     #[inline]
-    fn new() -> Self {
+    fn new(_config: &SerializerConfig) -> Self {
         Homogenous {
             id: Cell::new(None),
         }
@@ -1125,6 +1746,73 @@ impl TypeChecker for Homogenous {
     }
 }
 
+// Checks for homogeneity only in debug builds, and only if enabled through the builder
+pub struct DebugHomogenous {
+    checker: Option<Homogenous>,
+}
+
+impl TypeChecker for DebugHomogenous {
+    #[inline]
+    fn new(config: &SerializerConfig) -> Self {
+        DebugHomogenous {
+            checker: (cfg!(debug_assertions) && config.debug_checks)
+                .then(|| Homogenous::new(config)),
+        }
+    }
+
+    #[inline]
+    fn verify(&self, tag_id: u8) -> Result<(), NbtIoError> {
+        match &self.checker {
+            Some(checker) => checker.verify(tag_id).map_err(|error| match error {
+                NbtIoError::NonHomogenousList {
+                    list_type,
+                    encountered_type,
+                } => NbtIoError::NonHomogenousField {
+                    path: String::new(),
+                    list_type,
+                    encountered_type,
+                },
+                error => error,
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+// Adds a segment to the front of the path held by a NonHomogenousField error as it propagates out
+// of the list or compound containing the offending element
+#[cold]
+fn locate<F>(error: NbtIoError, push_segment: F) -> NbtIoError
+where F: FnOnce(&mut String) {
+    match error {
+        NbtIoError::NonHomogenousField {
+            path,
+            list_type,
+            encountered_type,
+        } => {
+            let mut located = String::new();
+            push_segment(&mut located);
+            if !path.is_empty() && !path.starts_with('[') {
+                located.push('.');
+            }
+            located.push_str(&path);
+            NbtIoError::NonHomogenousField {
+                path: located,
+                list_type,
+                encountered_type,
+            }
+        }
+        error => error,
+    }
+}
+
+// Pushes a key which has already been encoded by SerializeKey
+fn push_raw_key(path: &mut String, mut raw: &[u8]) {
+    if let Ok(key) = raw::read_string(&mut raw) {
+        push_key(path, &key);
+    }
+}
+
 pub trait Prefix: Sized {
     fn write_raw<W: Write>(self, writer: &mut W) -> Result<(), NbtIoError>;
 
@@ -1187,21 +1875,21 @@ impl<K: Serialize> Prefix for BorrowedPrefix<K> {
     }
 }
 
-struct RawPrefix {
-    raw: Box<[u8]>,
+struct RawPrefix<'a> {
+    raw: &'a [u8],
 }
 
-impl RawPrefix {
+impl<'a> RawPrefix<'a> {
     #[inline]
-    fn new(raw: Box<[u8]>) -> Self {
+    fn new(raw: &'a [u8]) -> Self {
         RawPrefix { raw }
     }
 }
 
-impl Prefix for RawPrefix {
+impl Prefix for RawPrefix<'_> {
     #[inline]
     fn write_raw<W: Write>(self, writer: &mut W) -> Result<(), NbtIoError> {
-        writer.write_all(&self.raw)?;
+        writer.write_all(self.raw)?;
         Ok(())
     }
 }
@@ -1,12 +1,23 @@
-use crate::tag::{NbtCompound, NbtList, NbtTag};
+use crate::{
+    io::{self, Flavor, NbtIoError, MAX_DEPTH},
+    path::Step,
+    raw,
+    tag::{NbtCompound, NbtList, NbtTag, NbtTagKind},
+    NbtStructureError,
+};
+use flate2::read::{DeflateDecoder, GzDecoder, ZlibDecoder};
 use std::{
     borrow::Cow,
     char,
+    collections::HashMap,
     error::Error,
     fmt::{self, Debug, Display, Formatter},
+    io::{BufRead, Read, Seek, SeekFrom, Write},
     iter::Peekable,
     mem,
-    str::{self, CharIndices},
+    num::IntErrorKind,
+    ops::Range,
+    str::{self, CharIndices, FromStr},
 };
 
 /// Parses the given string into an NBT tag compound.
@@ -39,212 +50,2186 @@ use std::{
 /// assert!(result.is_err());
 /// assert_eq!(
 ///     result.unwrap_err().to_string(),
-///     "Unexpected token at column 9 near '{garbage:;', expected value"
+///     "Unexpected token at 1:10 near '{garbage:;', expected value"
 /// );
 /// ```
 pub fn parse<T: AsRef<str> + ?Sized>(string_nbt: &T) -> Result<NbtCompound, SnbtError> {
     parse_and_size(string_nbt).map(|(tag, _)| tag)
 }
 
-/// Parses the given string just like [`parse`], but also returns the amount of parsed characters.
-///
+/// Parses the given string just like [`parse`], but also returns the number of bytes of the
+/// input which were parsed.
 pub fn parse_and_size<T: AsRef<str> + ?Sized>(
     string_nbt: &T,
 ) -> Result<(NbtCompound, usize), SnbtError> {
-    let mut tokens = Lexer::new(string_nbt.as_ref());
-    let open_curly = tokens.assert_next(Token::OpenCurly)?;
-    parse_compound_tag(&mut tokens, &open_curly)
+    parse_and_size_with(string_nbt.as_ref(), ParseOptions::new())
 }
 
-// Parses the next value in the token stream
-fn parse_next_value(
-    tokens: &mut Lexer<'_>,
-    delimiter: Option<fn(char) -> bool>,
-) -> Result<NbtTag, SnbtError> {
-    let token = tokens.next(delimiter).transpose()?;
-    parse_value(tokens, token)
+/// Parses the given string just like [`parse`], but with the given options.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::snbt::{self, ParseOptions};
+///
+/// const SNBT: &str = "{Pos: [1, 64.5, 2]}";
+/// assert!(snbt::parse(SNBT).is_err());
+///
+/// let options = ParseOptions::new().widen_numeric_lists(true);
+/// assert_eq!(
+///     snbt::parse_with(SNBT, options).unwrap(),
+///     compound! { "Pos": [1.0f64, 64.5f64, 2.0f64] }
+/// );
+/// ```
+pub fn parse_with<T: AsRef<str> + ?Sized>(
+    string_nbt: &T,
+    options: ParseOptions,
+) -> Result<NbtCompound, SnbtError> {
+    parse_and_size_with(string_nbt.as_ref(), options).map(|(tag, _)| tag)
 }
 
-// Parses a token into a value
-fn parse_value(tokens: &mut Lexer<'_>, token: Option<TokenData>) -> Result<NbtTag, SnbtError> {
-    match token {
-        // Open curly brace indicates a compound tag is present
-        #[rustfmt::skip]
-        Some(
-            td @ TokenData {
-                token: Token::OpenCurly,
-                ..
-            },
-        ) => parse_compound_tag(tokens, &td).map(|(tag, _)| tag.into()),
+/// Parses the given string just like [`parse_with`], but also returns every key which appeared
+/// more than once in the same compound, in the order the repeated keys appear. Duplicate keys are
+/// otherwise silently resolved by keeping the last value, as the game does, which can hide
+/// mistakes in hand-edited files. To reject duplicate keys instead, see
+/// [`ParseOptions::deny_duplicate_keys`].
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::snbt::{self, ParseOptions, Position};
+///
+/// const SNBT: &str = "{\n    Name: Steve,\n    name: Alex,\n    Name: Herobrine\n}";
+/// let (compound, duplicates) = snbt::parse_with_duplicate_keys(SNBT, ParseOptions::new())?;
+/// assert_eq!(compound, compound! { "Name": "Herobrine", "name": "Alex" });
+///
+/// assert_eq!(duplicates.len(), 1);
+/// assert_eq!(duplicates[0].key(), "Name");
+/// assert_eq!(&SNBT[duplicates[0].first()], "Name");
+/// assert_eq!(
+///     Position::from_offset(SNBT, duplicates[0].second().start),
+///     Position { line: 4, column: 5 }
+/// );
+/// # Ok::<_, snbt::SnbtError>(())
+/// ```
+///
+/// [`parse_with`]: crate::snbt::parse_with
+/// [`ParseOptions::deny_duplicate_keys`]: crate::snbt::ParseOptions::deny_duplicate_keys
+pub fn parse_with_duplicate_keys<T: AsRef<str> + ?Sized>(
+    string_nbt: &T,
+    options: ParseOptions,
+) -> Result<(NbtCompound, Vec<DuplicateKey>), SnbtError> {
+    let mut tokens = Lexer::new(string_nbt.as_ref(), options);
+    tokens.duplicate_keys = Some(Vec::new());
+    let (compound, _) = parse_root(&mut tokens)?;
+    Ok((compound, tokens.duplicate_keys.unwrap_or_default()))
+}
 
-        // Open square brace indicates that some kind of list is present
-        #[rustfmt::skip]
-        Some(
-            td @ TokenData {
-                token: Token::OpenSquare,
-                ..
+/// A key which appeared more than once in the same compound, as reported by
+/// [`parse_with_duplicate_keys`]. Keys are compared exactly as the game compares them, so keys
+/// which only differ in case, such as `Name` and `name`, are not duplicates.
+///
+/// The spans are byte ranges into the input covering each occurrence of the key as it was
+/// written, including any quotes. Use [`Position::from_offset`] to convert them into lines and
+/// columns.
+///
+/// [`parse_with_duplicate_keys`]: crate::snbt::parse_with_duplicate_keys
+/// [`Position::from_offset`]: crate::snbt::Position::from_offset
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DuplicateKey {
+    key: String,
+    first: Range<usize>,
+    second: Range<usize>,
+}
+
+impl DuplicateKey {
+    /// Returns the repeated key, with any escape sequences resolved.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Returns the span of the previous occurrence of the key, whose value was discarded.
+    pub fn first(&self) -> Range<usize> {
+        self.first.clone()
+    }
+
+    /// Returns the span of the repeated occurrence of the key, whose value was kept unless the key
+    /// appears again later.
+    pub fn second(&self) -> Range<usize> {
+        self.second.clone()
+    }
+}
+
+fn parse_and_size_with(
+    string_nbt: &str,
+    options: ParseOptions,
+) -> Result<(NbtCompound, usize), SnbtError> {
+    parse_root(&mut Lexer::new(string_nbt, options))
+}
+
+fn parse_root(tokens: &mut Lexer<'_>) -> Result<(NbtCompound, usize), SnbtError> {
+    let string_nbt = tokens.raw;
+    #[cfg(feature = "tracing")]
+    let span = crate::trace::parse_span(string_nbt.len());
+
+    let open_curly = tokens.assert_next(Token::OpenCurly)?;
+    let result = tokens.nested(&open_curly, |tokens| {
+        parse_compound_tag(tokens, &open_curly)
+    });
+
+    // The game rejects anything but whitespace after the root compound
+    let result = match result {
+        Ok((_, size)) if tokens.options.vanilla_strict =>
+            match string_nbt[size ..].find(|ch: char| !ch.is_ascii_whitespace()) {
+                Some(offset) => Err(SnbtError::unexpected_token_at(
+                    string_nbt,
+                    size + offset,
+                    1,
+                    "end of input",
+                )),
+                None => result,
             },
-        ) => parse_list(tokens, &td),
+        result => result,
+    };
 
-        // Could be a value token or delimiter token
-        Some(td) => match td.into_tag() {
-            Ok(tag) => Ok(tag),
-            Err(td) => Err(SnbtError::unexpected_token(tokens.raw, Some(&td), "value")),
-        },
+    #[cfg(feature = "tracing")]
+    if let Ok((compound, _)) = &result {
+        crate::trace::record_shape(&span, compound);
+    }
+    result
+}
 
-        // We expected a value but ran out of data
-        None => Err(SnbtError::unexpected_eos("value")),
+/// Reads the given flavor of SNBT data from the given reader and parses it into an NBT tag
+/// compound. Compressed input, such as a `.snbt.gz` file, is decompressed as it is read. The
+/// decompressed data must be valid UTF-8.
+///
+/// # Examples
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use flate2::{write::GzEncoder, Compression};
+/// use quartz_nbt::{io::Flavor, snbt};
+/// use std::io::Write;
+///
+/// let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+/// encoder.write_all(b"{name: \"Steve\", level: 30}").unwrap();
+/// let compressed = encoder.finish().unwrap();
+///
+/// let compound = snbt::parse_reader(&mut compressed.as_slice(), Flavor::GzCompressed).unwrap();
+/// assert_eq!(compound, compound! { "name": "Steve", "level": 30 });
+/// ```
+pub fn parse_reader<R: Read>(reader: &mut R, flavor: Flavor) -> Result<NbtCompound, NbtIoError> {
+    let mut string_nbt = String::new();
+    match flavor {
+        Flavor::Uncompressed => reader.read_to_string(&mut string_nbt)?,
+        Flavor::ZlibCompressed | Flavor::ZlibCompressedWith(_) =>
+            ZlibDecoder::new(reader).read_to_string(&mut string_nbt)?,
+        Flavor::GzCompressed | Flavor::GzCompressedWith(_) =>
+            GzDecoder::new(reader).read_to_string(&mut string_nbt)?,
+        Flavor::RawDeflate | Flavor::RawDeflateWith(_) =>
+            DeflateDecoder::new(reader).read_to_string(&mut string_nbt)?,
+    };
+
+    parse(&string_nbt).map_err(NbtIoError::from)
+}
+
+/// Returns an iterator which parses each line of the given reader as a separate SNBT compound, as
+/// found in logs and test fixtures which store one document per line.
+///
+/// Lines which are empty, only contain whitespace, or start with `#` or `//` after any leading
+/// whitespace are skipped. Every other line must hold exactly one compound, optionally surrounded
+/// by whitespace. Errors are reported with the line number of the offending line, and parsing
+/// continues with the next line, so a malformed line does not hide the rest of the input. An I/O
+/// error, including a line which is not valid UTF-8, ends the iteration.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::snbt;
+///
+/// let log = "\
+/// ## Player data dumped on join
+/// {name: Steve, level: 30}
+///
+/// {name: Alex, level: 12b
+/// // Trailing comments are fine too
+/// {name: \"Herobrine\", level: -1}
+/// ";
+///
+/// let mut players = snbt::parse_lines(log.as_bytes());
+/// assert_eq!(players.next().unwrap()?, compound! { "name": "Steve", "level": 30 });
+/// assert_eq!(
+///     players.next().unwrap().unwrap_err().to_string(),
+///     "Unmatched brace at 4:1 near '{name: Alex, lev'"
+/// );
+/// assert_eq!(players.next().unwrap()?, compound! { "name": "Herobrine", "level": -1 });
+/// assert!(players.next().is_none());
+/// # Ok::<(), quartz_nbt::io::NbtIoError>(())
+/// ```
+pub fn parse_lines<R: BufRead>(reader: R) -> ParseLines<R> {
+    parse_lines_with(reader, ParseOptions::new())
+}
+
+/// Returns an iterator which parses each line of the given reader just like [`parse_lines`], but
+/// with the given options.
+///
+/// [`parse_lines`]: crate::snbt::parse_lines
+pub fn parse_lines_with<R: BufRead>(reader: R, options: ParseOptions) -> ParseLines<R> {
+    ParseLines {
+        reader,
+        options,
+        line: String::new(),
+        line_number: 0,
+        finished: false,
     }
 }
 
-// Parses a list, which can be either a generic tag list or vector of primitives
-fn parse_list(tokens: &mut Lexer<'_>, open_square: &TokenData) -> Result<NbtTag, SnbtError> {
-    const DELIMITER: Option<fn(char) -> bool> = Some(|ch| matches!(ch, ',' | ']' | ';'));
+/// An iterator over the compounds parsed from each line of a reader, returned by [`parse_lines`].
+///
+/// [`parse_lines`]: crate::snbt::parse_lines
+#[derive(Debug)]
+pub struct ParseLines<R> {
+    reader: R,
+    options: ParseOptions,
+    line: String,
+    line_number: usize,
+    finished: bool,
+}
 
-    match tokens.next(DELIMITER).transpose()? {
-        // Empty list ('[]') with no type specifier is treated as an empty NBT tag list
-        Some(TokenData {
-            token: Token::ClosedSquare,
-            ..
-        }) => Ok(NbtList::new().into()),
+impl<R> ParseLines<R> {
+    /// Returns the number of the line which was read last, starting from one, or zero if no line
+    /// has been read yet.
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
 
-        // A string as the first "element" can either be a type specifier such as in [I; 1, 2], or
-        // a regular string in a tag list, such as in ['i', 'j', 'k'].
-        Some(TokenData {
-            token:
-                Token::String {
-                    value: string,
-                    quoted,
-                },
-            index,
-            char_width,
-        }) => {
-            // Peek at the next token to see if it's a semicolon, which would indicate a primitive vector
-            match tokens.peek(DELIMITER) {
-                // Parse as a primitive vector
-                Some(Ok(TokenData {
-                    token: Token::Semicolon,
-                    ..
-                })) => {
-                    if quoted {
-                        return Err(SnbtError::unexpected_token_at(
-                            tokens.raw,
-                            index,
-                            char_width,
-                            "'B', 'I', or 'L'",
-                        ));
+    /// Returns the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: BufRead> Iterator for ParseLines<R> {
+    type Item = Result<NbtCompound, NbtIoError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.finished {
+            self.line.clear();
+            match self.reader.read_line(&mut self.line) {
+                Ok(0) => self.finished = true,
+                Ok(_) => {
+                    self.line_number += 1;
+                    let line = self.line.trim_end_matches(['\n', '\r']);
+                    let content = line.trim_start();
+                    if content.is_empty() || content.starts_with('#') || content.starts_with("//") {
+                        continue;
                     }
 
-                    // Moves past the peeked semicolon
-                    tokens.next(None);
+                    return Some(
+                        parse_line(line, self.options)
+                            .map_err(|error| error.on_line(self.line_number).into()),
+                    );
+                }
+                Err(error) => {
+                    self.finished = true;
+                    return Some(Err(error.into()));
+                }
+            }
+        }
 
-                    // Determine the primitive type and parse it
-                    match string.as_str() {
-                        "b" | "B" => parse_prim_list::<u8>(tokens, open_square),
-                        "i" | "I" => parse_prim_list::<i32>(tokens, open_square),
-                        "l" | "L" => parse_prim_list::<i64>(tokens, open_square),
-                        _ => Err(SnbtError::unexpected_token_at(
-                            tokens.raw,
-                            index,
-                            char_width,
-                            "'B', 'I', or 'L'",
-                        )),
+        None
+    }
+}
+
+fn parse_line(line: &str, options: ParseOptions) -> Result<NbtCompound, SnbtError> {
+    let (compound, size) = parse_and_size_with(line, options)?;
+    match line[size ..].find(|ch: char| !ch.is_whitespace()) {
+        Some(offset) => Err(SnbtError::unexpected_token_at(
+            line,
+            size + offset,
+            1,
+            "end of line",
+        )),
+        None => Ok(compound),
+    }
+}
+
+/// A compound parsed once from SNBT containing placeholders, which can be filled in with different
+/// values any number of times without parsing the SNBT again.
+///
+/// A placeholder is a string value of the form `$name`, where the name consists of ASCII letters,
+/// digits and underscores and does not start with a digit. The name may be followed by whitespace
+/// and one of the suffixes `b`, `s`, `i`, `l`, `f` or `d`, in which case the value filled in must
+/// be a byte, short, int, long, float or double respectively. Placeholders may appear as the values
+/// of compounds and as the elements of lists, whether they are quoted or not, but not as keys or
+/// within arrays. A list may not mix placeholders with other values. Strings starting with `$$`
+/// are not placeholders, and have the first `$` removed.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::snbt::SnbtTemplate;
+///
+/// let template = SnbtTemplate::parse("{id: $id, Count: $count b, tag: {Lore: [$lore]}}")?;
+/// assert_eq!(template.names().collect::<Vec<_>>(), ["count", "id", "lore"]);
+///
+/// for (id, count) in [("minecraft:stone", 64i8), ("minecraft:dirt", 3)] {
+///     let item = template.fill(&compound! { "id": id, "count": count, "lore": "Mined" })?;
+///     assert_eq!(item, compound! {
+///         "id": id,
+///         "Count": count,
+///         "tag": { "Lore": ["Mined"] }
+///     });
+/// }
+///
+/// // Values must have the kind given by the suffix
+/// assert!(template.fill(&compound! { "id": "minecraft:stone", "count": 64i32, "lore": "" }).is_err());
+/// // Every placeholder must be filled
+/// assert!(template.fill(&compound! { "id": "minecraft:stone", "count": 64i8 }).is_err());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnbtTemplate {
+    root: NbtCompound,
+    placeholders: Vec<Placeholder>,
+    // The locations of the lists containing placeholders, which must be homogenous once filled
+    lists: Vec<Vec<Step<'static>>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Placeholder {
+    name: Box<str>,
+    kind: Option<NbtTagKind>,
+    location: Vec<Step<'static>>,
+}
+
+impl SnbtTemplate {
+    /// Parses the given SNBT into a template. The SNBT is parsed in the same way as with
+    /// [`parse`], so errors are reported at their position in the given string.
+    ///
+    /// [`parse`]: crate::snbt::parse
+    pub fn parse<T: AsRef<str> + ?Sized>(template: &T) -> Result<Self, SnbtError> {
+        let mut root = parse(template)?;
+        let mut placeholders = Vec::new();
+        let mut location = Vec::new();
+        for (key, tag) in root.inner_mut() {
+            location.push(Step::Key(Cow::Owned(key.clone())));
+            collect_placeholders(tag, &mut location, &mut placeholders);
+            location.pop();
+        }
+
+        let mut lists = placeholders
+            .iter()
+            .filter(|placeholder| matches!(placeholder.location.last(), Some(Step::Index(_))))
+            .map(|placeholder| placeholder.location[.. placeholder.location.len() - 1].to_vec())
+            .collect::<Vec<_>>();
+        lists.dedup();
+
+        Ok(SnbtTemplate {
+            root,
+            placeholders,
+            lists,
+        })
+    }
+
+    /// Returns the names of the placeholders in this template, sorted and without duplicates.
+    pub fn names(&self) -> impl Iterator<Item = &str> + '_ {
+        let mut names = self
+            .placeholders
+            .iter()
+            .map(|placeholder| &*placeholder.name)
+            .collect::<Vec<_>>();
+        names.sort_unstable();
+        names.dedup();
+        names.into_iter()
+    }
+
+    /// Returns a copy of this template with each placeholder replaced by the value of the same name
+    /// in the given compound. Values without a placeholder are ignored.
+    ///
+    /// An error is returned if a placeholder has no value, if a value does not have the kind given
+    /// by the suffix of its placeholder, or if the values filled into a list have different kinds.
+    pub fn fill(&self, values: &NbtCompound) -> Result<NbtCompound, NbtStructureError> {
+        let mut root = self.root.clone();
+        for placeholder in &self.placeholders {
+            let value = values
+                .0
+                .get(&*placeholder.name)
+                .ok_or_else(|| NbtStructureError::missing_tag(&*placeholder.name))?;
+            if let Some(kind) = placeholder.kind {
+                if value.kind() != kind {
+                    return Err(NbtStructureError::type_mismatch(
+                        kind.name(),
+                        value.tag_name(),
+                    ));
+                }
+            }
+
+            let (last, parents) = match placeholder.location.split_last() {
+                Some(split) => split,
+                None => continue,
+            };
+            match (last, tag_at_mut(&mut root, parents)) {
+                (Step::Key(key), Some(NbtTag::Compound(compound))) =>
+                    compound.insert(key.as_ref(), value.clone()),
+                (Step::Key(key), None) => root.insert(key.as_ref(), value.clone()),
+                (&Step::Index(index), Some(NbtTag::List(list))) => list[index] = value.clone(),
+                _ => {}
+            }
+        }
+
+        for location in &self.lists {
+            if let Some(NbtTag::List(list)) = tag_at_mut(&mut root, location) {
+                let kind = list[0].kind();
+                if let Some(element) = list.iter().find(|element| element.kind() != kind) {
+                    return Err(NbtStructureError::type_mismatch(
+                        kind.name(),
+                        element.tag_name(),
+                    ));
+                }
+            }
+        }
+
+        Ok(root)
+    }
+}
+
+impl FromStr for SnbtTemplate {
+    type Err = SnbtError;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+// Records the location of every placeholder within the given tag, and unescapes strings starting
+// with `$$`
+fn collect_placeholders(
+    tag: &mut NbtTag,
+    location: &mut Vec<Step<'static>>,
+    placeholders: &mut Vec<Placeholder>,
+) {
+    match tag {
+        NbtTag::String(string) if string.starts_with("$$") => {
+            string.remove(0);
+        }
+        NbtTag::String(string) =>
+            if let Some((name, kind)) = parse_placeholder(string) {
+                placeholders.push(Placeholder {
+                    name: name.into(),
+                    kind,
+                    location: location.clone(),
+                });
+            },
+        NbtTag::List(list) =>
+            for (index, element) in list.0.iter_mut().enumerate() {
+                location.push(Step::Index(index));
+                collect_placeholders(element, location, placeholders);
+                location.pop();
+            },
+        NbtTag::Compound(compound) =>
+            for (key, element) in compound.inner_mut() {
+                location.push(Step::Key(Cow::Owned(key.clone())));
+                collect_placeholders(element, location, placeholders);
+                location.pop();
+            },
+        _ => {}
+    }
+}
+
+// Splits a placeholder into its name and the kind given by its suffix, if any
+fn parse_placeholder(string: &str) -> Option<(&str, Option<NbtTagKind>)> {
+    let rest = string.strip_prefix('$')?;
+    let name_len = rest
+        .find(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '_'))
+        .unwrap_or(rest.len());
+    let (name, suffix) = rest.split_at(name_len);
+    if name.is_empty() || name.starts_with(|ch: char| ch.is_ascii_digit()) {
+        return None;
+    }
+
+    if suffix.is_empty() {
+        return Some((name, None));
+    }
+
+    // The suffix must be separated from the name, since it would otherwise be part of the name
+    let trimmed = suffix.trim_start();
+    if trimmed.len() == suffix.len() {
+        return None;
+    }
+
+    let kind = match trimmed {
+        "b" | "B" => NbtTagKind::Byte,
+        "s" | "S" => NbtTagKind::Short,
+        "i" | "I" => NbtTagKind::Int,
+        "l" | "L" => NbtTagKind::Long,
+        "f" | "F" => NbtTagKind::Float,
+        "d" | "D" => NbtTagKind::Double,
+        _ => return None,
+    };
+    Some((name, Some(kind)))
+}
+
+// Returns the tag at the given location within the given compound, or None if the location is
+// empty
+fn tag_at_mut<'a>(root: &'a mut NbtCompound, location: &[Step<'_>]) -> Option<&'a mut NbtTag> {
+    let (first, rest) = location.split_first()?;
+    let mut tag = match first {
+        Step::Key(key) => root.0.get_mut(key.as_ref())?,
+        Step::Index(_) => return None,
+    };
+    for step in rest {
+        tag = match (step, tag) {
+            (Step::Key(key), NbtTag::Compound(compound)) => compound.0.get_mut(key.as_ref())?,
+            (&Step::Index(index), NbtTag::List(list)) => list.0.get_mut(index)?,
+            _ => return None,
+        };
+    }
+    Some(tag)
+}
+
+/// Converts the given flavor of binary NBT data read from the given reader into SNBT written to the
+/// given writer, returning the root name of the document.
+///
+/// Unlike reading the document with [`read_nbt`] and formatting it with
+/// [`NbtCompound::to_snbt_with`], each tag is written as soon as it is read, so arbitrarily large
+/// documents are converted using memory bounded by their nesting depth and the length of their
+/// longest string. The output is formatted according to the given options, in the same way as
+/// [`NbtCompound::to_snbt_with`], except that compound entries are written in the order they appear
+/// in the binary data, and duplicate keys are written as many times as they appear.
+///
+/// Neither the reader nor the writer are buffered by this function, so wrapping them in a
+/// [`BufReader`] and [`BufWriter`] is recommended when they are files or sockets. If an error
+/// occurs, the SNBT written before it will be incomplete.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::{
+///     io::{self, Flavor},
+///     snbt::{self, FormatOptions},
+/// };
+///
+/// let compound = compound! { "pos": [I; 1, 64, -3], "name": "spawn" };
+/// let mut bytes = Vec::new();
+/// io::write_nbt(&mut bytes, Some("level"), &compound, Flavor::GzCompressed)?;
+///
+/// let mut snbt = Vec::new();
+/// let options = FormatOptions::new().pretty(true);
+/// let root_name = snbt::stream_from_binary(
+///     &mut bytes.as_slice(),
+///     &mut snbt,
+///     Flavor::GzCompressed,
+///     options,
+/// )?;
+///
+/// assert_eq!(root_name, "level");
+/// assert_eq!(snbt::parse(std::str::from_utf8(&snbt).unwrap())?, compound);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// [`read_nbt`]: crate::io::read_nbt
+/// [`NbtCompound::to_snbt_with`]: crate::NbtCompound::to_snbt_with
+/// [`BufReader`]: std::io::BufReader
+/// [`BufWriter`]: std::io::BufWriter
+pub fn stream_from_binary<R: Read, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    flavor: Flavor,
+    style: FormatOptions,
+) -> Result<String, NbtIoError> {
+    match flavor {
+        Flavor::Uncompressed => stream_from_binary_uncompressed(reader, writer, style),
+        Flavor::ZlibCompressed | Flavor::ZlibCompressedWith(_) =>
+            stream_from_binary_uncompressed(&mut ZlibDecoder::new(reader), writer, style),
+        Flavor::GzCompressed | Flavor::GzCompressedWith(_) =>
+            stream_from_binary_uncompressed(&mut GzDecoder::new(reader), writer, style),
+        Flavor::RawDeflate | Flavor::RawDeflateWith(_) =>
+            stream_from_binary_uncompressed(&mut DeflateDecoder::new(reader), writer, style),
+    }
+}
+
+fn stream_from_binary_uncompressed<R: Read, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    style: FormatOptions,
+) -> Result<String, NbtIoError> {
+    let root_name = io::read_root_header(reader)?;
+    BinaryToSnbt {
+        reader,
+        writer,
+        options: style,
+        indent: String::new(),
+    }
+    .convert(0xA, 0)?;
+    Ok(root_name)
+}
+
+/// Parses the given SNBT string and writes it to the given writer as uncompressed binary NBT with
+/// the given root name.
+///
+/// Unlike parsing the string with [`parse`] and writing the result with [`write_nbt`], tags are
+/// written as soon as they are parsed, so no [`NbtCompound`] is built in memory. Since the binary
+/// format stores the length of each list and array before its elements, a placeholder is written
+/// in its place and filled in by seeking back once the end of the list is reached, which is why
+/// the writer must implement [`Seek`]. To produce compressed data, compress the output afterwards
+/// with [`recompress`].
+///
+/// The SNBT is parsed in the same way as with [`parse`], except that compound entries are written
+/// in the order they appear, and duplicate keys are written as many times as they appear. If an
+/// error occurs, the data written before it will be incomplete.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::{
+///     io::{self, Flavor},
+///     snbt,
+/// };
+/// use std::io::Cursor;
+///
+/// let mut bytes = Cursor::new(Vec::new());
+/// snbt::stream_to_binary(
+///     "{pos: [I; 1, 64, -3], entities: [{id: pig}, {id: cow}]}",
+///     &mut bytes,
+///     Some("level"),
+/// )?;
+///
+/// let (compound, root_name) = io::read_nbt(&mut bytes.get_ref().as_slice(), Flavor::Uncompressed)?;
+/// assert_eq!(root_name, "level");
+/// assert_eq!(compound, compound! {
+///     "pos": [I; 1, 64, -3],
+///     "entities": [{ "id": "pig" }, { "id": "cow" }]
+/// });
+/// # Ok::<(), io::NbtIoError>(())
+/// ```
+///
+/// [`parse`]: crate::snbt::parse
+/// [`write_nbt`]: crate::io::write_nbt
+/// [`NbtCompound`]: crate::NbtCompound
+/// [`Seek`]: std::io::Seek
+/// [`recompress`]: crate::io::recompress
+pub fn stream_to_binary<W: Write + Seek>(
+    string_nbt: &str,
+    writer: &mut W,
+    root_name: Option<&str>,
+) -> Result<(), NbtIoError> {
+    let mut tokens = Lexer::new(string_nbt, ParseOptions::new());
+    let open_curly = tokens.assert_next(Token::OpenCurly)?;
+    tokens.nested(&open_curly, |tokens| {
+        raw::write_u8(writer, 0xA)?;
+        raw::write_string(writer, root_name.unwrap_or(""))?;
+        emit_compound(tokens, writer, &open_curly)
+    })
+}
+
+/// Options controlling how tags are converted into SNBT by methods such as
+/// [`NbtTag::to_snbt_with`]. The default options produce the same output as [`NbtTag::to_snbt`].
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::snbt::{self, FormatOptions};
+///
+/// let nbt = compound! { "Flags": [1i8, 0i8, 2i8] };
+/// let options = FormatOptions::new().bool_literals(true);
+///
+/// let string = nbt.to_snbt_with(options);
+/// assert_eq!(string, "{Flags:[true,false,2B]}");
+/// assert_eq!(snbt::parse(&string).unwrap(), nbt);
+/// ```
+///
+/// [`NbtTag::to_snbt`]: crate::NbtTag::to_snbt
+/// [`NbtTag::to_snbt_with`]: crate::NbtTag::to_snbt_with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct FormatOptions {
+    pub(crate) pretty: bool,
+    pub(crate) bool_literals: bool,
+    pub(crate) sort_keys: bool,
+    #[cfg(feature = "ansi")]
+    pub(crate) ansi_theme: Option<AnsiTheme>,
+    // Only set through `DisplayOptions`, since grouped digits cannot be parsed
+    pub(crate) digit_grouping: Option<DigitGrouping>,
+}
+
+impl FormatOptions {
+    /// Returns the default options, which produce compact output.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether extra spacing is added for readability, as with [`NbtTag::to_pretty_snbt`].
+    /// This defaults to `false`.
+    ///
+    /// [`NbtTag::to_pretty_snbt`]: crate::NbtTag::to_pretty_snbt
+    pub fn pretty(mut self, pretty: bool) -> Self {
+        self.pretty = pretty;
+        self
+    }
+
+    /// Sets whether byte tags containing `0` or `1` are written as `false` or `true`. The game and
+    /// [`parse`] read these literals back as bytes, so this only changes how boolean flags look.
+    /// Bytes within byte arrays are not affected. This defaults to `false`.
+    ///
+    /// [`parse`]: crate::snbt::parse
+    pub fn bool_literals(mut self, bool_literals: bool) -> Self {
+        self.bool_literals = bool_literals;
+        self
+    }
+
+    /// Sets whether the entries of every compound are written sorted by key, in the same order as
+    /// [`NbtCompound::into_sorted_entries`], rather than in the order of the compound's internal
+    /// map. The output is then the same for equal compounds on every platform and with or without
+    /// the `preserve_order` feature. This has no effect on [`stream_from_binary`], which writes
+    /// entries in the order they are read. This defaults to `false`.
+    ///
+    /// [`NbtCompound::into_sorted_entries`]: crate::NbtCompound::into_sorted_entries
+    /// [`stream_from_binary`]: crate::snbt::stream_from_binary
+    pub fn sort_keys(mut self, sort_keys: bool) -> Self {
+        self.sort_keys = sort_keys;
+        self
+    }
+
+    /// Sets the theme used to color the output with ANSI escape sequences for display in a
+    /// terminal. The colored output can no longer be parsed. By default, no escape sequences are
+    /// written.
+    ///
+    /// This method is only available when the `ansi` feature is enabled.
+    #[cfg(feature = "ansi")]
+    pub fn ansi_theme(mut self, theme: AnsiTheme) -> Self {
+        self.ansi_theme = Some(theme);
+        self
+    }
+
+    // Wraps the given value so that it is written in the color the theme assigns to the given
+    // element, if any
+    #[inline]
+    #[cfg_attr(not(feature = "ansi"), allow(unused_variables))]
+    pub(crate) fn paint<T: Display>(&self, element: Element, value: T) -> Painted<T> {
+        Painted {
+            #[cfg(feature = "ansi")]
+            color: self.ansi_theme.and_then(|theme| theme.color(element)),
+            value,
+        }
+    }
+
+    // Wraps the given number so that its digits are grouped, if the options are set to do so
+    #[inline]
+    pub(crate) fn group_digits<T: Display>(&self, value: T) -> Grouped<T> {
+        Grouped {
+            grouping: self.digit_grouping,
+            value,
+        }
+    }
+}
+
+/// Options controlling how tags are converted into human-readable strings by methods such as
+/// [`NbtTag::to_display_string`], for reports and logs rather than for storage. The output has the
+/// same layout as [`NbtTag::to_pretty_snbt`], but the integer digits of numbers are grouped in
+/// threes, which makes large values such as experience totals and timestamps easier to read.
+///
+/// Since the grouped numbers cannot be parsed back, these options are kept apart from
+/// [`FormatOptions`], and SNBT output is never affected by them. Each element of a list or array
+/// is written on its own line, so a group separator of `,` is never mistaken for the separator
+/// between elements.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::snbt::DisplayOptions;
+///
+/// let played = NbtTag::Long(1719850123456);
+/// assert_eq!(played.to_display_string(DisplayOptions::new()), "1,719,850,123,456L");
+/// assert_eq!(played.to_snbt(), "1719850123456L");
+///
+/// let german = DisplayOptions::new()
+///     .group_separator('.')
+///     .decimal_separator(',');
+/// assert_eq!(NbtTag::Double(-1234.5).to_display_string(german), "-1.234,5D");
+/// ```
+///
+/// [`NbtTag::to_display_string`]: crate::NbtTag::to_display_string
+/// [`NbtTag::to_pretty_snbt`]: crate::NbtTag::to_pretty_snbt
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DisplayOptions {
+    grouping: DigitGrouping,
+    #[cfg(feature = "ansi")]
+    ansi_theme: Option<AnsiTheme>,
+}
+
+impl DisplayOptions {
+    /// Returns the default options, which group digits with `,` and write decimal points as `.`.
+    pub fn new() -> Self {
+        DisplayOptions {
+            grouping: DigitGrouping {
+                group_separator: ',',
+                decimal_separator: '.',
+            },
+            #[cfg(feature = "ansi")]
+            ansi_theme: None,
+        }
+    }
+
+    /// Sets the character written between groups of three integer digits, such as `.` or a
+    /// narrow no-break space in many European locales. This defaults to `,`.
+    pub fn group_separator(mut self, separator: char) -> Self {
+        self.grouping.group_separator = separator;
+        self
+    }
+
+    /// Sets the character written in place of the decimal point of floats. Digits after the
+    /// decimal point are never grouped. This defaults to `.`.
+    pub fn decimal_separator(mut self, separator: char) -> Self {
+        self.grouping.decimal_separator = separator;
+        self
+    }
+
+    /// Sets the theme used to color the output with ANSI escape sequences, as with
+    /// [`FormatOptions::ansi_theme`]. By default, no escape sequences are written.
+    ///
+    /// This method is only available when the `ansi` feature is enabled.
+    ///
+    /// [`FormatOptions::ansi_theme`]: crate::snbt::FormatOptions::ansi_theme
+    #[cfg(feature = "ansi")]
+    pub fn ansi_theme(mut self, theme: AnsiTheme) -> Self {
+        self.ansi_theme = Some(theme);
+        self
+    }
+
+    pub(crate) fn format_options(self) -> FormatOptions {
+        FormatOptions {
+            pretty: true,
+            bool_literals: false,
+            sort_keys: false,
+            #[cfg(feature = "ansi")]
+            ansi_theme: self.ansi_theme,
+            digit_grouping: Some(self.grouping),
+        }
+    }
+}
+
+impl Default for DisplayOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct DigitGrouping {
+    group_separator: char,
+    decimal_separator: char,
+}
+
+// A number which is written with its integer digits grouped, if a grouping is set
+pub(crate) struct Grouped<T> {
+    grouping: Option<DigitGrouping>,
+    value: T,
+}
+
+impl<T: Display> Display for Grouped<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let grouping = match self.grouping {
+            Some(grouping) => grouping,
+            None => return Display::fmt(&self.value, f),
+        };
+
+        let number = self.value.to_string();
+        let (sign, unsigned) = match number.strip_prefix('-') {
+            Some(unsigned) => ("-", unsigned),
+            None => ("", number.as_str()),
+        };
+        // Non-finite floats have no digits and are written unchanged
+        let digits = unsigned
+            .find(|ch: char| !ch.is_ascii_digit())
+            .unwrap_or(unsigned.len());
+        let (integer, rest) = unsigned.split_at(digits);
+
+        f.write_str(sign)?;
+        for (index, digit) in integer.char_indices() {
+            if index != 0 && (digits - index) % 3 == 0 {
+                write!(f, "{}", grouping.group_separator)?;
+            }
+            write!(f, "{}", digit)?;
+        }
+        match rest.strip_prefix('.') {
+            Some(fraction) => write!(f, "{}{}", grouping.decimal_separator, fraction),
+            None => f.write_str(rest),
+        }
+    }
+}
+
+// The parts of the SNBT output which an `AnsiTheme` assigns colors to
+#[derive(Clone, Copy)]
+pub(crate) enum Element {
+    Key,
+    String,
+    Number,
+    Suffix,
+}
+
+// A value which is written surrounded by the escape sequences for a color, if one is set
+pub(crate) struct Painted<T> {
+    #[cfg(feature = "ansi")]
+    color: Option<AnsiColor>,
+    value: T,
+}
+
+impl<T: Display> Display for Painted<T> {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        #[cfg(feature = "ansi")]
+        if let Some(color) = self.color {
+            write!(f, "\x1b[{}m", color.code())?;
+            Display::fmt(&self.value, f)?;
+            return f.write_str("\x1b[0m");
+        }
+
+        Display::fmt(&self.value, f)
+    }
+}
+
+/// The colors assigned to the parts of SNBT output by [`FormatOptions::ansi_theme`] and
+/// [`NbtTag::to_ansi_snbt`]. The default theme matches the colors used by the vanilla `/data`
+/// command in chat. Punctuation is never colored.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::snbt::{AnsiColor, AnsiTheme};
+///
+/// let theme = AnsiTheme::new().key(None).number(Some(AnsiColor::Blue));
+/// assert_eq!(
+///     NbtTag::Compound(compound! { "x": 5i8 }).to_ansi_snbt(theme),
+///     "{\n    x: \x1b[34m5\x1b[0m\x1b[91mB\x1b[0m\n}"
+/// );
+/// ```
+///
+/// This type is only available when the `ansi` feature is enabled.
+///
+/// [`FormatOptions::ansi_theme`]: crate::snbt::FormatOptions::ansi_theme
+/// [`NbtTag::to_ansi_snbt`]: crate::NbtTag::to_ansi_snbt
+#[cfg(feature = "ansi")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AnsiTheme {
+    key: Option<AnsiColor>,
+    string: Option<AnsiColor>,
+    number: Option<AnsiColor>,
+    suffix: Option<AnsiColor>,
+}
+
+#[cfg(feature = "ansi")]
+impl AnsiTheme {
+    /// Returns the default theme, which uses the colors of the vanilla `/data` command.
+    pub const fn new() -> Self {
+        AnsiTheme {
+            key: Some(AnsiColor::BrightCyan),
+            string: Some(AnsiColor::BrightGreen),
+            number: Some(AnsiColor::Yellow),
+            suffix: Some(AnsiColor::BrightRed),
+        }
+    }
+
+    /// Returns a theme which colors nothing.
+    pub const fn plain() -> Self {
+        AnsiTheme {
+            key: None,
+            string: None,
+            number: None,
+            suffix: None,
+        }
+    }
+
+    /// Sets the color of the keys of compound entries. This defaults to
+    /// [`AnsiColor::BrightCyan`], which is closest to the aqua used by the game.
+    pub fn key(mut self, color: Option<AnsiColor>) -> Self {
+        self.key = color;
+        self
+    }
+
+    /// Sets the color of string values. This defaults to [`AnsiColor::BrightGreen`].
+    pub fn string(mut self, color: Option<AnsiColor>) -> Self {
+        self.string = color;
+        self
+    }
+
+    /// Sets the color of numbers, including the elements of arrays and boolean literals. This
+    /// defaults to [`AnsiColor::Yellow`], which is closest to the gold used by the game.
+    pub fn number(mut self, color: Option<AnsiColor>) -> Self {
+        self.number = color;
+        self
+    }
+
+    /// Sets the color of the type suffixes of numbers and of the type specifiers of arrays. This
+    /// defaults to [`AnsiColor::BrightRed`].
+    pub fn suffix(mut self, color: Option<AnsiColor>) -> Self {
+        self.suffix = color;
+        self
+    }
+
+    fn color(&self, element: Element) -> Option<AnsiColor> {
+        match element {
+            Element::Key => self.key,
+            Element::String => self.string,
+            Element::Number => self.number,
+            Element::Suffix => self.suffix,
+        }
+    }
+}
+
+#[cfg(feature = "ansi")]
+impl Default for AnsiTheme {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// One of the sixteen standard colors supported by nearly every terminal, which are written as
+/// ANSI foreground color escape sequences.
+///
+/// This type is only available when the `ansi` feature is enabled.
+#[cfg(feature = "ansi")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AnsiColor {
+    /// Black, written with the code 30.
+    Black,
+    /// Red, written with the code 31.
+    Red,
+    /// Green, written with the code 32.
+    Green,
+    /// Yellow, written with the code 33.
+    Yellow,
+    /// Blue, written with the code 34.
+    Blue,
+    /// Magenta, written with the code 35.
+    Magenta,
+    /// Cyan, written with the code 36.
+    Cyan,
+    /// White, written with the code 37.
+    White,
+    /// Bright black, written with the code 90.
+    BrightBlack,
+    /// Bright red, written with the code 91.
+    BrightRed,
+    /// Bright green, written with the code 92.
+    BrightGreen,
+    /// Bright yellow, written with the code 93.
+    BrightYellow,
+    /// Bright blue, written with the code 94.
+    BrightBlue,
+    /// Bright magenta, written with the code 95.
+    BrightMagenta,
+    /// Bright cyan, written with the code 96.
+    BrightCyan,
+    /// Bright white, written with the code 97.
+    BrightWhite,
+}
+
+#[cfg(feature = "ansi")]
+impl AnsiColor {
+    /// Returns the parameter of the escape sequence selecting this color as the foreground color.
+    ///
+    /// ```
+    /// use quartz_nbt::snbt::AnsiColor;
+    ///
+    /// assert_eq!(AnsiColor::Red.code(), 31);
+    /// assert_eq!(AnsiColor::BrightCyan.code(), 96);
+    /// ```
+    pub const fn code(self) -> u8 {
+        match self {
+            AnsiColor::Black => 30,
+            AnsiColor::Red => 31,
+            AnsiColor::Green => 32,
+            AnsiColor::Yellow => 33,
+            AnsiColor::Blue => 34,
+            AnsiColor::Magenta => 35,
+            AnsiColor::Cyan => 36,
+            AnsiColor::White => 37,
+            AnsiColor::BrightBlack => 90,
+            AnsiColor::BrightRed => 91,
+            AnsiColor::BrightGreen => 92,
+            AnsiColor::BrightYellow => 93,
+            AnsiColor::BrightBlue => 94,
+            AnsiColor::BrightMagenta => 95,
+            AnsiColor::BrightCyan => 96,
+            AnsiColor::BrightWhite => 97,
+        }
+    }
+}
+
+/// Options controlling how SNBT is parsed by [`parse_with`]. The default options parse SNBT in
+/// the same way as [`parse`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ParseOptions {
+    pub(crate) widen_numeric_lists: bool,
+    pub(crate) vanilla_strict: bool,
+    pub(crate) vanilla_numbers: bool,
+    pub(crate) deny_duplicate_keys: bool,
+    pub(crate) max_token_len: Option<usize>,
+}
+
+impl ParseOptions {
+    /// Returns the default options, which reject any list mixing different kinds of tags.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether lists which mix different kinds of numbers, such as `[1b, 2, 3s]`, are
+    /// widened to the largest kind present as with [`NbtList::normalize_numeric`], rather than
+    /// causing an error. An error is still returned if a number cannot be widened without loss
+    /// of precision. This defaults to `false`.
+    ///
+    /// [`NbtList::normalize_numeric`]: crate::NbtList::normalize_numeric
+    pub fn widen_numeric_lists(mut self, widen_numeric_lists: bool) -> Self {
+        self.widen_numeric_lists = widen_numeric_lists;
+        self
+    }
+
+    /// Sets whether SNBT is parsed exactly as the game parses it, so that input is rejected if and
+    /// only if the game would reject it. This is useful for validating user input destined for
+    /// commands. The reference is the parser of Minecraft: Java Edition from 1.13 through 1.21.4.
+    /// This defaults to `false`, in which case the parser is more lenient.
+    ///
+    /// In strict mode:
+    ///  - Unquoted strings may only contain the characters `A-Z`, `a-z`, `0-9`, `_`, `-`, `.` and
+    ///    `+`. Whitespace and brackets end an unquoted string rather than being included in it.
+    ///  - Unquoted strings are only read as numbers if they match the game's number syntax and fit
+    ///    in the type given by their suffix, and are read as strings otherwise. For example, `1.2.3`
+    ///    and `300b` are strings rather than errors.
+    ///  - Array headers must be written as `[B;`, `[I;` or `[L;` without any whitespace, and the
+    ///    elements of byte, int and long arrays must have the matching suffix, so `[B; 1b]` is
+    ///    valid but `[B; 1]` is not. `true` and `false` are allowed in byte arrays.
+    ///  - Quoted strings may only escape backslashes and the quote enclosing them.
+    ///  - Compound keys may be unquoted numbers such as `1`, but may not be empty.
+    ///  - A single trailing comma is allowed in lists, arrays and compounds, but leading and
+    ///    repeated commas are errors.
+    ///  - Anything other than whitespace following the root compound is an error.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// use quartz_nbt::snbt::{self, ParseOptions};
+    ///
+    /// let strict = ParseOptions::new().vanilla_strict(true);
+    ///
+    /// assert_eq!(
+    ///     snbt::parse("{name: Steve Jobs}").unwrap(),
+    ///     compound! { "name": "Steve Jobs" }
+    /// );
+    /// assert!(snbt::parse_with("{name: Steve Jobs}", strict).is_err());
+    /// assert!(snbt::parse_with("{name: 'Steve Jobs'}", strict).is_ok());
+    ///
+    /// assert!(snbt::parse("{version: 1.2.3}").is_err());
+    /// assert_eq!(
+    ///     snbt::parse_with("{version: 1.2.3}", strict).unwrap(),
+    ///     compound! { "version": "1.2.3" }
+    /// );
+    /// ```
+    pub fn vanilla_strict(mut self, vanilla_strict: bool) -> Self {
+        self.vanilla_strict = vanilla_strict;
+        self
+    }
+
+    /// Sets whether unquoted values are read as numbers exactly as the game reads them, as in
+    /// [`vanilla_strict`](Self::vanilla_strict) mode, without applying the rest of that mode's
+    /// restrictions. This is always the case in strict mode, and otherwise defaults to `false`.
+    ///
+    /// Both modes read numbers such as `+5`, `.5`, `1.` and `1e3f` the same way. They differ on
+    /// the values below, which are read as strings by the game:
+    ///
+    /// | Value          | Lenient          | Vanilla numbers   |
+    /// |----------------|------------------|-------------------|
+    /// | `007`          | `7`              | `"007"`           |
+    /// | `128b`         | error            | `"128b"`          |
+    /// | `2147483648`   | error            | `"2147483648"`    |
+    /// | `1e3`          | error            | `"1e3"`           |
+    /// | `0x10`         | error            | `"0x10"`          |
+    /// | `1.2.3`        | error            | `"1.2.3"`         |
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// use quartz_nbt::snbt::{self, ParseOptions};
+    ///
+    /// let vanilla_numbers = ParseOptions::new().vanilla_numbers(true);
+    ///
+    /// assert_eq!(
+    ///     snbt::parse("{a: 007, b: +5, c: .5f}").unwrap(),
+    ///     compound! { "a": 7i32, "b": 5i32, "c": 0.5f32 }
+    /// );
+    /// assert_eq!(
+    ///     snbt::parse_with("{a: 007, b: +5, c: .5f}", vanilla_numbers).unwrap(),
+    ///     compound! { "a": "007", "b": 5i32, "c": 0.5f32 }
+    /// );
+    /// ```
+    pub fn vanilla_numbers(mut self, vanilla_numbers: bool) -> Self {
+        self.vanilla_numbers = vanilla_numbers;
+        self
+    }
+
+    /// Sets whether a key appearing more than once in the same compound is an error rather than
+    /// replacing the earlier value. The error reports the positions of both occurrences. This
+    /// defaults to `false`, and is independent of [`vanilla_strict`] since the game accepts
+    /// duplicate keys.
+    ///
+    /// ```
+    /// use quartz_nbt::snbt::{self, ParseOptions};
+    ///
+    /// let options = ParseOptions::new().deny_duplicate_keys(true);
+    /// assert_eq!(
+    ///     snbt::parse_with("{a: 1, b: 2, a: 3}", options).unwrap_err().to_string(),
+    ///     "Duplicate key at 1:14 near 'b: 2, a', first defined at 1:2"
+    /// );
+    /// assert!(snbt::parse_with("{a: 1, A: 2}", options).is_ok());
+    /// ```
+    ///
+    /// [`vanilla_strict`]: crate::snbt::ParseOptions::vanilla_strict
+    pub fn deny_duplicate_keys(mut self, deny_duplicate_keys: bool) -> Self {
+        self.deny_duplicate_keys = deny_duplicate_keys;
+        self
+    }
+
+    /// Sets the maximum number of bytes of input a single key or value may span, including any
+    /// quotes enclosing it, after which parsing fails with
+    /// [`ParserErrorType::ExceededMaxTokenLength`]. The limit is checked as the token is read, so
+    /// an overlong token is rejected without reading the rest of it. By default, the length of
+    /// tokens is not limited, so setting this is recommended when parsing untrusted input.
+    ///
+    /// Unquoted values may contain brackets and quotes, so an unterminated quote or bracket in an
+    /// unquoted value can extend it to the end of the input. Limiting the length of tokens also
+    /// bounds the work done on such input.
+    ///
+    /// ```
+    /// use quartz_nbt::snbt::{self, ParseOptions};
+    ///
+    /// let options = ParseOptions::new().max_token_len(8);
+    /// assert!(snbt::parse_with("{name: 'Steve'}", options).is_ok());
+    /// assert_eq!(
+    ///     snbt::parse_with("{name: 'Herobrine'}", options).unwrap_err().to_string(),
+    ///     "Exceeded maximum token length of 8 bytes at 1:8 near ''Herobrine'}'"
+    /// );
+    /// ```
+    ///
+    /// [`ParserErrorType::ExceededMaxTokenLength`]: crate::snbt::ParserErrorType::ExceededMaxTokenLength
+    pub fn max_token_len(mut self, max_token_len: usize) -> Self {
+        self.max_token_len = Some(max_token_len);
+        self
+    }
+}
+
+// Writes the SNBT form of binary tags as they are read, mirroring the formatting of the tag types'
+// `to_formatted_snbt` methods
+struct BinaryToSnbt<'a, R, W> {
+    reader: &'a mut R,
+    writer: &'a mut W,
+    options: FormatOptions,
+    indent: String,
+}
+
+impl<R: Read, W: Write> BinaryToSnbt<'_, R, W> {
+    // The depth is the number of lists and compounds enclosing the tag being converted
+    fn convert(&mut self, tag_id: u8, depth: usize) -> Result<(), NbtIoError> {
+        match tag_id {
+            0x1 => {
+                let value = raw::read_i8(self.reader)?;
+                match value {
+                    0 | 1 if self.options.bool_literals => write!(
+                        self.writer,
+                        "{}",
+                        self.options
+                            .paint(Element::Number, if value == 1 { "true" } else { "false" })
+                    )?,
+                    _ => self.convert_number(value, "B")?,
+                }
+            }
+            0x2 => {
+                let value = raw::read_i16(self.reader)?;
+                self.convert_number(value, "S")?;
+            }
+            0x3 => {
+                let value = raw::read_i32(self.reader)?;
+                self.convert_number(value, "")?;
+            }
+            0x4 => {
+                let value = raw::read_i64(self.reader)?;
+                self.convert_number(value, "L")?;
+            }
+            0x5 => {
+                let value = raw::read_f32(self.reader)?;
+                self.convert_number(value, "F")?;
+            }
+            0x6 => {
+                let value = raw::read_f64(self.reader)?;
+                self.convert_number(value, "D")?;
+            }
+            0x7 => self.convert_array("B", raw::read_i8)?,
+            0x8 => {
+                let string = raw::read_string(self.reader)?;
+                write!(
+                    self.writer,
+                    "{}",
+                    self.options
+                        .paint(Element::String, NbtTag::string_to_snbt(&string))
+                )?;
+            }
+            0x9 => {
+                let depth = io::child_depth(depth)?;
+                let tag_id = raw::read_u8(self.reader)?;
+                let len = raw::read_len(self.reader)?;
+                if tag_id > 0xC || (tag_id == 0 && len > 0) {
+                    return Err(NbtIoError::InvalidTagId(tag_id));
+                }
+
+                if len == 0 {
+                    self.writer.write_all(b"[]")?;
+                    return Ok(());
+                }
+
+                self.open(b'[')?;
+                for index in 0 .. len {
+                    if index != 0 {
+                        self.separate()?;
+                    }
+                    self.convert(tag_id, depth)?;
+                }
+                self.close(b']')?;
+            }
+            0xA => {
+                let depth = io::child_depth(depth)?;
+                let mut tag_id = raw::read_u8(self.reader)?;
+                if tag_id == 0x0 {
+                    self.writer.write_all(b"{}")?;
+                    return Ok(());
+                }
+
+                self.open(b'{')?;
+                let mut first = true;
+                while tag_id != 0x0 {
+                    if !first {
+                        self.separate()?;
+                    }
+                    first = false;
+
+                    let key = raw::read_string(self.reader)?;
+                    let key = self.options.paint(Element::Key, NbtTag::format_key(&key));
+                    if self.options.pretty {
+                        write!(self.writer, "{}: ", key)?;
+                    } else {
+                        write!(self.writer, "{}:", key)?;
+                    }
+
+                    self.convert(tag_id, depth)?;
+                    tag_id = raw::read_u8(self.reader)?;
+                }
+                self.close(b'}')?;
+            }
+            0xB => self.convert_array("I", raw::read_i32)?,
+            0xC => self.convert_array("L", raw::read_i64)?,
+            _ => return Err(NbtIoError::InvalidTagId(tag_id)),
+        }
+
+        Ok(())
+    }
+
+    fn convert_number<T: Display>(&mut self, value: T, ts: &str) -> Result<(), NbtIoError> {
+        write!(
+            self.writer,
+            "{}",
+            self.options.paint(Element::Number, value)
+        )?;
+        if !ts.is_empty() {
+            write!(self.writer, "{}", self.options.paint(Element::Suffix, ts))?;
+        }
+        Ok(())
+    }
+
+    fn convert_array<T: Display>(
+        &mut self,
+        ts: &str,
+        read: fn(&mut R) -> std::io::Result<T>,
+    ) -> Result<(), NbtIoError> {
+        let ts = self.options.paint(Element::Suffix, ts);
+        let len = raw::read_len(self.reader)?;
+        if len == 0 {
+            write!(self.writer, "[{};]", ts)?;
+            return Ok(());
+        }
+
+        self.open(b'[')?;
+        write!(self.writer, "{};", ts)?;
+        for index in 0 .. len {
+            let value = read(self.reader)?;
+            if index != 0 {
+                self.writer.write_all(b",")?;
+            }
+            if self.options.pretty {
+                write!(self.writer, "\n{}", self.indent)?;
+            }
+            write!(
+                self.writer,
+                "{}",
+                self.options.paint(Element::Number, value)
+            )?;
+        }
+        self.close(b']')
+    }
+
+    fn open(&mut self, delimiter: u8) -> Result<(), NbtIoError> {
+        self.writer.write_all(&[delimiter])?;
+        if self.options.pretty {
+            self.indent.push_str("    ");
+            write!(self.writer, "\n{}", self.indent)?;
+        }
+        Ok(())
+    }
+
+    fn separate(&mut self) -> Result<(), NbtIoError> {
+        if self.options.pretty {
+            write!(self.writer, ",\n{}", self.indent)?;
+        } else {
+            self.writer.write_all(b",")?;
+        }
+        Ok(())
+    }
+
+    fn close(&mut self, delimiter: u8) -> Result<(), NbtIoError> {
+        if self.options.pretty {
+            self.indent.truncate(self.indent.len() - 4);
+            write!(self.writer, "\n{}", self.indent)?;
+        }
+        self.writer.write_all(&[delimiter])?;
+        Ok(())
+    }
+}
+
+// Parses the next value in the token stream
+fn parse_next_value(
+    tokens: &mut Lexer<'_>,
+    delimiter: Option<fn(char) -> bool>,
+) -> Result<NbtTag, SnbtError> {
+    let token = tokens.next(delimiter).transpose()?;
+    parse_value(tokens, token)
+}
+
+// Parses a token into a value
+fn parse_value(tokens: &mut Lexer<'_>, token: Option<TokenData>) -> Result<NbtTag, SnbtError> {
+    match token {
+        // Open curly brace indicates a compound tag is present
+        #[rustfmt::skip]
+        Some(
+            td @ TokenData {
+                token: Token::OpenCurly,
+                ..
+            },
+        ) => tokens
+            .nested(&td, |tokens| parse_compound_tag(tokens, &td))
+            .map(|(tag, _)| tag.into()),
+
+        // Open square brace indicates that some kind of list is present
+        #[rustfmt::skip]
+        Some(
+            td @ TokenData {
+                token: Token::OpenSquare,
+                ..
+            },
+        ) => tokens.nested(&td, |tokens| parse_list(tokens, &td)),
+
+        // Could be a value token or delimiter token
+        Some(td) => match td.into_tag() {
+            Ok(tag) => Ok(tag),
+            Err(td) => Err(SnbtError::unexpected_token(tokens.raw, Some(&td), "value")),
+        },
+
+        // We expected a value but ran out of data
+        None => Err(SnbtError::unexpected_eos("value")),
+    }
+}
+
+// Parses a list, which can be either a generic tag list or vector of primitives
+fn parse_list(tokens: &mut Lexer<'_>, open_square: &TokenData) -> Result<NbtTag, SnbtError> {
+    const DELIMITER: Option<fn(char) -> bool> = Some(|ch| matches!(ch, ',' | ']' | ';'));
+
+    match tokens.next(DELIMITER).transpose()? {
+        // Empty list ('[]') with no type specifier is treated as an empty NBT tag list
+        Some(TokenData {
+            token: Token::ClosedSquare,
+            ..
+        }) => Ok(NbtList::new().into()),
+
+        // A string as the first "element" can either be a type specifier such as in [I; 1, 2], or
+        // a regular string in a tag list, such as in ['i', 'j', 'k'].
+        Some(TokenData {
+            token:
+                Token::String {
+                    value: string,
+                    quoted,
+                },
+            index,
+            char_width,
+        }) => {
+            // Peek at the next token to see if it's a semicolon, which would indicate a primitive vector
+            let vanilla_strict = tokens.options.vanilla_strict;
+            match tokens.peek(DELIMITER) {
+                // The game only recognizes a type specifier directly between the bracket and the
+                // semicolon, and otherwise reads it as a list element
+                Some(Ok(TokenData {
+                    token: Token::Semicolon,
+                    index: semicolon,
+                    ..
+                })) if vanilla_strict
+                    && !(index == open_square.index + 1
+                        && *semicolon == index + 1
+                        && matches!(string.as_str(), "B" | "I" | "L")) =>
+                    parse_tag_list(tokens, string_tag(string, quoted)).map(Into::into),
+
+                // Parse as a primitive vector
+                Some(Ok(TokenData {
+                    token: Token::Semicolon,
+                    ..
+                })) => {
+                    if quoted {
+                        return Err(SnbtError::unexpected_token_at(
+                            tokens.raw,
+                            index,
+                            char_width,
+                            "'B', 'I', or 'L'",
+                        ));
+                    }
+
+                    // Moves past the peeked semicolon
+                    tokens.next(None);
+
+                    // Determine the primitive type and parse it
+                    match string.as_str() {
+                        "b" | "B" => parse_prim_list::<u8>(tokens, open_square, |token| {
+                            matches!(token, Token::Byte(_))
+                        }),
+                        "i" | "I" => parse_prim_list::<i32>(tokens, open_square, |token| {
+                            matches!(token, Token::Int(_))
+                        }),
+                        "l" | "L" => parse_prim_list::<i64>(tokens, open_square, |token| {
+                            matches!(token, Token::Long(_))
+                        }),
+                        _ => Err(SnbtError::unexpected_token_at(
+                            tokens.raw,
+                            index,
+                            char_width,
+                            "'B', 'I', or 'L'",
+                        )),
+                    }
+                }
+
+                // Parse as a tag list (token errors are delegated to this function)
+                _ => parse_tag_list(tokens, string_tag(string, quoted)).map(Into::into),
+            }
+        }
+
+        // Any other pattern is delegated to the general tag list parser
+        td => {
+            let first_element = parse_value(tokens, td)?;
+            parse_tag_list(tokens, first_element).map(Into::into)
+        }
+    }
+}
+
+// Converts a string token into a tag. Unquoted boolean literals are read as bytes, as they are by
+// the game.
+fn string_tag(value: String, quoted: bool) -> NbtTag {
+    if !quoted {
+        if value.eq_ignore_ascii_case("true") {
+            return NbtTag::Byte(1);
+        } else if value.eq_ignore_ascii_case("false") {
+            return NbtTag::Byte(0);
+        }
+    }
+
+    NbtTag::String(value)
+}
+
+// The game requires the elements of arrays to have the matching suffix, which is checked with the
+// given function in strict mode
+fn parse_prim_list<'a, T>(
+    tokens: &mut Lexer<'a>,
+    open_square: &TokenData,
+    is_vanilla_element: fn(&Token) -> bool,
+) -> Result<NbtTag, SnbtError>
+where
+    Token: Into<Result<T, Token>>,
+    NbtTag: From<Vec<T>>,
+{
+    let mut list: Vec<T> = Vec::new();
+    // Zero is used as a niche value so the first iteration of the loop runs correctly
+    let mut comma: Option<usize> = Some(0);
+
+    loop {
+        match tokens.next(Some(|ch| ch == ',' || ch == ']')).transpose()? {
+            // Finish off the list
+            Some(TokenData {
+                token: Token::ClosedSquare,
+                ..
+            }) => match comma {
+                Some(0) | None => return Ok(list.into()),
+                // The game allows trailing commas
+                Some(_) if tokens.options.vanilla_strict => return Ok(list.into()),
+                Some(index) => return Err(SnbtError::trailing_comma(tokens.raw, index)),
+            },
+
+            // The game requires a value before each comma
+            Some(
+                td @ TokenData {
+                    token: Token::Comma,
+                    ..
+                },
+            ) if tokens.options.vanilla_strict && comma.is_some() =>
+                return Err(SnbtError::unexpected_token(tokens.raw, Some(&td), "value")),
+
+            // Indicates another value should be parsed
+            Some(TokenData {
+                token: Token::Comma,
+                index,
+                ..
+            }) => comma = Some(index),
+
+            // Attempt to convert the token into a value
+            Some(td) => {
+                // Make sure a value was expected
+                match comma {
+                    Some(_) => {
+                        // The game reads boolean literals as bytes, even within arrays
+                        let td = match td.token {
+                            Token::String {
+                                ref value,
+                                quoted: false,
+                            } if tokens.options.vanilla_strict =>
+                                match string_tag(value.clone(), false) {
+                                    NbtTag::Byte(value) => TokenData::new(
+                                        Token::Byte(value.into()),
+                                        td.index,
+                                        td.char_width,
+                                    ),
+                                    _ => td,
+                                },
+                            _ => td,
+                        };
+
+                        if tokens.options.vanilla_strict && !is_vanilla_element(&td.token) {
+                            return Err(SnbtError::non_homogenous_list(
+                                tokens.raw,
+                                td.index,
+                                td.char_width,
+                            ));
+                        }
+
+                        match td.into_value::<T>() {
+                            Ok(value) => list.push(value),
+                            Err(td) if td.token.is_integer() =>
+                                return Err(SnbtError::invalid_number(
+                                    tokens.raw,
+                                    td.index,
+                                    td.char_width,
+                                )),
+                            Err(td) =>
+                                return Err(SnbtError::non_homogenous_list(
+                                    tokens.raw,
+                                    td.index,
+                                    td.char_width,
+                                )),
+                        }
+
+                        comma = None;
+                    }
+
+                    None =>
+                        return Err(SnbtError::unexpected_token(
+                            tokens.raw,
+                            Some(&td),
+                            Token::Comma.as_expectation(),
+                        )),
+                }
+            }
+
+            None => return Err(SnbtError::unmatched_brace(tokens.raw, open_square.index)),
+        }
+    }
+}
+
+fn parse_tag_list(tokens: &mut Lexer<'_>, first_element: NbtTag) -> Result<NbtList, SnbtError> {
+    const DELIMITER: Option<fn(char) -> bool> = Some(|ch| ch == ',' || ch == ']');
+
+    // Construct the list and use the first element to determine the list's type
+    let mut list = NbtList::new();
+    let descrim = mem::discriminant(&first_element);
+    let is_number = |tag: &NbtTag| tag.kind().id() <= NbtTagKind::Double.id();
+    let first_is_number = is_number(&first_element);
+    list.push(first_element);
+
+    // The first element of a different kind, if numbers are being widened
+    let mut mismatch = None;
+
+    loop {
+        // No delimiter needed since we only expect ']' and ','
+        match tokens.next(None).transpose()? {
+            // Finish off the list
+            Some(TokenData {
+                token: Token::ClosedSquare,
+                ..
+            }) =>
+                return match mismatch {
+                    Some((index, char_width)) => match list.normalize_numeric() {
+                        Ok(()) => Ok(list),
+                        Err(_) => Err(SnbtError::non_homogenous_list(
+                            tokens.raw, index, char_width,
+                        )),
+                    },
+                    None => Ok(list),
+                },
+
+            // Indicates another value should be parsed
+            Some(TokenData {
+                token: Token::Comma,
+                ..
+            }) => {
+                let (index, char_width) = match tokens.peek(DELIMITER) {
+                    Some(&Ok(TokenData {
+                        index, char_width, ..
+                    })) => (index, char_width),
+                    _ => (0, 0),
+                };
+
+                // The game allows trailing commas
+                if tokens.options.vanilla_strict
+                    && matches!(
+                        tokens.peek(DELIMITER),
+                        Some(Ok(TokenData {
+                            token: Token::ClosedSquare,
+                            ..
+                        }))
+                    )
+                {
+                    continue;
+                }
+
+                let element = parse_next_value(tokens, DELIMITER)?;
+
+                // Ensure type homogeneity
+                if mem::discriminant(&element) != descrim {
+                    if tokens.options.widen_numeric_lists && first_is_number && is_number(&element)
+                    {
+                        mismatch.get_or_insert((index, char_width));
+                    } else {
+                        return Err(SnbtError::non_homogenous_list(
+                            tokens.raw, index, char_width,
+                        ));
+                    }
+                }
+
+                list.push(element);
+            }
+
+            // Some invalid token
+            td =>
+                return Err(SnbtError::unexpected_token(
+                    tokens.raw,
+                    td.as_ref(),
+                    "',' or ']'",
+                )),
+        }
+    }
+}
+
+// Parses a single compound key in the same way as keys are read within a compound
+pub(crate) fn parse_key(input: &str) -> Result<String, SnbtError> {
+    let mut tokens = Lexer::new(input, ParseOptions::new());
+    let key = match tokens.next(Some(|ch| ch == ':')).transpose()? {
+        Some(TokenData {
+            token: Token::String { value, .. },
+            ..
+        }) => value,
+        token =>
+            return Err(SnbtError::unexpected_token(
+                input,
+                token.as_ref(),
+                "compound key",
+            )),
+    };
+
+    match tokens.next(Some(|ch| ch == ':')).transpose()? {
+        Some(token) => Err(SnbtError::unexpected_token(
+            input,
+            Some(&token),
+            "end of key",
+        )),
+        None => Ok(key),
+    }
+}
+
+fn parse_compound_tag<'a>(
+    tokens: &mut Lexer<'a>,
+    open_curly: &TokenData,
+) -> Result<(NbtCompound, usize), SnbtError> {
+    let mut compound = NbtCompound::new();
+    // Zero is used as a niche value so the first iteration of the loop runs correctly
+    let mut comma: Option<usize> = Some(0);
+    // The span of the latest occurrence of each key, only tracked if duplicates are reported
+    let mut key_spans = (tokens.options.deny_duplicate_keys || tokens.duplicate_keys.is_some())
+        .then(HashMap::<String, Range<usize>>::new);
+
+    loop {
+        let mut token = tokens.next(Some(|ch| ch == ':')).transpose()?;
+
+        // The game reads numbers in place of keys as strings, which in strict mode only contain
+        // ASCII characters
+        if tokens.options.vanilla_strict {
+            if let Some(td) = &mut token {
+                if let Token::Byte(_)
+                | Token::Short(_)
+                | Token::Int(_)
+                | Token::Long(_)
+                | Token::Float(_)
+                | Token::Double(_) = td.token
+                {
+                    td.token = Token::String {
+                        value: tokens.raw[td.index .. td.index + td.char_width].to_owned(),
+                        quoted: false,
+                    };
+                }
+            }
+        }
+
+        match token {
+            // Finish off the compound tag
+            Some(TokenData {
+                token: Token::ClosedCurly,
+                ..
+            }) => {
+                match comma {
+                    // First loop iteration or no comma
+                    Some(0) | None => return Ok((compound, tokens.index)),
+                    // The game allows trailing commas
+                    Some(_) if tokens.options.vanilla_strict =>
+                        return Ok((compound, tokens.index)),
+                    // Later iteration with a trailing comma
+                    Some(index) => return Err(SnbtError::trailing_comma(tokens.raw, index)),
+                }
+            }
+
+            // The game does not allow empty keys, even if they are quoted
+            Some(TokenData {
+                token: Token::String { value: ref key, .. },
+                index,
+                char_width,
+            }) if tokens.options.vanilla_strict && key.is_empty() =>
+                return Err(SnbtError::unexpected_token_at(
+                    tokens.raw,
+                    index,
+                    char_width,
+                    "compound key",
+                )),
+
+            // Parse a new key-value pair
+            Some(TokenData {
+                token: Token::String { value: key, .. },
+                index,
+                char_width,
+            }) => {
+                match comma {
+                    // First loop iteration or a comma indicated that more data is present
+                    Some(_) => {
+                        if let Some(key_spans) = &mut key_spans {
+                            let span = index .. char_end(tokens.raw, index, char_width);
+                            if let Some(first) = key_spans.insert(key.clone(), span.clone()) {
+                                if tokens.options.deny_duplicate_keys {
+                                    return Err(SnbtError::duplicate_key(
+                                        tokens.raw,
+                                        index,
+                                        char_width,
+                                        first.start,
+                                    ));
+                                }
+                                if let Some(duplicate_keys) = &mut tokens.duplicate_keys {
+                                    duplicate_keys.push(DuplicateKey {
+                                        key: key.clone(),
+                                        first,
+                                        second: span,
+                                    });
+                                }
+                            }
+                        }
+
+                        tokens.assert_next(Token::Colon)?;
+                        let value = parse_next_value(tokens, Some(|ch| ch == ',' || ch == '}'))?;
+                        #[cfg(feature = "tracing")]
+                        if compound.contains_key::<str>(&key) {
+                            crate::trace::duplicate_key(&key);
+                        }
+                        compound.insert(key, value);
+                        comma = None;
+                    }
+
+                    // There was not a comma before this string so therefore the token is unexpected
+                    None =>
+                        return Err(SnbtError::unexpected_token_at(
+                            tokens.raw,
+                            index,
+                            char_width,
+                            Token::Comma.as_expectation(),
+                        )),
+                }
+            }
+
+            // The game requires a key-value pair before each comma
+            Some(
+                td @ TokenData {
+                    token: Token::Comma,
+                    ..
+                },
+            ) if tokens.options.vanilla_strict && comma.is_some() =>
+                return Err(SnbtError::unexpected_token(
+                    tokens.raw,
+                    Some(&td),
+                    "compound key",
+                )),
+
+            // Denote that another key-value pair is anticipated
+            Some(TokenData {
+                token: Token::Comma,
+                index,
+                ..
+            }) => comma = Some(index),
+
+            // Catch-all for unexpected tokens
+            Some(td) =>
+                return Err(SnbtError::unexpected_token(
+                    tokens.raw,
+                    Some(&td),
+                    "compound key, '}', or ','",
+                )),
+
+            // End of file / unmatched brace
+            None => return Err(SnbtError::unmatched_brace(tokens.raw, open_curly.index)),
+        }
+    }
+}
+
+// The functions below write the binary form of SNBT as it is parsed, mirroring `parse_value` and
+// the functions it calls. The header passed to `emit_value` is called with the ID of the value
+// before its payload is written, which is how compounds write the ID and key of each entry, and how
+// lists determine and check the type of their elements.
+type Header<'h, W> = dyn FnMut(&mut W, u8) -> Result<(), NbtIoError> + 'h;
+
+fn emit_value<W: Write + Seek>(
+    tokens: &mut Lexer<'_>,
+    writer: &mut W,
+    token: Option<TokenData>,
+    header: &mut Header<'_, W>,
+) -> Result<(), NbtIoError> {
+    match token {
+        #[rustfmt::skip]
+        Some(
+            td @ TokenData {
+                token: Token::OpenCurly,
+                ..
+            },
+        ) => tokens.nested(&td, |tokens| {
+            header(writer, 0xA)?;
+            emit_compound(tokens, writer, &td)
+        }),
+
+        #[rustfmt::skip]
+        Some(
+            td @ TokenData {
+                token: Token::OpenSquare,
+                ..
+            },
+        ) => tokens.nested(&td, |tokens| emit_list(tokens, writer, &td, header)),
+
+        Some(td) => match td.into_tag() {
+            Ok(tag) => {
+                header(writer, raw::id_for_tag(Some(&tag)))?;
+                io::write_tag_body(writer, &tag)
+            }
+            Err(td) => Err(SnbtError::unexpected_token(tokens.raw, Some(&td), "value").into()),
+        },
+
+        None => Err(SnbtError::unexpected_eos("value").into()),
+    }
+}
+
+fn emit_list<W: Write + Seek>(
+    tokens: &mut Lexer<'_>,
+    writer: &mut W,
+    open_square: &TokenData,
+    header: &mut Header<'_, W>,
+) -> Result<(), NbtIoError> {
+    const DELIMITER: Option<fn(char) -> bool> = Some(|ch| matches!(ch, ',' | ']' | ';'));
+
+    match tokens.next(DELIMITER).transpose()? {
+        Some(TokenData {
+            token: Token::ClosedSquare,
+            ..
+        }) => {
+            header(writer, 0x9)?;
+            writer.write_all(&[0; 5])?;
+            Ok(())
+        }
+
+        Some(TokenData {
+            token:
+                Token::String {
+                    value: string,
+                    quoted,
+                },
+            index,
+            char_width,
+        }) => match tokens.peek(DELIMITER) {
+            Some(Ok(TokenData {
+                token: Token::Semicolon,
+                ..
+            })) => {
+                if quoted {
+                    return Err(SnbtError::unexpected_token_at(
+                        tokens.raw,
+                        index,
+                        char_width,
+                        "'B', 'I', or 'L'",
+                    )
+                    .into());
+                }
+
+                // Moves past the peeked semicolon
+                tokens.next(None);
+
+                match string.as_str() {
+                    "b" | "B" => {
+                        header(writer, 0x7)?;
+                        emit_prim_list::<u8, W>(tokens, writer, open_square, raw::write_u8)
+                    }
+                    "i" | "I" => {
+                        header(writer, 0xB)?;
+                        emit_prim_list::<i32, W>(tokens, writer, open_square, raw::write_i32)
                     }
+                    "l" | "L" => {
+                        header(writer, 0xC)?;
+                        emit_prim_list::<i64, W>(tokens, writer, open_square, raw::write_i64)
+                    }
+                    _ => Err(SnbtError::unexpected_token_at(
+                        tokens.raw,
+                        index,
+                        char_width,
+                        "'B', 'I', or 'L'",
+                    )
+                    .into()),
                 }
+            }
 
-                // Parse as a tag list (token errors are delegated to this function)
-                _ => parse_tag_list(tokens, NbtTag::String(string)).map(Into::into),
+            _ => {
+                header(writer, 0x9)?;
+                let first_element = TokenData::new(
+                    Token::String {
+                        value: string,
+                        quoted,
+                    },
+                    index,
+                    char_width,
+                );
+                emit_tag_list(tokens, writer, Some(first_element))
             }
-        }
+        },
 
-        // Any other pattern is delegated to the general tag list parser
         td => {
-            let first_element = parse_value(tokens, td)?;
-            parse_tag_list(tokens, first_element).map(Into::into)
+            header(writer, 0x9)?;
+            emit_tag_list(tokens, writer, td)
         }
     }
 }
 
-fn parse_prim_list<'a, T>(
-    tokens: &mut Lexer<'a>,
+fn emit_prim_list<T, W: Write + Seek>(
+    tokens: &mut Lexer<'_>,
+    writer: &mut W,
     open_square: &TokenData,
-) -> Result<NbtTag, SnbtError>
+    write: fn(&mut W, T) -> std::io::Result<()>,
+) -> Result<(), NbtIoError>
 where
     Token: Into<Result<T, Token>>,
-    NbtTag: From<Vec<T>>,
 {
-    let mut list: Vec<T> = Vec::new();
+    let start = writer.stream_position()?;
+    writer.write_all(&[0; 4])?;
+    let mut len = 0usize;
     // Zero is used as a niche value so the first iteration of the loop runs correctly
     let mut comma: Option<usize> = Some(0);
 
     loop {
         match tokens.next(Some(|ch| ch == ',' || ch == ']')).transpose()? {
-            // Finish off the list
             Some(TokenData {
                 token: Token::ClosedSquare,
                 ..
             }) => match comma {
-                Some(0) | None => return Ok(list.into()),
-                Some(index) => return Err(SnbtError::trailing_comma(tokens.raw, index)),
+                Some(0) | None => break,
+                Some(index) => return Err(SnbtError::trailing_comma(tokens.raw, index).into()),
             },
 
-            // Indicates another value should be parsed
             Some(TokenData {
                 token: Token::Comma,
                 index,
                 ..
             }) => comma = Some(index),
 
-            // Attempt to convert the token into a value
-            Some(td) => {
-                // Make sure a value was expected
-                match comma {
-                    Some(_) => {
-                        match td.into_value::<T>() {
-                            Ok(value) => list.push(value),
-                            Err(td) =>
-                                return Err(SnbtError::non_homogenous_list(
-                                    tokens.raw,
-                                    td.index,
-                                    td.char_width,
-                                )),
-                        }
-
-                        comma = None;
+            Some(td) => match comma {
+                Some(_) => {
+                    match td.into_value::<T>() {
+                        Ok(value) => write(writer, value)?,
+                        Err(td) if td.token.is_integer() =>
+                            return Err(SnbtError::invalid_number(
+                                tokens.raw,
+                                td.index,
+                                td.char_width,
+                            )
+                            .into()),
+                        Err(td) =>
+                            return Err(SnbtError::non_homogenous_list(
+                                tokens.raw,
+                                td.index,
+                                td.char_width,
+                            )
+                            .into()),
                     }
 
-                    None =>
-                        return Err(SnbtError::unexpected_token(
-                            tokens.raw,
-                            Some(&td),
-                            Token::Comma.as_expectation(),
-                        )),
+                    len += 1;
+                    comma = None;
                 }
-            }
 
-            None => return Err(SnbtError::unmatched_brace(tokens.raw, open_square.index)),
+                None =>
+                    return Err(SnbtError::unexpected_token(
+                        tokens.raw,
+                        Some(&td),
+                        Token::Comma.as_expectation(),
+                    )
+                    .into()),
+            },
+
+            None => return Err(SnbtError::unmatched_brace(tokens.raw, open_square.index).into()),
         }
     }
+
+    let len = raw::checked_len(len)?;
+    patch_at(writer, start, |writer| raw::write_i32(writer, len))
 }
 
-fn parse_tag_list(tokens: &mut Lexer<'_>, first_element: NbtTag) -> Result<NbtList, SnbtError> {
+fn emit_tag_list<W: Write + Seek>(
+    tokens: &mut Lexer<'_>,
+    writer: &mut W,
+    first_element: Option<TokenData>,
+) -> Result<(), NbtIoError> {
     const DELIMITER: Option<fn(char) -> bool> = Some(|ch| ch == ',' || ch == ']');
 
-    // Construct the list and use the first element to determine the list's type
-    let mut list = NbtList::new();
-    let descrim = mem::discriminant(&first_element);
-    list.push(first_element);
+    // The type and length of the list are filled in once its end is reached
+    let start = writer.stream_position()?;
+    writer.write_all(&[0; 5])?;
+
+    // The first element determines the list's type
+    let mut list_type = 0;
+    emit_value(tokens, writer, first_element, &mut |_, tag_id| {
+        list_type = tag_id;
+        Ok(())
+    })?;
+    let mut len = 1usize;
 
     loop {
-        // No delimiter needed since we only expect ']' and ','
         match tokens.next(None).transpose()? {
-            // Finish off the list
             Some(TokenData {
                 token: Token::ClosedSquare,
                 ..
-            }) => return Ok(list),
+            }) => break,
 
-            // Indicates another value should be parsed
             Some(TokenData {
                 token: Token::Comma,
                 ..
@@ -255,118 +2240,152 @@ fn parse_tag_list(tokens: &mut Lexer<'_>, first_element: NbtTag) -> Result<NbtLi
                     })) => (index, char_width),
                     _ => (0, 0),
                 };
-                let element = parse_next_value(tokens, DELIMITER)?;
+                let raw = tokens.raw;
+                let token = tokens.next(DELIMITER).transpose()?;
 
                 // Ensure type homogeneity
-                if mem::discriminant(&element) != descrim {
-                    return Err(SnbtError::non_homogenous_list(
-                        tokens.raw, index, char_width,
-                    ));
-                }
-
-                list.push(element);
+                emit_value(tokens, writer, token, &mut |_, tag_id| {
+                    if tag_id == list_type {
+                        Ok(())
+                    } else {
+                        Err(SnbtError::non_homogenous_list(raw, index, char_width).into())
+                    }
+                })?;
+                len += 1;
             }
 
-            // Some invalid token
             td =>
-                return Err(SnbtError::unexpected_token(
-                    tokens.raw,
-                    td.as_ref(),
-                    "',' or ']'",
-                )),
+                return Err(
+                    SnbtError::unexpected_token(tokens.raw, td.as_ref(), "',' or ']'").into(),
+                ),
         }
     }
+
+    let len = raw::checked_len(len)?;
+    patch_at(writer, start, |writer| {
+        raw::write_u8(writer, list_type)?;
+        raw::write_i32(writer, len)
+    })
 }
 
-fn parse_compound_tag<'a>(
-    tokens: &mut Lexer<'a>,
+fn emit_compound<W: Write + Seek>(
+    tokens: &mut Lexer<'_>,
+    writer: &mut W,
     open_curly: &TokenData,
-) -> Result<(NbtCompound, usize), SnbtError> {
-    let mut compound = NbtCompound::new();
+) -> Result<(), NbtIoError> {
     // Zero is used as a niche value so the first iteration of the loop runs correctly
     let mut comma: Option<usize> = Some(0);
 
     loop {
         match tokens.next(Some(|ch| ch == ':')).transpose()? {
-            // Finish off the compound tag
             Some(TokenData {
                 token: Token::ClosedCurly,
                 ..
-            }) => {
-                match comma {
-                    // First loop iteration or no comma
-                    Some(0) | None => return Ok((compound, tokens.index)),
-                    // Later iteration with a trailing comma
-                    Some(index) => return Err(SnbtError::trailing_comma(tokens.raw, index)),
-                }
-            }
+            }) => match comma {
+                Some(0) | None => return raw::write_u8(writer, 0x0).map_err(Into::into),
+                Some(index) => return Err(SnbtError::trailing_comma(tokens.raw, index).into()),
+            },
 
-            // Parse a new key-value pair
             Some(TokenData {
                 token: Token::String { value: key, .. },
                 index,
                 char_width,
-            }) => {
-                match comma {
-                    // First loop iteration or a comma indicated that more data is present
-                    Some(_) => {
-                        tokens.assert_next(Token::Colon)?;
-                        compound.insert(
-                            key,
-                            parse_next_value(tokens, Some(|ch| ch == ',' || ch == '}'))?,
-                        );
-                        comma = None;
-                    }
-
-                    // There was not a comma before this string so therefore the token is unexpected
-                    None =>
-                        return Err(SnbtError::unexpected_token_at(
-                            tokens.raw,
-                            index,
-                            char_width,
-                            Token::Comma.as_expectation(),
-                        )),
+            }) => match comma {
+                Some(_) => {
+                    tokens.assert_next(Token::Colon)?;
+                    let token = tokens.next(Some(|ch| ch == ',' || ch == '}')).transpose()?;
+                    emit_value(tokens, writer, token, &mut |writer, tag_id| {
+                        raw::write_u8(writer, tag_id)?;
+                        raw::write_string(writer, &key)?;
+                        Ok(())
+                    })?;
+                    comma = None;
                 }
-            }
 
-            // Denote that another key-value pair is anticipated
+                None =>
+                    return Err(SnbtError::unexpected_token_at(
+                        tokens.raw,
+                        index,
+                        char_width,
+                        Token::Comma.as_expectation(),
+                    )
+                    .into()),
+            },
+
             Some(TokenData {
                 token: Token::Comma,
                 index,
                 ..
             }) => comma = Some(index),
 
-            // Catch-all for unexpected tokens
             Some(td) =>
                 return Err(SnbtError::unexpected_token(
                     tokens.raw,
                     Some(&td),
                     "compound key, '}', or ','",
-                )),
+                )
+                .into()),
 
-            // End of file / unmatched brace
-            None => return Err(SnbtError::unmatched_brace(tokens.raw, open_curly.index)),
+            None => return Err(SnbtError::unmatched_brace(tokens.raw, open_curly.index).into()),
         }
     }
 }
 
+// Overwrites the placeholder at the given position using the given function, then returns to the
+// current position
+fn patch_at<W: Write + Seek>(
+    writer: &mut W,
+    position: u64,
+    patch: impl FnOnce(&mut W) -> std::io::Result<()>,
+) -> Result<(), NbtIoError> {
+    let end = writer.stream_position()?;
+    writer.seek(SeekFrom::Start(position))?;
+    patch(writer)?;
+    writer.seek(SeekFrom::Start(end))?;
+    Ok(())
+}
+
 struct Lexer<'a> {
     raw: &'a str,
     chars: Peekable<CharIndices<'a>>,
     index: usize,
     raw_token_buffer: Cow<'a, str>,
     peeked: Option<Option<Result<TokenData, SnbtError>>>,
+    depth: usize,
+    options: ParseOptions,
+    // Only collected by parse_with_duplicate_keys
+    duplicate_keys: Option<Vec<DuplicateKey>>,
 }
 
 impl<'a> Lexer<'a> {
-    fn new(raw: &'a str) -> Self {
+    fn new(raw: &'a str, options: ParseOptions) -> Self {
         Lexer {
             raw,
             chars: raw.char_indices().peekable(),
             index: 0,
             raw_token_buffer: Cow::Owned(String::new()),
             peeked: None,
+            depth: 0,
+            options,
+            duplicate_keys: None,
+        }
+    }
+
+    // Parses the list or compound opened by the given token with the given function, tracking how
+    // deeply lists and compounds are nested
+    fn nested<T, E: From<SnbtError>>(
+        &mut self,
+        open: &TokenData,
+        parse: impl FnOnce(&mut Self) -> Result<T, E>,
+    ) -> Result<T, E> {
+        if self.depth >= MAX_DEPTH {
+            return Err(SnbtError::exceeded_max_depth(self.raw, open.index).into());
         }
+
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
     }
 
     fn peek(
@@ -444,6 +2463,16 @@ impl<'a> Lexer<'a> {
         }
     }
 
+    // Fails if the token starting at the given offset has grown beyond the maximum length
+    #[inline]
+    fn check_token_len(&self, start: usize) -> Result<(), SnbtError> {
+        match self.options.max_token_len {
+            Some(max) if self.index - start > max =>
+                Err(SnbtError::exceeded_max_token_len(self.raw, start, max)),
+            _ => Ok(()),
+        }
+    }
+
     // Collects a token from the character iterator
     fn slurp_token(&mut self, delimiter: Option<fn(char) -> bool>) -> Result<TokenData, SnbtError> {
         let start = self.index;
@@ -465,6 +2494,21 @@ impl<'a> Lexer<'a> {
         };
 
         match state {
+            // The game only allows a restricted set of characters in unquoted strings
+            State::Unquoted if self.options.vanilla_strict => {
+                if !is_vanilla_unquoted(ch0) {
+                    return Err(SnbtError::unexpected_token_at(self.raw, start, 1, "value"));
+                }
+
+                while self.peek_ch().is_some_and(is_vanilla_unquoted) {
+                    self.next_ch();
+                    char_width += 1;
+                    self.check_token_len(start)?;
+                }
+
+                self.raw_token_buffer = Cow::Borrowed(&self.raw[start .. self.index]);
+            }
+
             State::Unquoted => {
                 // Last non-whitespace character and its index
                 let mut last_nws_char = ch0;
@@ -476,6 +2520,8 @@ impl<'a> Lexer<'a> {
                 let mut quotes = 0;
 
                 loop {
+                    self.check_token_len(start)?;
+
                     match self.peek_ch() {
                         // No characters left means we just finish the token
                         None => break,
@@ -520,15 +2566,9 @@ impl<'a> Lexer<'a> {
                             if (quotes & 0b11) == 0 {
                                 match ch {
                                     '{' => curly_count += 1,
-                                    '}' =>
-                                        if curly_count > 0 {
-                                            curly_count -= 1;
-                                        },
+                                    '}' if curly_count > 0 => curly_count -= 1,
                                     '[' => square_count += 1,
-                                    ']' =>
-                                        if square_count > 0 {
-                                            square_count -= 1;
-                                        },
+                                    ']' if square_count > 0 => square_count -= 1,
                                     _ => {}
                                 }
                             }
@@ -574,6 +2614,7 @@ impl<'a> Lexer<'a> {
                 }
 
                 loop {
+                    self.check_token_len(start)?;
                     char_width += 1;
 
                     match self.next_ch() {
@@ -590,6 +2631,16 @@ impl<'a> Lexer<'a> {
 
                             // Handle escape characters
                             match self.next_ch() {
+                                // The game only allows backslashes and the enclosing quote to be
+                                // escaped
+                                Some(ch)
+                                    if self.options.vanilla_strict && ch != '\\' && ch != ch0 =>
+                                    return Err(SnbtError::unknown_escape_sequence(
+                                        self.raw,
+                                        self.index - ch.len_utf8() - 1,
+                                        2,
+                                    )),
+
                                 // These are just directly quoted
                                 Some(ch @ ('\'' | '"' | '\\')) =>
                                     self.raw_token_buffer.to_mut().push(ch),
@@ -613,7 +2664,7 @@ impl<'a> Lexer<'a> {
                                                 "four-character hex unicode value",
                                             ))?;
 
-                                        if !ch.is_digit(16) {
+                                        if !ch.is_ascii_hexdigit() {
                                             return Err(SnbtError::unexpected_token_at(
                                                 self.raw,
                                                 self.index - ch.len_utf8(),
@@ -632,8 +2683,7 @@ impl<'a> Lexer<'a> {
                                         16,
                                     )
                                     .ok()
-                                    .map(char::from_u32)
-                                    .flatten()
+                                    .and_then(char::from_u32)
                                     .ok_or_else(|| {
                                         SnbtError::unknown_escape_sequence(
                                             self.raw,
@@ -646,10 +2696,10 @@ impl<'a> Lexer<'a> {
                                 }
 
                                 // Unknown sequence
-                                Some(_) => {
+                                Some(ch) => {
                                     return Err(SnbtError::unknown_escape_sequence(
                                         self.raw,
-                                        self.index - 2,
+                                        self.index - ch.len_utf8() - 1,
                                         2,
                                     ));
                                 }
@@ -723,10 +2773,17 @@ impl<'a> Lexer<'a> {
                     2,
                 )),
         };
-        let last = token_string.chars().rev().next().unwrap();
+
+        if (self.options.vanilla_strict || self.options.vanilla_numbers) && !quoted {
+            let token = vanilla_number(&token_string).unwrap_or_else(|| Token::String {
+                value: token_string.into_owned(),
+                quoted,
+            });
+            return Ok(TokenData::new(token, start, char_width));
+        }
 
         // Identify if the token is not a number (a string)
-        if quoted || !(first == '-' || (first.is_ascii() && first.is_numeric())) {
+        if quoted || !(matches!(first, '-' | '+' | '.') || first.is_ascii_digit()) {
             return Ok(TokenData::new(
                 Token::String {
                     value: token_string.into_owned(),
@@ -737,61 +2794,144 @@ impl<'a> Lexer<'a> {
             ));
         }
 
-        // Determine whether to parse as an integer or decimal
-        if token_string.contains('.') {
-            // Parse with highest precision ignoring the type suffix
-            let value: Option<f64> = match last {
-                'f' | 'F' | 'd' | 'D' => token_string[.. token_string.len() - 1].parse().ok(),
-                _ =>
-                    if last.is_numeric() {
-                        token_string.parse().ok()
-                    } else {
-                        None
-                    },
-            };
-
-            // Apply the type suffix if it is valid
-            match value {
-                Some(value) => match last {
-                    'f' | 'F' => Ok(TokenData::new(Token::Float(value), start, char_width)),
-                    _ => Ok(TokenData::new(Token::Double(value), start, char_width)),
+        match lenient_number(&token_string) {
+            Some(Some(token)) => Ok(TokenData::new(token, start, char_width)),
+            // Integers which do not fit in the type given by their suffix
+            Some(None) => Err(SnbtError::invalid_number(self.raw, start, char_width)),
+            // Tokens such as `+` and `.5.5` which start with a sign or point but are not numbers
+            // are read as strings, as the game does
+            None if matches!(first, '+' | '.') => Ok(TokenData::new(
+                Token::String {
+                    value: token_string.into_owned(),
+                    quoted,
                 },
-                _ => Err(SnbtError::invalid_number(self.raw, start, char_width)),
-            }
-        } else {
-            // Parse with highest precision ignoring the type suffix
-            let value: Option<i64> = match last {
-                'b' | 'B' | 's' | 'S' | 'l' | 'L' | 'f' | 'F' | 'd' | 'D' =>
-                    token_string[.. token_string.len() - 1].parse().ok(),
-                _ =>
-                    if last.is_numeric() {
-                        token_string.parse().ok()
-                    } else {
-                        None
-                    },
-            };
+                start,
+                char_width,
+            )),
+            None => Err(SnbtError::invalid_number(self.raw, start, char_width)),
+        }
+    }
+}
 
-            // Apply the type suffix if it is valid
-            match value {
-                Some(value) => match last {
-                    'b' | 'B' => Ok(TokenData::new(Token::Byte(value), start, char_width)),
-                    's' | 'S' => Ok(TokenData::new(Token::Short(value), start, char_width)),
-                    'l' | 'L' => Ok(TokenData::new(Token::Long(value), start, char_width)),
-                    'f' | 'F' => Ok(TokenData::new(
-                        Token::Float(value as f64),
-                        start,
-                        char_width,
-                    )),
-                    'd' | 'D' => Ok(TokenData::new(
-                        Token::Double(value as f64),
-                        start,
-                        char_width,
-                    )),
-                    _ => Ok(TokenData::new(Token::Int(value), start, char_width)),
-                },
-                _ => Err(SnbtError::invalid_number(self.raw, start, char_width)),
+// Reads an unquoted token as a number outside of strict mode. Unlike the game, leading zeros are
+// allowed, and integers which do not fit in the type given by their suffix are errors rather than
+// strings. Returns `None` if the token is not a number, and `Some(None)` if it is out of range.
+fn lenient_number(token: &str) -> Option<Option<Token>> {
+    let last = token.chars().next_back()?.to_ascii_lowercase();
+    let (number, suffix) = match last {
+        'b' | 's' | 'l' | 'f' | 'd' => (&token[.. token.len() - 1], Some(last)),
+        _ => (token, None),
+    };
+
+    // Only plain decimal digits, points, signs and exponents are accepted, so that `inf`, `NaN`
+    // and hexadecimal are never read as numbers
+    if !number
+        .bytes()
+        .all(|byte| byte.is_ascii_digit() || matches!(byte, b'.' | b'e' | b'E' | b'+' | b'-'))
+    {
+        return None;
+    }
+
+    // Parse with highest precision ignoring the type suffix
+    if number.contains('.') || matches!(suffix, Some('f' | 'd')) {
+        let value: f64 = number.parse().ok()?;
+        match suffix {
+            Some('f') => Some(Some(Token::Float(value))),
+            Some('d') | None => Some(Some(Token::Double(value))),
+            _ => None,
+        }
+    } else {
+        let value: i64 = match number.parse() {
+            Ok(value) => value,
+            Err(error)
+                if matches!(
+                    error.kind(),
+                    IntErrorKind::PosOverflow | IntErrorKind::NegOverflow
+                ) =>
+                return Some(None),
+            Err(_) => return None,
+        };
+        Some(match suffix {
+            Some('b') => i8::try_from(value)
+                .ok()
+                .map(|value| Token::Byte(value.into())),
+            Some('s') => i16::try_from(value)
+                .ok()
+                .map(|value| Token::Short(value.into())),
+            Some('l') => Some(Token::Long(value)),
+            _ => i32::try_from(value)
+                .ok()
+                .map(|value| Token::Int(value.into())),
+        })
+    }
+}
+
+// Returns whether the game allows the given character in unquoted strings
+fn is_vanilla_unquoted(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || matches!(ch, '_' | '-' | '.' | '+')
+}
+
+// Reads an unquoted token as a number in the same way as the game, returning `None` if the game
+// would read it as a string instead
+fn vanilla_number(token: &str) -> Option<Token> {
+    fn is_digits(string: &str) -> bool {
+        !string.is_empty() && string.bytes().all(|byte| byte.is_ascii_digit())
+    }
+
+    // Integers may not have leading zeros
+    fn is_integer(string: &str) -> bool {
+        string == "0" || (is_digits(string) && !string.starts_with('0'))
+    }
+
+    // Decimals may omit the digits on either side of the point, and have an exponent. Decimals
+    // with a suffix may also omit the point.
+    fn is_decimal(string: &str, suffixed: bool) -> bool {
+        let (mantissa, exponent) = match string.find(['e', 'E']) {
+            Some(index) => (&string[.. index], Some(&string[index + 1 ..])),
+            None => (string, None),
+        };
+
+        if let Some(exponent) = exponent {
+            if !is_digits(exponent.strip_prefix(['-', '+']).unwrap_or(exponent)) {
+                return false;
             }
         }
+
+        match mantissa.split_once('.') {
+            Some((integral, fraction)) =>
+                (is_digits(integral) && fraction.is_empty())
+                    || ((integral.is_empty() || is_digits(integral)) && is_digits(fraction)),
+            None => suffixed && is_digits(mantissa),
+        }
+    }
+
+    let (number, suffix) = match token.as_bytes().last()?.to_ascii_lowercase() {
+        suffix @ (b'b' | b's' | b'l' | b'f' | b'd') => (&token[.. token.len() - 1], Some(suffix)),
+        _ => (token, None),
+    };
+    let unsigned = number.strip_prefix(['-', '+']).unwrap_or(number);
+
+    match suffix {
+        Some(b'f') if is_decimal(unsigned, true) => number
+            .parse::<f32>()
+            .ok()
+            .map(|value| Token::Float(value.into())),
+        Some(b'd') if is_decimal(unsigned, true) => number.parse().ok().map(Token::Double),
+        Some(b'b') if is_integer(unsigned) => number
+            .parse::<i8>()
+            .ok()
+            .map(|value| Token::Byte(value.into())),
+        Some(b's') if is_integer(unsigned) => number
+            .parse::<i16>()
+            .ok()
+            .map(|value| Token::Short(value.into())),
+        Some(b'l') if is_integer(unsigned) => number.parse().ok().map(Token::Long),
+        None if is_integer(unsigned) => number
+            .parse::<i32>()
+            .ok()
+            .map(|value| Token::Int(value.into())),
+        None if is_decimal(unsigned, false) => number.parse().ok().map(Token::Double),
+        _ => None,
     }
 }
 
@@ -859,9 +2999,18 @@ impl Token {
         }
     }
 
+    // Returns whether this is an integer, which is only rejected as an array element if it is out
+    // of range
+    fn is_integer(&self) -> bool {
+        matches!(
+            self,
+            Token::Byte(_) | Token::Short(_) | Token::Int(_) | Token::Long(_)
+        )
+    }
+
     fn into_tag(self) -> Result<NbtTag, Self> {
         match self {
-            Token::String { value, .. } => Ok(NbtTag::String(value)),
+            Token::String { value, quoted } => Ok(string_tag(value, quoted)),
             Token::Byte(value) => Ok(NbtTag::Byte(value as i8)),
             Token::Short(value) => Ok(NbtTag::Short(value as i16)),
             Token::Int(value) => Ok(NbtTag::Int(value as i32)),
@@ -882,15 +3031,20 @@ impl From<Token> for Result<String, Token> {
     }
 }
 
+// Integers which do not fit in the given range type are rejected rather than wrapped
 macro_rules! opt_int_from_token {
     ($int:ty) => {
+        opt_int_from_token!($int, $int);
+    };
+    ($int:ty, $range:ty) => {
         impl From<Token> for Result<$int, Token> {
             fn from(tk: Token) -> Self {
                 match tk {
-                    Token::Byte(x) => Ok(x as $int),
-                    Token::Short(x) => Ok(x as $int),
-                    Token::Int(x) => Ok(x as $int),
-                    Token::Long(x) => Ok(x as $int),
+                    Token::Byte(x) | Token::Short(x) | Token::Int(x) | Token::Long(x) =>
+                        match <$range>::try_from(x) {
+                            Ok(x) => Ok(x as $int),
+                            Err(_) => Err(tk),
+                        },
                     tk => Err(tk),
                 }
             }
@@ -899,7 +3053,8 @@ macro_rules! opt_int_from_token {
 }
 
 opt_int_from_token!(i8);
-opt_int_from_token!(u8);
+// Byte arrays are read as unsigned bytes holding signed values
+opt_int_from_token!(u8, i8);
 opt_int_from_token!(i16);
 opt_int_from_token!(i32);
 opt_int_from_token!(i64);
@@ -930,24 +3085,53 @@ pub struct SnbtError {
 }
 
 impl SnbtError {
-    fn unmatched_quote(input: &str, index: usize) -> Self {
+    /// Returns an error for an unmatched quote at the given byte offset into the input.
+    ///
+    /// These constructors allow tools layered on top of SNBT, such as preprocessors and templating
+    /// engines, to report errors in the same format as the parser. Each takes the full input so
+    /// that the error can record the [`Position`] of the problem along with a short segment of the
+    /// input surrounding it, and clamps offsets which are out of bounds or not on a character
+    /// boundary.
+    ///
+    /// ```
+    /// use quartz_nbt::snbt::{ParserErrorType, Position, SnbtError};
+    ///
+    /// let input = "{name: \"Steve}";
+    /// let error = SnbtError::unmatched_quote(input, 7);
+    /// assert_eq!(error.position(), Some(Position { line: 1, column: 8 }));
+    /// assert!(matches!(error.error_type(), ParserErrorType::UnmatchedQuote { .. }));
+    /// assert_eq!(error.to_string(), "Unmatched quote at 1:8 near '{name: \"Steve}'");
+    /// ```
+    ///
+    /// [`Position`]: crate::snbt::Position
+    pub fn unmatched_quote(input: &str, index: usize) -> Self {
         SnbtError {
             segment: Self::segment(input, index, 1, 7, 7),
-            error: ParserErrorType::UnmatchedQuote { index },
+            error: ParserErrorType::UnmatchedQuote {
+                position: Position::from_offset(input, index),
+            },
         }
     }
 
-    fn unknown_escape_sequence(input: &str, index: usize, char_width: usize) -> Self {
+    /// Returns an error for an unknown escape sequence at the given byte offset into the input,
+    /// spanning the given number of characters.
+    pub fn unknown_escape_sequence(input: &str, index: usize, char_width: usize) -> Self {
         SnbtError {
             segment: Self::segment(input, index, char_width, 0, 0),
-            error: ParserErrorType::UnknownEscapeSequence,
+            error: ParserErrorType::UnknownEscapeSequence {
+                position: Position::from_offset(input, index),
+            },
         }
     }
 
-    fn invalid_number(input: &str, index: usize, char_width: usize) -> Self {
+    /// Returns an error for an invalid number at the given byte offset into the input, spanning
+    /// the given number of characters.
+    pub fn invalid_number(input: &str, index: usize, char_width: usize) -> Self {
         SnbtError {
             segment: Self::segment(input, index, char_width, 0, 0),
-            error: ParserErrorType::InvalidNumber,
+            error: ParserErrorType::InvalidNumber {
+                position: Position::from_offset(input, index),
+            },
         }
     }
 
@@ -959,7 +3143,10 @@ impl SnbtError {
         }
     }
 
-    fn unexpected_token_at(
+    /// Returns an error for an unexpected token at the given byte offset into the input, spanning
+    /// the given number of characters. The expectation describes what was expected instead, such
+    /// as `"',' or ']'"`.
+    pub fn unexpected_token_at(
         input: &str,
         index: usize,
         char_width: usize,
@@ -967,35 +3154,96 @@ impl SnbtError {
     ) -> Self {
         SnbtError {
             segment: Self::segment(input, index, char_width, 15, 0),
-            error: ParserErrorType::UnexpectedToken { index, expected },
+            error: ParserErrorType::UnexpectedToken {
+                position: Position::from_offset(input, index),
+                expected,
+            },
         }
     }
 
-    fn unexpected_eos(expected: &'static str) -> Self {
+    /// Returns an error for input which ended while the given token or value was still expected.
+    pub fn unexpected_eos(expected: &'static str) -> Self {
         SnbtError {
             segment: String::new(),
             error: ParserErrorType::UnexpectedEOS { expected },
         }
     }
 
-    fn trailing_comma(input: &str, index: usize) -> Self {
+    /// Returns an error for a trailing comma at the given byte offset into the input.
+    ///
+    /// ```
+    /// use quartz_nbt::snbt::{self, SnbtError};
+    ///
+    /// let input = "{a: 1, b: 2,}";
+    /// assert_eq!(
+    ///     SnbtError::trailing_comma(input, 11).to_string(),
+    ///     snbt::parse(input).unwrap_err().to_string()
+    /// );
+    /// ```
+    pub fn trailing_comma(input: &str, index: usize) -> Self {
         SnbtError {
             segment: Self::segment(input, index, 1, 15, 1),
-            error: ParserErrorType::TrailingComma { index },
+            error: ParserErrorType::TrailingComma {
+                position: Position::from_offset(input, index),
+            },
         }
     }
 
-    fn unmatched_brace(input: &str, index: usize) -> Self {
+    /// Returns an error for an unmatched brace at the given byte offset into the input.
+    pub fn unmatched_brace(input: &str, index: usize) -> Self {
         SnbtError {
             segment: Self::segment(input, index, 1, 0, 15),
-            error: ParserErrorType::UnmatchedBrace { index },
+            error: ParserErrorType::UnmatchedBrace {
+                position: Position::from_offset(input, index),
+            },
         }
     }
 
-    fn non_homogenous_list(input: &str, index: usize, char_width: usize) -> Self {
+    /// Returns an error for a list or compound opened at the given byte offset into the input
+    /// which is nested more than [`MAX_DEPTH`] levels deep.
+    ///
+    /// [`MAX_DEPTH`]: crate::io::MAX_DEPTH
+    pub fn exceeded_max_depth(input: &str, index: usize) -> Self {
+        SnbtError {
+            segment: Self::segment(input, index, 1, 15, 0),
+            error: ParserErrorType::ExceededMaxDepth {
+                position: Position::from_offset(input, index),
+            },
+        }
+    }
+
+    /// Returns an error for a list element at the given byte offset into the input, spanning the
+    /// given number of characters, whose type differs from that of the elements before it.
+    pub fn non_homogenous_list(input: &str, index: usize, char_width: usize) -> Self {
         SnbtError {
             segment: Self::segment(input, index, char_width, 15, 0),
-            error: ParserErrorType::NonHomogenousList { index },
+            error: ParserErrorType::NonHomogenousList {
+                position: Position::from_offset(input, index),
+            },
+        }
+    }
+
+    /// Returns an error for a compound key at the given byte offset into the input, spanning the
+    /// given number of characters, which repeats the key at the byte offset `first`.
+    pub fn duplicate_key(input: &str, index: usize, char_width: usize, first: usize) -> Self {
+        SnbtError {
+            segment: Self::segment(input, index, char_width, 6, 0),
+            error: ParserErrorType::DuplicateKey {
+                position: Position::from_offset(input, index),
+                first: Position::from_offset(input, first),
+            },
+        }
+    }
+
+    /// Returns an error for a token starting at the given byte offset into the input which is
+    /// longer than the given maximum length in bytes.
+    pub fn exceeded_max_token_len(input: &str, index: usize, max: usize) -> Self {
+        SnbtError {
+            segment: Self::segment(input, index, 1, 0, 15),
+            error: ParserErrorType::ExceededMaxTokenLength {
+                position: Position::from_offset(input, index),
+                max,
+            },
         }
     }
 
@@ -1006,52 +3254,121 @@ impl SnbtError {
         before: usize,
         after: usize,
     ) -> String {
-        let start = input[.. index]
+        // Keep the segment on the line containing the error so that it reads cleanly
+        let index = char_boundary(input, index);
+        let line_start = input[.. index].rfind('\n').map_or(0, |start| start + 1);
+        let line_end = input[index ..]
+            .find('\n')
+            .map_or(input.len(), |end| index + end);
+
+        let start = input[line_start .. index]
             .char_indices()
             .rev()
-            .nth(before.saturating_sub(1))
-            .map(|(index, _)| index)
-            .unwrap_or(0);
-        let end = (index
-            + input[index ..]
-                .char_indices()
-                .nth(char_width.min(20) + after)
-                .map(|(index, _)| index)
-                .unwrap_or(input.len()))
-        .min(input.len());
+            .take(before)
+            .last()
+            .map_or(index, |(start, _)| line_start + start);
+        let end = input[index .. line_end]
+            .char_indices()
+            .nth(char_width.min(20) + after)
+            .map_or(line_end, |(end, _)| index + end);
         input[start .. end].to_owned()
     }
+
+    /// Returns the type of this error, which includes its position.
+    pub fn error_type(&self) -> &ParserErrorType {
+        &self.error
+    }
+
+    /// Returns the position in the input at which this error occurred, or `None` if the input
+    /// ended early.
+    ///
+    /// ```
+    /// use quartz_nbt::snbt::{self, Position};
+    ///
+    /// let error = snbt::parse("{\n    a: 1,\n    b: ]\n}").unwrap_err();
+    /// assert_eq!(error.position(), Some(Position { line: 3, column: 8 }));
+    /// ```
+    pub fn position(&self) -> Option<Position> {
+        match self.error {
+            ParserErrorType::UnmatchedQuote { position }
+            | ParserErrorType::UnknownEscapeSequence { position }
+            | ParserErrorType::InvalidNumber { position }
+            | ParserErrorType::UnexpectedToken { position, .. }
+            | ParserErrorType::TrailingComma { position }
+            | ParserErrorType::UnmatchedBrace { position }
+            | ParserErrorType::NonHomogenousList { position }
+            | ParserErrorType::ExceededMaxDepth { position }
+            | ParserErrorType::DuplicateKey { position, .. }
+            | ParserErrorType::ExceededMaxTokenLength { position, .. } => Some(position),
+            ParserErrorType::UnexpectedEOS { .. } => None,
+        }
+    }
+
+    // Moves this error from the first line of its input to the given line, for input which was
+    // taken from a single line of a larger document
+    fn on_line(mut self, line: usize) -> Self {
+        match &mut self.error {
+            ParserErrorType::UnmatchedQuote { position }
+            | ParserErrorType::UnknownEscapeSequence { position }
+            | ParserErrorType::InvalidNumber { position }
+            | ParserErrorType::UnexpectedToken { position, .. }
+            | ParserErrorType::TrailingComma { position }
+            | ParserErrorType::UnmatchedBrace { position }
+            | ParserErrorType::NonHomogenousList { position }
+            | ParserErrorType::ExceededMaxDepth { position }
+            | ParserErrorType::ExceededMaxTokenLength { position, .. } => position.line = line,
+            ParserErrorType::DuplicateKey { position, first } => {
+                position.line = line;
+                first.line = line;
+            }
+            ParserErrorType::UnexpectedEOS { .. } => {}
+        }
+        self
+    }
 }
 
 impl Display for SnbtError {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match &self.error {
-            ParserErrorType::UnmatchedQuote { index } => write!(
+            ParserErrorType::UnmatchedQuote { position } =>
+                write!(f, "Unmatched quote at {} near '{}'", position, self.segment),
+            ParserErrorType::UnknownEscapeSequence { position } => write!(
                 f,
-                "Unmatched quote: column {} near '{}'",
-                index, self.segment
+                "Unknown escape sequence at {}: '{}'",
+                position, self.segment
             ),
-            &ParserErrorType::UnknownEscapeSequence =>
-                write!(f, "Unknown escape sequence: '{}'", self.segment),
-            &ParserErrorType::InvalidNumber => write!(f, "Invalid number: {}", self.segment),
-            &ParserErrorType::UnexpectedToken { index, expected } => write!(
+            ParserErrorType::InvalidNumber { position } =>
+                write!(f, "Invalid number at {}: {}", position, self.segment),
+            ParserErrorType::UnexpectedToken { position, expected } => write!(
                 f,
-                "Unexpected token at column {} near '{}', expected {}",
-                index, self.segment, expected
+                "Unexpected token at {} near '{}', expected {}",
+                position, self.segment, expected
             ),
-            &ParserErrorType::UnexpectedEOS { expected } =>
+            ParserErrorType::UnexpectedEOS { expected } =>
                 write!(f, "Reached end of input but expected {}", expected),
-            &ParserErrorType::TrailingComma { index } =>
-                write!(f, "Trailing comma at column {}: '{}'", index, self.segment),
-            &ParserErrorType::UnmatchedBrace { index } => write!(
+            ParserErrorType::TrailingComma { position } =>
+                write!(f, "Trailing comma at {}: '{}'", position, self.segment),
+            ParserErrorType::UnmatchedBrace { position } =>
+                write!(f, "Unmatched brace at {} near '{}'", position, self.segment),
+            ParserErrorType::NonHomogenousList { position } => write!(
+                f,
+                "Non-homogenous typed list at {} near '{}'",
+                position, self.segment
+            ),
+            ParserErrorType::ExceededMaxDepth { position } => write!(
+                f,
+                "Exceeded maximum nesting depth of {} at {} near '{}'",
+                MAX_DEPTH, position, self.segment
+            ),
+            ParserErrorType::DuplicateKey { position, first } => write!(
                 f,
-                "Unmatched brace at column {} near '{}'",
-                index, self.segment
+                "Duplicate key at {} near '{}', first defined at {}",
+                position, self.segment, first
             ),
-            &ParserErrorType::NonHomogenousList { index } => write!(
+            ParserErrorType::ExceededMaxTokenLength { position, max } => write!(
                 f,
-                "Non-homogenous typed list at column {} near '{}'",
-                index, self.segment
+                "Exceeded maximum token length of {} bytes at {} near '{}'",
+                max, position, self.segment
             ),
         }
     }
@@ -1067,20 +3384,27 @@ impl Error for SnbtError {}
 
 /// A specific type of parser error. This enum includes metadata about each specific error.
 #[derive(Clone, Debug)]
+#[non_exhaustive]
 pub enum ParserErrorType {
     /// An unmatched single or double quote.
     UnmatchedQuote {
-        /// The index of the unmatched quote.
-        index: usize,
+        /// The position of the unmatched quote.
+        position: Position,
     },
     /// An unknown or invalid escape sequence.
-    UnknownEscapeSequence,
+    UnknownEscapeSequence {
+        /// The position of the escape sequence.
+        position: Position,
+    },
     /// An invalid number.
-    InvalidNumber,
+    InvalidNumber {
+        /// The position of the number.
+        position: Position,
+    },
     /// An unexpected token was encountered.
     UnexpectedToken {
-        /// The index of the token.
-        index: usize,
+        /// The position of the token.
+        position: Position,
         /// The expected token or sequence of tokens.
         expected: &'static str,
     },
@@ -1091,17 +3415,95 @@ pub enum ParserErrorType {
     },
     /// A trailing comma was encountered in a list or compound.
     TrailingComma {
-        /// The index of the trailing comma.
-        index: usize,
+        /// The position of the trailing comma.
+        position: Position,
     },
     /// An unmatched curly or square bracket was encountered.
     UnmatchedBrace {
-        /// The index of the unmatched brace.
-        index: usize,
+        /// The position of the unmatched brace.
+        position: Position,
     },
     /// A non-homogenous list was encountered.
     NonHomogenousList {
-        /// The index where the invalid list value was encountered.
-        index: usize,
+        /// The position where the invalid list value was encountered.
+        position: Position,
+    },
+    /// Lists and compounds were nested more than [`MAX_DEPTH`] levels deep.
+    ///
+    /// [`MAX_DEPTH`]: crate::io::MAX_DEPTH
+    ExceededMaxDepth {
+        /// The position of the opening bracket of the list or compound which was too deep.
+        position: Position,
+    },
+    /// A key appeared more than once in the same compound while duplicate keys were denied.
+    DuplicateKey {
+        /// The position of the repeated key.
+        position: Position,
+        /// The position of the previous occurrence of the key.
+        first: Position,
     },
+    /// A key or value spanned more bytes than the limit set with [`ParseOptions::max_token_len`].
+    ///
+    /// [`ParseOptions::max_token_len`]: crate::snbt::ParseOptions::max_token_len
+    ExceededMaxTokenLength {
+        /// The position at which the token started.
+        position: Position,
+        /// The maximum length of a token in bytes.
+        max: usize,
+    },
+}
+
+/// A line and column in a string of SNBT, as reported by [`SnbtError`]. Both are one-based, and
+/// columns are counted in characters rather than bytes. This displays as `line:column`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Position {
+    /// The line number, where lines are separated by `\n`.
+    pub line: usize,
+    /// The column within the line.
+    pub column: usize,
+}
+
+impl Position {
+    /// Returns the position of the character containing the given byte offset into the input.
+    /// Offsets past the end of the input map to the position just after its last character.
+    ///
+    /// ```
+    /// use quartz_nbt::snbt::Position;
+    ///
+    /// let input = "{\n    名前: 1b\n}";
+    /// assert_eq!(Position::from_offset(input, 0), Position { line: 1, column: 1 });
+    /// assert_eq!(Position::from_offset(input, 12), Position { line: 2, column: 7 });
+    /// assert_eq!(Position::from_offset(input, 100).to_string(), "3:2");
+    /// ```
+    pub fn from_offset(input: &str, offset: usize) -> Self {
+        let prefix = &input[.. char_boundary(input, offset)];
+        let line_start = prefix.rfind('\n').map_or(0, |start| start + 1);
+        Position {
+            line: prefix.matches('\n').count() + 1,
+            column: prefix[line_start ..].chars().count() + 1,
+        }
+    }
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+// Clamps the index to the start of the character containing it so that slicing cannot panic
+fn char_boundary(input: &str, index: usize) -> usize {
+    let mut index = index.min(input.len());
+    while !input.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+// Returns the byte offset following the given number of characters starting at the given offset
+fn char_end(input: &str, index: usize, char_width: usize) -> usize {
+    input[index ..]
+        .char_indices()
+        .nth(char_width)
+        .map_or(input.len(), |(end, _)| index + end)
 }
@@ -1,19 +1,36 @@
 use crate::{
-    raw,
-    snbt::{self, SnbtError},
+    cast,
+    convert::{ConversionPolicy, ConversionTarget},
+    io::{self, NbtIoError},
+    literal::RustLiteral,
+    merge::{self, MergeHook},
+    path::{self, AsNbtPath, InsertOptions, ListPadding, NbtPath, PathConflict},
+    sanitize::{self, SanitizeReport, SanitizeRules},
+    snbt::{self, DisplayOptions, Element, FormatOptions, SnbtError},
     NbtReprError,
     NbtStructureError,
 };
 use std::{
     borrow::{Borrow, BorrowMut, Cow},
+    cmp::Ordering,
     fmt::{self, Debug, Display, Formatter},
-    hash::Hash,
-    ops::{Deref, DerefMut, Index, IndexMut},
+    hash::{Hash, Hasher},
+    ops::{Bound, Deref, DerefMut, Index, IndexMut, Range, RangeBounds},
+    slice,
     str::FromStr,
 };
 
+#[cfg(feature = "ansi")]
+use crate::snbt::AnsiTheme;
 #[allow(deprecated)]
 use crate::NbtRepr;
+#[cfg(feature = "rayon")]
+use rayon::iter::{
+    IndexedParallelIterator,
+    IntoParallelIterator,
+    IntoParallelRefIterator,
+    ParallelIterator,
+};
 
 /// The hash map type utilized in this crate. If the feature `preserve_order` is enabled, then this
 /// will use the `IndexMap` type from the crate <https://docs.rs/indexmap/latest/indexmap/>.
@@ -37,8 +54,34 @@ pub type Map<T> = std::collections::HashMap<String, T>;
 /// this, NBT tags are only compatible with self-describing formats, and also have slower deserialization
 /// implementations due to this restriction.
 ///
+/// Tags can be compared directly against primitives and strings. A tag is only equal to a primitive
+/// if it is the variant wrapping that primitive's type, so no numeric conversions are performed.
+/// ```
+/// # use quartz_nbt::*;
+/// let compound = compound! { "x": 1i8, "name": "foo" };
+/// let x = compound.get::<_, &NbtTag>("x").unwrap();
+///
+/// assert!(x == 1i8);
+/// assert!(1i8 == *x);
+/// assert!(x != 1i32);
+/// assert!(compound["name"] == "foo");
+/// ```
+///
 /// [`io`]: crate::io
 #[derive(Clone, PartialEq)]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    rkyv(
+        derive(Debug),
+        serialize_bounds(
+            __S: rkyv::ser::Writer + rkyv::ser::Allocator,
+            __S::Error: rkyv::rancor::Source,
+        ),
+        deserialize_bounds(__D::Error: rkyv::rancor::Source),
+        bytecheck(bounds(__C: rkyv::validation::ArchiveContext, __C::Error: rkyv::rancor::Source))
+    )
+)]
 pub enum NbtTag {
     /// A signed, one-byte integer.
     Byte(i8),
@@ -57,9 +100,9 @@ pub enum NbtTag {
     /// A UTF-8 string.
     String(String),
     /// An NBT tag list.
-    List(NbtList),
+    List(#[cfg_attr(feature = "rkyv", rkyv(omit_bounds))] NbtList),
     /// An NBT tag compound.
-    Compound(NbtCompound),
+    Compound(#[cfg_attr(feature = "rkyv", rkyv(omit_bounds))] NbtCompound),
     /// An array (vec) of signed, four-byte integers.
     IntArray(Vec<i32>),
     /// An array (vec) of signed, eight-byte integers.
@@ -92,20 +135,201 @@ impl NbtTag {
         }
     }
 
-    pub(crate) fn tag_name(&self) -> &'static str {
+    /// Returns the kind of this tag.
+    ///
+    /// ```
+    /// # use quartz_nbt::{NbtTag, NbtTagKind};
+    /// assert_eq!(NbtTag::Short(3).kind(), NbtTagKind::Short);
+    /// assert_eq!(NbtTag::LongArray(Vec::new()).kind(), NbtTagKind::LongArray);
+    /// ```
+    pub fn kind(&self) -> NbtTagKind {
         match self {
-            NbtTag::Byte(_) => "Byte",
-            NbtTag::Short(_) => "Short",
-            NbtTag::Int(_) => "Int",
-            NbtTag::Long(_) => "Long",
-            NbtTag::Float(_) => "Float",
-            NbtTag::Double(_) => "Double",
-            NbtTag::String(_) => "String",
-            NbtTag::ByteArray(_) => "ByteArray",
-            NbtTag::IntArray(_) => "IntArray",
-            NbtTag::LongArray(_) => "LongArray",
-            NbtTag::Compound(_) => "Compound",
-            NbtTag::List(_) => "List",
+            NbtTag::Byte(_) => NbtTagKind::Byte,
+            NbtTag::Short(_) => NbtTagKind::Short,
+            NbtTag::Int(_) => NbtTagKind::Int,
+            NbtTag::Long(_) => NbtTagKind::Long,
+            NbtTag::Float(_) => NbtTagKind::Float,
+            NbtTag::Double(_) => NbtTagKind::Double,
+            NbtTag::ByteArray(_) => NbtTagKind::ByteArray,
+            NbtTag::String(_) => NbtTagKind::String,
+            NbtTag::List(_) => NbtTagKind::List,
+            NbtTag::Compound(_) => NbtTagKind::Compound,
+            NbtTag::IntArray(_) => NbtTagKind::IntArray,
+            NbtTag::LongArray(_) => NbtTagKind::LongArray,
+        }
+    }
+
+    pub(crate) fn tag_name(&self) -> &'static str {
+        self.kind().name()
+    }
+
+    /// Returns the tag nested within this tag at the given index, which is a key if this tag is a
+    /// compound or a position if this tag is a list. `None` is returned if there is no such tag, or
+    /// if this tag is not of a kind which can be indexed with the given index.
+    ///
+    /// Lookups can be chained through [`TagLookup`], which provides the same method for the
+    /// returned option, so exploring nested data does not require handling each level.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// use quartz_nbt::TagLookup;
+    ///
+    /// let tag = NbtTag::Compound(compound! {
+    ///     "Inventory": [{ "id": "minecraft:diamond", "Count": 3i8 }]
+    /// });
+    ///
+    /// assert_eq!(tag.get("Inventory").get(0).get("id"), Some(&"minecraft:diamond".into()));
+    /// assert_eq!(tag.get("Inventory").get(1).get("id"), None); // Out of bounds
+    /// assert_eq!(tag.get("Inventory").get("id"), None); // Not a compound
+    /// assert_eq!(tag.get("Armor").get(0), None); // Missing
+    /// ```
+    ///
+    /// [`TagLookup`]: crate::TagLookup
+    #[inline]
+    pub fn get<I: TagIndex>(&self, index: I) -> Option<&NbtTag> {
+        index.index_into(self)
+    }
+
+    /// Returns whether this tag is contained in the given tag, using the same algorithm as
+    /// Minecraft's NBT predicates and the compound patterns of [`NbtPath`]s. See
+    /// `NbtCompound::`[`is_subset_of`] for details.
+    ///
+    /// [`NbtPath`]: crate::path::NbtPath
+    /// [`is_subset_of`]: crate::NbtCompound::is_subset_of
+    #[inline]
+    pub fn is_subset_of(&self, other: &NbtTag) -> bool {
+        path::compare_tag(self, other, true)
+    }
+
+    /// Returns whether this tag is contained in the given tag, requiring lists to be equal rather
+    /// than to merely contain the elements of this tag's lists. See
+    /// `NbtCompound::`[`is_subset_of_strict`] for details.
+    ///
+    /// [`is_subset_of_strict`]: crate::NbtCompound::is_subset_of_strict
+    #[inline]
+    pub fn is_subset_of_strict(&self, other: &NbtTag) -> bool {
+        path::compare_tag(self, other, false)
+    }
+
+    /// Calls the given function on every tag nested within this tag, in no particular order, apart
+    /// from lists and compounds, which are traversed instead. If this tag is not a list or compound,
+    /// then the function is called on this tag alone. The traversal is iterative, so deeply nested
+    /// data cannot overflow the stack.
+    ///
+    /// The function may modify tags in place. Changing the kind of a list element will make the
+    /// list heterogenous, which cannot be written.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// let mut tag = NbtTag::Compound(compound! {
+    ///     "Pos": [1.5f64, 64.0f64, -3.5f64],
+    ///     "Motion": { "x": 0.25f64 }
+    /// });
+    ///
+    /// tag.map_values(|tag| {
+    ///     if let NbtTag::Double(value) = tag {
+    ///         *value = value.floor();
+    ///     }
+    /// });
+    /// assert_eq!(tag, NbtTag::Compound(compound! {
+    ///     "Pos": [1.0f64, 64.0f64, -4.0f64],
+    ///     "Motion": { "x": 0.0f64 }
+    /// }));
+    /// ```
+    pub fn map_values<F>(&mut self, mut f: F)
+    where F: FnMut(&mut NbtTag) {
+        map_values(vec![self], &mut f);
+    }
+
+    /// Converts this tag into the given kind through a lossless numeric widening. If this tag is
+    /// already of the given kind then it is returned as-is without being cloned.
+    ///
+    /// Integral tags can be promoted to any wider integral kind, and to a float or double if the
+    /// value is exactly representable by that kind. Floats can be promoted to doubles. Byte arrays
+    /// can be promoted to int or long arrays, and int arrays can be promoted to long arrays. All
+    /// other conversions, including any narrowing conversion, result in an error.
+    ///
+    /// ```
+    /// # use quartz_nbt::{NbtTag, NbtTagKind};
+    /// assert_eq!(*NbtTag::Byte(-3).promote(NbtTagKind::Long).unwrap(), NbtTag::Long(-3));
+    /// assert_eq!(*NbtTag::Int(1 << 20).promote(NbtTagKind::Float).unwrap(), NbtTag::Float(1048576.0));
+    /// assert_eq!(
+    ///     *NbtTag::ByteArray(vec![1, 2]).promote(NbtTagKind::IntArray).unwrap(),
+    ///     NbtTag::IntArray(vec![1, 2])
+    /// );
+    ///
+    /// assert!(NbtTag::Int(16_777_217).promote(NbtTagKind::Float).is_err()); // Lossy
+    /// assert!(NbtTag::Long(10).promote(NbtTagKind::Int).is_err()); // Narrowing
+    /// assert!(NbtTag::String("1".to_owned()).promote(NbtTagKind::Int).is_err()); // Not numeric
+    /// ```
+    pub fn promote(&self, to: NbtTagKind) -> Result<Cow<'_, NbtTag>, NbtStructureError> {
+        let from = self.kind();
+        if from == to {
+            return Ok(Cow::Borrowed(self));
+        }
+
+        let lossy = || NbtStructureError::lossy_conversion(from.name(), to.name());
+
+        let promoted = match *self {
+            NbtTag::Byte(value) => promote_integral(i64::from(value), from, to),
+            NbtTag::Short(value) => promote_integral(i64::from(value), from, to),
+            NbtTag::Int(value) => promote_integral(i64::from(value), from, to),
+            NbtTag::Long(value) => promote_integral(value, from, to),
+            NbtTag::Float(value) => match to {
+                NbtTagKind::Double => Some(NbtTag::Double(f64::from(value))),
+                _ => None,
+            },
+            NbtTag::ByteArray(ref array) => match to {
+                NbtTagKind::IntArray => Some(NbtTag::IntArray(
+                    array.iter().copied().map(i32::from).collect(),
+                )),
+                NbtTagKind::LongArray => Some(NbtTag::LongArray(
+                    array.iter().copied().map(i64::from).collect(),
+                )),
+                _ => None,
+            },
+            NbtTag::IntArray(ref array) => match to {
+                NbtTagKind::LongArray => Some(NbtTag::LongArray(
+                    array.iter().copied().map(i64::from).collect(),
+                )),
+                _ => None,
+            },
+            _ => return Err(NbtStructureError::type_mismatch(to.name(), from.name())),
+        };
+
+        promoted.map(Cow::Owned).ok_or_else(lossy)
+    }
+
+    /// Overwrites the given tag with a deep copy of this tag, reusing the allocations of the
+    /// target wherever its structure matches this tag's. Strings, arrays, lists and compounds
+    /// which are of the same kind in both tags are cloned into in place, so repeatedly copying
+    /// state of a mostly fixed shape allocates little or nothing after the first copy.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// let current = NbtTag::Compound(compound! {
+    ///     "Pos": [1.5f64, 64.0f64, -3.5f64],
+    ///     "Name": "Steve"
+    /// });
+    /// let mut previous = NbtTag::Compound(compound! {
+    ///     "Pos": [0.0f64, 0.0f64, 0.0f64],
+    ///     "Name": "Alex",
+    ///     "Removed": 1i8
+    /// });
+    ///
+    /// current.clone_into(&mut previous);
+    /// assert_eq!(previous, current);
+    /// ```
+    pub fn clone_into(&self, target: &mut NbtTag) {
+        match (self, target) {
+            (NbtTag::ByteArray(source), NbtTag::ByteArray(target)) => target.clone_from(source),
+            (NbtTag::String(source), NbtTag::String(target)) => target.clone_from(source),
+            (NbtTag::List(source), NbtTag::List(target)) => target.clone_from_reusing(source),
+            (NbtTag::Compound(source), NbtTag::Compound(target)) =>
+                target.clone_from_reusing(source),
+            (NbtTag::IntArray(source), NbtTag::IntArray(target)) => target.clone_from(source),
+            (NbtTag::LongArray(source), NbtTag::LongArray(target)) => target.clone_from(source),
+            (source, target) => *target = source.clone(),
         }
     }
 
@@ -181,11 +405,185 @@ impl NbtTag {
         format!("{:#?}", self)
     }
 
+    /// Converts this NBT tag into a valid, parsable SNBT string formatted according to the given
+    /// options. See [`FormatOptions`] for details.
+    ///
+    /// [`FormatOptions`]: crate::snbt::FormatOptions
+    pub fn to_snbt_with(&self, options: FormatOptions) -> String {
+        format_snbt(options, |f| {
+            self.to_formatted_snbt(&mut String::new(), options, f)
+        })
+    }
+
+    /// Converts this NBT tag into SNBT with the same spacing as [`to_pretty_snbt`], colored with
+    /// ANSI escape sequences according to the given theme for display in a terminal. The output
+    /// can no longer be parsed as SNBT. See [`FormatOptions::ansi_theme`] to combine a theme with
+    /// other options.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// use quartz_nbt::snbt::AnsiTheme;
+    ///
+    /// let tag = NbtTag::Compound(compound! { "id": "minecraft:stone" });
+    /// assert_eq!(
+    ///     tag.to_ansi_snbt(AnsiTheme::new()),
+    ///     "{\n    \x1b[96mid\x1b[0m: \x1b[92m\"minecraft:stone\"\x1b[0m\n}"
+    /// );
+    /// assert_eq!(tag.to_ansi_snbt(AnsiTheme::plain()), tag.to_pretty_snbt());
+    /// ```
+    ///
+    /// This method is only available when the `ansi` feature is enabled.
+    ///
+    /// [`to_pretty_snbt`]: crate::NbtTag::to_pretty_snbt
+    /// [`FormatOptions::ansi_theme`]: crate::snbt::FormatOptions::ansi_theme
+    #[cfg(feature = "ansi")]
+    pub fn to_ansi_snbt(&self, theme: AnsiTheme) -> String {
+        self.to_snbt_with(FormatOptions::new().pretty(true).ansi_theme(theme))
+    }
+
+    /// Converts this NBT tag into a human-readable string with the digits of numbers grouped
+    /// according to the given options, for display in reports and logs. The output has the same
+    /// layout as [`to_pretty_snbt`], but cannot be parsed as SNBT. See [`DisplayOptions`] for
+    /// details.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// use quartz_nbt::snbt::DisplayOptions;
+    ///
+    /// let tag = NbtTag::Compound(compound! { "xp": [L; 1234567, -89] });
+    /// let repr =
+    /// r#"{
+    ///     xp: [
+    ///         L;
+    ///         1,234,567,
+    ///         -89
+    ///     ]
+    /// }"#;
+    /// assert_eq!(tag.to_display_string(DisplayOptions::new()), repr);
+    /// ```
+    ///
+    /// [`to_pretty_snbt`]: crate::NbtTag::to_pretty_snbt
+    /// [`DisplayOptions`]: crate::snbt::DisplayOptions
+    pub fn to_display_string(&self, options: DisplayOptions) -> String {
+        self.to_snbt_with(options.format_options())
+    }
+
+    /// Converts this NBT tag into Rust source code which evaluates to an equal tag, which is useful
+    /// for turning a document captured at runtime into a test fixture. Compounds are written with
+    /// the [`compound!`] macro, and the output assumes that the contents of this crate's root
+    /// module are in scope. Non-finite floats are written as the associated constants of their
+    /// type, so the payloads of NaNs are not preserved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// assert_eq!(NbtTag::Byte(5).to_rust_literal(), "NbtTag::Byte(5)");
+    /// assert_eq!(
+    ///     NbtTag::String("foo".to_owned()).to_rust_literal(),
+    ///     "NbtTag::String(\"foo\".to_owned())"
+    /// );
+    ///
+    /// let tag = NbtTag::Compound(compound! {
+    ///     "pos": [1.5f64, 64.0f64, -2.5f64]
+    /// });
+    /// let repr =
+    /// r#"NbtTag::Compound(compound! {
+    ///     "pos": [1.5f64, 64.0f64, -2.5f64]
+    /// })"#;
+    /// assert_eq!(tag.to_rust_literal(), repr);
+    /// ```
+    ///
+    /// [`compound!`]: crate::compound
+    pub fn to_rust_literal(&self) -> String {
+        RustLiteral::Tag(self).to_string()
+    }
+
+    /// Writes an annotated hexdump of the binary encoding of this tag to the given writer, which is
+    /// useful for debugging wire issues or for learning the format. The tag is encoded as a named
+    /// tag with an empty name, which for a compound is the same as an NBT document with an empty
+    /// root name.
+    ///
+    /// Every line starts with the offset of its first byte and contains up to `bytes_per_line`
+    /// bytes, with a value of zero being treated as one. A new line is started at each tag header,
+    /// list element and end tag, and is followed by a label with the kind of the tag, its name or
+    /// index, and its value if it is a number or string.
+    ///
+    /// An error is returned if this tag contains a non-homogenous list, or if the writer returns
+    /// an error.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// let nbt = NbtTag::Compound(compound! { "Items": [1i16, 2i16] });
+    ///
+    /// let mut dump = String::new();
+    /// nbt.hexdump(&mut dump, 8).unwrap();
+    /// assert_eq!(
+    ///     dump,
+    ///     "\
+    /// 00000000  0a 00 00                 Compound \"\"
+    /// 00000003  09 00 05 49 74 65 6d 73  List Items
+    /// 0000000b  02 00 00 00 02
+    /// 00000010  00 01                    Short [0] = 1S
+    /// 00000012  00 02                    Short [1] = 2S
+    /// 00000014  00                       End
+    /// "
+    /// );
+    /// ```
+    pub fn hexdump<W: fmt::Write>(
+        &self,
+        writer: &mut W,
+        bytes_per_line: usize,
+    ) -> Result<(), NbtIoError> {
+        io::write_hexdump(writer, self, bytes_per_line)
+    }
+
+    /// Returns the given compound key as it is written in SNBT, quoting and escaping it only if it
+    /// would otherwise be read differently. This is exactly how keys are written by the `Display`
+    /// implementations of tags, so [`parse_key`] reads every formatted key back unchanged.
+    ///
+    /// ```
+    /// # use quartz_nbt::NbtTag;
+    /// assert_eq!(NbtTag::format_key("Inventory"), "Inventory");
+    /// assert_eq!(NbtTag::format_key("minecraft:stone"), "\"minecraft:stone\"");
+    /// assert_eq!(NbtTag::format_key("1st"), "\"1st\"");
+    /// assert_eq!(NbtTag::format_key(""), "\"\"");
+    /// ```
+    ///
+    /// [`parse_key`]: crate::NbtTag::parse_key
+    #[inline]
+    pub fn format_key(key: &str) -> Cow<'_, str> {
+        Self::string_to_snbt(key)
+    }
+
+    /// Parses a single compound key, quoted or unquoted, exactly as the SNBT parser reads keys
+    /// within a compound. Whitespace around the key is ignored. An error is returned if the input
+    /// holds anything other than one key, or if the key would not be accepted in a compound, such
+    /// as an unquoted number.
+    ///
+    /// ```
+    /// # use quartz_nbt::NbtTag;
+    /// assert_eq!(NbtTag::parse_key("Inventory").unwrap(), "Inventory");
+    /// assert_eq!(NbtTag::parse_key(" 'say \\'hi\\'' ").unwrap(), "say 'hi'");
+    /// assert!(NbtTag::parse_key("minecraft:stone").is_err());
+    /// assert!(NbtTag::parse_key("5").is_err());
+    ///
+    /// let key = "a \"weird\" key: {}";
+    /// assert_eq!(NbtTag::parse_key(&NbtTag::format_key(key)).unwrap(), key);
+    /// ```
+    pub fn parse_key(input: &str) -> Result<String, SnbtError> {
+        snbt::parse_key(input)
+    }
+
     /// Returns whether or not the given string needs to be quoted due to non-alphanumeric or otherwise
     /// non-standard characters.
     #[inline]
     pub fn should_quote(string: &str) -> bool {
-        if string.is_empty() {
+        // Unquoted boolean literals are parsed as bytes
+        if string.is_empty()
+            || string.eq_ignore_ascii_case("true")
+            || string.eq_ignore_ascii_case("false")
+        {
             return true;
         }
 
@@ -195,6 +593,13 @@ impl NbtTag {
             }
         }
 
+        // A sign or point followed by a digit may begin a number, such as `+5` or `.5`
+        let rest = string.strip_prefix('+').unwrap_or(string);
+        let rest = rest.strip_prefix('.').unwrap_or(rest);
+        if rest.len() < string.len() && rest.starts_with(|ch: char| ch.is_ascii_digit()) {
+            return true;
+        }
+
         if let Some(last) = string.chars().next_back() {
             if last.is_whitespace() {
                 return true;
@@ -204,6 +609,7 @@ impl NbtTag {
         for ch in string.chars() {
             if ch == ':'
                 || ch == ','
+                || ch == ';'
                 || ch == '"'
                 || ch == '\''
                 || ch == '{'
@@ -242,12 +648,7 @@ impl NbtTag {
         }
 
         // Determine the best option for the surrounding quotes to minimize escape sequences
-        let surrounding: char;
-        if string.contains('"') {
-            surrounding = '\'';
-        } else {
-            surrounding = '"';
-        }
+        let surrounding = if string.contains('"') { '\'' } else { '"' };
 
         let mut snbt_string = String::with_capacity(2 + string.len());
         snbt_string.push(surrounding);
@@ -280,13 +681,20 @@ impl NbtTag {
     }
 
     #[allow(clippy::write_with_newline)]
-    fn to_formatted_snbt(&self, indent: &mut String, f: &mut Formatter<'_>) -> fmt::Result {
+    fn to_formatted_snbt(
+        &self,
+        indent: &mut String,
+        options: FormatOptions,
+        f: &mut Formatter<'_>,
+    ) -> fmt::Result {
         fn write_list(
             list: &[impl Display],
             indent: &mut String,
             ts: &str,
+            options: FormatOptions,
             f: &mut Formatter<'_>,
         ) -> fmt::Result {
+            let ts = options.paint(Element::Suffix, ts);
             if list.is_empty() {
                 return write!(f, "[{};]", ts);
             }
@@ -303,7 +711,10 @@ impl NbtTag {
                 if f.alternate() {
                     write!(f, "{}", indent)?;
                 }
-                Display::fmt(element, f)?;
+                Display::fmt(
+                    &options.paint(Element::Number, options.group_digits(element)),
+                    f,
+                )?;
                 if index != last_index {
                     if f.alternate() {
                         write!(f, ",\n")?;
@@ -322,31 +733,46 @@ impl NbtTag {
         }
 
         #[inline]
-        fn write(value: &impl Display, ts: Option<&str>, f: &mut Formatter<'_>) -> fmt::Result {
+        fn write(
+            value: &impl Display,
+            ts: Option<&str>,
+            options: FormatOptions,
+            f: &mut Formatter<'_>,
+        ) -> fmt::Result {
+            Display::fmt(
+                &options.paint(Element::Number, options.group_digits(value)),
+                f,
+            )?;
             match ts {
-                Some(ts) => {
-                    Display::fmt(value, f)?;
-                    write!(f, "{}", ts)
-                }
-                None => Display::fmt(value, f),
+                Some(ts) => write!(f, "{}", options.paint(Element::Suffix, ts)),
+                None => Ok(()),
             }
         }
 
         let ts = self.type_specifier();
 
         match self {
-            NbtTag::Byte(value) => write(value, ts, f),
-            NbtTag::Short(value) => write(value, ts, f),
-            NbtTag::Int(value) => write(value, ts, f),
-            NbtTag::Long(value) => write(value, ts, f),
-            NbtTag::Float(value) => write(value, ts, f),
-            NbtTag::Double(value) => write(value, ts, f),
-            NbtTag::ByteArray(value) => write_list(&**value, indent, ts.unwrap(), f),
-            NbtTag::String(value) => write!(f, "{}", Self::string_to_snbt(value)),
-            NbtTag::List(value) => value.to_formatted_snbt(indent, f),
-            NbtTag::Compound(value) => value.to_formatted_snbt(indent, f),
-            NbtTag::IntArray(value) => write_list(&**value, indent, ts.unwrap(), f),
-            NbtTag::LongArray(value) => write_list(&**value, indent, ts.unwrap(), f),
+            NbtTag::Byte(value @ (0 | 1)) if options.bool_literals => write!(
+                f,
+                "{}",
+                options.paint(Element::Number, if *value == 1 { "true" } else { "false" })
+            ),
+            NbtTag::Byte(value) => write(value, ts, options, f),
+            NbtTag::Short(value) => write(value, ts, options, f),
+            NbtTag::Int(value) => write(value, ts, options, f),
+            NbtTag::Long(value) => write(value, ts, options, f),
+            NbtTag::Float(value) => write(value, ts, options, f),
+            NbtTag::Double(value) => write(value, ts, options, f),
+            NbtTag::ByteArray(value) => write_list(value, indent, ts.unwrap(), options, f),
+            NbtTag::String(value) => write!(
+                f,
+                "{}",
+                options.paint(Element::String, Self::string_to_snbt(value))
+            ),
+            NbtTag::List(value) => value.to_formatted_snbt(indent, options, f),
+            NbtTag::Compound(value) => value.to_formatted_snbt(indent, options, f),
+            NbtTag::IntArray(value) => write_list(value, indent, ts.unwrap(), options, f),
+            NbtTag::LongArray(value) => write_list(value, indent, ts.unwrap(), options, f),
         }
     }
 }
@@ -354,14 +780,208 @@ impl NbtTag {
 impl Display for NbtTag {
     #[inline]
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        self.to_formatted_snbt(&mut String::new(), f)
+        self.to_formatted_snbt(&mut String::new(), FormatOptions::default(), f)
     }
 }
 
 impl Debug for NbtTag {
     #[inline]
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        self.to_formatted_snbt(&mut String::new(), f)
+        self.to_formatted_snbt(&mut String::new(), FormatOptions::default(), f)
+    }
+}
+
+/// The kind of an [`NbtTag`], without any associated data.
+///
+/// [`NbtTag`]: crate::NbtTag
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    rkyv(derive(Debug, Clone, Copy, PartialEq, Eq, Hash), compare(PartialEq))
+)]
+pub enum NbtTagKind {
+    /// The kind of [`NbtTag::Byte`](crate::NbtTag::Byte).
+    Byte,
+    /// The kind of [`NbtTag::Short`](crate::NbtTag::Short).
+    Short,
+    /// The kind of [`NbtTag::Int`](crate::NbtTag::Int).
+    Int,
+    /// The kind of [`NbtTag::Long`](crate::NbtTag::Long).
+    Long,
+    /// The kind of [`NbtTag::Float`](crate::NbtTag::Float).
+    Float,
+    /// The kind of [`NbtTag::Double`](crate::NbtTag::Double).
+    Double,
+    /// The kind of [`NbtTag::ByteArray`](crate::NbtTag::ByteArray).
+    ByteArray,
+    /// The kind of [`NbtTag::String`](crate::NbtTag::String).
+    String,
+    /// The kind of [`NbtTag::List`](crate::NbtTag::List).
+    List,
+    /// The kind of [`NbtTag::Compound`](crate::NbtTag::Compound).
+    Compound,
+    /// The kind of [`NbtTag::IntArray`](crate::NbtTag::IntArray).
+    IntArray,
+    /// The kind of [`NbtTag::LongArray`](crate::NbtTag::LongArray).
+    LongArray,
+}
+
+impl NbtTagKind {
+    /// Returns the binary tag ID associated with this kind.
+    ///
+    /// ```
+    /// # use quartz_nbt::NbtTagKind;
+    /// assert_eq!(NbtTagKind::Byte.id(), 0x1);
+    /// assert_eq!(NbtTagKind::Compound.id(), 0xA);
+    /// ```
+    pub const fn id(self) -> u8 {
+        match self {
+            NbtTagKind::Byte => 0x1,
+            NbtTagKind::Short => 0x2,
+            NbtTagKind::Int => 0x3,
+            NbtTagKind::Long => 0x4,
+            NbtTagKind::Float => 0x5,
+            NbtTagKind::Double => 0x6,
+            NbtTagKind::ByteArray => 0x7,
+            NbtTagKind::String => 0x8,
+            NbtTagKind::List => 0x9,
+            NbtTagKind::Compound => 0xA,
+            NbtTagKind::IntArray => 0xB,
+            NbtTagKind::LongArray => 0xC,
+        }
+    }
+
+    /// Returns the kind associated with the given binary tag ID, or `None` if the ID is invalid or
+    /// represents `TAG_End`.
+    ///
+    /// ```
+    /// # use quartz_nbt::NbtTagKind;
+    /// assert_eq!(NbtTagKind::from_id(0x8), Some(NbtTagKind::String));
+    /// assert_eq!(NbtTagKind::from_id(0x0), None);
+    /// assert_eq!(NbtTagKind::from_id(0xD), None);
+    /// ```
+    pub const fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0x1 => Some(NbtTagKind::Byte),
+            0x2 => Some(NbtTagKind::Short),
+            0x3 => Some(NbtTagKind::Int),
+            0x4 => Some(NbtTagKind::Long),
+            0x5 => Some(NbtTagKind::Float),
+            0x6 => Some(NbtTagKind::Double),
+            0x7 => Some(NbtTagKind::ByteArray),
+            0x8 => Some(NbtTagKind::String),
+            0x9 => Some(NbtTagKind::List),
+            0xA => Some(NbtTagKind::Compound),
+            0xB => Some(NbtTagKind::IntArray),
+            0xC => Some(NbtTagKind::LongArray),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of this kind, which matches the name of the corresponding [`NbtTag`]
+    /// variant.
+    ///
+    /// [`NbtTag`]: crate::NbtTag
+    pub const fn name(self) -> &'static str {
+        match self {
+            NbtTagKind::Byte => "Byte",
+            NbtTagKind::Short => "Short",
+            NbtTagKind::Int => "Int",
+            NbtTagKind::Long => "Long",
+            NbtTagKind::Float => "Float",
+            NbtTagKind::Double => "Double",
+            NbtTagKind::ByteArray => "ByteArray",
+            NbtTagKind::String => "String",
+            NbtTagKind::List => "List",
+            NbtTagKind::Compound => "Compound",
+            NbtTagKind::IntArray => "IntArray",
+            NbtTagKind::LongArray => "LongArray",
+        }
+    }
+}
+
+impl Display for NbtTagKind {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+// Widens the given integral value to the given kind, returning `None` if the conversion would
+// narrow the value's kind or the value cannot be represented exactly
+fn promote_integral(value: i64, from: NbtTagKind, to: NbtTagKind) -> Option<NbtTag> {
+    fn width(kind: NbtTagKind) -> u8 {
+        match kind {
+            NbtTagKind::Byte => 1,
+            NbtTagKind::Short => 2,
+            NbtTagKind::Int => 4,
+            _ => 8,
+        }
+    }
+
+    match to {
+        NbtTagKind::Short if width(from) < 2 => Some(NbtTag::Short(value as i16)),
+        NbtTagKind::Int if width(from) < 4 => Some(NbtTag::Int(value as i32)),
+        NbtTagKind::Long => Some(NbtTag::Long(value)),
+        // Compare in i128 so that saturating float-to-int casts cannot produce a false match
+        NbtTagKind::Float => {
+            let float = value as f32;
+            (float as i128 == i128::from(value)).then_some(NbtTag::Float(float))
+        }
+        NbtTagKind::Double => {
+            let double = value as f64;
+            (double as i128 == i128::from(value)).then_some(NbtTag::Double(double))
+        }
+        _ => None,
+    }
+}
+
+// Checks that the given tags are all numbers or all strings of a single kind, and thus can be
+// ordered with `compare_sortable`
+fn check_sortable<'a>(mut tags: impl Iterator<Item = &'a NbtTag>) -> Result<(), NbtStructureError> {
+    let kind = match tags.next() {
+        Some(tag) => tag.kind(),
+        None => return Ok(()),
+    };
+
+    if !matches!(
+        kind,
+        NbtTagKind::Byte
+            | NbtTagKind::Short
+            | NbtTagKind::Int
+            | NbtTagKind::Long
+            | NbtTagKind::Float
+            | NbtTagKind::Double
+            | NbtTagKind::String
+    ) {
+        return Err(NbtStructureError::type_mismatch(
+            "number or String",
+            kind.name(),
+        ));
+    }
+
+    match tags.find(|tag| tag.kind() != kind) {
+        Some(tag) => Err(NbtStructureError::type_mismatch(
+            kind.name(),
+            tag.kind().name(),
+        )),
+        None => Ok(()),
+    }
+}
+
+// Orders two tags which have been checked with `check_sortable`. Floats are ordered with
+// `total_cmp` so that NaN values do not break the sort.
+fn compare_sortable(a: &NbtTag, b: &NbtTag) -> Ordering {
+    match (a, b) {
+        (NbtTag::Byte(a), NbtTag::Byte(b)) => a.cmp(b),
+        (NbtTag::Short(a), NbtTag::Short(b)) => a.cmp(b),
+        (NbtTag::Int(a), NbtTag::Int(b)) => a.cmp(b),
+        (NbtTag::Long(a), NbtTag::Long(b)) => a.cmp(b),
+        (NbtTag::Float(a), NbtTag::Float(b)) => a.total_cmp(b),
+        (NbtTag::Double(a), NbtTag::Double(b)) => a.total_cmp(b),
+        (NbtTag::String(a), NbtTag::String(b)) => a.cmp(b),
+        _ => Ordering::Equal,
     }
 }
 
@@ -425,7 +1045,48 @@ impl From<u8> for NbtTag {
 impl From<Vec<u8>> for NbtTag {
     #[inline]
     fn from(value: Vec<u8>) -> Self {
-        NbtTag::ByteArray(raw::cast_byte_buf_to_signed(value))
+        NbtTag::ByteArray(cast::byte_buf_to_signed(value))
+    }
+}
+
+// Implement the from trait for borrowed slices and owned arrays of the array tags' element types
+macro_rules! array_tag_from {
+    ($($type:ty, $tag:ident);*) => {
+        $(
+            impl From<&[$type]> for NbtTag {
+                #[inline]
+                fn from(value: &[$type]) -> NbtTag {
+                    NbtTag::$tag(value.to_vec())
+                }
+            }
+
+            impl<const N: usize> From<[$type; N]> for NbtTag {
+                #[inline]
+                fn from(value: [$type; N]) -> NbtTag {
+                    NbtTag::$tag(Vec::from(value))
+                }
+            }
+        )*
+    };
+}
+
+array_tag_from!(
+    i8, ByteArray;
+    i32, IntArray;
+    i64, LongArray
+);
+
+impl From<&[u8]> for NbtTag {
+    #[inline]
+    fn from(value: &[u8]) -> Self {
+        NbtTag::ByteArray(cast::bytes_to_signed(value).to_vec())
+    }
+}
+
+impl<const N: usize> From<[u8; N]> for NbtTag {
+    #[inline]
+    fn from(value: [u8; N]) -> Self {
+        NbtTag::from(&value[..])
     }
 }
 
@@ -551,7 +1212,7 @@ impl<'a> TryFrom<&'a NbtTag> for &'a u8 {
     #[inline]
     fn try_from(tag: &'a NbtTag) -> Result<Self, Self::Error> {
         if let NbtTag::Byte(value) = tag {
-            Ok(unsafe { &*(value as *const i8 as *const u8) })
+            Ok(&cast::bytes_to_unsigned(slice::from_ref(value))[0])
         } else {
             Err(NbtStructureError::type_mismatch("Byte", tag.tag_name()))
         }
@@ -564,7 +1225,7 @@ impl<'a> TryFrom<&'a NbtTag> for &'a [u8] {
     #[inline]
     fn try_from(tag: &'a NbtTag) -> Result<Self, Self::Error> {
         if let NbtTag::ByteArray(value) = tag {
-            Ok(raw::cast_bytes_to_unsigned(value.as_slice()))
+            Ok(cast::bytes_to_unsigned(value.as_slice()))
         } else {
             Err(NbtStructureError::type_mismatch(
                 "ByteArray",
@@ -614,7 +1275,7 @@ impl TryFrom<NbtTag> for Vec<u8> {
     #[inline]
     fn try_from(tag: NbtTag) -> Result<Self, Self::Error> {
         if let NbtTag::ByteArray(value) = tag {
-            Ok(raw::cast_byte_buf_to_unsigned(value))
+            Ok(cast::byte_buf_to_unsigned(value))
         } else {
             Err(NbtStructureError::type_mismatch(
                 "ByteArray",
@@ -624,44 +1285,274 @@ impl TryFrom<NbtTag> for Vec<u8> {
     }
 }
 
-/// The NBT tag list type which is essentially just a wrapper for a vec of NBT tags.
-///
-/// This type will implement both `Serialize` and `Deserialize` when the serde feature is enabled,
-/// however this type should still be read and written with the utilities in the [`io`] module when
-/// possible if speed is the main priority. See [`NbtTag`] for more details.
-///
-/// [`io`]: crate::io
-/// [`NbtTag`]: crate::NbtTag
-#[repr(transparent)]
-#[derive(Clone, PartialEq)]
-pub struct NbtList(pub(crate) Vec<NbtTag>);
+// Implement comparisons between tags and the primitives they wrap, mirroring serde_json's impls
+macro_rules! tag_eq_prim {
+    ($($type:ty, $tag:ident);*) => {
+        $(
+            impl PartialEq<$type> for NbtTag {
+                #[inline]
+                fn eq(&self, other: &$type) -> bool {
+                    matches!(self, NbtTag::$tag(value) if value == other)
+                }
+            }
 
-impl NbtList {
-    /// Returns a new NBT tag list with an empty internal vec.
+            impl PartialEq<$type> for &NbtTag {
+                #[inline]
+                fn eq(&self, other: &$type) -> bool {
+                    **self == *other
+                }
+            }
+
+            impl PartialEq<$type> for &mut NbtTag {
+                #[inline]
+                fn eq(&self, other: &$type) -> bool {
+                    **self == *other
+                }
+            }
+
+            impl PartialEq<NbtTag> for $type {
+                #[inline]
+                fn eq(&self, other: &NbtTag) -> bool {
+                    *other == *self
+                }
+            }
+        )*
+    };
+}
+
+tag_eq_prim!(
+    i8, Byte;
+    i16, Short;
+    i32, Int;
+    i64, Long;
+    f32, Float;
+    f64, Double;
+    String, String
+);
+
+impl PartialEq<str> for NbtTag {
     #[inline]
-    pub const fn new() -> Self {
-        NbtList(Vec::new())
+    fn eq(&self, other: &str) -> bool {
+        matches!(self, NbtTag::String(value) if value == other)
     }
+}
 
-    /// Returns a mutable reference to the internal vector of this NBT list.
+impl PartialEq<&str> for NbtTag {
     #[inline]
-    pub fn inner_mut(&mut self) -> &mut Vec<NbtTag> {
-        &mut self.0
+    fn eq(&self, other: &&str) -> bool {
+        *self == **other
     }
+}
 
-    /// Returns the internal vector of this NBT list.
+impl PartialEq<str> for &NbtTag {
     #[inline]
-    pub fn into_inner(self) -> Vec<NbtTag> {
-        self.0
+    fn eq(&self, other: &str) -> bool {
+        **self == *other
     }
+}
 
-    /// Returns a new NBT tag list with the given initial capacity.
+impl PartialEq<NbtTag> for str {
     #[inline]
-    pub fn with_capacity(capacity: usize) -> Self {
-        NbtList(Vec::with_capacity(capacity))
+    fn eq(&self, other: &NbtTag) -> bool {
+        *other == *self
     }
+}
 
-    /// Clones the data in the given list and converts it into an [`NbtList`].
+impl PartialEq<NbtTag> for &str {
+    #[inline]
+    fn eq(&self, other: &NbtTag) -> bool {
+        *other == **self
+    }
+}
+
+/// A type which can index into a tag with [`NbtTag::get`]. This trait is implemented for `usize`,
+/// which indexes into lists, and for string types, which index into compounds.
+///
+/// [`NbtTag::get`]: crate::NbtTag::get
+pub trait TagIndex: private::Sealed {
+    /// Returns the tag nested within the given tag at this index, if there is one.
+    fn index_into<'a>(&self, tag: &'a NbtTag) -> Option<&'a NbtTag>;
+}
+
+mod private {
+    pub trait Sealed {}
+}
+
+impl private::Sealed for usize {}
+
+impl TagIndex for usize {
+    #[inline]
+    fn index_into<'a>(&self, tag: &'a NbtTag) -> Option<&'a NbtTag> {
+        match tag {
+            NbtTag::List(list) => list.0.get(*self),
+            _ => None,
+        }
+    }
+}
+
+impl private::Sealed for str {}
+
+impl TagIndex for str {
+    #[inline]
+    fn index_into<'a>(&self, tag: &'a NbtTag) -> Option<&'a NbtTag> {
+        match tag {
+            NbtTag::Compound(compound) => compound.0.get(self),
+            _ => None,
+        }
+    }
+}
+
+impl private::Sealed for String {}
+
+impl TagIndex for String {
+    #[inline]
+    fn index_into<'a>(&self, tag: &'a NbtTag) -> Option<&'a NbtTag> {
+        self.as_str().index_into(tag)
+    }
+}
+
+impl<T: TagIndex + ?Sized> private::Sealed for &T {}
+
+impl<T: TagIndex + ?Sized> TagIndex for &T {
+    #[inline]
+    fn index_into<'a>(&self, tag: &'a NbtTag) -> Option<&'a NbtTag> {
+        (**self).index_into(tag)
+    }
+}
+
+/// Chained lookups on the result of [`NbtTag::get`]. A lookup on `None` returns `None`, so a chain
+/// of lookups short-circuits at the first missing tag or kind mismatch without panicking.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::TagLookup;
+///
+/// let level = NbtTag::Compound(compound! {
+///     "Data": { "Player": { "Pos": [0.5f64, 70.0f64, -12.5f64] } }
+/// });
+///
+/// let y = level.get("Data").get("Player").get("Pos").get(1);
+/// assert_eq!(y, Some(&NbtTag::Double(70.0)));
+/// assert_eq!(level.get("Data").get("Player").get("Spawn").get(1), None);
+/// ```
+///
+/// [`NbtTag::get`]: crate::NbtTag::get
+pub trait TagLookup<'a> {
+    /// Returns the tag nested at the given index within the tag, if there is one. See
+    /// [`NbtTag::get`].
+    ///
+    /// [`NbtTag::get`]: crate::NbtTag::get
+    fn get<I: TagIndex>(self, index: I) -> Option<&'a NbtTag>;
+}
+
+impl<'a> TagLookup<'a> for Option<&'a NbtTag> {
+    #[inline]
+    fn get<I: TagIndex>(self, index: I) -> Option<&'a NbtTag> {
+        self.and_then(|tag| index.index_into(tag))
+    }
+}
+
+/// The NBT tag list type which is essentially just a wrapper for a vec of NBT tags.
+///
+/// This type will implement both `Serialize` and `Deserialize` when the serde feature is enabled,
+/// however this type should still be read and written with the utilities in the [`io`] module when
+/// possible if speed is the main priority. See [`NbtTag`] for more details.
+///
+/// An empty list has no elements to determine its element type from, so it is written with an
+/// element type of `TAG_End` by default, which some strict consumers reject. To avoid this, a list
+/// can carry an [element kind] hint which the [`io`] module and this crate's serde serializer write
+/// in its place. Lists read with the [`io`] module keep the element type they were read with, so
+/// empty lists round trip unchanged. The hint is not considered when comparing lists.
+///
+/// [`io`]: crate::io
+/// [`NbtTag`]: crate::NbtTag
+/// [element kind]: crate::NbtList::element_kind
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    rkyv(derive(Debug))
+)]
+pub struct NbtList(pub(crate) Vec<NbtTag>, pub(crate) Option<NbtTagKind>);
+
+impl NbtList {
+    /// Returns a new NBT tag list with an empty internal vec.
+    #[inline]
+    pub const fn new() -> Self {
+        NbtList(Vec::new(), None)
+    }
+
+    /// Returns a mutable reference to the internal vector of this NBT list.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut Vec<NbtTag> {
+        &mut self.0
+    }
+
+    /// Returns the internal vector of this NBT list.
+    #[inline]
+    pub fn into_inner(self) -> Vec<NbtTag> {
+        self.0
+    }
+
+    /// Returns a new NBT tag list with the given initial capacity.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        NbtList(Vec::with_capacity(capacity), None)
+    }
+
+    /// Returns a new, empty NBT tag list whose elements are of the given kind. See
+    /// [`set_element_kind`] for details.
+    ///
+    /// ```
+    /// # use quartz_nbt::{io::{self, Flavor}, *};
+    /// let nbt = compound! { "Pos": NbtList::with_element_kind(NbtTagKind::Double) };
+    /// let bytes = io::write_nbt_bytes(None, &nbt, Flavor::Uncompressed)?;
+    ///
+    /// let (read, _) = io::read_nbt_bytes(&bytes)?;
+    /// let list: &NbtList = read.get("Pos")?;
+    /// assert_eq!(list.element_kind(), Some(NbtTagKind::Double));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// [`set_element_kind`]: crate::NbtList::set_element_kind
+    #[inline]
+    pub const fn with_element_kind(kind: NbtTagKind) -> Self {
+        NbtList(Vec::new(), Some(kind))
+    }
+
+    /// Returns the kind of the elements of this list. This is the kind of the first element if
+    /// there is one, otherwise it is the hint set through [`set_element_kind`] or read along with
+    /// the list, if any.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// let mut list = NbtList::with_element_kind(NbtTagKind::Int);
+    /// assert_eq!(list.element_kind(), Some(NbtTagKind::Int));
+    ///
+    /// list.push("text");
+    /// assert_eq!(list.element_kind(), Some(NbtTagKind::String));
+    /// assert_eq!(NbtList::new().element_kind(), None);
+    /// ```
+    ///
+    /// [`set_element_kind`]: crate::NbtList::set_element_kind
+    #[inline]
+    pub fn element_kind(&self) -> Option<NbtTagKind> {
+        match self.0.first() {
+            Some(tag) => Some(tag.kind()),
+            None => self.1,
+        }
+    }
+
+    /// Sets the element kind hint of this list, which is written as the element type of the list
+    /// while it is empty. Once the list has elements, the type of its first element is written
+    /// instead. Passing `None` causes empty lists to be written with an element type of
+    /// `TAG_End`.
+    #[inline]
+    pub fn set_element_kind(&mut self, kind: Option<NbtTagKind>) {
+        self.1 = kind;
+    }
+
+    /// Clones the data in the given list and converts it into an [`NbtList`].
     ///
     /// # Examples
     ///
@@ -679,7 +1570,7 @@ impl NbtList {
         T: Clone + Into<NbtTag> + 'a,
         L: IntoIterator<Item = &'a T>,
     {
-        NbtList(list.into_iter().map(|x| x.clone().into()).collect())
+        NbtList(list.into_iter().map(|x| x.clone().into()).collect(), None)
     }
 
     /// Creates an [`NbtList`] of [`NbtCompound`]s by mapping each element in the given list to its
@@ -698,7 +1589,7 @@ impl NbtList {
         T: NbtRepr + 'a,
         L: IntoIterator<Item = &'a T>,
     {
-        NbtList(list.into_iter().map(|x| x.to_nbt().into()).collect())
+        NbtList(list.into_iter().map(|x| x.to_nbt().into()).collect(), None)
     }
 
     /// Iterates over this tag list, converting each tag reference into the specified type.
@@ -734,6 +1625,35 @@ impl NbtList {
         self.0.iter_mut().map(|tag| T::try_from(tag))
     }
 
+    /// Converts the tags in this list into the specified type in parallel. This is worthwhile when
+    /// each conversion is expensive, such as when decoding large numbers of item compounds into
+    /// structs. The list is split into chunks which are converted on rayon's thread pool, and the
+    /// results are produced in the same order as [`iter_map`] when collected.
+    ///
+    /// This method is only available when the `rayon` feature is enabled.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// use rayon::iter::ParallelIterator;
+    ///
+    /// let list = NbtList::clone_from(&(0i32 .. 1000).collect::<Vec<_>>());
+    /// let values = list.par_iter_map::<i32>().collect::<Result<Vec<_>, _>>()?;
+    /// assert_eq!(values, (0 .. 1000).collect::<Vec<_>>());
+    /// # Ok::<_, NbtReprError>(())
+    /// ```
+    ///
+    /// [`iter_map`]: crate::NbtList::iter_map
+    #[cfg(feature = "rayon")]
+    pub fn par_iter_map<'a, T>(
+        &'a self,
+    ) -> impl IndexedParallelIterator<Item = Result<T, <T as TryFrom<&'a NbtTag>>::Error>> + 'a
+    where
+        T: TryFrom<&'a NbtTag> + Send,
+        <T as TryFrom<&'a NbtTag>>::Error: Send,
+    {
+        self.0.par_iter().map(|tag| T::try_from(tag))
+    }
+
     /// Converts this tag list into a valid SNBT string. See `NbtTag::`[`to_snbt`] for details.
     ///
     /// [`to_snbt`]: crate::NbtTag::to_snbt
@@ -749,6 +1669,43 @@ impl NbtList {
         format!("{:#?}", self)
     }
 
+    /// Converts this tag list into a valid SNBT string formatted according to the given options.
+    /// See `NbtTag::`[`to_snbt_with`] for details.
+    ///
+    /// [`to_snbt_with`]: crate::NbtTag::to_snbt_with
+    pub fn to_snbt_with(&self, options: FormatOptions) -> String {
+        format_snbt(options, |f| {
+            self.to_formatted_snbt(&mut String::new(), options, f)
+        })
+    }
+
+    /// Converts this tag list into colored SNBT for display in a terminal. See
+    /// `NbtTag::`[`to_ansi_snbt`] for details.
+    ///
+    /// This method is only available when the `ansi` feature is enabled.
+    ///
+    /// [`to_ansi_snbt`]: crate::NbtTag::to_ansi_snbt
+    #[cfg(feature = "ansi")]
+    pub fn to_ansi_snbt(&self, theme: AnsiTheme) -> String {
+        self.to_snbt_with(FormatOptions::new().pretty(true).ansi_theme(theme))
+    }
+
+    /// Converts this tag list into a human-readable string with the digits of numbers grouped.
+    /// See `NbtTag::`[`to_display_string`] for details.
+    ///
+    /// [`to_display_string`]: crate::NbtTag::to_display_string
+    pub fn to_display_string(&self, options: DisplayOptions) -> String {
+        self.to_snbt_with(options.format_options())
+    }
+
+    /// Converts this tag list into Rust source code which evaluates to an equal list. See
+    /// `NbtTag::`[`to_rust_literal`] for details.
+    ///
+    /// [`to_rust_literal`]: crate::NbtTag::to_rust_literal
+    pub fn to_rust_literal(&self) -> String {
+        RustLiteral::List(self).to_string()
+    }
+
     /// Returns the length of this list.
     #[inline]
     pub fn len(&self) -> usize {
@@ -787,6 +1744,30 @@ impl NbtList {
         .map_err(NbtReprError::from_any)
     }
 
+    /// Returns the value of the tag at the given index after converting it to the type `T` according
+    /// to the given policy, or an error if the index is out of bounds or the conversion fails.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// use quartz_nbt::convert::ConversionPolicy;
+    ///
+    /// let list = NbtList::clone_from(&vec![1i8, 2, 3]);
+    ///
+    /// assert_eq!(list.get_converted::<i32>(0, ConversionPolicy::Widen).unwrap(), 1);
+    /// assert!(list.get_converted::<i32>(0, ConversionPolicy::Strict).is_err());
+    /// ```
+    pub fn get_converted<T: ConversionTarget>(
+        &self,
+        index: usize,
+        policy: ConversionPolicy,
+    ) -> Result<T, NbtStructureError> {
+        let tag = self
+            .0
+            .get(index)
+            .ok_or_else(|| NbtStructureError::invalid_index(index, self.len()))?;
+        policy.convert_to(tag)
+    }
+
     /// Returns a mutable reference to the tag at the given index, or an error if the index is out of bounds or
     /// tag type does not match the type specified. This method should be used for obtaining mutable references
     /// to elements.
@@ -816,6 +1797,276 @@ impl NbtList {
         .map_err(NbtReprError::from_any)
     }
 
+    /// Returns the tags in the given range of this list, or an error if the range is out of bounds.
+    /// Unlike slice indexing, an invalid range will not cause a panic.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// let list = NbtList::clone_from(&vec![1i32, 2, 3, 4, 5]);
+    ///
+    /// assert_eq!(list.get_slice(1 .. 3).unwrap(), &[NbtTag::Int(2), NbtTag::Int(3)]);
+    /// assert_eq!(list.get_slice(3 ..).unwrap().len(), 2);
+    /// assert!(list.get_slice(4 .. 6).is_err()); // Out of bounds
+    /// assert!(list.get_slice(3 .. 2).is_err()); // Start is past the end
+    /// ```
+    #[inline]
+    pub fn get_slice<R>(&self, range: R) -> Result<&[NbtTag], NbtStructureError>
+    where R: RangeBounds<usize> {
+        let range = resolve_range(&range, self.len())?;
+        Ok(&self.0[range])
+    }
+
+    /// Iterates over the tags in the given range of this list, converting each tag reference into
+    /// the specified type. An error is returned if the range is out of bounds, otherwise the
+    /// iterator behaves exactly like the one returned by [`iter_map`](crate::tag::NbtList::iter_map).
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// let list = NbtList::clone_from(&vec![1i32, 2, 3, 4, 5]);
+    ///
+    /// let page = list
+    ///     .iter_range_map::<i32, _>(2 ..= 3)
+    ///     .unwrap()
+    ///     .collect::<Result<Vec<_>, _>>()
+    ///     .unwrap();
+    /// assert_eq!(page, vec![3, 4]);
+    ///
+    /// let error = list.iter_range_map::<i32, _>(4 .. 10).err().unwrap();
+    /// assert_eq!(error.to_string(), "Range out of bounds: 4..10 for length 5");
+    /// ```
+    #[inline]
+    pub fn iter_range_map<'a, T, R>(
+        &'a self,
+        range: R,
+    ) -> Result<
+        impl Iterator<Item = Result<T, <T as TryFrom<&'a NbtTag>>::Error>> + 'a,
+        NbtStructureError,
+    >
+    where
+        T: TryFrom<&'a NbtTag>,
+        R: RangeBounds<usize>,
+    {
+        Ok(self.get_slice(range)?.iter().map(|tag| T::try_from(tag)))
+    }
+
+    /// Promotes every element of this list to the given kind. See `NbtTag::`[`promote`] for the
+    /// conversions which are permitted. If any element cannot be promoted losslessly, then an error
+    /// is returned and this list is left unchanged.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// let mut list = NbtList::clone_from(&vec![1i8, 2, 3]);
+    /// list.promote_numeric(NbtTagKind::Int).unwrap();
+    /// assert_eq!(list, NbtList::clone_from(&vec![1i32, 2, 3]));
+    ///
+    /// let mut list = NbtList::clone_from(&vec![1i32, i32::MAX]);
+    /// assert!(list.promote_numeric(NbtTagKind::Float).is_err());
+    /// assert_eq!(list, NbtList::clone_from(&vec![1i32, i32::MAX]));
+    /// ```
+    ///
+    /// [`promote`]: crate::NbtTag::promote
+    pub fn promote_numeric(&mut self, to: NbtTagKind) -> Result<(), NbtStructureError> {
+        if self.0.iter().all(|tag| tag.kind() == to) {
+            return Ok(());
+        }
+
+        let promoted = self
+            .0
+            .iter()
+            .map(|tag| tag.promote(to).map(Cow::into_owned))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.0 = promoted;
+        Ok(())
+    }
+
+    /// Widens the numbers in this list to the largest kind present, such as turning a list of
+    /// bytes and ints into a list of ints. This repairs lists which mix kinds by accident, such
+    /// as hand-written SNBT where some elements lack a suffix. The kinds are ordered as byte,
+    /// short, int, long, float and double, and are converted as with [`promote_numeric`].
+    ///
+    /// An error is returned and this list is left unchanged if a number cannot be converted
+    /// without loss of precision, or if this list mixes numbers with other kinds of tags. Lists
+    /// of a single kind are left as they are.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// let mut list = NbtList::from(vec![NbtTag::Byte(1), NbtTag::Int(300), NbtTag::Short(-2)]);
+    /// list.normalize_numeric().unwrap();
+    /// assert_eq!(list, NbtList::clone_from(&vec![1i32, 300, -2]));
+    ///
+    /// let mut list = NbtList::from(vec![NbtTag::Int(1), NbtTag::Double(0.5)]);
+    /// list.normalize_numeric().unwrap();
+    /// assert_eq!(list, NbtList::clone_from(&vec![1.0f64, 0.5]));
+    ///
+    /// let mut list = NbtList::from(vec![NbtTag::Long(i64::MAX), NbtTag::Double(0.5)]);
+    /// assert!(list.normalize_numeric().is_err());
+    /// ```
+    ///
+    /// [`promote_numeric`]: crate::NbtList::promote_numeric
+    pub fn normalize_numeric(&mut self) -> Result<(), NbtStructureError> {
+        // Numeric kinds have the IDs 1 to 6, ordered from narrowest to widest
+        match self.0.iter().map(NbtTag::kind).max_by_key(|kind| kind.id()) {
+            Some(widest) if widest.id() <= NbtTagKind::Double.id() => self.promote_numeric(widest),
+            Some(widest) => match self.0.iter().find(|tag| tag.kind() != widest) {
+                Some(tag) => Err(NbtStructureError::type_mismatch(
+                    widest.name(),
+                    tag.tag_name(),
+                )),
+                None => Ok(()),
+            },
+            None => Ok(()),
+        }
+    }
+
+    /// Sorts this list in place. Numbers are ordered numerically, with floats following
+    /// [`f64::total_cmp`], and strings are ordered lexicographically by their UTF-8 bytes. The sort
+    /// is stable.
+    ///
+    /// If the elements of this list are not all numbers or all strings of the same kind, then an
+    /// error is returned and this list is left unchanged.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// let mut list = NbtList::clone_from(&vec![3i32, -1, 2]);
+    /// list.sort().unwrap();
+    /// assert_eq!(list, NbtList::clone_from(&vec![-1i32, 2, 3]));
+    ///
+    /// let mut list = NbtList::from(vec![NbtTag::Int(1), NbtTag::Long(0)]);
+    /// assert!(list.sort().is_err());
+    /// assert_eq!(list, NbtList::from(vec![NbtTag::Int(1), NbtTag::Long(0)]));
+    /// ```
+    pub fn sort(&mut self) -> Result<(), NbtStructureError> {
+        check_sortable(self.0.iter())?;
+        self.0.sort_by(compare_sortable);
+        Ok(())
+    }
+
+    /// Sorts a list of compounds in place by the tag which the given [`NbtPath`] selects in each
+    /// compound, such as `"Slot"` for inventories or `"tag.Damage"` for nested keys. The selected
+    /// tags are ordered in the same way as [`sort`], and the sort is stable.
+    ///
+    /// An error is returned and this list is left unchanged if the path is invalid, if any element
+    /// is not a compound, if the path does not select exactly one tag in every element, or if the
+    /// selected tags are not all numbers or all strings of the same kind.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// let mut inventory = compound! {
+    ///     "Items": [
+    ///         { "Slot": 2i8, "id": "minecraft:torch" },
+    ///         { "Slot": 0i8, "id": "minecraft:stone" },
+    ///         { "Slot": 1i8, "id": "minecraft:dirt" }
+    ///     ]
+    /// };
+    ///
+    /// let items = inventory.get_mut::<_, &mut NbtList>("Items").unwrap();
+    /// items.sort_by_path("Slot").unwrap();
+    /// let ids = items
+    ///     .iter_map::<&NbtCompound>()
+    ///     .map(|item| item.unwrap().get::<_, &str>("id").unwrap())
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(ids, ["minecraft:stone", "minecraft:dirt", "minecraft:torch"]);
+    ///
+    /// // Not every item has a count
+    /// assert!(items.sort_by_path("Count").is_err());
+    /// ```
+    ///
+    /// [`sort`]: crate::NbtList::sort
+    pub fn sort_by_path<P: AsNbtPath + ?Sized>(&mut self, path: &P) -> Result<(), NbtReprError> {
+        let parsed = path.as_nbt_path().map_err(NbtReprError::from_any)?;
+        let keys = self
+            .0
+            .iter()
+            .map(|tag| {
+                let compound = <&NbtCompound>::try_from(tag)?;
+                match parsed.select(compound).as_slice() {
+                    [key] => Ok((*key).clone()),
+                    _ => Err(NbtStructureError::missing_tag(parsed.to_string())),
+                }
+            })
+            .collect::<Result<Vec<_>, NbtStructureError>>()?;
+        check_sortable(keys.iter())?;
+
+        let mut keyed = keys.into_iter().zip(self.0.drain(..)).collect::<Vec<_>>();
+        keyed.sort_by(|(a, _), (b, _)| compare_sortable(a, b));
+        self.0 = keyed.into_iter().map(|(_, tag)| tag).collect();
+        Ok(())
+    }
+
+    /// Returns the first compound in this list for which the given predicate returns `true`.
+    /// Elements which are not compounds are skipped.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// let items = NbtList::from(vec![
+    ///     compound! { "Slot": 0i8, "id": "minecraft:stone" },
+    ///     compound! { "Slot": 5i8, "id": "minecraft:dirt" },
+    /// ]);
+    ///
+    /// let item = items
+    ///     .find_compound(|item| matches!(item.get::<_, i8>("Slot"), Ok(5)))
+    ///     .unwrap();
+    /// assert_eq!(item.get::<_, &str>("id").unwrap(), "minecraft:dirt");
+    /// assert!(items.find_compound(|item| item.contains_key("Count")).is_none());
+    /// ```
+    pub fn find_compound<P>(&self, mut predicate: P) -> Option<&NbtCompound>
+    where P: FnMut(&NbtCompound) -> bool {
+        self.0.iter().find_map(|tag| match tag {
+            NbtTag::Compound(compound) if predicate(compound) => Some(compound),
+            _ => None,
+        })
+    }
+
+    /// Returns the index of and a reference to the first compound in this list in which the given
+    /// [`NbtPath`] selects a tag matching the given value, such as the inventory entry whose
+    /// `"Slot"` is `5b`. Values are matched in the same way as patterns in paths, so compounds and
+    /// lists only need to contain the given entries or elements. See [`matches`] for details.
+    ///
+    /// Elements which are not compounds are skipped, and an error is returned if the path is
+    /// invalid.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// let items = NbtList::from(vec![
+    ///     compound! { "Slot": 0i8, "id": "minecraft:stone" },
+    ///     compound! { "Slot": 5i8, "id": "minecraft:dirt", "tag": { "Damage": 3i32 } },
+    /// ]);
+    ///
+    /// let (index, item) = items.position_by_path("Slot", 5i8).unwrap().unwrap();
+    /// assert_eq!(index, 1);
+    /// assert_eq!(item.get::<_, &str>("id").unwrap(), "minecraft:dirt");
+    ///
+    /// let found = items.position_by_path("tag.Damage", 3i32).unwrap();
+    /// assert_eq!(found.map(|(index, _)| index), Some(1));
+    ///
+    /// // Tags of a different type never match
+    /// assert!(items.position_by_path("Slot", 5i32).unwrap().is_none());
+    /// assert!(items.position_by_path("tag.", 3i32).is_err());
+    /// ```
+    ///
+    /// [`matches`]: crate::path::matches
+    pub fn position_by_path<P: AsNbtPath + ?Sized, T: Into<NbtTag>>(
+        &self,
+        path: &P,
+        value: T,
+    ) -> Result<Option<(usize, &NbtCompound)>, NbtReprError> {
+        let parsed = path.as_nbt_path().map_err(NbtReprError::from_any)?;
+        let value = value.into();
+        Ok(self
+            .0
+            .iter()
+            .enumerate()
+            .find_map(|(index, tag)| match tag {
+                NbtTag::Compound(compound)
+                    if parsed
+                        .select(compound)
+                        .into_iter()
+                        .any(|tag| path::matches_tag(&value, tag)) =>
+                    Some((index, compound)),
+                _ => None,
+            }))
+    }
+
     /// Pushes the given value to the back of the list after wrapping it in an `NbtTag`.
     ///
     /// ```
@@ -833,8 +2084,28 @@ impl NbtList {
         self.0.push(value.into());
     }
 
+    /// Makes this list a deep copy of the given list, reusing the allocations of this list and of
+    /// its elements where possible. See [`NbtTag::clone_into`] for details.
+    ///
+    /// [`NbtTag::clone_into`]: crate::NbtTag::clone_into
+    pub fn clone_from_reusing(&mut self, source: &NbtList) {
+        self.1 = source.1;
+        self.0.truncate(source.len());
+        for (source, target) in source.0.iter().zip(self.0.iter_mut()) {
+            source.clone_into(target);
+        }
+
+        let len = self.0.len();
+        self.0.extend_from_slice(&source.0[len ..]);
+    }
+
     #[allow(clippy::write_with_newline)]
-    fn to_formatted_snbt(&self, indent: &mut String, f: &mut Formatter<'_>) -> fmt::Result {
+    fn to_formatted_snbt(
+        &self,
+        indent: &mut String,
+        options: FormatOptions,
+        f: &mut Formatter<'_>,
+    ) -> fmt::Result {
         if self.is_empty() {
             return write!(f, "[]");
         }
@@ -852,7 +2123,7 @@ impl NbtList {
                 write!(f, "{}", indent)?;
             }
 
-            element.to_formatted_snbt(indent, f)?;
+            element.to_formatted_snbt(indent, options, f)?;
 
             if index != last_index {
                 if f.alternate() {
@@ -872,54 +2143,177 @@ impl NbtList {
     }
 }
 
-impl Default for NbtList {
-    #[inline]
-    fn default() -> Self {
-        NbtList::new()
-    }
-}
+// Displays a value with the given function
+struct Snbt<F>(F);
 
-impl<T: Into<NbtTag>> From<Vec<T>> for NbtList {
-    #[inline]
-    fn from(list: Vec<T>) -> Self {
-        NbtList(list.into_iter().map(|x| x.into()).collect())
+impl<F> Display for Snbt<F>
+where F: Fn(&mut Formatter<'_>) -> fmt::Result
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        (self.0)(f)
     }
 }
 
-impl IntoIterator for NbtList {
-    type IntoIter = <Vec<NbtTag> as IntoIterator>::IntoIter;
-    type Item = NbtTag;
+// Feeds formatted output to a hasher rather than collecting it
+struct HashWriter<'a, H>(&'a mut H);
 
-    #[inline]
-    fn into_iter(self) -> Self::IntoIter {
-        self.0.into_iter()
+impl<H: Hasher> fmt::Write for HashWriter<'_, H> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0.write(s.as_bytes());
+        Ok(())
     }
 }
 
-impl<'a> IntoIterator for &'a NbtList {
-    type IntoIter = <&'a Vec<NbtTag> as IntoIterator>::IntoIter;
-    type Item = &'a NbtTag;
-
-    #[inline]
-    fn into_iter(self) -> Self::IntoIter {
-        self.0.iter()
+// Formats a value with the given function, using the alternate flag for pretty output
+fn format_snbt<F>(options: FormatOptions, format: F) -> String
+where F: Fn(&mut Formatter<'_>) -> fmt::Result {
+    if options.pretty {
+        format!("{:#}", Snbt(format))
+    } else {
+        Snbt(format).to_string()
     }
 }
 
-impl<'a> IntoIterator for &'a mut NbtList {
-    type IntoIter = <&'a mut Vec<NbtTag> as IntoIterator>::IntoIter;
-    type Item = &'a mut NbtTag;
+fn map_values<F>(mut stack: Vec<&mut NbtTag>, f: &mut F)
+where F: FnMut(&mut NbtTag) {
+    while let Some(tag) = stack.pop() {
+        match tag {
+            NbtTag::List(list) => stack.extend(list.0.iter_mut()),
+            NbtTag::Compound(compound) => stack.extend(compound.0.values_mut()),
+            tag => f(tag),
+        }
+    }
+}
 
-    #[inline]
-    fn into_iter(self) -> Self::IntoIter {
-        self.0.iter_mut()
+// Inserts the given tag into the given compound at the path described by its name, returning
+// false if the path is already taken
+fn nest_tag(compound: &mut NbtCompound, name: &str, separator: char, tag: &NbtTag) -> bool {
+    match name.split_once(separator) {
+        Some((head, rest)) => match compound
+            .0
+            .entry(head.to_owned())
+            .or_insert_with(|| NbtTag::Compound(NbtCompound::new()))
+        {
+            NbtTag::Compound(inner) => nest_tag(inner, rest, separator, tag),
+            _ => false,
+        },
+        None if compound.0.contains_key(name) => false,
+        None => {
+            compound.0.insert(name.to_owned(), tag.clone());
+            true
+        }
     }
 }
 
-impl FromIterator<NbtTag> for NbtList {
+// Copies the tags in the given compound into `flat`, with their names joined to the given prefix
+fn flatten_compound(
+    compound: &NbtCompound,
+    separator: char,
+    prefix: &mut String,
+    flat: &mut NbtCompound,
+    conflicts: &mut Vec<Box<str>>,
+) {
+    for (name, tag) in &compound.0 {
+        let prefix_len = prefix.len();
+        prefix.push_str(name);
+
+        match tag {
+            NbtTag::Compound(inner) if !inner.is_empty() => {
+                prefix.push(separator);
+                flatten_compound(inner, separator, prefix, flat, conflicts);
+            }
+            _ =>
+                if flat.0.contains_key(prefix.as_str()) {
+                    conflicts.push(prefix.as_str().into());
+                } else {
+                    flat.0.insert(prefix.clone(), tag.clone());
+                },
+        }
+
+        prefix.truncate(prefix_len);
+    }
+}
+
+// Converts the given range bounds into a concrete range, verifying that it lies within a sequence
+// of the given length
+fn resolve_range<R>(range: &R, length: usize) -> Result<Range<usize>, NbtStructureError>
+where R: RangeBounds<usize> + ?Sized {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => Some(start),
+        Bound::Excluded(&start) => start.checked_add(1),
+        Bound::Unbounded => Some(0),
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end.checked_add(1),
+        Bound::Excluded(&end) => Some(end),
+        Bound::Unbounded => Some(length),
+    };
+
+    match (start, end) {
+        (Some(start), Some(end)) if start <= end && end <= length => Ok(start .. end),
+        (start, end) => Err(NbtStructureError::invalid_range(
+            start.unwrap_or(usize::MAX),
+            end.unwrap_or(usize::MAX),
+            length,
+        )),
+    }
+}
+
+impl PartialEq for NbtList {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl Default for NbtList {
+    #[inline]
+    fn default() -> Self {
+        NbtList::new()
+    }
+}
+
+impl<T: Into<NbtTag>> From<Vec<T>> for NbtList {
+    #[inline]
+    fn from(list: Vec<T>) -> Self {
+        NbtList(list.into_iter().map(|x| x.into()).collect(), None)
+    }
+}
+
+impl IntoIterator for NbtList {
+    type IntoIter = <Vec<NbtTag> as IntoIterator>::IntoIter;
+    type Item = NbtTag;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a NbtList {
+    type IntoIter = <&'a Vec<NbtTag> as IntoIterator>::IntoIter;
+    type Item = &'a NbtTag;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut NbtList {
+    type IntoIter = <&'a mut Vec<NbtTag> as IntoIterator>::IntoIter;
+    type Item = &'a mut NbtTag;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter_mut()
+    }
+}
+
+impl FromIterator<NbtTag> for NbtList {
     #[inline]
     fn from_iter<T: IntoIterator<Item = NbtTag>>(iter: T) -> Self {
-        NbtList(Vec::from_iter(iter))
+        NbtList(Vec::from_iter(iter), None)
     }
 }
 
@@ -956,28 +2350,28 @@ impl Deref for NbtList {
 
     #[inline]
     fn deref(&self) -> &Self::Target {
-        &*self.0
+        &self.0
     }
 }
 
 impl DerefMut for NbtList {
     #[inline]
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut *self.0
+        &mut self.0
     }
 }
 
 impl Display for NbtList {
     #[inline]
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        self.to_formatted_snbt(&mut String::new(), f)
+        self.to_formatted_snbt(&mut String::new(), FormatOptions::default(), f)
     }
 }
 
 impl Debug for NbtList {
     #[inline]
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        self.to_formatted_snbt(&mut String::new(), f)
+        self.to_formatted_snbt(&mut String::new(), FormatOptions::default(), f)
     }
 }
 
@@ -1015,6 +2409,11 @@ impl IndexMut<usize> for NbtList {
 /// [`io`]: crate::io
 #[repr(transparent)]
 #[derive(Clone, PartialEq)]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    rkyv(derive(Debug))
+)]
 pub struct NbtCompound(pub(crate) Map<NbtTag>);
 
 impl NbtCompound {
@@ -1042,209 +2441,1270 @@ impl NbtCompound {
         self.0
     }
 
+    /// Returns the entries of this compound as key-value pairs sorted by key, regardless of the
+    /// order of the internal map. Keys are compared by their UTF-8 bytes, so the result is the same
+    /// on every platform and with or without the `preserve_order` feature, which makes it suitable
+    /// for exporting to formats which must be deterministic.
+    ///
+    /// Only the entries of this compound are sorted, not those of compounds nested within it.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// let compound = compound! { "b": 2i32, "a": 1i32, "B": 3i32 };
+    /// let keys = compound
+    ///     .into_sorted_entries()
+    ///     .into_iter()
+    ///     .map(|(key, _)| key)
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(keys, ["B", "a", "b"]);
+    /// ```
+    pub fn into_sorted_entries(self) -> Vec<(String, NbtTag)> {
+        let mut entries = self.0.into_iter().collect::<Vec<_>>();
+        entries.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        entries
+    }
+
+    /// Removes every entry from this compound and returns them sorted by key, in the same order as
+    /// [`into_sorted_entries`]. Unlike that method, this keeps the allocated capacity of this
+    /// compound, so it can be refilled without reallocating.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// let mut compound = compound! { "y": 64i32, "x": 10i32, "z": -3i32 };
+    /// let values = compound
+    ///     .drain_sorted()
+    ///     .map(|(_, value)| i32::try_from(value).unwrap())
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(values, [10, 64, -3]);
+    /// assert!(compound.is_empty());
+    /// ```
+    ///
+    /// [`into_sorted_entries`]: crate::NbtCompound::into_sorted_entries
+    pub fn drain_sorted(&mut self) -> std::vec::IntoIter<(String, NbtTag)> {
+        #[cfg(feature = "preserve_order")]
+        let drain = self.0.drain(..);
+        #[cfg(not(feature = "preserve_order"))]
+        let drain = self.0.drain();
+
+        let mut entries = drain.collect::<Vec<_>>();
+        entries.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        entries.into_iter()
+    }
+
     /// Returns a new NBT tag compound with the given initial capacity.
     #[inline]
     pub fn with_capacity(capacity: usize) -> Self {
         NbtCompound(Map::with_capacity(capacity))
     }
 
-    /// Clones the data in the given map and converts it into an [`NbtCompound`](crate::tag::NbtCompound).
+    /// Clones the data in the given map and converts it into an [`NbtCompound`](crate::tag::NbtCompound).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use quartz_nbt::NbtCompound;
+    /// # use std::collections::HashMap;
+    /// let mut map = HashMap::new();
+    /// map.insert("foo", 10i32);
+    /// map.insert("bar", -5i32);
+    ///
+    /// let compound = NbtCompound::clone_from(&map);
+    /// assert_eq!(
+    ///     compound.get::<_, i32>("foo").unwrap() + compound.get::<_, i32>("bar").unwrap(),
+    ///     5i32
+    /// );
+    /// ```
+    #[inline]
+    pub fn clone_from<'a, K, V, M>(map: &'a M) -> Self
+    where
+        K: Clone + Into<String> + 'a,
+        V: Clone + Into<NbtTag> + 'a,
+        &'a M: IntoIterator<Item = (&'a K, &'a V)>,
+    {
+        NbtCompound(
+            map.into_iter()
+                .map(|(key, value)| (key.clone().into(), value.clone().into()))
+                .collect(),
+        )
+    }
+
+    /// Creates an [`NbtCompound`] of [`NbtCompound`]s by mapping each element in the given map to its
+    /// NBT representation.
+    ///
+    /// [`NbtCompound`]: crate::tag::NbtCompound
+    #[inline]
+    #[deprecated(
+        since = "0.2.3",
+        note = "This method will eventually be made obsolete with serde compatibility"
+    )]
+    #[allow(deprecated)]
+    pub fn clone_repr_from<'a, K, V, M>(map: &'a M) -> Self
+    where
+        K: Clone + Into<String> + 'a,
+        V: NbtRepr + 'a,
+        &'a M: IntoIterator<Item = (&'a K, &'a V)>,
+    {
+        NbtCompound(
+            map.into_iter()
+                .map(|(key, value)| (key.clone().into(), value.to_nbt().into()))
+                .collect(),
+        )
+    }
+
+    /// Iterates over this tag compound, converting each tag reference into the specified type. Each key is
+    /// paired with the result of the attempted conversion into the specified type. The iterator will not
+    /// terminate even if some conversions fail.
+    #[inline]
+    pub fn iter_map<'a, T: TryFrom<&'a NbtTag>>(
+        &'a self,
+    ) -> impl Iterator<Item = (&'a str, Result<T, <T as TryFrom<&'a NbtTag>>::Error>)> + 'a {
+        self.0
+            .iter()
+            .map(|(key, tag)| (key.as_str(), T::try_from(tag)))
+    }
+
+    /// Iterates over this tag compound, converting each mutable tag reference into the specified type. See
+    /// [`iter_map`](crate::tag::NbtCompound::iter_map) for details.
+    #[inline]
+    pub fn iter_mut_map<'a, T: TryFrom<&'a mut NbtTag>>(
+        &'a mut self,
+    ) -> impl Iterator<Item = (&'a str, Result<T, <T as TryFrom<&'a mut NbtTag>>::Error>)> + 'a
+    {
+        self.0
+            .iter_mut()
+            .map(|(key, tag)| (key.as_str(), T::try_from(tag)))
+    }
+
+    /// Converts the tags in this compound into the specified type in parallel, pairing each key
+    /// with the result of its conversion in no particular order. See
+    /// [`NbtList::par_iter_map`] for details.
+    ///
+    /// This method is only available when the `rayon` feature is enabled.
+    ///
+    /// [`NbtList::par_iter_map`]: crate::NbtList::par_iter_map
+    #[cfg(feature = "rayon")]
+    pub fn par_iter_map<'a, T>(
+        &'a self,
+    ) -> impl ParallelIterator<Item = (&'a str, Result<T, <T as TryFrom<&'a NbtTag>>::Error>)> + 'a
+    where
+        T: TryFrom<&'a NbtTag> + Send,
+        <T as TryFrom<&'a NbtTag>>::Error: Send,
+    {
+        self.0
+            .iter()
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .map(|(key, tag)| (key.as_str(), T::try_from(tag)))
+    }
+
+    /// Converts this tag compound into a valid SNBT string. See `NbtTag::`[`to_snbt`] for details.
+    ///
+    /// [`to_snbt`]: crate::NbtTag::to_snbt
+    pub fn to_snbt(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    /// Converts this tag compound into a valid SNBT string with extra spacing for readability.
+    /// See `NbtTag::`[`to_pretty_snbt`] for details.
+    ///
+    /// [`to_pretty_snbt`]: crate::NbtTag::to_pretty_snbt
+    pub fn to_pretty_snbt(&self) -> String {
+        format!("{:#?}", self)
+    }
+
+    /// Converts this tag compound into a valid SNBT string formatted according to the given options.
+    /// See `NbtTag::`[`to_snbt_with`] for details.
+    ///
+    /// [`to_snbt_with`]: crate::NbtTag::to_snbt_with
+    pub fn to_snbt_with(&self, options: FormatOptions) -> String {
+        format_snbt(options, |f| {
+            self.to_formatted_snbt(&mut String::new(), options, f)
+        })
+    }
+
+    /// Converts this tag compound into its canonical SNBT form, which is compact and has the
+    /// entries of every compound sorted by key as with [`FormatOptions::sort_keys`]. Compounds
+    /// which are equal have the same canonical form, regardless of the order in which their
+    /// entries were inserted.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// let a = compound! { "b": { "y": 1i8, "x": 2i8 }, "a": [L; 3] };
+    /// let b = compound! { "a": [L; 3], "b": { "x": 2i8, "y": 1i8 } };
+    /// assert_eq!(a.to_canonical_snbt(), "{a:[L;3],b:{x:2B,y:1B}}");
+    /// assert_eq!(a.to_canonical_snbt(), b.to_canonical_snbt());
+    /// ```
+    ///
+    /// [`FormatOptions::sort_keys`]: crate::snbt::FormatOptions::sort_keys
+    pub fn to_canonical_snbt(&self) -> String {
+        self.to_snbt_with(FormatOptions::new().sort_keys(true))
+    }
+
+    /// Feeds the canonical SNBT form of this compound, as returned by [`to_canonical_snbt`], into
+    /// the given hasher without building the string. This is useful for keying caches on the
+    /// logical content of compounds, since equal compounds produce equal hashes.
+    ///
+    /// The hash depends only on the canonical form and the hasher, so a hasher with fixed keys
+    /// produces hashes which can be stored and compared across runs. Note that the standard
+    /// library's `DefaultHasher` is not guaranteed to produce the same hashes across Rust
+    /// releases.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// use std::{collections::hash_map::DefaultHasher, hash::Hasher};
+    ///
+    /// fn hash(compound: &NbtCompound) -> u64 {
+    ///     let mut hasher = DefaultHasher::new();
+    ///     compound.canonical_snbt_hash(&mut hasher);
+    ///     hasher.finish()
+    /// }
+    ///
+    /// let a = compound! { "id": "minecraft:stone", "Count": 64i8 };
+    /// let b = compound! { "Count": 64i8, "id": "minecraft:stone" };
+    /// assert_eq!(hash(&a), hash(&b));
+    /// assert_ne!(hash(&a), hash(&compound! { "id": "minecraft:stone", "Count": 64i32 }));
+    ///
+    /// let mut hasher = DefaultHasher::new();
+    /// hasher.write(a.to_canonical_snbt().as_bytes());
+    /// assert_eq!(hash(&a), hasher.finish());
+    /// ```
+    ///
+    /// [`to_canonical_snbt`]: crate::NbtCompound::to_canonical_snbt
+    pub fn canonical_snbt_hash<H: Hasher>(&self, state: &mut H) {
+        let options = FormatOptions::new().sort_keys(true);
+        let canonical =
+            Snbt(|f: &mut Formatter<'_>| self.to_formatted_snbt(&mut String::new(), options, f));
+        // Writing to a hasher cannot fail
+        let _ = fmt::write(&mut HashWriter(state), format_args!("{}", canonical));
+    }
+
+    /// Converts this tag compound into colored SNBT for display in a terminal. See
+    /// `NbtTag::`[`to_ansi_snbt`] for details.
+    ///
+    /// This method is only available when the `ansi` feature is enabled.
+    ///
+    /// [`to_ansi_snbt`]: crate::NbtTag::to_ansi_snbt
+    #[cfg(feature = "ansi")]
+    pub fn to_ansi_snbt(&self, theme: AnsiTheme) -> String {
+        self.to_snbt_with(FormatOptions::new().pretty(true).ansi_theme(theme))
+    }
+
+    /// Converts this tag compound into a human-readable string with the digits of numbers grouped.
+    /// See `NbtTag::`[`to_display_string`] for details.
+    ///
+    /// [`to_display_string`]: crate::NbtTag::to_display_string
+    pub fn to_display_string(&self, options: DisplayOptions) -> String {
+        self.to_snbt_with(options.format_options())
+    }
+
+    /// Converts this tag compound into an invocation of the [`compound!`] macro which evaluates to
+    /// an equal compound. See `NbtTag::`[`to_rust_literal`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// let compound = compound! {
+    ///     "Inventory": [{ "id": "minecraft:stone" }, {}]
+    /// };
+    /// let repr =
+    /// r#"compound! {
+    ///     "Inventory": [
+    ///         {
+    ///             "id": "minecraft:stone"
+    ///         },
+    ///         {}
+    ///     ]
+    /// }"#;
+    /// assert_eq!(compound.to_rust_literal(), repr);
+    /// ```
+    ///
+    /// [`compound!`]: crate::compound
+    /// [`to_rust_literal`]: crate::NbtTag::to_rust_literal
+    pub fn to_rust_literal(&self) -> String {
+        RustLiteral::Compound(self).to_string()
+    }
+
+    /// Returns the number of tags in this compound.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns true if the length of this compound is zero, false otherwise.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the value of the tag with the given name, or an error if no tag exists with the given name
+    /// or specified type. This method should be used to obtain primitives as well as shared references to
+    /// lists and compounds.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// let mut compound = NbtCompound::new();
+    /// compound.insert("test", 1.0f64);
+    ///
+    /// assert!((compound.get::<_, f64>("test").unwrap() - 1.0f64).abs() < 1e-5);
+    /// assert!(compound.get::<_, i32>("test").is_err()); // Type mismatch
+    /// assert!(compound.get::<_, f64>("foo").is_err()); // Missing tag
+    /// ```
+    #[inline]
+    pub fn get<'a, 'b, K, T>(&'a self, name: &'b K) -> Result<T, NbtReprError>
+    where
+        String: Borrow<K>,
+        K: Hash + Eq + ?Sized,
+        &'b K: Into<String>,
+        T: TryFrom<&'a NbtTag>,
+        T::Error: Into<anyhow::Error>,
+    {
+        T::try_from(
+            self.0
+                .get(name)
+                .ok_or_else(|| NbtStructureError::missing_tag(name))?,
+        )
+        .map_err(NbtReprError::from_any)
+    }
+
+    /// Returns the value of the tag with the given name after converting it to the type `T` according
+    /// to the given policy, or an error if no tag exists with the given name or the conversion fails.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// use quartz_nbt::convert::ConversionPolicy;
+    ///
+    /// let compound = compound! { "health": 20.0f32, "xp": 1_000_000i32 };
+    ///
+    /// assert_eq!(compound.get_converted::<_, f64>("health", ConversionPolicy::Widen).unwrap(), 20.0);
+    /// assert_eq!(compound.get_converted::<_, i16>("xp", ConversionPolicy::Saturate).unwrap(), i16::MAX);
+    /// assert!(compound.get_converted::<_, i16>("xp", ConversionPolicy::Widen).is_err());
+    /// ```
+    pub fn get_converted<'b, K, T>(
+        &self,
+        name: &'b K,
+        policy: ConversionPolicy,
+    ) -> Result<T, NbtStructureError>
+    where
+        String: Borrow<K>,
+        K: Hash + Eq + ?Sized,
+        &'b K: Into<String>,
+        T: ConversionTarget,
+    {
+        let tag = self
+            .0
+            .get(name)
+            .ok_or_else(|| NbtStructureError::missing_tag(name))?;
+        policy.convert_to(tag)
+    }
+
+    /// Returns the value of the boolean flag with the given name, or an error if no tag exists with
+    /// the given name or it is not a byte tag containing `0` or `1`.
+    ///
+    /// Unlike `get::<_, bool>`, which treats any nonzero integral tag as `true`, this only accepts
+    /// the encoding the game itself writes, so it is suited to validating untrusted data.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// let compound = compound! { "Invulnerable": 1i8, "NoAI": 2i8, "Silent": 1i32 };
+    ///
+    /// assert!(compound.get_bool_strict("Invulnerable").unwrap());
+    /// assert!(compound.get_bool_strict("NoAI").is_err());
+    /// assert!(compound.get_bool_strict("Silent").is_err());
+    /// assert!(compound.get::<_, bool>("Silent").unwrap());
+    /// ```
+    pub fn get_bool_strict<'b, K>(&self, name: &'b K) -> Result<bool, NbtStructureError>
+    where
+        String: Borrow<K>,
+        K: Hash + Eq + ?Sized,
+        &'b K: Into<String>,
+    {
+        match self.0.get(name) {
+            Some(NbtTag::Byte(0)) => Ok(false),
+            Some(NbtTag::Byte(1)) => Ok(true),
+            Some(NbtTag::Byte(_)) => Err(NbtStructureError::lossy_conversion("Byte", "bool")),
+            Some(tag) => Err(NbtStructureError::type_mismatch("Byte", tag.tag_name())),
+            None => Err(NbtStructureError::missing_tag(name)),
+        }
+    }
+
+    /// Copies the value of every tag in this compound into a map of strings. This is useful for
+    /// compounds such as `GameRules` which only contain string values.
+    ///
+    /// If any tag is not a string, then an error listing the name of every such tag is returned.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// let rules = compound! { "doDaylightCycle": "true", "randomTickSpeed": "3" };
+    /// let map = rules.to_string_map().unwrap();
+    /// assert_eq!(map["randomTickSpeed"], "3");
+    ///
+    /// let mixed = compound! { "doDaylightCycle": "true", "randomTickSpeed": 3i32 };
+    /// let error = mixed.to_string_map().unwrap_err();
+    /// assert!(error.to_string().contains("\"randomTickSpeed\""));
+    /// ```
+    pub fn to_string_map(&self) -> Result<Map<String>, NbtStructureError> {
+        let mut map = Map::with_capacity(self.0.len());
+        let mut non_conforming = Vec::new();
+        for (name, tag) in &self.0 {
+            match tag {
+                NbtTag::String(value) => {
+                    map.insert(name.clone(), value.clone());
+                }
+                _ => non_conforming.push(name.as_str().into()),
+            }
+        }
+
+        if non_conforming.is_empty() {
+            Ok(map)
+        } else {
+            Err(NbtStructureError::non_conforming_keys(
+                NbtTagKind::String.name(),
+                non_conforming,
+            ))
+        }
+    }
+
+    /// Converts the value of every tag in this compound to the type `T` according to the given
+    /// policy, and collects the results into a map.
+    ///
+    /// If any tag cannot be converted, then an error listing the name of every such tag is
+    /// returned.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// use quartz_nbt::convert::ConversionPolicy;
+    ///
+    /// let stats = compound! { "kills": 12i32, "deaths": 3i16, "name": "Ferris" };
+    ///
+    /// let error = stats.to_numeric_map::<i64>(ConversionPolicy::Widen).unwrap_err();
+    /// assert!(error.to_string().contains("\"name\""));
+    ///
+    /// let stats = compound! { "kills": 12i32, "deaths": 3i16 };
+    /// let map = stats.to_numeric_map::<i64>(ConversionPolicy::Widen).unwrap();
+    /// assert_eq!(map["deaths"], 3);
+    /// assert!(stats.to_numeric_map::<i64>(ConversionPolicy::Strict).is_err());
+    /// ```
+    pub fn to_numeric_map<T>(&self, policy: ConversionPolicy) -> Result<Map<T>, NbtStructureError>
+    where T: ConversionTarget {
+        let mut map = Map::with_capacity(self.0.len());
+        let mut non_conforming = Vec::new();
+        for (name, tag) in &self.0 {
+            match policy.convert_to::<T>(tag) {
+                Ok(value) => {
+                    map.insert(name.clone(), value);
+                }
+                Err(_) => non_conforming.push(name.as_str().into()),
+            }
+        }
+
+        if non_conforming.is_empty() {
+            Ok(map)
+        } else {
+            Err(NbtStructureError::non_conforming_keys(
+                T::KIND.name(),
+                non_conforming,
+            ))
+        }
+    }
+
+    /// Returns the value of the tag with the given name, or an error if no tag exists with the given name
+    /// or specified type. This method should be used to obtain mutable references to lists and compounds.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// let mut compound = NbtCompound::new();
+    /// compound.insert("test", 1.0f64);
+    ///
+    /// *compound.get_mut::<_, &mut f64>("test").unwrap() *= 2.0;
+    ///
+    /// assert!((compound.get::<_, f64>("test").unwrap() - 2.0f64).abs() < 1e-5);
+    /// assert!(compound.get::<_, i32>("test").is_err()); // Type mismatch
+    /// assert!(compound.get::<_, f64>("foo").is_err()); // Missing tag
+    /// ```
+    #[inline]
+    pub fn get_mut<'a, 'b, K, T>(&'a mut self, name: &'b K) -> Result<T, NbtReprError>
+    where
+        String: Borrow<K>,
+        K: Hash + Eq + ?Sized,
+        &'b K: Into<String>,
+        T: TryFrom<&'a mut NbtTag>,
+        T::Error: Into<anyhow::Error>,
+    {
+        T::try_from(
+            self.0
+                .get_mut(name)
+                .ok_or_else(|| NbtStructureError::missing_tag(name))?,
+        )
+        .map_err(NbtReprError::from_any)
+    }
+
+    /// Returns whether or not this compound has a tag with the given name.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// let mut compound = NbtCompound::new();
+    /// compound.insert("test", 1.0f64);
+    ///
+    /// assert!(compound.contains_key("test"));
+    /// assert!(!compound.contains_key("foo"));
+    /// ```
+    #[inline]
+    pub fn contains_key<K>(&self, key: &K) -> bool
+    where
+        String: Borrow<K>,
+        K: Hash + Eq + ?Sized,
+    {
+        self.0.contains_key(key)
+    }
+
+    /// Adds the given value to this compound with the given name after wrapping that value in an `NbtTag`.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// let mut compound = NbtCompound::new();
+    /// compound.insert("test", 1.0f64);
+    ///
+    /// assert!((compound.get::<_, f64>("test").unwrap() - 1.0f64).abs() < 1e-5);
+    /// ```
+    ///
+    /// Borrowed slices and fixed-size arrays of `i8`, `u8`, `i32`, and `i64` are copied into the
+    /// corresponding array tag.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// let positions: &[i32] = &[4, -2, 17];
+    ///
+    /// let mut compound = NbtCompound::new();
+    /// compound.insert("positions", positions);
+    /// compound.insert("uuid", [1i32, 2, 3, 4]);
+    /// compound.insert("bytes", [0u8, 255]);
+    ///
+    /// assert_eq!(compound.get::<_, &[i32]>("positions").unwrap(), positions);
+    /// assert_eq!(compound.get::<_, &[i32]>("uuid").unwrap(), &[1, 2, 3, 4]);
+    /// assert_eq!(compound.get::<_, &[u8]>("bytes").unwrap(), &[0, 255]);
+    /// ```
+    #[inline]
+    pub fn insert<K: Into<String>, T: Into<NbtTag>>(&mut self, name: K, value: T) {
+        self.0.insert(name.into(), value.into());
+    }
+
+    /// Sets the tags which the given [`NbtPath`] selects in this compound to the given value,
+    /// creating any compounds and lists along the path which do not exist yet. This is the
+    /// counterpart of [`NbtPath::select`], and follows the behavior of Minecraft's
+    /// `/data modify ... set` command, so a path such as `Items[]` sets every element of a list.
+    ///
+    /// List indices must refer to existing elements. Use [`insert_path_with`] to extend lists
+    /// instead.
+    ///
+    /// An error is returned if the path is invalid, if it selects nothing, such as when a pattern
+    /// does not match, if an existing tag along the path has the wrong type, or if a list would
+    /// end up holding tags of different types. Tags created before an error was encountered are
+    /// left in place.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// let mut level = NbtCompound::new();
+    /// level.insert_path("Level.Player.Abilities.mayfly", true)?;
+    /// level.insert_path("Level.Player.Inventory", NbtList::new())?;
+    /// assert_eq!(level, compound! {
+    ///     "Level": { "Player": { "Abilities": { "mayfly": 1i8 }, "Inventory": [] } }
+    /// });
+    ///
+    /// level.insert_path("Level.Player.Abilities.mayfly", false)?;
+    /// assert!(level.insert_path("Level.Player.Inventory[0]", "stone").is_err());
+    /// assert!(level.insert_path("Level.Player.Abilities.mayfly.x", 1i32).is_err());
+    /// # Ok::<(), NbtReprError>(())
+    /// ```
+    ///
+    /// [`insert_path_with`]: crate::NbtCompound::insert_path_with
+    /// [`NbtPath::select`]: crate::path::NbtPath::select
+    pub fn insert_path<P: AsNbtPath + ?Sized, T: Into<NbtTag>>(
+        &mut self,
+        path: &P,
+        value: T,
+    ) -> Result<(), NbtReprError> {
+        self.insert_path_with(path, value, ListPadding::Reject)
+    }
+
+    /// Sets the tags which the given [`NbtPath`] selects in this compound to the given value just
+    /// like [`insert_path`], but indices past the end of a list are handled according to the
+    /// given padding policy.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// use quartz_nbt::path::ListPadding;
+    ///
+    /// let mut player = NbtCompound::new();
+    /// player.insert_path_with("Pos[2]", 64.5f64, ListPadding::Fill)?;
+    /// player.insert_path_with("Inventory[1].id", "minecraft:dirt", ListPadding::Fill)?;
+    /// assert_eq!(player, compound! {
+    ///     "Pos": [0.0f64, 0.0f64, 64.5f64],
+    ///     "Inventory": [{}, { "id": "minecraft:dirt" }]
+    /// });
+    ///
+    /// // Elements matching a pattern are created if none exist
+    /// player.insert_path_with("Inventory[{Slot:3b}].Count", 1i8, ListPadding::Fill)?;
+    /// assert_eq!(player.get::<_, &NbtList>("Inventory")?.len(), 3);
+    /// # Ok::<(), NbtReprError>(())
+    /// ```
+    ///
+    /// [`insert_path`]: crate::NbtCompound::insert_path
+    pub fn insert_path_with<P: AsNbtPath + ?Sized, T: Into<NbtTag>>(
+        &mut self,
+        path: &P,
+        value: T,
+        padding: ListPadding,
+    ) -> Result<(), NbtReprError> {
+        let parsed = path.as_nbt_path().map_err(NbtReprError::from_any)?;
+        let options = InsertOptions {
+            padding,
+            merge: false,
+        };
+        match parsed.insert(self, &value.into(), options)? {
+            0 => Err(NbtStructureError::missing_tag(parsed.to_string()).into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Removes the tag which the given [`NbtPath`] selects in this compound and returns it, as
+    /// with Minecraft's `/data remove` command. The compounds and lists along the path are kept,
+    /// even if they are left empty.
+    ///
+    /// An error is returned and nothing is removed if the path is invalid or if it does not
+    /// select exactly one tag. Use [`take_path`] to remove any number of tags.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// let mut item = compound! {
+    ///     "id": "minecraft:diamond_sword",
+    ///     "tag": { "Damage": 3i32, "Enchantments": [{ "id": "sharpness", "lvl": 5i16 }] }
+    /// };
+    ///
+    /// assert_eq!(item.remove_path("tag.Damage")?, NbtTag::Int(3));
+    /// let enchantment = item.remove_path("tag.Enchantments[{id:\"sharpness\"}]")?;
+    /// assert_eq!(enchantment, NbtTag::Compound(compound! { "id": "sharpness", "lvl": 5i16 }));
+    /// assert_eq!(item, compound! {
+    ///     "id": "minecraft:diamond_sword",
+    ///     "tag": { "Enchantments": [] }
+    /// });
+    ///
+    /// assert!(item.remove_path("tag.Damage").is_err());
+    /// # Ok::<(), NbtReprError>(())
+    /// ```
+    ///
+    /// [`take_path`]: crate::NbtCompound::take_path
+    pub fn remove_path<P: AsNbtPath + ?Sized>(&mut self, path: &P) -> Result<NbtTag, NbtReprError> {
+        let parsed = path.as_nbt_path().map_err(NbtReprError::from_any)?;
+        if parsed.select(self).len() != 1 {
+            return Err(NbtStructureError::missing_tag(parsed.to_string()).into());
+        }
+
+        Ok(parsed.remove(self, false).pop().unwrap())
+    }
+
+    /// Removes every tag which the given [`NbtPath`] selects in this compound, returning them in
+    /// the order they appeared in their parents. If `prune_empty` is `true`, compounds and lists
+    /// along the path which are left empty by the removal are removed as well.
+    ///
+    /// An error is returned if the path is invalid. A path which selects nothing removes nothing.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// let mut player = compound! {
+    ///     "Inventory": [
+    ///         { "Slot": 0i8, "id": "minecraft:stone", "tag": { "Damage": 1i32 } },
+    ///         { "Slot": 1i8, "id": "minecraft:dirt" }
+    ///     ]
+    /// };
+    ///
+    /// let damage = player.take_path("Inventory[].tag.Damage", true)?;
+    /// assert_eq!(damage, vec![NbtTag::Int(1)]);
+    /// assert_eq!(player, compound! {
+    ///     "Inventory": [
+    ///         { "Slot": 0i8, "id": "minecraft:stone" },
+    ///         { "Slot": 1i8, "id": "minecraft:dirt" }
+    ///     ]
+    /// });
+    ///
+    /// assert_eq!(player.take_path("Inventory[].id", false)?.len(), 2);
+    /// assert_eq!(player.take_path("Inventory[{Slot:0b}]", true)?.len(), 1);
+    /// assert_eq!(player.take_path("Inventory[{Slot:1b}]", true)?.len(), 1);
+    /// assert!(player.is_empty());
+    /// # Ok::<(), NbtReprError>(())
+    /// ```
+    pub fn take_path<P: AsNbtPath + ?Sized>(
+        &mut self,
+        path: &P,
+        prune_empty: bool,
+    ) -> Result<Vec<NbtTag>, NbtReprError> {
+        let parsed = path.as_nbt_path().map_err(NbtReprError::from_any)?;
+        Ok(parsed.remove(self, prune_empty))
+    }
+
+    /// Returns a reference to the tag which the given [`NbtPath`] selects in this compound.
+    ///
+    /// An error is returned if the path is invalid or if it does not select exactly one tag. Use
+    /// [`NbtPath::select`] to get any number of tags.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// let item = compound! {
+    ///     "components": { "minecraft:custom_name": "Sword", "minecraft:lore": ["Sharp"] }
+    /// };
+    ///
+    /// let name = item.get_path("components.\"minecraft:custom_name\"")?;
+    /// assert_eq!(name, &NbtTag::from("Sword"));
+    /// assert_eq!(item.get_path("components.'minecraft:lore'[0]")?, &NbtTag::from("Sharp"));
+    ///
+    /// assert!(item.get_path("components.\"minecraft:lore\"[1]").is_err());
+    /// # Ok::<(), NbtReprError>(())
+    /// ```
+    ///
+    /// [`NbtPath::select`]: crate::path::NbtPath::select
+    pub fn get_path<P: AsNbtPath + ?Sized>(&self, path: &P) -> Result<&NbtTag, NbtReprError> {
+        let parsed = path.as_nbt_path().map_err(NbtReprError::from_any)?;
+        match parsed.select(self).as_slice() {
+            [tag] => Ok(*tag),
+            _ => Err(NbtStructureError::missing_tag(parsed.to_string()).into()),
+        }
+    }
+
+    /// Returns the value of the tag which the given [`NbtPath`] selects in this compound after
+    /// converting it to the type `T` according to the given policy.
+    ///
+    /// An error is returned if the path is invalid, if it does not select exactly one tag, or if
+    /// the conversion fails.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// use quartz_nbt::convert::ConversionPolicy;
+    ///
+    /// let player = compound! { "Inventory": [{ "Slot": 0i8, "Count": 300i32 }] };
+    ///
+    /// let count: i8 = player.get_path_converted("Inventory[0].Count", ConversionPolicy::Saturate)?;
+    /// assert_eq!(count, i8::MAX);
+    /// let slot: i64 = player.get_path_converted("Inventory[0].Slot", ConversionPolicy::Widen)?;
+    /// assert_eq!(slot, 0);
+    ///
+    /// assert!(player
+    ///     .get_path_converted::<_, i8>("Inventory[0].Count", ConversionPolicy::Widen)
+    ///     .is_err());
+    /// # Ok::<(), NbtReprError>(())
+    /// ```
+    pub fn get_path_converted<P, T>(
+        &self,
+        path: &P,
+        policy: ConversionPolicy,
+    ) -> Result<T, NbtReprError>
+    where
+        P: AsNbtPath + ?Sized,
+        T: ConversionTarget,
+    {
+        policy.convert_to(self.get_path(path)?).map_err(Into::into)
+    }
+
+    /// Returns a copy of the tag which the given [`NbtPath`] selects in this compound, as with the
+    /// source of Minecraft's `/data modify ... from` command.
+    ///
+    /// An error is returned if the path is invalid or if it does not select exactly one tag.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// let mut player = compound! { "Inventory": [{ "Slot": 0i8, "id": "minecraft:stone" }] };
+    ///
+    /// let item = player.copy_path("Inventory[{Slot:0b}]")?;
+    /// player.insert_path("EnderItems", NbtList::from(vec![item]))?;
+    /// assert_eq!(player.get::<_, &NbtList>("EnderItems")?.len(), 1);
+    /// assert_eq!(player.get::<_, &NbtList>("Inventory")?.len(), 1);
+    ///
+    /// assert!(player.copy_path("Inventory[1]").is_err());
+    /// # Ok::<(), NbtReprError>(())
+    /// ```
+    pub fn copy_path<P: AsNbtPath + ?Sized>(&self, path: &P) -> Result<NbtTag, NbtReprError> {
+        self.get_path(path).cloned()
+    }
+
+    /// Moves the tag which one [`NbtPath`] selects in this compound to the tags which another
+    /// selects. The destination is written in the same way as with [`insert_path`], and tags which
+    /// already exist there are handled according to the given conflict policy.
+    ///
+    /// An error is returned and the source tag is left in place if either path is invalid, if the
+    /// source path does not select exactly one tag, or if the tag cannot be inserted at the
+    /// destination. The source tag is removed before it is inserted, so the destination may not
+    /// pass through it.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// use quartz_nbt::path::PathConflict;
+    ///
+    /// let mut item = compound! {
+    ///     "display": { "Name": "Sword", "Lore": ["Old"] },
+    ///     "Backup": { "Lore": ["Sharp"], "Color": 5i32 }
+    /// };
+    ///
+    /// item.move_path("Backup", "display", PathConflict::Merge)?;
+    /// assert_eq!(item, compound! {
+    ///     "display": { "Name": "Sword", "Lore": ["Sharp"], "Color": 5i32 }
+    /// });
+    ///
+    /// item.move_path("display.Color", "Color", PathConflict::Error)?;
+    /// assert!(item.move_path("display.Name", "Color", PathConflict::Error).is_err());
+    /// item.move_path("display.Name", "Color", PathConflict::Replace)?;
+    /// assert_eq!(item, compound! { "display": { "Lore": ["Sharp"] }, "Color": "Sword" });
+    /// # Ok::<(), NbtReprError>(())
+    /// ```
+    ///
+    /// [`insert_path`]: crate::NbtCompound::insert_path
+    pub fn move_path<P, Q>(
+        &mut self,
+        from: &P,
+        to: &Q,
+        conflict: PathConflict,
+    ) -> Result<(), NbtReprError>
+    where
+        P: AsNbtPath + ?Sized,
+        Q: AsNbtPath + ?Sized,
+    {
+        let source = from.as_nbt_path().map_err(NbtReprError::from_any)?;
+        let destination = to.as_nbt_path().map_err(NbtReprError::from_any)?;
+        let location = source
+            .locate_one(self)
+            .ok_or_else(|| NbtStructureError::missing_tag(source.to_string()))?;
+        if conflict == PathConflict::Error && !destination.select(self).is_empty() {
+            let to = destination.to_string().into_boxed_str();
+            return Err(NbtStructureError::conflicting_keys(vec![to]).into());
+        }
+
+        // The location was just found, so there is a tag to remove
+        let tag = path::remove_at(self, &location).unwrap();
+        let options = InsertOptions {
+            padding: ListPadding::Reject,
+            merge: conflict == PathConflict::Merge,
+        };
+        match destination.insert(self, &tag, options) {
+            Ok(0) => {
+                path::restore_at(self, &location, tag);
+                Err(NbtStructureError::missing_tag(destination.to_string()).into())
+            }
+            Ok(_) => Ok(()),
+            Err(error) => {
+                path::restore_at(self, &location, tag);
+                Err(error.into())
+            }
+        }
+    }
+
+    /// Calls the given function on every tag nested within this compound, apart from lists and
+    /// compounds, which are traversed instead. See `NbtTag::`[`map_values`] for details.
+    ///
+    /// [`map_values`]: crate::NbtTag::map_values
+    pub fn map_values<F>(&mut self, mut f: F)
+    where F: FnMut(&mut NbtTag) {
+        map_values(self.0.values_mut().collect(), &mut f);
+    }
+
+    /// Replaces the value of every string tag nested within this compound with the value returned
+    /// by the given function, if any. Keys are left unchanged.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// let mut compound = compound! {
+    ///     "id": "minecraft:grass",
+    ///     "Items": [{ "id": " minecraft:stone " }, { "id": "minecraft:grass" }]
+    /// };
+    ///
+    /// compound.map_strings(|string| match string.trim() {
+    ///     "minecraft:grass" => Some("minecraft:short_grass".to_owned()),
+    ///     trimmed if trimmed.len() != string.len() => Some(trimmed.to_owned()),
+    ///     _ => None,
+    /// });
+    /// assert_eq!(compound, compound! {
+    ///     "id": "minecraft:short_grass",
+    ///     "Items": [{ "id": "minecraft:stone" }, { "id": "minecraft:short_grass" }]
+    /// });
+    /// ```
+    pub fn map_strings<F>(&mut self, mut f: F)
+    where F: FnMut(&str) -> Option<String> {
+        self.map_values(|tag| {
+            if let NbtTag::String(string) = tag {
+                if let Some(mapped) = f(string) {
+                    *string = mapped;
+                }
+            }
+        });
+    }
+
+    /// Returns an iterator over every tag nested within this compound which is not a list or
+    /// compound, along with the path leading to it from this compound. Each path consists only of
+    /// keys and list indices, so it selects exactly the tag it is paired with. The traversal is
+    /// iterative and depth first, visiting the elements of lists in order.
     ///
-    /// # Examples
+    /// Empty lists and compounds have no leaves, so they are skipped.
     ///
     /// ```
-    /// # use quartz_nbt::NbtCompound;
-    /// # use std::collections::HashMap;
-    /// let mut map = HashMap::new();
-    /// map.insert("foo", 10i32);
-    /// map.insert("bar", -5i32);
+    /// # use quartz_nbt::*;
+    /// let compound = compound! {
+    ///     "Pos": [1.5f64, 64.0f64],
+    ///     "Attributes": [{ "Name": "generic.max_health", "Base": 20.0f64 }],
+    ///     "Tags": []
+    /// };
     ///
-    /// let compound = NbtCompound::clone_from(&map);
-    /// assert_eq!(
-    ///     compound.get::<_, i32>("foo").unwrap() + compound.get::<_, i32>("bar").unwrap(),
-    ///     5i32
-    /// );
+    /// let mut leaves = compound
+    ///     .leaves()
+    ///     .map(|(path, tag)| format!("{} = {}", path, tag))
+    ///     .collect::<Vec<_>>();
+    /// leaves.sort();
+    /// assert_eq!(leaves, [
+    ///     "Attributes[0].Base = 20D",
+    ///     "Attributes[0].Name = generic.max_health",
+    ///     "Pos[0] = 1.5D",
+    ///     "Pos[1] = 64D"
+    /// ]);
     /// ```
-    #[inline]
-    pub fn clone_from<'a, K, V, M>(map: &'a M) -> Self
-    where
-        K: Clone + Into<String> + 'a,
-        V: Clone + Into<NbtTag> + 'a,
-        &'a M: IntoIterator<Item = (&'a K, &'a V)>,
-    {
-        NbtCompound(
-            map.into_iter()
-                .map(|(key, value)| (key.clone().into(), value.clone().into()))
-                .collect(),
-        )
+    pub fn leaves(&self) -> impl Iterator<Item = (NbtPath, &NbtTag)> + '_ {
+        path::Leaves::new(self)
     }
 
-    /// Creates an [`NbtCompound`] of [`NbtCompound`]s by mapping each element in the given map to its
-    /// NBT representation.
+    /// Returns a parallel iterator over the same leaves as [`leaves`], in no particular order. The
+    /// entries of this compound are walked in parallel with each other.
     ///
-    /// [`NbtCompound`]: crate::tag::NbtCompound
-    #[inline]
-    #[deprecated(
-        since = "0.2.3",
-        note = "This method will eventually be made obsolete with serde compatibility"
-    )]
-    #[allow(deprecated)]
-    pub fn clone_repr_from<'a, K, V, M>(map: &'a M) -> Self
-    where
-        K: Clone + Into<String> + 'a,
-        V: NbtRepr + 'a,
-        &'a M: IntoIterator<Item = (&'a K, &'a V)>,
-    {
-        NbtCompound(
-            map.into_iter()
-                .map(|(key, value)| (key.clone().into(), value.to_nbt().into()))
-                .collect(),
-        )
-    }
-
-    /// Iterates over this tag compound, converting each tag reference into the specified type. Each key is
-    /// paired with the result of the attempted conversion into the specified type. The iterator will not
-    /// terminate even if some conversions fail.
-    #[inline]
-    pub fn iter_map<'a, T: TryFrom<&'a NbtTag>>(
-        &'a self,
-    ) -> impl Iterator<Item = (&'a str, Result<T, <T as TryFrom<&'a NbtTag>>::Error>)> + 'a {
+    /// This method is only available when the `rayon` feature is enabled.
+    ///
+    /// [`leaves`]: crate::NbtCompound::leaves
+    #[cfg(feature = "rayon")]
+    pub fn par_leaves(&self) -> impl ParallelIterator<Item = (NbtPath, &NbtTag)> + '_ {
         self.0
             .iter()
-            .map(|(key, tag)| (key.as_str(), T::try_from(tag)))
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .flat_map_iter(|(key, tag)| path::Leaves::from_entry(key, tag))
     }
 
-    /// Iterates over this tag compound, converting each mutable tag reference into the specified type. See
-    /// [`iter_map`](crate::tag::NbtCompound::iter_map) for details.
-    #[inline]
-    pub fn iter_mut_map<'a, T: TryFrom<&'a mut NbtTag>>(
-        &'a mut self,
-    ) -> impl Iterator<Item = (&'a str, Result<T, <T as TryFrom<&'a mut NbtTag>>::Error>)> + 'a
-    {
+    /// Returns an iterator over the names of the tags in this compound which start with the given
+    /// prefix.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// let compound = compound! { "Paper.Origin": [0.0f64, 64.0f64, 0.0f64], "Health": 20.0f32 };
+    /// let keys = compound.keys_with_prefix("Paper.").collect::<Vec<_>>();
+    /// assert_eq!(keys, ["Paper.Origin"]);
+    /// ```
+    pub fn keys_with_prefix<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = &'a str> + 'a {
         self.0
-            .iter_mut()
-            .map(|(key, tag)| (key.as_str(), T::try_from(tag)))
+            .keys()
+            .filter(move |name| name.starts_with(prefix))
+            .map(String::as_str)
     }
 
-    /// Converts this tag compound into a valid SNBT string. See `NbtTag::`[`to_snbt`] for details.
+    /// Returns a copy of the tags in this compound whose names start with the given prefix. The
+    /// names are copied as they are, including the prefix.
     ///
-    /// [`to_snbt`]: crate::NbtTag::to_snbt
-    pub fn to_snbt(&self) -> String {
-        format!("{:?}", self)
+    /// ```
+    /// # use quartz_nbt::*;
+    /// let compound = compound! {
+    ///     "Paper.Origin": [0.0f64, 64.0f64, 0.0f64],
+    ///     "Paper.SpawnReason": "NATURAL",
+    ///     "Health": 20.0f32
+    /// };
+    /// assert_eq!(compound.subset("Paper."), compound! {
+    ///     "Paper.Origin": [0.0f64, 64.0f64, 0.0f64],
+    ///     "Paper.SpawnReason": "NATURAL"
+    /// });
+    /// ```
+    pub fn subset(&self, prefix: &str) -> NbtCompound {
+        NbtCompound(
+            self.0
+                .iter()
+                .filter(|(name, _)| name.starts_with(prefix))
+                .map(|(name, tag)| (name.clone(), tag.clone()))
+                .collect(),
+        )
     }
 
-    /// Converts this tag compound into a valid SNBT string with extra spacing for readability.
-    /// See `NbtTag::`[`to_pretty_snbt`] for details.
+    /// Removes every tag from this compound whose name starts with the given prefix, returning the
+    /// removed tags.
     ///
-    /// [`to_pretty_snbt`]: crate::NbtTag::to_pretty_snbt
-    pub fn to_pretty_snbt(&self) -> String {
-        format!("{:#?}", self)
+    /// ```
+    /// # use quartz_nbt::*;
+    /// let mut compound = compound! {
+    ///     "Paper.Origin": [0.0f64, 64.0f64, 0.0f64],
+    ///     "Paper.SpawnReason": "NATURAL",
+    ///     "Health": 20.0f32
+    /// };
+    ///
+    /// let removed = compound.remove_prefixed("Paper.");
+    /// assert_eq!(removed.len(), 2);
+    /// assert_eq!(compound, compound! { "Health": 20.0f32 });
+    /// ```
+    pub fn remove_prefixed(&mut self, prefix: &str) -> NbtCompound {
+        if !self.0.keys().any(|name| name.starts_with(prefix)) {
+            return NbtCompound::new();
+        }
+
+        let (removed, kept) = std::mem::take(&mut self.0)
+            .into_iter()
+            .partition(|(name, _)| name.starts_with(prefix));
+        self.0 = kept;
+        NbtCompound(removed)
     }
 
-    /// Returns the number of tags in this compound.
-    #[inline]
-    pub fn len(&self) -> usize {
-        self.0.len()
+    /// Converts the flat names of the tags in this compound into nested compounds by splitting them
+    /// at the given separator, such that a tag named `"a.b.c"` is moved to the path `a.b.c`. This
+    /// is the reverse of [`flatten`]. Only the names of the tags directly in this compound are
+    /// split; tags which are already compounds are merged with the compounds created for other
+    /// names.
+    ///
+    /// If a tag cannot be placed because its path passes through or ends at a tag which is already
+    /// present, such as with `"a": 1` and `"a.b": 2`, an error listing the names of every such tag
+    /// is returned.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// let flat = compound! {
+    ///     "display.Name": "Sword",
+    ///     "display.Lore": ["Sharp"],
+    ///     "Damage": 3i32
+    /// };
+    /// assert_eq!(flat.nest('.')?, compound! {
+    ///     "display": { "Name": "Sword", "Lore": ["Sharp"] },
+    ///     "Damage": 3i32
+    /// });
+    ///
+    /// assert!(compound! { "a": 1i32, "a.b": 2i32 }.nest('.').is_err());
+    /// # Ok::<(), NbtStructureError>(())
+    /// ```
+    ///
+    /// [`flatten`]: crate::NbtCompound::flatten
+    pub fn nest(&self, separator: char) -> Result<NbtCompound, NbtStructureError> {
+        let mut nested = NbtCompound::new();
+        let mut conflicts = Vec::new();
+        for (name, tag) in &self.0 {
+            if !nest_tag(&mut nested, name, separator, tag) {
+                conflicts.push(name.as_str().into());
+            }
+        }
+
+        if conflicts.is_empty() {
+            Ok(nested)
+        } else {
+            Err(NbtStructureError::conflicting_keys(conflicts))
+        }
     }
 
-    /// Returns true if the length of this compound is zero, false otherwise.
-    #[inline]
-    pub fn is_empty(&self) -> bool {
-        self.0.is_empty()
+    /// Converts the nested compounds in this compound into flat names by joining the names along
+    /// the path to each tag with the given separator, such that a tag at the path `a.b.c` is named
+    /// `"a.b.c"`. This is the reverse of [`nest`]. Empty compounds are kept as they are, so that
+    /// they are restored by [`nest`].
+    ///
+    /// If two tags end up with the same name, such as with `"a.b": 1` and `"a": { "b": 2 }`, an
+    /// error listing the conflicting names is returned. Names which contain the separator are
+    /// joined as they are, so they will be split by [`nest`].
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// let nested = compound! {
+    ///     "display": { "Name": "Sword", "Lore": ["Sharp"] },
+    ///     "Damage": 3i32
+    /// };
+    /// let flat = nested.flatten('.')?;
+    /// assert_eq!(flat, compound! {
+    ///     "display.Name": "Sword",
+    ///     "display.Lore": ["Sharp"],
+    ///     "Damage": 3i32
+    /// });
+    /// assert_eq!(flat.nest('.')?, nested);
+    /// # Ok::<(), NbtStructureError>(())
+    /// ```
+    ///
+    /// [`nest`]: crate::NbtCompound::nest
+    pub fn flatten(&self, separator: char) -> Result<NbtCompound, NbtStructureError> {
+        let mut flat = NbtCompound::new();
+        let mut conflicts = Vec::new();
+        flatten_compound(
+            self,
+            separator,
+            &mut String::new(),
+            &mut flat,
+            &mut conflicts,
+        );
+
+        if conflicts.is_empty() {
+            Ok(flat)
+        } else {
+            Err(NbtStructureError::conflicting_keys(conflicts))
+        }
     }
 
-    /// Returns the value of the tag with the given name, or an error if no tag exists with the given name
-    /// or specified type. This method should be used to obtain primitives as well as shared references to
-    /// lists and compounds.
+    /// Fixes or removes every entry in this compound, recursively, which violates the constraints
+    /// of the NBT format or the given rules, returning a report of the fixes made. See
+    /// [`SanitizeRules`] for the available rules.
     ///
     /// ```
     /// # use quartz_nbt::*;
-    /// let mut compound = NbtCompound::new();
-    /// compound.insert("test", 1.0f64);
+    /// use quartz_nbt::{
+    ///     convert::ConversionPolicy,
+    ///     sanitize::{NanHandling, SanitizeAction, SanitizeRules},
+    /// };
     ///
-    /// assert!((compound.get::<_, f64>("test").unwrap() - 1.0f64).abs() < 1e-5);
-    /// assert!(compound.get::<_, i32>("test").is_err()); // Type mismatch
-    /// assert!(compound.get::<_, f64>("foo").is_err()); // Missing tag
+    /// let mut nbt = compound! {
+    ///     "Pos": [0.5f64, f64::NAN, 3.0f64],
+    ///     "Inventory": [{ "Slot": 0i8, "Count": 64i32 }, { "Slot": 1i8, "Count": 1i8 }]
+    /// };
+    /// let rules = SanitizeRules::new()
+    ///     .nan(NanHandling::ReplaceWithZero)
+    ///     .expect_kind("Inventory[].Count", NbtTagKind::Byte)
+    ///     .conversion(ConversionPolicy::Saturate);
+    ///
+    /// let report = nbt.sanitize(&rules);
+    /// assert_eq!(report.len(), 2);
+    /// assert_eq!(nbt, compound! {
+    ///     "Pos": [0.5f64, 0.0f64, 3.0f64],
+    ///     "Inventory": [{ "Slot": 0i8, "Count": 64i8 }, { "Slot": 1i8, "Count": 1i8 }]
+    /// });
+    ///
+    /// let fix = report.iter().find(|fix| fix.path() == "Inventory[0].Count").unwrap();
+    /// assert_eq!(fix.action(), &SanitizeAction::ConvertedKind {
+    ///     from: NbtTagKind::Int,
+    ///     to: NbtTagKind::Byte
+    /// });
     /// ```
-    #[inline]
-    pub fn get<'a, 'b, K, T>(&'a self, name: &'b K) -> Result<T, NbtReprError>
-    where
-        String: Borrow<K>,
-        K: Hash + Eq + ?Sized,
-        &'b K: Into<String>,
-        T: TryFrom<&'a NbtTag>,
-        T::Error: Into<anyhow::Error>,
-    {
-        T::try_from(
-            self.0
-                .get(name)
-                .ok_or_else(|| NbtStructureError::missing_tag(name))?,
-        )
-        .map_err(NbtReprError::from_any)
+    ///
+    /// [`SanitizeRules`]: crate::sanitize::SanitizeRules
+    pub fn sanitize(&mut self, rules: &SanitizeRules) -> SanitizeReport {
+        sanitize::sanitize_compound(self, rules)
     }
 
-    /// Returns the value of the tag with the given name, or an error if no tag exists with the given name
-    /// or specified type. This method should be used to obtain mutable references to lists and compounds.
+    /// Makes this compound a deep copy of the given compound, reusing the allocations of this
+    /// compound and of the tags it shares with the source where possible. Entries missing from
+    /// the source are removed, and entries missing from this compound are cloned. See
+    /// [`NbtTag::clone_into`] for details.
     ///
     /// ```
     /// # use quartz_nbt::*;
-    /// let mut compound = NbtCompound::new();
-    /// compound.insert("test", 1.0f64);
+    /// let mut buffers = [
+    ///     compound! { "tick": 0i64, "entities": [{ "id": "pig", "Health": 10.0f32 }] },
+    ///     NbtCompound::new(),
+    /// ];
     ///
-    /// *compound.get_mut::<_, &mut f64>("test").unwrap() *= 2.0;
+    /// for tick in 1 .. 4 {
+    ///     let [current, next] = &mut buffers;
+    ///     next.clone_from_reusing(current);
+    ///     next.insert("tick", tick as i64);
+    ///     buffers.swap(0, 1);
+    /// }
     ///
-    /// assert!((compound.get::<_, f64>("test").unwrap() - 2.0f64).abs() < 1e-5);
-    /// assert!(compound.get::<_, i32>("test").is_err()); // Type mismatch
-    /// assert!(compound.get::<_, f64>("foo").is_err()); // Missing tag
+    /// assert_eq!(buffers[0].get::<_, i64>("tick").unwrap(), 3);
+    /// assert_eq!(buffers[1].get::<_, i64>("tick").unwrap(), 2);
     /// ```
-    #[inline]
-    pub fn get_mut<'a, 'b, K, T>(&'a mut self, name: &'b K) -> Result<T, NbtReprError>
-    where
-        String: Borrow<K>,
-        K: Hash + Eq + ?Sized,
-        &'b K: Into<String>,
-        T: TryFrom<&'a mut NbtTag>,
-        T::Error: Into<anyhow::Error>,
-    {
-        T::try_from(
+    pub fn clone_from_reusing(&mut self, source: &NbtCompound) {
+        self.0.retain(|key, _| source.0.contains_key(key));
+        for (key, value) in &source.0 {
+            match self.0.get_mut(key) {
+                Some(target) => value.clone_into(target),
+                None => {
+                    self.0.insert(key.clone(), value.clone());
+                }
+            }
+        }
+
+        // Entries kept from this compound precede the new ones, so restore the source's order
+        #[cfg(feature = "preserve_order")]
+        if !self.0.keys().eq(source.0.keys()) {
             self.0
-                .get_mut(name)
-                .ok_or_else(|| NbtStructureError::missing_tag(name))?,
-        )
-        .map_err(NbtReprError::from_any)
+                .sort_by(|a, _, b, _| source.0.get_index_of(a).cmp(&source.0.get_index_of(b)));
+        }
     }
 
-    /// Returns whether or not this compound has a tag with the given name.
+    /// Returns whether this compound is contained in the given compound, using the same algorithm
+    /// as Minecraft's NBT predicates, such as the `nbt` conditions of advancements, and the
+    /// compound patterns of [`NbtPath`]s.
+    ///
+    /// Every key in this compound must be present in the other compound with a matching value.
+    /// Nested compounds are matched in the same way, so they may contain additional keys. A
+    /// non-empty list matches a list containing a matching element for each of its elements, in
+    /// any order, whereas an empty list only matches an empty list. All other values, including
+    /// arrays, must be equal, and numbers of different types never match.
     ///
     /// ```
     /// # use quartz_nbt::*;
-    /// let mut compound = NbtCompound::new();
-    /// compound.insert("test", 1.0f64);
+    /// let item = compound! {
+    ///     "id": "minecraft:diamond_sword",
+    ///     "tag": { "Damage": 3i32, "Enchantments": [{ "id": "sharpness", "lvl": 5i16 }, { "id": "unbreaking" }] }
+    /// };
     ///
-    /// assert!(compound.contains_key("test"));
-    /// assert!(!compound.contains_key("foo"));
+    /// assert!(compound! { "id": "minecraft:diamond_sword" }.is_subset_of(&item));
+    /// assert!(compound! { "tag": { "Enchantments": [{ "id": "unbreaking" }] } }.is_subset_of(&item));
+    /// assert!(!compound! { "tag": { "Enchantments": [] } }.is_subset_of(&item));
+    /// assert!(!compound! { "tag": { "Damage": 3i16 } }.is_subset_of(&item));
     /// ```
+    ///
+    /// [`NbtPath`]: crate::path::NbtPath
     #[inline]
-    pub fn contains_key<K>(&self, key: &K) -> bool
-    where
-        String: Borrow<K>,
-        K: Hash + Eq + ?Sized,
-    {
-        self.0.contains_key(key)
+    pub fn is_subset_of(&self, other: &NbtCompound) -> bool {
+        path::compare(self, other, true)
     }
 
-    /// Adds the given value to this compound with the given name after wrapping that value in an `NbtTag`.
+    /// Returns whether this compound is contained in the given compound like [`is_subset_of`], but
+    /// requires every list to be equal to the corresponding list in the other compound, as
+    /// Minecraft does when list matching is not partial. Compounds outside of lists may still
+    /// contain additional keys, but everything within a list must be equal.
     ///
     /// ```
     /// # use quartz_nbt::*;
-    /// let mut compound = NbtCompound::new();
-    /// compound.insert("test", 1.0f64);
+    /// let entity = compound! { "Tags": ["boss", "glowing"], "Health": 20.0f32 };
     ///
-    /// assert!((compound.get::<_, f64>("test").unwrap() - 1.0f64).abs() < 1e-5);
+    /// assert!(compound! { "Tags": ["glowing"] }.is_subset_of(&entity));
+    /// assert!(!compound! { "Tags": ["glowing"] }.is_subset_of_strict(&entity));
+    /// assert!(compound! { "Tags": ["boss", "glowing"] }.is_subset_of_strict(&entity));
     /// ```
+    ///
+    /// [`is_subset_of`]: crate::NbtCompound::is_subset_of
     #[inline]
-    pub fn insert<K: Into<String>, T: Into<NbtTag>>(&mut self, name: K, value: T) {
-        self.0.insert(name.into(), value.into());
+    pub fn is_subset_of_strict(&self, other: &NbtCompound) -> bool {
+        path::compare(self, other, false)
+    }
+
+    /// Merges the given compound into this one like Minecraft's `/data merge` command. Compounds
+    /// present in both are merged recursively, whereas any other tag in the source replaces the
+    /// tag with the same key in this compound, or is inserted if there is none.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// let mut entity = compound! { "Health": 10.0f32, "Attributes": { "Speed": 0.1f64 } };
+    /// entity.merge(&compound! { "Health": 20.0f32, "Attributes": { "Armor": 2.0f64 } });
+    ///
+    /// assert_eq!(entity, compound! {
+    ///     "Health": 20.0f32,
+    ///     "Attributes": { "Speed": 0.1f64, "Armor": 2.0f64 }
+    /// });
+    /// ```
+    pub fn merge(&mut self, source: &NbtCompound) {
+        self.merge_with(source, &mut |_: &str, _: &NbtTag| {});
+    }
+
+    /// Merges the given compound into this one as with [`merge`], calling the given hook with the
+    /// path of every tag written. See [`MergeHook`] for details, and [`merge_traced`] for a hook
+    /// which records where each tag came from.
+    ///
+    /// ```
+    /// # use quartz_nbt::*;
+    /// let mut config = compound! { "limits": { "players": 20i32, "view": 10i32 } };
+    ///
+    /// let mut written = Vec::new();
+    /// config.merge_with(
+    ///     &compound! { "limits": { "players": 50i32 }, "name": "Lobby" },
+    ///     &mut |path: &str, _: &NbtTag| written.push(path.to_owned()),
+    /// );
+    /// written.sort();
+    /// assert_eq!(written, ["limits.players", "name"]);
+    /// ```
+    ///
+    /// [`merge`]: crate::NbtCompound::merge
+    /// [`MergeHook`]: crate::merge::MergeHook
+    /// [`merge_traced`]: crate::merge::merge_traced
+    pub fn merge_with<H: MergeHook>(&mut self, source: &NbtCompound, hook: &mut H) {
+        merge::merge_compound(self, source, &mut String::new(), hook);
     }
 
     /// Parses a nbt compound from snbt
@@ -1263,7 +3723,12 @@ impl NbtCompound {
     }
 
     #[allow(clippy::write_with_newline)]
-    fn to_formatted_snbt(&self, indent: &mut String, f: &mut Formatter<'_>) -> fmt::Result {
+    fn to_formatted_snbt(
+        &self,
+        indent: &mut String,
+        options: FormatOptions,
+        f: &mut Formatter<'_>,
+    ) -> fmt::Result {
         if self.is_empty() {
             return write!(f, "{{}}");
         }
@@ -1275,9 +3740,18 @@ impl NbtCompound {
             write!(f, "{{")?;
         }
 
+        let mut sorted = Vec::new();
+        let entries: &mut dyn Iterator<Item = (&String, &NbtTag)> = if options.sort_keys {
+            sorted.extend(self.0.iter());
+            sorted.sort_unstable_by_key(|&(key, _)| key);
+            &mut sorted.into_iter()
+        } else {
+            &mut self.0.iter()
+        };
+
         let last_index = self.len() - 1;
-        for (index, (key, value)) in self.0.iter().enumerate() {
-            let key = NbtTag::string_to_snbt(key);
+        for (index, (key, value)) in entries.enumerate() {
+            let key = options.paint(Element::Key, NbtTag::format_key(key));
 
             if f.alternate() {
                 write!(f, "{}{}: ", indent, key)?;
@@ -1285,7 +3759,7 @@ impl NbtCompound {
                 write!(f, "{}:", key)?;
             }
 
-            value.to_formatted_snbt(indent, f)?;
+            value.to_formatted_snbt(indent, options, f)?;
 
             if index != last_index {
                 if f.alternate() {
@@ -1374,14 +3848,14 @@ impl FromStr for NbtCompound {
 impl Display for NbtCompound {
     #[inline]
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        self.to_formatted_snbt(&mut String::new(), f)
+        self.to_formatted_snbt(&mut String::new(), FormatOptions::default(), f)
     }
 }
 
 impl Debug for NbtCompound {
     #[inline]
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        self.to_formatted_snbt(&mut String::new(), f)
+        self.to_formatted_snbt(&mut String::new(), FormatOptions::default(), f)
     }
 }
 
@@ -1392,17 +3866,21 @@ impl Extend<(String, NbtTag)> for NbtCompound {
     }
 }
 
+#[cfg(feature = "serde")]
+pub(crate) use serde_impl::{TagSeed, TagWithBools};
+
 #[cfg(feature = "serde")]
 mod serde_impl {
     use super::*;
-    use crate::serde::{Array, TypeHint};
+    use crate::serde::{Array, BoolConversion, TypeHint, EMPTY_LIST_NICHES};
     use serde::{
-        de::{self, MapAccess, Visitor},
+        de::{self, DeserializeSeed, MapAccess, Visitor},
         Deserialize,
         Deserializer,
         Serialize,
         Serializer,
     };
+    use std::{cell::Cell, marker::PhantomData};
 
     impl Serialize for NbtTag {
         fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -1424,17 +3902,78 @@ mod serde_impl {
         }
     }
 
+    // Serializes a tag with the given handling of byte tags holding 0 or 1
+    pub(crate) struct TagWithBools<'a>(pub(crate) &'a NbtTag, pub(crate) BoolConversion);
+
+    impl Serialize for TagWithBools<'_> {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer {
+            let bools = self.1;
+            match self.0 {
+                &NbtTag::Byte(value @ (0 | 1)) if bools == BoolConversion::Bidirectional =>
+                    serializer.serialize_bool(value == 1),
+                NbtTag::List(list)
+                    if bools == BoolConversion::Bidirectional && !list.is_empty() =>
+                    serializer.collect_seq(list.iter().map(|tag| TagWithBools(tag, bools))),
+                NbtTag::Compound(compound) if bools == BoolConversion::Bidirectional => serializer
+                    .collect_map(
+                        compound
+                            .0
+                            .iter()
+                            .map(|(name, tag)| (name, TagWithBools(tag, bools))),
+                    ),
+                tag => tag.serialize(serializer),
+            }
+        }
+    }
+
     impl<'de> Deserialize<'de> for NbtTag {
         #[inline]
         fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
         where D: Deserializer<'de> {
-            deserializer.deserialize_any(NbtTagVisitor)
+            TagSeed(BoolConversion::Implicit).deserialize(deserializer)
+        }
+    }
+
+    // Deserializes a tag with the given handling of booleans
+    #[derive(Clone, Copy)]
+    pub(crate) struct TagSeed(pub(crate) BoolConversion);
+
+    impl<'de> DeserializeSeed<'de> for TagSeed {
+        type Value = NbtTag;
+
+        #[inline]
+        fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+        where D: Deserializer<'de> {
+            deserializer.deserialize_any(NbtTagVisitor {
+                bools: self.0,
+                saw_bool: None,
+            })
+        }
+    }
+
+    // Deserializes an element of a sequence, recording whether the element was a boolean
+    struct ElementSeed<'a>(BoolConversion, &'a Cell<bool>);
+
+    impl<'de> DeserializeSeed<'de> for ElementSeed<'_> {
+        type Value = NbtTag;
+
+        #[inline]
+        fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+        where D: Deserializer<'de> {
+            deserializer.deserialize_any(NbtTagVisitor {
+                bools: self.0,
+                saw_bool: Some(self.1),
+            })
         }
     }
 
-    struct NbtTagVisitor;
+    struct NbtTagVisitor<'a> {
+        bools: BoolConversion,
+        saw_bool: Option<&'a Cell<bool>>,
+    }
 
-    impl<'de> Visitor<'de> for NbtTagVisitor {
+    impl<'de> Visitor<'de> for NbtTagVisitor<'_> {
         type Value = NbtTag;
 
         fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
@@ -1444,6 +3983,13 @@ mod serde_impl {
         #[inline]
         fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E>
         where E: de::Error {
+            if self.bools == BoolConversion::Error {
+                return Err(E::invalid_type(de::Unexpected::Bool(v), &self));
+            }
+
+            if let Some(saw_bool) = self.saw_bool {
+                saw_bool.set(true);
+            }
             Ok(NbtTag::Byte(if v { 1 } else { 0 }))
         }
 
@@ -1477,6 +4023,28 @@ mod serde_impl {
             Ok(NbtTag::Long(v))
         }
 
+        // Unsigned integers are widened to the smallest tag type which holds all of their values
+        #[inline]
+        fn visit_u16<E>(self, v: u16) -> Result<Self::Value, E>
+        where E: de::Error {
+            Ok(NbtTag::Int(v.into()))
+        }
+
+        #[inline]
+        fn visit_u32<E>(self, v: u32) -> Result<Self::Value, E>
+        where E: de::Error {
+            Ok(NbtTag::Long(v.into()))
+        }
+
+        #[inline]
+        fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+        where E: de::Error {
+            match i64::try_from(v) {
+                Ok(v) => Ok(NbtTag::Long(v)),
+                Err(_) => Err(E::invalid_value(de::Unexpected::Unsigned(v), &self)),
+            }
+        }
+
         #[inline]
         fn visit_f32<E>(self, v: f32) -> Result<Self::Value, E>
         where E: de::Error {
@@ -1498,7 +4066,7 @@ mod serde_impl {
         #[inline]
         fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E>
         where E: de::Error {
-            Ok(NbtTag::ByteArray(raw::cast_byte_buf_to_signed(v)))
+            Ok(NbtTag::ByteArray(cast::byte_buf_to_signed(v)))
         }
 
         #[inline]
@@ -1516,10 +4084,12 @@ mod serde_impl {
         fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
         where A: MapAccess<'de> {
             let mut dest = match map.size_hint() {
-                Some(hint) => Map::with_capacity(hint),
+                Some(hint) => Map::with_capacity(hint.min(crate::raw::PREALLOC_LIMIT)),
                 None => Map::new(),
             };
-            while let Some((key, tag)) = map.next_entry::<String, NbtTag>()? {
+            while let Some((key, tag)) =
+                map.next_entry_seed(PhantomData::<String>, TagSeed(self.bools))?
+            {
                 dest.insert(key, tag);
             }
             Ok(NbtTag::Compound(NbtCompound(dest)))
@@ -1541,7 +4111,7 @@ mod serde_impl {
                         ArbitraryList::Byte(list) => NbtTag::ByteArray(list),
                         ArbitraryList::Int(list) => NbtTag::IntArray(list),
                         ArbitraryList::Long(list) => NbtTag::LongArray(list),
-                        ArbitraryList::Tag(list) => NbtTag::List(NbtList(list)),
+                        ArbitraryList::Tag(list) => NbtTag::List(NbtList(list, None)),
                         ArbitraryList::Indeterminate => NbtTag::List(NbtList::new()),
                     }
                 }
@@ -1552,8 +4122,9 @@ mod serde_impl {
             fn init_vec<T>(element: T, size: Option<usize>) -> Vec<T> {
                 match size {
                     Some(size) => {
-                        // Add one because the size hint returns the remaining amount
-                        let mut vec = Vec::with_capacity(1 + size);
+                        // Add one because the size hint returns the remaining amount, which may
+                        // come from untrusted input
+                        let mut vec = Vec::with_capacity(1 + size.min(crate::raw::PREALLOC_LIMIT));
                         vec.push(element);
                         vec
                     }
@@ -1561,7 +4132,12 @@ mod serde_impl {
                 }
             }
 
-            while let Some(tag) = seq.next_element::<NbtTag>()? {
+            // Booleans are kept in a list rather than a byte array when they need to be
+            // serialized as booleans again
+            let saw_bool = Cell::new(false);
+            let keep_bools = self.bools == BoolConversion::Bidirectional;
+
+            while let Some(tag) = seq.next_element_seed(ElementSeed(self.bools, &saw_bool))? {
                 match (tag, &mut list) {
                     (NbtTag::Byte(value), ArbitraryList::Byte(list)) => list.push(value),
                     (NbtTag::Int(value), ArbitraryList::Int(list)) => list.push(value),
@@ -1570,6 +4146,8 @@ mod serde_impl {
                     (tag, list @ ArbitraryList::Indeterminate) => {
                         let size = seq.size_hint();
                         match tag {
+                            NbtTag::Byte(_) if keep_bools && saw_bool.get() =>
+                                *list = ArbitraryList::Tag(init_vec(tag, size)),
                             NbtTag::Byte(value) =>
                                 *list = ArbitraryList::Byte(init_vec(value, size)),
                             NbtTag::Int(value) => *list = ArbitraryList::Int(init_vec(value, size)),
@@ -1589,12 +4167,15 @@ mod serde_impl {
                 Ok(Some(TypeHint { hint: Some(tag_id) })) => match (list, tag_id) {
                     (ArbitraryList::Byte(list), 0x9) => Ok(NbtTag::List(NbtList(
                         list.into_iter().map(Into::into).collect(),
+                        None,
                     ))),
                     (ArbitraryList::Int(list), 0x9) => Ok(NbtTag::List(NbtList(
                         list.into_iter().map(Into::into).collect(),
+                        None,
                     ))),
                     (ArbitraryList::Long(list), 0x9) => Ok(NbtTag::List(NbtList(
                         list.into_iter().map(Into::into).collect(),
+                        None,
                     ))),
                     (ArbitraryList::Indeterminate, 0x7) => Ok(NbtTag::ByteArray(Vec::new())),
                     (ArbitraryList::Indeterminate, 0xB) => Ok(NbtTag::IntArray(Vec::new())),
@@ -1610,7 +4191,14 @@ mod serde_impl {
         #[inline]
         fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
         where S: Serializer {
-            self.0.serialize(serializer)
+            // Let this crate's serializer write the element kind hint of empty lists
+            match self.1 {
+                Some(kind) if self.0.is_empty() => serializer.serialize_newtype_struct(
+                    EMPTY_LIST_NICHES[usize::from(kind.id() - 1)],
+                    &self.0,
+                ),
+                _ => self.0.serialize(serializer),
+            }
         }
     }
 
@@ -1618,7 +4206,7 @@ mod serde_impl {
         #[inline]
         fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
         where D: Deserializer<'de> {
-            Ok(NbtList(Deserialize::deserialize(deserializer)?))
+            Ok(NbtList(Deserialize::deserialize(deserializer)?, None))
         }
     }
 
@@ -0,0 +1,375 @@
+#[cfg(feature = "rand")]
+pub use crate::validate::{Schema, TagSchema};
+use crate::{
+    path::{push_index, push_key},
+    NbtCompound,
+    NbtList,
+    NbtTag,
+    NbtTagKind,
+};
+#[cfg(feature = "rand")]
+use rand::{distributions::Alphanumeric, seq::SliceRandom, Rng};
+use std::{
+    fmt::{self, Display, Formatter},
+    mem,
+};
+
+/// Asserts that two [`NbtCompound`]s are equal. Either owned compounds or references to compounds
+/// may be passed to this macro.
+///
+/// On failure, this macro panics with a message describing the first difference found between the
+/// two compounds, rather than the full contents of both compounds. See [`find_difference`] for
+/// details on how differences are located and reported.
+///
+/// ```
+/// # use quartz_nbt::{assert_compound_eq, compound};
+/// let test = compound! { "pos": [1, 2, 3], "name": "foo" };
+/// assert_compound_eq!(&test, compound! { "name": "foo", "pos": [1, 2, 3] });
+/// ```
+///
+/// ```should_panic
+/// # use quartz_nbt::{assert_compound_eq, compound};
+/// // Panics with "Compounds are not equal at pos[2]: expected 4 but found 3"
+/// assert_compound_eq!(compound! { "pos": [1, 2, 3] }, compound! { "pos": [1, 2, 4] });
+/// ```
+///
+/// [`NbtCompound`]: crate::NbtCompound
+/// [`find_difference`]: crate::testing::find_difference
+#[macro_export]
+macro_rules! assert_compound_eq {
+    ($test:expr, $validate:expr $(,)?) => {
+        match (&$test, &$validate) {
+            (test, validate) => {
+                if let ::std::option::Option::Some(difference) = $crate::testing::find_difference(
+                    ::std::borrow::Borrow::<$crate::NbtCompound>::borrow(test),
+                    ::std::borrow::Borrow::<$crate::NbtCompound>::borrow(validate),
+                ) {
+                    ::std::panic!("Compounds are not equal at {}", difference);
+                }
+            }
+        }
+    };
+}
+
+/// Finds the first difference between the `test` compound and the `validate` compound, returning
+/// `None` if the two compounds are equal.
+///
+/// Keys which appear in `validate` are compared first, in iteration order, followed by a check for
+/// keys which only appear in `test`. Lists and arrays are compared element-wise after their lengths
+/// are checked, so the reported path points at the first differing index.
+///
+/// ```
+/// # use quartz_nbt::{compound, NbtTagKind};
+/// use quartz_nbt::testing::{find_difference, DifferenceKind};
+///
+/// let test = compound! { "Inventory": [{ "Slot": 0u8, "Count": 1i16 }] };
+/// let validate = compound! { "Inventory": [{ "Slot": 0u8, "Count": 1i8 }] };
+///
+/// let difference = find_difference(&test, &validate).unwrap();
+/// assert_eq!(difference.path(), "Inventory[0].Count");
+/// assert_eq!(difference.kind(), &DifferenceKind::KindMismatch {
+///     expected: NbtTagKind::Byte,
+///     found: NbtTagKind::Short
+/// });
+///
+/// assert!(find_difference(&test, &test).is_none());
+/// ```
+pub fn find_difference(test: &NbtCompound, validate: &NbtCompound) -> Option<Difference> {
+    let mut path = String::new();
+    compare_compounds(test, validate, &mut path).map(|kind| Difference { path, kind })
+}
+
+/// A difference between two compounds, as reported by [`find_difference`].
+///
+/// [`find_difference`]: crate::testing::find_difference
+#[derive(Debug, Clone, PartialEq)]
+pub struct Difference {
+    path: String,
+    kind: DifferenceKind,
+}
+
+impl Difference {
+    /// Returns the path to the differing tag, such as `Inventory[3].id`. Keys containing characters
+    /// other than ASCII letters, digits, `_`, `-`, and `+` are wrapped in double quotes. The path is
+    /// empty if the difference concerns the root compound itself.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the kind of difference found at the path.
+    pub fn kind(&self) -> &DifferenceKind {
+        &self.kind
+    }
+}
+
+impl Display for Difference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "root: {}", self.kind)
+        } else {
+            write!(f, "{}: {}", self.path, self.kind)
+        }
+    }
+}
+
+/// The ways in which two tags may differ.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DifferenceKind {
+    /// A key present in the validation compound is missing from the test compound.
+    MissingKey,
+    /// A key present in the test compound is absent from the validation compound.
+    UnexpectedKey,
+    /// The two tags are of different kinds.
+    KindMismatch {
+        /// The kind of the validation tag.
+        expected: NbtTagKind,
+        /// The kind of the test tag.
+        found: NbtTagKind,
+    },
+    /// Two lists or arrays have different lengths.
+    LengthMismatch {
+        /// The kind of the sequence.
+        kind: NbtTagKind,
+        /// The length of the validation sequence.
+        expected: usize,
+        /// The length of the test sequence.
+        found: usize,
+    },
+    /// Two tags of the same kind have different values.
+    ValueMismatch {
+        /// The validation tag.
+        expected: NbtTag,
+        /// The test tag.
+        found: NbtTag,
+    },
+}
+
+impl Display for DifferenceKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            DifferenceKind::MissingKey => write!(f, "key is missing"),
+            DifferenceKind::UnexpectedKey => write!(f, "unexpected key"),
+            DifferenceKind::KindMismatch { expected, found } =>
+                write!(f, "expected {} but found {}", expected, found),
+            DifferenceKind::LengthMismatch {
+                kind,
+                expected,
+                found,
+            } => write!(
+                f,
+                "expected {} of length {} but found length {}",
+                kind, expected, found
+            ),
+            DifferenceKind::ValueMismatch { expected, found } =>
+                write!(f, "expected {} but found {}", expected, found),
+        }
+    }
+}
+
+// Each of the following functions leaves `path` pointing at the first difference if one is found,
+// and otherwise restores it to its original value
+
+fn compare_compounds(
+    test: &NbtCompound,
+    validate: &NbtCompound,
+    path: &mut String,
+) -> Option<DifferenceKind> {
+    for (key, validate_value) in validate.inner() {
+        let len = path.len();
+        push_key(path, key);
+
+        let difference = match test.inner().get(key) {
+            Some(test_value) => compare_tags(test_value, validate_value, path),
+            None => Some(DifferenceKind::MissingKey),
+        };
+        if difference.is_some() {
+            return difference;
+        }
+
+        path.truncate(len);
+    }
+
+    for key in test.inner().keys() {
+        if !validate.contains_key(key) {
+            push_key(path, key);
+            return Some(DifferenceKind::UnexpectedKey);
+        }
+    }
+
+    None
+}
+
+fn compare_lists(test: &NbtList, validate: &NbtList, path: &mut String) -> Option<DifferenceKind> {
+    if test.len() != validate.len() {
+        return Some(DifferenceKind::LengthMismatch {
+            kind: NbtTagKind::List,
+            expected: validate.len(),
+            found: test.len(),
+        });
+    }
+
+    for (index, (test_value, validate_value)) in test.iter().zip(validate.iter()).enumerate() {
+        let len = path.len();
+        push_index(path, index);
+
+        let difference = compare_tags(test_value, validate_value, path);
+        if difference.is_some() {
+            return difference;
+        }
+
+        path.truncate(len);
+    }
+
+    None
+}
+
+fn compare_arrays<T>(
+    test: &[T],
+    validate: &[T],
+    kind: NbtTagKind,
+    path: &mut String,
+) -> Option<DifferenceKind>
+where
+    T: Copy + PartialEq + Into<NbtTag>,
+{
+    if test.len() != validate.len() {
+        return Some(DifferenceKind::LengthMismatch {
+            kind,
+            expected: validate.len(),
+            found: test.len(),
+        });
+    }
+
+    let index = test.iter().zip(validate).position(|(a, b)| a != b)?;
+    push_index(path, index);
+    Some(DifferenceKind::ValueMismatch {
+        expected: validate[index].into(),
+        found: test[index].into(),
+    })
+}
+
+fn compare_tags(test: &NbtTag, validate: &NbtTag, path: &mut String) -> Option<DifferenceKind> {
+    if mem::discriminant(test) != mem::discriminant(validate) {
+        return Some(DifferenceKind::KindMismatch {
+            expected: validate.kind(),
+            found: test.kind(),
+        });
+    }
+
+    match (test, validate) {
+        (NbtTag::Compound(test), NbtTag::Compound(validate)) =>
+            compare_compounds(test, validate, path),
+        (NbtTag::List(test), NbtTag::List(validate)) => compare_lists(test, validate, path),
+        (NbtTag::ByteArray(test), NbtTag::ByteArray(validate)) =>
+            compare_arrays(test, validate, NbtTagKind::ByteArray, path),
+        (NbtTag::IntArray(test), NbtTag::IntArray(validate)) =>
+            compare_arrays(test, validate, NbtTagKind::IntArray, path),
+        (NbtTag::LongArray(test), NbtTag::LongArray(validate)) =>
+            compare_arrays(test, validate, NbtTagKind::LongArray, path),
+        _ if test != validate => Some(DifferenceKind::ValueMismatch {
+            expected: validate.clone(),
+            found: test.clone(),
+        }),
+        _ => None,
+    }
+}
+
+/// The maximum length of the strings and arrays generated for [`TagSchema::Kind`].
+///
+/// This constant is only available when the `rand` feature is enabled.
+///
+/// [`TagSchema::Kind`]: crate::validate::TagSchema::Kind
+#[cfg(feature = "rand")]
+pub const MAX_GENERATED_LEN: usize = 16;
+
+// Deeper compounds and lists are generated empty, so that recursive schemas remain finite
+#[cfg(feature = "rand")]
+const MAX_GENERATED_DEPTH: usize = 64;
+
+/// Generates a random compound following the given schema, which is useful for load testing
+/// storage and for fuzzing code which consumes NBT with structurally valid data rather than random
+/// bytes. The same schema and seeded generator always produce the same compound.
+///
+/// Lists and compounds nested more than 64 levels deep are generated empty, so that schemas
+/// which contain themselves produce finite compounds.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::{testing, validate::Schema};
+/// use rand::{rngs::StdRng, SeedableRng};
+///
+/// let schema = Schema::infer(&compound! { "Pos": [0.0f64, 64.0f64, 0.0f64], "Name": "Steve" });
+/// let mut rng = StdRng::seed_from_u64(7);
+/// for _ in 0 .. 100 {
+///     let generated = testing::generate(&schema, &mut rng);
+///     assert!(schema.is_valid(&generated));
+///     assert!(generated.get::<_, &str>("Name").unwrap().len() <= testing::MAX_GENERATED_LEN);
+/// }
+/// ```
+///
+/// This function is only available when the `rand` feature is enabled.
+#[cfg(feature = "rand")]
+pub fn generate<R: Rng + ?Sized>(schema: &Schema, rng: &mut R) -> NbtCompound {
+    generate_compound(schema, rng, 1)
+}
+
+#[cfg(feature = "rand")]
+fn generate_compound<R: Rng + ?Sized>(schema: &Schema, rng: &mut R, depth: usize) -> NbtCompound {
+    let mut compound = NbtCompound::new();
+    for field in &schema.fields {
+        if field.required || rng.gen_bool(0.5) {
+            compound.insert(field.key.as_str(), generate_tag(&field.schema, rng, depth));
+        }
+    }
+    compound
+}
+
+#[cfg(feature = "rand")]
+fn generate_tag<R: Rng + ?Sized>(schema: &TagSchema, rng: &mut R, depth: usize) -> NbtTag {
+    let depth = depth + 1;
+    match schema {
+        TagSchema::Kind(kind) => generate_value(*kind, rng),
+        TagSchema::OneOf(values) => match values.choose(rng) {
+            Some(value) => value.clone(),
+            None => NbtTag::Compound(NbtCompound::new()),
+        },
+        TagSchema::List { element, max_len } => {
+            let len = if depth < MAX_GENERATED_DEPTH {
+                rng.gen_range(0 ..= *max_len)
+            } else {
+                0
+            };
+            let mut list = NbtList::with_capacity(len);
+            for _ in 0 .. len {
+                list.push(generate_tag(element, rng, depth));
+            }
+            NbtTag::List(list)
+        }
+        TagSchema::Compound(_) if depth >= MAX_GENERATED_DEPTH =>
+            NbtTag::Compound(NbtCompound::new()),
+        TagSchema::Compound(schema) => NbtTag::Compound(generate_compound(schema, rng, depth)),
+    }
+}
+
+#[cfg(feature = "rand")]
+fn generate_value<R: Rng + ?Sized>(kind: NbtTagKind, rng: &mut R) -> NbtTag {
+    let len = rng.gen_range(0 ..= MAX_GENERATED_LEN);
+    match kind {
+        NbtTagKind::Byte => NbtTag::Byte(rng.gen()),
+        NbtTagKind::Short => NbtTag::Short(rng.gen()),
+        NbtTagKind::Int => NbtTag::Int(rng.gen()),
+        NbtTagKind::Long => NbtTag::Long(rng.gen()),
+        NbtTagKind::Float => NbtTag::Float(rng.gen_range(-1.0e6 .. 1.0e6)),
+        NbtTagKind::Double => NbtTag::Double(rng.gen_range(-1.0e6 .. 1.0e6)),
+        NbtTagKind::ByteArray => NbtTag::ByteArray((0 .. len).map(|_| rng.gen()).collect()),
+        NbtTagKind::String => NbtTag::String(
+            (0 .. len)
+                .map(|_| char::from(rng.sample(Alphanumeric)))
+                .collect(),
+        ),
+        NbtTagKind::List => NbtTag::List(NbtList::new()),
+        NbtTagKind::Compound => NbtTag::Compound(NbtCompound::new()),
+        NbtTagKind::IntArray => NbtTag::IntArray((0 .. len).map(|_| rng.gen()).collect()),
+        NbtTagKind::LongArray => NbtTag::LongArray((0 .. len).map(|_| rng.gen()).collect()),
+    }
+}
@@ -0,0 +1,210 @@
+use crate::{NbtCompound, NbtList, NbtReprError, NbtStructureError, NbtTag, NbtTagKind};
+use serde_json::{Map, Number, Value};
+
+/// Describes how a text component is stored in NBT data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ComponentEncoding {
+    /// The component is serialized as JSON and stored in a string tag, as in versions of Minecraft
+    /// before 1.21.5.
+    JsonString,
+    /// The component is stored as NBT, as in Minecraft 1.21.5 and later. Plain text components are
+    /// string tags, and all other components are compounds or lists.
+    Nbt,
+}
+
+/// Reads the text component stored in the given tag with the given encoding.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::text::{self, ComponentEncoding};
+/// use serde_json::json;
+///
+/// let legacy = NbtTag::from(r#"{"text":"Excalibur","bold":true}"#);
+/// assert_eq!(
+///     text::read_component(&legacy, ComponentEncoding::JsonString).unwrap(),
+///     json!({ "text": "Excalibur", "bold": true })
+/// );
+///
+/// let modern = NbtTag::Compound(compound! { "text": "Excalibur", "bold": 1i8 });
+/// assert_eq!(
+///     text::read_component(&modern, ComponentEncoding::Nbt).unwrap(),
+///     json!({ "text": "Excalibur", "bold": 1 })
+/// );
+///
+/// assert!(text::read_component(&NbtTag::Int(1), ComponentEncoding::JsonString).is_err());
+/// ```
+pub fn read_component(tag: &NbtTag, encoding: ComponentEncoding) -> Result<Value, NbtReprError> {
+    match encoding {
+        ComponentEncoding::JsonString => match tag {
+            NbtTag::String(json) => serde_json::from_str(json).map_err(NbtReprError::from_any),
+            _ => Err(NbtStructureError::type_mismatch(
+                NbtTagKind::String.name(),
+                tag.kind().name(),
+            )
+            .into()),
+        },
+        ComponentEncoding::Nbt => Ok(nbt_to_json(tag)),
+    }
+}
+
+/// Converts the given text component to a tag with the given encoding.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::text::{self, ComponentEncoding};
+/// use serde_json::json;
+///
+/// let name = json!({ "text": "Excalibur", "color": "gold" });
+///
+/// let legacy = text::write_component(&name, ComponentEncoding::JsonString);
+/// assert!(matches!(legacy, NbtTag::String(_)));
+/// assert_eq!(text::read_component(&legacy, ComponentEncoding::JsonString).unwrap(), name);
+///
+/// let modern = text::write_component(&name, ComponentEncoding::Nbt);
+/// assert_eq!(modern, NbtTag::Compound(compound! { "text": "Excalibur", "color": "gold" }));
+/// ```
+pub fn write_component(component: &Value, encoding: ComponentEncoding) -> NbtTag {
+    match encoding {
+        ComponentEncoding::JsonString => NbtTag::String(component.to_string()),
+        ComponentEncoding::Nbt => json_to_nbt(component),
+    }
+}
+
+/// Converts the given JSON value to a tag in the same way as Minecraft does when storing text
+/// components as NBT.
+///
+/// Objects become compounds, strings become string tags, booleans become byte tags, integers become
+/// int tags, or long tags if they do not fit in an int, and all other numbers become double tags.
+/// Null values are omitted from objects and arrays, and a null value on its own becomes an empty
+/// string tag, which is the empty text component.
+///
+/// Arrays become lists. Since the elements of a list must all be of the same kind, the elements of
+/// an array containing several kinds of values are each wrapped in a compound under an empty key,
+/// except for objects which do not already look like such a wrapper. [`nbt_to_json`] removes these
+/// wrappers again.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::text;
+/// use serde_json::json;
+///
+/// let extra = json!(["Hello, ", { "text": "world", "italic": true }]);
+/// assert_eq!(
+///     text::json_to_nbt(&extra),
+///     NbtTag::List(NbtList::from(vec![
+///         NbtTag::Compound(compound! { "": "Hello, " }),
+///         NbtTag::Compound(compound! { "text": "world", "italic": 1i8 }),
+///     ]))
+/// );
+/// assert_eq!(
+///     text::nbt_to_json(&text::json_to_nbt(&extra)),
+///     json!(["Hello, ", { "text": "world", "italic": 1 }])
+/// );
+/// ```
+///
+/// [`nbt_to_json`]: crate::text::nbt_to_json
+pub fn json_to_nbt(value: &Value) -> NbtTag {
+    match value {
+        Value::Null => NbtTag::String(String::new()),
+        Value::Bool(value) => NbtTag::Byte(*value as i8),
+        Value::Number(number) => number_to_nbt(number),
+        Value::String(string) => NbtTag::String(string.clone()),
+        Value::Array(array) => {
+            let elements = array
+                .iter()
+                .filter(|element| !element.is_null())
+                .map(json_to_nbt)
+                .collect::<Vec<_>>();
+
+            let homogenous = elements
+                .first()
+                .is_none_or(|first| elements.iter().all(|tag| tag.kind() == first.kind()));
+            if homogenous {
+                return NbtTag::List(NbtList::from(elements));
+            }
+
+            NbtTag::List(
+                elements
+                    .into_iter()
+                    .map(|tag| match tag {
+                        NbtTag::Compound(compound) if !is_wrapper(&compound) =>
+                            NbtTag::Compound(compound),
+                        tag => {
+                            let mut wrapper = NbtCompound::new();
+                            wrapper.insert(String::new(), tag);
+                            NbtTag::Compound(wrapper)
+                        }
+                    })
+                    .collect(),
+            )
+        }
+        Value::Object(object) => {
+            let mut compound = NbtCompound::new();
+            for (key, value) in object {
+                if !value.is_null() {
+                    compound.insert(key.clone(), json_to_nbt(value));
+                }
+            }
+            NbtTag::Compound(compound)
+        }
+    }
+}
+
+/// Converts the given tag to a JSON value, reversing the conversion performed by
+/// [`json_to_nbt`].
+///
+/// Numeric tags become numbers, with non-finite floats becoming null, and arrays become JSON arrays
+/// of numbers. Compounds in a list which consist of a single entry with an empty key are replaced by
+/// the value of that entry. Since NBT has no boolean type, byte tags always become numbers, which
+/// Minecraft accepts wherever a boolean is expected.
+///
+/// [`json_to_nbt`]: crate::text::json_to_nbt
+pub fn nbt_to_json(tag: &NbtTag) -> Value {
+    match tag {
+        NbtTag::Byte(value) => Value::from(*value),
+        NbtTag::Short(value) => Value::from(*value),
+        NbtTag::Int(value) => Value::from(*value),
+        NbtTag::Long(value) => Value::from(*value),
+        NbtTag::Float(value) => float_to_json(f64::from(*value)),
+        NbtTag::Double(value) => float_to_json(*value),
+        NbtTag::ByteArray(array) => Value::from(array.clone()),
+        NbtTag::String(string) => Value::String(string.clone()),
+        NbtTag::List(list) => Value::Array(
+            list.iter()
+                .map(|tag| match tag {
+                    NbtTag::Compound(compound) if is_wrapper(compound) =>
+                        nbt_to_json(&compound.inner()[""]),
+                    tag => nbt_to_json(tag),
+                })
+                .collect(),
+        ),
+        NbtTag::Compound(compound) => Value::Object(
+            compound
+                .inner()
+                .iter()
+                .map(|(key, tag)| (key.clone(), nbt_to_json(tag)))
+                .collect::<Map<_, _>>(),
+        ),
+        NbtTag::IntArray(array) => Value::from(array.clone()),
+        NbtTag::LongArray(array) => Value::from(array.clone()),
+    }
+}
+
+fn number_to_nbt(number: &Number) -> NbtTag {
+    match number.as_i64() {
+        Some(value) => match i32::try_from(value) {
+            Ok(value) => NbtTag::Int(value),
+            Err(_) => NbtTag::Long(value),
+        },
+        None => NbtTag::Double(number.as_f64().unwrap_or(f64::NAN)),
+    }
+}
+
+fn float_to_json(value: f64) -> Value {
+    Number::from_f64(value).map_or(Value::Null, Value::Number)
+}
+
+// Whether the given compound has the form used to wrap elements of heterogenous lists
+fn is_wrapper(compound: &NbtCompound) -> bool {
+    compound.len() == 1 && compound.contains_key("")
+}
@@ -0,0 +1,84 @@
+use crate::{NbtCompound, NbtTag};
+use std::io::{self, Write};
+use tracing::{field::Empty, span::EnteredSpan, Span};
+
+// Enters a span covering the reading of a binary document, whose size is filled in by
+// `record_document` once it has been read
+pub(crate) fn read_span() -> EnteredSpan {
+    tracing::debug_span!("read_nbt", bytes = Empty, tags = Empty, depth = Empty).entered()
+}
+
+// Enters a span covering the writing of a binary document
+pub(crate) fn write_span() -> EnteredSpan {
+    tracing::debug_span!("write_nbt", bytes = Empty, tags = Empty, depth = Empty).entered()
+}
+
+// Enters a span covering the parsing of an SNBT string of the given length in bytes
+pub(crate) fn parse_span(len: usize) -> EnteredSpan {
+    tracing::debug_span!("parse_snbt", len, tags = Empty, depth = Empty).entered()
+}
+
+// Records the number of uncompressed bytes in a document along with its shape
+pub(crate) fn record_document(span: &Span, bytes: usize, root: &NbtCompound) {
+    if span.is_disabled() {
+        return;
+    }
+
+    span.record("bytes", bytes);
+    record_shape(span, root);
+}
+
+// Records the number of tags in the given compound and how deeply they are nested
+pub(crate) fn record_shape(span: &Span, root: &NbtCompound) {
+    if span.is_disabled() {
+        return;
+    }
+
+    let mut tags = 0usize;
+    let mut depth = 0usize;
+    let mut stack = root.0.values().map(|tag| (tag, 1)).collect::<Vec<_>>();
+    while let Some((tag, level)) = stack.pop() {
+        tags += 1;
+        depth = depth.max(level);
+        match tag {
+            NbtTag::List(list) => stack.extend(list.0.iter().map(|tag| (tag, level + 1))),
+            NbtTag::Compound(compound) =>
+                stack.extend(compound.0.values().map(|tag| (tag, level + 1))),
+            _ => {}
+        }
+    }
+
+    span.record("tags", tags);
+    span.record("depth", depth);
+}
+
+pub(crate) fn duplicate_key(key: &str) {
+    tracing::debug!(key, "Replaced a tag with a duplicate key");
+}
+
+pub(crate) fn latin1_fallback(path: &str) {
+    tracing::debug!(path, "Decoded an invalid string as Latin-1");
+}
+
+pub(crate) struct CountingWriter<W> {
+    inner: W,
+    pub(crate) position: usize,
+}
+
+impl<W> CountingWriter<W> {
+    pub(crate) fn new(inner: W) -> Self {
+        CountingWriter { inner, position: 0 }
+    }
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.position = self.position.saturating_add(written);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,556 @@
+use crate::{
+    path::{push_index, push_key},
+    NbtCompound,
+    NbtTag,
+    NbtTagKind,
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Display, Formatter},
+    slice,
+};
+
+/// A description of a family of compounds: the keys they contain, and the kind and shape of the
+/// tag under each key. Schemas are used to check compounds with [`is_valid`] and by a
+/// [`Validator`], and to produce random compounds with `testing::generate` when the `rand` feature
+/// is enabled.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::validate::{Schema, TagSchema};
+///
+/// let item = Schema::new()
+///     .required("id", TagSchema::one_of(["minecraft:stone", "minecraft:dirt"]))
+///     .required("Count", TagSchema::Kind(NbtTagKind::Byte))
+///     .optional("tag", TagSchema::Compound(Schema::new().required("Damage", TagSchema::Kind(NbtTagKind::Int))));
+/// let player = Schema::new()
+///     .required("Name", TagSchema::Kind(NbtTagKind::String))
+///     .required("Inventory", TagSchema::list(TagSchema::Compound(item), 36));
+///
+/// // Schemas can also be inferred from a sample document
+/// let sample = compound! { "Name": "Steve", "Inventory": [{ "id": "minecraft:stone", "Count": 1i8 }] };
+/// assert!(Schema::infer(&sample).is_valid(&sample));
+/// ```
+///
+/// [`is_valid`]: crate::validate::Schema::is_valid
+/// [`Validator`]: crate::validate::Validator
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Schema {
+    pub(crate) fields: Vec<Field>,
+    allow_other_keys: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Field {
+    pub(crate) key: String,
+    pub(crate) schema: TagSchema,
+    pub(crate) required: bool,
+}
+
+impl Schema {
+    /// Returns a schema without any keys, which only describes empty compounds.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a key which every matching compound contains, and which is present in every generated
+    /// compound, replacing any previous schema for the key.
+    pub fn required<K: Into<String>>(self, key: K, schema: TagSchema) -> Self {
+        self.field(key.into(), schema, true)
+    }
+
+    /// Adds a key which matching compounds may omit, and which is present in about half of the
+    /// generated compounds, replacing any previous schema for the key.
+    pub fn optional<K: Into<String>>(self, key: K, schema: TagSchema) -> Self {
+        self.field(key.into(), schema, false)
+    }
+
+    fn field(mut self, key: String, schema: TagSchema, required: bool) -> Self {
+        self.fields.retain(|field| field.key != key);
+        self.fields.push(Field {
+            key,
+            schema,
+            required,
+        });
+        self
+    }
+
+    /// Infers a schema from a sample compound, in which every key of the sample is required and
+    /// has the kind of its tag in the sample. The elements of a list follow the schema inferred
+    /// from its first element, and generated lists are at most as long as the sample.
+    pub fn infer(sample: &NbtCompound) -> Self {
+        sample
+            .inner()
+            .iter()
+            .fold(Schema::new(), |schema, (key, tag)| {
+                schema.required(key.as_str(), TagSchema::infer(tag))
+            })
+    }
+
+    /// Sets whether compounds may contain keys which are not part of this schema, in which case
+    /// the tags under those keys are not checked. Generated compounds never contain such keys.
+    /// This defaults to `false`.
+    pub fn allow_other_keys(mut self, allow_other_keys: bool) -> Self {
+        self.allow_other_keys = allow_other_keys;
+        self
+    }
+
+    /// Returns whether the given compound could have been generated from this schema. Compounds
+    /// may not contain keys which are not part of the schema unless [`allow_other_keys`] is set.
+    ///
+    /// [`allow_other_keys`]: crate::validate::Schema::allow_other_keys
+    pub fn is_valid(&self, compound: &NbtCompound) -> bool {
+        (self.allow_other_keys
+            || compound
+                .inner()
+                .keys()
+                .all(|key| self.fields.iter().any(|field| &field.key == key)))
+            && self
+                .fields
+                .iter()
+                .all(|field| match compound.inner().get(&field.key) {
+                    Some(tag) => field.schema.is_valid(tag),
+                    None => !field.required,
+                })
+    }
+}
+
+/// A description of the tags under one key of a [`Schema`], or of the elements of a list.
+///
+/// [`Schema`]: crate::validate::Schema
+#[derive(Debug, Clone, PartialEq)]
+pub enum TagSchema {
+    /// A tag of the given kind with any value. Generated values are random: floats are finite,
+    /// and strings and arrays are up to `testing::MAX_GENERATED_LEN` long, with strings made up of
+    /// ASCII letters and digits. Generated lists and compounds of this schema are empty.
+    Kind(NbtTagKind),
+    /// One of the given tags, chosen at random. The tags should be of the same kind if this schema
+    /// is used for the elements of a list, since lists cannot hold tags of different kinds. An
+    /// empty compound is generated if no tags are given.
+    OneOf(Vec<NbtTag>),
+    /// A list of up to `max_len` elements which each follow the given schema.
+    List {
+        /// The schema of the elements.
+        element: Box<TagSchema>,
+        /// The maximum number of elements.
+        max_len: usize,
+    },
+    /// A compound following the given schema.
+    Compound(Schema),
+}
+
+impl TagSchema {
+    /// Returns a schema choosing one of the given values at random.
+    pub fn one_of<I>(values: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<NbtTag>,
+    {
+        TagSchema::OneOf(values.into_iter().map(Into::into).collect())
+    }
+
+    /// Returns a schema for lists of up to `max_len` elements following the given schema.
+    pub fn list(element: TagSchema, max_len: usize) -> Self {
+        TagSchema::List {
+            element: Box::new(element),
+            max_len,
+        }
+    }
+
+    /// Infers a schema from a sample tag in the same way as [`Schema::infer`].
+    ///
+    /// [`Schema::infer`]: crate::validate::Schema::infer
+    pub fn infer(sample: &NbtTag) -> Self {
+        match sample {
+            NbtTag::List(list) => TagSchema::list(
+                match list.iter().next() {
+                    Some(element) => TagSchema::infer(element),
+                    None => TagSchema::Kind(list.element_kind().unwrap_or(NbtTagKind::Compound)),
+                },
+                list.len(),
+            ),
+            NbtTag::Compound(compound) => TagSchema::Compound(Schema::infer(compound)),
+            tag => TagSchema::Kind(tag.kind()),
+        }
+    }
+
+    /// Returns whether the given tag could have been generated from this schema.
+    pub fn is_valid(&self, tag: &NbtTag) -> bool {
+        match (self, tag) {
+            (TagSchema::Kind(kind), tag) => tag.kind() == *kind,
+            (TagSchema::OneOf(values), NbtTag::Compound(compound)) if values.is_empty() =>
+                compound.is_empty(),
+            (TagSchema::OneOf(values), tag) => values.contains(tag),
+            (TagSchema::List { element, max_len }, NbtTag::List(list)) =>
+                list.len() <= *max_len && list.iter().all(|tag| element.is_valid(tag)),
+            (TagSchema::Compound(schema), NbtTag::Compound(compound)) => schema.is_valid(compound),
+            _ => false,
+        }
+    }
+}
+
+/// The kinds of typed compounds recognized by a [`Validator`], which are identified by the string
+/// under their `id` key.
+///
+/// [`Validator`]: crate::validate::Validator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum IdKind {
+    /// An entity, such as `minecraft:zombie`.
+    Entity,
+    /// A block entity, such as `minecraft:chest`.
+    BlockEntity,
+}
+
+impl Display for IdKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            IdKind::Entity => write!(f, "entity"),
+            IdKind::BlockEntity => write!(f, "block entity"),
+        }
+    }
+}
+
+/// A registry of the entity and block entity ids known to a [`Validator`], along with the schema
+/// expected of each, and the keys under which entities and block entities are stored.
+///
+/// A compound is recognized as an entity or block entity if it is stored under one of the
+/// registry's container keys, either directly or as an element of a list. By default, entities are
+/// found under `Entities` and `Passengers`, and block entities under `block_entities` and
+/// `TileEntities`, which covers chunks, entity chunks and structures from both before and after
+/// Minecraft 1.18. Containers are found at any depth, including within other entities.
+///
+/// Since entity compounds hold many keys which are rarely of interest, schemas registered for
+/// entities will usually want to [`allow_other_keys`].
+///
+/// [`Validator`]: crate::validate::Validator
+/// [`allow_other_keys`]: crate::validate::Schema::allow_other_keys
+#[derive(Debug, Clone)]
+pub struct IdRegistry {
+    schemas: HashMap<IdKind, HashMap<String, Schema>>,
+    containers: HashMap<String, IdKind>,
+}
+
+impl IdRegistry {
+    /// Returns a registry without any known ids, which uses the default container keys.
+    pub fn new() -> Self {
+        IdRegistry {
+            schemas: HashMap::new(),
+            containers: [
+                ("Entities", IdKind::Entity),
+                ("Passengers", IdKind::Entity),
+                ("block_entities", IdKind::BlockEntity),
+                ("TileEntities", IdKind::BlockEntity),
+            ]
+            .into_iter()
+            .map(|(key, kind)| (key.to_owned(), kind))
+            .collect(),
+        }
+    }
+
+    /// Registers an id of the given kind, replacing any previous schema for it. Compounds with
+    /// this id are checked against the given schema, including their `id` key.
+    pub fn register<I: Into<String>>(mut self, kind: IdKind, id: I, schema: Schema) -> Self {
+        self.schemas
+            .entry(kind)
+            .or_default()
+            .insert(id.into(), schema);
+        self
+    }
+
+    /// Registers an entity id, as with [`register`].
+    ///
+    /// [`register`]: crate::validate::IdRegistry::register
+    pub fn entity<I: Into<String>>(self, id: I, schema: Schema) -> Self {
+        self.register(IdKind::Entity, id, schema)
+    }
+
+    /// Registers a block entity id, as with [`register`].
+    ///
+    /// [`register`]: crate::validate::IdRegistry::register
+    pub fn block_entity<I: Into<String>>(self, id: I, schema: Schema) -> Self {
+        self.register(IdKind::BlockEntity, id, schema)
+    }
+
+    /// Adds a key under which compounds of the given kind are stored, replacing the kind of any
+    /// existing container with the same key. This is useful for mods which store entities
+    /// elsewhere.
+    pub fn container<K: Into<String>>(mut self, key: K, kind: IdKind) -> Self {
+        self.containers.insert(key.into(), kind);
+        self
+    }
+
+    /// Returns whether the given id of the given kind has been registered.
+    pub fn contains(&self, kind: IdKind, id: &str) -> bool {
+        self.schema(kind, id).is_some()
+    }
+
+    fn schema(&self, kind: IdKind, id: &str) -> Option<&Schema> {
+        self.schemas.get(&kind).and_then(|schemas| schemas.get(id))
+    }
+}
+
+impl Default for IdRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Finds the entities and block entities anywhere within a document, and checks each against the
+/// schema registered for its id in an [`IdRegistry`]. This is useful for scanning worlds for
+/// content added by mods which are no longer installed.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::validate::{IdKind, IdRegistry, IdStatus, Schema, TagSchema, Validator};
+///
+/// let registry = IdRegistry::new()
+///     .entity(
+///         "minecraft:pig",
+///         Schema::new()
+///             .required("id", TagSchema::Kind(NbtTagKind::String))
+///             .optional("Saddle", TagSchema::Kind(NbtTagKind::Byte))
+///             .allow_other_keys(true),
+///     )
+///     .block_entity("minecraft:chest", Schema::new().allow_other_keys(true));
+/// let validator = Validator::with_registry(registry);
+///
+/// let chunk = compound! {
+///     "block_entities": [{ "id": "minecraft:chest", "x": 1 }, { "id": "somemod:pipe" }],
+///     "Entities": [{
+///         "id": "minecraft:pig",
+///         "Saddle": "yes",
+///         "Passengers": [{ "id": "somemod:robot" }]
+///     }]
+/// };
+///
+/// let report = validator.validate(&chunk);
+/// assert_eq!(report.len(), 4);
+/// assert!(!report.is_valid());
+///
+/// let mut issues = report.issues().map(ToString::to_string).collect::<Vec<_>>();
+/// issues.sort();
+/// assert_eq!(issues, [
+///     "Entities[0].Passengers[0]: unknown entity somemod:robot",
+///     "Entities[0]: entity minecraft:pig does not match its schema",
+///     "block_entities[1]: unknown block entity somemod:pipe",
+/// ]);
+///
+/// let unknown = report.unknown_ids();
+/// assert_eq!(unknown.get(&(IdKind::BlockEntity, "somemod:pipe")), Some(&1));
+/// assert!(report.iter().any(|entry| entry.status() == IdStatus::Valid));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Validator {
+    registry: IdRegistry,
+}
+
+impl Validator {
+    /// Returns a validator using the given registry.
+    pub fn with_registry(registry: IdRegistry) -> Self {
+        Validator { registry }
+    }
+
+    /// Returns the registry used by this validator.
+    pub fn registry(&self) -> &IdRegistry {
+        &self.registry
+    }
+
+    /// Finds every entity and block entity within the given compound, returning a report which
+    /// annotates each with its id and whether it is valid.
+    pub fn validate(&self, compound: &NbtCompound) -> ValidationReport {
+        let mut walker = Walker {
+            registry: &self.registry,
+            path: String::new(),
+            report: ValidationReport::default(),
+        };
+        walker.compound(compound);
+        walker.report
+    }
+}
+
+/// A report of every entity and block entity found by a [`Validator`], in the order in which the
+/// document's compounds are iterated.
+///
+/// [`Validator`]: crate::validate::Validator
+#[derive(Debug, Clone, Default)]
+pub struct ValidationReport {
+    entries: Vec<ValidationEntry>,
+}
+
+impl ValidationReport {
+    /// Returns the number of entities and block entities found.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if no entities or block entities were found.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns true if every entity and block entity found has a known id and matches its schema.
+    pub fn is_valid(&self) -> bool {
+        self.issues().next().is_none()
+    }
+
+    /// Returns an iterator over every entity and block entity found.
+    pub fn iter(&self) -> slice::Iter<'_, ValidationEntry> {
+        self.entries.iter()
+    }
+
+    /// Returns an iterator over the entities and block entities which are not valid.
+    pub fn issues(&self) -> impl Iterator<Item = &ValidationEntry> + '_ {
+        self.entries
+            .iter()
+            .filter(|entry| entry.status != IdStatus::Valid)
+    }
+
+    /// Returns each unknown id along with its kind, mapped to the number of times it was found.
+    pub fn unknown_ids(&self) -> BTreeMap<(IdKind, &str), usize> {
+        let mut unknown = BTreeMap::new();
+        for entry in &self.entries {
+            if let (IdStatus::Unknown, Some(id)) = (entry.status, &entry.id) {
+                *unknown.entry((entry.kind, id.as_str())).or_insert(0) += 1;
+            }
+        }
+        unknown
+    }
+}
+
+impl<'a> IntoIterator for &'a ValidationReport {
+    type IntoIter = slice::Iter<'a, ValidationEntry>;
+    type Item = &'a ValidationEntry;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter()
+    }
+}
+
+/// An entity or block entity found by a validator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationEntry {
+    path: String,
+    kind: IdKind,
+    id: Option<String>,
+    status: IdStatus,
+}
+
+impl ValidationEntry {
+    /// Returns the path to the entity or block entity.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns whether this is an entity or a block entity.
+    pub fn kind(&self) -> IdKind {
+        self.kind
+    }
+
+    /// Returns the id of the entity or block entity, or `None` if it does not have a string under
+    /// its `id` key.
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    /// Returns the result of validating the entity or block entity.
+    pub fn status(&self) -> IdStatus {
+        self.status
+    }
+}
+
+impl Display for ValidationEntry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let id = self.id.as_deref().unwrap_or_default();
+        match self.status {
+            IdStatus::Valid => write!(f, "{}: {} {}", self.path, self.kind, id),
+            IdStatus::Unknown => write!(f, "{}: unknown {} {}", self.path, self.kind, id),
+            IdStatus::SchemaMismatch => write!(
+                f,
+                "{}: {} {} does not match its schema",
+                self.path, self.kind, id
+            ),
+            IdStatus::MissingId => write!(f, "{}: {} without an id", self.path, self.kind),
+        }
+    }
+}
+
+/// The result of validating a single entity or block entity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IdStatus {
+    /// The id is registered, and the compound matches its schema.
+    Valid,
+    /// The id is not registered.
+    Unknown,
+    /// The id is registered, but the compound does not match its schema.
+    SchemaMismatch,
+    /// The compound does not have a string under its `id` key.
+    MissingId,
+}
+
+struct Walker<'a> {
+    registry: &'a IdRegistry,
+    path: String,
+    report: ValidationReport,
+}
+
+impl Walker<'_> {
+    fn compound(&mut self, compound: &NbtCompound) {
+        for (key, tag) in compound.inner() {
+            let len = self.path.len();
+            push_key(&mut self.path, key);
+            match (self.registry.containers.get(key), tag) {
+                (Some(&kind), NbtTag::Compound(compound)) => self.typed(kind, compound),
+                (Some(&kind), NbtTag::List(list)) =>
+                    for (index, tag) in list.iter().enumerate() {
+                        let len = self.path.len();
+                        push_index(&mut self.path, index);
+                        match tag {
+                            NbtTag::Compound(compound) => self.typed(kind, compound),
+                            tag => self.tag(tag),
+                        }
+                        self.path.truncate(len);
+                    },
+                (_, tag) => self.tag(tag),
+            }
+            self.path.truncate(len);
+        }
+    }
+
+    fn tag(&mut self, tag: &NbtTag) {
+        match tag {
+            NbtTag::Compound(compound) => self.compound(compound),
+            NbtTag::List(list) =>
+                for (index, tag) in list.iter().enumerate() {
+                    let len = self.path.len();
+                    push_index(&mut self.path, index);
+                    self.tag(tag);
+                    self.path.truncate(len);
+                },
+            _ => {}
+        }
+    }
+
+    fn typed(&mut self, kind: IdKind, compound: &NbtCompound) {
+        let id = match compound.inner().get("id") {
+            Some(NbtTag::String(id)) => Some(id.clone()),
+            _ => None,
+        };
+        let status = match &id {
+            Some(id) => match self.registry.schema(kind, id) {
+                Some(schema) if schema.is_valid(compound) => IdStatus::Valid,
+                Some(_) => IdStatus::SchemaMismatch,
+                None => IdStatus::Unknown,
+            },
+            None => IdStatus::MissingId,
+        };
+        self.report.entries.push(ValidationEntry {
+            path: self.path.clone(),
+            kind,
+            id,
+            status,
+        });
+
+        // Entities may carry passengers, and block entities may hold items with block entity data
+        self.compound(compound);
+    }
+}
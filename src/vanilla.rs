@@ -0,0 +1,411 @@
+use crate::{path::remove_key, NbtCompound, NbtList, NbtReprError, NbtStructureError, NbtTag};
+use std::ops::{Deref, DerefMut};
+
+/// A view of the `GameRules` compound stored in `level.dat`, which maps the name of each rule to
+/// its value.
+///
+/// Minecraft stores the value of every rule as a string, such as `"true"` or `"3"`. The getters
+/// of this view parse those strings, and also accept byte and int tags holding the values. The
+/// setters keep the kind of tag already holding the rule, and write strings for new rules.
+///
+/// The view can wrap either `&NbtCompound` or `&mut NbtCompound`, and only provides setters in the
+/// latter case.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::vanilla::GameRules;
+///
+/// let mut level = compound! {
+///     "GameRules": { "doDaylightCycle": "true", "randomTickSpeed": "3" }
+/// };
+///
+/// let mut rules = GameRules::new(level.get_mut::<_, &mut NbtCompound>("GameRules")?);
+/// assert!(rules.get_bool("doDaylightCycle")?);
+/// assert_eq!(rules.get_int("randomTickSpeed")?, 3);
+///
+/// rules.set_bool("doDaylightCycle", false);
+/// rules.set_int("spawnRadius", 0);
+/// assert_eq!(
+///     level,
+///     compound! {
+///         "GameRules": {
+///             "doDaylightCycle": "false",
+///             "randomTickSpeed": "3",
+///             "spawnRadius": "0"
+///         }
+///     }
+/// );
+/// # Ok::<(), NbtReprError>(())
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct GameRules<C> {
+    compound: C,
+}
+
+impl<C: Deref<Target = NbtCompound>> GameRules<C> {
+    /// Returns a view of the given `GameRules` compound.
+    #[inline]
+    pub fn new(compound: C) -> Self {
+        GameRules { compound }
+    }
+
+    /// Returns the wrapped compound.
+    #[inline]
+    pub fn into_inner(self) -> C {
+        self.compound
+    }
+
+    /// Returns the string value of the given rule, or an error if the rule is missing or is not
+    /// stored as a string.
+    pub fn get(&self, name: &str) -> Result<&str, NbtReprError> {
+        self.compound.get::<_, &str>(name)
+    }
+
+    /// Returns the value of the given boolean rule, which must be stored as `"true"`, `"false"`,
+    /// or a byte tag holding `1` or `0`.
+    pub fn get_bool(&self, name: &str) -> Result<bool, NbtReprError> {
+        match self.rule(name)? {
+            NbtTag::String(value) => value.parse().map_err(NbtReprError::from_any),
+            _ => Ok(self.compound.get_bool_strict(name)?),
+        }
+    }
+
+    /// Returns the value of the given integer rule, which must be stored as a string holding a
+    /// decimal integer or as an int tag.
+    pub fn get_int(&self, name: &str) -> Result<i32, NbtReprError> {
+        match self.rule(name)? {
+            NbtTag::String(value) => value.parse().map_err(NbtReprError::from_any),
+            _ => self.compound.get::<_, i32>(name),
+        }
+    }
+
+    /// Returns whether the given rule is present.
+    #[inline]
+    pub fn contains(&self, name: &str) -> bool {
+        self.compound.contains_key(name)
+    }
+
+    /// Returns an iterator over the names of the rules which are present.
+    pub fn names(&self) -> impl Iterator<Item = &str> + '_ {
+        self.compound.inner().keys().map(String::as_str)
+    }
+
+    fn rule(&self, name: &str) -> Result<&NbtTag, NbtStructureError> {
+        self.compound
+            .inner()
+            .get(name)
+            .ok_or_else(|| NbtStructureError::missing_tag(name))
+    }
+}
+
+impl<C: DerefMut<Target = NbtCompound>> GameRules<C> {
+    /// Sets the given rule to the given string value.
+    pub fn set<K: Into<String>, V: Into<String>>(&mut self, name: K, value: V) {
+        self.compound.insert(name, value.into());
+    }
+
+    /// Sets the given boolean rule. The value is written as `"true"` or `"false"`, unless the rule
+    /// is currently stored as a byte tag.
+    pub fn set_bool<K: Into<String>>(&mut self, name: K, value: bool) {
+        let name = name.into();
+        match self.compound.inner_mut().get_mut(&name) {
+            Some(NbtTag::Byte(byte)) => *byte = value as i8,
+            _ => self.set(name, value.to_string()),
+        }
+    }
+
+    /// Sets the given integer rule. The value is written as a decimal string, unless the rule is
+    /// currently stored as an int tag.
+    pub fn set_int<K: Into<String>>(&mut self, name: K, value: i32) {
+        let name = name.into();
+        match self.compound.inner_mut().get_mut(&name) {
+            Some(NbtTag::Int(int)) => *int = value,
+            _ => self.set(name, value.to_string()),
+        }
+    }
+
+    /// Removes the given rule, returning its tag if it was present.
+    pub fn remove(&mut self, name: &str) -> Option<NbtTag> {
+        remove_key(&mut self.compound, name)
+    }
+}
+
+/// A view of the `Attributes` list of an entity, where each element is a compound describing one
+/// attribute.
+///
+/// Both the format used since Minecraft 1.20.5, where attributes have `id` and `base` keys, and
+/// the older format using `Name` and `Base` are understood. Attributes are looked up by the exact
+/// value of their ID or name, so `minecraft:generic.max_health` and `generic.max_health` are
+/// distinct. New attributes are written in the format of the first attribute in the list, or in
+/// the newer format if the list is empty.
+///
+/// The view can wrap either `&NbtList` or `&mut NbtList`, and only provides setters in the latter
+/// case.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::vanilla::Attributes;
+///
+/// let mut entity = compound! {
+///     "attributes": [
+///         { "id": "minecraft:generic.max_health", "base": 20.0f64 },
+///         { "id": "minecraft:generic.movement_speed", "base": 0.1f64 }
+///     ]
+/// };
+///
+/// let mut attributes = Attributes::new(entity.get_mut::<_, &mut NbtList>("attributes")?);
+/// assert_eq!(attributes.base("minecraft:generic.max_health")?, 20.0);
+///
+/// attributes.set_base("minecraft:generic.max_health", 40.0);
+/// attributes.set_base("minecraft:generic.armor", 4.0);
+/// attributes.remove("minecraft:generic.movement_speed");
+/// assert_eq!(
+///     entity,
+///     compound! {
+///         "attributes": [
+///             { "id": "minecraft:generic.max_health", "base": 40.0f64 },
+///             { "id": "minecraft:generic.armor", "base": 4.0f64 }
+///         ]
+///     }
+/// );
+/// # Ok::<(), NbtReprError>(())
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Attributes<L> {
+    list: L,
+}
+
+impl<L: Deref<Target = NbtList>> Attributes<L> {
+    /// Returns a view of the given attribute list.
+    #[inline]
+    pub fn new(list: L) -> Self {
+        Attributes { list }
+    }
+
+    /// Returns the wrapped list.
+    #[inline]
+    pub fn into_inner(self) -> L {
+        self.list
+    }
+
+    /// Returns the compound describing the given attribute, if it is present.
+    pub fn get(&self, name: &str) -> Option<&NbtCompound> {
+        self.list
+            .iter()
+            .filter_map(as_compound)
+            .find(|attribute| attribute_name(attribute) == Some(name))
+    }
+
+    /// Returns the base value of the given attribute, or an error if the attribute is missing or
+    /// its base value is not a double.
+    pub fn base(&self, name: &str) -> Result<f64, NbtReprError> {
+        let attribute = self
+            .get(name)
+            .ok_or_else(|| NbtStructureError::missing_tag(name))?;
+        attribute.get::<_, f64>(base_key(attribute))
+    }
+
+    /// Returns an iterator over the IDs or names of the attributes in the list.
+    pub fn names(&self) -> impl Iterator<Item = &str> + '_ {
+        self.list
+            .iter()
+            .filter_map(as_compound)
+            .filter_map(attribute_name)
+    }
+}
+
+impl<L: DerefMut<Target = NbtList>> Attributes<L> {
+    /// Returns a mutable reference to the compound describing the given attribute, if it is
+    /// present.
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut NbtCompound> {
+        self.list
+            .iter_mut()
+            .filter_map(as_compound_mut)
+            .find(|attribute| attribute_name(attribute) == Some(name))
+    }
+
+    /// Sets the base value of the given attribute, adding the attribute to the list if it is not
+    /// present.
+    pub fn set_base(&mut self, name: &str, value: f64) {
+        if let Some(attribute) = self.get_mut(name) {
+            let key = base_key(attribute);
+            attribute.insert(key, value);
+            return;
+        }
+
+        let legacy = self
+            .list
+            .first()
+            .and_then(as_compound)
+            .is_some_and(|attribute| attribute.contains_key("Name"));
+        let (name_key, base_key) = if legacy {
+            ("Name", "Base")
+        } else {
+            ("id", "base")
+        };
+
+        let mut attribute = NbtCompound::with_capacity(2);
+        attribute.insert(name_key, name);
+        attribute.insert(base_key, value);
+        self.list.push(attribute);
+    }
+
+    /// Removes the given attribute, returning its compound if it was present.
+    pub fn remove(&mut self, name: &str) -> Option<NbtCompound> {
+        let index = self
+            .list
+            .iter()
+            .position(|tag| as_compound(tag).and_then(attribute_name) == Some(name))?;
+        match self.list.inner_mut().remove(index) {
+            NbtTag::Compound(attribute) => Some(attribute),
+            _ => unreachable!("Attribute is not a compound"),
+        }
+    }
+}
+
+fn attribute_name(attribute: &NbtCompound) -> Option<&str> {
+    attribute
+        .get::<_, &str>("id")
+        .or_else(|_| attribute.get::<_, &str>("Name"))
+        .ok()
+}
+
+fn base_key(attribute: &NbtCompound) -> &'static str {
+    if !attribute.contains_key("id") && attribute.contains_key("Name") {
+        "Base"
+    } else {
+        "base"
+    }
+}
+
+/// A view of a list of enchantments, such as the `Enchantments` or `StoredEnchantments` list of
+/// an item from before Minecraft 1.20.5, where each element is a compound holding the `id` and the
+/// short `lvl` of one enchantment.
+///
+/// The view can wrap either `&NbtList` or `&mut NbtList`, and only provides setters in the latter
+/// case.
+///
+/// ```
+/// # use quartz_nbt::*;
+/// use quartz_nbt::vanilla::Enchantments;
+///
+/// let mut tag = compound! {
+///     "Enchantments": [{ "id": "minecraft:sharpness", "lvl": 5i16 }]
+/// };
+///
+/// let mut enchantments = Enchantments::new(tag.get_mut::<_, &mut NbtList>("Enchantments")?);
+/// assert_eq!(enchantments.level("minecraft:sharpness")?, 5);
+/// assert!(enchantments.level("minecraft:mending").is_err());
+///
+/// enchantments.set_level("minecraft:mending", 1);
+/// assert_eq!(
+///     enchantments.iter().collect::<Vec<_>>(),
+///     [("minecraft:sharpness", 5), ("minecraft:mending", 1)]
+/// );
+///
+/// enchantments.remove("minecraft:sharpness");
+/// assert_eq!(
+///     tag,
+///     compound! { "Enchantments": [{ "id": "minecraft:mending", "lvl": 1i16 }] }
+/// );
+/// # Ok::<(), NbtReprError>(())
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Enchantments<L> {
+    list: L,
+}
+
+impl<L: Deref<Target = NbtList>> Enchantments<L> {
+    /// Returns a view of the given enchantment list.
+    #[inline]
+    pub fn new(list: L) -> Self {
+        Enchantments { list }
+    }
+
+    /// Returns the wrapped list.
+    #[inline]
+    pub fn into_inner(self) -> L {
+        self.list
+    }
+
+    /// Returns the level of the given enchantment, or an error if the enchantment is missing or
+    /// its level is not a short.
+    pub fn level(&self, id: &str) -> Result<i16, NbtReprError> {
+        self.list
+            .iter()
+            .filter_map(as_compound)
+            .find(|enchantment| enchantment_id(enchantment) == Some(id))
+            .ok_or_else(|| NbtStructureError::missing_tag(id))?
+            .get::<_, i16>("lvl")
+    }
+
+    /// Returns whether the given enchantment is present.
+    pub fn contains(&self, id: &str) -> bool {
+        self.list
+            .iter()
+            .filter_map(as_compound)
+            .any(|enchantment| enchantment_id(enchantment) == Some(id))
+    }
+
+    /// Returns an iterator over the ID and level of each enchantment in the list, skipping any
+    /// elements which are not well-formed.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, i16)> + '_ {
+        self.list
+            .iter()
+            .filter_map(as_compound)
+            .filter_map(|enchantment| {
+                Some((
+                    enchantment_id(enchantment)?,
+                    enchantment.get::<_, i16>("lvl").ok()?,
+                ))
+            })
+    }
+}
+
+impl<L: DerefMut<Target = NbtList>> Enchantments<L> {
+    /// Sets the level of the given enchantment, adding the enchantment to the end of the list if
+    /// it is not present.
+    pub fn set_level(&mut self, id: &str, level: i16) {
+        let existing = self
+            .list
+            .iter_mut()
+            .filter_map(as_compound_mut)
+            .find(|enchantment| enchantment_id(enchantment) == Some(id));
+        match existing {
+            Some(enchantment) => enchantment.insert("lvl", level),
+            None => {
+                let mut enchantment = NbtCompound::with_capacity(2);
+                enchantment.insert("id", id);
+                enchantment.insert("lvl", level);
+                self.list.push(enchantment);
+            }
+        }
+    }
+
+    /// Removes the given enchantment, returning whether it was present.
+    pub fn remove(&mut self, id: &str) -> bool {
+        let len = self.list.len();
+        self.list
+            .inner_mut()
+            .retain(|tag| as_compound(tag).and_then(enchantment_id) != Some(id));
+        self.list.len() != len
+    }
+}
+
+fn enchantment_id(enchantment: &NbtCompound) -> Option<&str> {
+    enchantment.get::<_, &str>("id").ok()
+}
+
+fn as_compound(tag: &NbtTag) -> Option<&NbtCompound> {
+    match tag {
+        NbtTag::Compound(compound) => Some(compound),
+        _ => None,
+    }
+}
+
+fn as_compound_mut(tag: &mut NbtTag) -> Option<&mut NbtCompound> {
+    match tag {
+        NbtTag::Compound(compound) => Some(compound),
+        _ => None,
+    }
+}
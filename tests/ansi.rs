@@ -0,0 +1,103 @@
+#![cfg(feature = "ansi")]
+
+mod assets;
+use assets::*;
+use quartz_nbt::{
+    compound,
+    io::{self, Flavor},
+    snbt::{self, AnsiColor, AnsiTheme, FormatOptions},
+    NbtList,
+    NbtTag,
+};
+use std::io::Cursor;
+
+// Removes every SGR escape sequence from the given string
+fn strip_escapes(string: &str) -> String {
+    let mut stripped = String::with_capacity(string.len());
+    let mut chars = string.chars();
+    while let Some(ch) = chars.next() {
+        if ch == '\x1b' {
+            chars.by_ref().find(|&ch| ch == 'm');
+        } else {
+            stripped.push(ch);
+        }
+    }
+    stripped
+}
+
+#[test]
+fn colored_snbt() {
+    let theme = AnsiTheme::new();
+    let nbt = compound! {
+        "flags": [1i8, 2i8],
+        "bytes": [B; 3],
+        "name": "Steve"
+    };
+
+    let colored = nbt.to_ansi_snbt(theme);
+    assert_eq!(strip_escapes(&colored), nbt.to_pretty_snbt());
+    assert!(colored.contains("\x1b[96mflags\x1b[0m: "));
+    assert!(colored.contains("\x1b[33m1\x1b[0m\x1b[91mB\x1b[0m"));
+    assert!(colored.contains("[\n        \x1b[91mB\x1b[0m;\n        \x1b[33m3\x1b[0m\n    ]"));
+    assert!(colored.contains("\x1b[92mSteve\x1b[0m"));
+
+    // Ints have no suffix, and boolean literals are colored like numbers
+    let options = FormatOptions::new().bool_literals(true).ansi_theme(theme);
+    assert_eq!(
+        NbtList::from(vec![NbtTag::Byte(1), NbtTag::Byte(5)]).to_snbt_with(options),
+        "[\x1b[33mtrue\x1b[0m,\x1b[33m5\x1b[0m\x1b[91mB\x1b[0m]"
+    );
+    assert_eq!(
+        NbtTag::Int(-7).to_ansi_snbt(theme.number(Some(AnsiColor::BrightWhite))),
+        "\x1b[97m-7\x1b[0m"
+    );
+
+    let big_test = io::read_nbt(&mut Cursor::new(BIG_TEST), BIG_TEST_FLAVOR)
+        .unwrap()
+        .0;
+    let colored = big_test.to_ansi_snbt(theme);
+    assert_eq!(snbt::parse(&strip_escapes(&colored)).unwrap(), big_test);
+    assert_eq!(
+        big_test.to_ansi_snbt(AnsiTheme::plain()),
+        big_test.to_pretty_snbt()
+    );
+}
+
+#[test]
+fn colored_streaming() {
+    // Every compound has at most one entry so that the output doesn't depend on map ordering
+    let nbt = compound! {
+        "root": {
+            "entries": [
+                { "id": "minecraft:stone" },
+                { "count": 64i8 },
+                { "data": [L; 1, -2] },
+                {}
+            ]
+        }
+    };
+    let bytes = io::write_nbt_bytes(None, &nbt, Flavor::Uncompressed).unwrap();
+
+    for options in [
+        FormatOptions::new().ansi_theme(AnsiTheme::new()),
+        FormatOptions::new()
+            .pretty(true)
+            .ansi_theme(AnsiTheme::new()),
+        FormatOptions::new()
+            .bool_literals(true)
+            .ansi_theme(AnsiTheme::new().key(Some(AnsiColor::Magenta)).suffix(None)),
+    ] {
+        let mut streamed = Vec::new();
+        snbt::stream_from_binary(
+            &mut bytes.as_slice(),
+            &mut streamed,
+            Flavor::Uncompressed,
+            options,
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(streamed).unwrap(),
+            nbt.to_snbt_with(options)
+        );
+    }
+}
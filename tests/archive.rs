@@ -0,0 +1,133 @@
+#![cfg(feature = "rkyv")]
+
+mod assets;
+use assets::*;
+use quartz_nbt::{
+    archive,
+    assert_compound_eq,
+    compound,
+    io,
+    ArchivedNbtTag,
+    NbtCompound,
+    NbtList,
+    NbtTag,
+    NbtTagKind,
+};
+use rkyv::util::AlignedVec;
+use std::io::Cursor;
+
+#[test]
+fn big_test() {
+    let (nbt, _) = io::read_nbt(&mut Cursor::new(BIG_TEST), BIG_TEST_FLAVOR).unwrap();
+    let bytes = archive::to_bytes(&nbt).unwrap();
+
+    let archived = archive::access(&bytes).unwrap();
+    assert_eq!(archived.len(), nbt.len());
+    for (key, tag) in archived.iter() {
+        assert_eq!(tag.kind(), nbt.get::<_, &NbtTag>(key).unwrap().kind());
+        assert_eq!(&tag.to_tag().unwrap(), nbt.get::<_, &NbtTag>(key).unwrap());
+    }
+
+    assert_compound_eq!(archive::from_bytes(&bytes).unwrap(), nbt);
+    assert_compound_eq!(archived.to_compound().unwrap(), *BIG_TEST_VALIDATE);
+}
+
+#[test]
+fn accessors() {
+    let mut empty_ints = NbtList::new();
+    empty_ints.set_element_kind(Some(NbtTagKind::Int));
+    let mut nbt = compound! {
+        "name": "Steve",
+        "xp": 30i32,
+        "pos": [1.0f64, 64.0f64, -3.5f64],
+        "inventory": [{ "id": "minecraft:stone", "Count": 64i8 }],
+        "uuid": [I; 1, 2, 3, 4],
+        "nan": f64::NAN
+    };
+    nbt.insert("empty", empty_ints);
+
+    let bytes = archive::to_bytes(&nbt).unwrap();
+    let archived = archive::access(&bytes).unwrap();
+    assert_eq!(archived.len(), 7);
+    assert!(!archived.is_empty());
+    assert!(archived.contains_key("xp"));
+    assert!(archived.get("missing").is_none());
+
+    match archived.get("name") {
+        Some(ArchivedNbtTag::String(name)) => assert_eq!(name.as_str(), "Steve"),
+        tag => panic!("unexpected tag {:?}", tag),
+    }
+    match archived.get("xp") {
+        Some(ArchivedNbtTag::Int(xp)) => assert_eq!(xp.to_native(), 30),
+        tag => panic!("unexpected tag {:?}", tag),
+    }
+    match archived.get("nan") {
+        Some(ArchivedNbtTag::Double(value)) => assert!(value.to_native().is_nan()),
+        tag => panic!("unexpected tag {:?}", tag),
+    }
+
+    let pos = match archived.get("pos") {
+        Some(ArchivedNbtTag::List(pos)) => pos,
+        tag => panic!("unexpected tag {:?}", tag),
+    };
+    assert_eq!(pos.len(), 3);
+    assert_eq!(pos.element_kind(), None);
+    assert!(pos.iter().all(|tag| tag.kind() == NbtTagKind::Double));
+    assert!(pos.get(3).is_none());
+
+    let inventory = match archived.get("inventory") {
+        Some(ArchivedNbtTag::List(inventory)) => inventory,
+        tag => panic!("unexpected tag {:?}", tag),
+    };
+    match inventory.get(0) {
+        Some(ArchivedNbtTag::Compound(item)) => {
+            assert_eq!(item.len(), 2);
+            assert_eq!(
+                item.to_compound().unwrap(),
+                compound! { "id": "minecraft:stone", "Count": 64i8 }
+            );
+        }
+        tag => panic!("unexpected tag {:?}", tag),
+    }
+
+    match archived.get("empty") {
+        Some(ArchivedNbtTag::List(empty)) => {
+            assert!(empty.is_empty());
+            assert_eq!(empty.element_kind(), Some(NbtTagKind::Int));
+        }
+        tag => panic!("unexpected tag {:?}", tag),
+    }
+
+    let read = archive::from_bytes(&bytes).unwrap();
+    assert_eq!(
+        read.get::<_, &NbtList>("empty").unwrap().element_kind(),
+        Some(NbtTagKind::Int)
+    );
+    assert!(read.get::<_, f64>("nan").unwrap().is_nan());
+}
+
+#[test]
+fn invalid_archives() {
+    let bytes = archive::to_bytes(&compound! { "name": "Steve", "list": [1i32, 2i32] }).unwrap();
+
+    assert!(archive::access(&[]).is_err());
+    assert!(archive::from_bytes(&bytes[.. bytes.len() - 4]).is_err());
+
+    // The same bytes at an odd address are rejected rather than misread
+    let mut misaligned = AlignedVec::<16>::new();
+    misaligned.push(0);
+    misaligned.extend_from_slice(&bytes);
+    assert!(archive::access(&misaligned[1 ..]).is_err());
+
+    // Corrupt every byte in turn, none of which may cause undefined behavior
+    for index in 0 .. bytes.len() {
+        let mut corrupt = bytes.clone();
+        corrupt[index] = corrupt[index].wrapping_add(0x55);
+        if let Ok(archived) = archive::access(&corrupt) {
+            let _ = archived.to_compound().unwrap();
+        }
+    }
+
+    let empty = archive::to_bytes(&NbtCompound::new()).unwrap();
+    assert!(archive::access(&empty).unwrap().is_empty());
+}
@@ -113,31 +113,3 @@ pub static SNBT_EDGE_CASES_VALIDATE: Lazy<NbtCompound> = Lazy::new(|| {
 pub const BIG_SNBT: &str = include_str!("big_snbt.snbt");
 
 pub const LEVEL_DAT: &[u8] = include_bytes!("level.dat");
-
-#[macro_export]
-macro_rules! assert_compound_eq {
-    ($test:expr, $validate:expr) => {{
-        let test = $test;
-        let validate = $validate;
-
-        for (key, value) in validate.inner().iter() {
-            let test_value = match test.inner().get(key) {
-                Some(test_value) => test_value,
-                None => {
-                    panic!(
-                        "Compound missing field {} found in \
-                         validation:\nTest:{:#?}\nValidate:{:#?}",
-                        key, test, validate
-                    );
-                }
-            };
-
-            if test_value != value {
-                panic!(
-                    "Test field does not equal validation field:\nTest:{:#?}\nValidate:{:#?}",
-                    test_value, value
-                )
-            }
-        }
-    }};
-}
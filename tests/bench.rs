@@ -0,0 +1,81 @@
+#![cfg(feature = "bench-util")]
+
+mod assets;
+use assets::*;
+use quartz_nbt::{
+    bench::Bencher,
+    io::{self, Flavor},
+};
+
+#[test]
+fn throughput() {
+    let bencher = Bencher::new().warmup(0).iterations(3);
+    let read = bencher.read(BIG_TEST, BIG_TEST_FLAVOR).unwrap();
+    assert_eq!(read.bytes(), BIG_TEST.len());
+    assert_eq!(read.iterations(), 3);
+    assert!(read.bytes_per_second() > 0.0);
+    assert!(read.per_iteration() <= read.elapsed());
+
+    let written = io::write_nbt_bytes(None, &BIG_TEST_VALIDATE, Flavor::Uncompressed).unwrap();
+    let write = bencher
+        .write(&BIG_TEST_VALIDATE, Flavor::Uncompressed)
+        .unwrap();
+    assert_eq!(write.bytes(), written.len());
+
+    // At least one iteration is always timed
+    assert_eq!(
+        bencher
+            .iterations(0)
+            .read(BIG_TEST, BIG_TEST_FLAVOR)
+            .unwrap()
+            .iterations(),
+        1
+    );
+
+    // Malformed documents are reported rather than measured
+    assert!(bencher.read(&BIG_TEST[.. 100], BIG_TEST_FLAVOR).is_err());
+}
+
+#[test]
+fn report() {
+    let report = Bencher::new()
+        .warmup(0)
+        .iterations(2)
+        .report(BIG_TEST)
+        .unwrap();
+    assert_eq!(report.flavor(), BIG_TEST_FLAVOR);
+    assert_eq!(report.read().bytes(), BIG_TEST.len());
+    assert!(report.read_uncompressed().bytes() > report.read().bytes());
+    assert_eq!(
+        report.write_uncompressed().bytes(),
+        report.read_uncompressed().bytes()
+    );
+
+    let display = report.to_string();
+    assert!(display.contains("read (GzCompressed)"));
+    assert!(display.contains("MiB/s"));
+    #[cfg(feature = "serde")]
+    {
+        assert_eq!(report.deserialize().bytes(), BIG_TEST.len());
+        assert!(display.contains("serialize (GzCompressed)"));
+    }
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn parity() {
+    use quartz_nbt::{bench, compound, NbtList, NbtTag, NbtTagKind};
+
+    assert!(bench::check_parity(Some("Level"), &BIG_TEST_VALIDATE).is_ok());
+    assert!(bench::check_parity(None, &SNBT_EDGE_CASES_VALIDATE).is_ok());
+
+    let nbt = compound! {
+        "bytes": NbtTag::ByteArray(Vec::new()),
+        "nested": [[[{ "x": 1i32 }]]]
+    };
+    assert!(bench::check_parity(Some(""), &nbt).is_ok());
+
+    // Both paths write the element kind of empty lists
+    let nbt = compound! { "empty": NbtList::with_element_kind(NbtTagKind::Int) };
+    assert!(bench::check_parity(None, &nbt).is_ok());
+}
@@ -0,0 +1,241 @@
+#![cfg(feature = "compact")]
+
+mod assets;
+use assets::*;
+use quartz_nbt::{
+    assert_compound_eq,
+    compact::{self, MAGIC, VERSION},
+    compound,
+    io::{self, Flavor, NbtIoError},
+    NbtCompound,
+    NbtList,
+    NbtTag,
+    NbtTagKind,
+};
+
+fn round_trip(nbt: &NbtCompound, flavor: Flavor) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    compact::write_compact(&mut bytes, Some("root"), nbt, flavor).unwrap();
+    let (read, root_name) = compact::read_compact(&mut bytes.as_slice(), flavor).unwrap();
+    assert_eq!(root_name, "root");
+    assert_compound_eq!(&read, nbt);
+    bytes
+}
+
+#[test]
+fn big_test() {
+    for flavor in [
+        Flavor::Uncompressed,
+        Flavor::ZlibCompressed,
+        Flavor::GzCompressedWith(flate2::Compression::best()),
+        Flavor::RawDeflate,
+    ] {
+        let compact = round_trip(&BIG_TEST_VALIDATE, flavor);
+        let standard = io::write_nbt_bytes(Some("root"), &BIG_TEST_VALIDATE, flavor).unwrap();
+        assert!(compact.len() < standard.len());
+    }
+}
+
+#[test]
+fn edge_values() {
+    let mut empty_ints = NbtList::new();
+    empty_ints.set_element_kind(Some(NbtTagKind::Int));
+
+    let nbt = compound! {
+        "bytes": [i8::MIN, -1i8, 0i8, i8::MAX],
+        "shorts": [i16::MIN, -1i16, 0i16, i16::MAX],
+        "ints": [i32::MIN, -1i32, 0i32, i32::MAX],
+        "longs": [i64::MIN, -1i64, 0i64, i64::MAX],
+        "floats": [f32::MIN, -0.0f32, f32::INFINITY],
+        "doubles": [f64::MAX, f64::MIN_POSITIVE, f64::NEG_INFINITY],
+        "byte_array": [B; -128, 0, 127],
+        "int_array": [I; 2147483647, -2147483648, 0, -1, 2147483647],
+        "long_array": [L; 9223372036854775807, -9223372036854775808, 1, -1],
+        "empty_bytes": NbtTag::ByteArray(Vec::new()),
+        "empty_ints_array": NbtTag::IntArray(Vec::new()),
+        "empty_longs": NbtTag::LongArray(Vec::new()),
+        "strings": ["", "\0", "ünïcödé", "🦀", "a\u{FFFF}"],
+        "empty": {},
+        "empty_list": [],
+        "empty_ints": empty_ints,
+        "": "empty key"
+    };
+    let bytes = round_trip(&nbt, Flavor::Uncompressed);
+    assert!(bytes.starts_with(&MAGIC));
+    assert_eq!(bytes[MAGIC.len()], VERSION);
+
+    let (read, _) = compact::read_compact(&mut bytes.as_slice(), Flavor::Uncompressed).unwrap();
+    assert_eq!(
+        read.get::<_, &NbtList>("empty_ints")
+            .unwrap()
+            .element_kind(),
+        Some(NbtTagKind::Int)
+    );
+
+    // Strings longer than standard NBT allows are fine
+    let long = "x".repeat(100_000);
+    round_trip(&compound! { "long": long }, Flavor::Uncompressed);
+}
+
+#[test]
+fn encoding() {
+    let mut bytes = Vec::new();
+    compact::write_compact(
+        &mut bytes,
+        None,
+        &compound! { "s": -2i16, "i": 300i32, "a": [I; 100, 101, 99] },
+        Flavor::Uncompressed,
+    )
+    .unwrap();
+
+    let body = &bytes[MAGIC.len() + 2 ..];
+    let mut entries = body[.. body.len() - 1].to_vec();
+    let expected: [&[u8]; 3] = [&[0x02, 1, b's', 3], &[0x03, 1, b'i', 0xD8, 0x04], &[
+        0x0B, 1, b'a', 3, 0xC8, 0x01, 2, 3,
+    ]];
+    assert_eq!(
+        entries.len(),
+        expected.iter().map(|e| e.len()).sum::<usize>()
+    );
+    for entry in expected {
+        let position = entries
+            .windows(entry.len())
+            .position(|window| window == entry)
+            .unwrap();
+        entries.drain(position .. position + entry.len());
+    }
+    assert!(entries.is_empty());
+    assert_eq!(body.last(), Some(&0));
+}
+
+#[test]
+fn conversion() {
+    let mut compact = Vec::new();
+    compact::nbt_to_compact(
+        &mut &*BIG_TEST,
+        &mut compact,
+        BIG_TEST_FLAVOR,
+        Flavor::ZlibCompressed,
+    )
+    .unwrap();
+
+    let mut standard = Vec::new();
+    compact::compact_to_nbt(
+        &mut compact.as_slice(),
+        &mut standard,
+        Flavor::ZlibCompressed,
+        Flavor::Uncompressed,
+    )
+    .unwrap();
+    let (nbt, root_name) = io::read_nbt(&mut standard.as_slice(), Flavor::Uncompressed).unwrap();
+    assert_eq!(root_name, "Level");
+    assert_compound_eq!(&nbt, &*BIG_TEST_VALIDATE);
+
+    // Standard NBT is not mistaken for the compact encoding, and vice versa
+    assert!(compact::read_compact(&mut standard.as_slice(), Flavor::Uncompressed).is_err());
+    assert!(io::read_nbt(&mut compact.as_slice(), Flavor::ZlibCompressed).is_err());
+}
+
+#[test]
+fn malformed() {
+    let read = |body: &[u8]| {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        bytes.extend_from_slice(body);
+        compact::read_compact(&mut bytes.as_slice(), Flavor::Uncompressed)
+    };
+    let invalid = |body: &[u8]| match read(body) {
+        Err(NbtIoError::InvalidCompactData(reason)) => reason,
+        result => panic!("expected invalid data, got {:?}", result),
+    };
+
+    assert_eq!(read(&[0, 0]).unwrap(), (NbtCompound::new(), String::new()));
+    assert!(matches!(
+        compact::read_compact(&mut &b"QNBC\x02\x00\x00"[..], Flavor::Uncompressed),
+        Err(NbtIoError::InvalidCompactData("unsupported version"))
+    ));
+    assert!(matches!(
+        compact::read_compact(&mut &b"QNBT\x01\x00\x00"[..], Flavor::Uncompressed),
+        Err(NbtIoError::InvalidCompactData("missing magic bytes"))
+    ));
+
+    assert_eq!(invalid(&[2, 0xFF, 0xFE]), "string is not valid UTF-8");
+    assert_eq!(
+        invalid(&[0, 0x02, 0, 0xFF, 0xFF, 0x07]),
+        "integer is out of range"
+    );
+    assert_eq!(
+        invalid(&[0, 0x04, 0, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x02]),
+        "varint is too long"
+    );
+    assert_eq!(
+        invalid(&[0, 0x04, 0, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0]),
+        "varint is too long"
+    );
+    assert_eq!(
+        read(&[0, 0x04, 0, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x01, 0])
+            .unwrap()
+            .0,
+        compound! { "": i64::MIN }
+    );
+
+    // Huge lengths fail once the data runs out, without allocating for them up front
+    assert!(matches!(
+        read(&[0, 0x07, 0, 0xFF, 0xFF, 0xFF, 0xFF, 0x0F, 1, 2, 3]),
+        Err(NbtIoError::StdIo(_))
+    ));
+    assert!(matches!(
+        read(&[0, 0x09, 0, 0x01, 0xFF, 0xFF, 0xFF, 0xFF, 0x0F, 1]),
+        Err(NbtIoError::StdIo(_))
+    ));
+    assert!(matches!(
+        read(&[0, 0x09, 0, 0x00, 0x01]),
+        Err(NbtIoError::InvalidTagId(0))
+    ));
+    assert!(matches!(
+        read(&[0, 0x0D, 0]),
+        Err(NbtIoError::InvalidTagId(0x0D))
+    ));
+
+    // Lists are checked for homogeneity when written
+    let mut list = NbtList::new();
+    list.push(1i32);
+    list.push(NbtTag::Byte(1));
+    assert!(matches!(
+        compact::write_compact(
+            &mut Vec::new(),
+            None,
+            &compound! { "list": list },
+            Flavor::Uncompressed
+        ),
+        Err(NbtIoError::NonHomogenousList { .. })
+    ));
+}
+
+#[test]
+fn max_depth() {
+    // A root compound holding the given number of lists nested within each other
+    let nested_lists = |depth: usize| {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&[VERSION, 0, 0x09, 0]);
+        bytes.extend((1 .. depth).flat_map(|_| [0x09, 1]));
+        bytes.extend_from_slice(&[0x00, 0x00, 0x00]);
+        bytes
+    };
+
+    // Unoptimized builds use far more stack per level than optimized ones
+    std::thread::Builder::new()
+        .stack_size(64 * 1024 * 1024)
+        .spawn(move || {
+            let read =
+                |bytes: Vec<u8>| compact::read_compact(&mut bytes.as_slice(), Flavor::Uncompressed);
+            assert!(read(nested_lists(io::MAX_DEPTH - 1)).is_ok());
+            assert!(matches!(
+                read(nested_lists(io::MAX_DEPTH)),
+                Err(NbtIoError::ExceededMaxDepth)
+            ));
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+}
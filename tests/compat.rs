@@ -0,0 +1,180 @@
+#![cfg(feature = "serde")]
+
+//! Round trips between this crate and other Rust NBT crates. The representations which differ
+//! between the crates are summarized in `tests/assets/compatibility.md`, which should be kept in
+//! sync with these tests.
+
+use quartz_nbt::{
+    compound,
+    io::{self, Flavor},
+    serde::{deserialize, serialize, SerializerBuilder, UnitVariantEncoding},
+    NbtCompound,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+enum GameMode {
+    Survival,
+    Creative,
+    Adventure,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Item {
+    id: String,
+    count: i8,
+}
+
+// Only uses types which every crate represents in the same way. Since hematite-nbt cannot
+// deserialize enums, the mode type is left generic so that it can read modes as strings.
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Profile<M = GameMode> {
+    name: String,
+    level: i32,
+    xp: f32,
+    hardcore: bool,
+    mode: M,
+    pos: Vec<f64>,
+    inventory: Vec<Item>,
+    spawn: Option<Vec<i32>>,
+    death_location: Option<i64>,
+    modes_unlocked: Vec<M>,
+}
+
+fn profile() -> Profile {
+    profile_with(|mode| mode)
+}
+
+fn profile_with<M>(mode: impl Fn(GameMode) -> M) -> Profile<M> {
+    Profile {
+        name: "Ferris".to_owned(),
+        level: 30,
+        xp: 0.25,
+        hardcore: true,
+        mode: mode(GameMode::Adventure),
+        pos: vec![1.5, 64.0, -3.25],
+        inventory: vec![
+            Item {
+                id: "minecraft:stone".to_owned(),
+                count: 64,
+            },
+            Item {
+                id: "minecraft:torch".to_owned(),
+                count: 12,
+            },
+        ],
+        spawn: Some(vec![0, 70, 0]),
+        death_location: None,
+        modes_unlocked: vec![mode(GameMode::Survival), mode(GameMode::Creative)],
+    }
+}
+
+fn profile_nbt() -> NbtCompound {
+    compound! {
+        "name": "Ferris",
+        "level": 30i32,
+        "xp": 0.25f32,
+        "hardcore": 1i8,
+        "mode": "Adventure",
+        "pos": [1.5f64, 64.0f64, -3.25f64],
+        "inventory": [
+            { "id": "minecraft:stone", "count": 64i8 },
+            { "id": "minecraft:torch", "count": 12i8 }
+        ],
+        "spawn": [0i32, 70i32, 0i32],
+        "modes_unlocked": ["Survival", "Creative"]
+    }
+}
+
+fn read(bytes: &[u8]) -> NbtCompound {
+    io::read_nbt(&mut &*bytes, Flavor::Uncompressed).unwrap().0
+}
+
+fn serialize_by_name<T: Serialize>(value: &T) -> Vec<u8> {
+    SerializerBuilder::new()
+        .unit_variants(UnitVariantEncoding::Name)
+        .serialize(value, None, Flavor::Uncompressed)
+        .unwrap()
+}
+
+#[test]
+fn read_fastnbt() {
+    let bytes = fastnbt::to_bytes(&profile()).unwrap();
+    assert_eq!(read(&bytes), profile_nbt());
+
+    let (deserialized, root_name) = deserialize::<Profile>(&bytes, Flavor::Uncompressed).unwrap();
+    assert_eq!(root_name, "");
+    assert_eq!(deserialized, profile());
+}
+
+#[test]
+fn write_fastnbt() {
+    let bytes = serialize_by_name(&profile());
+    assert_eq!(read(&bytes), profile_nbt());
+    assert_eq!(fastnbt::from_bytes::<Profile>(&bytes).unwrap(), profile());
+
+    // Unit variants are written by index unless configured otherwise, which fastnbt rejects
+    let bytes = serialize(&profile(), None, Flavor::Uncompressed).unwrap();
+    assert!(fastnbt::from_bytes::<Profile>(&bytes).is_err());
+}
+
+#[test]
+fn read_hematite() {
+    let mut bytes = Vec::new();
+    nbt::to_writer(&mut bytes, &profile(), Some("Player")).unwrap();
+    assert_eq!(read(&bytes), profile_nbt());
+
+    let (deserialized, root_name) = deserialize::<Profile>(&bytes, Flavor::Uncompressed).unwrap();
+    assert_eq!(root_name, "Player");
+    assert_eq!(deserialized, profile());
+}
+
+#[test]
+fn write_hematite() {
+    let by_name = profile_with(|mode| format!("{:?}", mode));
+
+    let bytes = serialize_by_name(&profile());
+    assert_eq!(
+        nbt::from_reader::<_, Profile<String>>(bytes.as_slice()).unwrap(),
+        by_name
+    );
+
+    let bytes = serialize(&profile(), None, Flavor::Uncompressed).unwrap();
+    assert!(nbt::from_reader::<_, Profile<String>>(bytes.as_slice()).is_err());
+}
+
+#[test]
+fn unit_variant_encodings() {
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Modes {
+        current: GameMode,
+        previous: Vec<GameMode>,
+    }
+
+    let modes = Modes {
+        current: GameMode::Creative,
+        previous: vec![GameMode::Survival, GameMode::Adventure],
+    };
+
+    let by_index = serialize(&modes, None, Flavor::Uncompressed).unwrap();
+    assert_eq!(
+        read(&by_index),
+        compound! { "current": 1i32, "previous": [0i32, 2i32] }
+    );
+
+    let by_name = serialize_by_name(&modes);
+    assert_eq!(
+        read(&by_name),
+        compound! { "current": "Creative", "previous": ["Survival", "Adventure"] }
+    );
+
+    // Both encodings are accepted when deserializing
+    for bytes in [by_index, by_name] {
+        assert_eq!(
+            deserialize::<Modes>(&bytes, Flavor::Uncompressed)
+                .unwrap()
+                .0,
+            modes
+        );
+    }
+}
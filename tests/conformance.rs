@@ -0,0 +1,62 @@
+#![cfg(feature = "test_vectors")]
+
+use quartz_nbt::{
+    compound,
+    conformance::{self, ErrorKind, Failure, Mode, Outcome, TestVector, VECTORS},
+    snbt,
+};
+
+#[test]
+fn vectors() {
+    let failures = conformance::run(VECTORS);
+    assert!(
+        failures.is_empty(),
+        "{}",
+        failures
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n")
+    );
+}
+
+#[test]
+fn custom_parser() {
+    let vectors = [
+        TestVector::both("{a: 1}", Outcome::Value("{a: 1}")),
+        TestVector::new(
+            "{a: 1,}",
+            Outcome::Error(ErrorKind::TrailingComma),
+            Outcome::Value("{a: 1}"),
+        ),
+    ];
+
+    let mut modes = Vec::new();
+    let failures = conformance::run_with(&vectors, |input, mode| {
+        modes.push(mode);
+        snbt::parse(input)
+    });
+    assert_eq!(modes, [
+        Mode::Lenient,
+        Mode::Strict,
+        Mode::Lenient,
+        Mode::Strict
+    ]);
+    assert_eq!(failures, [Failure {
+        input: "{a: 1,}",
+        mode: Mode::Strict,
+        expected: Outcome::Value("{a: 1}"),
+        actual: Err(ErrorKind::TrailingComma),
+    }]);
+    assert_eq!(
+        failures[0].to_string(),
+        "\"{a: 1,}\" in strict mode: expected {a: 1} but found TrailingComma"
+    );
+}
+
+#[test]
+fn mismatched_value() {
+    let failures = conformance::run(&[TestVector::both("{a: 1b}", Outcome::Value("{a: 1}"))]);
+    assert_eq!(failures.len(), 2);
+    assert_eq!(failures[0].actual, Ok(compound! { "a": 1i8 }));
+}
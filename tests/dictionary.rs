@@ -0,0 +1,159 @@
+#![cfg(feature = "zlib-dictionary")]
+
+mod assets;
+use assets::*;
+use quartz_nbt::{
+    assert_compound_eq,
+    compound,
+    io::{self, Flavor, KeyRules, NbtIoError, NbtReadBuilder, NbtWriteBuilder},
+    NbtCompound,
+};
+use std::io::ErrorKind;
+
+const FLAVORS: [Flavor; 4] = [
+    Flavor::ZlibCompressed,
+    Flavor::ZlibCompressedWith(flate2::Compression::best()),
+    Flavor::RawDeflate,
+    Flavor::RawDeflateWith(flate2::Compression::fast()),
+];
+
+fn write(root: &NbtCompound, flavor: Flavor, dictionary: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    io::write_nbt_with_dictionary(&mut bytes, Some("Level"), root, flavor, dictionary).unwrap();
+    bytes
+}
+
+#[test]
+fn round_trip() {
+    let dictionary = io::write_nbt_bytes(None, &BIG_TEST_VALIDATE, Flavor::Uncompressed).unwrap();
+    for flavor in FLAVORS {
+        let bytes = write(&BIG_TEST_VALIDATE, flavor, &dictionary);
+        let (nbt, root_name) =
+            io::read_nbt_with_dictionary(&mut bytes.as_slice(), flavor, &dictionary).unwrap();
+        assert_eq!(root_name, "Level");
+        assert_compound_eq!(&nbt, &*BIG_TEST_VALIDATE);
+
+        // Data compressed against itself barely takes any space
+        let plain = io::write_nbt_bytes(Some("Level"), &BIG_TEST_VALIDATE, flavor).unwrap();
+        assert!(bytes.len() < plain.len() / 4, "{:?}", flavor);
+    }
+
+    // Other flavors cannot be written with a dictionary, but reading them ignores it
+    for flavor in [Flavor::Uncompressed, Flavor::GzCompressed] {
+        let mut bytes = Vec::new();
+        match io::write_nbt_with_dictionary(
+            &mut bytes,
+            Some("Level"),
+            &BIG_TEST_VALIDATE,
+            flavor,
+            &dictionary,
+        ) {
+            Err(NbtIoError::StdIo(error)) => assert_eq!(error.kind(), ErrorKind::InvalidInput),
+            result => panic!("unexpected result {:?}", result),
+        }
+        assert!(bytes.is_empty());
+
+        let bytes = io::write_nbt_bytes(Some("Level"), &BIG_TEST_VALIDATE, flavor).unwrap();
+        let (nbt, _) =
+            io::read_nbt_with_dictionary(&mut bytes.as_slice(), flavor, &dictionary).unwrap();
+        assert_compound_eq!(&nbt, &*BIG_TEST_VALIDATE);
+    }
+
+    // Zlib data without a dictionary is read as usual
+    let bytes =
+        io::write_nbt_bytes(Some("Level"), &BIG_TEST_VALIDATE, Flavor::ZlibCompressed).unwrap();
+    let (nbt, _) =
+        io::read_nbt_with_dictionary(&mut bytes.as_slice(), Flavor::ZlibCompressed, &dictionary)
+            .unwrap();
+    assert_compound_eq!(&nbt, &*BIG_TEST_VALIDATE);
+}
+
+#[test]
+fn mismatched_dictionaries() {
+    let nbt = compound! { "name": "Steve", "xp": 30i32, "pos": [1.0f64, 64.0f64, -3.5f64] };
+    let dictionary = io::write_nbt_bytes(None, &nbt, Flavor::Uncompressed).unwrap();
+
+    for flavor in FLAVORS {
+        let bytes = write(&nbt, flavor, &dictionary);
+        assert!(io::read_nbt(&mut bytes.as_slice(), flavor).is_err());
+        assert!(io::read_nbt_with_dictionary(&mut bytes.as_slice(), flavor, b"other").is_err());
+    }
+
+    // Zlib streams record which dictionary they were written with
+    let bytes = write(&nbt, Flavor::ZlibCompressed, &dictionary);
+    match io::read_nbt_with_dictionary(&mut bytes.as_slice(), Flavor::ZlibCompressed, b"other") {
+        Err(NbtIoError::StdIo(error)) => assert_eq!(error.kind(), ErrorKind::InvalidData),
+        result => panic!("unexpected result {:?}", result),
+    }
+}
+
+#[test]
+fn truncated_data() {
+    let dictionary = b"name";
+    for flavor in FLAVORS {
+        let bytes = write(&BIG_TEST_VALIDATE, flavor, dictionary);
+        for len in [0, 1, 2, bytes.len() / 2, bytes.len() - 8] {
+            assert!(
+                io::read_nbt_with_dictionary(&mut &bytes[.. len], flavor, dictionary).is_err(),
+                "{:?} truncated to {}",
+                flavor,
+                len
+            );
+        }
+    }
+}
+
+#[test]
+fn builder_options() {
+    let nbt = compound! { "Name": "Steve", "Pos": [{ "X": 1i32 }] };
+    let dictionary = io::write_nbt_bytes(None, &nbt, Flavor::Uncompressed).unwrap();
+    let lowercase = |key: &str| Some(key.to_lowercase());
+
+    for flavor in FLAVORS {
+        let bytes = write(&nbt, flavor, &dictionary);
+        let reader = NbtReadBuilder::new()
+            .flavor(flavor)
+            .dictionary(&dictionary)
+            .key_transform(&lowercase);
+        let (read, root_name, report) =
+            reader.read_from_with_report(&mut bytes.as_slice()).unwrap();
+        assert_eq!(root_name, "Level");
+        assert_eq!(read, compound! { "name": "Steve", "pos": [{ "x": 1i32 }] });
+        assert_eq!(report.renamed_keys().len(), 3);
+
+        assert!(matches!(
+            reader.max_depth(2).read_from(&mut bytes.as_slice()),
+            Err(NbtIoError::ExceededMaxDepth)
+        ));
+    }
+
+    // Zlib data with a dictionary is still detected
+    let bytes = write(&nbt, Flavor::ZlibCompressed, &dictionary);
+    let reader = NbtReadBuilder::new().dictionary(&dictionary);
+    assert_eq!(reader.read_bytes(&bytes).unwrap().0, nbt);
+}
+
+#[test]
+fn write_builder() {
+    let nbt = compound! { "": "Steve", "xp": 30i32 };
+    let dictionary = io::write_nbt_bytes(None, &nbt, Flavor::Uncompressed).unwrap();
+
+    for flavor in FLAVORS {
+        let writer = NbtWriteBuilder::new()
+            .flavor(flavor)
+            .root_name("Level")
+            .dictionary(&dictionary);
+        let bytes = writer.write_to_bytes(&nbt).unwrap();
+        assert_eq!(bytes, write(&nbt, flavor, &dictionary));
+
+        let bytes = writer.auto_compression(true).write_to_bytes(&nbt).unwrap();
+        let (read, root_name) =
+            io::read_nbt_with_dictionary(&mut bytes.as_slice(), flavor, &dictionary).unwrap();
+        assert_eq!((read, root_name.as_str()), (nbt.clone(), "Level"));
+
+        assert!(matches!(
+            writer.key_rules(KeyRules::strict()).write_to_bytes(&nbt),
+            Err(NbtIoError::SuspiciousKey { .. })
+        ));
+    }
+}
@@ -0,0 +1,122 @@
+use quartz_nbt::{
+    compound,
+    envelope::{self, EnvelopeError, EnvelopeHeader},
+    io::Flavor,
+    snbt,
+    NbtCompound,
+};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[test]
+fn wrap_and_unwrap() {
+    let header = EnvelopeHeader::new("test:inventory", 2)
+        .with_created_at(1719850123456)
+        .with_tool("inventory-tool 0.3.0");
+    let payload = compound! { "Items": [{ "id": "minecraft:stone", "Count": 64i8 }] };
+
+    let document = header.wrap(payload.clone());
+    assert_eq!(document, compound! {
+        "Envelope": {
+            "Schema": "test:inventory",
+            "Version": 2i32,
+            "CreatedAt": 1719850123456i64,
+            "Tool": "inventory-tool 0.3.0"
+        },
+        "Data": {
+            "Items": [{ "id": "minecraft:stone", "Count": 64i8 }]
+        }
+    });
+    assert_eq!(
+        envelope::unwrap(document.clone()).unwrap(),
+        (header.clone(), payload.clone())
+    );
+    assert_eq!(
+        envelope::unwrap_checked(document, "test:inventory", 2 ..).unwrap(),
+        (header, payload)
+    );
+
+    // New headers are stamped with the current time
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64;
+    let header = EnvelopeHeader::new("test:inventory", 1);
+    assert!((header.created_at() - now).abs() < 60_000);
+    assert_eq!(header.tool(), "");
+}
+
+#[test]
+fn lenient_headers() {
+    // Unknown header tags are ignored, and the tool is optional
+    let document = snbt::parse(
+        "{Envelope: {Schema: 'test:a', Version: 1, CreatedAt: 0L, Host: 'build-7'}, Data: {}}",
+    )
+    .unwrap();
+    let (header, payload) = envelope::unwrap(document).unwrap();
+    assert_eq!(header.schema(), "test:a");
+    assert_eq!(header.version(), 1);
+    assert_eq!(header.created_at(), 0);
+    assert_eq!(header.tool(), "");
+    assert_eq!(payload, NbtCompound::new());
+}
+
+#[test]
+fn invalid_envelopes() {
+    for snbt in [
+        "{}",
+        "{Data: {}}",
+        "{Envelope: {Schema: 'test:a', Version: 1, CreatedAt: 0L}}",
+        "{Envelope: {Schema: 'test:a', Version: 1, CreatedAt: 0L}, Data: []}",
+        "{Envelope: {Schema: 'test:a', Version: 1b, CreatedAt: 0L}, Data: {}}",
+        "{Envelope: {Schema: 'test:a', Version: 1, CreatedAt: 0L, Tool: 5}, Data: {}}",
+        "{Envelope: {Version: 1, CreatedAt: 0L}, Data: {}}",
+    ] {
+        let document = snbt::parse(snbt).unwrap();
+        assert!(
+            matches!(envelope::unwrap(document), Err(EnvelopeError::Malformed(_))),
+            "{}",
+            snbt
+        );
+    }
+
+    let document = EnvelopeHeader::new("test:a", 5).wrap(NbtCompound::new());
+    for (schema, versions, ok) in [
+        ("test:a", 1 ..= 5, true),
+        ("test:a", 1 ..= 4, false),
+        ("test:a", 6 ..= 9, false),
+        ("test:b", 1 ..= 5, false),
+    ] {
+        let result = envelope::unwrap_checked(document.clone(), schema, versions);
+        assert_eq!(result.is_ok(), ok, "{} {:?}", schema, result);
+    }
+    match envelope::unwrap_checked(document, "test:b", ..) {
+        Err(EnvelopeError::SchemaMismatch { expected, found }) => {
+            assert_eq!(expected, "test:b");
+            assert_eq!(found, "test:a");
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+}
+
+#[test]
+fn read_and_write() {
+    let header = EnvelopeHeader::new("test:a", 3).with_tool("tests");
+    let payload = compound! { "xp": 30i32 };
+
+    for flavor in [Flavor::Uncompressed, Flavor::GzCompressed] {
+        let mut bytes = Vec::new();
+        envelope::write_enveloped(&mut bytes, &header, payload.clone(), flavor).unwrap();
+        assert_eq!(
+            envelope::read_enveloped(&mut bytes.as_slice(), flavor, "test:a", 3 ..= 3).unwrap(),
+            (header.clone(), payload.clone())
+        );
+        assert!(matches!(
+            envelope::read_enveloped(&mut bytes.as_slice(), flavor, "test:a", .. 3),
+            Err(EnvelopeError::UnsupportedVersion(3))
+        ));
+        assert!(matches!(
+            envelope::read_enveloped(&mut &bytes[.. 4], flavor, "test:a", ..),
+            Err(EnvelopeError::Io(_))
+        ));
+    }
+}
@@ -0,0 +1,67 @@
+use quartz_nbt::{
+    compound,
+    export::{self, CsvOptions},
+    path::NbtPath,
+};
+
+fn export(compounds: &[quartz_nbt::NbtCompound], options: CsvOptions) -> String {
+    let mut output = Vec::new();
+    export::to_csv(&mut output, &compounds.iter().collect::<Vec<_>>(), options).unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn csv_fields() {
+    let rows = [
+        compound! {
+            "quoted": "say \"hi\"",
+            "lines": "a\nb",
+            "ids": [I; 1, 2],
+            "items": [{ "id": "stone" }, { "id": "dirt" }],
+            "long": -9000000000i64,
+            "byte": 1i8
+        },
+        compound! { "byte": -1i8, "semi": "a;b" },
+    ];
+
+    let columns = [
+        "quoted",
+        "lines",
+        "ids",
+        "items[].id",
+        "long",
+        "byte",
+        "semi",
+        "missing",
+    ]
+    .map(|path| NbtPath::parse(path).unwrap());
+    assert_eq!(
+        export(&rows, CsvOptions::new().columns(columns.clone())),
+        "quoted,lines,ids,items[].id,long,byte,semi,missing\n\"say \
+         \"\"hi\"\"\",\"a\nb\",\"[I;1,2]\",\"[stone,dirt]\",-9000000000,1,,\n,,,,,-1,a;b,\n"
+    );
+    assert_eq!(
+        export(
+            &rows[1 ..],
+            CsvOptions::new()
+                .delimiter(';')
+                .header(false)
+                .columns(columns)
+        ),
+        ";;;;;-1;\"a;b\";\n"
+    );
+}
+
+#[test]
+fn csv_default_columns() {
+    let rows = [
+        compound! { "b": { "x": 1i32 }, "a": [] },
+        compound! { "c": 2.5f64, "b": { "y": "z" } },
+    ];
+    assert_eq!(
+        export(&rows, CsvOptions::tsv()),
+        "b.x\tb.y\tc\n1\t\t\n\tz\t2.5\n"
+    );
+
+    assert_eq!(export(&[], CsvOptions::new().header(false)), "");
+}
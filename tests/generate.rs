@@ -0,0 +1,78 @@
+#![cfg(feature = "rand")]
+
+mod assets;
+use assets::*;
+use quartz_nbt::{
+    compound,
+    io::{self, Flavor},
+    testing::{self, Schema, TagSchema},
+    NbtTag,
+    NbtTagKind,
+};
+use rand::{rngs::StdRng, SeedableRng};
+
+#[test]
+fn inferred_schemas() {
+    let schema = Schema::infer(&BIG_TEST_VALIDATE);
+    assert!(schema.is_valid(&BIG_TEST_VALIDATE));
+
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0 .. 50 {
+        let generated = testing::generate(&schema, &mut rng);
+        assert!(schema.is_valid(&generated));
+        assert_eq!(generated.len(), BIG_TEST_VALIDATE.len());
+
+        // Generated compounds can always be written and read back
+        let bytes = io::write_nbt_bytes(None, &generated, Flavor::Uncompressed).unwrap();
+        let (read, _) = io::read_nbt(&mut bytes.as_slice(), Flavor::Uncompressed).unwrap();
+        assert_eq!(read, generated);
+    }
+
+    // Seeded generators are deterministic
+    assert_eq!(
+        testing::generate(&schema, &mut StdRng::seed_from_u64(1)),
+        testing::generate(&schema, &mut StdRng::seed_from_u64(1))
+    );
+}
+
+#[test]
+fn handwritten_schemas() {
+    let schema = Schema::new()
+        .required("id", TagSchema::one_of(["a", "b"]))
+        .optional("count", TagSchema::Kind(NbtTagKind::Byte))
+        .required(
+            "nested",
+            TagSchema::list(TagSchema::Kind(NbtTagKind::LongArray), 3),
+        )
+        .required("empty", TagSchema::OneOf(Vec::new()))
+        .required("id", TagSchema::one_of(["c"]));
+
+    let mut rng = StdRng::seed_from_u64(2);
+    let mut with_count = 0;
+    for _ in 0 .. 200 {
+        let generated = testing::generate(&schema, &mut rng);
+        assert!(schema.is_valid(&generated));
+        assert_eq!(generated.get::<_, &str>("id").unwrap(), "c");
+        assert!(generated
+            .get::<_, &NbtTag>("nested")
+            .unwrap()
+            .get(3)
+            .is_none());
+        with_count += generated.contains_key("count") as usize;
+    }
+    assert!((50 .. 150).contains(&with_count));
+
+    assert!(!schema.is_valid(&compound! {}));
+    assert!(!schema.is_valid(&compound! { "id": "a", "nested": [], "empty": {} }));
+    assert!(!schema.is_valid(&compound! { "id": "c", "nested": [], "empty": {}, "other": 1i8 }));
+    assert!(schema.is_valid(&compound! { "id": "c", "nested": [], "empty": {} }));
+
+    // Schemas which contain themselves still produce finite compounds
+    let mut recursive = Schema::new();
+    for _ in 0 .. 100 {
+        recursive = Schema::new().required("child", TagSchema::Compound(recursive));
+    }
+    let generated = testing::generate(&recursive, &mut rng);
+    let bytes = io::write_nbt_bytes(None, &generated, Flavor::Uncompressed).unwrap();
+    assert!(io::read_nbt(&mut bytes.as_slice(), Flavor::Uncompressed).is_ok());
+}
@@ -0,0 +1,179 @@
+#![cfg(any(
+    feature = "serde_yaml",
+    feature = "toml",
+    feature = "hematite-nbt",
+    feature = "fastnbt"
+))]
+
+#[cfg(any(feature = "hematite-nbt", feature = "fastnbt"))]
+mod assets;
+#[cfg(any(feature = "hematite-nbt", feature = "fastnbt"))]
+use assets::*;
+#[cfg(any(feature = "serde_yaml", feature = "toml"))]
+use quartz_nbt::interop;
+use quartz_nbt::{compound, NbtTag};
+#[cfg(any(feature = "hematite-nbt", feature = "fastnbt"))]
+use quartz_nbt::{
+    io::{self, Flavor},
+    NbtList,
+};
+#[cfg(any(feature = "hematite-nbt", feature = "fastnbt"))]
+use std::convert::TryFrom;
+
+#[cfg(feature = "serde_yaml")]
+#[test]
+fn yaml_conversions() {
+    let yaml = |input: &str| interop::yaml_to_nbt(&serde_yaml::from_str(input).unwrap());
+
+    assert_eq!(
+        yaml("{ a: 1, b: 3000000000, c: [1, 2.5], d: true, e: ~, f: [1, ~], 7: x }").unwrap(),
+        NbtTag::Compound(compound! {
+            "a": 1i32,
+            "b": 3000000000i64,
+            "c": [1.0f64, 2.5f64],
+            "d": 1i8,
+            "f": [1i32],
+            "7": "x"
+        })
+    );
+    assert_eq!(
+        yaml("{ b: !B [1, -2], i: !I [], l: !L [3000000000], other: !Custom 5 }").unwrap(),
+        NbtTag::Compound(compound! {
+            "b": [B; 1, -2],
+            "i": [I;],
+            "l": [L; 3000000000],
+            "other": 5i32
+        })
+    );
+
+    assert!(yaml("~").is_err());
+    assert!(yaml("18446744073709551615").is_err());
+    assert!(yaml("[1, a]").is_err());
+    assert!(yaml("{ [1]: a }").is_err());
+    assert!(yaml("!B [128]").is_err());
+    assert!(yaml("!I [a]").is_err());
+    assert!(yaml("!L 1").is_err());
+
+    let tag = NbtTag::Compound(compound! {
+        "bytes": [B; -1, 2],
+        "ints": [I; 1],
+        "longs": [L; -5],
+        "nested": [{ "x": 1.5f64 }, {}],
+        "long": -3000000000i64
+    });
+    let round_trip =
+        serde_yaml::from_str(&serde_yaml::to_string(&interop::nbt_to_yaml(&tag)).unwrap()).unwrap();
+    assert_eq!(interop::yaml_to_nbt(&round_trip).unwrap(), tag);
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn toml_conversions() {
+    let toml = |input: &str| interop::toml_to_nbt(&toml::from_str(input).unwrap());
+
+    assert_eq!(
+        toml(
+            "a = 1\nb = 3000000000\nc = [1, 2.5]\nd = false\ne = 1979-05-27T07:32:00Z\n[f]\ng = []"
+        )
+        .unwrap(),
+        NbtTag::Compound(compound! {
+            "a": 1i32,
+            "b": 3000000000i64,
+            "c": [1.0f64, 2.5f64],
+            "d": 0i8,
+            "e": "1979-05-27T07:32:00Z",
+            "f": { "g": [] }
+        })
+    );
+    assert!(toml("a = [1, 'b']").is_err());
+
+    // Typed arrays become lists of integers
+    let tag = NbtTag::Compound(compound! { "a": [B; 1, 2], "b": [L; 3] });
+    let round_trip = interop::toml_to_nbt(&interop::nbt_to_toml(&tag)).unwrap();
+    assert_eq!(
+        round_trip,
+        NbtTag::Compound(compound! { "a": [1i32, 2i32], "b": [3i32] })
+    );
+}
+
+#[cfg(feature = "hematite-nbt")]
+#[test]
+fn hematite_conversions() {
+    let big_test = NbtTag::Compound(BIG_TEST_VALIDATE.clone());
+    let value = nbt::Value::from(big_test.clone());
+    assert_eq!(NbtTag::try_from(value.clone()).unwrap(), big_test);
+
+    // Check that hematite-nbt writes the converted value as the same document
+    let mut blob = nbt::Blob::new();
+    match value {
+        nbt::Value::Compound(map) =>
+            for (key, value) in map {
+                blob.insert(key, value).unwrap();
+            },
+        _ => panic!("Expected a compound"),
+    }
+    let mut bytes = Vec::new();
+    blob.to_writer(&mut bytes).unwrap();
+    let (read, _) = io::read_nbt(&mut bytes.as_slice(), Flavor::Uncompressed).unwrap();
+    assert_eq!(read, *BIG_TEST_VALIDATE);
+
+    let mixed = nbt::Value::List(vec![
+        nbt::Value::String("a".to_owned()),
+        nbt::Value::Byte(1),
+    ]);
+    assert!(NbtTag::try_from(mixed.clone()).is_err());
+    let mut map = nbt::Map::new();
+    map.insert("nested".to_owned(), nbt::Value::List(vec![mixed]));
+    assert!(NbtTag::try_from(nbt::Value::Compound(map)).is_err());
+
+    let tag = NbtTag::Compound(compound! {
+        "bytes": [B; -1, 2],
+        "ints": [I; 1],
+        "longs": [L; -5],
+        "empty": []
+    });
+    assert_eq!(
+        NbtTag::try_from(nbt::Value::from(tag.clone())).unwrap(),
+        tag
+    );
+
+    assert_eq!(
+        NbtTag::try_from(nbt::Value::List(Vec::new())).unwrap(),
+        NbtTag::List(NbtList::new())
+    );
+}
+
+#[cfg(feature = "fastnbt")]
+#[test]
+fn fastnbt_conversions() {
+    let big_test = NbtTag::Compound(BIG_TEST_VALIDATE.clone());
+    let value = fastnbt::Value::from(big_test.clone());
+    assert_eq!(NbtTag::try_from(value.clone()).unwrap(), big_test);
+
+    // Check that the converted value matches the one fastnbt reads from the same document
+    let mut bytes = Vec::new();
+    io::write_nbt(&mut bytes, None, &BIG_TEST_VALIDATE, Flavor::Uncompressed).unwrap();
+    assert_eq!(
+        fastnbt::from_bytes::<fastnbt::Value>(&bytes).unwrap(),
+        value
+    );
+
+    let tag = NbtTag::Compound(compound! {
+        "bytes": [B; -1, 2],
+        "ints": [I; 1],
+        "longs": [L; -5],
+        "empty": []
+    });
+    assert_eq!(
+        NbtTag::try_from(fastnbt::Value::from(tag.clone())).unwrap(),
+        tag
+    );
+
+    let mixed = fastnbt::Value::List(vec![fastnbt::Value::Int(1), fastnbt::Value::Long(1)]);
+    assert!(NbtTag::try_from(mixed).is_err());
+
+    assert_eq!(
+        NbtTag::try_from(fastnbt::Value::List(Vec::new())).unwrap(),
+        NbtTag::List(NbtList::new())
+    );
+}
@@ -1,11 +1,31 @@
 mod assets;
 use assets::*;
 use quartz_nbt::{
+    assert_compound_eq,
     compound,
-    io::{self, read_nbt, write_nbt, Flavor},
+    io::{
+        self,
+        read_nbt,
+        write_nbt,
+        CachedDocument,
+        DuplicateKeyPolicy,
+        Flavor,
+        KeyIssue,
+        KeyRules,
+        LengthPrefix,
+        NbtIoError,
+        NbtReadBuilder,
+        NbtWriteBuilder,
+        RenamedKey,
+        UnknownListType,
+    },
+    sanitize::SanitizeRules,
+    NbtCompound,
     NbtList,
+    NbtTag,
+    NbtTagKind,
 };
-use std::io::Cursor;
+use std::io::{Cursor, Read, Write};
 
 #[test]
 fn big_test() {
@@ -15,6 +35,580 @@ fn big_test() {
     assert_compound_eq!(&nbt, &*BIG_TEST_VALIDATE);
 }
 
+#[test]
+fn big_test_info() {
+    let (nbt, info) = io::read_nbt_with_info(&mut &*BIG_TEST).unwrap();
+    assert_compound_eq!(&nbt, &*BIG_TEST_VALIDATE);
+    assert_eq!(info.root_name(), "Level");
+    assert_eq!(info.flavor(), Flavor::GzCompressed);
+    assert_eq!(info.compressed_len(), BIG_TEST.len());
+
+    let mut uncompressed = Vec::new();
+    write_nbt(&mut uncompressed, Some("Level"), &nbt, Flavor::Uncompressed).unwrap();
+    assert_eq!(info.uncompressed_len(), uncompressed.len());
+
+    for flavor in [Flavor::Uncompressed, Flavor::ZlibCompressed] {
+        let mut bytes = Vec::new();
+        write_nbt(&mut bytes, Some("Level"), &nbt, flavor).unwrap();
+        let document_len = bytes.len();
+        bytes.extend_from_slice(b"trailing data");
+
+        let mut reader = bytes.as_slice();
+        let (_, info) = io::read_nbt_with_info(&mut reader).unwrap();
+        assert_eq!(info.flavor(), flavor);
+        assert_eq!(info.compressed_len(), document_len);
+        assert_eq!(info.uncompressed_len(), uncompressed.len());
+    }
+}
+
+#[test]
+fn peek_big_test() {
+    let info = io::peek_header(&mut &*BIG_TEST, BIG_TEST_FLAVOR).unwrap();
+    assert_eq!(info.root_name(), "Level");
+
+    // Every top-level entry of the full document is listed with its kind
+    let mut peeked = info
+        .entries()
+        .map(|(name, kind)| (name.to_owned(), kind))
+        .collect::<Vec<_>>();
+    let mut read = BIG_TEST_VALIDATE
+        .inner()
+        .iter()
+        .map(|(name, tag)| (name.clone(), tag.kind()))
+        .collect::<Vec<_>>();
+    peeked.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+    read.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(peeked, read);
+    assert_eq!(info.len(), BIG_TEST_VALIDATE.len());
+    assert_eq!(info.get("listTest (compound)"), Some(NbtTagKind::List));
+
+    let mut bytes = Vec::new();
+    write_nbt(
+        &mut bytes,
+        None,
+        &NbtCompound::new(),
+        Flavor::ZlibCompressed,
+    )
+    .unwrap();
+    let info = io::peek_header(&mut bytes.as_slice(), Flavor::ZlibCompressed).unwrap();
+    assert!(info.is_empty());
+
+    // Truncated values are still detected while skipping over them
+    let mut bytes = Vec::new();
+    write_nbt(&mut bytes, None, &BIG_TEST_VALIDATE, Flavor::Uncompressed).unwrap();
+    bytes.truncate(bytes.len() - 2);
+    assert!(io::peek_header(&mut bytes.as_slice(), Flavor::Uncompressed).is_err());
+}
+
+#[test]
+fn big_test_spans() {
+    let (nbt, root_name, spans) =
+        io::read_nbt_with_spans(&mut Cursor::new(BIG_TEST), BIG_TEST_FLAVOR).unwrap();
+
+    assert_eq!(root_name, "Level");
+    assert_compound_eq!(&nbt, &*BIG_TEST_VALIDATE);
+
+    // Every tag in the document is covered by its parent's span
+    let root = spans.get("").unwrap();
+    for span in &spans {
+        assert!(span.range().start >= root.start && span.range().end <= root.end);
+    }
+
+    let egg = spans.get(r#""nested compound test".egg"#).unwrap();
+    let name = spans.get(r#""nested compound test".egg.name"#).unwrap();
+    assert!(egg.start < name.start && name.end <= egg.end);
+    assert_eq!(
+        spans.innermost_at(name.start).unwrap().path(),
+        r#""nested compound test".egg.name"#
+    );
+}
+
+#[test]
+fn big_test_partial() {
+    let mut bytes = Vec::new();
+    write_nbt(
+        &mut bytes,
+        Some("Level"),
+        &BIG_TEST_VALIDATE,
+        Flavor::Uncompressed,
+    )
+    .unwrap();
+
+    let (nbt, root_name, error) = io::read_nbt_partial(&mut bytes.as_slice(), Flavor::Uncompressed);
+    assert_eq!(root_name, "Level");
+    assert!(error.is_none());
+    assert_compound_eq!(&nbt, &*BIG_TEST_VALIDATE);
+
+    // Every truncation should be reported, with everything read before it kept intact
+    for len in 0 .. bytes.len() {
+        let (nbt, _, error) = io::read_nbt_partial(&mut &bytes[.. len], Flavor::Uncompressed);
+        let error = error.unwrap();
+        assert!(error.offset() <= len);
+        for (key, value) in nbt.inner() {
+            if !matches!(value, NbtTag::List(_) | NbtTag::Compound(_)) {
+                assert_eq!(value, &BIG_TEST_VALIDATE[key.as_str()]);
+            }
+        }
+    }
+}
+
+#[test]
+fn sanitize_before_writing() {
+    let mut nbt = compound! {
+        "long": "x".repeat(70_000),
+        "mixed": NbtList::from(vec![NbtTag::Int(1), NbtTag::String("two".to_owned()), NbtTag::Int(3)])
+    };
+    nbt.insert("x".repeat(70_000), 0i8);
+
+    let mut bytes = Vec::new();
+    assert!(write_nbt(&mut bytes, None, &nbt, Flavor::Uncompressed).is_err());
+
+    let report = nbt.sanitize(&SanitizeRules::new());
+    assert_eq!(report.len(), 3);
+
+    bytes.clear();
+    write_nbt(&mut bytes, None, &nbt, Flavor::Uncompressed).unwrap();
+    let (read, _) = read_nbt(&mut bytes.as_slice(), Flavor::Uncompressed).unwrap();
+    assert_compound_eq!(&read, &nbt);
+    assert_eq!(
+        read.get::<_, &str>("long").unwrap().len(),
+        u16::MAX as usize
+    );
+    assert_eq!(read.get::<_, &NbtList>("mixed").unwrap().len(), 2);
+}
+
+#[test]
+fn framed() {
+    for prefix in [LengthPrefix::VarInt, LengthPrefix::U32] {
+        let mut stream = Vec::new();
+        for flavor in [Flavor::Uncompressed, Flavor::GzCompressed] {
+            io::write_nbt_framed(
+                &mut stream,
+                prefix,
+                Some("Level"),
+                &BIG_TEST_VALIDATE,
+                flavor,
+            )
+            .unwrap();
+        }
+
+        let mut stream = Cursor::new(stream);
+        for flavor in [Flavor::Uncompressed, Flavor::GzCompressed] {
+            let (nbt, root_name) = io::read_nbt_framed(&mut stream, prefix, 4096, flavor).unwrap();
+            assert_eq!(root_name, "Level");
+            assert_compound_eq!(&nbt, &*BIG_TEST_VALIDATE);
+        }
+        assert_eq!(stream.position() as usize, stream.get_ref().len());
+    }
+
+    // An empty compound followed by a stray byte inside the same frame
+    let mut stream = Cursor::new(vec![5, 10, 0, 0, 0, 0]);
+    assert!(matches!(
+        io::read_nbt_framed(&mut stream, LengthPrefix::VarInt, 16, Flavor::Uncompressed),
+        Err(NbtIoError::TrailingFrameBytes(1))
+    ));
+
+    let mut stream = Cursor::new(vec![0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x01]);
+    assert!(matches!(
+        io::read_nbt_framed(&mut stream, LengthPrefix::VarInt, 16, Flavor::Uncompressed),
+        Err(NbtIoError::InvalidFrameLength)
+    ));
+
+    let mut stream = Cursor::new(vec![0x80, 0, 0, 0]);
+    assert!(matches!(
+        io::read_nbt_framed(&mut stream, LengthPrefix::U32, 16, Flavor::Uncompressed),
+        Err(NbtIoError::FrameTooLarge { .. })
+    ));
+}
+
+#[test]
+fn tag_extension() {
+    #[rustfmt::skip]
+    let bytes = [
+        0x0A, 0, 0,
+        // A list of two custom tags with a one byte payload
+        0x09, 0, 1, b'l', 99, 0, 0, 0, 2, 1, 2,
+        // A compound containing a custom tag
+        0x0A, 0, 1, b'c', 99, 0, 1, b'x', 5, 0,
+        // A custom tag without a payload, which is dropped
+        100, 0, 1, b's',
+        0x03, 0, 5, b'a', b'f', b't', b'e', b'r', 0, 0, 0, 7,
+        0,
+    ];
+
+    let mut extension = |tag_id: u8, reader: &mut dyn Read| match tag_id {
+        99 => {
+            let mut payload = [0u8; 1];
+            reader.read_exact(&mut payload)?;
+            Ok(Some(NbtTag::Byte(payload[0] as i8)))
+        }
+        100 => Ok(None),
+        _ => Err(NbtIoError::InvalidTagId(tag_id)),
+    };
+
+    let (nbt, root_name) =
+        io::read_nbt_with_extension(&mut &bytes[..], Flavor::Uncompressed, &mut extension).unwrap();
+    assert_eq!(root_name, "");
+    assert_eq!(
+        nbt,
+        compound! { "l": [1i8, 2i8], "c": { "x": 5i8 }, "after": 7i32 }
+    );
+
+    assert!(matches!(
+        io::read_nbt(&mut &bytes[..], Flavor::Uncompressed),
+        Err(NbtIoError::InvalidTagId(99))
+    ));
+
+    let bytes = [0x0A, 0, 0, 101, 0, 0, 0];
+    assert!(matches!(
+        io::read_nbt_with_extension(&mut &bytes[..], Flavor::Uncompressed, &mut extension),
+        Err(NbtIoError::InvalidTagId(101))
+    ));
+}
+
+#[test]
+fn latin1_fallback() {
+    let (nbt, root_name, fallbacks) =
+        io::read_nbt_with_latin1_fallback(&mut Cursor::new(BIG_TEST), BIG_TEST_FLAVOR).unwrap();
+    assert_eq!(root_name, "Level");
+    assert_compound_eq!(&nbt, &*BIG_TEST_VALIDATE);
+    assert!(fallbacks.is_empty());
+
+    #[rustfmt::skip]
+    let bytes = [
+        0x0A, 0, 1, 0xC0,
+        // A list of strings, the second of which is Latin-1
+        0x09, 0, 1, b'l', 0x08, 0, 0, 0, 2, 0, 1, b'a', 0, 1, 0xFF,
+        // A compound containing a byte with a Latin-1 name
+        0x0A, 0, 1, b'c', 0x01, 0, 2, b'n', 0xF6, 1, 0,
+        0,
+    ];
+    let (nbt, root_name, fallbacks) =
+        io::read_nbt_with_latin1_fallback(&mut &bytes[..], Flavor::Uncompressed).unwrap();
+    assert_eq!(root_name, "À");
+    assert_eq!(nbt, compound! { "l": ["a", "ÿ"], "c": { "nö": 1i8 } });
+    assert_eq!(fallbacks, ["", "l[1]", "c.\"nö\""]);
+}
+
+#[test]
+fn unknown_list_types() {
+    for policy in [
+        UnknownListType::Error,
+        UnknownListType::SkipRest,
+        UnknownListType::Capture,
+    ] {
+        let (nbt, root_name, stopped) = io::read_nbt_with_unknown_list_types(
+            &mut Cursor::new(BIG_TEST),
+            BIG_TEST_FLAVOR,
+            policy,
+        )
+        .unwrap();
+        assert_eq!(root_name, "Level");
+        assert_compound_eq!(&nbt, &*BIG_TEST_VALIDATE);
+        assert!(stopped.is_none());
+    }
+
+    #[rustfmt::skip]
+    let bytes = [
+        0x0A, 0, 0,
+        0x03, 0, 1, b'x', 0, 0, 0, 7,
+        // A list of two compounds, the second of which holds a list of unknown type
+        0x09, 0, 5, b'i', b't', b'e', b'm', b's', 0x0A, 0, 0, 0, 2,
+        0x08, 0, 2, b'i', b'd', 0, 1, b'a', 0,
+        0x08, 0, 2, b'i', b'd', 0, 1, b'b', 0x09, 0, 3, b't', b'a', b'g', 0x0D, 0, 0, 0, 1, 0, 0,
+        0x03, 0, 1, b'y', 0, 0, 0, 8,
+        0,
+    ];
+
+    let error = io::read_nbt_with_unknown_list_types(
+        &mut &bytes[..],
+        Flavor::Uncompressed,
+        UnknownListType::Error,
+    )
+    .unwrap_err();
+    assert!(matches!(error, NbtIoError::InvalidTagId(0x0D)));
+
+    let (nbt, _, stopped) = io::read_nbt_with_unknown_list_types(
+        &mut &bytes[..],
+        Flavor::Uncompressed,
+        UnknownListType::SkipRest,
+    )
+    .unwrap();
+    assert_eq!(
+        nbt,
+        compound! { "x": 7i32, "items": [{ "id": "a" }, { "id": "b" }] }
+    );
+    let stopped = stopped.unwrap();
+    assert_eq!(stopped.path(), "items[1].tag");
+    let list_end = bytes.iter().position(|&byte| byte == 0x0D).unwrap() + 5;
+    assert_eq!(stopped.offset(), list_end);
+    assert!(matches!(stopped.error(), NbtIoError::InvalidTagId(0x0D)));
+
+    // Compressed documents capture the decompressed bytes
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    std::io::Write::write_all(&mut encoder, &bytes).unwrap();
+    let compressed = encoder.finish().unwrap();
+    match io::read_nbt_with_unknown_list_types(
+        &mut compressed.as_slice(),
+        Flavor::GzCompressed,
+        UnknownListType::Capture,
+    ) {
+        Err(NbtIoError::UnknownListType {
+            tag_id,
+            path,
+            remaining,
+        }) => {
+            assert_eq!((tag_id, path.as_str()), (0x0D, "items[1].tag"));
+            assert_eq!(remaining, &bytes[list_end ..]);
+        }
+        result => panic!("{:?}", result.map(|(nbt, ..)| nbt)),
+    }
+
+    // The policy composes with the other options of the builder
+    let uppercase = |key: &str| Some(key.to_uppercase());
+    let reader = NbtReadBuilder::new()
+        .unknown_list_types(UnknownListType::SkipRest)
+        .key_transform(&uppercase);
+    let (nbt, _, report) = reader.read_from_with_report(&mut &bytes[..]).unwrap();
+    assert_eq!(
+        nbt,
+        compound! { "X": 7i32, "ITEMS": [{ "ID": "a" }, { "ID": "b" }] }
+    );
+    assert_eq!(report.stopped().unwrap().path(), "ITEMS[1].TAG");
+    assert!(matches!(
+        reader.max_depth(2).read_from(&mut &bytes[..]),
+        Err(NbtIoError::ExceededMaxDepth)
+    ));
+}
+
+#[test]
+fn key_rules() {
+    let nbt = compound! { "display": { "Lore": [{ "bad\nkey": 1i8 }] } };
+
+    let rules = KeyRules::new().deny_empty(true);
+    assert!(rules.validate(&nbt).is_ok());
+
+    let rules = rules.deny_control_chars(true);
+    assert_eq!(rules.check("bad\nkey"), Some(KeyIssue::ControlChar('\n')));
+    match rules.validate(&nbt) {
+        Err(NbtIoError::SuspiciousKey { path, issue }) => {
+            assert_eq!(path, "display.Lore[0].\"bad\nkey\"");
+            assert_eq!(issue, KeyIssue::ControlChar('\n'));
+        }
+        result => panic!("unexpected result: {:?}", result),
+    }
+
+    assert_compound_eq!(
+        &io::read_nbt_with_key_rules(&mut Cursor::new(BIG_TEST), BIG_TEST_FLAVOR, &rules)
+            .unwrap()
+            .0,
+        &*BIG_TEST_VALIDATE
+    );
+}
+
+#[cfg(feature = "base64")]
+#[test]
+fn base64() {
+    // An empty compound with an empty root name
+    let mut encoded = Vec::new();
+    io::write_nbt_base64(&mut encoded, None, &compound! {}, Flavor::Uncompressed).unwrap();
+    assert_eq!(encoded, b"CgAAAA==");
+
+    let mut encoded = Vec::new();
+    io::write_nbt_base64(
+        &mut encoded,
+        Some("Level"),
+        &BIG_TEST_VALIDATE,
+        Flavor::GzCompressed,
+    )
+    .unwrap();
+    let mut encoded = String::from_utf8(encoded).unwrap();
+    encoded.push('\n');
+
+    let (nbt, root_name) = io::read_nbt_base64(&encoded, Flavor::GzCompressed).unwrap();
+    assert_eq!(root_name, "Level");
+    assert_compound_eq!(&nbt, &*BIG_TEST_VALIDATE);
+
+    assert!(io::read_nbt_base64("CgAA*A==", Flavor::Uncompressed).is_err());
+}
+
+#[test]
+fn empty_list_element_kind() {
+    // An empty list of ints named "a" in an unnamed root compound
+    let bytes = [0x0A, 0, 0, 0x09, 0, 1, b'a', 0x03, 0, 0, 0, 0, 0];
+
+    let (nbt, _) = io::read_nbt_bytes(&bytes).unwrap();
+    let list = nbt.get::<_, &NbtList>("a").unwrap();
+    assert_eq!(list.element_kind(), Some(NbtTagKind::Int));
+    assert_eq!(nbt, compound! { "a": NbtList::new() });
+    assert_eq!(
+        io::write_nbt_bytes(None, &nbt, Flavor::Uncompressed).unwrap(),
+        bytes
+    );
+
+    let mut nbt = nbt;
+    nbt.get_mut::<_, &mut NbtList>("a")
+        .unwrap()
+        .set_element_kind(None);
+    assert_eq!(
+        io::write_nbt_bytes(None, &nbt, Flavor::Uncompressed).unwrap()[7],
+        0x00
+    );
+}
+
+#[cfg(feature = "bytes")]
+#[test]
+fn read_from_buf() {
+    use bytes::{Buf, BytesMut};
+
+    let mut buf = BytesMut::new();
+    for flavor in [
+        Flavor::Uncompressed,
+        Flavor::ZlibCompressed,
+        Flavor::GzCompressed,
+        Flavor::RawDeflate,
+    ] {
+        let bytes = io::write_nbt_bytes(Some("Level"), &BIG_TEST_VALIDATE, flavor).unwrap();
+        buf.extend_from_slice(&bytes);
+    }
+    buf.extend_from_slice(b"rest");
+
+    let mut buf = buf.freeze();
+    for flavor in [
+        Flavor::Uncompressed,
+        Flavor::ZlibCompressed,
+        Flavor::GzCompressed,
+        Flavor::RawDeflate,
+    ] {
+        let (nbt, root_name) = io::read_nbt_buf(&mut buf, flavor).unwrap();
+        assert_eq!(root_name, "Level");
+        assert_compound_eq!(&nbt, &*BIG_TEST_VALIDATE);
+    }
+    assert_eq!(buf.chunk(), b"rest");
+
+    // Buffers made up of several chunks are read across chunk boundaries
+    let bytes = io::write_nbt_bytes(None, &BIG_TEST_VALIDATE, Flavor::Uncompressed).unwrap();
+    let (head, tail) = bytes.split_at(bytes.len() / 2);
+    let (nbt, _) = io::read_nbt_buf(&mut Buf::chain(head, tail), Flavor::Uncompressed).unwrap();
+    assert_compound_eq!(&nbt, &*BIG_TEST_VALIDATE);
+
+    assert!(io::read_nbt_buf(&mut &bytes[.. 10], Flavor::Uncompressed).is_err());
+}
+
+#[cfg(feature = "bytes")]
+#[test]
+fn read_buf_with_builder() {
+    use bytes::Buf;
+
+    let nbt = compound! { "Name": "Steve", "Pos": [{ "X": 1i32 }] };
+    let lowercase = |key: &str| Some(key.to_lowercase());
+
+    let mut bytes = Vec::new();
+    for flavor in [
+        Flavor::Uncompressed,
+        Flavor::ZlibCompressed,
+        Flavor::GzCompressed,
+    ] {
+        bytes.extend(io::write_nbt_bytes(Some("Level"), &nbt, flavor).unwrap());
+    }
+    bytes.extend_from_slice(b"rest");
+
+    // The flavor of each document is detected, and only its bytes are consumed
+    let reader = NbtReadBuilder::new().key_transform(&lowercase);
+    let mut buf = bytes.as_slice();
+    for _ in 0 .. 3 {
+        let (read, root_name) = reader.read_buf(&mut buf).unwrap();
+        assert_eq!(root_name, "Level");
+        assert_eq!(read, compound! { "name": "Steve", "pos": [{ "x": 1i32 }] });
+    }
+    assert_eq!(buf.chunk(), b"rest");
+
+    let raw = io::write_nbt_bytes(None, &nbt, Flavor::RawDeflate).unwrap();
+    let reader = NbtReadBuilder::new().flavor(Flavor::RawDeflate);
+    assert_eq!(reader.read_buf(&mut raw.as_slice()).unwrap().0, nbt);
+    assert!(matches!(
+        reader.max_depth(1).read_buf(&mut raw.as_slice()),
+        Err(NbtIoError::ExceededMaxDepth)
+    ));
+}
+
+#[test]
+fn framed_vectored() {
+    let frame = io::NbtFrame::encode(
+        LengthPrefix::U32,
+        Some("Level"),
+        &BIG_TEST_VALIDATE,
+        Flavor::GzCompressed,
+    )
+    .unwrap();
+    assert_eq!(frame.header(), (frame.payload().len() as u32).to_be_bytes());
+
+    let mut stream = Vec::new();
+    io::write_nbt_framed(
+        &mut stream,
+        LengthPrefix::U32,
+        Some("Level"),
+        &BIG_TEST_VALIDATE,
+        Flavor::GzCompressed,
+    )
+    .unwrap();
+    let joined = frame
+        .io_slices()
+        .iter()
+        .flat_map(|slice| slice.iter().copied())
+        .collect::<Vec<_>>();
+    assert_eq!(joined, stream);
+    assert_eq!(frame.len(), stream.len());
+}
+
+#[cfg(feature = "bytes")]
+#[test]
+fn write_into_buf() {
+    use bytes::{BufMut, BytesMut};
+
+    let mut buf = BytesMut::new();
+    buf.put_u8(0xFF);
+    io::write_nbt_buf(
+        &mut buf,
+        Some("Level"),
+        &BIG_TEST_VALIDATE,
+        Flavor::ZlibCompressed,
+    )
+    .unwrap();
+    let (nbt, root_name) = io::read_nbt_bytes(&buf[1 ..]).unwrap();
+    assert_eq!(root_name, "Level");
+    assert_compound_eq!(&nbt, &*BIG_TEST_VALIDATE);
+
+    let mut small = [0u8; 16];
+    assert!(io::write_nbt_buf(
+        &mut &mut small[..],
+        None,
+        &BIG_TEST_VALIDATE,
+        Flavor::Uncompressed
+    )
+    .is_err());
+}
+
+#[test]
+fn hexdump() {
+    let nbt = NbtTag::List(NbtList::from(vec![compound! {}, compound! { "a": 1i8 }]));
+    let mut dump = String::new();
+    nbt.hexdump(&mut dump, 0).unwrap();
+
+    let lines = dump.lines().collect::<Vec<_>>();
+    assert_eq!(lines[0], "00000000  09  List \"\"");
+    assert!(lines.contains(&"00000008      Compound [0]"));
+    assert!(lines.contains(&"00000008  00  End"));
+    assert!(lines.contains(&"00000009  01  Byte a = 1B"));
+    assert_eq!(lines.len(), 17);
+
+    let nbt = NbtTag::List(NbtList::from(vec![NbtTag::Int(1), NbtTag::Long(2)]));
+    assert!(matches!(
+        nbt.hexdump(&mut String::new(), 16),
+        Err(NbtIoError::NonHomogenousList { .. })
+    ));
+}
+
 #[test]
 fn player_nan_value() {
     let (nbt, _) =
@@ -104,3 +698,595 @@ fn preserve_order() {
         }
     }
 }
+
+#[test]
+fn recompress() {
+    let mut payload = Vec::new();
+    io::recompress(
+        &mut Cursor::new(BIG_TEST),
+        &mut payload,
+        BIG_TEST_FLAVOR,
+        Flavor::Uncompressed,
+    )
+    .unwrap();
+
+    let flavors = [
+        Flavor::Uncompressed,
+        Flavor::ZlibCompressed,
+        Flavor::ZlibCompressedWith(flate2::Compression::best()),
+        Flavor::GzCompressed,
+        Flavor::GzCompressedWith(flate2::Compression::fast()),
+        Flavor::RawDeflate,
+        Flavor::RawDeflateWith(flate2::Compression::best()),
+    ];
+    for from in flavors {
+        let mut source = Vec::new();
+        io::recompress(
+            &mut payload.as_slice(),
+            &mut source,
+            Flavor::Uncompressed,
+            from,
+        )
+        .unwrap();
+
+        for to in flavors {
+            let mut converted = Vec::new();
+            let copied =
+                io::recompress_validated(&mut source.as_slice(), &mut converted, from, to).unwrap();
+            assert_eq!(copied, payload.len() as u64);
+
+            let (nbt, root_name) = read_nbt(&mut converted.as_slice(), to).unwrap();
+            assert_eq!(root_name, "Level");
+            assert_compound_eq!(&nbt, &*BIG_TEST_VALIDATE);
+        }
+    }
+
+    // Invalid data is only converted without validation
+    let mut garbage = Vec::new();
+    io::recompress(
+        &mut &[1u8, 2, 3][..],
+        &mut garbage,
+        Flavor::Uncompressed,
+        Flavor::GzCompressed,
+    )
+    .unwrap();
+    let mut output = Vec::new();
+    assert!(io::recompress_validated(
+        &mut garbage.as_slice(),
+        &mut output,
+        Flavor::GzCompressed,
+        Flavor::Uncompressed
+    )
+    .is_err());
+    assert!(output.is_empty());
+    assert!(io::recompress(
+        &mut &[1u8, 2, 3][..],
+        &mut output,
+        Flavor::GzCompressed,
+        Flavor::Uncompressed
+    )
+    .is_err());
+}
+
+#[test]
+fn builders() {
+    let flavors = [
+        Flavor::Uncompressed,
+        Flavor::ZlibCompressed,
+        Flavor::GzCompressed,
+    ];
+
+    // The builders with only the flavor set match the free functions
+    for flavor in flavors {
+        let bytes = NbtWriteBuilder::new()
+            .flavor(flavor)
+            .root_name("Level")
+            .write_to_bytes(&BIG_TEST_VALIDATE)
+            .unwrap();
+        let (nbt, root_name) = read_nbt(&mut bytes.as_slice(), flavor).unwrap();
+        assert_eq!(root_name, "Level");
+        assert_compound_eq!(&nbt, &*BIG_TEST_VALIDATE);
+
+        let (nbt, root_name) = NbtReadBuilder::new()
+            .read_from(&mut bytes.as_slice())
+            .unwrap();
+        assert_eq!(root_name, "Level");
+        assert_compound_eq!(&nbt, &*BIG_TEST_VALIDATE);
+    }
+
+    // Nameless roots
+    let nbt = compound! { "name": "Steve", "pos": [1i32, 2i32, 3i32] };
+    for flavor in flavors {
+        let bytes = NbtWriteBuilder::new()
+            .flavor(flavor)
+            .root_name("ignored")
+            .nameless_root(true)
+            .write_to_bytes(&nbt)
+            .unwrap();
+        let named = io::write_nbt_bytes(Some(""), &nbt, flavor).unwrap();
+        assert_ne!(bytes, named);
+
+        let reader = NbtReadBuilder::new().flavor(flavor).nameless_root(true);
+        assert_eq!(
+            reader.read_from(&mut bytes.as_slice()).unwrap(),
+            (nbt.clone(), String::new())
+        );
+        assert_eq!(
+            reader
+                .latin1_fallback(true)
+                .read_from(&mut bytes.as_slice())
+                .unwrap(),
+            (nbt.clone(), String::new())
+        );
+        assert!(read_nbt(&mut bytes.as_slice(), flavor).is_err());
+    }
+
+    // Depth limits count the root compound
+    let nbt = compound! { "a": [[{ "b": 1i8 }]] };
+    let bytes = io::write_nbt_bytes(None, &nbt, Flavor::Uncompressed).unwrap();
+    for (max_depth, ok) in [
+        (0, false),
+        (1, false),
+        (3, false),
+        (4, true),
+        (usize::MAX, true),
+    ] {
+        let result = NbtReadBuilder::new()
+            .max_depth(max_depth)
+            .read_from(&mut bytes.as_slice());
+        match result {
+            Ok((read, _)) => {
+                assert!(ok, "max depth {} should have been exceeded", max_depth);
+                assert_eq!(read, nbt);
+            }
+            Err(NbtIoError::ExceededMaxDepth) => assert!(!ok, "max depth {}", max_depth),
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    // The limit is enforced while reading, before the rest of the document is reached
+    let truncated = &bytes[.. bytes.len() - 4];
+    assert!(matches!(
+        NbtReadBuilder::new()
+            .max_depth(2)
+            .read_from(&mut &truncated[..]),
+        Err(NbtIoError::ExceededMaxDepth)
+    ));
+    assert!(matches!(
+        NbtReadBuilder::new()
+            .max_depth(4)
+            .read_from(&mut &truncated[..]),
+        Err(NbtIoError::StdIo(_))
+    ));
+
+    // Latin-1 fallback
+    let bytes = [0x0A, 0, 0, 0x08, 0, 1, b's', 0, 1, 0xE9, 0];
+    assert!(NbtReadBuilder::new().read_from(&mut &bytes[..]).is_err());
+    assert_eq!(
+        NbtReadBuilder::new()
+            .latin1_fallback(true)
+            .read_from(&mut &bytes[..])
+            .unwrap()
+            .0,
+        compound! { "s": "é" }
+    );
+
+    // Key rules
+    let nbt = compound! { "bad\nkey": 1i8 };
+    let bytes = io::write_nbt_bytes(None, &nbt, Flavor::GzCompressed).unwrap();
+    let rules = KeyRules::new().deny_control_chars(true);
+    assert!(matches!(
+        NbtReadBuilder::new()
+            .key_rules(rules)
+            .read_from(&mut bytes.as_slice()),
+        Err(NbtIoError::SuspiciousKey {
+            issue: KeyIssue::ControlChar('\n'),
+            ..
+        })
+    ));
+    let mut output = Vec::new();
+    assert!(NbtWriteBuilder::new()
+        .key_rules(rules)
+        .write_to(&mut output, &nbt)
+        .is_err());
+    assert!(output.is_empty());
+}
+
+// Counts the bytes written without storing them, so that maximal documents can be written without
+// using any memory beyond that of the tags themselves
+struct CountingSink(u64);
+
+impl Write for CountingSink {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0 += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+// Writes arrays of the maximal length and one past it, which allocates several gigabytes of
+// address space and streams two gigabytes through the writer
+#[test]
+#[ignore]
+#[cfg(target_pointer_width = "64")]
+fn maximal_lengths() {
+    let max = i32::MAX as usize;
+
+    let nbt = compound! { "voxels": NbtTag::ByteArray(vec![0; max]) };
+    let mut sink = CountingSink(0);
+    write_nbt(&mut sink, None, &nbt, Flavor::Uncompressed).unwrap();
+    // Root header, named array header, array and TAG_End
+    assert_eq!(sink.0, 3 + 13 + max as u64 + 1);
+
+    let nbt = compound! { "voxels": NbtTag::ByteArray(vec![0; max + 1]) };
+    assert!(matches!(
+        write_nbt(&mut CountingSink(0), None, &nbt, Flavor::Uncompressed),
+        Err(NbtIoError::LengthTooLarge(len)) if len == max + 1
+    ));
+}
+
+#[test]
+fn string_lengths() {
+    let max = "a".repeat(u16::MAX as usize);
+    let nbt = compound! { "name": max.as_str() };
+    let bytes = io::write_nbt_bytes(None, &nbt, Flavor::Uncompressed).unwrap();
+    assert_eq!(io::read_nbt_bytes(&bytes).unwrap().0, nbt);
+
+    let nbt = compound! { "name": format!("{}a", max) };
+    assert!(matches!(
+        io::write_nbt_bytes(None, &nbt, Flavor::Uncompressed),
+        Err(NbtIoError::StringTooLong(65536))
+    ));
+
+    // The limit applies to the encoded length, and each of these characters is encoded as six
+    // bytes
+    let mut nbt = NbtCompound::new();
+    nbt.insert("\u{1F600}".repeat(10923), 0i8);
+    assert!(matches!(
+        io::write_nbt_bytes(None, &nbt, Flavor::Uncompressed),
+        Err(NbtIoError::StringTooLong(65538))
+    ));
+    let mut nbt = NbtCompound::new();
+    nbt.insert("\u{1F600}".repeat(10922), 0i8);
+    assert!(io::write_nbt_bytes(None, &nbt, Flavor::Uncompressed).is_ok());
+}
+
+// Round trips an array of the maximal length, which needs several gigabytes of memory
+#[test]
+#[ignore]
+#[cfg(target_pointer_width = "64")]
+fn maximal_round_trip() {
+    let max = i32::MAX as usize;
+    let mut voxels = vec![0i8; max];
+    voxels[max - 1] = 7;
+    let nbt = compound! { "voxels": NbtTag::ByteArray(voxels) };
+
+    let bytes = io::write_nbt_bytes(None, &nbt, Flavor::Uncompressed).unwrap();
+    let (read, _) = io::read_nbt_bytes(&bytes).unwrap();
+    drop(bytes);
+    assert_eq!(read, nbt);
+}
+
+#[test]
+fn key_transforms() {
+    fn renamed_pairs(renamed: &[RenamedKey]) -> Vec<(&str, &str)> {
+        let mut pairs = renamed
+            .iter()
+            .map(|key| (key.path(), key.original()))
+            .collect::<Vec<_>>();
+        pairs.sort_unstable();
+        pairs
+    }
+
+    let nbt = compound! {
+        "Items": [
+            { "Slot": 0i8, "id": "minecraft:stone" },
+            { "Slot": 1i8, "tag": { "Display": { "Name": "Rock" } } }
+        ],
+        "Nested": [[{ "A": 1i8 }], []]
+    };
+    let bytes = io::write_nbt_bytes(Some("Root"), &nbt, Flavor::ZlibCompressed).unwrap();
+
+    let lowercase = |key: &str| Some(key.to_lowercase());
+    let (read, root_name, renamed) =
+        io::read_nbt_with_key_transform(&mut bytes.as_slice(), Flavor::ZlibCompressed, &lowercase)
+            .unwrap();
+    assert_eq!(root_name, "Root");
+    assert_eq!(read, compound! {
+        "items": [
+            { "slot": 0i8, "id": "minecraft:stone" },
+            { "slot": 1i8, "tag": { "display": { "name": "Rock" } } }
+        ],
+        "nested": [[{ "a": 1i8 }], []]
+    });
+    assert_eq!(renamed_pairs(&renamed), [
+        ("items", "Items"),
+        ("items[0].slot", "Slot"),
+        ("items[1].slot", "Slot"),
+        ("items[1].tag.display", "Display"),
+        ("items[1].tag.display.name", "Name"),
+        ("nested", "Nested"),
+        ("nested[0][0].a", "A"),
+    ]);
+
+    // Keys which are returned unchanged are not recorded
+    let (read, _, renamed) = io::read_nbt_with_key_transform(
+        &mut Cursor::new(BIG_TEST),
+        BIG_TEST_FLAVOR,
+        &|key: &str| Some(key.to_owned()),
+    )
+    .unwrap();
+    assert_compound_eq!(&read, &*BIG_TEST_VALIDATE);
+    assert!(renamed.is_empty());
+
+    // The entry read last wins when keys collide
+    let mut bytes = Vec::new();
+    io::write_nbt(
+        &mut bytes,
+        None,
+        &compound! { "mod:x": 1i32 },
+        Flavor::Uncompressed,
+    )
+    .unwrap();
+    // Append a second entry named "x" before the end of the root compound
+    bytes.pop();
+    bytes.extend_from_slice(&[0x03, 0, 1, b'x', 0, 0, 0, 2, 0]);
+    let strip = |key: &str| key.strip_prefix("mod:").map(str::to_owned);
+    let (read, _, renamed) =
+        io::read_nbt_with_key_transform(&mut bytes.as_slice(), Flavor::Uncompressed, &strip)
+            .unwrap();
+    assert_eq!(read, compound! { "x": 2i32 });
+    assert_eq!(renamed_pairs(&renamed), [("x", "mod:x")]);
+
+    // The transform composes with the other options of the builder
+    let bytes = [0x0A, 0, 0, 0x08, 0, 1, b'S', 0, 1, 0xE9, 0];
+    let (read, _, report) = NbtReadBuilder::new()
+        .latin1_fallback(true)
+        .key_transform(&lowercase)
+        .read_from_with_report(&mut &bytes[..])
+        .unwrap();
+    assert_eq!(read, compound! { "s": "é" });
+    assert_eq!(report.latin1_fallbacks(), ["s"]);
+    assert_eq!(renamed_pairs(report.renamed_keys()), [("s", "S")]);
+    assert!(matches!(
+        NbtReadBuilder::new()
+            .key_transform(&lowercase)
+            .key_rules(KeyRules::new().deny_empty(true))
+            .read_from(&mut &[0x0A, 0, 0, 0x01, 0, 0, 1, 0][..]),
+        Err(NbtIoError::SuspiciousKey { .. })
+    ));
+}
+
+#[test]
+fn builder_modes() {
+    let uncompressed =
+        io::write_nbt_bytes(Some("Level"), &BIG_TEST_VALIDATE, Flavor::Uncompressed).unwrap();
+    let compressed =
+        io::write_nbt_bytes(Some("Level"), &BIG_TEST_VALIDATE, Flavor::GzCompressed).unwrap();
+
+    // Spans of compressed data refer to the decompressed data, whose flavor is detected
+    let (nbt, _, report) = NbtReadBuilder::new()
+        .spans(true)
+        .read_from_with_report(&mut compressed.as_slice())
+        .unwrap();
+    assert_compound_eq!(&nbt, &*BIG_TEST_VALIDATE);
+    let (_, _, spans) =
+        io::read_nbt_with_spans(&mut uncompressed.as_slice(), Flavor::Uncompressed).unwrap();
+    assert_eq!(
+        report.spans().unwrap().iter().collect::<Vec<_>>(),
+        spans.iter().collect::<Vec<_>>()
+    );
+    assert_eq!(spans.get(""), Some(0 .. uncompressed.len()));
+
+    // Partial reads compose with the other options
+    let bytes = [
+        0x0A, 0, 0, 0x08, 0, 1, b'S', 0, 1, 0xE9, 0x01, 0, 1, b'B', 1, 0x03, 0, 1, b'I', 0, 0,
+    ];
+    let lowercase = |key: &str| Some(key.to_lowercase());
+    let (read, _, report) = NbtReadBuilder::new()
+        .flavor(Flavor::Uncompressed)
+        .latin1_fallback(true)
+        .key_transform(&lowercase)
+        .partial(true)
+        .read_from_with_report(&mut &bytes[..])
+        .unwrap();
+    assert_eq!(read, compound! { "s": "é", "b": 1i8 });
+    assert_eq!(report.latin1_fallbacks(), ["s"]);
+    let stopped = report.stopped().unwrap();
+    assert_eq!((stopped.path(), stopped.offset()), ("i", bytes.len()));
+
+    // So do extensions
+    let bytes = [0x0A, 0, 0, 99, 0, 1, b'x', 42, 0x01, 0, 1, b'y', 1, 0];
+    let mut extension = |_: u8, reader: &mut dyn Read| {
+        let mut payload = [0u8; 1];
+        reader.read_exact(&mut payload)?;
+        Ok(Some(NbtTag::Byte(payload[0] as i8)))
+    };
+    let (read, _, report) = NbtReadBuilder::new()
+        .spans(true)
+        .read_from_with_extension(&mut &bytes[..], &mut extension)
+        .unwrap();
+    assert_eq!(read, compound! { "x": 42i8, "y": 1i8 });
+    assert_eq!(report.spans().unwrap().get("x"), Some(3 .. 8));
+
+    // Duplicate keys stop a partial read like any other error
+    let bytes = [
+        0x0A, 0, 0, 0x01, 0, 1, b'a', 1, 0x01, 0, 1, b'a', 2, 0x01, 0, 1, b'b', 3, 0,
+    ];
+    let (read, _, report) = NbtReadBuilder::new()
+        .duplicate_keys(DuplicateKeyPolicy::Error)
+        .partial(true)
+        .read_from_with_report(&mut &bytes[..])
+        .unwrap();
+    assert_eq!(read, compound! { "a": 1i8 });
+    assert!(matches!(
+        report.stopped().unwrap().error(),
+        NbtIoError::DuplicateKey { path } if path == "a"
+    ));
+    let (read, _) = NbtReadBuilder::new()
+        .duplicate_keys(DuplicateKeyPolicy::KeepFirst)
+        .read_from(&mut &bytes[..])
+        .unwrap();
+    assert_eq!(read, compound! { "a": 1i8, "b": 3i8 });
+
+    // Peeking applies the options which concern the root compound
+    let bytes = [0x0A, 0x09, 0, 1, b'l', 0x09, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0];
+    let reader = NbtReadBuilder::new().nameless_root(true);
+    let info = reader.peek_header(&mut &bytes[..]).unwrap();
+    assert_eq!(info.root_name(), "");
+    assert_eq!(info.get("l"), Some(NbtTagKind::List));
+    assert!(matches!(
+        reader.max_depth(2).peek_header(&mut &bytes[..]),
+        Err(NbtIoError::ExceededMaxDepth)
+    ));
+    assert!(reader.max_depth(3).peek_header(&mut &bytes[..]).is_ok());
+}
+
+#[test]
+fn cached_document() {
+    let bytes =
+        io::write_nbt_bytes(Some("Level"), &BIG_TEST_VALIDATE, Flavor::Uncompressed).unwrap();
+    let (_, _, spans) =
+        io::read_nbt_with_spans(&mut bytes.as_slice(), Flavor::Uncompressed).unwrap();
+    let untouched = &bytes[spans.get("\"nested compound test\"").unwrap()];
+
+    let mut document =
+        CachedDocument::read_from(&mut bytes.as_slice(), Flavor::Uncompressed).unwrap();
+    assert_eq!(document.root_name(), "Level");
+    assert_eq!(document.flavor(), Flavor::Uncompressed);
+    assert_compound_eq!(document.root(), &*BIG_TEST_VALIDATE);
+
+    let mut expected = BIG_TEST_VALIDATE.clone();
+    for (path, value) in [
+        ("intTest", NbtTag::Int(-1)),
+        ("\"listTest (compound)\"[1].name", NbtTag::from("Renamed")),
+        (
+            "\"listTest (compound)\"[0]",
+            NbtTag::Compound(compound! { "new": 1i8 }),
+        ),
+        ("\"listTest (long)\"[2]", NbtTag::Long(0)),
+        ("stringTest", NbtTag::from("Changed twice")),
+        ("stringTest", NbtTag::Short(7)),
+    ] {
+        *document.get_path_mut(path).unwrap() = value.clone();
+        expected.insert_path(path, value).unwrap();
+    }
+    assert_compound_eq!(document.root(), &expected);
+
+    // Saving twice in a row gives the same result, since the second save copies everything
+    for _ in 0 .. 2 {
+        let mut saved = Vec::new();
+        document.save_to(&mut saved).unwrap();
+        let (read, root_name) = read_nbt(&mut saved.as_slice(), Flavor::Uncompressed).unwrap();
+        assert_eq!(root_name, "Level");
+        assert_compound_eq!(&read, &expected);
+        assert!(saved
+            .windows(untouched.len())
+            .any(|window| window == untouched));
+    }
+
+    // Changes within a copied subtree are still picked up after a save
+    *document
+        .get_path_mut("\"nested compound test\".egg.value")
+        .unwrap() = NbtTag::Float(1.0);
+    expected
+        .insert_path("\"nested compound test\".egg.value", 1.0f32)
+        .unwrap();
+    document.root_mut().insert("added", "by root_mut");
+    expected.insert("added", "by root_mut");
+    for flavor in [Flavor::GzCompressed, Flavor::ZlibCompressed] {
+        let mut saved = Vec::new();
+        let mut document = CachedDocument::new("Level", document.root().clone(), flavor);
+        document.save_to(&mut saved).unwrap();
+        *document.get_path_mut("byteTest").unwrap() = NbtTag::Byte(0);
+        saved.clear();
+        document.save_to(&mut saved).unwrap();
+
+        let (read, _) = read_nbt(&mut saved.as_slice(), flavor).unwrap();
+        let mut expected = expected.clone();
+        expected.insert("byteTest", 0i8);
+        assert_compound_eq!(&read, &expected);
+    }
+
+    assert!(document.get_path_mut("missing").is_err());
+    assert!(document.get_path_mut("\"listTest (long)\"[]").is_err());
+
+    // Invalid changes are reported without losing the document
+    *document.get_path_mut("\"listTest (long)\"[0]").unwrap() = NbtTag::Int(0);
+    assert!(matches!(
+        document.save_to(&mut Vec::new()),
+        Err(NbtIoError::NonHomogenousList { .. })
+    ));
+    *document.get_path_mut("\"listTest (long)\"[0]").unwrap() = NbtTag::Long(11);
+    let mut saved = Vec::new();
+    document.save_to(&mut saved).unwrap();
+    let (read, _) = read_nbt(&mut saved.as_slice(), Flavor::Uncompressed).unwrap();
+    assert_compound_eq!(&read, document.root());
+
+    assert!(
+        CachedDocument::read_from(&mut &bytes[.. bytes.len() - 1], Flavor::Uncompressed).is_err()
+    );
+}
+
+#[test]
+fn auto_compression() {
+    let item = compound! { "id": "minecraft:stone", "Count": 1i8 };
+    let chunk = compound! {
+        "DataVersion": 3465i32,
+        "BlockStates": NbtTag::LongArray((0 .. 33000).collect())
+    };
+    let mixed = compound! {
+        "BlockStates": NbtTag::LongArray(vec![0; 40000]),
+        "Names": NbtList::from(vec!["minecraft:stone".to_owned(); 30000])
+    };
+    assert_eq!(
+        io::auto_compression_level(&item),
+        flate2::Compression::none()
+    );
+    assert_eq!(
+        io::auto_compression_level(&chunk),
+        flate2::Compression::best()
+    );
+    assert_eq!(
+        io::auto_compression_level(&mixed),
+        flate2::Compression::default()
+    );
+
+    for nbt in [&item, &chunk, &mixed] {
+        let level = io::auto_compression_level(nbt);
+        for (flavor, with_level) in [
+            (Flavor::ZlibCompressed, Flavor::ZlibCompressedWith(level)),
+            (Flavor::GzCompressed, Flavor::GzCompressedWith(level)),
+            (Flavor::RawDeflate, Flavor::RawDeflateWith(level)),
+        ] {
+            let auto = NbtWriteBuilder::new()
+                .flavor(flavor)
+                .auto_compression(true)
+                .write_to_bytes(nbt)
+                .unwrap();
+            let explicit = NbtWriteBuilder::new()
+                .flavor(with_level)
+                .write_to_bytes(nbt)
+                .unwrap();
+            assert_eq!(auto, explicit);
+            assert_eq!(&read_nbt(&mut auto.as_slice(), flavor).unwrap().0, nbt);
+        }
+    }
+
+    // Explicit levels are kept
+    let fast = Flavor::ZlibCompressedWith(flate2::Compression::fast());
+    assert_eq!(
+        NbtWriteBuilder::new()
+            .flavor(fast)
+            .auto_compression(true)
+            .write_to_bytes(&item)
+            .unwrap(),
+        NbtWriteBuilder::new()
+            .flavor(fast)
+            .write_to_bytes(&item)
+            .unwrap()
+    );
+}
@@ -0,0 +1,437 @@
+use quartz_nbt::{
+    compound,
+    io::{
+        self,
+        Flavor,
+        LengthRepairAction,
+        NbtIoError,
+        NbtReadBuilder,
+        NegativeLengthPolicy,
+        UnknownListType,
+        MAX_DEPTH,
+    },
+    snbt::{self, ParseOptions, ParserErrorType},
+    NbtCompound,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::io::Read;
+
+// A document whose root compound holds the given number of lists nested within each other
+fn nested_lists(depth: usize) -> Vec<u8> {
+    let mut bytes = vec![0x0A, 0, 0, 0x09, 0, 1, b'a'];
+    for _ in 1 .. depth {
+        bytes.extend_from_slice(&[0x09, 0, 0, 0, 1]);
+    }
+    bytes.extend_from_slice(&[0, 0, 0, 0, 0, 0]);
+    bytes
+}
+
+fn read_all_ways(bytes: &[u8]) -> Vec<Result<(), NbtIoError>> {
+    let mut results = vec![
+        io::read_nbt(&mut &bytes[..], Flavor::Uncompressed).map(drop),
+        io::read_nbt_with_spans(&mut &bytes[..], Flavor::Uncompressed).map(drop),
+        io::read_nbt_with_latin1_fallback(&mut &bytes[..], Flavor::Uncompressed).map(drop),
+        io::read_nbt_with_extension(
+            &mut &bytes[..],
+            Flavor::Uncompressed,
+            &mut |tag_id, _: &mut dyn Read| Err(NbtIoError::InvalidTagId(tag_id)),
+        )
+        .map(drop),
+        match io::read_nbt_partial(&mut &bytes[..], Flavor::Uncompressed).2 {
+            Some(error) => Err(error.into_inner()),
+            None => Ok(()),
+        },
+        io::read_nbt_with_unknown_list_types(
+            &mut &bytes[..],
+            Flavor::Uncompressed,
+            UnknownListType::Error,
+        )
+        .map(drop),
+        io::peek_header(&mut &bytes[..], Flavor::Uncompressed).map(drop),
+        io::read_nbt_with_negative_lengths(
+            &mut &bytes[..],
+            Flavor::Uncompressed,
+            NegativeLengthPolicy::Error,
+        )
+        .map(drop),
+    ];
+
+    results.extend(deserialize_all_ways(bytes));
+    results
+}
+
+#[cfg(feature = "serde")]
+fn deserialize_all_ways(bytes: &[u8]) -> Vec<Result<(), NbtIoError>> {
+    use quartz_nbt::serde::{self, RawNbt};
+    use std::collections::HashMap;
+
+    vec![
+        serde::deserialize::<NbtCompound>(bytes, Flavor::Uncompressed).map(drop),
+        serde::deserialize_from_buffer::<NbtCompound>(bytes).map(drop),
+        serde::deserialize::<HashMap<String, RawNbt>>(bytes, Flavor::Uncompressed).map(drop),
+    ]
+}
+
+#[cfg(not(feature = "serde"))]
+fn deserialize_all_ways(_bytes: &[u8]) -> Vec<Result<(), NbtIoError>> {
+    Vec::new()
+}
+
+// Unoptimized builds use far more stack per level than optimized ones
+fn with_large_stack<F: FnOnce() + Send + 'static>(f: F) {
+    std::thread::Builder::new()
+        .stack_size(64 * 1024 * 1024)
+        .spawn(f)
+        .unwrap()
+        .join()
+        .unwrap();
+}
+
+#[test]
+fn max_depth() {
+    with_large_stack(|| {
+        for result in read_all_ways(&nested_lists(MAX_DEPTH - 1)) {
+            assert!(result.is_ok());
+        }
+
+        for depth in [MAX_DEPTH, 1_000_000] {
+            for result in read_all_ways(&nested_lists(depth)) {
+                assert!(matches!(result, Err(NbtIoError::ExceededMaxDepth)));
+            }
+        }
+    });
+}
+
+#[test]
+fn max_depth_snbt() {
+    let nested = |depth: usize| format!("{{a:{}{}}}", "[".repeat(depth), "]".repeat(depth));
+
+    with_large_stack(move || {
+        assert!(snbt::parse(&nested(MAX_DEPTH - 1)).is_ok());
+        for depth in [MAX_DEPTH, 1_000_000] {
+            let error = snbt::parse(&nested(depth)).unwrap_err();
+            assert!(error
+                .to_string()
+                .starts_with("Exceeded maximum nesting depth"));
+        }
+    });
+}
+
+#[test]
+fn long_snbt_tokens() {
+    const MAX: usize = 1 << 16;
+    let long = "a".repeat(8 << 20);
+    let inputs = [
+        format!("{{key: {}}}", long),
+        format!("{{key: '{}'}}", long),
+        format!("{{key: \"{}\"}}", "\\\\".repeat(4 << 20)),
+        // Unterminated quotes and brackets within unquoted values extend them to the end
+        format!("{{key: a'{}}}", long),
+        format!("{{key: a{{{}}}", "['\"".repeat(2 << 20)),
+        format!("{{{}: 1}}", long),
+        format!("{{key: [{}]}}", long),
+    ];
+
+    for input in &inputs {
+        let options = ParseOptions::new().max_token_len(MAX);
+        let error = snbt::parse_with(input, options).unwrap_err();
+        assert!(
+            matches!(
+                error.error_type(),
+                ParserErrorType::ExceededMaxTokenLength { max: MAX, .. }
+            ),
+            "{:?} in {:?}",
+            error,
+            &input[.. 16]
+        );
+        // The error points at the start of the token, so only a short segment is kept
+        assert!(error.position().unwrap().column <= 8);
+        assert!(error.to_string().len() < 100);
+
+        // Strict mode ends unquoted values at quotes and brackets, so it may fail earlier
+        assert!(snbt::parse_with(input, options.vanilla_strict(true)).is_err());
+    }
+
+    // Tokens at the limit are accepted
+    let value = "a".repeat(MAX - 2);
+    let compound = snbt::parse_with(
+        &format!("{{key: '{}'}}", value),
+        ParseOptions::new().max_token_len(MAX),
+    )
+    .unwrap();
+    assert_eq!(compound.get::<_, &str>("key").unwrap(), value);
+}
+
+#[test]
+fn negative_lengths() {
+    // Byte array, list, int array and long array entries named "a" with a length of -1
+    for prefix in [&[0x07][..], &[0x09, 0x01], &[0x0B], &[0x0C]] {
+        let mut bytes = vec![0x0A, 0, 0, prefix[0], 0, 1, b'a'];
+        bytes.extend_from_slice(&prefix[1 ..]);
+        bytes.extend_from_slice(&[0xFF, 0xFF, 0xFF, 0xFF, 0]);
+
+        for result in read_all_ways(&bytes) {
+            assert!(matches!(result, Err(NbtIoError::NegativeLength(-1))));
+        }
+
+        let read = |policy| {
+            io::read_nbt_with_negative_lengths(&mut &bytes[..], Flavor::Uncompressed, policy)
+                .unwrap()
+        };
+
+        let (nbt, _, repairs) = read(NegativeLengthPolicy::TreatAsZero);
+        assert_eq!(nbt.len(), 1);
+        assert_eq!(repairs.len(), 1);
+        assert_eq!(repairs[0].path(), "a");
+        assert_eq!(repairs[0].offset(), 6 + prefix.len());
+        assert_eq!(repairs[0].action(), LengthRepairAction::TreatedAsZero);
+
+        let (nbt, _, repairs) = read(NegativeLengthPolicy::Recover);
+        assert!(nbt.is_empty());
+        assert_eq!(repairs[0].action(), LengthRepairAction::DroppedEntry {
+            skipped: bytes.len() - 4
+        });
+    }
+}
+
+#[test]
+fn recover_negative_lengths() {
+    let chunk = compound! {
+        "xPos": 3i32,
+        "sections": [
+            { "Y": 0i8, "data": [L; 1, 2, 3], "light": [B; 1, 2] },
+            { "Y": 1i8, "data": [L; 4, 5, 6], "light": [B; 3, 4] }
+        ],
+        "zPos": 4i32
+    };
+    let mut bytes = Vec::new();
+    io::write_nbt(&mut bytes, None, &chunk, Flavor::Uncompressed).unwrap();
+
+    // Corrupt the length of the first section's long array
+    let data = bytes
+        .windows(4)
+        .position(|window| window == b"data")
+        .unwrap()
+        + 4;
+    bytes[data .. data + 4].copy_from_slice(&(-3i32).to_be_bytes());
+
+    assert!(matches!(
+        io::read_nbt(&mut &bytes[..], Flavor::Uncompressed),
+        Err(NbtIoError::NegativeLength(-3))
+    ));
+
+    // Only the corrupted entry is lost, and the rest of the section and chunk are kept
+    let (nbt, _, repairs) = io::read_nbt_with_negative_lengths(
+        &mut &bytes[..],
+        Flavor::Uncompressed,
+        NegativeLengthPolicy::Recover,
+    )
+    .unwrap();
+    assert_eq!(nbt, compound! {
+        "xPos": 3i32,
+        "sections": [
+            { "Y": 0i8, "light": [B; 1, 2] },
+            { "Y": 1i8, "data": [L; 4, 5, 6], "light": [B; 3, 4] }
+        ],
+        "zPos": 4i32
+    });
+    assert_eq!(repairs.len(), 1);
+    assert_eq!(repairs[0].path(), "sections[0].data");
+    assert_eq!(repairs[0].offset(), data);
+    assert_eq!(repairs[0].length(), -3);
+    assert_eq!(
+        repairs[0].to_string(),
+        format!(
+            "sections[0].data: negative length -3 at offset {} dropped along with 35 bytes",
+            data
+        )
+    );
+}
+
+#[test]
+fn recover_negative_lengths_bounded() {
+    let reader = NbtReadBuilder::new()
+        .flavor(Flavor::Uncompressed)
+        .negative_lengths(NegativeLengthPolicy::Recover);
+    #[rustfmt::skip]
+    let document = [
+        0x0A, 0, 0,
+        0x0B, 0, 1, b'a', 0xFF, 0xFF, 0xFF, 0xFF,
+        0x01, 0, 1, b'b', 2,
+        0,
+    ];
+
+    // Bytes trailing the root compound do not prevent recovery
+    let mut bytes = document.to_vec();
+    bytes.extend_from_slice(b"trailing data");
+    let (nbt, _, report) = reader.read_from_with_report(&mut bytes.as_slice()).unwrap();
+    assert_eq!(nbt, compound! { "b": 2i8 });
+    assert_eq!(report.length_repairs().len(), 1);
+    assert_eq!(
+        report.length_repairs()[0].action(),
+        LengthRepairAction::DroppedEntry { skipped: 8 }
+    );
+
+    // Junk between the negative length and the rest of the document is skipped
+    let mut bytes = document[.. 11].to_vec();
+    bytes.resize(11 + 100_000, 0xFF);
+    bytes.extend_from_slice(&document[11 ..]);
+    let (nbt, _, report) = reader.read_from_with_report(&mut bytes.as_slice()).unwrap();
+    assert_eq!(nbt, compound! { "b": 2i8 });
+    assert_eq!(
+        report.length_repairs()[0].action(),
+        LengthRepairAction::DroppedEntry { skipped: 100_008 }
+    );
+
+    // Every offset of this data reads as a long run of byte entries, which would take quadratic
+    // time to check if the rest of the document were read from each of them
+    let mut bytes = document[.. 11].to_vec();
+    bytes.resize(11 + (1 << 22), 0x01);
+    assert!(matches!(
+        reader.read_from(&mut bytes.as_slice()),
+        Err(NbtIoError::StdIo(_))
+    ));
+}
+
+#[test]
+fn huge_lengths() {
+    // Lengths of i32::MAX with no data following them fail without allocating for every element
+    for prefix in [&[0x07][..], &[0x09, 0x01], &[0x0B], &[0x0C]] {
+        let mut bytes = vec![0x0A, 0, 0, prefix[0], 0, 1, b'a'];
+        bytes.extend_from_slice(&prefix[1 ..]);
+        bytes.extend_from_slice(&[0x7F, 0xFF, 0xFF, 0xFF, 1, 2, 3]);
+
+        for result in read_all_ways(&bytes) {
+            assert!(matches!(result, Err(NbtIoError::StdIo(_))));
+        }
+    }
+}
+
+#[test]
+fn multibyte_snbt_errors() {
+    for input in ["{a:'\\😀'}", "{a:\"é\\中\"}", "{😀:[1,'中']}", "{a:[B;é]}"] {
+        let error = snbt::parse(input).unwrap_err();
+        assert!(!error.to_string().is_empty());
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn unit_variant_in_compound() {
+    #[derive(serde::Deserialize)]
+    #[allow(dead_code)]
+    enum Mode {
+        Off,
+        Level(i32),
+    }
+
+    #[derive(serde::Deserialize)]
+    #[allow(dead_code)]
+    struct Settings {
+        mode: Mode,
+    }
+
+    let bytes = quartz_nbt::serde::serialize(
+        &compound! { "mode": { "Off": 1i32 } },
+        None,
+        Flavor::Uncompressed,
+    )
+    .unwrap();
+    let result = quartz_nbt::serde::deserialize::<Settings>(&bytes, Flavor::Uncompressed);
+    assert!(matches!(result, Err(NbtIoError::ExpectedEnum)));
+}
+
+// Randomly corrupts valid documents and SNBT strings, checking that reading them never panics
+#[test]
+fn random_corruption() {
+    let documents = [
+        compound! {
+            "list": [[1i8, 2i8], [3i8]],
+            "nested": { "x": { "y": [1i64, 2i64] } },
+            "arrays": [[B; 1, 2], [B; 3]],
+            "name": "héllo"
+        },
+        compound! {
+            "deep": [[[{ "z": [I; 1, 2] }]]],
+            "longs": [L; 5, 6],
+            "float": 1.5f32,
+            "double": -2.5f64,
+            "short": 3i16
+        },
+    ];
+
+    let mut rng = StdRng::seed_from_u64(0x6E6274);
+    let mut valid = Vec::new();
+    for document in &documents {
+        let mut bytes = Vec::new();
+        io::write_nbt(&mut bytes, Some("root"), document, Flavor::Uncompressed).unwrap();
+        valid.push(bytes);
+    }
+
+    for _ in 0 .. 20_000 {
+        let mut bytes = valid[rng.gen_range(0 .. valid.len())].clone();
+        for _ in 0 .. rng.gen_range(1 ..= 4) {
+            let index = rng.gen_range(0 .. bytes.len());
+            match rng.gen_range(0 .. 4) {
+                0 => bytes[index] = rng.r#gen(),
+                1 => bytes[index] = [0x00, 0x09, 0x0A, 0x7F, 0x80, 0xFF][rng.gen_range(0 .. 6)],
+                2 => bytes.insert(index, rng.r#gen()),
+                _ => bytes.truncate(index.max(1)),
+            }
+        }
+
+        let _ = read_all_ways(&bytes);
+        for policy in [
+            NegativeLengthPolicy::TreatAsZero,
+            NegativeLengthPolicy::Recover,
+        ] {
+            let _ =
+                io::read_nbt_with_negative_lengths(&mut &bytes[..], Flavor::Uncompressed, policy);
+        }
+    }
+
+    let snbt = documents
+        .iter()
+        .map(NbtCompound::to_snbt)
+        .collect::<Vec<_>>();
+    let alphabet = "{}[]:,;'\"\\ aZ09.-+eEbBsSlLfFdDI😀中é"
+        .chars()
+        .collect::<Vec<_>>();
+    for _ in 0 .. 20_000 {
+        let mut string = snbt[rng.gen_range(0 .. snbt.len())]
+            .chars()
+            .collect::<Vec<_>>();
+        for _ in 0 .. rng.gen_range(1 ..= 4) {
+            if string.is_empty() {
+                break;
+            }
+
+            let index = rng.gen_range(0 .. string.len());
+            match rng.gen_range(0 .. 3) {
+                0 => string[index] = alphabet[rng.gen_range(0 .. alphabet.len())],
+                1 => string.insert(index, alphabet[rng.gen_range(0 .. alphabet.len())]),
+                _ => drop(string.remove(index)),
+            }
+        }
+
+        let string = string.into_iter().collect::<String>();
+        if let Err(error) = snbt::parse(&string) {
+            let _ = error.to_string();
+        }
+
+        // A limit which no token can exceed never changes the result
+        for options in [
+            ParseOptions::new(),
+            ParseOptions::new().vanilla_strict(true),
+        ] {
+            let unlimited = snbt::parse_with(&string, options).map_err(|error| error.to_string());
+            let limited = snbt::parse_with(&string, options.max_token_len(string.len()))
+                .map_err(|error| error.to_string());
+            assert_eq!(limited, unlimited);
+
+            if let Err(error) = snbt::parse_with(&string, options.max_token_len(4)) {
+                let _ = error.to_string();
+            }
+        }
+    }
+}
@@ -0,0 +1,91 @@
+#![cfg(feature = "math")]
+
+use glam::{DVec2, DVec3, Vec2, Vec3};
+use quartz_nbt::{compound, NbtList, NbtTag};
+
+#[test]
+fn vectors_from_lists() {
+    let nbt = compound! {
+        "Pos": [-12.5f64, 70.0f64, 3.25f64],
+        "Rotation": [180.0f32, 45.0f32],
+        "Offset": [1.0f64, 2.0f64],
+        "Scale": [0.5f32, 1.0f32, 2.0f32],
+        "Empty": [],
+        "Ints": [1i32, 2i32, 3i32],
+        "Array": [I; 1, 2, 3]
+    };
+
+    assert_eq!(
+        nbt.get::<_, DVec3>("Pos").unwrap(),
+        DVec3::new(-12.5, 70.0, 3.25)
+    );
+    assert_eq!(
+        nbt.get::<_, Vec2>("Rotation").unwrap(),
+        Vec2::new(180.0, 45.0)
+    );
+    assert_eq!(nbt.get::<_, DVec2>("Offset").unwrap(), DVec2::new(1.0, 2.0));
+    assert_eq!(
+        nbt.get::<_, Vec3>("Scale").unwrap(),
+        Vec3::new(0.5, 1.0, 2.0)
+    );
+
+    assert_eq!(
+        nbt.get::<_, DVec3>("Offset").unwrap_err().to_string(),
+        "List length mismatch: expected 3 elements but found 2"
+    );
+    assert_eq!(
+        nbt.get::<_, DVec2>("Empty").unwrap_err().to_string(),
+        "List length mismatch: expected 2 elements but found 0"
+    );
+    assert_eq!(
+        nbt.get::<_, Vec3>("Pos").unwrap_err().to_string(),
+        "Tag type mismatch: expected Float but found Double"
+    );
+    assert_eq!(
+        nbt.get::<_, DVec3>("Ints").unwrap_err().to_string(),
+        "Tag type mismatch: expected Double but found Int"
+    );
+    assert_eq!(
+        nbt.get::<_, DVec3>("Array").unwrap_err().to_string(),
+        "Tag type mismatch: expected List of 3 Doubles but found IntArray"
+    );
+}
+
+#[test]
+fn vectors_to_lists() {
+    assert_eq!(
+        NbtTag::from(DVec3::new(1.0, 2.0, 3.0)),
+        NbtTag::List(NbtList::from(vec![1.0f64, 2.0, 3.0]))
+    );
+    assert_eq!(
+        NbtList::from(Vec2::new(-90.0, 0.0)),
+        NbtList::from(vec![-90.0f32, 0.0])
+    );
+
+    let vector = Vec3::new(0.1, 0.2, 0.3);
+    assert_eq!(Vec3::try_from(&NbtTag::from(vector)).unwrap(), vector);
+    let vector = DVec2::new(f64::MIN_POSITIVE, f64::MAX);
+    assert_eq!(DVec2::try_from(&NbtList::from(vector)).unwrap(), vector);
+}
+
+#[test]
+fn entity_accessors() {
+    let mut entity = compound! { "id": "minecraft:pig" };
+    assert!(entity.pos().is_err());
+    assert!(entity.motion().is_err());
+    assert!(entity.rotation().is_err());
+
+    entity.set_pos(DVec3::new(8.5, 64.0, -8.5));
+    entity.set_motion(DVec3::new(0.0, -0.0784, 0.0));
+    entity.set_rotation(Vec2::new(270.0, 0.0));
+
+    assert_eq!(entity, compound! {
+        "id": "minecraft:pig",
+        "Pos": [8.5f64, 64.0f64, -8.5f64],
+        "Motion": [0.0f64, -0.0784f64, 0.0f64],
+        "Rotation": [270.0f32, 0.0f32]
+    });
+    assert_eq!(entity.pos().unwrap(), DVec3::new(8.5, 64.0, -8.5));
+    assert_eq!(entity.motion().unwrap(), DVec3::new(0.0, -0.0784, 0.0));
+    assert_eq!(entity.rotation().unwrap(), Vec2::new(270.0, 0.0));
+}
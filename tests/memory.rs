@@ -0,0 +1,84 @@
+#![cfg(feature = "alloc-track")]
+
+mod assets;
+use assets::*;
+use quartz_nbt::{
+    assert_compound_eq,
+    io::{self, Flavor},
+    memory::{self, TrackingAllocator},
+};
+use std::io::Cursor;
+
+#[global_allocator]
+static ALLOCATOR: TrackingAllocator = TrackingAllocator::new();
+
+#[test]
+fn read_tracked() {
+    let (nbt, root_name, stats) =
+        memory::read_nbt_tracked(&mut Cursor::new(BIG_TEST), BIG_TEST_FLAVOR).unwrap();
+    assert_eq!(root_name, "Level");
+    assert_compound_eq!(&nbt, &*BIG_TEST_VALIDATE);
+    assert!(stats.allocations() > 0);
+    assert!(stats.retained_bytes() > 0);
+    assert!(stats.peak_bytes() >= stats.retained_bytes());
+    assert!(stats.allocated_bytes() >= stats.peak_bytes());
+
+    // Reading the same data again allocates the same amount
+    let (_, _, again) =
+        memory::read_nbt_tracked(&mut Cursor::new(BIG_TEST), BIG_TEST_FLAVOR).unwrap();
+    assert_eq!(again, stats);
+
+    // Uncompressed reads don't need the decompressor's buffers
+    let mut bytes = Vec::new();
+    io::write_nbt(&mut bytes, Some("Level"), &nbt, Flavor::Uncompressed).unwrap();
+    drop(nbt);
+    let (_, _, uncompressed) =
+        memory::read_nbt_tracked(&mut bytes.as_slice(), Flavor::Uncompressed).unwrap();
+    assert!(uncompressed.peak_bytes() < stats.peak_bytes());
+    assert_eq!(uncompressed.retained_bytes(), stats.retained_bytes());
+}
+
+#[test]
+fn nested_tracking() {
+    let ((inner, kept), outer) = memory::track(|| {
+        let before = vec![0u8; 100];
+        let (kept, inner) = memory::track(|| {
+            let scratch = vec![0u8; 1000];
+            drop(scratch);
+            vec![0u8; 10]
+        });
+        drop(before);
+        (inner, kept)
+    });
+
+    assert_eq!(kept.len(), 10);
+    assert_eq!(
+        (
+            inner.allocations(),
+            inner.peak_bytes(),
+            inner.retained_bytes()
+        ),
+        (2, 1000, 10)
+    );
+    assert_eq!(
+        (
+            outer.allocations(),
+            outer.peak_bytes(),
+            outer.retained_bytes()
+        ),
+        (3, 1100, 10)
+    );
+    assert_eq!(outer.allocated_bytes(), 1110);
+
+    // Memory allocated before tracking started isn't counted when it's freed
+    let existing = vec![0u8; 50];
+    let ((), stats) = memory::track(|| drop(existing));
+    assert_eq!(stats, Default::default());
+}
+
+#[test]
+fn tracking_stops_on_panic() {
+    assert!(std::panic::catch_unwind(|| memory::track(|| panic!("oops"))).is_err());
+    let ((), stats) = memory::track(|| drop(vec![0u8; 10]));
+    assert_eq!((stats.allocations(), stats.retained_bytes()), (1, 0));
+}
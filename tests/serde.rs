@@ -2,18 +2,41 @@
 
 mod assets;
 use assets::*;
+use flate2::{
+    bufread::{DeflateDecoder, GzDecoder, ZlibDecoder},
+    Compression,
+};
 use quartz_nbt::{
+    assert_compound_eq,
     compound,
-    io::{self, Flavor},
-    serde::{deserialize, deserialize_from, deserialize_from_buffer, serialize, Array},
+    convert::ConversionPolicy,
+    io::{self, Flavor, NbtIoError},
+    path::escape_key,
+    serde::{
+        deserialize,
+        deserialize_from,
+        deserialize_from_buffer,
+        serialize,
+        serialize_batch_into,
+        Array,
+        BoolConversion,
+        DeserializerBuilder,
+        FixedArray,
+        NonFiniteFloats,
+        RawNbt,
+        SerializerBuilder,
+        TagSerdeOptions,
+        TaggedValue,
+    },
     NbtCompound,
     NbtList,
     NbtTag,
+    NbtTagKind,
 };
 use serde::{de::Visitor, ser::SerializeStruct, Deserialize, Serialize};
 use std::{
     collections::HashMap,
-    io::{Cursor, Seek, SeekFrom},
+    io::{Cursor, Read, Seek, SeekFrom},
 };
 
 #[derive(Serialize, Deserialize, PartialEq)]
@@ -162,6 +185,64 @@ fn level_dat() {
     assert_compound_eq!(test_nbt, validate_nbt)
 }
 
+#[test]
+fn batch_serde() {
+    let level: Level = deserialize(LEVEL_DAT, Flavor::GzCompressed).unwrap().0;
+    let names = ["first", "second", "third"];
+    let expected: Vec<Vec<u8>> = names
+        .iter()
+        .map(|&name| serialize(&level, Some(name), Flavor::Uncompressed).unwrap())
+        .collect();
+
+    for flavor in [
+        Flavor::Uncompressed,
+        Flavor::ZlibCompressed,
+        Flavor::GzCompressedWith(Compression::best()),
+        Flavor::RawDeflate,
+    ] {
+        let mut bytes = Vec::new();
+        serialize_batch_into(&mut bytes, names.iter().map(|&name| (name, &level)), flavor).unwrap();
+
+        let mut reader = bytes.as_slice();
+        for expected in &expected {
+            let mut document = Vec::new();
+            match flavor {
+                Flavor::Uncompressed => {
+                    document.extend_from_slice(&reader[.. expected.len()]);
+                    reader = &reader[expected.len() ..];
+                }
+                Flavor::ZlibCompressed => {
+                    ZlibDecoder::new(&mut reader)
+                        .read_to_end(&mut document)
+                        .unwrap();
+                }
+                Flavor::RawDeflate => {
+                    DeflateDecoder::new(&mut reader)
+                        .read_to_end(&mut document)
+                        .unwrap();
+                }
+                _ => {
+                    GzDecoder::new(&mut reader)
+                        .read_to_end(&mut document)
+                        .unwrap();
+                }
+            }
+            assert_eq!(&document, expected);
+        }
+        assert!(reader.is_empty());
+    }
+
+    // Each Gz document must also be readable on its own
+    let mut bytes = Vec::new();
+    serialize_batch_into(&mut bytes, [("only", &level)], Flavor::GzCompressed).unwrap();
+    let (test_nbt, root_name) = io::read_nbt(&mut bytes.as_slice(), Flavor::GzCompressed).unwrap();
+    assert_eq!(root_name, "only");
+    let validate_nbt = io::read_nbt(&mut Cursor::new(LEVEL_DAT), Flavor::GzCompressed)
+        .unwrap()
+        .0;
+    assert_compound_eq!(test_nbt, validate_nbt);
+}
+
 #[test]
 fn basic_datatypes_serde() {
     #[derive(Serialize, Deserialize, PartialEq, Debug)]
@@ -725,6 +806,270 @@ fn vec_serde() {
     assert_eq!(deserialized_struct, test_struct);
 }
 
+#[test]
+fn non_finite_floats() {
+    #[derive(Serialize)]
+    struct Entity {
+        health: f32,
+        motion: Vec<f64>,
+    }
+
+    let entity = Entity {
+        health: f32::INFINITY,
+        motion: vec![0.5, f64::NAN],
+    };
+
+    let bytes = serialize(&entity, None, Flavor::Uncompressed).unwrap();
+    let nbt = io::read_nbt(&mut bytes.as_slice(), Flavor::Uncompressed)
+        .unwrap()
+        .0;
+    assert_eq!(nbt.get::<_, f32>("health").unwrap(), f32::INFINITY);
+
+    let result = SerializerBuilder::new()
+        .non_finite_floats(NonFiniteFloats::Error)
+        .serialize(&entity, None, Flavor::Uncompressed);
+    assert!(matches!(result, Err(NbtIoError::NonFiniteFloat(value)) if value == f64::INFINITY));
+
+    let bytes = SerializerBuilder::new()
+        .non_finite_floats(NonFiniteFloats::Normalize)
+        .serialize(&entity, None, Flavor::Uncompressed)
+        .unwrap();
+    let nbt = io::read_nbt(&mut bytes.as_slice(), Flavor::Uncompressed)
+        .unwrap()
+        .0;
+    assert_eq!(
+        nbt,
+        compound! { "health": f32::MAX, "motion": [0.5f64, 0.0f64] }
+    );
+}
+
+#[test]
+fn empty_list_element_kinds() {
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Chunk {
+        entities: NbtList,
+        sections: Vec<NbtList>,
+        data: NbtCompound,
+    }
+
+    let mut ints = NbtList::new();
+    ints.set_element_kind(Some(NbtTagKind::Int));
+    let mut compounds = NbtList::new();
+    compounds.set_element_kind(Some(NbtTagKind::Compound));
+    let chunk = Chunk {
+        entities: compounds.clone(),
+        sections: vec![ints.clone(), NbtList::from(vec![1i32])],
+        data: compound! { "ticks": ints, "unknown": NbtList::new() },
+    };
+
+    let bytes = serialize(&chunk, None, Flavor::Uncompressed).unwrap();
+    let nbt = io::read_nbt(&mut bytes.as_slice(), Flavor::Uncompressed)
+        .unwrap()
+        .0;
+    let kind = |path: &str| match nbt.get_path(path).unwrap() {
+        NbtTag::List(list) => list.element_kind(),
+        tag => panic!("{} is not a list: {}", path, tag),
+    };
+    assert_eq!(kind("entities"), Some(NbtTagKind::Compound));
+    assert_eq!(kind("sections[0]"), Some(NbtTagKind::Int));
+    assert_eq!(kind("data.ticks"), Some(NbtTagKind::Int));
+    assert_eq!(kind("data.unknown"), None);
+    assert_eq!(
+        nbt.get::<_, &NbtList>("sections").unwrap().element_kind(),
+        Some(NbtTagKind::List)
+    );
+
+    let deserialized: Chunk = deserialize(&bytes, Flavor::Uncompressed).unwrap().0;
+    assert_eq!(deserialized, chunk);
+
+    let mut unchecked = Vec::new();
+    chunk
+        .serialize(SerializerBuilder::new().build_unchecked(&mut unchecked, None))
+        .unwrap();
+    assert_eq!(unchecked, bytes);
+}
+
+#[test]
+fn strict_sequences() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Sequences {
+        bytes: Vec<i8>,
+        ints: Vec<i32>,
+        longs: Vec<i64>,
+    }
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Arrays {
+        bytes: Array<Vec<i8>>,
+        ints: Array<Vec<i32>>,
+        longs: Array<Vec<i64>>,
+    }
+
+    let as_arrays = compound! {
+        "bytes": NbtTag::ByteArray(vec![1, 2]),
+        "ints": NbtTag::IntArray(vec![3, 4]),
+        "longs": NbtTag::LongArray(vec![5, 6])
+    };
+    let as_lists = compound! {
+        "bytes": [1i8, 2i8],
+        "ints": [3i32, 4i32],
+        "longs": [5i64, 6i64]
+    };
+    let array_bytes = io::write_nbt_bytes(None, &as_arrays, Flavor::Uncompressed).unwrap();
+    let list_bytes = io::write_nbt_bytes(None, &as_lists, Flavor::Uncompressed).unwrap();
+
+    let sequences = Sequences {
+        bytes: vec![1, 2],
+        ints: vec![3, 4],
+        longs: vec![5, 6],
+    };
+    let arrays = Arrays {
+        bytes: Array::from(vec![1, 2]),
+        ints: Array::from(vec![3, 4]),
+        longs: Array::from(vec![5, 6]),
+    };
+
+    // Either representation is accepted by default
+    for bytes in [&array_bytes, &list_bytes] {
+        let (read, _) = deserialize::<Sequences>(bytes, Flavor::Uncompressed).unwrap();
+        assert_eq!(read, sequences);
+        let (read, _) = deserialize::<Arrays>(bytes, Flavor::Uncompressed).unwrap();
+        assert_eq!(read, arrays);
+    }
+
+    let strict = DeserializerBuilder::new().strict_sequences(true);
+    let (read, _) = strict
+        .deserialize::<Sequences>(&list_bytes, Flavor::Uncompressed)
+        .unwrap();
+    assert_eq!(read, sequences);
+    let (read, _) = strict
+        .deserialize::<Arrays>(&array_bytes, Flavor::Uncompressed)
+        .unwrap();
+    assert_eq!(read, arrays);
+
+    assert!(matches!(
+        strict.deserialize::<Sequences>(&array_bytes, Flavor::Uncompressed),
+        Err(NbtIoError::TagTypeMismatch {
+            expected: 0x9,
+            found: 0x7
+        })
+    ));
+    assert!(matches!(
+        strict.deserialize::<Arrays>(&list_bytes, Flavor::Uncompressed),
+        Err(NbtIoError::TagTypeMismatch {
+            expected: 0x7,
+            found: 0x9
+        })
+    ));
+
+    // Self-describing types are unaffected
+    let (read, _) = strict
+        .deserialize::<NbtCompound>(&array_bytes, Flavor::Uncompressed)
+        .unwrap();
+    assert_eq!(read, as_arrays);
+}
+
+#[test]
+fn number_conversion() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Stats {
+        health: f32,
+        level: i64,
+        count: i8,
+        ratio: i32,
+    }
+
+    let nbt = compound! {
+        "health": 20.5f64,
+        "level": 3i16,
+        "count": 300i32,
+        "ratio": 2.75f32
+    };
+    let bytes = io::write_nbt_bytes(None, &nbt, Flavor::Uncompressed).unwrap();
+
+    // Without a policy, serde only accepts integers which fit and never converts floats
+    assert!(deserialize::<Stats>(&bytes, Flavor::Uncompressed).is_err());
+
+    let (read, _) = DeserializerBuilder::new()
+        .conversion(ConversionPolicy::Lossy)
+        .deserialize::<Stats>(&bytes, Flavor::Uncompressed)
+        .unwrap();
+    assert_eq!(read, Stats {
+        health: 20.5,
+        level: 3,
+        count: 44,
+        ratio: 2
+    });
+
+    let (read, _) = DeserializerBuilder::new()
+        .conversion(ConversionPolicy::Saturate)
+        .deserialize::<Stats>(&bytes, Flavor::Uncompressed)
+        .unwrap();
+    assert_eq!(read.count, i8::MAX);
+
+    assert!(DeserializerBuilder::new()
+        .conversion(ConversionPolicy::Widen)
+        .deserialize::<Stats>(&bytes, Flavor::Uncompressed)
+        .is_err());
+    assert!(matches!(
+        DeserializerBuilder::new()
+            .conversion(ConversionPolicy::Strict)
+            .deserialize::<Stats>(&bytes, Flavor::Uncompressed),
+        Err(NbtIoError::TagTypeMismatch { .. })
+    ));
+
+    // Self-describing types are unaffected
+    let (read, _) = DeserializerBuilder::new()
+        .conversion(ConversionPolicy::Strict)
+        .deserialize::<NbtCompound>(&bytes, Flavor::Uncompressed)
+        .unwrap();
+    assert_eq!(read, nbt);
+}
+
+#[cfg(feature = "bytes")]
+#[test]
+fn deserialize_buf() {
+    use bytes::Buf;
+    use quartz_nbt::serde::deserialize_from_buf;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Position {
+        x: i32,
+        y: i32,
+    }
+
+    let position = Position { x: 3, y: -1 };
+    let mut bytes = serialize(&position, None, Flavor::GzCompressed).unwrap();
+    bytes.extend(serialize(&position, None, Flavor::Uncompressed).unwrap());
+
+    let mut buf = bytes.as_slice();
+    let (read, _) = deserialize_from_buf::<_, Position>(&mut buf, Flavor::GzCompressed).unwrap();
+    assert_eq!(read, position);
+    let (read, _) = deserialize_from_buf::<_, Position>(&mut buf, Flavor::Uncompressed).unwrap();
+    assert_eq!(read, position);
+    assert!(!buf.has_remaining());
+
+    // Builder options apply when reading from a buffer
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct WidePosition {
+        x: i64,
+        y: i64,
+    }
+
+    let bytes = serialize(&position, None, Flavor::ZlibCompressed).unwrap();
+    let builder = DeserializerBuilder::new().conversion(ConversionPolicy::Strict);
+    assert!(matches!(
+        builder
+            .deserialize_from_buf::<_, WidePosition>(&mut bytes.as_slice(), Flavor::ZlibCompressed),
+        Err(NbtIoError::TagTypeMismatch { .. })
+    ));
+    let (read, _) = builder
+        .conversion(ConversionPolicy::Widen)
+        .deserialize_from_buf::<_, WidePosition>(&mut bytes.as_slice(), Flavor::ZlibCompressed)
+        .unwrap();
+    assert_eq!(read, WidePosition { x: 3, y: -1 });
+}
+
 #[test]
 fn option_serde() {
     #[derive(Serialize, Deserialize, PartialEq, Debug)]
@@ -1015,3 +1360,630 @@ fn preserve_order() {
         }
     }
 }
+
+#[test]
+fn json_bools() {
+    let json = serde_json::json!({
+        "flags": [true, false],
+        "nested": { "enabled": true, "count": -1 },
+        "name": "test"
+    });
+    let mut expected = compound! {
+        "flags": [B; 1, 0],
+        "nested": { "enabled": 1i8, "count": -1i64 },
+        "name": "test"
+    };
+
+    // The default handling matches the Deserialize implementation
+    let compound: NbtCompound = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(compound, expected);
+    let implicit = TagSerdeOptions::new()
+        .deserialize_compound(json.clone())
+        .unwrap();
+    assert_eq!(implicit, expected);
+    let output = TagSerdeOptions::new()
+        .serialize_compound(&implicit, serde_json::value::Serializer)
+        .unwrap();
+    assert_eq!(output["flags"], serde_json::json!([1, 0]));
+
+    // Booleans are kept in a list so that they can be serialized as booleans again
+    let options = TagSerdeOptions::new().bools(BoolConversion::Bidirectional);
+    let compound = options.deserialize_compound(json.clone()).unwrap();
+    expected.insert("flags".to_owned(), NbtList::from(vec![1i8, 0i8]));
+    assert_eq!(compound, expected);
+    let output = options
+        .serialize_compound(&compound, serde_json::value::Serializer)
+        .unwrap();
+    assert_eq!(output, json);
+
+    let options = TagSerdeOptions::new().bools(BoolConversion::Error);
+    assert!(options.deserialize_compound(json).is_err());
+    assert!(options
+        .deserialize_compound(serde_json::json!({ "name": "test" }))
+        .is_ok());
+}
+
+#[test]
+fn raw_nbt() {
+    let (expected, _) = io::read_nbt(&mut &BIG_TEST[..], BIG_TEST_FLAVOR).unwrap();
+
+    // Every entry of the root is captured and can be decoded or written back on demand
+    let (entries, root_name) =
+        deserialize::<HashMap<String, Box<RawNbt>>>(BIG_TEST, BIG_TEST_FLAVOR).unwrap();
+    assert_eq!(entries.len(), expected.len());
+    for (key, raw) in &entries {
+        let tag = raw.to_tag().unwrap();
+        assert_eq!(&tag, expected.get::<_, &NbtTag>(key.as_str()).unwrap());
+        assert_eq!(raw.tag_id(), tag.kind().id());
+        assert_eq!(**raw, RawNbt::from_tag(&tag).unwrap());
+    }
+    assert!(entries["nested compound test"].to_compound().is_ok());
+    assert!(matches!(
+        entries["intTest"].to_compound(),
+        Err(NbtIoError::TagTypeMismatch {
+            expected: 0xA,
+            found: 0x3
+        })
+    ));
+
+    let bytes = serialize(&entries, Some(&root_name), Flavor::Uncompressed).unwrap();
+    let (written, _) = io::read_nbt(&mut &bytes[..], Flavor::Uncompressed).unwrap();
+    assert_eq!(written, expected);
+
+    // Raw tags in sequences are checked for homogeneity when written
+    let raw = |tag: NbtTag| RawNbt::from_tag(&tag).unwrap();
+    let list = HashMap::from([("list", vec![raw(1i32.into()), raw(2i32.into())])]);
+    let bytes = serialize(&list, None, Flavor::Uncompressed).unwrap();
+    let (written, _) = io::read_nbt(&mut &bytes[..], Flavor::Uncompressed).unwrap();
+    assert_eq!(written, compound! { "list": [1i32, 2i32] });
+
+    let mixed = HashMap::from([("list", vec![raw(1i32.into()), raw("a".into())])]);
+    assert!(matches!(
+        serialize(&mixed, None, Flavor::Uncompressed),
+        Err(NbtIoError::NonHomogenousList {
+            list_type: 0x3,
+            encountered_type: 0x8
+        })
+    ));
+
+    // Elements of lists can be captured individually
+    let (lists, _) = deserialize_from_buffer::<HashMap<String, Vec<RawNbt>>>(&bytes).unwrap();
+    assert_eq!(lists["list"], [raw(1i32.into()), raw(2i32.into())]);
+}
+
+#[test]
+fn other_formats() {
+    fn every_kind() -> NbtCompound {
+        compound! {
+            "byte": 1i8,
+            "short": -2i16,
+            "int": 3i32,
+            "long": i64::MIN,
+            "float": 0.5f32,
+            "double": -1.25f64,
+            "bytes": [B; 1, -2, 3],
+            "string": "text",
+            "list": [{ "a": 1i16 }, {}],
+            "empty_list": NbtList::new(),
+            "ints": [I; i32::MAX],
+            "longs": [L; -1, 0]
+        }
+    }
+
+    let compound = every_kind();
+    let value = TaggedValue(compound.clone());
+
+    let json = serde_json::to_string(&value).unwrap();
+    assert_compound_eq!(
+        serde_json::from_str::<TaggedValue<NbtCompound>>(&json)
+            .unwrap()
+            .0,
+        compound
+    );
+
+    let mut cbor = Vec::new();
+    ciborium::into_writer(&value, &mut cbor).unwrap();
+    assert_compound_eq!(
+        ciborium::from_reader::<TaggedValue<NbtCompound>, _>(cbor.as_slice())
+            .unwrap()
+            .0,
+        compound
+    );
+
+    let msgpack = rmp_serde::to_vec(&value).unwrap();
+    assert_compound_eq!(
+        rmp_serde::from_slice::<TaggedValue<NbtCompound>>(&msgpack)
+            .unwrap()
+            .0,
+        compound
+    );
+
+    // Tags and lists can be wrapped too
+    let tag = NbtTag::List(NbtList::from(vec![1.5f32, 2.0]));
+    let json = serde_json::to_string(&TaggedValue(tag.clone())).unwrap();
+    assert_eq!(json, r#"{"List":[{"Float":1.5},{"Float":2.0}]}"#);
+    assert_eq!(
+        serde_json::from_str::<TaggedValue<NbtTag>>(&json)
+            .unwrap()
+            .0,
+        tag
+    );
+    assert!(serde_json::from_str::<TaggedValue<NbtTag>>(r#"{"Char":1}"#).is_err());
+    assert!(serde_json::from_str::<TaggedValue<NbtTag>>(r#"{"Byte":300}"#).is_err());
+
+    // Without the wrapper, integers only keep their value
+    let compound =
+        compound! { "short": 5i16, "negative": -5i16, "double": 0.5f64, "string": "text" };
+    let json = serde_json::to_string(&compound).unwrap();
+    assert_compound_eq!(
+        serde_json::from_str::<NbtCompound>(&json).unwrap(),
+        compound! { "short": 5i64, "negative": -5i64, "double": 0.5f64, "string": "text" }
+    );
+    let msgpack = rmp_serde::to_vec(&compound).unwrap();
+    assert_compound_eq!(
+        rmp_serde::from_slice::<NbtCompound>(&msgpack).unwrap(),
+        compound! { "short": 5i8, "negative": -5i8, "double": 0.5f64, "string": "text" }
+    );
+
+    assert_eq!(
+        serde_json::from_str::<NbtTag>("5").unwrap(),
+        NbtTag::Long(5)
+    );
+    assert!(serde_json::from_str::<NbtTag>(&u64::MAX.to_string()).is_err());
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn unchecked_debug_checks() {
+    #[derive(Serialize)]
+    #[serde(untagged)]
+    enum Value {
+        Int(i32),
+        Text(&'static str),
+    }
+
+    #[derive(Serialize)]
+    struct Outer {
+        entries: Vec<HashMap<&'static str, Vec<Value>>>,
+    }
+
+    let outer = Outer {
+        entries: vec![
+            HashMap::from([("fine", vec![Value::Int(1), Value::Int(2)])]),
+            HashMap::from([("odd key", vec![Value::Text("a"), Value::Int(2)])]),
+        ],
+    };
+
+    let mut bytes = Vec::new();
+    let result = outer.serialize(
+        SerializerBuilder::new()
+            .debug_checks(true)
+            .build_unchecked(&mut bytes, None),
+    );
+    match result {
+        Err(NbtIoError::NonHomogenousField {
+            path,
+            list_type,
+            encountered_type,
+        }) => {
+            assert_eq!(path, r#"entries[1]."odd key"[1]"#);
+            assert_eq!((list_type, encountered_type), (0x8, 0x3));
+        }
+        result => panic!("unexpected result: {:?}", result),
+    }
+
+    // The checks are off by default
+    let mut bytes = Vec::new();
+    assert!(outer
+        .serialize(SerializerBuilder::new().build_unchecked(&mut bytes, None))
+        .is_ok());
+
+    // The checked serializer is unaffected
+    assert!(matches!(
+        serialize(&outer, None, Flavor::Uncompressed),
+        Err(NbtIoError::NonHomogenousList {
+            list_type: 0x8,
+            encountered_type: 0x3
+        })
+    ));
+}
+
+#[test]
+fn root_options_and_units() {
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Heartbeat;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Player {
+        name: String,
+    }
+
+    let empty = serialize(&compound! {}, Some("root"), Flavor::Uncompressed).unwrap();
+    assert_eq!(
+        serialize(&(), Some("root"), Flavor::Uncompressed).unwrap(),
+        empty
+    );
+    assert_eq!(
+        serialize(&Heartbeat, Some("root"), Flavor::Uncompressed).unwrap(),
+        empty
+    );
+    assert_eq!(
+        serialize(&None::<Player>, Some("root"), Flavor::Uncompressed).unwrap(),
+        empty
+    );
+
+    let player = Player {
+        name: "Steve".to_owned(),
+    };
+    let bytes = serialize(&Some(&player), None, Flavor::Uncompressed).unwrap();
+    assert_eq!(
+        bytes,
+        serialize(&player, None, Flavor::Uncompressed).unwrap()
+    );
+    assert_eq!(
+        deserialize::<Option<Player>>(&bytes, Flavor::Uncompressed)
+            .unwrap()
+            .0,
+        Some(player)
+    );
+
+    // Units ignore the contents of the root compound
+    assert_eq!(
+        deserialize::<Heartbeat>(&empty, Flavor::Uncompressed)
+            .unwrap()
+            .0,
+        Heartbeat
+    );
+    deserialize::<()>(&bytes, Flavor::Uncompressed).unwrap();
+
+    // Other non-compound types are still rejected
+    assert!(matches!(
+        serialize(&5i32, None, Flavor::Uncompressed),
+        Err(NbtIoError::MissingRootTag)
+    ));
+    assert!(matches!(
+        serialize(&Some(5i32), None, Flavor::Uncompressed),
+        Err(NbtIoError::MissingRootTag)
+    ));
+}
+
+#[test]
+fn human_readable() {
+    use std::net::{IpAddr, Ipv6Addr};
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Server {
+        address: IpAddr,
+        fallbacks: Vec<IpAddr>,
+    }
+
+    let server = Server {
+        address: IpAddr::V6(Ipv6Addr::LOCALHOST),
+        fallbacks: vec!["10.0.0.1".parse().unwrap(), "::2".parse().unwrap()],
+    };
+
+    // The compact representation round-trips by default
+    let bytes = serialize(&server, None, Flavor::Uncompressed).unwrap();
+    let (nbt, _) = io::read_nbt(&mut bytes.as_slice(), Flavor::Uncompressed).unwrap();
+    assert!(nbt.get::<_, &NbtCompound>("address").is_ok());
+    assert_eq!(
+        deserialize::<Server>(&bytes, Flavor::Uncompressed)
+            .unwrap()
+            .0,
+        server
+    );
+
+    let bytes = SerializerBuilder::new()
+        .human_readable(true)
+        .serialize(&server, None, Flavor::Uncompressed)
+        .unwrap();
+    let (nbt, _) = io::read_nbt(&mut bytes.as_slice(), Flavor::Uncompressed).unwrap();
+    assert_compound_eq!(nbt, compound! {
+        "address": "::1",
+        "fallbacks": ["10.0.0.1", "::2"]
+    });
+    assert_eq!(
+        DeserializerBuilder::new()
+            .human_readable(true)
+            .deserialize::<Server>(&bytes, Flavor::Uncompressed)
+            .unwrap()
+            .0,
+        server
+    );
+    assert!(deserialize::<Server>(&bytes, Flavor::Uncompressed).is_err());
+}
+
+#[test]
+fn sorted_maps() {
+    #[derive(Serialize)]
+    struct Datapack {
+        name: &'static str,
+        tags: HashMap<String, HashMap<String, i32>>,
+        loot: Vec<HashMap<String, i8>>,
+        extra: NbtCompound,
+    }
+
+    let keys = [
+        "zeta", "alpha", "mu", "beta", "omega", "Gamma", "épsilon", "_",
+    ];
+    let datapack = || Datapack {
+        name: "pack",
+        tags: keys
+            .iter()
+            .map(|&outer| {
+                let inner = keys.iter().map(|&k| (k.to_owned(), k.len() as i32));
+                (outer.to_owned(), inner.collect())
+            })
+            .collect(),
+        loot: vec![keys.iter().map(|&k| (k.to_owned(), 1i8)).collect()],
+        extra: keys
+            .iter()
+            .map(|&k| (k.to_owned(), NbtTag::Byte(0)))
+            .collect(),
+    };
+
+    let builder = SerializerBuilder::new().sort_maps(true);
+    let bytes = builder
+        .serialize(&datapack(), Some("root"), Flavor::Uncompressed)
+        .unwrap();
+    for _ in 0 .. 16 {
+        assert_eq!(
+            builder
+                .serialize(&datapack(), Some("root"), Flavor::Uncompressed)
+                .unwrap(),
+            bytes
+        );
+    }
+
+    // The same data is written as without sorting
+    let unsorted = serialize(&datapack(), Some("root"), Flavor::Uncompressed).unwrap();
+    assert_eq!(bytes.len(), unsorted.len());
+    let (nbt, root_name, spans) =
+        io::read_nbt_with_spans(&mut bytes.as_slice(), Flavor::Uncompressed).unwrap();
+    assert_eq!(root_name, "root");
+    assert_eq!(
+        nbt,
+        io::read_nbt(&mut unsorted.as_slice(), Flavor::Uncompressed)
+            .unwrap()
+            .0
+    );
+
+    // Struct fields keep their order, and map entries are sorted at every level
+    let mut sorted_keys = keys;
+    sorted_keys.sort_unstable();
+    let start = |path: String| spans.get(&path).unwrap().start;
+    assert!(start("name".into()) < start("tags".into()));
+    assert!(start("tags".into()) < start("loot".into()));
+    assert!(start("loot".into()) < start("extra".into()));
+    for prefix in ["tags.mu.", "tags.", "loot[0].", "extra."] {
+        let starts = sorted_keys
+            .iter()
+            .map(|key| start(format!("{}{}", prefix, escape_key(key))))
+            .collect::<Vec<_>>();
+        assert!(starts.windows(2).all(|w| w[0] < w[1]), "{}", prefix);
+    }
+
+    // Errors within buffered entries are still located
+    #[derive(Serialize)]
+    #[serde(untagged)]
+    enum Mixed {
+        Int(i32),
+        Str(&'static str),
+    }
+    let map = HashMap::from([("key", vec![Mixed::Int(1), Mixed::Str("x")])]);
+    let mut bytes = Vec::new();
+    let result = map.serialize(
+        SerializerBuilder::new()
+            .sort_maps(true)
+            .debug_checks(true)
+            .build_unchecked(&mut bytes, None),
+    );
+    if cfg!(debug_assertions) {
+        assert!(matches!(
+            result,
+            Err(NbtIoError::NonHomogenousField { path, .. }) if path == "key[1]"
+        ));
+    }
+}
+
+// A sequence which claims to have more elements than NBT can represent
+struct OversizedSeq;
+
+impl Serialize for OversizedSeq {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeSeq;
+        serializer.serialize_seq(Some(i32::MAX as usize + 1))?.end()
+    }
+}
+
+#[test]
+fn oversized_lengths() {
+    #[derive(Serialize)]
+    struct Voxels {
+        list: OversizedSeq,
+    }
+
+    let too_large = i32::MAX as usize + 1;
+    assert!(matches!(
+        serialize(&Voxels { list: OversizedSeq }, None, Flavor::Uncompressed),
+        Err(NbtIoError::LengthTooLarge(len)) if len == too_large
+    ));
+}
+
+#[test]
+fn key_transform_parity() {
+    let shorten = |key: &str| match key {
+        "nested compound test" => Some("nested".to_owned()),
+        _ => Some(key.to_lowercase().replace(' ', "_")),
+    };
+    let (from_io, io_root_name, mut io_renamed) =
+        io::read_nbt_with_key_transform(&mut Cursor::new(BIG_TEST), BIG_TEST_FLAVOR, &shorten)
+            .unwrap();
+    let (from_serde, serde_root_name, mut serde_renamed) = DeserializerBuilder::new()
+        .deserialize_from_with_key_transform::<_, NbtCompound, _>(
+            &mut Cursor::new(BIG_TEST),
+            BIG_TEST_FLAVOR,
+            &shorten,
+        )
+        .unwrap();
+
+    assert_compound_eq!(&from_io, &from_serde);
+    assert_eq!(io_root_name, serde_root_name);
+    assert!(from_io.contains_key("nested"));
+    assert!(from_io.contains_key("longtest"));
+
+    io_renamed.sort_by(|a, b| a.path().cmp(b.path()));
+    serde_renamed.sort_by(|a, b| a.path().cmp(b.path()));
+    assert_eq!(io_renamed, serde_renamed);
+    assert!(io_renamed
+        .iter()
+        .any(|key| key.path() == "nested" && key.original() == "nested compound test"));
+
+    // Keys produced by the transform are subject to the usual length limit when rewritten
+    let bytes = io::write_nbt_bytes(None, &compound! { "a": 1i8 }, Flavor::Uncompressed).unwrap();
+    let result = DeserializerBuilder::new()
+        .deserialize_from_with_key_transform::<_, NbtCompound, _>(
+            &mut bytes.as_slice(),
+            Flavor::Uncompressed,
+            &|_: &str| Some("a".repeat(u16::MAX as usize + 1)),
+        );
+    assert!(matches!(result, Err(NbtIoError::StringTooLong(_))));
+}
+
+#[test]
+fn fixed_arrays() {
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Fixed {
+        signed: FixedArray<i8, 2>,
+        unsigned: FixedArray<u8, 3>,
+        ints: FixedArray<i32, 4>,
+        longs: FixedArray<i64, 1>,
+        empty: FixedArray<i32, 0>,
+    }
+
+    let fixed = Fixed {
+        signed: FixedArray::from([-1, 2]),
+        unsigned: FixedArray::from([0, 128, 255]),
+        ints: FixedArray::from([1, -2, i32::MIN, i32::MAX]),
+        longs: FixedArray::from([i64::MIN]),
+        empty: FixedArray::from([]),
+    };
+    let validation = compound! {
+        "signed": [B; -1, 2],
+        "unsigned": [B; 0, -128, -1],
+        "ints": [I; 1, -2, i32::MIN, i32::MAX],
+        "longs": [L; i64::MIN],
+        "empty": [I;]
+    };
+
+    let bytes = serialize(&fixed, None, Flavor::Uncompressed).unwrap();
+    let (nbt, _) = io::read_nbt(&mut Cursor::new(&bytes), Flavor::Uncompressed).unwrap();
+    assert_compound_eq!(nbt, validation);
+    let (read, _) = deserialize::<Fixed>(&bytes, Flavor::Uncompressed).unwrap();
+    assert_eq!(read, fixed);
+    assert_eq!(FixedArray::into_inner(read.ints), [
+        1,
+        -2,
+        i32::MIN,
+        i32::MAX
+    ]);
+
+    let error = |nbt: &NbtCompound, strict: bool| {
+        let bytes = io::write_nbt_bytes(None, nbt, Flavor::Uncompressed).unwrap();
+        DeserializerBuilder::new()
+            .strict_sequences(strict)
+            .deserialize::<Fixed>(&bytes, Flavor::Uncompressed)
+            .err()
+            .unwrap()
+    };
+    let with = |key: &str, tag: NbtTag| {
+        let mut nbt = validation.clone();
+        nbt.insert(key, tag);
+        nbt
+    };
+
+    // Lengths are checked before reading any elements, in both directions
+    for (nbt, message) in [
+        (
+            with("ints", NbtTag::IntArray(vec![1, 2, 3])),
+            "invalid length 3, expected an int array of length 4",
+        ),
+        (
+            with("signed", NbtTag::ByteArray(vec![1, 2, 3])),
+            "invalid length 3, expected a byte array of length 2",
+        ),
+        (
+            with("longs", NbtTag::LongArray(vec![])),
+            "invalid length 0, expected a long array of length 1",
+        ),
+        (
+            with("empty", NbtTag::IntArray(vec![1])),
+            "invalid length 1, expected an int array of length 0",
+        ),
+        (
+            with("ints", NbtTag::List(NbtList::from(vec![1i32, 2, 3, 4, 5]))),
+            "invalid length 5, expected an int array of length 4",
+        ),
+    ] {
+        assert_eq!(error(&nbt, false).to_string(), message);
+    }
+
+    // Lists of the right length are accepted unless sequences are strict
+    let as_list = with(
+        "ints",
+        NbtTag::List(NbtList::from(vec![1i32, -2, i32::MIN, i32::MAX])),
+    );
+    let bytes = io::write_nbt_bytes(None, &as_list, Flavor::Uncompressed).unwrap();
+    assert_eq!(
+        deserialize::<Fixed>(&bytes, Flavor::Uncompressed)
+            .unwrap()
+            .0,
+        fixed
+    );
+    assert!(matches!(
+        error(&as_list, true),
+        NbtIoError::TagTypeMismatch {
+            expected: 0xB,
+            found: 0x9
+        }
+    ));
+    assert!(matches!(
+        error(&with("ints", NbtTag::LongArray(vec![1, 2, 3, 4])), true),
+        NbtIoError::TagTypeMismatch {
+            expected: 0xB,
+            found: 0xC
+        }
+    ));
+
+    // Deserializers which do not know the length up front report the same lengths
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Uuid {
+        uuid: FixedArray<i32, 4>,
+    }
+
+    let uuid = Uuid {
+        uuid: FixedArray::from([1, -2, i32::MIN, i32::MAX]),
+    };
+    let json = serde_json::to_value(&uuid).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({ "uuid": [1, -2, i32::MIN, i32::MAX] })
+    );
+    assert_eq!(serde_json::from_value::<Uuid>(json).unwrap(), uuid);
+    for (json, found) in [
+        serde_json::json!({ "uuid": [1, 2, 3] }),
+        serde_json::json!({ "uuid": [1, 2, 3, 4, 5, 6] }),
+    ]
+    .into_iter()
+    .zip([3, 6])
+    {
+        let error = serde_json::from_value::<Uuid>(json).err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "invalid length {}, expected an int array of length 4",
+                found
+            )
+        );
+    }
+}
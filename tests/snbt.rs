@@ -1,7 +1,27 @@
 mod assets;
 use assets::*;
-use quartz_nbt::{snbt, NbtCompound};
+use flate2::{write::GzEncoder, Compression};
+use quartz_nbt::{
+    assert_compound_eq,
+    io::{Flavor, NbtIoError},
+    path::{self, ListPadding, NbtPath, PathConflict},
+    snbt::{
+        self,
+        DisplayOptions,
+        FormatOptions,
+        ParseOptions,
+        ParserErrorType,
+        Position,
+        SnbtTemplate,
+    },
+    NbtCompound,
+    NbtList,
+    NbtTag,
+    NbtTagKind,
+    TagLookup,
+};
 use quartz_nbt_macros::compound;
+use std::io::{Cursor, Write};
 
 #[test]
 fn edge_cases() {
@@ -34,6 +54,19 @@ fn big_test() {
     );
 }
 
+#[test]
+fn compressed_reader() {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(SNBT_EDGE_CASES.as_bytes()).unwrap();
+    let compressed = encoder.finish().unwrap();
+
+    let nbt = snbt::parse_reader(&mut compressed.as_slice(), Flavor::GzCompressed).unwrap();
+    assert_compound_eq!(&nbt, &*SNBT_EDGE_CASES_VALIDATE);
+
+    let nbt = snbt::parse_reader(&mut SNBT_EDGE_CASES.as_bytes(), Flavor::Uncompressed).unwrap();
+    assert_compound_eq!(&nbt, &*SNBT_EDGE_CASES_VALIDATE);
+}
+
 #[test]
 fn formatting() {
     let repr = format!("{:+#.2?}", &*SNBT_EDGE_CASES_VALIDATE);
@@ -68,6 +101,133 @@ fn number_like_strings() {
     assert_round_trip_same(compound! { "str": "-0" });
     assert_round_trip_same(compound! { "str": "0.5" });
     assert_round_trip_same(compound! { "str": "-0.5" });
+    assert_round_trip_same(compound! { "str": "true" });
+    assert_round_trip_same(compound! { "str": "False" });
+}
+
+#[test]
+fn bool_literals() {
+    let nbt = snbt::parse("{a: true, b: FALSE, c: 'true', d: [true, 2b]}").unwrap();
+    assert_eq!(
+        nbt,
+        compound! { "a": 1i8, "b": 0i8, "c": "true", "d": [1i8, 2i8] }
+    );
+
+    let options = FormatOptions::new().bool_literals(true);
+    assert_eq!(snbt::parse(&nbt.to_snbt_with(options)).unwrap(), nbt);
+    assert_eq!(
+        snbt::parse(&nbt.to_snbt_with(options.pretty(true))).unwrap(),
+        nbt
+    );
+    assert_eq!(nbt.to_snbt_with(FormatOptions::new()), nbt.to_snbt());
+
+    let nbt = compound! { "d": [1i8, 0i8, 2i8] };
+    assert_eq!(nbt.to_snbt_with(options), "{d:[true,false,2B]}");
+    assert_eq!(
+        nbt.to_snbt_with(options.pretty(true)),
+        "{\n    d: [\n        true,\n        false,\n        2B\n    ]\n}"
+    );
+    assert_eq!(
+        compound! { "c": "true" }.to_snbt_with(options),
+        "{c:\"true\"}"
+    );
+}
+
+#[test]
+fn display_strings() {
+    let options = DisplayOptions::new();
+    for (tag, expected) in [
+        (NbtTag::Byte(-128), "-128B"),
+        (NbtTag::Short(1000), "1,000S"),
+        (NbtTag::Int(999), "999"),
+        (NbtTag::Int(i32::MIN), "-2,147,483,648"),
+        (NbtTag::Long(i64::MAX), "9,223,372,036,854,775,807L"),
+        (NbtTag::Float(12345.125), "12,345.125F"),
+        (NbtTag::Double(-0.5), "-0.5D"),
+        (NbtTag::Double(1e6), "1,000,000D"),
+        (NbtTag::Double(f64::NEG_INFINITY), "-infD"),
+        (NbtTag::String("1234567".to_owned()), "\"1234567\""),
+    ] {
+        assert_eq!(tag.to_display_string(options), expected);
+    }
+
+    let options = options.group_separator('\u{202F}').decimal_separator(',');
+    assert_eq!(
+        NbtTag::Float(-1234.5).to_display_string(options),
+        "-1\u{202F}234,5F"
+    );
+
+    // Containers use the pretty layout, and their keys are left alone
+    let nbt = compound! { "12345": [12345i32] };
+    assert_eq!(
+        nbt.to_display_string(options),
+        "{\n    \"12345\": [\n        12\u{202F}345\n    ]\n}"
+    );
+    let list = NbtList::from(vec![1000i64, 10i64]);
+    assert_eq!(
+        list.to_display_string(DisplayOptions::new()),
+        "[\n    1,000L,\n    10L\n]"
+    );
+
+    // SNBT is unaffected
+    assert_eq!(nbt.to_snbt(), "{\"12345\":[12345]}");
+    assert_eq!(NbtTag::Short(1000).to_pretty_snbt(), "1000S");
+}
+
+#[test]
+fn canonical_form() {
+    use std::{collections::hash_map::DefaultHasher, hash::Hasher};
+
+    fn hash(compound: &NbtCompound) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        compound.canonical_snbt_hash(&mut hasher);
+        hasher.finish()
+    }
+
+    let canonical = SNBT_EDGE_CASES_VALIDATE.to_canonical_snbt();
+    assert_eq!(snbt::parse(&canonical).unwrap(), *SNBT_EDGE_CASES_VALIDATE);
+    let mut hasher = DefaultHasher::new();
+    hasher.write(canonical.as_bytes());
+    assert_eq!(hash(&SNBT_EDGE_CASES_VALIDATE), hasher.finish());
+
+    // Insertion order does not matter at any depth, including within lists of compounds
+    let mut a = NbtCompound::new();
+    let mut b = NbtCompound::new();
+    for (index, key) in ["z", "a", "m", "B"].iter().enumerate() {
+        let nested = NbtTag::List(NbtList::from(vec![NbtTag::Compound(compound! {
+            "q": index as i32,
+            "p": [B; 1]
+        })]));
+        a.insert(*key, nested.clone());
+        b.insert(*key, nested);
+    }
+    let b = b
+        .into_sorted_entries()
+        .into_iter()
+        .rev()
+        .collect::<NbtCompound>();
+    assert_eq!(a.to_canonical_snbt(), b.to_canonical_snbt());
+    assert_eq!(hash(&a), hash(&b));
+    assert!(a
+        .to_canonical_snbt()
+        .starts_with("{B:[{p:[B;1],q:3}],a:[{p:[B;1],q:1}],"));
+
+    // Different values hash differently
+    assert_ne!(
+        hash(&compound! { "a": 1i32 }),
+        hash(&compound! { "a": 1i64 })
+    );
+    assert_ne!(
+        hash(&compound! { "a": "b" }),
+        hash(&compound! { "a": { "b": "" } })
+    );
+
+    // Sorting also applies to pretty output
+    let options = FormatOptions::new().pretty(true).sort_keys(true);
+    assert_eq!(
+        compound! { "b": 1i32, "a": { "d": 2i32, "c": 3i32 } }.to_snbt_with(options),
+        "{\n    a: {\n        c: 3,\n        d: 2\n    },\n    b: 1\n}"
+    );
 }
 
 #[test]
@@ -80,3 +240,1340 @@ fn empty_string_value() {
 
     assert_eq!(quartz_nbt::snbt::parse(&repr).unwrap(), tag);
 }
+
+#[test]
+fn duplicate_keys() {
+    let input = "{a: 1, b: {a: 2, 'a': 3}, \"b\": 4, A: 5, a: 6, a: 7}";
+    let (nbt, duplicates) = snbt::parse_with_duplicate_keys(input, ParseOptions::new()).unwrap();
+    assert_eq!(nbt, snbt::parse(input).unwrap());
+    assert_eq!(nbt, compound! { "a": 7i32, "b": 4i32, "A": 5i32 });
+
+    let spans = duplicates
+        .iter()
+        .map(|duplicate| {
+            (
+                duplicate.key(),
+                &input[duplicate.first()],
+                &input[duplicate.second()],
+                duplicate.first().start,
+                duplicate.second().start,
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(spans, [
+        ("a", "a", "'a'", 11, 17),
+        ("b", "b", "\"b\"", 7, 26),
+        ("a", "a", "a", 1, 40),
+        ("a", "a", "a", 40, 46),
+    ]);
+
+    let (_, duplicates) =
+        snbt::parse_with_duplicate_keys("{a: {x: 1}, b: {x: 2}}", ParseOptions::new()).unwrap();
+    assert!(duplicates.is_empty());
+
+    let options = ParseOptions::new().deny_duplicate_keys(true);
+    let error = snbt::parse_with("{\n    名前: 1,\n    名前: 2\n}", options).unwrap_err();
+    assert_eq!(error.position(), Some(Position { line: 3, column: 5 }));
+    assert!(matches!(
+        error.error_type(),
+        ParserErrorType::DuplicateKey {
+            first: Position { line: 2, column: 5 },
+            ..
+        }
+    ));
+    assert!(snbt::parse_with("{a: 1, b: {a: 2}}", options).is_ok());
+
+    let mut lines = snbt::parse_lines_with("{a: 1}\n{a: 1, a: 2}".as_bytes(), options);
+    assert!(lines.next().unwrap().is_ok());
+    assert_eq!(
+        lines.next().unwrap().unwrap_err().to_string(),
+        "Duplicate key at 2:8 near 'a: 1, a', first defined at 2:2"
+    );
+}
+
+#[test]
+fn multibyte_errors() {
+    // Columns count characters rather than bytes, and segments never split a character
+    for (input, message) in [
+        (
+            "{😀😀:;}",
+            "Unexpected token at 1:5 near '{😀😀:;', expected value",
+        ),
+        ("{名前:1,}", "Trailing comma at 1:6: '{名前:1,}'"),
+        ("{a:'中文", "Unmatched quote at 1:4 near '{a:'中文'"),
+        ("{a:'\\😀'}", "Unknown escape sequence at 1:5: '\\😀'"),
+        (
+            "{中:'\\uD800'}",
+            "Unknown escape sequence at 1:5: '\\uD800'",
+        ),
+        (
+            "{😀:[1,'中']}",
+            "Non-homogenous typed list at 1:7 near '{😀:[1,'中''",
+        ),
+        (
+            "{é:[B;中]}",
+            "Non-homogenous typed list at 1:7 near '{é:[B;中'",
+        ),
+        (
+            "{键:{值:[}}",
+            "Unexpected token at 1:8 near '{键:{值:[}', expected value",
+        ),
+    ] {
+        assert_eq!(snbt::parse(input).unwrap_err().to_string(), message);
+    }
+}
+
+#[test]
+fn multiline_errors() {
+    let input = "{\n    名前: \"Steve\",\n    level: ;,\n    items: []\n}";
+    let error = snbt::parse(input).unwrap_err();
+    assert_eq!(
+        error.position(),
+        Some(Position {
+            line: 3,
+            column: 12
+        })
+    );
+    assert_eq!(
+        error.to_string(),
+        "Unexpected token at 3:12 near '    level: ;', expected value"
+    );
+
+    let error = snbt::parse("{\n    a: [1, 2,\n]}").unwrap_err();
+    assert_eq!(error.position(), Some(Position { line: 3, column: 1 }));
+
+    let error = snbt::parse("{\n    a: 1").unwrap_err();
+    assert_eq!(error.to_string(), "Unmatched brace at 1:1 near '{'");
+    assert_eq!(snbt::parse("{\n    a:").unwrap_err().position(), None);
+
+    for (offset, line, column) in [(0, 1, 1), (1, 1, 2), (2, 2, 1), (7, 2, 5), (12, 2, 7)] {
+        assert_eq!(Position::from_offset(input, offset), Position {
+            line,
+            column
+        });
+    }
+}
+
+#[test]
+fn path_filters() {
+    let nbt = compound! {
+        "Inventory": [
+            { "Slot": 0i8, "id": "minecraft:stone", "tag": { "Tags": ["a", "b"] } },
+            { "Slot": 1i8, "id": "minecraft:dirt" }
+        ],
+        "odd key.name": { "value": 5i32 },
+        "OnGround": 1i8
+    };
+
+    let select = |path: &str| NbtPath::parse(path).unwrap().select(&nbt);
+
+    assert_eq!(select("Inventory[{Slot:0b}].id"), vec![&NbtTag::from(
+        "minecraft:stone"
+    )]);
+    assert_eq!(select("Inventory[{tag:{Tags:[\"b\"]}}].Slot"), vec![
+        &NbtTag::Byte(0)
+    ]);
+    assert!(select("Inventory[{Slot:0}].id").is_empty());
+    assert_eq!(select("\"odd key.name\".value"), vec![&NbtTag::Int(5)]);
+    assert_eq!(select("'odd key.name'{value:5}.value"), vec![&NbtTag::Int(
+        5
+    )]);
+    assert_eq!(select("{OnGround:1b}.Inventory[1].Slot"), vec![
+        &NbtTag::Byte(1)
+    ]);
+    assert!(select("{OnGround:0b}.Inventory").is_empty());
+    assert!(select("Inventory[5]").is_empty());
+    assert!(select("Inventory[-3]").is_empty());
+
+    for (path, index) in [
+        ("", 0),
+        ("Inventory.", 10),
+        ("Inventory[x]", 10),
+        ("Inventory[{Slot:}]", 10),
+        ("Inventory]", 9),
+        ("\"unterminated", 13),
+        ("{OnGround:1b}", 13),
+    ] {
+        let error = NbtPath::parse(path).unwrap_err();
+        assert_eq!(error.index(), index, "{}: {}", path, error);
+    }
+}
+
+#[test]
+fn insert_paths() {
+    let mut player = compound! {
+        "Inventory": [
+            { "Slot": 0i8, "id": "minecraft:stone" },
+            { "Slot": 1i8, "id": "minecraft:dirt" }
+        ],
+        "Pos": [1.0f64, 2.0f64, 3.0f64]
+    };
+
+    player.insert_path("Inventory[].Count", 1i8).unwrap();
+    player
+        .insert_path("Inventory[-1].tag.Damage", 5i32)
+        .unwrap();
+    player.insert_path("Pos[1]", 64.0f64).unwrap();
+    player
+        .insert_path("{Pos:[1.0d]}.Abilities{flying:0b}.flying", true)
+        .unwrap();
+    player
+        .insert_path_with("Tags[1]", "b", ListPadding::Fill)
+        .unwrap();
+    assert_eq!(player, compound! {
+        "Inventory": [
+            { "Slot": 0i8, "id": "minecraft:stone", "Count": 1i8 },
+            { "Slot": 1i8, "id": "minecraft:dirt", "Count": 1i8, "tag": { "Damage": 5i32 } }
+        ],
+        "Pos": [1.0f64, 64.0f64, 3.0f64],
+        "Abilities": { "flying": 1i8 },
+        "Tags": ["", "b"]
+    });
+
+    let unchanged = player.clone();
+    for path in [
+        "Pos[3]",
+        "Pos[-4]",
+        "{Pos:[5.0d]}.x",
+        "Abilities{flying:0b}.x",
+        "Inventory.x",
+        "Abilities[0]",
+        "Inventory[]",
+        "Inventory[",
+    ] {
+        assert!(player.insert_path(path, 1.0f64).is_err(), "{}", path);
+    }
+    assert!(player.insert_path("Pos[0]", 1i32).is_err());
+    assert!(player
+        .insert_path_with("Tags[3]", 1i32, ListPadding::Fill)
+        .is_err());
+    let index = 2 + path::MAX_LIST_PADDING;
+    assert!(player
+        .insert_path_with(&format!("Tags[{}]", index), "c", ListPadding::Fill)
+        .is_err());
+    assert_eq!(player, unchanged);
+
+    player
+        .insert_path_with(&format!("Tags[{}]", index - 1), "c", ListPadding::Fill)
+        .unwrap();
+    assert_eq!(player.get::<_, &NbtList>("Tags").unwrap().len(), index);
+}
+
+#[test]
+fn remove_paths() {
+    let mut level = compound! {
+        "Data": {
+            "Player": {
+                "Tags": ["a", "b", "c"],
+                "Abilities": { "flying": 0i8 }
+            },
+            "Version": 3i32
+        }
+    };
+
+    assert_eq!(
+        level.remove_path("Data.Player.Tags[-1]").unwrap(),
+        NbtTag::from("c")
+    );
+    assert!(level.remove_path("Data.Player.Tags[]").is_err());
+    assert!(level.remove_path("Data.Player.Tags[2]").is_err());
+    assert!(level.remove_path("Data.Player.Tags[").is_err());
+    assert!(level.remove_path("{Data:{Version:4}}.Data").is_err());
+    assert_eq!(
+        level
+            .take_path("{Data:{Version:3}}.Data.Player.Tags[]", false)
+            .unwrap(),
+        vec![NbtTag::from("a"), NbtTag::from("b")]
+    );
+    assert!(level
+        .take_path("Data.Player.Missing.x", true)
+        .unwrap()
+        .is_empty());
+    assert!(level
+        .take_path("Data.Player.Abilities{flying:1b}.flying", true)
+        .unwrap()
+        .is_empty());
+    assert_eq!(level, compound! {
+        "Data": {
+            "Player": { "Tags": [], "Abilities": { "flying": 0i8 } },
+            "Version": 3i32
+        }
+    });
+
+    level
+        .take_path("Data.Player.Abilities.flying", true)
+        .unwrap();
+    assert_eq!(level, compound! {
+        "Data": { "Player": { "Tags": [] }, "Version": 3i32 }
+    });
+}
+
+#[test]
+fn escaped_path_keys() {
+    let keys = [
+        "weird.key",
+        "[0]",
+        "{a:1}",
+        "with space",
+        "it's",
+        "say \"hi\"",
+        "back\\slash",
+        "",
+        "minecraft:stone",
+        "plain_key-1+",
+    ];
+
+    let mut nbt = NbtCompound::new();
+    for key in keys {
+        let path = format!("root.{}[0]", path::escape_key(key));
+        nbt.insert_path_with(&path, key, ListPadding::Fill).unwrap();
+        assert_eq!(nbt.get_path(&path).unwrap(), &NbtTag::from(key), "{}", path);
+
+        let parsed = NbtPath::parse(&path).unwrap();
+        assert_eq!(parsed.to_string(), path);
+        assert_eq!(nbt.get_path(&parsed).unwrap(), &NbtTag::from(key));
+    }
+
+    let root = nbt.get::<_, &NbtCompound>("root").unwrap();
+    assert_eq!(root.len(), keys.len());
+    for key in keys {
+        assert_eq!(root.get::<_, &NbtList>(key).unwrap().len(), 1);
+    }
+
+    // Single quotes work as well, and may escape either kind of quote
+    assert_eq!(
+        nbt.remove_path("root.'it\\'s'[0]").unwrap(),
+        NbtTag::from("it's")
+    );
+    assert_eq!(
+        nbt.remove_path("root.'say \\\"hi\\\"'[0]").unwrap(),
+        NbtTag::from("say \"hi\"")
+    );
+
+    // Unquoted keys end at dots and brackets
+    assert!(nbt.get_path("root.weird.key[0]").is_err());
+    assert!(nbt.get_path("root.[0][0]").is_err());
+    assert!(NbtPath::parse("root.\"unterminated").is_err());
+    assert!(NbtPath::parse("root.\"bad\\escape\"").is_err());
+}
+
+#[test]
+fn move_paths() {
+    let mut player = compound! {
+        "Inventory": [
+            { "Slot": 0i8, "id": "minecraft:stone" },
+            { "Slot": 1i8, "id": "minecraft:dirt" }
+        ],
+        "EnderItems": [],
+        "Pos": [1.0f64, 2.0f64, 3.0f64]
+    };
+
+    // Failed moves leave the source where it was
+    let unchanged = player.clone();
+    assert!(player
+        .move_path("Inventory[0]", "Pos[0]", PathConflict::Replace)
+        .is_err());
+    assert!(player
+        .move_path("Inventory[0]", "EnderItems[0]", PathConflict::Replace)
+        .is_err());
+    assert!(player
+        .move_path("Inventory[]", "Items", PathConflict::Replace)
+        .is_err());
+    assert!(player
+        .move_path("Inventory[0]", "Pos", PathConflict::Error)
+        .is_err());
+    assert!(player
+        .move_path("Inventory[0]", "Pos{x:1}", PathConflict::Error)
+        .is_err());
+    assert_eq!(player, unchanged);
+
+    player
+        .move_path(
+            "Inventory[{Slot:1b}]",
+            "EnderItems[{}]",
+            PathConflict::Replace,
+        )
+        .unwrap();
+    player
+        .move_path("Pos[-1]", "Height", PathConflict::Error)
+        .unwrap();
+    player
+        .move_path("Inventory[0].Slot", "Pos[0]", PathConflict::Merge)
+        .unwrap_err();
+    player
+        .move_path("Inventory[0]", "EnderItems[0]", PathConflict::Merge)
+        .unwrap();
+    assert_eq!(player, compound! {
+        "Inventory": [],
+        "EnderItems": [{ "Slot": 0i8, "id": "minecraft:stone" }],
+        "Pos": [1.0f64, 2.0f64],
+        "Height": 3.0f64
+    });
+
+    assert_eq!(
+        player.copy_path("EnderItems[0].id").unwrap(),
+        NbtTag::from("minecraft:stone")
+    );
+    assert!(player.copy_path("Pos[]").is_err());
+}
+
+#[test]
+fn widen_numeric_lists() {
+    let options = ParseOptions::new().widen_numeric_lists(true);
+    let parse = |input: &str| snbt::parse_with(input, options).map_err(|e| e.to_string());
+
+    assert_eq!(
+        parse("{a: [1b, 2, 3s], b: [1.5f, 2L], c: [[1b], [2s, 3]]}").unwrap(),
+        compound! {
+            "a": [1i32, 2i32, 3i32],
+            "b": [1.5f32, 2.0f32],
+            "c": [[1i8], [2i32, 3i32]]
+        }
+    );
+    assert_eq!(
+        parse("{a: [16777217, 0.5f]}").unwrap_err(),
+        "Non-homogenous typed list at 1:16 near '{a: [16777217, 0.5f'"
+    );
+    assert_eq!(
+        parse("{a: [1, \"2\"]}").unwrap_err(),
+        "Non-homogenous typed list at 1:9 near '{a: [1, \"2\"'"
+    );
+    assert!(snbt::parse("{a: [1b, 2]}").is_err());
+
+    let mut list = NbtList::from(vec![NbtTag::ByteArray(vec![1]), NbtTag::IntArray(vec![2])]);
+    assert!(list.normalize_numeric().is_err());
+    let mut list = NbtList::from(vec![NbtTag::Byte(1), NbtTag::from("2")]);
+    assert!(list.normalize_numeric().is_err());
+    let mut list = NbtList::from(vec![NbtTag::from("1"), NbtTag::from("2")]);
+    assert!(list.normalize_numeric().is_ok());
+}
+
+#[test]
+fn clone_reusing() {
+    let source = compound! {
+        "name": "pig",
+        "pos": [1.5f64, 64.0f64],
+        "data": { "ints": [I; 1, 2, 3], "tags": ["a", "b"] },
+        "kind": 1i32
+    };
+    let mut target = compound! {
+        "name": String::with_capacity(64),
+        "pos": [0.0f64, 0.0f64, 0.0f64],
+        "data": { "ints": [I; 4], "stale": 0i8 },
+        "kind": "changed",
+        "removed": 0i8
+    };
+
+    let name = target.get::<_, &str>("name").unwrap().as_ptr();
+    let ints = target.get::<_, &NbtCompound>("data").unwrap();
+    let ints = ints.get::<_, &[i32]>("ints").unwrap().as_ptr();
+
+    target.clone_from_reusing(&source);
+    assert_eq!(target, source);
+    assert_eq!(target.get::<_, &str>("name").unwrap().as_ptr(), name);
+    let data = target.get::<_, &NbtCompound>("data").unwrap();
+    assert_eq!(data.get::<_, &[i32]>("ints").unwrap().as_ptr(), ints);
+
+    #[cfg(feature = "preserve_order")]
+    assert!(target.inner().keys().eq(source.inner().keys()));
+
+    // Element kinds of lists are copied along with their elements
+    let mut list = NbtTag::List(NbtList::with_element_kind(NbtTagKind::Int));
+    NbtTag::List(NbtList::with_element_kind(NbtTagKind::String)).clone_into(&mut list);
+    match &list {
+        NbtTag::List(list) => assert_eq!(list.element_kind(), Some(NbtTagKind::String)),
+        _ => unreachable!(),
+    }
+
+    let mut tag = NbtTag::Int(1);
+    NbtTag::Compound(source.clone()).clone_into(&mut tag);
+    assert_eq!(tag, NbtTag::Compound(source));
+}
+
+#[test]
+fn sorted_entries() {
+    let keys = ["zeta", "Alpha", "beta", "é", "_", "10", "9"];
+    let mut compound = NbtCompound::new();
+    for (index, key) in keys.iter().enumerate() {
+        compound.insert(*key, index as i32);
+    }
+
+    let mut expected = keys.to_vec();
+    expected.sort_unstable();
+    let sorted = compound.clone().into_sorted_entries();
+    assert_eq!(
+        sorted
+            .iter()
+            .map(|(key, _)| key.as_str())
+            .collect::<Vec<_>>(),
+        expected
+    );
+    assert!(sorted
+        .iter()
+        .all(|(key, value)| value == compound.get::<_, &NbtTag>(key.as_str()).unwrap()));
+
+    let capacity = compound.inner().capacity();
+    assert_eq!(compound.drain_sorted().collect::<Vec<_>>(), sorted);
+    assert!(compound.is_empty());
+    assert_eq!(compound.inner().capacity(), capacity);
+}
+
+#[test]
+fn leaves() {
+    let compound = compound! {
+        "plain": 1i8,
+        "with space": { "a.b": [[1i32, 2i32], []], "[x]": "y" },
+        "quote\"d": [{ "": 1.5f64 }, {}],
+        "arrays": [[I; 1, 2], [L; 3]],
+        "empty": {}
+    };
+
+    let leaves = compound.leaves().collect::<Vec<_>>();
+    assert_eq!(leaves.len(), 7);
+    for (path, tag) in &leaves {
+        assert_eq!(path.select(&compound), [*tag]);
+        assert_eq!(&path.to_string().parse::<NbtPath>().unwrap(), path);
+    }
+
+    let mut paths = leaves
+        .iter()
+        .map(|(path, _)| path.to_string())
+        .collect::<Vec<_>>();
+    paths.sort();
+    assert_eq!(paths, [
+        "\"quote\\\"d\"[0].\"\"",
+        "\"with space\".\"[x]\"",
+        "\"with space\".\"a.b\"[0][0]",
+        "\"with space\".\"a.b\"[0][1]",
+        "arrays[0]",
+        "arrays[1]",
+        "plain",
+    ]);
+
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::iter::ParallelIterator;
+
+        let mut parallel = compound
+            .par_leaves()
+            .map(|(path, _)| path.to_string())
+            .collect::<Vec<_>>();
+        parallel.sort();
+        assert_eq!(parallel, paths);
+    }
+
+    let path = NbtPath::parse("{a:1b}.b{c:\"d\"}[{e:[1]}][-1]").unwrap();
+    assert_eq!(path.to_string().parse::<NbtPath>().unwrap(), path);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn par_iter_map() {
+    use quartz_nbt::NbtReprError;
+    use rayon::iter::ParallelIterator;
+    use std::convert::TryFrom;
+
+    #[derive(Debug, PartialEq)]
+    struct Item {
+        id: String,
+        count: i8,
+    }
+
+    impl TryFrom<&NbtTag> for Item {
+        type Error = NbtReprError;
+
+        fn try_from(tag: &NbtTag) -> Result<Self, Self::Error> {
+            let compound = <&NbtCompound>::try_from(tag)?;
+            Ok(Item {
+                id: compound.get::<_, &str>("id")?.to_owned(),
+                count: compound.get("Count")?,
+            })
+        }
+    }
+
+    let inventory = (0 .. 500)
+        .map(|slot| {
+            NbtTag::Compound(compound! { "id": "minecraft:stone", "Count": (slot % 64) as i8 })
+        })
+        .collect::<NbtList>();
+    let items = inventory
+        .par_iter_map::<Item>()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(
+        items,
+        inventory
+            .iter_map::<Item>()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap()
+    );
+
+    let mut inventory = inventory;
+    inventory.push(compound! { "id": "minecraft:dirt" });
+    assert!(inventory
+        .par_iter_map::<Item>()
+        .collect::<Result<Vec<_>, _>>()
+        .is_err());
+
+    let compound = compound! { "a": 1i32, "b": 2i32, "c": "three" };
+    let mut converted = compound
+        .par_iter_map::<i32>()
+        .map(|(key, result)| (key, result.ok()))
+        .collect::<Vec<_>>();
+    converted.sort();
+    assert_eq!(converted, [("a", Some(1)), ("b", Some(2)), ("c", None)]);
+}
+
+#[test]
+fn merge_provenance() {
+    use quartz_nbt::merge::{self, Provenance};
+
+    let base = compound! {
+        "a": { "b": 1i32, "c": 2i32 },
+        "a-b": 3i32,
+        "list": [1i32, 2i32],
+        "dotted.key": { "x": 1i8 }
+    };
+    let patch = compound! {
+        "a": { "c": 5i32 },
+        "list": [3i32],
+        "dotted.key": { "y": 2i8 }
+    };
+    let overwrite = compound! { "a": "flat" };
+
+    let (merged, provenance) = merge::merge_traced([(0, &base), (1, &patch)]);
+    assert_eq!(merged, compound! {
+        "a": { "b": 1i32, "c": 5i32 },
+        "a-b": 3i32,
+        "list": [3i32],
+        "dotted.key": { "x": 1i8, "y": 2i8 }
+    });
+    assert_eq!(provenance.source("a.b"), Some(&0));
+    assert_eq!(provenance.source("a.c"), Some(&1));
+    assert_eq!(provenance.source("a-b"), Some(&0));
+    assert_eq!(provenance.source("list"), Some(&1));
+    assert_eq!(provenance.source("list[0]"), Some(&1));
+    assert_eq!(provenance.source("\"dotted.key\".x"), Some(&0));
+    assert_eq!(provenance.source("\"dotted.key\".y"), Some(&1));
+    // The first layer wrote the compound as a whole
+    assert_eq!(provenance.source("a"), Some(&0));
+    assert_eq!(provenance.source("missing"), None);
+    assert_eq!(provenance.len(), 6);
+
+    // Replacing a compound drops the records of the tags within it
+    let (merged, provenance) = merge::merge_traced([(0, &base), (1, &patch), (2, &overwrite)]);
+    assert_eq!(merged.get::<_, &str>("a").unwrap(), "flat");
+    assert_eq!(provenance.source("a"), Some(&2));
+    assert_eq!(provenance.source("a.c"), Some(&2));
+    assert_eq!(provenance.source("a-b"), Some(&0));
+    assert_eq!(
+        provenance.iter().map(|(path, _)| path).collect::<Vec<_>>(),
+        ["\"dotted.key\"", "\"dotted.key\".y", "a", "a-b", "list"]
+    );
+
+    // Replacing a scalar with a compound records the compound as a whole
+    let (_, provenance) = merge::merge_traced([(0, &overwrite), (1, &base)]);
+    assert_eq!(provenance.source("a"), Some(&1));
+    assert_eq!(provenance.source("a.b"), Some(&1));
+
+    assert!(Provenance::<()>::default().is_empty());
+}
+
+#[test]
+fn overlay_compounds() {
+    use quartz_nbt::merge::OverlayCompound;
+
+    let base = compound! {
+        "a": { "b": 1i32, "c": 2i32 },
+        "a-b": 3i32,
+        "list": [1i32, 2i32]
+    };
+    let patch = compound! {
+        "a": { "c": 5i32, "d": { "e": 1i8 } },
+        "list": [3i32]
+    };
+    let overwrite = compound! { "a": "flat", "z": 0i8 };
+    let restore = compound! { "a": { "f": 6i32 } };
+
+    let overlay = [&base, &patch].into_iter().collect::<OverlayCompound>();
+    assert_eq!(overlay.get::<_, &NbtList>("list").unwrap().len(), 1);
+    assert_eq!(overlay.get::<_, i32>("a-b").unwrap(), 3);
+    assert!(overlay.get::<_, i32>("missing").is_err());
+    assert!(overlay.get::<_, i64>("a-b").is_err());
+    assert!(overlay.contains_key("a-b"));
+    assert!(!overlay.contains_key("z"));
+    assert_eq!(overlay.len(), 3);
+
+    let mut keys = overlay.keys().collect::<Vec<_>>();
+    keys.sort_unstable();
+    assert_eq!(keys, ["a", "a-b", "list"]);
+    assert!(overlay
+        .iter()
+        .all(|(key, tag)| Some(tag) == overlay.get_tag(key)));
+
+    let nested = overlay.get_compound("a").unwrap();
+    assert_eq!(nested.layers().len(), 2);
+    assert_eq!(nested.get::<_, i32>("b").unwrap(), 1);
+    assert_eq!(nested.get::<_, i32>("c").unwrap(), 5);
+    assert_eq!(
+        nested.get_compound("d").unwrap().get::<_, i8>("e").unwrap(),
+        1
+    );
+    assert!(overlay.get_compound("list").is_err());
+    assert!(overlay.get_compound("missing").is_err());
+    assert_eq!(overlay.to_compound(), compound! {
+        "a": { "b": 1i32, "c": 5i32, "d": { "e": 1i8 } },
+        "a-b": 3i32,
+        "list": [3i32]
+    });
+
+    // A tag other than a compound hides the compounds below it
+    let mut overlay = overlay.with_layer(&overwrite);
+    assert_eq!(overlay.get::<_, &str>("a").unwrap(), "flat");
+    assert!(overlay.get_compound("a").is_err());
+    assert_eq!(overlay.len(), 4);
+
+    overlay.push(&restore);
+    let nested = overlay.get_compound("a").unwrap();
+    assert_eq!(nested.layers(), [restore
+        .get::<_, &NbtCompound>("a")
+        .unwrap()]);
+    assert!(nested.get::<_, i32>("b").is_err());
+
+    // The view agrees with merging the layers in order
+    let (merged, _) =
+        quartz_nbt::merge::merge_traced(overlay.layers().iter().map(|&layer| ((), layer)));
+    assert_eq!(overlay.to_compound(), merged);
+    assert_eq!(overlay.len(), merged.len());
+    assert_eq!(
+        nested.to_compound(),
+        *merged.get::<_, &NbtCompound>("a").unwrap()
+    );
+
+    assert!(OverlayCompound::new().is_empty());
+    assert!(OverlayCompound::new()
+        .with_layer(&NbtCompound::new())
+        .is_empty());
+}
+
+#[test]
+fn stream_from_binary() {
+    // Every compound has at most one entry so that the output doesn't depend on map ordering
+    let nbt = compound! {
+        "root": {
+            "entries": [
+                { "flag": 1i8 },
+                { "string": "needs \"quotes\"" },
+                {},
+                { "nested": [[1i16, 2i16], []] },
+                { "numbers": [3i32, 4i32] },
+                { "long": -5i64 },
+                { "float": 1.5f32 },
+                { "double": -0.25f64 },
+                { "bytes": [B; 1, -2] },
+                { "ints": [I;] },
+                { "longs": [L; 7, 8, 9] }
+            ]
+        }
+    };
+
+    let mut bytes = Vec::new();
+    quartz_nbt::io::write_nbt(&mut bytes, Some("name"), &nbt, Flavor::ZlibCompressed).unwrap();
+
+    for options in [
+        FormatOptions::new(),
+        FormatOptions::new().pretty(true),
+        FormatOptions::new().pretty(true).bool_literals(true),
+    ] {
+        let mut snbt = Vec::new();
+        let root_name = snbt::stream_from_binary(
+            &mut bytes.as_slice(),
+            &mut snbt,
+            Flavor::ZlibCompressed,
+            options,
+        )
+        .unwrap();
+        assert_eq!(root_name, "name");
+        assert_eq!(String::from_utf8(snbt).unwrap(), nbt.to_snbt_with(options));
+    }
+
+    // Entries keep the order of the binary data
+    let mut bytes = vec![0xA, 0, 0];
+    for (key, value) in [("b", 1u8), ("a", 2u8)] {
+        bytes.extend_from_slice(&[0x1, 0, 1, key.as_bytes()[0], value]);
+    }
+    bytes.push(0);
+
+    let mut snbt = Vec::new();
+    snbt::stream_from_binary(
+        &mut bytes.as_slice(),
+        &mut snbt,
+        Flavor::Uncompressed,
+        FormatOptions::new(),
+    )
+    .unwrap();
+    assert_eq!(snbt, b"{b:1B,a:2B}");
+
+    // Truncated and invalid input is reported
+    let mut sink = Vec::new();
+    assert!(snbt::stream_from_binary(
+        &mut &bytes[.. bytes.len() - 1],
+        &mut sink,
+        Flavor::Uncompressed,
+        FormatOptions::new()
+    )
+    .is_err());
+    bytes[3] = 0xD;
+    assert!(snbt::stream_from_binary(
+        &mut bytes.as_slice(),
+        &mut sink,
+        Flavor::Uncompressed,
+        FormatOptions::new()
+    )
+    .is_err());
+}
+
+#[test]
+fn stream_to_binary() {
+    for string_nbt in [SNBT_EDGE_CASES, BIG_SNBT] {
+        let mut bytes = Cursor::new(Vec::new());
+        snbt::stream_to_binary(string_nbt, &mut bytes, Some("root")).unwrap();
+
+        let (nbt, root_name) =
+            quartz_nbt::io::read_nbt(&mut bytes.get_ref().as_slice(), Flavor::Uncompressed)
+                .unwrap();
+        assert_eq!(root_name, "root");
+        assert_compound_eq!(nbt, snbt::parse(string_nbt).unwrap());
+    }
+
+    // Entries keep the order of the input, and lengths are patched in after the data
+    let mut bytes = Cursor::new(Vec::new());
+    snbt::stream_to_binary("{b: [[], [1s]], a: [B; 2, 3]}", &mut bytes, None).unwrap();
+    assert_eq!(bytes.into_inner(), [
+        0xA, 0, 0, // Root
+        0x9, 0, 1, b'b', 0x9, 0, 0, 0, 2, // List of two lists
+        0, 0, 0, 0, 0, // Empty list
+        0x2, 0, 0, 0, 1, 0, 1, // List with one short
+        0x7, 0, 1, b'a', 0, 0, 0, 2, 2, 3, // Byte array
+        0  // End of root
+    ]);
+
+    // Errors are the same as those reported by the parser
+    for string_nbt in [
+        "{list: [1, 2b]}",
+        "{list: [I; 1, 2,]}",
+        "{compound: {a: 1,}}",
+        "{unclosed: [1, 2",
+        "[]",
+    ] {
+        let error = snbt::stream_to_binary(string_nbt, &mut Cursor::new(Vec::new()), None);
+        match (error, snbt::parse(string_nbt)) {
+            (Err(NbtIoError::Snbt(error)), Err(expected)) =>
+                assert_eq!(error.to_string(), expected.to_string()),
+            (result, _) => panic!("unexpected result for {}: {:?}", string_nbt, result),
+        }
+    }
+}
+
+#[test]
+fn parse_lines() {
+    let input =
+        "{a: 1}\r\n  \t\r\n  # comment\n\t{b: 2}  \n{c: 3} {d: 4}\n//{e: 5}\n{f: [1, 2b]}\n{g: 6}";
+    let mut lines = snbt::parse_lines(input.as_bytes());
+    assert_eq!(lines.next().unwrap().unwrap(), compound! { "a": 1 });
+    assert_eq!(lines.next().unwrap().unwrap(), compound! { "b": 2 });
+    assert_eq!(lines.line_number(), 4);
+    assert_eq!(
+        lines.next().unwrap().unwrap_err().to_string(),
+        "Unexpected token at 5:8 near '{c: 3} {', expected end of line"
+    );
+    match lines.next().unwrap().unwrap_err() {
+        NbtIoError::Snbt(error) =>
+            assert_eq!(error.position(), Some(Position { line: 7, column: 9 })),
+        error => panic!("{}", error),
+    }
+    assert_eq!(lines.next().unwrap().unwrap(), compound! { "g": 6 });
+    assert!(lines.next().is_none());
+    assert!(lines.next().is_none());
+
+    // Invalid UTF-8 ends the iteration
+    let input = b"{a: 1}\n{b: \"\xFF\"}\n{c: 3}\n";
+    let results = snbt::parse_lines(&input[..]).collect::<Vec<_>>();
+    assert_eq!(results.len(), 2);
+    assert!(matches!(&results[1], Err(NbtIoError::StdIo(_))));
+
+    let strict = ParseOptions::new().vanilla_strict(true);
+    let results = snbt::parse_lines_with("{a: b c}\n{a: 'b c'}".as_bytes(), strict)
+        .map(|result| result.is_ok())
+        .collect::<Vec<_>>();
+    assert_eq!(results, [false, true]);
+}
+
+#[test]
+fn vanilla_strict() {
+    let strict = ParseOptions::new().vanilla_strict(true);
+    let parse = |string_nbt: &str| snbt::parse_with(string_nbt, strict);
+
+    assert_eq!(
+        parse("{ a : 1b , 'b c' : [I; 1 , -2 ] , d : [ 3s , +4s ], }").unwrap(),
+        compound! { "a": 1i8, "b c": [I; 1, -2], "d": [3i16, 4i16] }
+    );
+
+    // Numbers are read exactly as the game reads them, and anything else becomes a string
+    for (input, expected) in [
+        ("1", NbtTag::Int(1)),
+        ("-2147483648", NbtTag::Int(i32::MIN)),
+        ("2147483648", NbtTag::String("2147483648".to_owned())),
+        ("127b", NbtTag::Byte(127)),
+        ("128b", NbtTag::String("128b".to_owned())),
+        ("01", NbtTag::String("01".to_owned())),
+        ("0", NbtTag::Int(0)),
+        ("5L", NbtTag::Long(5)),
+        ("1.5", NbtTag::Double(1.5)),
+        ("1.", NbtTag::Double(1.0)),
+        (".5", NbtTag::Double(0.5)),
+        ("1e3", NbtTag::String("1e3".to_owned())),
+        ("1.5e3", NbtTag::Double(1500.0)),
+        ("2f", NbtTag::Float(2.0)),
+        ("2.5E-1d", NbtTag::Double(0.25)),
+        ("1.2.3", NbtTag::String("1.2.3".to_owned())),
+        ("-", NbtTag::String("-".to_owned())),
+        ("True", NbtTag::Byte(1)),
+        (
+            "minecraft.stone",
+            NbtTag::String("minecraft.stone".to_owned()),
+        ),
+    ] {
+        let compound = parse(&format!("{{value: {}}}", input)).unwrap();
+        assert_eq!(
+            compound.get::<_, &NbtTag>("value").unwrap(),
+            &expected,
+            "{}",
+            input
+        );
+    }
+
+    // Input accepted by the lenient parser but rejected by the game
+    for input in [
+        "{name: Steve Jobs}",
+        "{name: minecraft:stone}",
+        "{key with spaces: 1}",
+        "{nested: a{b}c}",
+        "{array: [B; 1, 2]}",
+        "{array: [L; 1L, 2]}",
+        "{a: 1} trailing",
+        "{at: @s}",
+        "{array: [ I; 1]}",
+        "{array: [i; 1]}",
+        "{array: [I; ,1]}",
+        "{list: [1,,2]}",
+        "{,a: 1}",
+        "{'': 1}",
+        "{text: 'a\\nb'}",
+    ] {
+        assert!(parse(input).is_err(), "{}", input);
+    }
+
+    // Input rejected by the lenient parser but accepted by the game
+    assert_eq!(
+        parse("{1: [B; true, 0b], list: [1,], array: [I; 1,],}").unwrap(),
+        compound! { "1": [B; 1, 0], "list": [1i32], "array": [I; 1] }
+    );
+    assert!(parse("{a: 1}  \n").is_ok());
+    assert!(snbt::parse("{name: Steve Jobs}").is_ok());
+}
+
+#[test]
+fn chained_lookups() {
+    let tag = NbtTag::Compound(compound! {
+        "Inventory": [
+            { "Slot": 0i8, "id": "minecraft:stone", "tag": { "Damage": 3i32 } },
+            { "Slot": 1i8, "id": "minecraft:dirt" }
+        ],
+        "Pos": [1.0f64, 2.0f64, 3.0f64],
+        "Heightmap": [I; 64, 65]
+    });
+
+    assert_eq!(
+        tag.get("Inventory").get(0).get("tag").get("Damage"),
+        Some(&NbtTag::Int(3))
+    );
+    let key = "Inventory".to_owned();
+    assert_eq!(
+        tag.get(&key).get(1).get(String::from("id")),
+        Some(&NbtTag::from("minecraft:dirt"))
+    );
+    assert_eq!(tag.get("Pos").get(2), Some(&NbtTag::Double(3.0)));
+
+    // Missing tags and kind mismatches short-circuit
+    assert_eq!(tag.get("Inventory").get(1).get("tag").get("Damage"), None);
+    assert_eq!(tag.get("Inventory").get(2).get("id"), None);
+    assert_eq!(tag.get("Inventory").get("id"), None);
+    assert_eq!(tag.get("Pos").get(0).get(0), None);
+    assert_eq!(tag.get("Heightmap").get(0), None);
+    assert_eq!(tag.get(0), None);
+}
+
+#[test]
+fn templates() {
+    let template = SnbtTemplate::parse(
+        "{id: $id, Count: $count b, Pos: [$x d, $y d, $z d], tag: {Name: '$name', Price: '$$5', \
+         Tags: [\"rare\", $tag]}}",
+    )
+    .unwrap();
+    assert_eq!(template.names().collect::<Vec<_>>(), [
+        "count", "id", "name", "tag", "x", "y", "z"
+    ]);
+
+    let values = compound! {
+        "id": "minecraft:diamond",
+        "count": 2i8,
+        "x": 1.5f64,
+        "y": 64.0f64,
+        "z": -3.5f64,
+        "name": { "text": "Shiny" },
+        "tag": "shiny",
+        "unused": 0i32
+    };
+    let filled = template.fill(&values).unwrap();
+    assert_eq!(filled, compound! {
+        "id": "minecraft:diamond",
+        "Count": 2i8,
+        "Pos": [1.5f64, 64.0f64, -3.5f64],
+        "tag": {
+            "Name": { "text": "Shiny" },
+            "Price": "$5",
+            "Tags": ["rare", "shiny"]
+        }
+    });
+
+    // Filling does not change the template
+    assert_eq!(template.fill(&values).unwrap(), filled);
+    assert_eq!(template.clone().fill(&values).unwrap(), filled);
+
+    // Missing values, suffix mismatches and heterogenous lists
+    let mut missing = values.clone();
+    missing.remove_path("z").unwrap();
+    assert!(template.fill(&missing).is_err());
+
+    let mut mismatched = values.clone();
+    mismatched.insert("count", 2i32);
+    assert!(template.fill(&mismatched).is_err());
+
+    let mut heterogenous = values.clone();
+    heterogenous.insert("tag", 1i32);
+    assert!(template.fill(&heterogenous).is_err());
+
+    // Strings which are not placeholders are kept as-is
+    let template: SnbtTemplate = "{a: $, b: $1x, c: $x y, d: $xb, e: foo$x}".parse().unwrap();
+    assert_eq!(template.names().count(), 1);
+    assert_eq!(
+        template.fill(&compound! { "xb": 1i8 }).unwrap(),
+        compound! { "a": "$", "b": "$1x", "c": "$x y", "d": 1i8, "e": "foo$x" }
+    );
+
+    // Parse errors are reported at their position in the template
+    assert_eq!(
+        SnbtTemplate::parse("{id: $id,}").unwrap_err().to_string(),
+        snbt::parse("{id: $id,}").unwrap_err().to_string()
+    );
+}
+
+#[test]
+fn rust_literals() {
+    let mut mixed = NbtList::new();
+    mixed.push(1i8);
+    mixed.push("mixed");
+    let compound = compound! {
+        "byte": -3i8,
+        "short": 300i16,
+        "long": 9_000_000_000i64,
+        "float": f32::INFINITY,
+        "double": -0.1f64,
+        "tiny": 1e-30f64,
+        "string": "quote \" and \\ and \n",
+        "bytes": [B; -128, 0, 127],
+        "longs": [L; -1, 9_000_000_000i64],
+        "empty": [],
+        "kinded": NbtList::with_element_kind(NbtTagKind::Int),
+        "mixed": mixed,
+        "nested": [[1i32, 2i32], [3i32]],
+        "arrays": [[I; 1], [I; 2, 3]],
+        "compound": { "inner": { "inf": f64::NEG_INFINITY } }
+    };
+
+    // Generated by `compound.to_rust_literal()`
+    let generated = compound! {
+        "arrays": [
+            [I; 1],
+            [I; 2, 3]
+        ],
+        "bytes": [B; -128, 0, 127],
+        "compound": {
+            "inner": {
+                "inf": f64::NEG_INFINITY
+            }
+        },
+        "nested": [
+            [1i32, 2i32],
+            [3i32]
+        ],
+        "long": 9000000000i64,
+        "double": -0.1f64,
+        "longs": [L; -1, 9000000000i64],
+        "byte": -3i8,
+        "mixed": NbtList::from(vec![NbtTag::Byte(1), NbtTag::String("mixed".to_owned())]),
+        "short": 300i16,
+        "float": f32::INFINITY,
+        "empty": [],
+        "tiny": 1e-30f64,
+        "string": "quote \" and \\ and \n",
+        "kinded": NbtList::with_element_kind(NbtTagKind::Int)
+    };
+    assert_eq!(generated, compound);
+
+    let nested = NbtTag::List(compound.get::<_, &NbtList>("nested").unwrap().clone());
+    assert_eq!(
+        nested.to_rust_literal(),
+        "NbtTag::List(NbtList::from(vec![
+    NbtTag::List(NbtList::from(vec![NbtTag::Int(1), NbtTag::Int(2)])),
+    NbtTag::List(NbtList::from(vec![NbtTag::Int(3)]))
+]))"
+    );
+    assert_eq!(
+        compound
+            .get::<_, &NbtList>("kinded")
+            .unwrap()
+            .to_rust_literal(),
+        "NbtList::with_element_kind(NbtTagKind::Int)"
+    );
+    assert_eq!(
+        NbtTag::Float(f32::NAN).to_rust_literal(),
+        "NbtTag::Float(f32::NAN)"
+    );
+    assert_eq!(
+        NbtTag::Double(2.0).to_rust_literal(),
+        "NbtTag::Double(2.0f64)"
+    );
+    assert_eq!(
+        NbtTag::LongArray(vec![i64::MIN]).to_rust_literal(),
+        "NbtTag::LongArray(vec![-9223372036854775808])"
+    );
+    assert_eq!(
+        compound! { "longs": [L; i64::MIN] }.to_rust_literal(),
+        "compound! {\n    \"longs\": [L; -9223372036854775808i64]\n}"
+    );
+}
+
+#[test]
+fn key_symmetry() {
+    let alphabet = [
+        'a', 'Z', '0', '5', '-', '+', '.', '_', ' ', ':', ',', ';', '{', '}', '[', ']', '"', '\'',
+        '\\', '\n', '\t', '$', 'é', '\u{a0}', '\u{3000}',
+    ];
+
+    let mut keys = vec![
+        String::new(),
+        "true".to_owned(),
+        "False".to_owned(),
+        "1b".to_owned(),
+        "-5".to_owned(),
+        "1e5".to_owned(),
+    ];
+    for &first in &alphabet {
+        keys.push(first.to_string());
+        for &second in &alphabet {
+            keys.push([first, second].iter().collect());
+            keys.push([first, 'x', second].iter().collect());
+        }
+    }
+
+    for key in keys {
+        let formatted = NbtTag::format_key(&key);
+        assert_eq!(
+            NbtTag::parse_key(&formatted)
+                .map_err(|e| (e, formatted.clone()))
+                .unwrap(),
+            key
+        );
+
+        let mut compound = NbtCompound::new();
+        compound.insert(key.as_str(), 1i8);
+        let snbt = compound.to_snbt();
+        assert_eq!(snbt, format!("{{{}:1B}}", formatted));
+        assert_eq!(snbt::parse(&snbt).unwrap(), compound, "{}", snbt);
+    }
+
+    assert!(NbtTag::parse_key("").is_err());
+    assert!(NbtTag::parse_key("a b:c").is_err());
+    assert!(NbtTag::parse_key("'unterminated").is_err());
+    assert!(NbtTag::parse_key("{}").is_err());
+    assert_eq!(NbtTag::parse_key("a b").unwrap(), "a b");
+}
+
+#[test]
+fn subset_matching() {
+    let entity = compound! {
+        "id": "minecraft:zombie",
+        "Tags": ["boss", "glowing"],
+        "Health": 20.0f32,
+        "Pos": [1.0f64, 64.0f64, -3.5f64],
+        "UUID": [I; 1, 2, 3, 4],
+        "ArmorItems": [
+            { "id": "minecraft:iron_boots", "Count": 1i8, "tag": { "Damage": 7i32 } },
+            {}
+        ],
+        "Empty": []
+    };
+
+    let partial = [
+        compound! {},
+        compound! { "id": "minecraft:zombie" },
+        compound! { "Tags": ["glowing"] },
+        compound! { "Tags": ["glowing", "boss", "glowing"] },
+        compound! { "ArmorItems": [{ "tag": { "Damage": 7i32 } }] },
+        compound! { "ArmorItems": [{}] },
+        compound! { "Empty": [] },
+        compound! { "UUID": [I; 1, 2, 3, 4] },
+    ];
+    let strict = [
+        compound! {},
+        compound! { "id": "minecraft:zombie", "Health": 20.0f32 },
+        compound! { "Tags": ["boss", "glowing"] },
+        compound! { "Pos": [1.0f64, 64.0f64, -3.5f64] },
+        compound! { "Empty": [] },
+        compound! {
+            "ArmorItems": [
+                { "id": "minecraft:iron_boots", "Count": 1i8, "tag": { "Damage": 7i32 } },
+                {}
+            ]
+        },
+    ];
+    let neither = [
+        compound! { "id": "minecraft:skeleton" },
+        compound! { "Missing": 0i8 },
+        compound! { "Health": 20i32 },
+        compound! { "Health": 20.0f64 },
+        compound! { "Tags": [] },
+        compound! { "Tags": ["invisible"] },
+        compound! { "Empty": ["boss"] },
+        compound! { "UUID": [I; 1, 2, 3] },
+        compound! { "ArmorItems": [{ "tag": { "Damage": 7i16 } }] },
+        compound! { "Tags": "boss" },
+    ];
+
+    for pattern in &partial {
+        assert!(pattern.is_subset_of(&entity), "{}", pattern);
+        assert_eq!(
+            pattern.is_subset_of(&entity),
+            path::matches(pattern, &entity)
+        );
+    }
+    for pattern in &strict {
+        assert!(pattern.is_subset_of_strict(&entity), "{}", pattern);
+        assert!(pattern.is_subset_of(&entity), "{}", pattern);
+    }
+    for pattern in &neither {
+        assert!(!pattern.is_subset_of(&entity), "{}", pattern);
+        assert!(!pattern.is_subset_of_strict(&entity), "{}", pattern);
+        assert!(!path::matches(pattern, &entity));
+    }
+
+    // Lists must be equal in strict mode, including any compounds within them
+    assert!(!compound! { "Tags": ["glowing", "boss"] }.is_subset_of_strict(&entity));
+    assert!(!compound! { "ArmorItems": [{}] }.is_subset_of_strict(&entity));
+    assert!(
+        !compound! { "ArmorItems": [{ "id": "minecraft:iron_boots" }, {}] }
+            .is_subset_of_strict(&entity)
+    );
+
+    // Every compound is a subset of itself
+    assert!(entity.is_subset_of(&entity));
+    assert!(entity.is_subset_of_strict(&entity));
+    assert!(!entity.is_subset_of(&compound! { "id": "minecraft:zombie" }));
+
+    // Tags which are not compounds or lists must be equal
+    let tag = NbtTag::from(entity.clone());
+    assert!(NbtTag::Compound(compound! { "Tags": ["boss"] }).is_subset_of(&tag));
+    assert!(NbtTag::from(5i8).is_subset_of(&NbtTag::from(5i8)));
+    assert!(!NbtTag::from(5i8).is_subset_of(&NbtTag::from(5i16)));
+    assert!(
+        NbtTag::from(NbtList::from(vec![NbtTag::from(2i32)])).is_subset_of(&NbtTag::from(
+            NbtList::from(vec![NbtTag::from(1i32), NbtTag::from(2i32)])
+        ))
+    );
+    assert!(
+        !NbtTag::from(NbtList::from(vec![NbtTag::from(2i32)])).is_subset_of_strict(&NbtTag::from(
+            NbtList::from(vec![NbtTag::from(1i32), NbtTag::from(2i32)])
+        ))
+    );
+}
+
+#[test]
+fn number_edge_cases() {
+    // Each row gives the value read in lenient mode, then with vanilla numbers, or `None` for an
+    // error. Expected values are written in vanilla syntax, so strings are quoted.
+    let table: &[(&str, Option<&str>, &str)] = &[
+        ("+5", Some("5"), "5"),
+        ("+5b", Some("5B"), "5B"),
+        ("-5", Some("-5"), "-5"),
+        (".5", Some("0.5D"), "0.5D"),
+        ("+.5", Some("0.5D"), "0.5D"),
+        ("-.5f", Some("-0.5F"), "-0.5F"),
+        ("1.", Some("1D"), "1D"),
+        ("1.f", Some("1F"), "1F"),
+        ("1.e3", Some("1000D"), "1000D"),
+        ("1e3f", Some("1000F"), "1000F"),
+        ("1e3d", Some("1000D"), "1000D"),
+        ("007", Some("7"), "\"007\""),
+        ("00.5", Some("0.5D"), "0.5D"),
+        ("01L", Some("1L"), "\"01L\""),
+        ("128b", None, "\"128b\""),
+        ("+128b", None, "\"+128b\""),
+        ("-32769s", None, "\"-32769s\""),
+        ("2147483648", None, "\"2147483648\""),
+        ("9223372036854775808", None, "\"9223372036854775808\""),
+        ("1e3", None, "\"1e3\""),
+        ("0x10", None, "\"0x10\""),
+        ("1.2.3", None, "\"1.2.3\""),
+        ("-", None, "\"-\""),
+        ("+", Some("\"+\""), "\"+\""),
+        (".", Some("\".\""), "\".\""),
+        ("+5x", Some("\"+5x\""), "\"+5x\""),
+        (".5.5", Some("\".5.5\""), "\".5.5\""),
+        ("NaN", Some("\"NaN\""), "\"NaN\""),
+        ("-infd", None, "\"-infd\""),
+    ];
+
+    let read = |input: &str, options: ParseOptions| {
+        snbt::parse_with(&format!("{{v: {}}}", input), options)
+            .ok()
+            .map(|compound| compound.get::<_, &NbtTag>("v").unwrap().clone())
+    };
+    let expected = |value: &str| read(value, ParseOptions::new().vanilla_strict(true)).unwrap();
+
+    for &(input, lenient, vanilla) in table {
+        assert_eq!(
+            read(input, ParseOptions::new()),
+            lenient.map(expected),
+            "{}",
+            input
+        );
+        for options in [
+            ParseOptions::new().vanilla_numbers(true),
+            ParseOptions::new().vanilla_strict(true),
+        ] {
+            assert_eq!(read(input, options), Some(expected(vanilla)), "{}", input);
+        }
+    }
+
+    // Other lenient syntax is still accepted alongside vanilla numbers
+    assert_eq!(
+        snbt::parse_with(
+            "{name: Steve Jobs, n: 007}",
+            ParseOptions::new().vanilla_numbers(true)
+        )
+        .unwrap(),
+        compound! { "name": "Steve Jobs", "n": "007" }
+    );
+}
@@ -0,0 +1,121 @@
+// These tests exercise the code which is, or used to be, implemented with unsafe code, on inputs
+// small enough to run under Miri with `cargo +nightly miri test --test soundness --features serde`
+
+use quartz_nbt::{
+    cast,
+    compound,
+    io::{self, Flavor, NbtIoError},
+    NbtCompound,
+    NbtTag,
+};
+use std::{convert::TryFrom, io::Cursor};
+
+fn write(nbt: &NbtCompound) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    io::write_nbt(&mut bytes, None, nbt, Flavor::Uncompressed).unwrap();
+    bytes
+}
+
+#[test]
+fn byte_casts() {
+    let signed = cast::byte_buf_to_signed(vec![0, 1, 127, 128, 255]);
+    assert_eq!(signed, [0, 1, 127, -128, -1]);
+    let mut unsigned = cast::byte_buf_to_unsigned(signed);
+    assert_eq!(unsigned, [0, 1, 127, 128, 255]);
+
+    // The vec must still be usable, and freed with the layout it was allocated with
+    unsigned.push(7);
+    unsigned.shrink_to_fit();
+    assert_eq!(cast::byte_buf_to_signed(unsigned)[5], 7);
+    assert!(cast::byte_buf_to_signed(Vec::new()).is_empty());
+
+    assert_eq!(cast::bytes_to_signed(&[255, 1]), [-1, 1]);
+    assert_eq!(cast::bytes_to_unsigned(&[-1, 1]), [255, 1]);
+    assert!(cast::bytes_to_signed(&[]).is_empty());
+
+    let mut bytes = [0u8; 3];
+    cast::bytes_to_signed_mut(&mut bytes[1 ..])[1] = -2;
+    assert_eq!(bytes, [0, 0, 254]);
+    cast::bytes_to_unsigned_mut(cast::bytes_to_signed_mut(&mut bytes))[0] = 9;
+    assert_eq!(bytes, [9, 0, 254]);
+
+    let tag = NbtTag::Byte(-1);
+    assert_eq!(<&u8>::try_from(&tag).unwrap(), &255);
+    assert!(<&u8>::try_from(&NbtTag::Short(1)).is_err());
+    assert_eq!(
+        <&[u8]>::try_from(&NbtTag::ByteArray(vec![-1, 2])).unwrap(),
+        [255, 2]
+    );
+}
+
+#[test]
+fn int_arrays() {
+    let nbt = compound! {
+        "empty_ints": [I;],
+        "ints": [I; 0, 1, -1, i32::MIN, i32::MAX, 0x01020304],
+        "empty_longs": [L;],
+        "longs": [L; 0, 1, -1, i64::MIN, i64::MAX, 0x0102030405060708]
+    };
+    let bytes = write(&nbt);
+    assert_eq!(
+        io::read_nbt(&mut Cursor::new(&bytes), Flavor::Uncompressed)
+            .unwrap()
+            .0,
+        nbt
+    );
+
+    // Truncating the input within an array has to drop the partially read array correctly
+    for nbt in [
+        compound! { "ints": [I; 1, 2, 3] },
+        compound! { "longs": [L; 1, 2, 3] },
+    ] {
+        let bytes = write(&nbt);
+        for len in 0 .. bytes.len() {
+            assert!(io::read_nbt(&mut &bytes[.. len], Flavor::Uncompressed).is_err());
+        }
+    }
+}
+
+#[test]
+fn chunked_arrays() {
+    // Arrays longer than the preallocation limit are read in chunks
+    let ints = (0 .. 70_000).collect::<Vec<i32>>();
+    let nbt = compound! { "ints": ints.clone() };
+    let bytes = write(&nbt);
+    let (read, _) = io::read_nbt(&mut Cursor::new(&bytes), Flavor::Uncompressed).unwrap();
+    assert_eq!(read.get::<_, &[i32]>("ints").unwrap(), &*ints);
+
+    assert!(matches!(
+        io::read_nbt(&mut &bytes[.. bytes.len() - 3], Flavor::Uncompressed),
+        Err(NbtIoError::StdIo(_))
+    ));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn borrowed_deserialization() {
+    use quartz_nbt::serde::{deserialize_from_buffer, Array};
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    struct Borrowed<'a> {
+        #[serde(borrow)]
+        bytes: Array<&'a [u8]>,
+        string: &'a str,
+    }
+
+    let bytes = write(&compound! { "bytes": [B; 1, 2, -1], "string": "borrowed" });
+    let (borrowed, _) = deserialize_from_buffer::<Borrowed<'_>>(&bytes).unwrap();
+    let array = Array::into_inner(borrowed.bytes);
+    assert_eq!(array, [1, 2, 255]);
+    assert_eq!(borrowed.string, "borrowed");
+
+    // The borrowed slices point into the buffer
+    let range = bytes.as_ptr_range();
+    assert!(range.contains(&array.as_ptr()));
+    assert!(range.contains(&borrowed.string.as_ptr()));
+
+    for len in 0 .. bytes.len() {
+        assert!(deserialize_from_buffer::<Borrowed<'_>>(&bytes[.. len]).is_err());
+    }
+}
@@ -0,0 +1,127 @@
+#![cfg(feature = "tracing")]
+
+use quartz_nbt::{
+    compound,
+    io::{self, Flavor},
+    snbt,
+};
+use std::{
+    fmt::Debug,
+    sync::{Arc, Mutex},
+};
+use tracing::{
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+    Event,
+    Metadata,
+    Subscriber,
+};
+
+// Collects the names of spans and events along with the fields recorded on them
+#[derive(Default)]
+struct Collector {
+    spans: Mutex<Vec<&'static str>>,
+    records: Arc<Mutex<Vec<(String, String, String)>>>,
+}
+
+struct FieldVisitor<'a> {
+    name: &'a str,
+    records: &'a Mutex<Vec<(String, String, String)>>,
+}
+
+impl Visit for FieldVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.records.lock().unwrap().push((
+            self.name.to_owned(),
+            field.name().to_owned(),
+            format!("{:?}", value),
+        ));
+    }
+}
+
+impl Subscriber for Collector {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let name = span.metadata().name();
+        span.record(&mut FieldVisitor {
+            name,
+            records: &self.records,
+        });
+
+        let mut spans = self.spans.lock().unwrap();
+        spans.push(name);
+        Id::from_u64(spans.len() as u64)
+    }
+
+    fn record(&self, span: &Id, values: &Record<'_>) {
+        let name = self.spans.lock().unwrap()[span.into_u64() as usize - 1];
+        values.record(&mut FieldVisitor {
+            name,
+            records: &self.records,
+        });
+    }
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        event.record(&mut FieldVisitor {
+            name: "event",
+            records: &self.records,
+        });
+    }
+
+    fn enter(&self, _span: &Id) {}
+
+    fn exit(&self, _span: &Id) {}
+}
+
+fn collect<F: FnOnce()>(f: F) -> Vec<(String, String, String)> {
+    let collector = Collector::default();
+    let records = Arc::clone(&collector.records);
+    tracing::subscriber::with_default(collector, f);
+    let records = records.lock().unwrap();
+    records.clone()
+}
+
+fn has(records: &[(String, String, String)], name: &str, field: &str, value: &str) -> bool {
+    records
+        .iter()
+        .any(|record| record.0 == name && record.1 == field && record.2 == value)
+}
+
+#[test]
+fn document_spans() {
+    let nbt = compound! { "a": { "b": [1i32, 2i32] }, "c": "d" };
+    let bytes = io::write_nbt_bytes(None, &nbt, Flavor::Uncompressed).unwrap();
+
+    let records = collect(|| {
+        io::write_nbt_bytes(None, &nbt, Flavor::GzCompressed).unwrap();
+        io::read_nbt(&mut bytes.as_slice(), Flavor::Uncompressed).unwrap();
+    });
+    for name in ["write_nbt", "read_nbt"] {
+        assert!(has(&records, name, "bytes", &bytes.len().to_string()));
+        assert!(has(&records, name, "tags", "5"));
+        assert!(has(&records, name, "depth", "3"));
+    }
+}
+
+#[test]
+fn snbt_events() {
+    let input = "{a: 1, a: 2}";
+    let records = collect(|| {
+        snbt::parse(input).unwrap();
+    });
+    assert!(has(&records, "parse_snbt", "len", &input.len().to_string()));
+    assert!(has(&records, "parse_snbt", "tags", "1"));
+    assert!(has(&records, "event", "key", "\"a\""));
+
+    // An unnamed root compound containing a string "s" whose value is not valid CESU-8
+    let bytes = [0x0A, 0, 0, 0x08, 0, 1, b's', 0, 1, 0xE9, 0];
+    let records = collect(|| {
+        io::read_nbt_with_latin1_fallback(&mut &bytes[..], Flavor::Uncompressed).unwrap();
+    });
+    assert!(has(&records, "event", "path", "\"s\""));
+}
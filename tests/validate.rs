@@ -0,0 +1,99 @@
+use quartz_nbt::{
+    compound,
+    validate::{IdKind, IdRegistry, IdStatus, Schema, TagSchema, Validator},
+    NbtTagKind,
+};
+
+#[test]
+fn containers() {
+    let validator = Validator::with_registry(
+        IdRegistry::new()
+            .entity("minecraft:zombie", Schema::new().allow_other_keys(true))
+            .block_entity("minecraft:spawner", Schema::new().allow_other_keys(true))
+            .container("SpawnData", IdKind::Entity),
+    );
+
+    let region = compound! {
+        "sections": [{ "Entities": "not a container" }],
+        "TileEntities": [
+            { "id": "minecraft:spawner", "SpawnData": { "id": "minecraft:zombie" } },
+            { "x": 1 }
+        ],
+        "Passengers": [1, 2],
+        "Entities": [{ "id": "minecraft:zombie" }, { "id": 5 }],
+        "Items": [{ "id": "minecraft:stone" }]
+    };
+
+    let report = validator.validate(&region);
+    let mut entries = report
+        .iter()
+        .map(|entry| (entry.path(), entry.kind(), entry.id(), entry.status()))
+        .collect::<Vec<_>>();
+    // Compounds may iterate in any order
+    entries.sort_unstable_by_key(|&(path, ..)| path);
+    assert_eq!(entries, [
+        (
+            "Entities[0]",
+            IdKind::Entity,
+            Some("minecraft:zombie"),
+            IdStatus::Valid
+        ),
+        ("Entities[1]", IdKind::Entity, None, IdStatus::MissingId),
+        (
+            "TileEntities[0]",
+            IdKind::BlockEntity,
+            Some("minecraft:spawner"),
+            IdStatus::Valid
+        ),
+        (
+            "TileEntities[0].SpawnData",
+            IdKind::Entity,
+            Some("minecraft:zombie"),
+            IdStatus::Valid
+        ),
+        (
+            "TileEntities[1]",
+            IdKind::BlockEntity,
+            None,
+            IdStatus::MissingId
+        ),
+    ]);
+    assert!(report.unknown_ids().is_empty());
+    assert_eq!(report.issues().count(), 2);
+}
+
+#[test]
+fn kinds_are_separate() {
+    let validator = Validator::with_registry(
+        IdRegistry::new().entity("somemod:thing", Schema::new().allow_other_keys(true)),
+    );
+    assert!(validator
+        .registry()
+        .contains(IdKind::Entity, "somemod:thing"));
+    assert!(!validator
+        .registry()
+        .contains(IdKind::BlockEntity, "somemod:thing"));
+
+    let chunk = compound! {
+        "Entities": [{ "id": "somemod:thing" }, { "id": "somemod:thing" }],
+        "block_entities": [{ "id": "somemod:thing" }, { "id": "somemod:thing" }]
+    };
+    let report = validator.validate(&chunk);
+    assert_eq!(report.len(), 4);
+    assert_eq!(report.unknown_ids().into_iter().collect::<Vec<_>>(), [(
+        (IdKind::BlockEntity, "somemod:thing"),
+        2
+    )]);
+    assert!(Validator::default().validate(&compound! {}).is_empty());
+}
+
+#[test]
+fn other_keys() {
+    let schema = Schema::new().required("id", TagSchema::Kind(NbtTagKind::String));
+    let entity = compound! { "id": "minecraft:cow", "Age": 0 };
+    assert!(!schema.is_valid(&entity));
+    assert!(schema.clone().allow_other_keys(true).is_valid(&entity));
+    assert!(!schema
+        .allow_other_keys(true)
+        .is_valid(&compound! { "Age": 0 }));
+}
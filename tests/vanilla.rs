@@ -0,0 +1,127 @@
+use quartz_nbt::{
+    compound,
+    vanilla::{Attributes, Enchantments, GameRules},
+    NbtCompound,
+    NbtList,
+    NbtTag,
+};
+
+#[test]
+fn game_rules() {
+    let mut compound = compound! {
+        "keepInventory": "false",
+        "maxEntityCramming": "24",
+        "minecraft:pvp": 1i8,
+        "minecraft:spawn_radius": 10i32,
+        "broken": "yes"
+    };
+
+    let rules = GameRules::new(&compound);
+    assert!(!rules.get_bool("keepInventory").unwrap());
+    assert_eq!(rules.get_int("maxEntityCramming").unwrap(), 24);
+    assert!(rules.get_bool("minecraft:pvp").unwrap());
+    assert_eq!(rules.get_int("minecraft:spawn_radius").unwrap(), 10);
+    assert_eq!(rules.get("keepInventory").unwrap(), "false");
+    assert!(rules.get("minecraft:pvp").is_err());
+    assert!(rules.get_bool("broken").is_err());
+    assert!(rules.get_int("keepInventory").is_err());
+    assert!(rules.get_bool("missing").is_err());
+    assert!(rules.contains("broken"));
+    assert_eq!(rules.names().count(), 5);
+
+    let mut rules = GameRules::new(&mut compound);
+    rules.set_bool("keepInventory", true);
+    rules.set_bool("minecraft:pvp", false);
+    rules.set_int("minecraft:spawn_radius", 0);
+    rules.set("broken", "no");
+    assert_eq!(rules.remove("maxEntityCramming"), Some(NbtTag::from("24")));
+    assert_eq!(rules.remove("maxEntityCramming"), None);
+
+    assert_eq!(compound, compound! {
+        "keepInventory": "true",
+        "minecraft:pvp": 0i8,
+        "minecraft:spawn_radius": 0i32,
+        "broken": "no"
+    });
+}
+
+#[test]
+fn legacy_attributes() {
+    let mut list = NbtList::from(vec![
+        NbtTag::Compound(compound! {
+            "Name": "generic.max_health",
+            "Base": 20.0f64,
+            "Modifiers": [{ "Name": "Health Boost", "Amount": 4.0f64, "Operation": 0i32 }]
+        }),
+        NbtTag::from("not an attribute"),
+    ]);
+
+    let attributes = Attributes::new(&list);
+    assert_eq!(attributes.base("generic.max_health").unwrap(), 20.0);
+    assert!(attributes.base("minecraft:generic.max_health").is_err());
+    assert_eq!(attributes.names().collect::<Vec<_>>(), [
+        "generic.max_health"
+    ]);
+    assert!(attributes
+        .get("generic.max_health")
+        .unwrap()
+        .contains_key("Modifiers"));
+
+    let mut attributes = Attributes::new(&mut list);
+    attributes.set_base("generic.max_health", 10.0);
+    attributes.set_base("generic.armor", 2.0);
+    attributes
+        .get_mut("generic.armor")
+        .unwrap()
+        .insert("Modifiers", NbtList::new());
+    assert!(attributes.remove("generic.luck").is_none());
+
+    assert_eq!(list.len(), 3);
+    assert_eq!(
+        list[0],
+        NbtTag::Compound(compound! {
+            "Name": "generic.max_health",
+            "Base": 10.0f64,
+            "Modifiers": [{ "Name": "Health Boost", "Amount": 4.0f64, "Operation": 0i32 }]
+        })
+    );
+    assert_eq!(
+        list[2],
+        NbtTag::Compound(compound! { "Name": "generic.armor", "Base": 2.0f64, "Modifiers": [] })
+    );
+}
+
+#[test]
+fn enchantments() {
+    let mut item = compound! {
+        "StoredEnchantments": [
+            { "id": "minecraft:unbreaking", "lvl": 3i16 },
+            { "id": "minecraft:efficiency", "lvl": 5i32 }
+        ]
+    };
+
+    let enchantments = Enchantments::new(item.get::<_, &NbtList>("StoredEnchantments").unwrap());
+    assert_eq!(enchantments.level("minecraft:unbreaking").unwrap(), 3);
+    // Levels must be shorts
+    assert!(enchantments.level("minecraft:efficiency").is_err());
+    assert!(enchantments.contains("minecraft:efficiency"));
+    assert_eq!(enchantments.iter().collect::<Vec<_>>(), [(
+        "minecraft:unbreaking",
+        3
+    )]);
+
+    let mut enchantments = Enchantments::new(
+        item.get_mut::<_, &mut NbtList>("StoredEnchantments")
+            .unwrap(),
+    );
+    enchantments.set_level("minecraft:efficiency", 4);
+    assert!(enchantments.remove("minecraft:unbreaking"));
+    assert!(!enchantments.remove("minecraft:unbreaking"));
+
+    let list = enchantments.into_inner();
+    assert_eq!(list.len(), 1);
+    assert_eq!(
+        list.get::<&NbtCompound>(0).unwrap(),
+        &compound! { "id": "minecraft:efficiency", "lvl": 4i16 }
+    );
+}